@@ -43,6 +43,9 @@ pin_project! {
         #[pin]
         sleep: Option<Sleep>,
         timeout: Duration,
+        // The runtime the body was created on, since the body may be read
+        // outside of it, such as by the blocking `Response`.
+        runtime: Option<tokio::runtime::Handle>,
     }
 }
 
@@ -289,6 +292,7 @@ pub(crate) fn with_read_timeout<B>(body: B, timeout: Duration) -> ReadTimeoutBod
         inner: body,
         sleep: None,
         timeout,
+        runtime: tokio::runtime::Handle::try_current().ok(),
     }
 }
 
@@ -306,7 +310,9 @@ where
     ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
         let this = self.project();
         if let Poll::Ready(()) = this.timeout.as_mut().poll(cx) {
-            return Poll::Ready(Some(Err(crate::error::body(crate::error::TimedOut))));
+            return Poll::Ready(Some(Err(crate::error::body(crate::error::TimedOut(
+                crate::error::TimeoutPhase::Total,
+            )))));
         }
         Poll::Ready(
             ready!(this.inner.poll_frame(cx))
@@ -343,13 +349,16 @@ where
         let sleep_pinned = if let Some(some) = this.sleep.as_mut().as_pin_mut() {
             some
         } else {
+            let _enter = this.runtime.as_ref().map(|rt| rt.enter());
             this.sleep.set(Some(tokio::time::sleep(*this.timeout)));
             this.sleep.as_mut().as_pin_mut().unwrap()
         };
 
         // Error if the timeout has expired.
         if let Poll::Ready(()) = sleep_pinned.poll(cx) {
-            return Poll::Ready(Some(Err(crate::error::body(crate::error::TimedOut))));
+            return Poll::Ready(Some(Err(crate::error::body(crate::error::TimedOut(
+                crate::error::TimeoutPhase::BodyRead,
+            )))));
         }
 
         let item = ready!(this.inner.poll_frame(cx))
@@ -3,39 +3,78 @@ use std::any::Any;
 use std::future::Future;
 use std::net::IpAddr;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock, RwLock};
 use std::task::{ready, Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, convert::TryInto, net::SocketAddr};
 use std::{fmt, str};
 
-use super::request::{Request, RequestBuilder};
+use super::request::{RawTargetExtension, RawTargetService, Request, RequestBuilder};
 use super::response::Response;
+use super::body::ResponseBody;
 use super::Body;
+#[cfg(feature = "cache")]
+use crate::cache::{
+    self, CacheService, ConditionalService, ConditionalSettings, Settings as CacheSettings,
+};
 #[cfg(feature = "http3")]
 use crate::async_impl::h3_client::connect::{H3ClientConfig, H3Connector};
 #[cfg(feature = "http3")]
 use crate::async_impl::h3_client::H3Client;
-use crate::config::{RequestConfig, TotalTimeout};
+use crate::auth::{TokenProvider, TokenService};
+use crate::circuit::{Breaker, CircuitService, Circuits, TimedOutSlot};
+#[cfg(feature = "client-config")]
+use crate::client_config::ClientConfig;
+#[cfg(feature = "cache")]
+use crate::config::CacheMode;
+use crate::config::{
+    Deadline, EarlyHints, HeaderTimeout, InFlightReserved, OnInformational, RawTarget, ReadTimeout,
+    RequestConfig, RequestPriority, TotalTimeout,
+};
 #[cfg(unix)]
 use crate::connect::uds::UnixSocketProvider;
 #[cfg(target_os = "windows")]
 use crate::connect::windows_named_pipe::WindowsNamedPipeProvider;
+#[cfg(feature = "__tls")]
+use crate::connect::{HostTls, TlsReloader};
 use crate::connect::{
     sealed::{Conn, Unnameable},
     BoxedConnectorLayer, BoxedConnectorService, Connector, ConnectorBuilder,
 };
+#[cfg(feature = "content-digest")]
+use crate::content_digest::ContentDigestService;
+#[cfg(feature = "message-signatures")]
+use crate::signature::{SignatureService, Signer};
+#[cfg(feature = "tracing")]
+use crate::trace::{CurrentContext, TraceContext};
 #[cfg(feature = "cookies")]
 use crate::cookie;
 #[cfg(feature = "cookies")]
 use crate::cookie::service::CookieService;
 #[cfg(feature = "hickory-dns")]
 use crate::dns::hickory::HickoryDnsResolver;
-use crate::dns::{gai::GaiResolver, DnsResolverWithOverrides, DynResolver, Resolve};
-use crate::error::{self, BoxError};
+use crate::dns::{
+    gai::GaiResolver, Balance, BalancedResolve, Balancer, DnsResolverWithOverrides, DynResolver,
+    Resolve,
+};
+use crate::error::{self, BoxError, TimedOut, TimeoutPhase};
+use crate::host::HostConfig;
+use crate::in_flight::{
+    InFlightPermit, InFlightService, Limits as InFlightLimits, Readiness as InFlightReadiness,
+    Reserved,
+};
 use crate::into_url::try_uri;
+use crate::observe::{
+    CompleteBody, ConnectTimings, ConnectionObserver, ConnectionSlot, ObservedResolve, Observer, RequestTimings,
+    Audit, AuditObserver, Audited, Counters, FailedRequest, OnError, TransferStats, Wire,
+    WireObserver, WireService,
+};
 use crate::proxy::Matcher as ProxyMatcher;
+use crate::proxy_protocol::ProxyProtocol;
 use crate::redirect::{self, TowerRedirectPolicy};
+use crate::redact::{self, Redaction};
+use crate::tcp::TcpConnector;
+use crate::{config::InterfaceIndex, tcp::InterfaceIndexExtension};
 #[cfg(feature = "__rustls")]
 use crate::tls::CertificateRevocationList;
 #[cfg(feature = "__tls")]
@@ -49,7 +88,6 @@ use crate::{IntoUrl, Method, Proxy, Url};
 use http::header::{Entry, HeaderMap, HeaderValue, ACCEPT, PROXY_AUTHORIZATION, USER_AGENT};
 use http::uri::Scheme;
 use http::Uri;
-use hyper_util::client::legacy::connect::HttpConnector;
 #[cfg(feature = "__native-tls")]
 use native_tls_crate::TlsConnector;
 use pin_project_lite::pin_project;
@@ -58,7 +96,7 @@ use quinn::TransportConfig;
 #[cfg(feature = "http3")]
 use quinn::VarInt;
 use tokio::time::Sleep;
-use tower::util::BoxCloneSyncServiceLayer;
+use tower::util::{BoxCloneSyncService, BoxCloneSyncServiceLayer};
 use tower::{Layer, Service};
 #[cfg(any(
     feature = "gzip",
@@ -92,14 +130,18 @@ use tower_http::follow_redirect::FollowRedirect;
 #[derive(Clone)]
 pub struct Client {
     inner: Arc<ClientRef>,
+    // the room reserved by `Service::poll_ready`
+    ready: InFlightReadiness,
 }
 
 /// A `ClientBuilder` can be used to create a `Client` with custom configuration.
 #[must_use]
 pub struct ClientBuilder {
     config: Config,
+    error: Option<crate::Error>,
 }
 
+#[derive(Clone, Copy)]
 enum HttpVersionPref {
     Http1,
     #[cfg(feature = "http2")]
@@ -109,6 +151,26 @@ enum HttpVersionPref {
     All,
 }
 
+/// The settings of the TLS connector of a client, kept to build it again
+/// when the client reloads its certificates.
+#[cfg(feature = "__tls")]
+#[derive(Clone)]
+struct TlsSettings {
+    http_version_pref: HttpVersionPref,
+    hostname_verification: bool,
+    certs_verification: bool,
+    tls_sni: bool,
+    tls_certs_only: bool,
+    root_certs: Vec<Certificate>,
+    identity: Option<Identity>,
+    min_tls_version: Option<tls::Version>,
+    max_tls_version: Option<tls::Version>,
+    #[cfg(feature = "__rustls")]
+    crls: Arc<Vec<CertificateRevocationList>>,
+    #[cfg(feature = "__rustls")]
+    tls_sslkeylogfile: bool,
+}
+
 #[derive(Clone, Copy, Debug)]
 struct Accepts {
     #[cfg(feature = "gzip")]
@@ -119,6 +181,14 @@ struct Accepts {
     zstd: bool,
     #[cfg(feature = "deflate")]
     deflate: bool,
+    // the length from which compressed bodies are decoded on the blocking pool
+    #[cfg(any(
+        feature = "gzip",
+        feature = "brotli",
+        feature = "zstd",
+        feature = "deflate"
+    ))]
+    offload: Option<u64>,
 }
 
 impl Default for Accepts {
@@ -132,35 +202,478 @@ impl Default for Accepts {
             zstd: true,
             #[cfg(feature = "deflate")]
             deflate: true,
+            #[cfg(any(
+                feature = "gzip",
+                feature = "brotli",
+                feature = "zstd",
+                feature = "deflate"
+            ))]
+            offload: None,
+        }
+    }
+}
+
+#[cfg(feature = "__tls")]
+impl TlsSettings {
+    #[cfg(feature = "__native-tls")]
+    fn native_tls(&self) -> crate::Result<native_tls_crate::TlsConnectorBuilder> {
+        let mut tls = TlsConnector::builder();
+
+        #[cfg(all(feature = "__native-tls-alpn", not(feature = "http3")))]
+        {
+            match self.http_version_pref {
+                HttpVersionPref::Http1 => {
+                    tls.request_alpns(&["http/1.1"]);
+                }
+                #[cfg(feature = "http2")]
+                HttpVersionPref::Http2 => {
+                    tls.request_alpns(&["h2"]);
+                }
+                HttpVersionPref::All => {
+                    tls.request_alpns(&[
+                        #[cfg(feature = "http2")]
+                        "h2",
+                        "http/1.1",
+                    ]);
+                }
+            }
+        }
+
+        tls.danger_accept_invalid_hostnames(!self.hostname_verification);
+
+        tls.danger_accept_invalid_certs(!self.certs_verification);
+
+        tls.use_sni(self.tls_sni);
+
+        tls.disable_built_in_roots(self.tls_certs_only);
+
+        for cert in self.root_certs.iter().cloned() {
+            cert.add_to_native_tls(&mut tls);
+        }
+
+        #[cfg(feature = "__native-tls")]
+        {
+            if let Some(id) = self.identity.clone() {
+                id.add_to_native_tls(&mut tls)?;
+            }
+        }
+        #[cfg(all(feature = "__rustls", not(feature = "__native-tls")))]
+        {
+            // Default backend + rustls Identity doesn't work.
+            if let Some(_id) = self.identity.clone() {
+                return Err(crate::error::builder("incompatible TLS identity type"));
+            }
+        }
+
+        if let Some(min_tls_version) = self.min_tls_version {
+            let protocol = min_tls_version.to_native_tls().ok_or_else(|| {
+                // native-tls added support for TLS v1.3 in 0.2.16 🎉
+                // `to_native_tls` could arguably return the value directly
+                // instead of making us check for an impossible None here,
+                // but given that 1.4 does not exist yet, that might get
+                // messy in the future.
+                crate::error::builder("invalid minimum TLS version for backend")
+            })?;
+            tls.min_protocol_version(Some(protocol));
+        }
+
+        if let Some(max_tls_version) = self.max_tls_version {
+            let protocol = max_tls_version.to_native_tls().ok_or_else(|| {
+                // We could arguably do max_protocol_version(None), given
+                // that 1.4 does not exist yet, but that'd get messy in the
+                // future.
+                crate::error::builder("invalid maximum TLS version for backend")
+            })?;
+            tls.max_protocol_version(Some(protocol));
+        }
+
+        Ok(tls)
+    }
+
+    #[cfg(feature = "__rustls")]
+    fn rustls(&self) -> crate::Result<rustls::ClientConfig> {
+        use crate::tls::{IgnoreHostname, NoVerifier};
+
+        // Set TLS versions.
+        let mut versions = rustls::ALL_VERSIONS.to_vec();
+
+        if let Some(min_tls_version) = self.min_tls_version {
+            versions.retain(|&supported_version| {
+                match tls::Version::from_rustls(supported_version.version) {
+                    Some(version) => version >= min_tls_version,
+                    // Assume it's so new we don't know about it, allow it
+                    // (as of writing this is unreachable)
+                    None => true,
+                }
+            });
+        }
+
+        if let Some(max_tls_version) = self.max_tls_version {
+            versions.retain(|&supported_version| {
+                match tls::Version::from_rustls(supported_version.version) {
+                    Some(version) => version <= max_tls_version,
+                    None => false,
+                }
+            });
+        }
+
+        if versions.is_empty() {
+            return Err(crate::error::builder("empty supported tls versions"));
+        }
+
+        // Allow user to have installed a runtime default.
+        // If not, we ship with _our_ recommended default.
+        let provider = rustls::crypto::CryptoProvider::get_default()
+            .map(|arc| arc.clone())
+            .unwrap_or_else(default_rustls_crypto_provider);
+
+        // Build TLS config
+        let signature_algorithms = provider.signature_verification_algorithms;
+        let config_builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(&versions)
+            .map_err(|_| crate::error::builder("invalid TLS versions"))?;
+
+        let config_builder = if !self.certs_verification {
+            config_builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoVerifier))
+        } else if !self.hostname_verification {
+            if !self.tls_certs_only {
+                // Should this just warn? Error for now...
+                return Err(crate::error::builder(
+                    "disabling rustls hostname verification only allowed with tls_certs_only()",
+                ));
+            }
+
+            config_builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(IgnoreHostname::new(
+                    crate::tls::rustls_store(self.root_certs.clone())?,
+                    signature_algorithms,
+                )))
+        } else if !self.tls_certs_only {
+            // Check for some misconfigurations and report them.
+            if !self.crls.is_empty() {
+                return Err(crate::error::builder(
+                    "CRLs only allowed with tls_certs_only()",
+                ));
+            }
+
+            let verifier = if self.root_certs.is_empty() {
+                rustls_platform_verifier::Verifier::new(provider.clone())
+                    .map_err(crate::error::builder)?
+            } else {
+                #[cfg(any(all(unix, not(target_os = "android")), target_os = "windows"))]
+                {
+                    rustls_platform_verifier::Verifier::new_with_extra_roots(
+                        crate::tls::rustls_der(self.root_certs.clone())?,
+                        provider.clone(),
+                    )
+                    .map_err(crate::error::builder)?
+                }
+
+                #[cfg(not(any(all(unix, not(target_os = "android")), target_os = "windows")))]
+                return Err(crate::error::builder(
+                    "rustls-platform-verifier could not load extra certs",
+                ));
+            };
+
+            config_builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(verifier))
+        } else {
+            if self.crls.is_empty() {
+                config_builder
+                    .with_root_certificates(crate::tls::rustls_store(self.root_certs.clone())?)
+            } else {
+                let crls = self
+                    .crls
+                    .iter()
+                    .map(|e| e.as_rustls_crl())
+                    .collect::<Vec<_>>();
+                let verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(
+                    Arc::new(crate::tls::rustls_store(self.root_certs.clone())?),
+                    provider,
+                )
+                .with_crls(crls)
+                .build()
+                .map_err(|_| crate::error::builder("invalid TLS verification settings"))?;
+                config_builder.with_webpki_verifier(verifier)
+            }
+        };
+
+        // Finalize TLS config
+        let mut tls = if let Some(id) = self.identity.clone() {
+            id.add_to_rustls(config_builder)?
+        } else {
+            config_builder.with_no_client_auth()
+        };
+
+        tls.enable_sni = self.tls_sni;
+
+        if self.tls_sslkeylogfile {
+            tls.key_log = Arc::new(rustls::KeyLogFile::new());
+        }
+
+        // ALPN protocol
+        match self.http_version_pref {
+            HttpVersionPref::Http1 => {
+                tls.alpn_protocols = vec!["http/1.1".into()];
+            }
+            #[cfg(feature = "http2")]
+            HttpVersionPref::Http2 => {
+                tls.alpn_protocols = vec!["h2".into()];
+            }
+            #[cfg(feature = "http3")]
+            HttpVersionPref::Http3 => {
+                // h3 ALPN is not valid over TCP
+            }
+            HttpVersionPref::All => {
+                tls.alpn_protocols = vec![
+                    #[cfg(feature = "http2")]
+                    "h2".into(),
+                    "http/1.1".into(),
+                ];
+            }
+        }
+
+        Ok(tls)
+    }
+}
+
+/// The TLS configuration of a running client, and what is needed to replace
+/// it.
+#[cfg(feature = "__tls")]
+struct ReloadableTls {
+    backend: ReloadBackend,
+    settings: std::sync::Mutex<TlsSettings>,
+    reloader: TlsReloader,
+    hyper_builder: hyper_util::client::legacy::Builder,
+    connector: Connector,
+    hyper: Arc<RwLock<HyperClient>>,
+    interfaces: Arc<InterfaceClients>,
+}
+
+/// Build the TLS configurations of the hosts with TLS settings of their own,
+/// from the settings of the client.
+#[cfg(feature = "__tls")]
+fn host_tls(
+    settings: &TlsSettings,
+    backend: Option<ReloadBackend>,
+    hosts: &[(String, HostConfig)],
+) -> crate::Result<HostTls> {
+    let mut host_tls = HostTls::default();
+    for (host, config) in hosts.iter().filter(|(_, config)| config.has_tls()) {
+        let mut settings = settings.clone();
+        settings
+            .root_certs
+            .extend(config.root_certs.iter().cloned());
+        if let Some(ref identity) = config.identity {
+            settings.identity = Some(identity.clone());
+        }
+        if let Some(accept_invalid_certs) = config.accept_invalid_certs {
+            settings.certs_verification = !accept_invalid_certs;
+        }
+        if config.min_tls_version.is_some() {
+            settings.min_tls_version = config.min_tls_version;
+        }
+        if config.max_tls_version.is_some() {
+            settings.max_tls_version = config.max_tls_version;
+        }
+
+        match backend {
+            #[cfg(feature = "__native-tls")]
+            Some(ReloadBackend::NativeTls) => {
+                host_tls.insert_native_tls(host, settings.native_tls()?)?
+            }
+            #[cfg(feature = "__rustls")]
+            Some(ReloadBackend::Rustls) => host_tls.insert_rustls(host, settings.rustls()?),
+            None => {
+                return Err(crate::error::builder(
+                    "the TLS settings of a host can't be used with a preconfigured TLS backend",
+                ));
+            }
+        }
+    }
+    Ok(host_tls)
+}
+
+#[cfg(feature = "__tls")]
+#[derive(Clone, Copy)]
+enum ReloadBackend {
+    #[cfg(feature = "__native-tls")]
+    NativeTls,
+    #[cfg(feature = "__rustls")]
+    Rustls,
+}
+
+#[cfg(feature = "__tls")]
+impl ReloadableTls {
+    fn reload(&self, reload: tls::TlsReload) -> crate::Result<()> {
+        let mut settings = self.settings.lock().expect("lock poisoned");
+        let mut next = settings.clone();
+        if let Some(root_certs) = reload.root_certs {
+            next.root_certs = root_certs;
+        }
+        if let Some(identity) = reload.identity {
+            next.identity = Some(identity);
+        }
+
+        match self.backend {
+            #[cfg(feature = "__native-tls")]
+            ReloadBackend::NativeTls => self.reloader.set_native_tls(next.native_tls()?)?,
+            #[cfg(feature = "__rustls")]
+            ReloadBackend::Rustls => self.reloader.set_rustls(next.rustls()?),
+        }
+        *settings = next;
+
+        if reload.drain {
+            // A new client has a new pool: the connections of the old one
+            // close once they are idle, and are no longer reused.
+            let hyper = self.hyper_builder.build(self.connector.clone());
+            *self.hyper.write().expect("lock poisoned") = hyper;
+            self.interfaces
+                .clients
+                .lock()
+                .expect("lock poisoned")
+                .clear();
         }
+        Ok(())
     }
 }
 
 #[derive(Clone)]
 struct HyperService {
-    hyper: HyperClient,
+    transport: Transport,
+    schemes: Arc<HashMap<String, BoxedHttpService>>,
+    interfaces: Arc<InterfaceClients>,
+}
+
+/// The clients of the requests bound to another network interface than the
+/// one of the client, each with its own pool, as the pool of a client does
+/// not tell connections apart by the interface they are bound to.
+struct InterfaceClients {
+    hyper_builder: hyper_util::client::legacy::Builder,
+    connector: ConnectorBuilder,
+    layers: Vec<BoxedConnectorLayer>,
+    clients: std::sync::Mutex<HashMap<u32, HyperClient>>,
+}
+
+impl InterfaceClients {
+    fn get(&self, index: u32) -> HyperClient {
+        let mut clients = self.clients.lock().expect("lock poisoned");
+        clients
+            .entry(index)
+            .or_insert_with(|| {
+                let mut connector = self.connector.clone();
+                connector.set_interface_index(Some(index));
+                self.hyper_builder
+                    .build(connector.build(self.layers.clone()))
+            })
+            .clone()
+    }
+}
+
+#[derive(Clone)]
+enum Transport {
+    // shared with `ReloadableTls`, which replaces it to drain the pool
+    Hyper(Arc<RwLock<HyperClient>>),
+    Custom(BoxedHttpService),
+}
+
+impl HyperService {
+    /// The client sending a request, which depends on the network interface
+    /// it is bound to.
+    fn hyper_client(
+        &self,
+        hyper: &RwLock<HyperClient>,
+        req: &hyper::Request<crate::async_impl::body::Body>,
+    ) -> HyperClient {
+        if let Some(index) = req.extensions().get::<InterfaceIndexExtension>() {
+            return self.interfaces.get(index.0);
+        }
+        hyper.read().expect("lock poisoned").clone()
+    }
 }
 
 impl Service<hyper::Request<crate::async_impl::body::Body>> for HyperService {
     type Error = crate::Error;
-    type Response = http::Response<hyper::body::Incoming>;
+    type Response = http::Response<ResponseBody>;
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + Sync>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.hyper.poll_ready(cx).map_err(crate::error::request)
+        match self.transport {
+            // the legacy client is always ready
+            Transport::Hyper(_) => Poll::Ready(Ok(())),
+            Transport::Custom(ref mut svc) => svc.poll_ready(cx).map_err(custom_service_error),
+        }
     }
 
     fn call(&mut self, req: hyper::Request<crate::async_impl::body::Body>) -> Self::Future {
-        let clone = self.hyper.clone();
-        let mut inner = std::mem::replace(&mut self.hyper, clone);
-        Box::pin(async move { inner.call(req).await.map_err(crate::error::request) })
+        let handler = req
+            .uri()
+            .scheme_str()
+            .and_then(|scheme| self.schemes.get(scheme));
+        if let Some(handler) = handler {
+            use tower::ServiceExt;
+
+            // The boxed service's future is not `Sync`, but `Pending` is.
+            let fut = sync_wrapper::SyncFuture::new(handler.clone().oneshot(req));
+            return Box::pin(async move {
+                let res = fut.await.map_err(custom_service_error)?;
+                Ok(res.map(super::body::boxed))
+            });
+        }
+        match self.transport {
+            Transport::Hyper(ref hyper) => {
+                let mut inner = self.hyper_client(hyper, &req);
+                Box::pin(async move {
+                    let res = inner.call(req).await.map_err(crate::error::request)?;
+                    #[cfg(any(
+                        feature = "gzip",
+                        feature = "brotli",
+                        feature = "zstd",
+                        feature = "deflate"
+                    ))]
+                    let res = super::offload::mark(res);
+                    Ok(res.map(super::body::boxed))
+                })
+            }
+            Transport::Custom(ref mut svc) => {
+                let clone = svc.clone();
+                let mut inner = std::mem::replace(svc, clone);
+                // The boxed service's future is not `Sync`, but `Pending` is.
+                let fut = sync_wrapper::SyncFuture::new(inner.call(req));
+                Box::pin(async move {
+                    let res = fut.await.map_err(custom_service_error)?;
+                    #[cfg(any(
+                        feature = "gzip",
+                        feature = "brotli",
+                        feature = "zstd",
+                        feature = "deflate"
+                    ))]
+                    let res = super::offload::mark(res);
+                    Ok(res.map(super::body::boxed))
+                })
+            }
+        }
+    }
+}
+
+fn custom_service_error(err: BoxError) -> crate::Error {
+    match err.downcast::<crate::Error>() {
+        Ok(err) => *err,
+        Err(err) => crate::error::request(err),
     }
 }
 
+#[derive(Clone)]
 struct Config {
     // NOTE: When adding a new field, update `fmt::Debug for ClientBuilder`
     accepts: Accepts,
     headers: HeaderMap,
+    query: Vec<(String, String)>,
+    hosts: Vec<(String, HostConfig)>,
     #[cfg(feature = "__tls")]
     hostname_verification: bool,
     #[cfg(feature = "__tls")]
@@ -170,14 +683,25 @@ struct Config {
     #[cfg(feature = "__rustls")]
     tls_sslkeylogfile: bool,
     connect_timeout: Option<Duration>,
+    connect_retry: Option<crate::retry::Connect>,
     connection_verbose: bool,
+    connection_observer: Option<Observer>,
+    wire_observer: Option<Wire>,
+    audit_observer: Option<Audit>,
+    on_error: Option<OnError>,
+    counters: Arc<Counters>,
     pool_idle_timeout: Option<Duration>,
     pool_max_idle_per_host: usize,
+    max_in_flight: Option<usize>,
+    max_in_flight_per_host: Option<usize>,
+    in_flight_queue_timeout: Option<Duration>,
+    circuit_breaker: Option<Breaker>,
     tcp_keepalive: Option<Duration>,
     tcp_keepalive_interval: Option<Duration>,
     tcp_keepalive_retries: Option<u32>,
     #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
     tcp_user_timeout: Option<Duration>,
+    proxy_protocol: Option<ProxyProtocol>,
     #[cfg(any(feature = "__native-tls", feature = "__rustls"))]
     identity: Option<Identity>,
     proxies: Vec<ProxyMatcher>,
@@ -185,6 +709,14 @@ struct Config {
     redirect_policy: redirect::Policy,
     retry_policy: crate::retry::Builder,
     referer: bool,
+    #[cfg(feature = "content-digest")]
+    content_digest: bool,
+    #[cfg(feature = "message-signatures")]
+    message_signer: Option<Arc<Signer>>,
+    token_providers: Vec<(String, Arc<dyn TokenProvider>)>,
+    #[cfg(feature = "tracing")]
+    trace_context: Option<CurrentContext>,
+    redaction: Option<Redaction>,
     read_timeout: Option<Duration>,
     timeout: Option<Duration>,
     #[cfg(feature = "__tls")]
@@ -202,12 +734,17 @@ struct Config {
     #[cfg(feature = "__tls")]
     tls: TlsBackend,
     connector_layers: Vec<BoxedConnectorLayer>,
+    http_service: Option<BoxedHttpService>,
+    scheme_handlers: HashMap<String, BoxedHttpService>,
     http_version_pref: HttpVersionPref,
     http09_responses: bool,
     http1_title_case_headers: bool,
     http1_allow_obsolete_multiline_headers_in_responses: bool,
     http1_ignore_invalid_headers_in_responses: bool,
     http1_allow_spaces_after_header_name_in_responses: bool,
+    http1_max_headers: Option<usize>,
+    http1_max_buf_size: Option<usize>,
+    http1_writev: Option<bool>,
     #[cfg(feature = "http2")]
     http2_initial_stream_window_size: Option<u32>,
     #[cfg(feature = "http2")]
@@ -238,12 +775,23 @@ struct Config {
         target_os = "watchos",
     ))]
     interface: Option<String>,
+    interface_index: Option<u32>,
     nodelay: bool,
     #[cfg(feature = "cookies")]
     cookie_store: Option<Arc<dyn cookie::CookieStore>>,
+    #[cfg(feature = "cache")]
+    cache: CacheSettings,
+    #[cfg(feature = "cache")]
+    conditional: ConditionalSettings,
     hickory_dns: bool,
-    error: Option<crate::Error>,
+    base_url: Option<Url>,
     https_only: bool,
+    #[cfg(feature = "hsts-preload")]
+    hsts_preload: Option<Arc<crate::hsts::Preload>>,
+    #[cfg(feature = "file")]
+    file_urls: bool,
+    strict_content_type: bool,
+    idna: bool,
     #[cfg(feature = "http3")]
     tls_enable_early_data: bool,
     #[cfg(feature = "http3")]
@@ -262,6 +810,8 @@ struct Config {
     h3_send_grease: Option<bool>,
     dns_overrides: HashMap<String, Vec<SocketAddr>>,
     dns_resolver: Option<Arc<dyn Resolve>>,
+    dns_balance: Balance,
+    avoid_failed_addresses: Option<Duration>,
 
     #[cfg(unix)]
     unix_socket: Option<Arc<std::path::Path>>,
@@ -285,9 +835,11 @@ impl ClientBuilder {
 
         ClientBuilder {
             config: Config {
-                error: None,
+                base_url: None,
                 accepts: Accepts::default(),
                 headers,
+                query: Vec::new(),
+                hosts: Vec::new(),
                 #[cfg(feature = "__tls")]
                 hostname_verification: true,
                 #[cfg(feature = "__tls")]
@@ -297,19 +849,38 @@ impl ClientBuilder {
                 #[cfg(feature = "__rustls")]
                 tls_sslkeylogfile: false,
                 connect_timeout: None,
+                connect_retry: None,
                 connection_verbose: false,
+                connection_observer: None,
+                wire_observer: None,
+                audit_observer: None,
+                on_error: None,
+                counters: Arc::new(Counters::default()),
                 pool_idle_timeout: Some(Duration::from_secs(90)),
                 pool_max_idle_per_host: usize::MAX,
+                max_in_flight: None,
+                max_in_flight_per_host: None,
+                in_flight_queue_timeout: None,
+                circuit_breaker: None,
                 tcp_keepalive: Some(Duration::from_secs(15)),
                 tcp_keepalive_interval: Some(Duration::from_secs(15)),
                 tcp_keepalive_retries: Some(3),
                 #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
                 tcp_user_timeout: Some(Duration::from_secs(30)),
+                proxy_protocol: None,
                 proxies: Vec::new(),
                 auto_sys_proxy: true,
                 redirect_policy: redirect::Policy::default(),
                 retry_policy: crate::retry::Builder::default(),
                 referer: true,
+                #[cfg(feature = "content-digest")]
+                content_digest: false,
+                #[cfg(feature = "message-signatures")]
+                message_signer: None,
+                token_providers: Vec::new(),
+                #[cfg(feature = "tracing")]
+                trace_context: None,
+                redaction: None,
                 read_timeout: None,
                 timeout: None,
                 #[cfg(feature = "__tls")]
@@ -329,12 +900,17 @@ impl ClientBuilder {
                 #[cfg(feature = "__tls")]
                 tls: TlsBackend::default(),
                 connector_layers: Vec::new(),
+                http_service: None,
+                scheme_handlers: HashMap::new(),
                 http_version_pref: HttpVersionPref::All,
                 http09_responses: false,
                 http1_title_case_headers: false,
                 http1_allow_obsolete_multiline_headers_in_responses: false,
                 http1_ignore_invalid_headers_in_responses: false,
                 http1_allow_spaces_after_header_name_in_responses: false,
+                http1_max_headers: None,
+                http1_max_buf_size: None,
+                http1_writev: None,
                 #[cfg(feature = "http2")]
                 http2_initial_stream_window_size: None,
                 #[cfg(feature = "http2")]
@@ -365,11 +941,22 @@ impl ClientBuilder {
                     target_os = "watchos",
                 ))]
                 interface: None,
+                interface_index: None,
                 nodelay: true,
                 hickory_dns: cfg!(feature = "hickory-dns"),
                 #[cfg(feature = "cookies")]
                 cookie_store: None,
+                #[cfg(feature = "cache")]
+                cache: CacheSettings::default(),
+                #[cfg(feature = "cache")]
+                conditional: ConditionalSettings::default(),
                 https_only: false,
+                #[cfg(feature = "hsts-preload")]
+                hsts_preload: None,
+                #[cfg(feature = "file")]
+                file_urls: false,
+                strict_content_type: false,
+                idna: true,
                 dns_overrides: HashMap::new(),
                 #[cfg(feature = "http3")]
                 tls_enable_early_data: false,
@@ -388,12 +975,154 @@ impl ClientBuilder {
                 #[cfg(feature = "http3")]
                 h3_send_grease: None,
                 dns_resolver: None,
+                dns_balance: Balance::default(),
+                avoid_failed_addresses: None,
                 #[cfg(unix)]
                 unix_socket: None,
                 #[cfg(target_os = "windows")]
                 windows_named_pipe: None,
             },
+            error: None,
+        }
+    }
+
+    /// Constructs a new `ClientBuilder` with the settings of a [`ClientConfig`].
+    ///
+    /// The settings missing from `config` keep their default, and all of
+    /// them can still be changed before building the client.
+    ///
+    /// # Errors
+    ///
+    /// Building the client fails if a header, a proxy URL or a TLS version
+    /// of `config` is invalid, if a root certificate can't be read, or if
+    /// TLS settings are set without a TLS backend.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `client-config` feature to be enabled.
+    ///
+    /// [`ClientConfig`]: crate::client_config::ClientConfig
+    #[cfg(feature = "client-config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client-config")))]
+    pub fn from_config(config: &ClientConfig) -> ClientBuilder {
+        let mut builder = ClientBuilder::new();
+        if let Some(ref user_agent) = config.user_agent {
+            builder = builder.user_agent(user_agent.as_str());
+        }
+        for (name, value) in &config.headers {
+            match (
+                http::header::HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    builder.config.headers.insert(name, value);
+                }
+                (Err(e), _) => builder.error = Some(crate::error::builder(e)),
+                (_, Err(e)) => builder.error = Some(crate::error::builder(e)),
+            }
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = config.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(max) = config.max_redirects {
+            builder = builder.redirect(if max == 0 {
+                redirect::Policy::none()
+            } else {
+                redirect::Policy::limited(max)
+            });
+        }
+        if let Some(https_only) = config.https_only {
+            builder = builder.https_only(https_only);
+        }
+
+        let proxy = &config.proxy;
+        if proxy.system == Some(false) {
+            builder = builder.no_proxy();
+        }
+        let proxies = [
+            proxy.all.as_deref().map(Proxy::all),
+            proxy.http.as_deref().map(Proxy::http),
+            proxy.https.as_deref().map(Proxy::https),
+        ];
+        for p in proxies.into_iter().flatten() {
+            match p {
+                Ok(p) => {
+                    let no_proxy = proxy
+                        .no_proxy
+                        .as_deref()
+                        .and_then(crate::NoProxy::from_string);
+                    builder = builder.proxy(p.no_proxy(no_proxy));
+                }
+                Err(e) => builder.error = Some(e),
+            }
+        }
+
+        if let Some(timeout) = config.pool.idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = config.pool.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(nodelay) = config.tcp.nodelay {
+            builder = builder.tcp_nodelay(nodelay);
+        }
+        if let Some(interval) = config.tcp.keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+
+        #[cfg(feature = "__tls")]
+        {
+            builder = builder.tls_config(&config.tls);
+        }
+        #[cfg(not(feature = "__tls"))]
+        if !config.tls.is_empty() {
+            builder.error = Some(crate::error::builder("TLS settings require a TLS backend"));
+        }
+        builder
+    }
+
+    #[cfg(all(feature = "client-config", feature = "__tls"))]
+    fn tls_config(mut self, config: &crate::client_config::TlsConfig) -> ClientBuilder {
+        if let Some(ref version) = config.min_version {
+            match crate::client_config::tls_version(version) {
+                Ok(version) => self = self.tls_version_min(version),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        if let Some(ref version) = config.max_version {
+            match crate::client_config::tls_version(version) {
+                Ok(version) => self = self.tls_version_max(version),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        let mut certs = Vec::new();
+        for path in &config.root_certificates {
+            match std::fs::read(path)
+                .map_err(crate::error::builder)
+                .and_then(|pem| Certificate::from_pem_bundle(&pem))
+            {
+                Ok(bundle) => certs.extend(bundle),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        self = if config.certs_only {
+            self.tls_certs_only(certs)
+        } else {
+            self.tls_certs_merge(certs)
+        };
+        if let Some(sni) = config.sni {
+            self = self.tls_sni(sni);
+        }
+        if let Some(accept) = config.danger_accept_invalid_certs {
+            self = self.tls_danger_accept_invalid_certs(accept);
         }
+        self
     }
 }
 
@@ -405,12 +1134,13 @@ impl ClientBuilder {
     /// This method fails if a TLS backend cannot be initialized, or the resolver
     /// cannot load the system configuration.
     pub fn build(self) -> crate::Result<Client> {
-        let config = self.config;
-
-        if let Some(err) = config.error {
+        if let Some(err) = self.error {
             return Err(err);
         }
 
+        let config = self.config;
+        let builder_config = config.clone();
+
         let mut proxies = config.proxies;
         if config.auto_sys_proxy {
             proxies.push(ProxyMatcher::system());
@@ -421,6 +1151,11 @@ impl ClientBuilder {
         #[cfg(feature = "http3")]
         let mut h3_connector = None;
 
+        let balancer = Some(config.dns_balance)
+            .filter(|balance| {
+                !balance.is_resolver_order() || config.avoid_failed_addresses.is_some()
+            })
+            .map(|balance| Arc::new(Balancer::new(balance, config.avoid_failed_addresses)));
         let resolver = {
             let mut resolver: Arc<dyn Resolve> = match config.hickory_dns {
                 false => Arc::new(GaiResolver::new()),
@@ -438,16 +1173,42 @@ impl ClientBuilder {
                     config.dns_overrides,
                 ));
             }
+            if let Some(ref balancer) = balancer {
+                resolver = Arc::new(BalancedResolve::new(resolver, balancer.clone()));
+            }
+            resolver = Arc::new(ObservedResolve::new(
+                resolver,
+                config.connection_observer.clone(),
+            ));
             DynResolver::new(resolver)
         };
 
+        #[cfg(feature = "__tls")]
+        let tls_settings = TlsSettings {
+            http_version_pref: config.http_version_pref,
+            hostname_verification: config.hostname_verification,
+            certs_verification: config.certs_verification,
+            tls_sni: config.tls_sni,
+            tls_certs_only: config.tls_certs_only,
+            root_certs: config.root_certs,
+            identity: config.identity,
+            min_tls_version: config.min_tls_version,
+            max_tls_version: config.max_tls_version,
+            #[cfg(feature = "__rustls")]
+            crls: Arc::new(config.crls),
+            #[cfg(feature = "__rustls")]
+            tls_sslkeylogfile: config.tls_sslkeylogfile,
+        };
+        #[cfg(feature = "__tls")]
+        let mut tls_backend = None;
+
         let mut connector_builder = {
             #[cfg(feature = "__tls")]
             fn user_agent(headers: &HeaderMap) -> Option<HeaderValue> {
                 headers.get(USER_AGENT).cloned()
             }
 
-            let mut http = HttpConnector::new_with_resolver(resolver.clone());
+            let mut http = TcpConnector::new(resolver.clone());
             http.set_connect_timeout(config.connect_timeout);
 
             #[cfg(all(feature = "http3", feature = "__rustls"))]
@@ -522,75 +1283,8 @@ impl ClientBuilder {
             match config.tls {
                 #[cfg(feature = "__native-tls")]
                 TlsBackend::NativeTls => {
-                    let mut tls = TlsConnector::builder();
-
-                    #[cfg(all(feature = "__native-tls-alpn", not(feature = "http3")))]
-                    {
-                        match config.http_version_pref {
-                            HttpVersionPref::Http1 => {
-                                tls.request_alpns(&["http/1.1"]);
-                            }
-                            #[cfg(feature = "http2")]
-                            HttpVersionPref::Http2 => {
-                                tls.request_alpns(&["h2"]);
-                            }
-                            HttpVersionPref::All => {
-                                tls.request_alpns(&[
-                                    #[cfg(feature = "http2")]
-                                    "h2",
-                                    "http/1.1",
-                                ]);
-                            }
-                        }
-                    }
-
-                    tls.danger_accept_invalid_hostnames(!config.hostname_verification);
-
-                    tls.danger_accept_invalid_certs(!config.certs_verification);
-
-                    tls.use_sni(config.tls_sni);
-
-                    tls.disable_built_in_roots(config.tls_certs_only);
-
-                    for cert in config.root_certs {
-                        cert.add_to_native_tls(&mut tls);
-                    }
-
-                    #[cfg(feature = "__native-tls")]
-                    {
-                        if let Some(id) = config.identity {
-                            id.add_to_native_tls(&mut tls)?;
-                        }
-                    }
-                    #[cfg(all(feature = "__rustls", not(feature = "__native-tls")))]
-                    {
-                        // Default backend + rustls Identity doesn't work.
-                        if let Some(_id) = config.identity {
-                            return Err(crate::error::builder("incompatible TLS identity type"));
-                        }
-                    }
-
-                    if let Some(min_tls_version) = config.min_tls_version {
-                        let protocol = min_tls_version.to_native_tls().ok_or_else(|| {
-                            // native-tls added support for TLS v1.3 in 0.2.16 🎉
-                            // `to_native_tls` could arguably return the value directly
-                            // instead of making us check for an impossible None here,
-                            // but given that 1.4 does not exist yet, that might get
-                            // messy in the future.
-                            crate::error::builder("invalid minimum TLS version for backend")
-                        })?;
-                        tls.min_protocol_version(Some(protocol));
-                    }
-
-                    if let Some(max_tls_version) = config.max_tls_version {
-                        let protocol = max_tls_version.to_native_tls().ok_or_else(|| {
-                            // We could arguably do max_protocol_version(None), given
-                            // that 1.4 does not exist yet, but that'd get messy in the
-                            // future.
-                            crate::error::builder("invalid maximum TLS version for backend")
-                        })?;
-                        tls.max_protocol_version(Some(protocol));
-                    }
+                    let tls = tls_settings.native_tls()?;
+                    tls_backend = Some(ReloadBackend::NativeTls);
 
                     ConnectorBuilder::new_native_tls(
                         http,
@@ -685,169 +1379,16 @@ impl ClientBuilder {
                 }
                 #[cfg(feature = "__rustls")]
                 TlsBackend::Rustls => {
-                    use crate::tls::{IgnoreHostname, NoVerifier};
-
-                    // Set TLS versions.
-                    let mut versions = rustls::ALL_VERSIONS.to_vec();
-
-                    if let Some(min_tls_version) = config.min_tls_version {
-                        versions.retain(|&supported_version| {
-                            match tls::Version::from_rustls(supported_version.version) {
-                                Some(version) => version >= min_tls_version,
-                                // Assume it's so new we don't know about it, allow it
-                                // (as of writing this is unreachable)
-                                None => true,
-                            }
-                        });
-                    }
-
-                    if let Some(max_tls_version) = config.max_tls_version {
-                        versions.retain(|&supported_version| {
-                            match tls::Version::from_rustls(supported_version.version) {
-                                Some(version) => version <= max_tls_version,
-                                None => false,
-                            }
-                        });
-                    }
-
-                    if versions.is_empty() {
-                        return Err(crate::error::builder("empty supported tls versions"));
-                    }
-
-                    // Allow user to have installed a runtime default.
-                    // If not, we ship with _our_ recommended default.
-                    let provider = rustls::crypto::CryptoProvider::get_default()
-                        .map(|arc| arc.clone())
-                        .unwrap_or_else(default_rustls_crypto_provider);
-
-                    // Build TLS config
-                    let signature_algorithms = provider.signature_verification_algorithms;
-                    let config_builder =
-                        rustls::ClientConfig::builder_with_provider(provider.clone())
-                            .with_protocol_versions(&versions)
-                            .map_err(|_| crate::error::builder("invalid TLS versions"))?;
-
-                    let config_builder = if !config.certs_verification {
-                        config_builder
-                            .dangerous()
-                            .with_custom_certificate_verifier(Arc::new(NoVerifier))
-                    } else if !config.hostname_verification {
-                        if !config.tls_certs_only {
-                            // Should this just warn? Error for now...
-                            return Err(crate::error::builder(
-                                    "disabling rustls hostname verification only allowed with tls_certs_only()"
-                            ));
-                        }
-
-                        config_builder
-                            .dangerous()
-                            .with_custom_certificate_verifier(Arc::new(IgnoreHostname::new(
-                                crate::tls::rustls_store(config.root_certs)?,
-                                signature_algorithms,
-                            )))
-                    } else if !config.tls_certs_only {
-                        // Check for some misconfigurations and report them.
-                        if !config.crls.is_empty() {
-                            return Err(crate::error::builder(
-                                "CRLs only allowed with tls_certs_only()",
-                            ));
-                        }
-
-                        let verifier = if config.root_certs.is_empty() {
-                            rustls_platform_verifier::Verifier::new(provider.clone())
-                                .map_err(crate::error::builder)?
-                        } else {
-                            #[cfg(any(
-                                all(unix, not(target_os = "android")),
-                                target_os = "windows"
-                            ))]
-                            {
-                                rustls_platform_verifier::Verifier::new_with_extra_roots(
-                                    crate::tls::rustls_der(config.root_certs)?,
-                                    provider.clone(),
-                                )
-                                .map_err(crate::error::builder)?
-                            }
+                    let tls = tls_settings.rustls()?;
+                    tls_backend = Some(ReloadBackend::Rustls);
 
-                            #[cfg(not(any(
-                                all(unix, not(target_os = "android")),
-                                target_os = "windows"
-                            )))]
-                            return Err(crate::error::builder(
-                                "rustls-platform-verifier could not load extra certs",
-                            ));
-                        };
-
-                        config_builder
-                            .dangerous()
-                            .with_custom_certificate_verifier(Arc::new(verifier))
-                    } else {
-                        if config.crls.is_empty() {
-                            config_builder.with_root_certificates(crate::tls::rustls_store(
-                                config.root_certs,
-                            )?)
-                        } else {
-                            let crls = config
-                                .crls
-                                .iter()
-                                .map(|e| e.as_rustls_crl())
-                                .collect::<Vec<_>>();
-                            let verifier =
-                                rustls::client::WebPkiServerVerifier::builder_with_provider(
-                                    Arc::new(crate::tls::rustls_store(config.root_certs)?),
-                                    provider,
-                                )
-                                .with_crls(crls)
-                                .build()
-                                .map_err(|_| {
-                                    crate::error::builder("invalid TLS verification settings")
-                                })?;
-                            config_builder.with_webpki_verifier(verifier)
-                        }
-                    };
+                    #[cfg(feature = "http3")]
+                    {
+                        let mut h3_tls = tls.clone();
+                        h3_tls.enable_early_data = config.tls_enable_early_data;
 
-                    // Finalize TLS config
-                    let mut tls = if let Some(id) = config.identity {
-                        id.add_to_rustls(config_builder)?
-                    } else {
-                        config_builder.with_no_client_auth()
-                    };
-
-                    tls.enable_sni = config.tls_sni;
-
-                    if config.tls_sslkeylogfile {
-                        tls.key_log = Arc::new(rustls::KeyLogFile::new());
-                    }
-
-                    // ALPN protocol
-                    match config.http_version_pref {
-                        HttpVersionPref::Http1 => {
-                            tls.alpn_protocols = vec!["http/1.1".into()];
-                        }
-                        #[cfg(feature = "http2")]
-                        HttpVersionPref::Http2 => {
-                            tls.alpn_protocols = vec!["h2".into()];
-                        }
-                        #[cfg(feature = "http3")]
-                        HttpVersionPref::Http3 => {
-                            // h3 ALPN is not valid over TCP
-                        }
-                        HttpVersionPref::All => {
-                            tls.alpn_protocols = vec![
-                                #[cfg(feature = "http2")]
-                                "h2".into(),
-                                "http/1.1".into(),
-                            ];
-                        }
-                    }
-
-                    #[cfg(feature = "http3")]
-                    {
-                        let mut h3_tls = tls.clone();
-                        h3_tls.enable_early_data = config.tls_enable_early_data;
-
-                        // h3 ALPN is required over QUIC for HTTP/3
-                        h3_tls.alpn_protocols = vec!["h3".into()];
+                        // h3 ALPN is required over QUIC for HTTP/3
+                        h3_tls.alpn_protocols = vec!["h3".into()];
 
                         h3_connector = build_h3_connector(
                             resolver.clone(),
@@ -918,12 +1459,20 @@ impl ClientBuilder {
         };
 
         connector_builder.set_timeout(config.connect_timeout);
+        connector_builder.set_retry(config.connect_retry);
         connector_builder.set_verbose(config.connection_verbose);
+        connector_builder.set_redaction(config.redaction.clone());
+        connector_builder.set_observer(config.connection_observer);
+        connector_builder.set_counters(config.counters.clone());
+        connector_builder.set_balancer(balancer);
         connector_builder.set_keepalive(config.tcp_keepalive);
         connector_builder.set_keepalive_interval(config.tcp_keepalive_interval);
         connector_builder.set_keepalive_retries(config.tcp_keepalive_retries);
         #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
         connector_builder.set_tcp_user_timeout(config.tcp_user_timeout);
+        connector_builder.set_proxy_protocol(config.proxy_protocol);
+        #[cfg(feature = "__tls")]
+        connector_builder.set_host_tls(host_tls(&tls_settings, tls_backend, &config.hosts)?);
 
         #[cfg(feature = "socks")]
         connector_builder.set_socks_resolver(resolver);
@@ -931,6 +1480,7 @@ impl ClientBuilder {
         // TODO: It'd be best to refactor this so the HttpConnector is never
         // constructed at all. But there's a lot of code for all the different
         // ways TLS can be configured...
+        connector_builder.set_interface_index(config.interface_index);
         #[cfg(unix)]
         connector_builder.set_unix_socket(config.unix_socket);
         #[cfg(target_os = "windows")]
@@ -998,6 +1548,18 @@ impl ClientBuilder {
             builder.http1_allow_spaces_after_header_name_in_responses(true);
         }
 
+        if let Some(max) = config.http1_max_headers {
+            builder.http1_max_headers(max);
+        }
+
+        if let Some(max) = config.http1_max_buf_size {
+            builder.http1_max_buf_size(max);
+        }
+
+        if let Some(enabled) = config.http1_writev {
+            builder.http1_writev(enabled);
+        }
+
         let proxies_maybe_http_auth = proxies.iter().any(|p| p.maybe_has_http_auth());
         let proxies_maybe_http_custom_headers =
             proxies.iter().any(|p| p.maybe_has_http_custom_headers());
@@ -1008,24 +1570,82 @@ impl ClientBuilder {
             Some(format!("{:?}", &config.redirect_policy))
         };
 
-        let hyper_client = builder.build(connector_builder.build(config.connector_layers));
+        #[cfg(feature = "__tls")]
+        let tls_reloader = connector_builder.tls_reloader();
+        let interfaces = Arc::new(InterfaceClients {
+            hyper_builder: builder.clone(),
+            connector: connector_builder.clone(),
+            layers: config.connector_layers.clone(),
+            clients: Default::default(),
+        });
+        let connector = connector_builder.build(config.connector_layers);
+        let hyper_client = Arc::new(RwLock::new(builder.build(connector.clone())));
+        #[cfg(feature = "__tls")]
+        let tls_reload = match (tls_backend, &config.http_service) {
+            (Some(backend), None) => Some(Arc::new(ReloadableTls {
+                backend,
+                settings: std::sync::Mutex::new(tls_settings),
+                reloader: tls_reloader,
+                hyper_builder: builder,
+                connector,
+                hyper: hyper_client.clone(),
+                interfaces: interfaces.clone(),
+            })),
+            _ => None,
+        };
+        let scheme_names = config.scheme_handlers.keys().cloned().collect();
         let hyper_service = HyperService {
-            hyper: hyper_client,
+            transport: match config.http_service {
+                Some(svc) => Transport::Custom(svc),
+                None => Transport::Hyper(hyper_client),
+            },
+            schemes: Arc::new(config.scheme_handlers),
+            interfaces,
         };
 
         let redirect_policy = {
             let mut p = TowerRedirectPolicy::new(config.redirect_policy);
             p.with_referer(config.referer)
                 .with_https_only(config.https_only);
+            #[cfg(feature = "hsts-preload")]
+            p.with_hsts_preload(config.hsts_preload.clone());
             p
         };
 
         let retry_policy = config.retry_policy.into_policy();
 
+        let hyper_service = WireService::new(
+            hyper_service,
+            config.counters.clone(),
+            config.wire_observer.clone(),
+            config.redaction.clone(),
+        );
+        #[cfg(feature = "message-signatures")]
+        let hyper_service = SignatureService::new(hyper_service, config.message_signer.clone());
+        let in_flight = match (config.max_in_flight_per_host, config.max_in_flight) {
+            (None, None) => None,
+            (per_host, total) => Some(Arc::new(InFlightLimits::new(
+                per_host,
+                total,
+                config.in_flight_queue_timeout,
+            ))),
+        };
+        let hyper_service = RawTargetService::new(hyper_service);
         let svc = tower::retry::Retry::new(retry_policy.clone(), hyper_service);
-
+        let svc = InFlightService::new(svc, in_flight.clone());
+        let circuits = config
+            .circuit_breaker
+            .map(|breaker| Arc::new(Circuits::new(breaker)));
+        let svc = CircuitService::new(svc, circuits.clone());
+
+        #[cfg(feature = "content-digest")]
+        let svc = ContentDigestService::new(svc, config.content_digest);
         #[cfg(feature = "cookies")]
         let svc = CookieService::new(svc, config.cookie_store.clone());
+        #[cfg(feature = "cache")]
+        let svc = ConditionalService::new(svc, config.conditional.clone());
+        #[cfg(feature = "cache")]
+        let svc = CacheService::new(svc, config.cache.clone());
         let hyper = FollowRedirect::with_policy(svc, redirect_policy.clone());
         #[cfg(any(
             feature = "gzip",
@@ -1048,6 +1668,8 @@ impl ClientBuilder {
         let hyper = hyper.zstd(config.accepts.zstd);
         #[cfg(feature = "deflate")]
         let hyper = hyper.deflate(config.accepts.deflate);
+        let token_providers = Arc::new(config.token_providers);
+        let hyper = TokenService::new(hyper, token_providers.clone());
 
         Ok(Client {
             inner: Arc::new(ClientRef {
@@ -1060,9 +1682,26 @@ impl ClientBuilder {
                 h3_client: match h3_connector {
                     Some(h3_connector) => {
                         let h3_service = H3Client::new(h3_connector, config.pool_idle_timeout);
+                        let h3_service = WireService::new(
+                            h3_service,
+                            config.counters.clone(),
+                            config.wire_observer,
+                            config.redaction.clone(),
+                        );
+                        #[cfg(feature = "message-signatures")]
+                        let h3_service = SignatureService::new(h3_service, config.message_signer);
+                        let h3_service = RawTargetService::new(h3_service);
                         let svc = tower::retry::Retry::new(retry_policy, h3_service);
+                        let svc = InFlightService::new(svc, in_flight.clone());
+                        let svc = CircuitService::new(svc, circuits);
+                        #[cfg(feature = "content-digest")]
+                        let svc = ContentDigestService::new(svc, config.content_digest);
                         #[cfg(feature = "cookies")]
                         let svc = CookieService::new(svc, config.cookie_store);
+                        #[cfg(feature = "cache")]
+                        let svc = ConditionalService::new(svc, config.conditional);
+                        #[cfg(feature = "cache")]
+                        let svc = CacheService::new(svc, config.cache);
                         let svc = FollowRedirect::with_policy(svc, redirect_policy);
                         #[cfg(any(
                             feature = "gzip",
@@ -1085,21 +1724,43 @@ impl ClientBuilder {
                         let svc = svc.zstd(config.accepts.zstd);
                         #[cfg(feature = "deflate")]
                         let svc = svc.deflate(config.accepts.deflate);
+                        let svc = TokenService::new(svc, token_providers);
                         Some(svc)
                     }
                     None => None,
                 },
                 headers: config.headers,
+                query: config.query,
+                hosts: config.hosts,
+                base_url: config.base_url,
                 referer: config.referer,
-                read_timeout: config.read_timeout,
+                read_timeout: RequestConfig::new(config.read_timeout),
                 total_timeout: RequestConfig::new(config.timeout),
                 hyper,
                 proxies,
                 proxies_maybe_http_auth,
                 proxies_maybe_http_custom_headers,
                 https_only: config.https_only,
+                #[cfg(feature = "hsts-preload")]
+                hsts_preload: config.hsts_preload.clone(),
+                #[cfg(feature = "file")]
+                file_urls: config.file_urls,
+                strict_content_type: config.strict_content_type,
+                idna: config.idna,
+                schemes: scheme_names,
                 redirect_policy_desc,
+                #[cfg(feature = "tracing")]
+                trace_context: config.trace_context,
+                redaction: config.redaction,
+                audit_observer: config.audit_observer,
+                on_error: config.on_error,
+                counters: config.counters,
+                in_flight,
+                #[cfg(feature = "__tls")]
+                tls_reload,
+                builder_config,
             }),
+            ready: InFlightReadiness::default(),
         })
     }
 
@@ -1135,11 +1796,59 @@ impl ClientBuilder {
                 self.config.headers.insert(USER_AGENT, value);
             }
             Err(e) => {
-                self.config.error = Some(crate::error::builder(e.into()));
+                self.error = Some(crate::error::builder(e.into()));
             }
         };
         self
     }
+
+    /// Sets the base URL that relative URLs of requests are resolved against.
+    ///
+    /// With a base URL, `client.get("users")` can be used instead of the
+    /// full URL of the resource. URLs are resolved like links in a page: a
+    /// path starting with `/` replaces the whole path of the base URL, and
+    /// other paths are appended to it. A slash is added at the end of the
+    /// path of the base URL if it doesn't have one, so that
+    /// `https://api.example.com/v2` and `https://api.example.com/v2/` are the
+    /// same base. Absolute URLs are used as they are.
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .base_url("https://api.example.com/v2")
+    ///     .build()?;
+    /// // GET https://api.example.com/v2/users/42
+    /// let req = client.get("users/42").build()?;
+    /// # assert_eq!(req.url().as_str(), "https://api.example.com/v2/users/42");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Building the client fails if `url` is not a valid base URL.
+    pub fn base_url<U: IntoUrl>(mut self, url: U) -> ClientBuilder {
+        match url.into_url() {
+            Ok(mut url) if !url.cannot_be_a_base() => {
+                if !url.path().ends_with('/') {
+                    let path = format!("{}/", url.path());
+                    url.set_path(&path);
+                }
+                self.config.base_url = Some(url);
+            }
+            Ok(url) => {
+                self.error = Some(crate::error::builder("not a base URL").with_url(url));
+            }
+            Err(err) => self.error = Some(err),
+        }
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn configured_base_url(&self) -> Option<&Url> {
+        self.config.base_url.as_ref()
+    }
+
     /// Sets the default headers for every request.
     ///
     /// # Example
@@ -1170,6 +1879,103 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the default query parameters for every request.
+    ///
+    /// They are appended to the URL of each request, unless it already has a
+    /// parameter with the same name. Setting a parameter again replaces its
+    /// previous default value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn doc() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .default_query(&[("api_key", "secret"), ("version", "2")])
+    ///     .build()?;
+    /// // GET https://api.example.com/items?page=2&api_key=secret&version=2
+    /// let res = client.get("https://api.example.com/items?page=2").send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn default_query<K, V>(mut self, query: &[(K, V)]) -> ClientBuilder
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (key, value) in query {
+            let key = key.as_ref();
+            self.config.query.retain(|(k, _)| k != key);
+            self.config
+                .query
+                .push((key.to_owned(), value.as_ref().to_owned()));
+        }
+        self
+    }
+
+    /// Override settings of the client for the requests to one host.
+    ///
+    /// `configure` gets the settings of `host` set so far, empty at first,
+    /// and returns them with the changes it makes. The host is matched
+    /// exactly, without its port, so `example.com` doesn't cover
+    /// `www.example.com`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn doc() -> Result<(), reqwest::Error> {
+    /// use std::time::Duration;
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .timeout(Duration::from_secs(10))
+    ///     .for_host("slow.example.com", |host| {
+    ///         host.timeout(Duration::from_secs(60))
+    ///             .read_timeout(Duration::from_secs(30))
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// See the [`host`](crate::host) module for more.
+    pub fn for_host<F>(mut self, host: &str, configure: F) -> ClientBuilder
+    where
+        F: FnOnce(HostConfig) -> HostConfig,
+    {
+        let host = host.to_ascii_lowercase();
+        let position = self.config.hosts.iter().position(|(name, _)| *name == host);
+        let current = match position {
+            Some(i) => self.config.hosts.remove(i).1,
+            None => HostConfig::default(),
+        };
+        self.config.hosts.push((host, configure(current)));
+        self
+    }
+
+    /// Set the default priority of requests, with the `Priority` header of
+    /// RFC 9218.
+    ///
+    /// Requests without a priority of their own, set with
+    /// [`RequestBuilder::priority`](crate::RequestBuilder::priority), get this
+    /// one. A client sending bulk downloads can give them a low urgency, so
+    /// that the server favors other requests sharing their connection.
+    ///
+    /// The stream dependencies and weights of HTTP/2, deprecated by RFC 9113,
+    /// can't be set: servers multiplexing the streams of an HTTP/2 or HTTP/3
+    /// connection schedule them by this header instead.
+    ///
+    /// # Errors
+    ///
+    /// Building the client fails if `urgency` is greater than `7`.
+    pub fn priority(mut self, urgency: u8, incremental: bool) -> ClientBuilder {
+        match crate::util::priority(urgency, incremental) {
+            Ok(value) => {
+                self.config.headers.insert(crate::util::PRIORITY, value);
+            }
+            Err(err) => self.error = Some(err),
+        }
+        self
+    }
+
     /// Enable a persistent cookie store for the client.
     ///
     /// Cookies received in responses will be preserved and included in
@@ -1310,6 +2116,187 @@ impl ClientBuilder {
         self
     }
 
+    /// Enable automatic `Content-Digest` computation and verification.
+    ///
+    /// If turned on:
+    ///
+    /// - When sending a request with a body, and the request doesn't already
+    ///   contain a `Content-Digest`, a `sha-256` digest of the body is added.
+    ///   Streaming bodies are hashed as they are sent, and the digest is sent
+    ///   as a trailer field, which requires HTTP/2 or a chunked HTTP/1.1 body.
+    /// - When receiving a response with a `Content-Digest` header, or one
+    ///   declared as a trailer, the body is hashed as it is read. If the
+    ///   digest doesn't match, reading the body returns an error.
+    ///
+    /// Digests are computed over the content as sent on the wire, before any
+    /// automatic decompression.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `content-digest` feature to be enabled.
+    #[cfg(feature = "content-digest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "content-digest")))]
+    pub fn content_digest(mut self, enable: bool) -> ClientBuilder {
+        self.config.content_digest = enable;
+        self
+    }
+
+    /// Cache responses in a store.
+    ///
+    /// Fresh responses are then answered from the store, without contacting
+    /// the server, and stale ones are revalidated when they can be. See the
+    /// [`cache`](crate::cache) module for which responses are stored.
+    ///
+    /// By default, no cache is used.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache<S: cache::Store + 'static>(mut self, store: Arc<S>) -> ClientBuilder {
+        self.config.cache.store = Some(store as _);
+        self
+    }
+
+    /// Serve stale cached responses while revalidating them in the background.
+    ///
+    /// When a stored response is stale, but still within the
+    /// `stale-while-revalidate` window of its `Cache-Control` header, as
+    /// defined by [RFC 5861], it is returned right away, and revalidated with
+    /// the server in a background task for the next requests.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    ///
+    /// [RFC 5861]: https://www.rfc-editor.org/rfc/rfc5861.html
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_stale_while_revalidate(mut self, enabled: bool) -> ClientBuilder {
+        self.config.cache.stale_while_revalidate = enabled;
+        self
+    }
+
+    /// Serve stale cached responses when the server fails.
+    ///
+    /// When revalidating a stale response fails, or the server answers with
+    /// a `500`, `502`, `503` or `504` status, the stored response is returned
+    /// instead if it is still within the `stale-if-error` window of the
+    /// `Cache-Control` header of the request or of the response, as defined
+    /// by [RFC 5861].
+    ///
+    /// Default is `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    ///
+    /// [RFC 5861]: https://www.rfc-editor.org/rfc/rfc5861.html
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_stale_if_error(mut self, enabled: bool) -> ClientBuilder {
+        self.config.cache.stale_if_error = enabled;
+        self
+    }
+
+    /// Make conditional requests with the validators of earlier responses.
+    ///
+    /// The `ETag` and `Last-Modified` headers of successful responses to
+    /// `GET` requests are kept in the store, and sent back as
+    /// `If-None-Match` and `If-Modified-Since` with the next request for the
+    /// same URL. `not_modified` chooses whether a `304 Not Modified` answer
+    /// is returned as is, or replaced by the stored response.
+    ///
+    /// Unlike [`cache`](ClientBuilder::cache), every request is sent to the
+    /// server. The store should not be shared with a cache.
+    ///
+    /// By default, no conditional requests are made.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn conditional_requests<S: cache::Store + 'static>(
+        mut self,
+        store: Arc<S>,
+        not_modified: cache::NotModified,
+    ) -> ClientBuilder {
+        self.config.conditional.store = Some(store as _);
+        self.config.conditional.not_modified = not_modified;
+        self
+    }
+
+    /// Sign every request with HTTP Message Signatures.
+    ///
+    /// The [`Signer`] adds `Signature-Input` and `Signature` fields to each
+    /// request, right before it is sent. Retries and redirects are signed
+    /// again.
+    ///
+    /// See the [`signature`](crate::signature) module for more details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `message-signatures` feature to be enabled.
+    #[cfg(feature = "message-signatures")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "message-signatures")))]
+    pub fn message_signer(mut self, signer: Signer) -> ClientBuilder {
+        self.config.message_signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Authorize the requests to `host` with a bearer token from `provider`.
+    ///
+    /// The provider is asked for a token before each request to `host`, and
+    /// to refresh it when `host` answers `401 Unauthorized`, in which case
+    /// the request is sent once more with the new token. The requests to
+    /// other hosts are sent without its tokens. The host is matched exactly,
+    /// without its port, as with [`for_host`](ClientBuilder::for_host).
+    ///
+    /// Setting a provider for a host again replaces the previous one. To
+    /// share a provider between hosts, set it wrapped in an `Arc`.
+    ///
+    /// See the [`auth`](crate::auth) module for more details.
+    pub fn bearer_token_provider<P>(mut self, host: &str, provider: P) -> ClientBuilder
+    where
+        P: TokenProvider + 'static,
+    {
+        let host = host.to_ascii_lowercase();
+        self.config
+            .token_providers
+            .retain(|(name, _)| *name != host);
+        self.config.token_providers.push((host, Arc::new(provider)));
+        self
+    }
+
+    /// Propagate the current W3C trace context with every request.
+    ///
+    /// Before each request is sent, `current` is called for the trace
+    /// context of the current span, which is sent in the `traceparent` and
+    /// `tracestate` headers unless the request already has a `traceparent`.
+    /// The status of the response is then recorded on the `tracing` span
+    /// that was current when the request was sent.
+    ///
+    /// See the [`trace`](crate::trace) module for more.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tracing` feature to be enabled.
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    pub fn propagate_trace_context<F>(mut self, current: F) -> ClientBuilder
+    where
+        F: Fn() -> Option<TraceContext> + Send + Sync + 'static,
+    {
+        self.config.trace_context = Some(Arc::new(current));
+        self
+    }
+
     /// Disable auto response body gzip decompression.
     ///
     /// This method exists even if the optional `gzip` feature is not enabled.
@@ -1378,6 +2365,44 @@ impl ClientBuilder {
         }
     }
 
+    /// Decode compressed response bodies on tokio's blocking thread pool.
+    ///
+    /// Decoding a large body takes a lot of CPU time, during which the thread
+    /// reading it can't serve the other tasks of the runtime. With this set,
+    /// the bodies the client decodes, of at least `min_len` compressed bytes
+    /// or of an unknown length, are read and decoded by a thread of the
+    /// blocking pool instead, which hands the decoded chunks back.
+    ///
+    /// The thread is held until the body is read to the end or dropped, even
+    /// while waiting for the server, so this suits bodies which are large
+    /// rather than slow.
+    ///
+    /// Default is off.
+    ///
+    /// # Optional
+    ///
+    /// This requires at least one of the optional `gzip`, `brotli`, `zstd`
+    /// or `deflate` features to be enabled.
+    #[cfg(any(
+        feature = "gzip",
+        feature = "brotli",
+        feature = "zstd",
+        feature = "deflate"
+    ))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "gzip",
+            feature = "brotli",
+            feature = "zstd",
+            feature = "deflate"
+        )))
+    )]
+    pub fn offload_decompression(mut self, min_len: u64) -> ClientBuilder {
+        self.config.accepts.offload = Some(min_len);
+        self
+    }
+
     // Redirect options
 
     /// Set a `RedirectPolicy` for this client.
@@ -1460,6 +2485,11 @@ impl ClientBuilder {
 
     /// Set a timeout for only the connect phase of a `Client`.
     ///
+    /// When a name resolves to several addresses, the timeout is split
+    /// between them, so that the next address is tried when one doesn't
+    /// answer. See also
+    /// [`avoid_failed_addresses`](ClientBuilder::avoid_failed_addresses).
+    ///
     /// Default is `None`.
     ///
     /// # Note
@@ -1471,6 +2501,19 @@ impl ClientBuilder {
         self
     }
 
+    /// Set a policy to retry opening connections which were refused, or
+    /// which timed out.
+    ///
+    /// This is separate from the [`retry`](ClientBuilder::retry) policy of
+    /// requests: a connection is retried before any request is sent on it.
+    /// See [`retry::Connect`](crate::retry::Connect) for the details.
+    ///
+    /// Default is to attempt each connection once.
+    pub fn connect_retry(mut self, policy: crate::retry::Connect) -> ClientBuilder {
+        self.config.connect_retry = Some(policy);
+        self
+    }
+
     /// Set whether connections should emit verbose logs.
     ///
     /// Enabling this option will emit [log][] messages at the `TRACE` level
@@ -1482,6 +2525,119 @@ impl ClientBuilder {
         self
     }
 
+    /// Set how URLs and headers are shown in logs and errors.
+    ///
+    /// The policy applies to the `Debug` output of requests and responses,
+    /// the messages of errors, and the logs of
+    /// [`connection_verbose`](ClientBuilder::connection_verbose). See the
+    /// [`redact`](crate::redact) module for details.
+    ///
+    /// Default is [`Policy::none()`](redact::Policy::none).
+    pub fn redaction(mut self, policy: redact::Policy) -> ClientBuilder {
+        self.config.redaction = Some(Redaction(Arc::new(policy)));
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn configured_redaction(&self) -> Option<&Redaction> {
+        self.config.redaction.as_ref()
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn configured_counters(&self) -> &Arc<Counters> {
+        &self.config.counters
+    }
+
+    /// A copy of this builder, with new transfer stats.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn duplicate(&self) -> ClientBuilder {
+        let mut config = self.config.clone();
+        config.counters = Arc::new(Counters::default());
+        ClientBuilder {
+            config,
+            error: self
+                .error
+                .as_ref()
+                .map(|err| crate::error::builder(err.to_string())),
+        }
+    }
+
+    /// Set an observer to be notified of connection events.
+    ///
+    /// The observer is told about DNS lookups, TCP connects and TLS
+    /// handshakes, and when connections are established and closed. See the
+    /// [`observe`](crate::observe) module for the events.
+    pub fn connection_events<O>(mut self, observer: O) -> ClientBuilder
+    where
+        O: ConnectionObserver,
+    {
+        self.config.connection_observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Set an observer to be shown the messages sent and received.
+    ///
+    /// The observer is given the head of each request and response, and the
+    /// size of the body data, like the output of `curl --verbose`. Unlike
+    /// [`connection_verbose`](ClientBuilder::connection_verbose), it does not
+    /// need the `log` crate. See [`WireObserver`] for details.
+    pub fn wire_events<O>(mut self, observer: O) -> ClientBuilder
+    where
+        O: WireObserver,
+    {
+        self.config.wire_observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Set an observer to be shown each completed request.
+    ///
+    /// The observer is given the final request, the status and headers of
+    /// its response, the sizes of their bodies, how long it took, and any
+    /// error, for audit logging. See [`AuditObserver`] for details.
+    pub fn audit<O>(mut self, observer: O) -> ClientBuilder
+    where
+        O: AuditObserver,
+    {
+        self.config.audit_observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Set a callback to be shown each request which fails.
+    ///
+    /// The callback is given the [code](crate::ErrorCode) and the error of
+    /// the request, its URL as redacted by the
+    /// [redaction policy](ClientBuilder::redaction), how many times it was
+    /// sent, and how long it took, once sending it fails. It isn't shown
+    /// the requests which fail before being sent, such as for an invalid
+    /// URL, nor the responses with an error status. See [`FailedRequest`]
+    /// for details.
+    ///
+    /// It is called on the task sending the request, so it should return
+    /// quickly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let client = reqwest::Client::builder()
+    ///     .on_error(|failed| {
+    ///         eprintln!(
+    ///             "{} {} failed with {} after {} attempts",
+    ///             failed.method(),
+    ///             failed.url(),
+    ///             failed.code(),
+    ///             failed.attempts(),
+    ///         );
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_error<F>(mut self, callback: F) -> ClientBuilder
+    where
+        F: Fn(&FailedRequest<'_>) + Send + Sync + 'static,
+    {
+        self.config.on_error = Some(Arc::new(callback));
+        self
+    }
+
     // HTTP options
 
     /// Set an optional timeout for idle sockets being kept-alive.
@@ -1505,13 +2661,95 @@ impl ClientBuilder {
         self
     }
 
-    /// Send headers as title case instead of lowercase.
-    pub fn http1_title_case_headers(mut self) -> ClientBuilder {
-        self.config.http1_title_case_headers = true;
-        self
-    }
-
-    /// Set whether HTTP/1 connections will accept obsolete line folding for
+    /// Limit the number of requests in flight in total.
+    ///
+    /// Once `max` requests are in flight, the next ones wait for one of them
+    /// to end. The waiting requests are sent by
+    /// [`queue_priority`](crate::RequestBuilder::queue_priority), so that background
+    /// requests don't hold up the others, and in the order they were sent
+    /// for each priority. A request is in flight until its response body is
+    /// read to the end or dropped.
+    ///
+    /// Each hop of a redirect waits on its own. The time spent waiting
+    /// counts towards the [`timeout`](ClientBuilder::timeout) of the
+    /// request, and can be limited with
+    /// [`in_flight_queue_timeout`](ClientBuilder::in_flight_queue_timeout).
+    ///
+    /// By default, there is no limit.
+    ///
+    /// # Errors
+    ///
+    /// Building the client fails if `max` is `0`.
+    pub fn max_in_flight(mut self, max: usize) -> ClientBuilder {
+        if max == 0 {
+            self.error = Some(crate::error::builder("max_in_flight must be at least 1"));
+        }
+        self.config.max_in_flight = Some(max);
+        self
+    }
+
+    /// Limit the number of requests in flight to each host.
+    ///
+    /// Once `max` requests to a host are in flight, the next ones wait for
+    /// one of them to end, in the order they were sent. A request is in
+    /// flight until its response body is read to the end or dropped, so
+    /// responses kept unread hold up the requests waiting behind them.
+    ///
+    /// Hosts are told apart by scheme, host and port, and each hop of a
+    /// redirect waits for its own host. The time spent waiting counts
+    /// towards the [`timeout`](ClientBuilder::timeout) of the request, and
+    /// can be limited with
+    /// [`in_flight_queue_timeout`](ClientBuilder::in_flight_queue_timeout).
+    ///
+    /// By default, there is no limit.
+    ///
+    /// # Errors
+    ///
+    /// Building the client fails if `max` is `0`.
+    pub fn max_in_flight_per_host(mut self, max: usize) -> ClientBuilder {
+        if max == 0 {
+            self.error = Some(crate::error::builder(
+                "max_in_flight_per_host must be at least 1",
+            ));
+        }
+        self.config.max_in_flight_per_host = Some(max);
+        self
+    }
+
+    /// Set how long a request waits for the number of requests in flight
+    /// to drop under [`max_in_flight`](ClientBuilder::max_in_flight) and
+    /// [`max_in_flight_per_host`](ClientBuilder::max_in_flight_per_host).
+    ///
+    /// A request which waits longer fails with an error for which
+    /// [`Error::is_timeout`](crate::Error::is_timeout) is `true`.
+    ///
+    /// Default is no timeout.
+    pub fn in_flight_queue_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.in_flight_queue_timeout = Some(timeout);
+        self
+    }
+
+    /// Stop sending requests to the hosts which keep failing.
+    ///
+    /// Once too many requests to a host fail, the next ones fail at once
+    /// for a while, with an error for which
+    /// [`Error::is_circuit_open`](crate::Error::is_circuit_open) is `true`,
+    /// until probe requests find the host working again. See the
+    /// [`circuit`](crate::circuit) module for how failures are counted.
+    ///
+    /// By default, there is no circuit breaker.
+    pub fn circuit_breaker(mut self, breaker: Breaker) -> ClientBuilder {
+        self.config.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Send headers as title case instead of lowercase.
+    pub fn http1_title_case_headers(mut self) -> ClientBuilder {
+        self.config.http1_title_case_headers = true;
+        self
+    }
+
+    /// Set whether HTTP/1 connections will accept obsolete line folding for
     /// header values.
     ///
     /// Newline codepoints (`\r` and `\n`) will be transformed to spaces when
@@ -1545,6 +2783,48 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the maximum number of headers of HTTP/1 responses.
+    ///
+    /// A response with more headers fails to parse. Setting this allocates
+    /// the headers of each response on the heap, which is a bit slower.
+    ///
+    /// Default is 100.
+    pub fn http1_max_headers(mut self, max: usize) -> ClientBuilder {
+        self.config.http1_max_headers = Some(max);
+        self
+    }
+
+    /// Set the maximum size of the read buffer of HTTP/1 connections.
+    ///
+    /// The head of a response, its status line and headers, must fit in
+    /// the buffer, so this is the limit on its size. The minimum is 8192
+    /// bytes: building the `Client` fails with a smaller `max`.
+    ///
+    /// Default is about 400 KiB.
+    pub fn http1_max_buf_size(mut self, max: usize) -> ClientBuilder {
+        if max < 8192 {
+            self.error = Some(crate::error::builder(
+                "http1_max_buf_size must be at least 8192",
+            ));
+        }
+        self.config.http1_max_buf_size = Some(max);
+        self
+    }
+
+    /// Set whether HTTP/1 connections write the head and the chunks of the
+    /// body of requests with vectored writes.
+    ///
+    /// With vectored writes, the chunks of a body are handed to the socket as
+    /// they are, next to the head, in a single write. Otherwise they are
+    /// copied into the write buffer of the connection first, which can be
+    /// faster for small bodies.
+    ///
+    /// Default is to use vectored writes if the connection supports them.
+    pub fn http1_writev(mut self, enabled: bool) -> ClientBuilder {
+        self.config.http1_writev = Some(enabled);
+        self
+    }
+
     /// Only use HTTP/1.
     pub fn http1_only(mut self) -> ClientBuilder {
         self.config.http_version_pref = HttpVersionPref::Http1;
@@ -1747,6 +3027,45 @@ impl ClientBuilder {
         self
     }
 
+    cfg_interface_index! {
+        /// Bind connections only on the network interface with the specified
+        /// index.
+        ///
+        /// Unlike [`ClientBuilder::interface`], which takes the name of the
+        /// interface, this keeps working when the interface is renamed, and is
+        /// supported on Windows. If both are set, the index is used.
+        ///
+        /// The name of the interface is looked up each time a connection is
+        /// established, which fails if no network interface has the index, and
+        /// the connection is bound as with [`ClientBuilder::interface`]. On
+        /// Windows, this uses the `IP_UNICAST_IF` and `IPV6_UNICAST_IF` socket
+        /// options, and the addresses of a host are tried one after the other,
+        /// sharing the connect timeout.
+        ///
+        /// A request can be bound to another interface with
+        /// [`RequestBuilder::interface_index`].
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// # fn doc() -> Result<(), reqwest::Error> {
+        /// let client = reqwest::Client::builder()
+        ///     .interface_index(1)
+        ///     .build()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index(mut self, index: u32) -> ClientBuilder {
+            self.config.interface_index = Some(index);
+            self
+        }
+    }
+
     /// Set that all sockets have `SO_KEEPALIVE` set with the supplied duration.
     ///
     /// If `None`, the option will not be set.
@@ -1795,6 +3114,22 @@ impl ClientBuilder {
         self
     }
 
+    /// Send a PROXY protocol header at the start of each connection.
+    ///
+    /// For servers expecting their connections from a load balancer speaking
+    /// the HAProxy PROXY protocol. The header is only sent on direct TCP
+    /// connections, before any TLS handshake. See the
+    /// [`proxy_protocol`](crate::proxy_protocol) module for details.
+    ///
+    /// Default is `None`.
+    pub fn proxy_protocol<P>(mut self, header: P) -> ClientBuilder
+    where
+        P: Into<Option<ProxyProtocol>>,
+    {
+        self.config.proxy_protocol = header.into();
+        self
+    }
+
     // Alt Transports
 
     /// Set that all connections will use this Unix socket.
@@ -2247,6 +3582,91 @@ impl ClientBuilder {
         self
     }
 
+    /// Upgrade the `http://` URLs of the domains in an HSTS preload list to
+    /// `https://`, before sending anything.
+    ///
+    /// This applies to the URLs of the requests, and to the locations of
+    /// the redirects they follow, which `https_only` then accepts. See the
+    /// [`hsts`](crate::hsts) module for where to get the list browsers use.
+    ///
+    /// By default, URLs are never upgraded.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hsts-preload` feature to be enabled.
+    #[cfg(feature = "hsts-preload")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hsts-preload")))]
+    pub fn hsts_preload(mut self, preload: crate::hsts::Preload) -> ClientBuilder {
+        self.config.hsts_preload = Some(Arc::new(preload));
+        self
+    }
+
+    /// Answer the requests for `file://` URLs with local files.
+    ///
+    /// A `GET` request gets a `200 OK` response with the body of the file,
+    /// its `Content-Length`, and a `Content-Type` guessed from its
+    /// extension. A missing file is a `404 Not Found`, a file that can't be
+    /// read a `403 Forbidden`, and a method other than `GET` or `HEAD` a
+    /// `405 Method Not Allowed`.
+    ///
+    /// These requests never reach the network, so no layers, cookies or
+    /// cache apply to them, and a redirect never leads to a local file.
+    /// Clients that take URLs from untrusted sources shouldn't enable this.
+    ///
+    /// Defaults to false. With `https_only`, `file` URLs are refused.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `file` feature to be enabled.
+    #[cfg(feature = "file")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file")))]
+    pub fn file_urls(mut self, enabled: bool) -> ClientBuilder {
+        self.config.file_urls = enabled;
+        self
+    }
+
+    /// Check the `Content-Type` of responses before decoding their bodies.
+    ///
+    /// With this enabled, [`Response::json`] refuses a response that isn't
+    /// `application/json` or a `+json` type, such as an HTML error page, and
+    /// the other typed decoders, like `Response::xml`, refuse responses
+    /// without a `Content-Type`. The error is a decode error for which
+    /// [`Error::is_content_type`](crate::Error::is_content_type) is true, and
+    /// [`Error::content_type`](crate::Error::content_type) returns the
+    /// `Content-Type` of the response.
+    ///
+    /// Defaults to false.
+    pub fn strict_content_type(mut self, enabled: bool) -> ClientBuilder {
+        self.config.strict_content_type = enabled;
+        self
+    }
+
+    /// Allow international domain names in URLs, converted to their ASCII
+    /// form.
+    ///
+    /// URLs are parsed following the WHATWG URL standard, which converts a
+    /// host with non-ASCII characters, such as `bücher.example`, to its
+    /// punycode form, `xn--bcher-kva.example`, and decodes a percent-encoded
+    /// host before doing so. Disabling this doesn't send such hosts as they
+    /// were written: it rejects them, so that building a request for a URL
+    /// whose host would be rewritten fails.
+    ///
+    /// This applies to the URLs passed as strings to [`Client::request`] and
+    /// the like, including relative ones resolved against the
+    /// [`base_url`](ClientBuilder::base_url) with a host of their own, such
+    /// as `//bücher.example/`; a [`Url`] has already been parsed.
+    ///
+    /// Defaults to true.
+    pub fn idna(mut self, enabled: bool) -> ClientBuilder {
+        self.config.idna = enabled;
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn configured_idna(&self) -> bool {
+        self.config.idna
+    }
+
     /// Enables the [hickory-dns](hickory_resolver) async resolver instead of a default threadpool
     /// using `getaddrinfo`.
     ///
@@ -2315,6 +3735,31 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the order in which to try the addresses a name resolves to.
+    ///
+    /// By default, they are tried in the order the resolver returned them.
+    /// See [`dns::Balance`](crate::dns::Balance) for the other strategies.
+    pub fn dns_balance(mut self, balance: Balance) -> ClientBuilder {
+        self.config.dns_balance = balance;
+        self
+    }
+
+    /// Try the addresses which recently failed to accept a connection after
+    /// the other addresses of their name.
+    ///
+    /// When connecting to an address fails, the next addresses the name
+    /// resolved to are tried, sharing the
+    /// [`connect_timeout`](ClientBuilder::connect_timeout). With this, an
+    /// address which failed is tried last for `duration`, rather than on
+    /// every new connection.
+    ///
+    /// By default, addresses are tried in the order of the
+    /// [`dns_balance`](ClientBuilder::dns_balance) strategy.
+    pub fn avoid_failed_addresses(mut self, duration: Duration) -> ClientBuilder {
+        self.config.avoid_failed_addresses = Some(duration);
+        self
+    }
+
     /// Whether to send data on the first flight ("early data") in TLS 1.3 handshakes
     /// for HTTP/3 connections.
     ///
@@ -2468,10 +3913,115 @@ impl ClientBuilder {
 
         self
     }
+
+    /// Replaces the internal HTTP client with a custom Tower
+    /// [`Service`](https://docs.rs/tower/latest/tower/trait.Service.html).
+    ///
+    /// Requests are still built, and responses handled, by the `Client` as
+    /// usual: default headers, cookies, redirects, retries, timeouts and
+    /// decompression all apply. Only the final exchange of a request for a
+    /// response is handed to the service, which can be used for custom or
+    /// in-process transports.
+    ///
+    /// The request URI is always in absolute form. Options that configure
+    /// connections, such as proxies, TLS and DNS, are not used.
+    ///
+    /// If the service returns a `reqwest::Error`, it is passed through as-is.
+    /// Other errors are wrapped as request errors.
+    ///
+    /// Example usage:
+    /// ```
+    /// use reqwest::Body;
+    ///
+    /// let service = tower::service_fn(|req: http::Request<Body>| async move {
+    ///     let res = http::Response::new(Body::from(format!("you asked for {}", req.uri())));
+    ///     Ok::<_, std::convert::Infallible>(res)
+    /// });
+    ///
+    /// # #[cfg(not(feature = "rustls-no-provider"))]
+    /// let client = reqwest::Client::builder()
+    ///     .http_service(service)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn http_service<S>(mut self, service: S) -> ClientBuilder
+    where
+        S: Service<http::Request<Body>, Response = http::Response<Body>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        use tower::ServiceExt;
+
+        let service = service.map_err(Into::into);
+        self.config.http_service = Some(BoxCloneSyncService::new(service));
+        self
+    }
+
+    /// Send the requests for URLs of a custom scheme to a service.
+    ///
+    /// The requests go through the client like any other, so default
+    /// headers, cookies, timeouts and layers apply to them, and the service
+    /// answers them in place of a connection. This lets an application route
+    /// its internal resources, such as `app://assets/index.html`, through the
+    /// same code as remote ones. The URLs must have a host.
+    ///
+    /// As with [`http_service`](ClientBuilder::http_service), a
+    /// `reqwest::Error` returned by the service is passed through as-is, and
+    /// other errors are wrapped as request errors. A synchronous handler can
+    /// return a ready future:
+    ///
+    /// ```
+    /// use reqwest::Body;
+    ///
+    /// let assets = tower::service_fn(|req: http::Request<Body>| {
+    ///     let body = format!("asset {}", req.uri().path());
+    ///     std::future::ready(Ok::<_, std::convert::Infallible>(http::Response::new(Body::from(body))))
+    /// });
+    ///
+    /// # #[cfg(not(feature = "rustls-no-provider"))]
+    /// let client = reqwest::Client::builder()
+    ///     .scheme_handler("app", assets)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Redirects never lead to a custom scheme, and `http` and `https` can't
+    /// be handled this way: building the client fails.
+    pub fn scheme_handler<S>(mut self, scheme: &str, service: S) -> ClientBuilder
+    where
+        S: Service<http::Request<Body>, Response = http::Response<Body>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        use tower::ServiceExt;
+
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme == "http" || scheme == "https" {
+            self.error = Some(crate::error::builder(format!(
+                "cannot replace the {scheme} scheme with a handler"
+            )));
+            return self;
+        }
+        let service = service.map_err(Into::into);
+        self.config
+            .scheme_handlers
+            .insert(scheme, BoxCloneSyncService::new(service));
+        self
+    }
 }
 
 type HyperClient = hyper_util::client::legacy::Client<Connector, super::Body>;
 
+type BoxedHttpService = BoxCloneSyncService<http::Request<Body>, http::Response<Body>, BoxError>;
+
 impl Default for Client {
     fn default() -> Self {
         Self::new()
@@ -2514,6 +4064,47 @@ impl Client {
         ClientBuilder::new()
     }
 
+    /// Creates a `ClientBuilder` with the configuration of this `Client`.
+    ///
+    /// This allows building variants of a shared base client:
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// use std::time::Duration;
+    ///
+    /// let base = reqwest::Client::builder()
+    ///     .user_agent("my-app/1.0")
+    ///     .build()?;
+    /// let slow = base.to_builder()
+    ///     .timeout(Duration::from_secs(300))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The clients built from it have their own connection pool and
+    /// transfer stats, but share what was given to the builder of this
+    /// client, such as its cookie store, observers, DNS resolver and
+    /// connector layers.
+    pub fn to_builder(&self) -> ClientBuilder {
+        let mut config = self.inner.builder_config.clone();
+        config.counters = Arc::new(Counters::default());
+        #[cfg(feature = "__tls")]
+        if let Some(ref tls) = self.inner.tls_reload {
+            // keep the certificates of the last `reload_tls()`
+            let settings = tls.settings.lock().expect("lock poisoned");
+            config.root_certs = settings.root_certs.clone();
+            #[cfg(any(feature = "__native-tls", feature = "__rustls"))]
+            {
+                config.identity = settings.identity.clone();
+            }
+        }
+        ClientBuilder {
+            config,
+            error: None,
+        }
+    }
+
     /// Convenience method to make a `GET` request to a URL.
     ///
     /// # Errors
@@ -2568,6 +4159,15 @@ impl Client {
         self.request(Method::HEAD, url)
     }
 
+    /// Convenience method to make an `OPTIONS` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn options<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::OPTIONS, url)
+    }
+
     /// Start building a `Request` with the `Method` and `Url`.
     ///
     /// Returns a `RequestBuilder`, which will allow setting headers and
@@ -2577,7 +4177,8 @@ impl Client {
     ///
     /// This method fails whenever the supplied `Url` cannot be parsed.
     pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
-        let req = url.into_url().map(move |url| Request::new(method, url));
+        let req = crate::into_url::request_url(url, self.inner.base_url.as_ref(), self.inner.idna)
+            .map(move |url| Request::new(method, url));
         RequestBuilder::new(self.clone(), req)
     }
 
@@ -2600,15 +4201,266 @@ impl Client {
         self.execute_request(request)
     }
 
+    /// Waits for the client to have room for one more request in flight,
+    /// and reserves it.
+    ///
+    /// This is for producers feeding requests to the client from a queue of
+    /// work: waiting here before building each request applies backpressure
+    /// to the producer, rather than buffering a future for every request
+    /// waiting to be sent. Pass the permit to
+    /// [`RequestBuilder::in_flight_permit`] for the request to use the room
+    /// reserved for it.
+    ///
+    /// Only the [`max_in_flight`](ClientBuilder::max_in_flight) limit is
+    /// waited for, as the host of the request isn't known yet, and the
+    /// connection pool has no limit of its own. Without that limit, this
+    /// returns at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run(urls: Vec<String>) -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder().max_in_flight(8).build()?;
+    /// for url in urls {
+    ///     let permit = client.ready().await?;
+    ///     let req = client.get(url).in_flight_permit(permit);
+    ///     tokio::spawn(req.send());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the wait is longer than the
+    /// [`in_flight_queue_timeout`](ClientBuilder::in_flight_queue_timeout).
+    pub async fn ready(&self) -> crate::Result<InFlightPermit> {
+        match self.inner.in_flight {
+            Some(ref limits) => limits.ready().await,
+            None => Ok(InFlightPermit::unlimited()),
+        }
+    }
+
+    /// Reserves room for one more request in flight, if the client has
+    /// some.
+    ///
+    /// Returns `None` if [`max_in_flight`](ClientBuilder::max_in_flight)
+    /// requests are in flight or waiting to be sent. See [`Client::ready`].
+    pub fn try_acquire(&self) -> Option<InFlightPermit> {
+        match self.inner.in_flight {
+            Some(ref limits) => limits.try_acquire(),
+            None => Some(InFlightPermit::unlimited()),
+        }
+    }
+
+    /// Start building a CORS preflight request, asking whether a request with
+    /// the `method` from the `origin` is allowed.
+    ///
+    /// See the [`cors`](crate::cors) module for details.
+    ///
+    /// # Errors
+    ///
+    /// Sending the request fails whenever the supplied `Url` cannot be
+    /// parsed.
+    pub fn preflight<U: IntoUrl>(
+        &self,
+        url: U,
+        origin: &str,
+        method: Method,
+    ) -> crate::cors::PreflightBuilder {
+        crate::cors::PreflightBuilder::new(self.options(url), origin, method)
+    }
+
+    /// Send a request, and then a request for each next page of the response.
+    ///
+    /// See the [`paginate`](crate::paginate) module for details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn paginate(&self, request: Request) -> crate::paginate::Pages<Response> {
+        crate::paginate::Pages::new(self.clone(), request)
+    }
+
+    /// Start building a download of a URL to a file.
+    ///
+    /// See the [`download`](crate::download) module for details.
+    ///
+    /// # Errors
+    ///
+    /// Sending the download fails whenever the supplied `Url` cannot be
+    /// parsed.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `download` feature to be enabled.
+    #[cfg(feature = "download")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "download")))]
+    pub fn download<U: IntoUrl>(&self, url: U) -> crate::download::DownloadBuilder {
+        crate::download::DownloadBuilder::new(
+            self.clone(),
+            url.into_url_with_base(self.inner.base_url.as_ref()),
+        )
+    }
+
+    /// Start building an upload of a body in chunks.
+    ///
+    /// See the [`upload`](crate::upload) module for details.
+    ///
+    /// # Errors
+    ///
+    /// Sending the upload fails whenever the supplied `Url` cannot be
+    /// parsed.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `upload` feature to be enabled.
+    #[cfg(feature = "upload")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "upload")))]
+    pub fn upload<U: IntoUrl>(&self, url: U) -> crate::upload::UploadBuilder {
+        crate::upload::UploadBuilder::new(
+            self.clone(),
+            url.into_url_with_base(self.inner.base_url.as_ref()),
+        )
+    }
+
+    /// Returns what this `Client` has transferred so far.
+    ///
+    /// See [`TransferStats`] for what is counted.
+    pub fn transfer_stats(&self) -> TransferStats {
+        self.inner.counters.stats()
+    }
+
+    /// Replace the root certificates or the identity of this `Client`.
+    ///
+    /// The connections made from then on use a TLS configuration built from
+    /// the settings of the `ClientBuilder`, with the certificates of
+    /// `reload`. The connections already open keep the configuration they
+    /// were made with, and stay in the pool, unless `reload` drains them.
+    ///
+    /// ```rust,no_run
+    /// # fn run(client: &reqwest::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use reqwest::tls::{Identity, TlsReload};
+    ///
+    /// let identity = Identity::from_pem(&std::fs::read("renewed.pem")?)?;
+    /// client.reload_tls(TlsReload::new().identity(identity).drain(true))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Only the TLS configuration of HTTP/1 and HTTP/2 connections is
+    /// replaced, not the one of HTTP/3.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the new configuration can't be built, in which case the
+    /// client keeps the current one, or if the client was built with
+    /// `tls_backend_preconfigured()` or `http_service()`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "default-tls", feature = "native-tls", feature = "rustls")))
+    )]
+    pub fn reload_tls(&self, reload: tls::TlsReload) -> crate::Result<()> {
+        match self.inner.tls_reload {
+            Some(ref tls) => tls.reload(reload),
+            None => Err(crate::error::builder(
+                "the TLS configuration of this client can't be reloaded",
+            )),
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn counters(&self) -> &Arc<Counters> {
+        &self.inner.counters
+    }
+
+    pub(crate) fn redaction(&self) -> Option<&Redaction> {
+        self.inner.redaction.as_ref()
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn base_url(&self) -> Option<&Url> {
+        self.inner.base_url.as_ref()
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn idna(&self) -> bool {
+        self.inner.idna
+    }
+
     pub(super) fn execute_request(&self, req: Request) -> Pending {
-        let (method, url, mut headers, body, version, extensions) = req.pieces();
-        if url.scheme() != "http" && url.scheme() != "https" {
-            return Pending::new_err(error::url_bad_scheme(url));
+        let (method, mut url, mut headers, body, mut version, extensions) = req.pieces();
+        #[cfg(feature = "file")]
+        if url.scheme() == "file" && self.inner.file_urls && !self.inner.https_only {
+            let fetch = super::file::fetch(
+                method,
+                url,
+                self.inner.redaction.clone(),
+                self.inner.strict_content_type,
+            );
+            return Pending {
+                inner: PendingInner::File(Box::pin(sync_wrapper::SyncFuture::new(fetch))),
+            };
+        }
+        let handled = self.inner.schemes.iter().any(|s| s == url.scheme());
+        if url.scheme() != "http" && url.scheme() != "https" && !handled {
+            return Pending::new_err(error::url_bad_scheme(url).with_redaction(self.redaction()));
+        }
+
+        #[cfg(feature = "hsts-preload")]
+        if let Some(ref preload) = self.inner.hsts_preload {
+            preload.upgrade(&mut url);
         }
 
         // check if we're in https_only mode and check the scheme of the current URL
         if self.inner.https_only && url.scheme() != "https" {
-            return Pending::new_err(error::url_bad_scheme(url));
+            return Pending::new_err(error::url_bad_scheme(url).with_redaction(self.redaction()));
+        }
+
+        // append the default query parameters the URL doesn't have
+        if !self.inner.query.is_empty() {
+            let present = url
+                .query_pairs()
+                .map(|(key, _)| key.into_owned())
+                .collect::<Vec<_>>();
+            let missing = self
+                .inner
+                .query
+                .iter()
+                .filter(|(key, _)| !present.contains(key))
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                url.query_pairs_mut().extend_pairs(missing);
+            }
+        }
+
+        let host = url.host_str().and_then(|host| {
+            self.inner
+                .hosts
+                .iter()
+                .find(|(name, _)| name == host)
+                .map(|(_, config)| config)
+        });
+
+        if let Some(host) = host {
+            for (key, value) in &host.headers {
+                if let Entry::Vacant(entry) = headers.entry(key) {
+                    entry.insert(value.clone());
+                }
+            }
+            if let Some(host_version) = host.version {
+                if version == http::Version::default() {
+                    version = host_version;
+                }
+            }
         }
 
         // insert default headers in the request headers
@@ -2621,7 +4473,11 @@ impl Client {
 
         let uri = match try_uri(&url) {
             Ok(uri) => uri,
-            _ => return Pending::new_err(error::url_invalid_uri(url)),
+            _ => {
+                return Pending::new_err(
+                    error::url_invalid_uri(url).with_redaction(self.redaction()),
+                )
+            }
         };
 
         let body = body.unwrap_or_else(Body::empty);
@@ -2629,38 +4485,114 @@ impl Client {
         self.proxy_auth(&uri, &mut headers);
         self.proxy_custom_headers(&uri, &mut headers);
 
+        #[cfg(feature = "tracing")]
+        let span = self.inner.trace_context.as_ref().map(|current| {
+            let span = crate::trace::caller_span(&extensions);
+            span.in_scope(|| crate::trace::inject(current, &mut headers));
+            span
+        });
+        #[cfg(not(feature = "tracing"))]
+        let span = ();
+
         let builder = hyper::Request::builder()
             .method(method.clone())
             .uri(uri)
             .version(version);
 
-        let in_flight = match version {
+        let start = Instant::now();
+        let audited = self.inner.audit_observer.as_ref().map(|observer| {
+            Audited::new(
+                observer.clone(),
+                method.clone(),
+                headers.clone(),
+                http_body::Body::size_hint(&body).exact(),
+                start,
+                self.inner.redaction.clone(),
+            )
+        });
+        let connection = ConnectionSlot::default();
+        let timed_out = TimedOutSlot::default();
+        let (in_flight, informational) = match version {
             #[cfg(feature = "http3")]
             http::Version::HTTP_3 if self.inner.h3_client.is_some() => {
                 let mut req = builder.body(body).expect("valid request parts");
                 *req.headers_mut() = headers.clone();
+                #[cfg(feature = "cache")]
+                if let Some(mode) = RequestConfig::<CacheMode>::get(&extensions) {
+                    req.extensions_mut().insert(*mode);
+                }
+                if let Some(priority) = RequestConfig::<RequestPriority>::get(&extensions) {
+                    req.extensions_mut().insert(*priority);
+                }
+                if let Some(reserved) = RequestConfig::<InFlightReserved>::get(&extensions) {
+                    req.extensions_mut().insert(reserved.clone());
+                }
+                if let Some(target) = RequestConfig::<RawTarget>::get(&extensions) {
+                    req.extensions_mut()
+                        .insert(RawTargetExtension(target.clone()));
+                }
+                req.extensions_mut().insert(timed_out.clone());
                 let mut h3 = self.inner.h3_client.as_ref().unwrap().clone();
-                ResponseFuture::H3(h3.call(req))
+                (ResponseFuture::H3(h3.call(req)), None)
             }
             _ => {
                 let mut req = builder.body(body).expect("valid request parts");
                 *req.headers_mut() = headers.clone();
+                #[cfg(feature = "cache")]
+                if let Some(mode) = RequestConfig::<CacheMode>::get(&extensions) {
+                    req.extensions_mut().insert(*mode);
+                }
+                if let Some(priority) = RequestConfig::<RequestPriority>::get(&extensions) {
+                    req.extensions_mut().insert(*priority);
+                }
+                if let Some(reserved) = RequestConfig::<InFlightReserved>::get(&extensions) {
+                    req.extensions_mut().insert(reserved.clone());
+                }
+                if let Some(target) = RequestConfig::<RawTarget>::get(&extensions) {
+                    req.extensions_mut()
+                        .insert(RawTargetExtension(target.clone()));
+                }
+                req.extensions_mut().insert(timed_out.clone());
+                if let Some(index) = RequestConfig::<InterfaceIndex>::get(&extensions) {
+                    req.extensions_mut().insert(InterfaceIndexExtension(*index));
+                }
+                req.extensions_mut().insert(connection.clone());
+                let informational = crate::informational::Received::register(
+                    &mut req,
+                    RequestConfig::<OnInformational>::get(&extensions).cloned(),
+                    RequestConfig::<EarlyHints>::get(&extensions).cloned(),
+                );
                 let mut hyper = self.inner.hyper.clone();
-                ResponseFuture::Default(hyper.call(req))
+                (
+                    ResponseFuture::Default(hyper.call(req)),
+                    Some(informational),
+                )
             }
         };
 
-        let total_timeout = self
-            .inner
-            .total_timeout
-            .fetch(&extensions)
+        let deadline = RequestConfig::<Deadline>::get(&extensions)
             .copied()
-            .map(tokio::time::sleep)
+            .into_iter()
+            .chain(crate::deadline::current())
+            .chain(
+                RequestConfig::<TotalTimeout>::get(&extensions)
+                    .or(host.and_then(|host| host.timeout.as_ref()))
+                    .or(self.inner.total_timeout.fetch(&extensions))
+                    .and_then(|timeout| start.checked_add(*timeout)),
+            )
+            .min();
+        let total_timeout = deadline
+            .map(|deadline| tokio::time::sleep_until(deadline.into()))
             .map(Box::pin);
 
-        let read_timeout_fut = self
-            .inner
-            .read_timeout
+        let read_timeout = RequestConfig::<ReadTimeout>::get(&extensions)
+            .or(host.and_then(|host| host.read_timeout.as_ref()))
+            .or(self.inner.read_timeout.fetch(&extensions))
+            .copied();
+        let read_timeout_fut = read_timeout.map(tokio::time::sleep).map(Box::pin);
+
+        let header_timeout = RequestConfig::<HeaderTimeout>::get(&extensions)
+            .copied()
             .map(tokio::time::sleep)
             .map(Box::pin);
 
@@ -2672,10 +4604,17 @@ impl Client {
 
                 client: self.inner.clone(),
 
+                start,
+                span,
+                audited,
+                informational,
+                connection,
+                timed_out,
                 in_flight,
                 total_timeout,
                 read_timeout_fut,
-                read_timeout: self.inner.read_timeout,
+                read_timeout,
+                header_timeout,
             })),
         }
     }
@@ -2732,16 +4671,34 @@ impl fmt::Debug for Client {
     }
 }
 
+/// A `Client` is a [`tower_service::Service`] with backpressure: with a
+/// [`max_in_flight`](ClientBuilder::max_in_flight) limit, `poll_ready` waits
+/// for room among the requests in flight, as [`Client::ready`] does, and
+/// the next `call` sends its request in that room. It fails if the wait is
+/// longer than the
+/// [`in_flight_queue_timeout`](ClientBuilder::in_flight_queue_timeout).
+/// Without a limit, the client is always ready.
+///
+/// A clone of the client is a service of its own, which has to be made
+/// ready before it is called. A `&Client` is always ready, and its requests
+/// wait for room once they are sent.
 impl tower_service::Service<Request> for Client {
     type Response = Response;
     type Error = crate::Error;
     type Future = Pending;
 
-    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Poll::Ready(Ok(()))
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.ready.poll_ready(self.inner.in_flight.as_deref(), cx)
     }
 
-    fn call(&mut self, req: Request) -> Self::Future {
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        if let Some(permit) = self.ready.take() {
+            let reserved = RequestConfig::<InFlightReserved>::get_mut(req.extensions_mut());
+            // a permit of the request itself comes first
+            if reserved.is_none() {
+                *reserved = Some(Reserved::new(permit));
+            }
+        }
         self.execute_request(req)
     }
 }
@@ -2760,6 +4717,44 @@ impl tower_service::Service<Request> for &'_ Client {
     }
 }
 
+/// A `Client` can also be used directly as a [`tower_service::Service`] of
+/// `http` types, so it fits into middleware stacks built for them.
+///
+/// Its readiness is the same as for [`Request`]s: with a
+/// [`max_in_flight`](ClientBuilder::max_in_flight) limit, `poll_ready`
+/// reserves room for the request of the next `call`.
+impl tower_service::Service<http::Request<Body>> for Client {
+    type Response = http::Response<Body>;
+    type Error = crate::Error;
+    type Future = HttpPending;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        tower_service::Service::<Request>::poll_ready(self, cx)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let inner = match Request::try_from(req) {
+            Ok(req) => tower_service::Service::<Request>::call(self, req),
+            Err(err) => Pending::new_err(err),
+        };
+        HttpPending { inner }
+    }
+}
+
+impl tower_service::Service<http::Request<Body>> for &'_ Client {
+    type Response = http::Response<Body>;
+    type Error = crate::Error;
+    type Future = HttpPending;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        tower_service::Service::<Request>::poll_ready(self, cx)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        HttpPending::new(self, req)
+    }
+}
+
 impl fmt::Debug for ClientBuilder {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut builder = f.debug_struct("ClientBuilder");
@@ -2782,6 +4777,10 @@ impl Config {
 
         f.field("accepts", &self.accepts);
 
+        if let Some(ref url) = self.base_url {
+            f.field("base_url", &url.as_str());
+        }
+
         if !self.proxies.is_empty() {
             f.field("proxies", &self.proxies);
         }
@@ -2794,7 +4793,86 @@ impl Config {
             f.field("referer", &true);
         }
 
-        f.field("default_headers", &self.headers);
+        if !self.scheme_handlers.is_empty() {
+            f.field("scheme_handlers", &self.scheme_handlers.keys());
+        }
+
+        if self.http_service.is_some() {
+            f.field("http_service", &true);
+        }
+
+        #[cfg(feature = "cache")]
+        {
+            if self.cache.store.is_some() {
+                f.field("cache", &true);
+            }
+            if self.cache.stale_while_revalidate {
+                f.field("cache_stale_while_revalidate", &true);
+            }
+            if self.cache.stale_if_error {
+                f.field("cache_stale_if_error", &true);
+            }
+            if self.conditional.store.is_some() {
+                f.field("conditional_requests", &self.conditional.not_modified);
+            }
+        }
+
+        #[cfg(feature = "content-digest")]
+        if self.content_digest {
+            f.field("content_digest", &true);
+        }
+
+        #[cfg(feature = "message-signatures")]
+        if let Some(ref signer) = self.message_signer {
+            f.field("message_signer", signer);
+        }
+
+        if !self.token_providers.is_empty() {
+            let hosts = self.token_providers.iter().map(|(host, _)| host);
+            f.field("bearer_token_hosts", &hosts.collect::<Vec<_>>());
+        }
+
+        #[cfg(feature = "tracing")]
+        if self.trace_context.is_some() {
+            f.field("trace_context", &true);
+        }
+
+        #[cfg(feature = "hsts-preload")]
+        if let Some(ref preload) = self.hsts_preload {
+            f.field("hsts_preload", preload);
+        }
+
+        #[cfg(feature = "file")]
+        if self.file_urls {
+            f.field("file_urls", &true);
+        }
+
+        if self.strict_content_type {
+            f.field("strict_content_type", &true);
+        }
+
+        if !self.idna {
+            f.field("idna", &false);
+        }
+
+        if let Some(Redaction(ref policy)) = self.redaction {
+            f.field("redaction", policy);
+        }
+
+        f.field(
+            "default_headers",
+            &redact::headers(self.redaction.as_ref(), &self.headers),
+        );
+
+        if !self.query.is_empty() {
+            // only the keys, the values are often API keys
+            let keys = self.query.iter().map(|(key, _)| key).collect::<Vec<_>>();
+            f.field("default_query", &keys);
+        }
+
+        if !self.hosts.is_empty() {
+            f.field("hosts", &self.hosts);
+        }
 
         if self.http1_title_case_headers {
             f.field("http1_title_case_headers", &true);
@@ -2812,6 +4890,18 @@ impl Config {
             f.field("http1_allow_spaces_after_header_name_in_responses", &true);
         }
 
+        if let Some(max) = self.http1_max_headers {
+            f.field("http1_max_headers", &max);
+        }
+
+        if let Some(max) = self.http1_max_buf_size {
+            f.field("http1_max_buf_size", &max);
+        }
+
+        if let Some(enabled) = self.http1_writev {
+            f.field("http1_writev", &enabled);
+        }
+
         if matches!(self.http_version_pref, HttpVersionPref::Http1) {
             f.field("http1_only", &true);
         }
@@ -2825,10 +4915,46 @@ impl Config {
             f.field("connect_timeout", d);
         }
 
+        if let Some(ref policy) = self.connect_retry {
+            f.field("connect_retry", policy);
+        }
+
+        if self.connection_observer.is_some() {
+            f.field("connection_events", &true);
+        }
+
+        if self.wire_observer.is_some() {
+            f.field("wire_events", &true);
+        }
+
+        if self.audit_observer.is_some() {
+            f.field("audit", &true);
+        }
+
+        if self.on_error.is_some() {
+            f.field("on_error", &true);
+        }
+
         if let Some(ref d) = self.timeout {
             f.field("timeout", d);
         }
 
+        if let Some(ref max) = self.max_in_flight {
+            f.field("max_in_flight", max);
+        }
+
+        if let Some(ref max) = self.max_in_flight_per_host {
+            f.field("max_in_flight_per_host", max);
+        }
+
+        if let Some(ref d) = self.in_flight_queue_timeout {
+            f.field("in_flight_queue_timeout", d);
+        }
+
+        if let Some(ref breaker) = self.circuit_breaker {
+            f.field("circuit_breaker", breaker);
+        }
+
         if let Some(ref v) = self.local_address {
             f.field("local_address", v);
         }
@@ -2849,6 +4975,10 @@ impl Config {
             f.field("interface", v);
         }
 
+        if let Some(index) = self.interface_index {
+            f.field("interface_index", &index);
+        }
+
         if self.nodelay {
             f.field("tcp_nodelay", &true);
         }
@@ -2893,6 +5023,14 @@ impl Config {
             f.field("dns_overrides", &self.dns_overrides);
         }
 
+        if !self.dns_balance.is_resolver_order() {
+            f.field("dns_balance", &self.dns_balance);
+        }
+
+        if let Some(ref d) = self.avoid_failed_addresses {
+            f.field("avoid_failed_addresses", d);
+        }
+
         #[cfg(feature = "http3")]
         {
             if self.tls_enable_early_data {
@@ -2900,6 +5038,10 @@ impl Config {
             }
         }
 
+        if let Some(ref header) = self.proxy_protocol {
+            f.field("proxy_protocol", header);
+        }
+
         #[cfg(unix)]
         if let Some(ref p) = self.unix_socket {
             f.field("unix_socket", p);
@@ -2913,6 +5055,24 @@ type MaybeCookieService<T> = T;
 #[cfg(feature = "cookies")]
 type MaybeCookieService<T> = CookieService<T>;
 
+#[cfg(not(feature = "cache"))]
+type MaybeCacheService<T> = T;
+
+#[cfg(feature = "cache")]
+type MaybeCacheService<T> = CacheService<ConditionalService<T>>;
+
+#[cfg(not(feature = "content-digest"))]
+type MaybeContentDigestService<T> = T;
+
+#[cfg(feature = "content-digest")]
+type MaybeContentDigestService<T> = ContentDigestService<T>;
+
+#[cfg(not(feature = "message-signatures"))]
+type MaybeSignatureService<T> = T;
+
+#[cfg(feature = "message-signatures")]
+type MaybeSignatureService<T> = SignatureService<T>;
+
 #[cfg(not(any(
     feature = "gzip",
     feature = "brotli",
@@ -2929,10 +5089,25 @@ type MaybeDecompression<T> = T;
 ))]
 type MaybeDecompression<T> = Decompression<T>;
 
-type LayeredService<T> = MaybeDecompression<
-    FollowRedirect<
-        MaybeCookieService<tower::retry::Retry<crate::retry::Policy, T>>,
-        TowerRedirectPolicy,
+type LayeredService<T> = TokenService<
+    MaybeDecompression<
+        FollowRedirect<
+            MaybeCacheService<
+                MaybeCookieService<
+                    MaybeContentDigestService<
+                        CircuitService<
+                            InFlightService<
+                                tower::retry::Retry<
+                                    crate::retry::Policy,
+                                    RawTargetService<MaybeSignatureService<WireService<T>>>,
+                                >,
+                            >,
+                        >,
+                    >,
+                >,
+            >,
+            TowerRedirectPolicy,
+        >,
     >,
 >;
 type LayeredFuture<T> = <LayeredService<T> as Service<http::Request<Body>>>::Future;
@@ -2942,17 +5117,38 @@ struct ClientRef {
     #[cfg(feature = "cookies")]
     cookie_store: Option<Arc<dyn cookie::CookieStore>>,
     headers: HeaderMap,
+    query: Vec<(String, String)>,
+    hosts: Vec<(String, HostConfig)>,
+    base_url: Option<Url>,
     hyper: LayeredService<HyperService>,
     #[cfg(feature = "http3")]
     h3_client: Option<LayeredService<H3Client>>,
+    #[cfg(feature = "__tls")]
+    tls_reload: Option<Arc<ReloadableTls>>,
     referer: bool,
     total_timeout: RequestConfig<TotalTimeout>,
-    read_timeout: Option<Duration>,
+    read_timeout: RequestConfig<ReadTimeout>,
     proxies: Arc<Vec<ProxyMatcher>>,
     proxies_maybe_http_auth: bool,
     proxies_maybe_http_custom_headers: bool,
     https_only: bool,
+    #[cfg(feature = "hsts-preload")]
+    hsts_preload: Option<Arc<crate::hsts::Preload>>,
+    #[cfg(feature = "file")]
+    file_urls: bool,
+    strict_content_type: bool,
+    idna: bool,
+    schemes: Vec<String>,
     redirect_policy_desc: Option<String>,
+    #[cfg(feature = "tracing")]
+    trace_context: Option<CurrentContext>,
+    redaction: Option<Redaction>,
+    audit_observer: Option<Audit>,
+    on_error: Option<OnError>,
+    counters: Arc<Counters>,
+    in_flight: Option<Arc<InFlightLimits>>,
+    // the configuration of the client, for `Client::to_builder()`
+    builder_config: Config,
 }
 
 impl ClientRef {
@@ -2969,6 +5165,10 @@ impl ClientRef {
 
         f.field("accepts", &self.accepts);
 
+        if let Some(ref url) = self.base_url {
+            f.field("base_url", &url.as_str());
+        }
+
         if !self.proxies.is_empty() {
             f.field("proxies", &self.proxies);
         }
@@ -2981,13 +5181,62 @@ impl ClientRef {
             f.field("referer", &true);
         }
 
-        f.field("default_headers", &self.headers);
+        f.field(
+            "default_headers",
+            &redact::headers(self.redaction.as_ref(), &self.headers),
+        );
 
-        self.total_timeout.fmt_as_field(f);
+        if !self.query.is_empty() {
+            // only the keys, the values are often API keys
+            let keys = self.query.iter().map(|(key, _)| key).collect::<Vec<_>>();
+            f.field("default_query", &keys);
+        }
+
+        if !self.hosts.is_empty() {
+            f.field("hosts", &self.hosts);
+        }
+
+        #[cfg(feature = "tracing")]
+        if self.trace_context.is_some() {
+            f.field("trace_context", &true);
+        }
+
+        #[cfg(feature = "hsts-preload")]
+        if let Some(ref preload) = self.hsts_preload {
+            f.field("hsts_preload", preload);
+        }
 
-        if let Some(ref d) = self.read_timeout {
-            f.field("read_timeout", d);
+        #[cfg(feature = "file")]
+        if self.file_urls {
+            f.field("file_urls", &true);
         }
+
+        if self.strict_content_type {
+            f.field("strict_content_type", &true);
+        }
+
+        if !self.idna {
+            f.field("idna", &false);
+        }
+
+        if !self.schemes.is_empty() {
+            f.field("scheme_handlers", &self.schemes);
+        }
+
+        if let Some(Redaction(ref policy)) = self.redaction {
+            f.field("redaction", policy);
+        }
+
+        if self.audit_observer.is_some() {
+            f.field("audit", &true);
+        }
+
+        if self.on_error.is_some() {
+            f.field("on_error", &true);
+        }
+
+        self.total_timeout.fmt_as_field(f);
+        self.read_timeout.fmt_as_field(f);
     }
 }
 
@@ -3000,6 +5249,8 @@ pin_project! {
 
 enum PendingInner {
     Request(Pin<Box<PendingRequest>>),
+    #[cfg(feature = "file")]
+    File(Pin<Box<dyn Future<Output = Result<Response, crate::Error>> + Send + Sync>>),
     Error(Option<crate::Error>),
 }
 
@@ -3011,6 +5262,12 @@ pin_project! {
 
         client: Arc<ClientRef>,
 
+        start: Instant,
+        span: RequestSpan,
+        audited: Option<Audited>,
+        informational: Option<crate::informational::Received>,
+        connection: ConnectionSlot,
+        timed_out: TimedOutSlot,
         #[pin]
         in_flight: ResponseFuture,
         #[pin]
@@ -3018,9 +5275,17 @@ pin_project! {
         #[pin]
         read_timeout_fut: Option<Pin<Box<Sleep>>>,
         read_timeout: Option<Duration>,
+        #[pin]
+        header_timeout: Option<Pin<Box<Sleep>>>,
     }
 }
 
+// The span to record the response on, if tracing.
+#[cfg(feature = "tracing")]
+type RequestSpan = Option<tracing::Span>;
+#[cfg(not(feature = "tracing"))]
+type RequestSpan = ();
+
 enum ResponseFuture {
     Default(LayeredFuture<HyperService>),
     #[cfg(feature = "http3")]
@@ -3039,6 +5304,10 @@ impl PendingRequest {
     fn read_timeout(self: Pin<&mut Self>) -> Pin<&mut Option<Pin<Box<Sleep>>>> {
         self.project().read_timeout_fut
     }
+
+    fn header_timeout(self: Pin<&mut Self>) -> Pin<&mut Option<Pin<Box<Sleep>>>> {
+        self.project().header_timeout
+    }
 }
 
 impl Pending {
@@ -3059,7 +5328,27 @@ impl Future for Pending {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let inner = self.inner();
         match inner.get_mut() {
-            PendingInner::Request(ref mut req) => Pin::new(req).poll(cx),
+            PendingInner::Request(ref mut req) => {
+                let res = ready!(req.as_mut().poll(cx));
+                let res = res.map_err(|err| err.with_redaction(req.client.redaction.as_ref()));
+                if let (Err(err), Some(audited)) = (&res, req.audited.take()) {
+                    audited.failed(&req.url, err);
+                }
+                if let (Err(err), Some(on_error)) = (&res, &req.client.on_error) {
+                    crate::observe::failed(
+                        on_error,
+                        &req.method,
+                        &req.url,
+                        err,
+                        req.client.redaction.as_ref(),
+                        &req.connection,
+                        req.start,
+                    );
+                }
+                Poll::Ready(res)
+            }
+            #[cfg(feature = "file")]
+            PendingInner::File(ref mut fut) => fut.as_mut().poll(cx),
             PendingInner::Error(ref mut err) => Poll::Ready(Err(err
                 .take()
                 .expect("Pending error polled more than once"))),
@@ -3067,42 +5356,118 @@ impl Future for Pending {
     }
 }
 
+pin_project! {
+    /// The future returned when calling a `Client` as a service of `http`
+    /// types.
+    pub struct HttpPending {
+        #[pin]
+        inner: Pending,
+    }
+}
+
+impl HttpPending {
+    fn new(client: &Client, req: http::Request<Body>) -> HttpPending {
+        let inner = match Request::try_from(req) {
+            Ok(req) => client.execute_request(req),
+            Err(err) => Pending::new_err(err),
+        };
+        HttpPending { inner }
+    }
+}
+
+impl Future for HttpPending {
+    type Output = Result<http::Response<Body>, crate::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().inner.poll(cx))?;
+        Poll::Ready(Ok(res.into()))
+    }
+}
+
+impl fmt::Debug for HttpPending {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("HttpPending").field(&self.inner).finish()
+    }
+}
+
 impl Future for PendingRequest {
     type Output = Result<Response, crate::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if let Some(delay) = self.as_mut().total_timeout().as_mut().as_pin_mut() {
             if let Poll::Ready(()) = delay.poll(cx) {
-                return Poll::Ready(Err(
-                    crate::error::request(crate::error::TimedOut).with_url(self.url.clone())
-                ));
+                self.timed_out.set();
+                return Poll::Ready(Err(crate::error::request(TimedOut(TimeoutPhase::Total))
+                    .with_url(self.url.clone())
+                    .with_connection(&self.connection, self.start)));
             }
         }
 
         if let Some(delay) = self.as_mut().read_timeout().as_mut().as_pin_mut() {
             if let Poll::Ready(()) = delay.poll(cx) {
-                return Poll::Ready(Err(
-                    crate::error::request(crate::error::TimedOut).with_url(self.url.clone())
-                ));
+                self.timed_out.set();
+                return Poll::Ready(Err(crate::error::request(TimedOut(
+                    TimeoutPhase::ResponseHeaders,
+                ))
+                .with_url(self.url.clone())
+                .with_connection(&self.connection, self.start)));
             }
         }
 
-        let res = match self.as_mut().in_flight().get_mut() {
+        if let Some(delay) = self.as_mut().header_timeout().as_mut().as_pin_mut() {
+            if let Poll::Ready(()) = delay.poll(cx) {
+                self.timed_out.set();
+                return Poll::Ready(Err(crate::error::request(TimedOut(
+                    TimeoutPhase::ResponseHeaders,
+                ))
+                .with_url(self.url.clone())
+                .with_connection(&self.connection, self.start)));
+            }
+        }
+
+        let body_complete = Arc::new(OnceLock::new());
+        let mut res = match self.as_mut().in_flight().get_mut() {
             ResponseFuture::Default(r) => match ready!(Pin::new(r).poll(cx)) {
                 Err(e) => {
-                    return Poll::Ready(Err(e.if_no_url(|| self.url.clone())));
+                    return Poll::Ready(Err(e
+                        .if_no_url(|| self.url.clone())
+                        .with_connection(&self.connection, self.start)));
+                }
+                Ok(res) => {
+                    #[cfg(any(
+                        feature = "gzip",
+                        feature = "brotli",
+                        feature = "zstd",
+                        feature = "deflate"
+                    ))]
+                    let res = super::offload::response(res, self.client.accepts.offload);
+                    res.map(|body| {
+                        super::body::boxed(CompleteBody::new(body, body_complete.clone()))
+                    })
                 }
-                Ok(res) => res.map(super::body::boxed),
             },
             #[cfg(feature = "http3")]
             ResponseFuture::H3(r) => match ready!(Pin::new(r).poll(cx)) {
                 Err(e) => {
                     return Poll::Ready(Err(crate::error::request(e).with_url(self.url.clone())));
                 }
-                Ok(res) => res.map(super::body::boxed),
+                Ok(res) => res
+                    .map(|body| super::body::boxed(CompleteBody::new(body, body_complete.clone()))),
             },
         };
 
+        #[cfg(feature = "tracing")]
+        if let Some(ref span) = self.span {
+            crate::trace::record_status(span, res.status());
+        }
+
+        let conn_timings = res.extensions_mut().remove::<ConnectTimings>();
+        let timings = RequestTimings::new(self.start, conn_timings, res.version(), body_complete);
+        res.extensions_mut().insert(timings);
+        if let Some(ref informational) = self.informational {
+            res.extensions_mut().insert(informational.take());
+        }
+
         if let Some(url) = &res
             .extensions()
             .get::<tower_http::follow_redirect::RequestUri>()
@@ -3110,14 +5475,29 @@ impl Future for PendingRequest {
             self.url = match Url::parse(&url.0.to_string()) {
                 Ok(url) => url,
                 Err(e) => return Poll::Ready(Err(crate::error::decode(e))),
+            };
+            // the location of a redirect, as it was before being upgraded
+            #[cfg(feature = "hsts-preload")]
+            if let Some(ref preload) = self.client.hsts_preload {
+                let mut url = self.url.clone();
+                preload.upgrade(&mut url);
+                self.url = url;
             }
         };
 
+        if let Some(audited) = self.audited.take() {
+            res = audited
+                .response(self.url.clone(), res)
+                .map(super::body::boxed);
+        }
+
         let res = Response::new(
             res,
             self.url.clone(),
             self.total_timeout.take(),
             self.read_timeout,
+            self.client.redaction.clone(),
+            self.client.strict_content_type,
         );
         Poll::Ready(Ok(res))
     }
@@ -3129,8 +5509,10 @@ impl fmt::Debug for Pending {
             PendingInner::Request(ref req) => f
                 .debug_struct("Pending")
                 .field("method", &req.method)
-                .field("url", &req.url)
+                .field("url", &redact::url(req.client.redaction.as_ref(), &req.url))
                 .finish(),
+            #[cfg(feature = "file")]
+            PendingInner::File(_) => f.debug_struct("Pending").finish_non_exhaustive(),
             PendingInner::Error(ref err) => f.debug_struct("Pending").field("error", err).finish(),
         }
     }
@@ -0,0 +1,168 @@
+//! Responses for `file` URLs.
+//!
+//! A client with [`file_urls`](super::ClientBuilder::file_urls) answers the
+//! requests for `file` URLs itself, like a static file server would: a `GET`
+//! request gets the file as a `200 OK` response, with its length and the
+//! content type guessed from its extension. A missing file, or a directory,
+//! is a `404 Not Found`, and a file that can't be read a `403 Forbidden`.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use http::header::{HeaderValue, ALLOW, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{Method, StatusCode};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::body::ResponseBody;
+use super::response::Response;
+use crate::redact::Redaction;
+
+/// The size of the chunks a file is read in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub(super) async fn fetch(
+    method: Method,
+    url: Url,
+    redaction: Option<Redaction>,
+    strict_content_type: bool,
+) -> crate::Result<Response> {
+    let respond = |res: http::Response<ResponseBody>| {
+        Response::new(
+            res,
+            url.clone(),
+            None,
+            None,
+            redaction.clone(),
+            strict_content_type,
+        )
+    };
+
+    if method != Method::GET && method != Method::HEAD {
+        let mut res = empty(StatusCode::METHOD_NOT_ALLOWED);
+        res.headers_mut()
+            .insert(ALLOW, HeaderValue::from_static("GET, HEAD"));
+        return Ok(respond(res));
+    }
+    let path = url
+        .to_file_path()
+        .map_err(|()| crate::error::url_bad_scheme(url.clone()))?;
+
+    let opened = async {
+        let file = File::open(&path).await?;
+        let metadata = file.metadata().await?;
+        Ok::<_, io::Error>((file, metadata))
+    };
+    let (file, metadata) = match opened.await {
+        Ok((_, metadata)) if !metadata.is_file() => {
+            return Ok(respond(empty(StatusCode::NOT_FOUND)))
+        }
+        Ok(opened) => opened,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(respond(empty(StatusCode::NOT_FOUND)))
+        }
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+            return Ok(respond(empty(StatusCode::FORBIDDEN)))
+        }
+        Err(err) => return Err(crate::error::request(err).with_url(url)),
+    };
+
+    let len = metadata.len();
+    let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+    let body = if method == Method::HEAD {
+        super::body::boxed(http_body_util::Empty::new())
+    } else {
+        super::body::boxed(FileBody::new(file, len))
+    };
+    let mut res = http::Response::new(body);
+    res.headers_mut()
+        .insert(CONTENT_LENGTH, HeaderValue::from(len));
+    if let Ok(value) = HeaderValue::from_str(content_type.as_ref()) {
+        res.headers_mut().insert(CONTENT_TYPE, value);
+    }
+    Ok(respond(res))
+}
+
+fn empty(status: StatusCode) -> http::Response<ResponseBody> {
+    let mut res = http::Response::new(super::body::boxed(http_body_util::Empty::new()));
+    *res.status_mut() = status;
+    res
+}
+
+/// The body of a file, of a known length.
+///
+/// The file is read by a task of the runtime that fetched it, so that the
+/// body can be read anywhere, such as by a blocking `Response`.
+struct FileBody {
+    chunks: mpsc::Receiver<io::Result<Bytes>>,
+    remaining: u64,
+}
+
+impl FileBody {
+    fn new(file: File, len: u64) -> FileBody {
+        let (tx, chunks) = mpsc::channel(1);
+        tokio::spawn(read(file, len, tx));
+        FileBody {
+            chunks,
+            remaining: len,
+        }
+    }
+}
+
+async fn read(mut file: File, len: u64, tx: mpsc::Sender<io::Result<Bytes>>) {
+    let mut remaining = len;
+    while remaining > 0 {
+        let size = usize::try_from(remaining).map_or(CHUNK_SIZE, |len| len.min(CHUNK_SIZE));
+        let mut buf = vec![0; size];
+        let chunk = match file.read(&mut buf).await {
+            // the file was truncated since it was opened
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                remaining -= read as u64;
+                buf.truncate(read);
+                Ok(Bytes::from(buf))
+            }
+            Err(err) => Err(err),
+        };
+        let failed = chunk.is_err();
+        if tx.send(chunk).await.is_err() || failed {
+            return;
+        }
+    }
+}
+
+impl HttpBody for FileBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        if this.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        match ready!(this.chunks.poll_recv(cx)) {
+            Some(Ok(chunk)) => {
+                this.remaining -= chunk.len() as u64;
+                Poll::Ready(Some(Ok(Frame::data(chunk))))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(Some(Err(io::ErrorKind::UnexpectedEof.into()))),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.remaining)
+    }
+}
@@ -6,9 +6,18 @@ pub use self::upgrade::Upgraded;
 
 pub mod body;
 pub mod client;
+#[cfg(feature = "file")]
+mod file;
 pub mod h3_client;
 #[cfg(feature = "multipart")]
 pub mod multipart;
+#[cfg(any(
+    feature = "gzip",
+    feature = "brotli",
+    feature = "zstd",
+    feature = "deflate"
+))]
+mod offload;
 pub(crate) mod request;
 mod response;
 mod upgrade;
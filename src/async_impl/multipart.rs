@@ -186,23 +186,20 @@ impl Form {
     where
         T: Into<Cow<'static, str>>,
     {
-        // start with boundary
-        let boundary = stream::once(future::ready(Ok(
-            format!("--{}\r\n", self.boundary()).into()
-        )));
-        // append headers
-        let header = stream::once(future::ready(Ok({
-            let mut h = self
-                .inner
-                .percent_encoding
-                .encode_headers(&name.into(), &part.meta);
+        // start with boundary and headers, in a single chunk
+        let head = stream::once(future::ready(Ok({
+            let mut h = format!("--{}\r\n", self.boundary()).into_bytes();
+            h.extend_from_slice(
+                &self
+                    .inner
+                    .percent_encoding
+                    .encode_headers(&name.into(), &part.meta),
+            );
             h.extend_from_slice(b"\r\n\r\n");
             h.into()
         })));
         // then append form data followed by terminating CRLF
-        boundary
-            .chain(header)
-            .chain(part.value.into_data_stream())
+        head.chain(part.value.into_data_stream())
             .chain(stream::once(future::ready(Ok("\r\n".into()))))
     }
 
@@ -0,0 +1,149 @@
+//! Decoding compressed response bodies on the blocking thread pool.
+//!
+//! With [`offload_decompression`](super::ClientBuilder::offload_decompression),
+//! the body of a large compressed response is decoded by a thread of tokio's
+//! blocking pool rather than by the task reading it, which would otherwise
+//! keep a thread of the runtime busy for as long as decoding takes. The
+//! thread reads the body from the connection and decodes it, handing the
+//! decoded chunks back through a channel.
+
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use http::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+use http_body::{Body as HttpBody, Frame};
+use http_body_util::Either;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::error::BoxError;
+
+/// The number of decoded chunks waiting to be read, past which decoding
+/// pauses.
+const CHUNKS: usize = 4;
+
+/// Marks a response which was compressed when it came off the connection,
+/// with the length of its compressed body.
+#[derive(Clone, Copy)]
+struct Encoded {
+    len: Option<u64>,
+}
+
+/// Mark `res` if its body is compressed, for [`response`] to tell the bodies
+/// the client decodes.
+pub(super) fn mark<B>(mut res: http::Response<B>) -> http::Response<B> {
+    if res.headers().contains_key(CONTENT_ENCODING) {
+        let len = res
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse().ok());
+        res.extensions_mut().insert(Encoded { len });
+    }
+    res
+}
+
+/// Decode the body of `res` on the blocking pool if the client decodes it,
+/// and it is at least `min_len` bytes long or of an unknown length.
+pub(super) fn response<B>(
+    mut res: http::Response<B>,
+    min_len: Option<u64>,
+) -> http::Response<Either<B, OffloadedBody>>
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let encoded = res.extensions_mut().remove::<Encoded>();
+    let offload = match (min_len, encoded) {
+        // still encoded if the client doesn't decode it
+        (Some(min_len), Some(encoded)) => {
+            !res.headers().contains_key(CONTENT_ENCODING)
+                && encoded.len.is_none_or(|len| len >= min_len)
+        }
+        _ => false,
+    };
+    res.map(|body| {
+        if offload {
+            Either::Right(OffloadedBody::new(body))
+        } else {
+            Either::Left(body)
+        }
+    })
+}
+
+/// A response body decoded by a thread of the blocking pool.
+pub(super) struct OffloadedBody {
+    chunks: mpsc::Receiver<Result<Frame<Bytes>, BoxError>>,
+    // taken once the decoding thread is done
+    task: Option<JoinHandle<()>>,
+}
+
+impl OffloadedBody {
+    fn new<B>(body: B) -> OffloadedBody
+    where
+        B: HttpBody<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let (tx, chunks) = mpsc::channel(CHUNKS);
+        let handle = tokio::runtime::Handle::current();
+        let task = tokio::task::spawn_blocking(move || handle.block_on(decode(body, tx)));
+        OffloadedBody {
+            chunks,
+            task: Some(task),
+        }
+    }
+}
+
+async fn decode<B>(body: B, tx: mpsc::Sender<Result<Frame<Bytes>, BoxError>>)
+where
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let mut body = pin!(body);
+    loop {
+        // stop as soon as the body is dropped, rather than at the next chunk
+        let mut closed = pin!(tx.closed());
+        let frame = poll_fn(|cx| {
+            if closed.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            body.as_mut().poll_frame(cx)
+        })
+        .await;
+        let Some(frame) = frame else {
+            return;
+        };
+        let failed = frame.is_err();
+        if tx.send(frame.map_err(Into::into)).await.is_err() || failed {
+            return;
+        }
+    }
+}
+
+impl HttpBody for OffloadedBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        if let Some(frame) = ready!(this.chunks.poll_recv(cx)) {
+            return Poll::Ready(Some(frame));
+        }
+        // the thread is done, having sent every chunk unless it panicked
+        let Some(task) = this.task.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let done = ready!(Pin::new(task).poll(cx));
+        this.task = None;
+        Poll::Ready(done.err().map(|err| Err(err.into())))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.task.is_none()
+    }
+}
@@ -1,9 +1,17 @@
 use std::convert::TryFrom;
 use std::fmt;
 use std::future::Future;
-use std::time::Duration;
-
-#[cfg(any(feature = "query", feature = "form", feature = "json"))]
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+#[cfg(any(
+    feature = "query",
+    feature = "form",
+    feature = "json",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "xml"
+))]
 use serde::Serialize;
 #[cfg(feature = "json")]
 use serde_json;
@@ -13,14 +21,24 @@ use super::client::{Client, Pending};
 #[cfg(feature = "multipart")]
 use super::multipart;
 use super::response::Response;
-use crate::config::{RequestConfig, TotalTimeout};
+#[cfg(feature = "cache")]
+use crate::config::CacheMode;
+cfg_interface_index! {
+    use crate::config::InterfaceIndex;
+}
+use crate::config::{
+    Deadline, EarlyHints, HeaderTimeout, InFlightReserved, OnInformational, RawTarget, ReadTimeout,
+    RequestConfig, RequestPriority, TotalTimeout,
+};
 #[cfg(feature = "multipart")]
 use crate::header::CONTENT_LENGTH;
-#[cfg(any(feature = "multipart", feature = "form", feature = "json"))]
-use crate::header::CONTENT_TYPE;
-use crate::header::{HeaderMap, HeaderName, HeaderValue};
-use crate::{Method, Url};
+use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use crate::in_flight::Reserved;
+use crate::redact::{self, Redaction};
+use crate::{InFlightPermit, Method, QueuePriority, Url};
+use http::uri::PathAndQuery;
 use http::{request::Parts, Extensions, Request as HttpRequest, Version};
+use tower::Service;
 
 /// A request which can be executed with `Client::execute()`.
 pub struct Request {
@@ -127,6 +145,109 @@ impl Request {
         RequestConfig::<TotalTimeout>::get_mut(&mut self.extensions)
     }
 
+    /// Get the deadline.
+    #[inline]
+    pub fn deadline(&self) -> Option<&Instant> {
+        RequestConfig::<Deadline>::get(&self.extensions)
+    }
+
+    /// Get a mutable reference to the deadline.
+    #[inline]
+    pub fn deadline_mut(&mut self) -> &mut Option<Instant> {
+        RequestConfig::<Deadline>::get_mut(&mut self.extensions)
+    }
+
+    /// Get the read timeout.
+    #[inline]
+    pub fn read_timeout(&self) -> Option<&Duration> {
+        RequestConfig::<ReadTimeout>::get(&self.extensions)
+    }
+
+    /// Get a mutable reference to the read timeout.
+    #[inline]
+    pub fn read_timeout_mut(&mut self) -> &mut Option<Duration> {
+        RequestConfig::<ReadTimeout>::get_mut(&mut self.extensions)
+    }
+
+    /// Get the header timeout.
+    #[inline]
+    pub fn header_timeout(&self) -> Option<&Duration> {
+        RequestConfig::<HeaderTimeout>::get(&self.extensions)
+    }
+
+    /// Get a mutable reference to the header timeout.
+    #[inline]
+    pub fn header_timeout_mut(&mut self) -> &mut Option<Duration> {
+        RequestConfig::<HeaderTimeout>::get_mut(&mut self.extensions)
+    }
+
+    /// Get the queue priority.
+    #[inline]
+    pub fn queue_priority(&self) -> Option<&QueuePriority> {
+        RequestConfig::<RequestPriority>::get(&self.extensions)
+    }
+
+    /// Get a mutable reference to the queue priority.
+    #[inline]
+    pub fn queue_priority_mut(&mut self) -> &mut Option<QueuePriority> {
+        RequestConfig::<RequestPriority>::get_mut(&mut self.extensions)
+    }
+
+    /// Get the raw request target.
+    #[inline]
+    pub fn raw_target(&self) -> Option<&PathAndQuery> {
+        RequestConfig::<RawTarget>::get(&self.extensions)
+    }
+
+    /// Get a mutable reference to the raw request target.
+    #[inline]
+    pub fn raw_target_mut(&mut self) -> &mut Option<PathAndQuery> {
+        RequestConfig::<RawTarget>::get_mut(&mut self.extensions)
+    }
+
+    cfg_interface_index! {
+        /// Get the index of the network interface the request is bound to.
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        #[inline]
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index(&self) -> Option<&u32> {
+            RequestConfig::<InterfaceIndex>::get(&self.extensions)
+        }
+    }
+
+    cfg_interface_index! {
+        /// Get a mutable reference to the index of the network interface the
+        /// request is bound to.
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        #[inline]
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index_mut(&mut self) -> &mut Option<u32> {
+            RequestConfig::<InterfaceIndex>::get_mut(&mut self.extensions)
+        }
+    }
+
+    /// Get the cache mode.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    #[inline]
+    pub fn cache_mode(&self) -> Option<&crate::cache::Mode> {
+        RequestConfig::<CacheMode>::get(&self.extensions)
+    }
+
+    /// Get a mutable reference to the cache mode.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    #[inline]
+    pub fn cache_mode_mut(&mut self) -> &mut Option<crate::cache::Mode> {
+        RequestConfig::<CacheMode>::get_mut(&mut self.extensions)
+    }
+
     /// Get the http version.
     #[inline]
     pub fn version(&self) -> Version {
@@ -169,7 +290,10 @@ impl Request {
 }
 
 impl RequestBuilder {
-    pub(super) fn new(client: Client, request: crate::Result<Request>) -> RequestBuilder {
+    pub(super) fn new(client: Client, mut request: crate::Result<Request>) -> RequestBuilder {
+        if let (Ok(req), Some(redaction)) = (request.as_mut(), client.redaction()) {
+            req.extensions_mut().insert(redaction.clone());
+        }
         let mut builder = RequestBuilder { client, request };
 
         let auth = builder
@@ -298,6 +422,201 @@ impl RequestBuilder {
         self
     }
 
+    /// Set a time by which this request must be done, whatever its
+    /// timeout.
+    ///
+    /// Like the timeout, it applies until the response body has finished.
+    /// The earliest of the deadline, the timeout and the deadline of the
+    /// [`deadline::scope`](crate::deadline::scope) the request is sent in
+    /// applies.
+    pub fn deadline(mut self, deadline: Instant) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.deadline_mut() = Some(deadline);
+        }
+        self
+    }
+
+    /// Enables a read timeout.
+    ///
+    /// The timeout applies to each read operation, and resets after a
+    /// successful read. It affects only this request and overrides the read
+    /// timeout configured using `ClientBuilder::read_timeout()`.
+    ///
+    /// The connect timeout can only be set on the `Client`, since
+    /// connections are shared between requests.
+    pub fn read_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.read_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Enables a timeout for the response headers.
+    ///
+    /// The timeout applies from when the request is sent until the headers
+    /// of its response are received, including the time to connect and to
+    /// follow redirects, but not the time to read the response body. It is
+    /// bounded by the total timeout of the request.
+    pub fn header_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.header_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Set the priority of this request, when it waits for the number of
+    /// requests in flight to go under the
+    /// [`max_in_flight`](crate::ClientBuilder::max_in_flight) limit of the
+    /// client.
+    ///
+    /// Unlike [`priority`](RequestBuilder::priority), which is sent to the
+    /// server, this only orders the requests waiting in the client.
+    ///
+    /// Default is [`QueuePriority::Normal`].
+    pub fn queue_priority(mut self, priority: QueuePriority) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.queue_priority_mut() = Some(priority);
+        }
+        self
+    }
+
+    /// Send the path and query of this request as they are given, rather
+    /// than as they are in its URL.
+    ///
+    /// The URL of a request is normalized when parsed: dot segments are
+    /// resolved and some characters are percent-encoded. The raw target is
+    /// sent in the request line instead, byte for byte, which lets
+    /// conformance tools and security scanners send unusual targets, such
+    /// as `/a/%2e%2e/b` or a query with invalid percent-encoding. The URL
+    /// still decides where the request is sent, and is the one of the
+    /// [`Response`]; redirects are followed from it, and the requests that
+    /// follow them send the target of their own URL.
+    ///
+    /// # Errors
+    ///
+    /// Building the request fails if `target` has bytes not allowed in a
+    /// request target, such as spaces or control characters, or a `#`.
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let res = reqwest::Client::new()
+    ///     .get("http://httpbin.org/")
+    ///     .raw_target("/anything/../%2e%2e/get?q=%zz")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn raw_target(mut self, target: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            match parse_raw_target(target) {
+                Ok(target) => *req.raw_target_mut() = Some(target),
+                Err(err) => self.request = Err(err),
+            }
+        }
+        self
+    }
+
+    cfg_interface_index! {
+        /// Bind the connection of this request only on the network interface
+        /// with the specified index, overriding
+        /// [`ClientBuilder::interface_index`](crate::ClientBuilder::interface_index).
+        ///
+        /// The requests that follow its redirects are bound to the same
+        /// interface. Connections are pooled per interface, so this request
+        /// does not reuse the connections of requests bound to another one.
+        /// HTTP/3 connections are not bound.
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index(mut self, index: u32) -> RequestBuilder {
+            if let Ok(ref mut req) = self.request {
+                *req.interface_index_mut() = Some(index);
+            }
+            self
+        }
+    }
+
+    /// Send the request in the room reserved by a permit of
+    /// [`Client::ready`] or [`Client::try_acquire`], rather than waiting for
+    /// the number of requests in flight to go under the
+    /// [`max_in_flight`](crate::ClientBuilder::max_in_flight) limit.
+    ///
+    /// The permit is only used by the first attempt to send the request,
+    /// and only by the client which reserved it.
+    pub fn in_flight_permit(mut self, permit: InFlightPermit) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *RequestConfig::<InFlightReserved>::get_mut(req.extensions_mut()) =
+                Some(Reserved::new(permit));
+        }
+        self
+    }
+
+    /// Set a function called with each interim `1xx` response, as it
+    /// arrives.
+    ///
+    /// See the [`informational`](crate::informational) module for details.
+    pub fn on_informational<F>(mut self, f: F) -> RequestBuilder
+    where
+        F: Fn(&crate::informational::Informational) + Send + Sync + 'static,
+    {
+        if let Ok(ref mut req) = self.request {
+            *RequestConfig::<OnInformational>::get_mut(req.extensions_mut()) =
+                Some(crate::informational::Callback::new(f));
+        }
+        self
+    }
+
+    /// Set a function called with each `103 Early Hints` interim response.
+    ///
+    /// See the [`early_hints`](crate::early_hints) module for details.
+    ///
+    /// ```rust
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let res = reqwest::Client::new()
+    ///     .get("https://example.com/")
+    ///     .on_early_hints(|hints| {
+    ///         for link in hints.links() {
+    ///             println!("hinted {}", link.target());
+    ///         }
+    ///     })
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_early_hints<F>(mut self, f: F) -> RequestBuilder
+    where
+        F: Fn(&crate::early_hints::EarlyHints) + Send + Sync + 'static,
+    {
+        if let Ok(ref mut req) = self.request {
+            *RequestConfig::<EarlyHints>::get_mut(req.extensions_mut()) =
+                Some(crate::early_hints::Callback::new(f));
+        }
+        self
+    }
+
+    /// Set how this request uses the cache of the client.
+    ///
+    /// See [`cache::Mode`](crate::cache::Mode) for the modes. This has no
+    /// effect if the client has no cache, except that
+    /// [`Mode::OnlyIfCached`](crate::cache::Mode::OnlyIfCached) requests
+    /// always fail.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_mode(mut self, mode: crate::cache::Mode) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.cache_mode_mut() = Some(mode);
+        }
+        self
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -385,6 +704,118 @@ impl RequestBuilder {
         self
     }
 
+    /// Modify the query string of the URL, with a custom encoder.
+    ///
+    /// `encode` turns `query` into an `application/x-www-form-urlencoded`
+    /// string, which is appended to the query string of the URL like
+    /// [`query`](RequestBuilder::query) does. It can be any serializer, such
+    /// as one with other casing or formats for booleans and dates than
+    /// `serde_urlencoded`, or one supporting nested structures.
+    ///
+    /// ```rust
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// struct Search {
+    ///     term: String,
+    ///     exact_match: bool,
+    /// }
+    ///
+    /// // camelCase keys, and booleans as `1` or `0`
+    /// fn encode(search: &Search) -> Result<String, std::convert::Infallible> {
+    ///     Ok(url::form_urlencoded::Serializer::new(String::new())
+    ///         .append_pair("term", &search.term)
+    ///         .append_pair("exactMatch", if search.exact_match { "1" } else { "0" })
+    ///         .finish())
+    /// }
+    ///
+    /// let search = Search {
+    ///     term: "rust http".into(),
+    ///     exact_match: true,
+    /// };
+    /// let res = reqwest::Client::new()
+    ///     .get("http://httpbin.org/get")
+    ///     .query_with(&search, encode)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `encode` fails.
+    pub fn query_with<T, F, E>(mut self, query: &T, encode: F) -> RequestBuilder
+    where
+        T: ?Sized,
+        F: FnOnce(&T) -> Result<String, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match encode(query) {
+                Ok(encoded) if encoded.is_empty() => {}
+                Ok(encoded) => {
+                    let url = req.url_mut();
+                    let query = match url.query() {
+                        Some(existing) if !existing.is_empty() => format!("{existing}&{encoded}"),
+                        _ => encoded,
+                    };
+                    url.set_query(Some(&query));
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Set the priority of this request, with the `Priority` header of
+    /// [RFC 9218].
+    ///
+    /// The `urgency` goes from `0`, the most urgent, to `7`; requests
+    /// without a priority have an urgency of `3`. The response to an
+    /// `incremental` request is useful as it arrives, so the server can
+    /// interleave it with the other responses of the same urgency rather
+    /// than send them one after the other.
+    ///
+    /// This overrides the default priority of the client, set with
+    /// [`ClientBuilder::priority`](crate::ClientBuilder::priority).
+    ///
+    /// Servers multiplexing the streams of an HTTP/2 or HTTP/3 connection
+    /// schedule them by this header, which replaces the stream priorities of
+    /// HTTP/2. No `PRIORITY_UPDATE` frames are sent: the priority of a request
+    /// can't change once it is sent.
+    ///
+    /// ```rust
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::new();
+    /// let res = client
+    ///     .get("https://example.com/app.js")
+    ///     .priority(1, true)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Sending the request fails if `urgency` is greater than `7`.
+    ///
+    /// [RFC 9218]: https://www.rfc-editor.org/rfc/rfc9218
+    pub fn priority(mut self, urgency: u8, incremental: bool) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            match crate::util::priority(urgency, incremental) {
+                Ok(value) => {
+                    req.headers_mut().insert(crate::util::PRIORITY, value);
+                }
+                Err(err) => self.request = Err(err),
+            }
+        }
+        self
+    }
+
     /// Set HTTP version
     pub fn version(mut self, version: Version) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
@@ -447,6 +878,43 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a form body, with a custom encoder.
+    ///
+    /// `encode` turns `form` into an `application/x-www-form-urlencoded`
+    /// string, sent as the body like [`form`](RequestBuilder::form) does,
+    /// with the same `Content-Type`. It can be any serializer, such as one
+    /// with other casing or formats for booleans and dates than
+    /// `serde_urlencoded`, or one supporting nested structures.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `encode` fails.
+    pub fn form_with<T, F, E>(mut self, form: &T, encode: F) -> RequestBuilder
+    where
+        T: ?Sized,
+        F: FnOnce(&T) -> Result<String, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match encode(form) {
+                Ok(body) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert(HeaderValue::from_static(
+                            "application/x-www-form-urlencoded",
+                        ));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
     /// Send a JSON body.
     ///
     /// # Optional
@@ -478,6 +946,259 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a GraphQL query, with its variables.
+    ///
+    /// The query and the variables are sent as a JSON body, without the
+    /// variables if they serialize to `null`, such as `()`. The
+    /// `Content-Type` is set to `application/json` and the `Accept` header
+    /// to the GraphQL response media type, unless the request has them.
+    /// The method of the request is set to `POST`, as a query sent with a
+    /// body must be.
+    ///
+    /// See the [`graphql`](crate::graphql) module for details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql<T: Serialize + ?Sized>(mut self, query: &str, variables: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match crate::graphql::body(query, variables) {
+                Ok(body) => {
+                    *req.method_mut() = Method::POST;
+                    let headers = req.headers_mut();
+                    headers
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/json"));
+                    headers
+                        .entry(crate::header::ACCEPT)
+                        .or_insert_with(|| HeaderValue::from_static(crate::graphql::ACCEPT));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send a MessagePack body.
+    ///
+    /// Structs are serialized as maps, with the names of their fields, and
+    /// the `Content-Type` is set to `application/msgpack` if the request has
+    /// none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `msgpack` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn msgpack<T: Serialize + ?Sized>(mut self, msgpack: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match rmp_serde::to_vec_named(msgpack) {
+                Ok(body) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/msgpack"));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send a CBOR body.
+    ///
+    /// The `Content-Type` is set to `application/cbor` if the request has
+    /// none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cbor` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn cbor<T: Serialize + ?Sized>(mut self, cbor: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            let mut body = Vec::new();
+            match ciborium::into_writer(cbor, &mut body) {
+                Ok(()) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/cbor"));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send an XML body.
+    ///
+    /// The root element is named after the type of `xml`, and the
+    /// `Content-Type` is set to `application/xml` if the request has none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `xml` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` can't be represented as an XML document.
+    #[cfg(feature = "xml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xml")))]
+    pub fn xml<T: Serialize + ?Sized>(mut self, xml: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match quick_xml::se::to_string(xml) {
+                Ok(body) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/xml"));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send a Protocol Buffers message.
+    ///
+    /// The `Content-Type` is set to `application/x-protobuf` if the request
+    /// has none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `protobuf` feature enabled.
+    #[cfg(feature = "protobuf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+    pub fn protobuf<M: prost::Message>(mut self, message: &M) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.headers_mut()
+                .entry(CONTENT_TYPE)
+                .or_insert_with(|| HeaderValue::from_static("application/x-protobuf"));
+            *req.body_mut() = Some(message.encode_to_vec().into());
+        }
+        self
+    }
+
+    /// Set fetch cache mode to 'default'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target, and does
+    /// nothing otherwise. It exists so code shared between targets compiles.
+    ///
+    /// The [request cache][mdn] will be set to 'default'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache_default(self) -> RequestBuilder {
+        self
+    }
+
+    /// Set fetch cache mode to 'no-store'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target, and does
+    /// nothing otherwise. It exists so code shared between targets compiles.
+    ///
+    /// The [request cache][mdn] will be set to 'no-store'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache_no_store(self) -> RequestBuilder {
+        self
+    }
+
+    /// Set fetch cache mode to 'reload'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target, and does
+    /// nothing otherwise. It exists so code shared between targets compiles.
+    ///
+    /// The [request cache][mdn] will be set to 'reload'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache_reload(self) -> RequestBuilder {
+        self
+    }
+
+    /// Set fetch cache mode to 'no-cache'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target, and does
+    /// nothing otherwise. It exists so code shared between targets compiles.
+    ///
+    /// The [request cache][mdn] will be set to 'no-cache'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache_no_cache(self) -> RequestBuilder {
+        self
+    }
+
+    /// Set fetch cache mode to 'force-cache'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target, and does
+    /// nothing otherwise. It exists so code shared between targets compiles.
+    ///
+    /// The [request cache][mdn] will be set to 'force-cache'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache_force_cache(self) -> RequestBuilder {
+        self
+    }
+
+    /// Set fetch cache mode to 'only-if-cached'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target, and does
+    /// nothing otherwise. It exists so code shared between targets compiles.
+    ///
+    /// The [request cache][mdn] will be set to 'only-if-cached'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache_only_if_cached(self) -> RequestBuilder {
+        self
+    }
+
     /// Build a `Request`, which can be inspected, modified and executed with
     /// `Client::execute()`.
     pub fn build(self) -> crate::Result<Request> {
@@ -572,9 +1293,66 @@ fn fmt_request_fields<'a, 'b>(
     f: &'a mut fmt::DebugStruct<'a, 'b>,
     req: &Request,
 ) -> &'a mut fmt::DebugStruct<'a, 'b> {
+    let redaction = req.extensions.get::<Redaction>();
     f.field("method", &req.method)
-        .field("url", &req.url)
-        .field("headers", &req.headers)
+        .field("url", &redact::url(redaction, &req.url))
+        .field("headers", &redact::headers(redaction, &req.headers))
+}
+
+pub(crate) fn parse_raw_target(target: &str) -> crate::Result<PathAndQuery> {
+    // parsing would drop a fragment rather than refuse it
+    if target.contains('#') {
+        return Err(crate::error::builder("request target has a fragment"));
+    }
+    PathAndQuery::try_from(target).map_err(crate::error::builder)
+}
+
+// ===== impl RawTargetService =====
+
+/// The raw target of a request, as an extension of the request sent by
+/// the client.
+///
+/// Requests that follow a redirect have none, as they lose the extensions.
+#[derive(Clone)]
+pub(crate) struct RawTargetExtension(pub(crate) PathAndQuery);
+
+/// A [`Service`] that sends the raw target of a request in place of the
+/// path and query of its URI.
+///
+/// It sits below redirects, which resolve a `Location` against the URI of
+/// the request, so that they resolve it against the URL.
+#[derive(Clone)]
+pub(crate) struct RawTargetService<S> {
+    inner: S,
+}
+
+impl<S> RawTargetService<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        RawTargetService { inner }
+    }
+}
+
+impl<S, B> Service<HttpRequest<B>> for RawTargetService<S>
+where
+    S: Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: HttpRequest<B>) -> Self::Future {
+        if let Some(RawTargetExtension(target)) = req.extensions_mut().remove() {
+            let mut parts = std::mem::take(req.uri_mut()).into_parts();
+            parts.path_and_query = Some(target);
+            *req.uri_mut() = http::Uri::from_parts(parts).expect("URI parts are valid");
+        }
+        self.inner.call(req)
+    }
 }
 
 /// Check the request URL for a "username:password" type authority, and if
@@ -7,17 +7,24 @@ use bytes::Bytes;
 use http_body_util::BodyExt;
 use hyper::{HeaderMap, StatusCode, Version};
 use hyper_util::client::legacy::connect::HttpInfo;
-#[cfg(feature = "json")]
+#[cfg(any(
+    feature = "json",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "xml"
+))]
 use serde::de::DeserializeOwned;
-#[cfg(feature = "json")]
+#[cfg(all(feature = "json", not(feature = "simd-json")))]
 use serde_json;
 use tokio::time::Sleep;
 use url::Url;
 
 use super::body::Body;
 use crate::async_impl::body::ResponseBody;
+use crate::informational::{Informational, Interim};
 #[cfg(feature = "cookies")]
 use crate::cookie;
+use crate::redact::{self, Redaction};
 
 #[cfg(feature = "charset")]
 use encoding_rs::{Encoding, UTF_8};
@@ -30,6 +37,10 @@ pub struct Response {
     // Boxed to save space (11 words to 1 word), and it's not accessed
     // frequently internally.
     url: Box<Url>,
+    redaction: Option<Redaction>,
+    // Only read by the typed decoders, which all need an optional feature.
+    #[allow(unused)]
+    strict_content_type: bool,
 }
 
 impl Response {
@@ -38,6 +49,8 @@ impl Response {
         url: Url,
         total_timeout: Option<Pin<Box<Sleep>>>,
         read_timeout: Option<Duration>,
+        redaction: Option<Redaction>,
+        strict_content_type: bool,
     ) -> Response {
         let (parts, body) = res.into_parts();
         let res = hyper::Response::from_parts(
@@ -48,6 +61,8 @@ impl Response {
         Response {
             res,
             url: Box::new(url),
+            redaction,
+            strict_content_type,
         }
     }
 
@@ -120,6 +135,16 @@ impl Response {
             .map(|info| info.remote_addr())
     }
 
+    /// Get the interim `1xx` responses received before this `Response`.
+    ///
+    /// See the [`informational`](crate::informational) module for details.
+    pub fn informational(&self) -> &[Informational] {
+        self.res
+            .extensions()
+            .get::<Interim>()
+            .map_or(&[], |interim| &interim.0)
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &http::Extensions {
         self.res.extensions()
@@ -263,15 +288,180 @@ impl Response {
     /// or it cannot be properly deserialized to target type `T`. For more
     /// details please see [`serde_json::from_reader`].
     ///
+    /// With the optional `simd-json` feature enabled, the body is parsed by
+    /// simd-json instead, which is much faster on large bodies. The SIMD
+    /// instructions are detected at runtime, and a portable parser is used
+    /// on CPUs without them.
+    ///
+    /// With [`ClientBuilder::strict_content_type`] enabled, this also fails if
+    /// the `Content-Type` of the response isn't `application/json` or a
+    /// `+json` type.
+    ///
+    /// [`ClientBuilder::strict_content_type`]: crate::ClientBuilder::strict_content_type
     /// [`serde_json::from_reader`]: https://docs.serde.rs/serde_json/fn.from_reader.html
     #[cfg(feature = "json")]
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub async fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
+        if self.strict_content_type {
+            self.check_content_type("application/json", is_json)?;
+        }
         let (full, url) = self.do_bytes().await?;
 
+        #[cfg(feature = "simd-json")]
+        {
+            // simd-json parses in place, and needs the body to be mutable,
+            // which it is without a copy unless its buffer is shared.
+            let mut full = full
+                .try_into_mut()
+                .unwrap_or_else(|shared| bytes::BytesMut::from(&shared[..]));
+            simd_json::serde::from_slice(&mut full)
+                .map_err(|err| crate::error::decode(err).with_url(*url))
+        }
+        #[cfg(not(feature = "simd-json"))]
         serde_json::from_slice(&full).map_err(|err| crate::error::decode(err).with_url(*url))
     }
 
+    /// Try to deserialize the `data` of a GraphQL response.
+    ///
+    /// See the [`graphql`](crate::graphql) module for details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the body isn't a GraphQL response, or its data
+    /// cannot be deserialized to the target type `T`. It also fails if the
+    /// response has errors, and then
+    /// [`Error::graphql_errors`](crate::Error::graphql_errors) returns them.
+    /// The checks of the `Content-Type` are those of
+    /// [`json`](Response::json).
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub async fn graphql_data<T: DeserializeOwned>(self) -> crate::Result<T> {
+        if self.strict_content_type {
+            self.check_content_type("application/graphql-response+json", is_json)?;
+        }
+        let (full, url) = self.do_bytes().await?;
+
+        crate::graphql::data(&full).map_err(|err| crate::error::decode(err).with_url(*url))
+    }
+
+    /// Try to deserialize the response body as MessagePack.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `msgpack` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/msgpack`, `application/x-msgpack` or
+    /// `application/vnd.msgpack`, or if the body cannot be deserialized to
+    /// the target type `T`. A response without a `Content-Type` is decoded,
+    /// unless [`ClientBuilder::strict_content_type`] is enabled.
+    ///
+    /// [`ClientBuilder::strict_content_type`]: crate::ClientBuilder::strict_content_type
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub async fn msgpack<T: DeserializeOwned>(self) -> crate::Result<T> {
+        self.check_content_type("application/msgpack", is_msgpack)?;
+        let (full, url) = self.do_bytes().await?;
+
+        rmp_serde::from_slice(&full).map_err(|err| crate::error::decode(err).with_url(*url))
+    }
+
+    /// Try to deserialize the response body as CBOR.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cbor` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/cbor` or one with the `+cbor` suffix, or if the body
+    /// cannot be deserialized to the target type `T`. A response without a
+    /// `Content-Type` is decoded, unless
+    /// [`ClientBuilder::strict_content_type`] is enabled.
+    ///
+    /// [`ClientBuilder::strict_content_type`]: crate::ClientBuilder::strict_content_type
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub async fn cbor<T: DeserializeOwned>(self) -> crate::Result<T> {
+        self.check_content_type("application/cbor", is_cbor)?;
+        let (full, url) = self.do_bytes().await?;
+
+        ciborium::from_reader(&full[..]).map_err(|err| crate::error::decode(err).with_url(*url))
+    }
+
+    /// Try to deserialize the response body as XML.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `xml` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/xml`, `text/xml` or one with the `+xml` suffix, or if the
+    /// body cannot be deserialized to the target type `T`. A response without
+    /// a `Content-Type` is decoded, unless
+    /// [`ClientBuilder::strict_content_type`] is enabled.
+    ///
+    /// [`ClientBuilder::strict_content_type`]: crate::ClientBuilder::strict_content_type
+    #[cfg(feature = "xml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xml")))]
+    pub async fn xml<T: DeserializeOwned>(self) -> crate::Result<T> {
+        self.check_content_type("application/xml", is_xml)?;
+        let (full, url) = self.do_bytes().await?;
+
+        quick_xml::de::from_reader(&full[..])
+            .map_err(|err| crate::error::decode(err).with_url(*url))
+    }
+
+    /// Try to decode the response body as a Protocol Buffers message.
+    ///
+    /// If the `Content-Type` of the response has a `delimited=true` or
+    /// `encoding=delimited` parameter, the body starts with the length of the
+    /// message as a varint, and only this first message is decoded.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `protobuf` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/x-protobuf`, `application/protobuf`,
+    /// `application/vnd.google.protobuf` or `application/x-google-protobuf`,
+    /// or if the body isn't a valid `M` message. A response without a
+    /// `Content-Type` is decoded as a single message, unless
+    /// [`ClientBuilder::strict_content_type`] is enabled.
+    ///
+    /// [`ClientBuilder::strict_content_type`]: crate::ClientBuilder::strict_content_type
+    #[cfg(feature = "protobuf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+    pub async fn protobuf<M: prost::Message + Default>(self) -> crate::Result<M> {
+        self.check_content_type("application/x-protobuf", |content_type| {
+            protobuf_encoding(content_type).is_some()
+        })?;
+        let delimited = self
+            .headers()
+            .get(crate::header::CONTENT_TYPE)
+            .and_then(protobuf_encoding)
+            .unwrap_or(false);
+        let (full, url) = self.do_bytes().await?;
+
+        if delimited {
+            M::decode_length_delimited(full)
+        } else {
+            M::decode(full)
+        }
+        .map_err(|err| crate::error::decode(err).with_url(*url))
+    }
+
     /// Get the full response body as `Bytes`.
     ///
     /// # Example
@@ -379,7 +569,8 @@ impl Response {
         let status = self.status();
         let reason = self.extensions().get::<hyper::ext::ReasonPhrase>().cloned();
         if status.is_client_error() || status.is_server_error() {
-            Err(crate::error::status_code(*self.url, status, reason))
+            Err(crate::error::status_code(*self.url, status, reason)
+                .with_redaction(self.redaction.as_ref()))
         } else {
             Ok(self)
         }
@@ -410,7 +601,10 @@ impl Response {
         let status = self.status();
         let reason = self.extensions().get::<hyper::ext::ReasonPhrase>().cloned();
         if status.is_client_error() || status.is_server_error() {
-            Err(crate::error::status_code(*self.url.clone(), status, reason))
+            Err(
+                crate::error::status_code(*self.url.clone(), status, reason)
+                    .with_redaction(self.redaction.as_ref()),
+            )
         } else {
             Ok(self)
         }
@@ -427,6 +621,29 @@ impl Response {
     pub(crate) fn body_mut(&mut self) -> &mut ResponseBody {
         self.res.body_mut()
     }
+    /// Check that the `Content-Type` of the response is one `matches`
+    /// accepts, before decoding its body as `expected`. A response without
+    /// one is only refused in strict mode.
+    #[cfg(any(
+        feature = "json",
+        feature = "msgpack",
+        feature = "cbor",
+        feature = "xml",
+        feature = "protobuf"
+    ))]
+    fn check_content_type(
+        &self,
+        expected: &'static str,
+        matches: impl Fn(&http::HeaderValue) -> bool,
+    ) -> crate::Result<()> {
+        match self.headers().get(crate::header::CONTENT_TYPE) {
+            Some(content_type) if matches(content_type) => Ok(()),
+            None if !self.strict_content_type => Ok(()),
+            actual => Err(crate::error::content_type(expected, actual.cloned())
+                .with_url(self.url().clone())),
+        }
+    }
+
     async fn do_bytes(self) -> crate::Result<(Bytes, Box<Url>)> {
         use http_body_util::BodyExt;
 
@@ -439,10 +656,11 @@ impl Response {
 
 impl fmt::Debug for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let redaction = self.redaction.as_ref();
         f.debug_struct("Response")
-            .field("url", &self.url().as_str())
+            .field("url", &redact::url(redaction, self.url()).as_str())
             .field("status", &self.status())
-            .field("headers", self.headers())
+            .field("headers", &redact::headers(redaction, self.headers()))
             .finish()
     }
 }
@@ -471,6 +689,8 @@ impl<T: Into<Body>> From<http::Response<T>> for Response {
         Response {
             res,
             url: Box::new(url),
+            redaction: None,
+            strict_content_type: false,
         }
     }
 }
@@ -485,6 +705,80 @@ impl From<Response> for http::Response<Body> {
     }
 }
 
+/// Get the lowercase `type/subtype` of a `Content-Type`, and its parameters.
+#[cfg(any(
+    feature = "json",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "xml",
+    feature = "protobuf"
+))]
+fn media_type(content_type: &http::HeaderValue) -> Option<(String, &str)> {
+    let content_type = content_type.to_str().ok()?;
+    let (essence, params) = content_type.split_once(';').unwrap_or((content_type, ""));
+    Some((essence.trim().to_ascii_lowercase(), params))
+}
+
+/// Check whether a `Content-Type` is `application/json`, or a media type
+/// with the `+json` structured syntax suffix.
+#[cfg(feature = "json")]
+fn is_json(content_type: &http::HeaderValue) -> bool {
+    media_type(content_type)
+        .is_some_and(|(essence, _)| essence == "application/json" || essence.ends_with("+json"))
+}
+
+/// Check whether a `Content-Type` is one of the MessagePack media types.
+#[cfg(feature = "msgpack")]
+fn is_msgpack(content_type: &http::HeaderValue) -> bool {
+    media_type(content_type).is_some_and(|(essence, _)| {
+        matches!(
+            &*essence,
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack"
+        )
+    })
+}
+
+/// Check whether a `Content-Type` is `application/cbor`, or a media type
+/// with the `+cbor` structured syntax suffix.
+#[cfg(feature = "cbor")]
+fn is_cbor(content_type: &http::HeaderValue) -> bool {
+    media_type(content_type)
+        .is_some_and(|(essence, _)| essence == "application/cbor" || essence.ends_with("+cbor"))
+}
+
+/// Check whether a `Content-Type` is `application/xml`, `text/xml`, or a
+/// media type with the `+xml` structured syntax suffix.
+#[cfg(feature = "xml")]
+fn is_xml(content_type: &http::HeaderValue) -> bool {
+    media_type(content_type).is_some_and(|(essence, _)| {
+        essence == "application/xml" || essence == "text/xml" || essence.ends_with("+xml")
+    })
+}
+
+/// Check whether a `Content-Type` is one of the Protocol Buffers media
+/// types, and whether its message is length-delimited, as announced by a
+/// `delimited=true` or `encoding=delimited` parameter.
+#[cfg(feature = "protobuf")]
+fn protobuf_encoding(content_type: &http::HeaderValue) -> Option<bool> {
+    let (essence, params) = media_type(content_type)?;
+    if !matches!(
+        &*essence,
+        "application/x-protobuf"
+            | "application/protobuf"
+            | "application/vnd.google.protobuf"
+            | "application/x-google-protobuf"
+    ) {
+        return None;
+    }
+    let delimited = params.split(';').any(|param| {
+        let (name, value) = param.split_once('=').unwrap_or((param, ""));
+        let (name, value) = (name.trim(), value.trim().trim_matches('"'));
+        (name.eq_ignore_ascii_case("delimited") && value.eq_ignore_ascii_case("true"))
+            || (name.eq_ignore_ascii_case("encoding") && value.eq_ignore_ascii_case("delimited"))
+    });
+    Some(delimited)
+}
+
 #[cfg(test)]
 mod tests {
     use super::Response;
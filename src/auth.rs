@@ -0,0 +1,237 @@
+//! Authorization with tokens which expire.
+//!
+//! OAuth access tokens and the like are only valid for a while, after which
+//! the server answers `401 Unauthorized` until a new one is fetched. Rather
+//! than handling this around every request, a [`TokenProvider`] can be set
+//! for a host with [`ClientBuilder::bearer_token_provider`]:
+//!
+//! - before each request to the host, the provider is asked for a token,
+//!   which is sent in an `Authorization: Bearer` header;
+//! - when the host answers `401 Unauthorized`, the provider is asked to
+//!   refresh the token, and the request is sent once more with the new one.
+//!
+//! The requests to other hosts are sent without the tokens of the provider.
+//!
+//! ```rust
+//! use std::sync::Mutex;
+//!
+//! use reqwest::auth::{TokenFuture, TokenProvider};
+//!
+//! struct Tokens {
+//!     current: Mutex<String>,
+//! }
+//!
+//! impl TokenProvider for Tokens {
+//!     fn token(&self) -> TokenFuture {
+//!         let token = self.current.lock().unwrap().clone();
+//!         Box::pin(async move { Ok(token) })
+//!     }
+//!
+//!     fn refresh(&self, _rejected: &str) -> TokenFuture {
+//!         // ask the authorization server for a new token...
+//!         let token = String::from("new-token");
+//!         *self.current.lock().unwrap() = token.clone();
+//!         Box::pin(async move { Ok(token) })
+//!     }
+//! }
+//!
+//! # fn run() -> Result<(), reqwest::Error> {
+//! let client = reqwest::Client::builder()
+//!     .bearer_token_provider(
+//!         "api.example.com",
+//!         Tokens {
+//!             current: Mutex::new(String::from("token")),
+//!         },
+//!     )
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Requests which already have an `Authorization` header, such as one set
+//! with [`RequestBuilder::bearer_auth`](crate::RequestBuilder::bearer_auth),
+//! are sent as they are. A request is only sent again if its body can be
+//! sent twice, which isn't the case of streaming bodies: the
+//! `401 Unauthorized` response is returned otherwise.
+//!
+//! The token is set before redirects are followed, so that it is removed
+//! from the redirects to other hosts, as any `Authorization` header. A
+//! `401 Unauthorized` answered by another host, after such a redirect, is
+//! returned without refreshing the token.
+//!
+//! [`ClientBuilder::bearer_token_provider`]: crate::ClientBuilder::bearer_token_provider
+
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::header::{HeaderValue, AUTHORIZATION};
+use http::{Request, Response, StatusCode, Uri};
+use tower::{Service, ServiceExt};
+use tower_http::follow_redirect::RequestUri;
+
+use crate::async_impl::body::Body;
+use crate::error::BoxError;
+
+/// Alias for the `Future` type returned by a token provider.
+pub type TokenFuture = Pin<Box<dyn Future<Output = Result<String, BoxError>> + Send>>;
+
+/// Provides the bearer tokens of the requests of a client.
+///
+/// See the [module documentation](self) for when it is called.
+pub trait TokenProvider: Send + Sync {
+    /// Get the token to send with a request.
+    ///
+    /// This is called before every request, so a provider should keep its
+    /// token until it expires, rather than fetch one each time.
+    fn token(&self) -> TokenFuture;
+
+    /// Get a new token, after the server answered `401 Unauthorized` to a
+    /// request sent with `rejected`.
+    ///
+    /// Several requests may be refused at once: a provider can compare
+    /// `rejected` with its current token to only fetch a new one once.
+    fn refresh(&self, rejected: &str) -> TokenFuture;
+}
+
+/// A provider shared by several clients.
+impl<P: TokenProvider + ?Sized> TokenProvider for Arc<P> {
+    fn token(&self) -> TokenFuture {
+        (**self).token()
+    }
+
+    fn refresh(&self, rejected: &str) -> TokenFuture {
+        (**self).refresh(rejected)
+    }
+}
+
+// ===== impl TokenService =====
+
+/// The token providers of a client, with the host each one is for.
+pub(crate) type TokenProviders = Arc<Vec<(String, Arc<dyn TokenProvider>)>>;
+
+/// A [`Service`] that authorizes each request with the token of the
+/// [`TokenProvider`] of its host, refreshing it once if the host refuses it.
+#[derive(Clone)]
+pub(crate) struct TokenService<S> {
+    inner: S,
+    providers: TokenProviders,
+}
+
+impl<S> TokenService<S> {
+    pub(crate) fn new(inner: S, providers: TokenProviders) -> Self {
+        TokenService { inner, providers }
+    }
+
+    fn provider(&self, req: &Request<Body>) -> Option<Arc<dyn TokenProvider>> {
+        if req.headers().contains_key(AUTHORIZATION) {
+            return None;
+        }
+        let host = req.uri().host()?;
+        self.providers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(host))
+            .map(|(_, provider)| provider.clone())
+    }
+}
+
+impl<S, B> Service<Request<Body>> for TokenService<S>
+where
+    S: Service<Request<Body>, Response = Response<B>, Error = crate::Error>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = Response<B>;
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + Sync>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = mem::replace(&mut self.inner, clone);
+        match self.provider(&req) {
+            // The provider's future is not `Sync`, but `Pending` is.
+            Some(provider) => Box::pin(sync_wrapper::SyncFuture::new(authorize(
+                inner, provider, req,
+            ))),
+            None => Box::pin(sync_wrapper::SyncFuture::new(inner.call(req))),
+        }
+    }
+}
+
+async fn authorize<S, B>(
+    mut inner: S,
+    provider: Arc<dyn TokenProvider>,
+    mut req: Request<Body>,
+) -> crate::Result<Response<B>>
+where
+    S: Service<Request<Body>, Response = Response<B>, Error = crate::Error>,
+{
+    let token = provider.token().await.map_err(crate::error::request)?;
+    let replay = try_clone(&req);
+    let sent_to = req.uri().clone();
+    set_bearer(&mut req, &token)?;
+    let res = inner.call(req).await?;
+
+    if res.status() != StatusCode::UNAUTHORIZED {
+        return Ok(res);
+    }
+    // a redirect to another host, which didn't get the token
+    if let Some(RequestUri(answered_by)) = res.extensions().get::<RequestUri>() {
+        if !same_origin(&sent_to, answered_by) {
+            return Ok(res);
+        }
+    }
+    let Some(mut req) = replay else {
+        return Ok(res);
+    };
+    drop(res);
+
+    let token = provider
+        .refresh(&token)
+        .await
+        .map_err(crate::error::request)?;
+    set_bearer(&mut req, &token)?;
+    inner.ready().await?.call(req).await
+}
+
+fn same_origin(a: &Uri, b: &Uri) -> bool {
+    fn port(uri: &Uri) -> Option<u16> {
+        uri.port_u16().or(match uri.scheme_str() {
+            Some("http") => Some(80),
+            Some("https") => Some(443),
+            _ => None,
+        })
+    }
+    a.scheme() == b.scheme()
+        && a.host().map(str::to_ascii_lowercase) == b.host().map(str::to_ascii_lowercase)
+        && port(a) == port(b)
+}
+
+fn try_clone(req: &Request<Body>) -> Option<Request<Body>> {
+    let body = req.body().try_clone()?;
+    let mut clone = Request::new(body);
+    *clone.method_mut() = req.method().clone();
+    *clone.uri_mut() = req.uri().clone();
+    *clone.version_mut() = req.version();
+    *clone.headers_mut() = req.headers().clone();
+    *clone.extensions_mut() = req.extensions().clone();
+    Some(clone)
+}
+
+fn set_bearer(req: &mut Request<Body>, token: &str) -> crate::Result<()> {
+    let mut value =
+        HeaderValue::try_from(format!("Bearer {token}")).map_err(crate::error::request)?;
+    value.set_sensitive(true);
+    req.headers_mut().insert(AUTHORIZATION, value);
+    Ok(())
+}
@@ -89,6 +89,8 @@ impl Body {
         match self.kind {
             Kind::Reader(_, _) => None,
             Kind::Bytes(ref bytes) => Some(bytes.as_ref()),
+            #[cfg(feature = "multipart")]
+            Kind::Chunks(_) => None,
         }
     }
 
@@ -111,6 +113,20 @@ impl Body {
                 self.buffer()
             }
             Kind::Bytes(ref bytes) => Ok(bytes.as_ref()),
+            #[cfg(feature = "multipart")]
+            Kind::Chunks(ref chunks) => {
+                self.kind = Kind::Bytes(chunks.concat().into());
+                self.buffer()
+            }
+        }
+    }
+
+    /// A body of chunks in memory, which are sent as they are rather than
+    /// copied into a buffer.
+    #[cfg(feature = "multipart")]
+    pub(crate) fn chunks(chunks: Vec<Bytes>) -> Body {
+        Body {
+            kind: Kind::Chunks(chunks),
         }
     }
 
@@ -119,6 +135,16 @@ impl Body {
         match self.kind {
             Kind::Reader(_, len) => len,
             Kind::Bytes(ref bytes) => Some(bytes.len() as u64),
+            Kind::Chunks(ref chunks) => Some(chunks_len(chunks)),
+        }
+    }
+
+    /// The bytes of the body, if it is in memory in a single piece.
+    #[cfg(feature = "multipart")]
+    pub(crate) fn into_bytes(self) -> Result<Bytes, Body> {
+        match self.kind {
+            Kind::Bytes(bytes) => Ok(bytes),
+            kind => Err(Body { kind }),
         }
     }
 
@@ -127,6 +153,7 @@ impl Body {
         match self.kind {
             Kind::Reader(r, _) => Reader::Reader(r),
             Kind::Bytes(b) => Reader::Bytes(Cursor::new(b)),
+            Kind::Chunks(chunks) => Reader::Bytes(Cursor::new(chunks.concat().into())),
         }
     }
 
@@ -144,6 +171,13 @@ impl Body {
                 let len = chunk.len() as u64;
                 (None, async_impl::Body::reusable(chunk), Some(len))
             }
+            #[cfg(feature = "multipart")]
+            Kind::Chunks(chunks) => {
+                let len = chunks_len(&chunks);
+                let chunks = chunks.into_iter().map(Ok::<_, std::convert::Infallible>);
+                let body = async_impl::Body::stream(futures_util::stream::iter(chunks));
+                (None, body, Some(len))
+            }
         }
     }
 
@@ -155,6 +189,8 @@ impl Body {
 enum Kind {
     Reader(Box<dyn Read + Send>, Option<u64>),
     Bytes(Bytes),
+    #[cfg(feature = "multipart")]
+    Chunks(Vec<Bytes>),
 }
 
 impl Kind {
@@ -162,10 +198,17 @@ impl Kind {
         match self {
             Kind::Reader(..) => None,
             Kind::Bytes(v) => Some(Kind::Bytes(v.clone())),
+            #[cfg(feature = "multipart")]
+            Kind::Chunks(v) => Some(Kind::Chunks(v.clone())),
         }
     }
 }
 
+#[cfg(feature = "multipart")]
+fn chunks_len(chunks: &[Bytes]) -> u64 {
+    chunks.iter().map(|chunk| chunk.len() as u64).sum()
+}
+
 impl From<Vec<u8>> for Body {
     #[inline]
     fn from(v: Vec<u8>) -> Body {
@@ -224,6 +267,12 @@ impl fmt::Debug for Kind {
                 .field("length", &DebugLength(v))
                 .finish(),
             Kind::Bytes(ref v) => fmt::Debug::fmt(v, f),
+            #[cfg(feature = "multipart")]
+            Kind::Chunks(ref v) => f
+                .debug_struct("Chunks")
+                .field("count", &v.len())
+                .field("length", &chunks_len(v))
+                .finish(),
         }
     }
 }
@@ -367,6 +416,8 @@ pub(crate) fn read_to_string(mut body: Body) -> io::Result<String> {
     match body.kind {
         Kind::Reader(ref mut reader, _) => reader.read_to_string(&mut s),
         Kind::Bytes(ref mut bytes) => (&**bytes).read_to_string(&mut s),
+        #[cfg(feature = "multipart")]
+        Kind::Chunks(ref chunks) => (&*chunks.concat()).read_to_string(&mut s),
     }
     .map(|_| s)
 }
@@ -3,9 +3,10 @@ use std::any::Any;
 use std::convert::TryInto;
 use std::fmt;
 use std::future::Future;
+use std::mem;
 use std::net::IpAddr;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::task::{ready, Poll};
 use std::thread;
 use std::time::Duration;
@@ -25,6 +26,10 @@ use crate::connect::uds::UnixSocketProvider;
 use crate::connect::BoxedConnectorService;
 use crate::dns::Resolve;
 use crate::error::BoxError;
+use crate::host::HostConfig;
+use crate::observe::Counters;
+use crate::proxy_protocol::ProxyProtocol;
+use crate::redact::Redaction;
 #[cfg(feature = "__tls")]
 use crate::tls;
 #[cfg(feature = "__rustls")]
@@ -33,7 +38,7 @@ use crate::tls::CertificateRevocationList;
 use crate::Certificate;
 #[cfg(any(feature = "__native-tls", feature = "__rustls"))]
 use crate::Identity;
-use crate::{async_impl, header, redirect, IntoUrl, Method, Proxy};
+use crate::{async_impl, header, redirect, IntoUrl, Method, Proxy, Url};
 
 /// A `Client` to make Requests with.
 ///
@@ -80,6 +85,8 @@ pub struct Client {
 pub struct ClientBuilder {
     inner: async_impl::ClientBuilder,
     timeout: Timeout,
+    runtime: Runtime,
+    lazy_runtime: bool,
 }
 
 impl Default for ClientBuilder {
@@ -96,6 +103,31 @@ impl ClientBuilder {
         ClientBuilder {
             inner: async_impl::ClientBuilder::new(),
             timeout: Timeout::default(),
+            runtime: Runtime::Dedicated,
+            lazy_runtime: false,
+        }
+    }
+
+    /// Constructs a new `ClientBuilder` with the settings of a [`ClientConfig`].
+    ///
+    /// The `timeout` of `config` replaces the default timeout of 30 seconds
+    /// of the blocking client.
+    ///
+    /// See [`reqwest::ClientBuilder::from_config`](crate::ClientBuilder::from_config)
+    /// for more.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `client-config` feature to be enabled.
+    ///
+    /// [`ClientConfig`]: crate::client_config::ClientConfig
+    #[cfg(feature = "client-config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client-config")))]
+    pub fn from_config(config: &crate::client_config::ClientConfig) -> Self {
+        ClientBuilder {
+            inner: async_impl::ClientBuilder::from_config(config),
+            timeout: Timeout(config.timeout.or(Timeout::default().0)),
+            ..ClientBuilder::new()
         }
     }
 }
@@ -106,11 +138,14 @@ impl ClientBuilder {
     /// # Errors
     ///
     /// This method fails if TLS backend cannot be initialized, or the resolver
-    /// cannot load the system configuration.
+    /// cannot load the system configuration. With
+    /// [`lazy_runtime()`](ClientBuilder::lazy_runtime), these errors are
+    /// returned by the first request instead.
     ///
     /// # Panics
     ///
-    /// This method panics if called from within an async runtime. See docs on
+    /// This method panics if called from within an async runtime, unless
+    /// [`lazy_runtime()`](ClientBuilder::lazy_runtime) is enabled. See docs on
     /// [`reqwest::blocking`][crate::blocking] for details.
     pub fn build(self) -> crate::Result<Client> {
         ClientHandle::new(self).map(|handle| Client { inner: handle })
@@ -146,6 +181,13 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.user_agent(value))
     }
 
+    /// Sets the base URL that relative URLs of requests are resolved against.
+    ///
+    /// See [`reqwest::ClientBuilder::base_url`](crate::ClientBuilder::base_url).
+    pub fn base_url<U: IntoUrl>(self, url: U) -> ClientBuilder {
+        self.with_inner(move |inner| inner.base_url(url))
+    }
+
     /// Sets the default headers for every request.
     ///
     /// # Example
@@ -174,6 +216,50 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.default_headers(headers))
     }
 
+    /// Sets the default query parameters for every request.
+    ///
+    /// They are appended to the URL of each request, unless it already has a
+    /// parameter with the same name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn doc() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::blocking::Client::builder()
+    ///     .default_query(&[("api_key", "secret")])
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn default_query<K, V>(self, query: &[(K, V)]) -> ClientBuilder
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.with_inner(move |inner| inner.default_query(query))
+    }
+
+    /// Override settings of the client for the requests to one host.
+    ///
+    /// See [`reqwest::ClientBuilder::for_host`](crate::ClientBuilder::for_host).
+    /// The timeout of a host can only make requests time out sooner: the
+    /// [`timeout`](ClientBuilder::timeout) of the client still bounds every
+    /// blocking request.
+    pub fn for_host<F>(self, host: &str, configure: F) -> ClientBuilder
+    where
+        F: FnOnce(HostConfig) -> HostConfig,
+    {
+        self.with_inner(move |inner| inner.for_host(host, configure))
+    }
+
+    /// Set the default priority of requests, with the `Priority` header of
+    /// RFC 9218.
+    ///
+    /// See [`reqwest::ClientBuilder::priority`](crate::ClientBuilder::priority).
+    pub fn priority(self, urgency: u8, incremental: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.priority(urgency, incremental))
+    }
+
     /// Enable a persistent cookie store for the client.
     ///
     /// Cookies received in responses will be preserved and included in
@@ -297,6 +383,122 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.deflate(enable))
     }
 
+    /// Enable automatic `Content-Digest` computation and verification.
+    ///
+    /// If turned on:
+    ///
+    /// - When sending a request with a body, and the request doesn't already
+    ///   contain a `Content-Digest`, a `sha-256` digest of the body is added.
+    /// - When receiving a response with a `Content-Digest` header, or one
+    ///   declared as a trailer, the body is hashed as it is read. If the
+    ///   digest doesn't match, reading the body returns an error.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `content-digest` feature to be enabled.
+    #[cfg(feature = "content-digest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "content-digest")))]
+    pub fn content_digest(self, enable: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.content_digest(enable))
+    }
+
+    /// Cache responses in a store.
+    ///
+    /// See [`reqwest::ClientBuilder::cache`](crate::ClientBuilder::cache).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache<S: crate::cache::Store + 'static>(self, store: Arc<S>) -> ClientBuilder {
+        self.with_inner(|inner| inner.cache(store))
+    }
+
+    /// Serve stale cached responses while revalidating them in the background.
+    ///
+    /// See [`reqwest::ClientBuilder::cache_stale_while_revalidate`](crate::ClientBuilder::cache_stale_while_revalidate).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_stale_while_revalidate(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.cache_stale_while_revalidate(enabled))
+    }
+
+    /// Serve stale cached responses when the server fails.
+    ///
+    /// See [`reqwest::ClientBuilder::cache_stale_if_error`](crate::ClientBuilder::cache_stale_if_error).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_stale_if_error(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.cache_stale_if_error(enabled))
+    }
+
+    /// Make conditional requests with the validators of earlier responses.
+    ///
+    /// See [`reqwest::ClientBuilder::conditional_requests`](crate::ClientBuilder::conditional_requests).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn conditional_requests<S: crate::cache::Store + 'static>(
+        self,
+        store: Arc<S>,
+        not_modified: crate::cache::NotModified,
+    ) -> ClientBuilder {
+        self.with_inner(|inner| inner.conditional_requests(store, not_modified))
+    }
+
+    /// Sign every request with HTTP Message Signatures.
+    ///
+    /// See the [`signature`](crate::signature) module for more details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `message-signatures` feature to be enabled.
+    #[cfg(feature = "message-signatures")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "message-signatures")))]
+    pub fn message_signer(self, signer: crate::signature::Signer) -> ClientBuilder {
+        self.with_inner(|inner| inner.message_signer(signer))
+    }
+
+    /// Authorize the requests to `host` with a bearer token from `provider`.
+    ///
+    /// See the [`auth`](crate::auth) module for more details.
+    pub fn bearer_token_provider<P>(self, host: &str, provider: P) -> ClientBuilder
+    where
+        P: crate::auth::TokenProvider + 'static,
+    {
+        self.with_inner(|inner| inner.bearer_token_provider(host, provider))
+    }
+
+    /// Propagate the current W3C trace context with every request.
+    ///
+    /// See [`reqwest::ClientBuilder::propagate_trace_context`](crate::ClientBuilder::propagate_trace_context).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tracing` feature to be enabled.
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    pub fn propagate_trace_context<F>(self, current: F) -> ClientBuilder
+    where
+        F: Fn() -> Option<crate::trace::TraceContext> + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.propagate_trace_context(current))
+    }
+
     /// Disable auto response body gzip decompression.
     ///
     /// This method exists even if the optional `gzip` feature is not enabled.
@@ -333,6 +535,34 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.no_deflate())
     }
 
+    /// Decode compressed response bodies of at least `min_len` bytes, or of
+    /// an unknown length, on tokio's blocking thread pool.
+    ///
+    /// Default is off.
+    ///
+    /// # Optional
+    ///
+    /// This requires at least one of the optional `gzip`, `brotli`, `zstd`
+    /// or `deflate` features to be enabled.
+    #[cfg(any(
+        feature = "gzip",
+        feature = "brotli",
+        feature = "zstd",
+        feature = "deflate"
+    ))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "gzip",
+            feature = "brotli",
+            feature = "zstd",
+            feature = "deflate"
+        )))
+    )]
+    pub fn offload_decompression(self, min_len: u64) -> ClientBuilder {
+        self.with_inner(|inner| inner.offload_decompression(min_len))
+    }
+
     // Redirect options
 
     /// Set a `redirect::Policy` for this client.
@@ -408,6 +638,14 @@ impl ClientBuilder {
         }
     }
 
+    /// Set a policy to retry opening connections which were refused, or
+    /// which timed out.
+    ///
+    /// Default is to attempt each connection once.
+    pub fn connect_retry(self, policy: crate::retry::Connect) -> ClientBuilder {
+        self.with_inner(move |inner| inner.connect_retry(policy))
+    }
+
     /// Set whether connections should emit verbose logs.
     ///
     /// Enabling this option will emit [log][] messages at the `TRACE` level
@@ -418,6 +656,53 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.connection_verbose(verbose))
     }
 
+    /// Set how URLs and headers are shown in logs and errors.
+    ///
+    /// See the [`redact`](crate::redact) module for details.
+    pub fn redaction(self, policy: crate::redact::Policy) -> ClientBuilder {
+        self.with_inner(move |inner| inner.redaction(policy))
+    }
+
+    /// Set an observer to be notified of connection events.
+    ///
+    /// See the [`observe`](crate::observe) module for the events.
+    pub fn connection_events<O>(self, observer: O) -> ClientBuilder
+    where
+        O: crate::observe::ConnectionObserver,
+    {
+        self.with_inner(move |inner| inner.connection_events(observer))
+    }
+
+    /// Set an observer to be shown the messages sent and received.
+    ///
+    /// See [`WireObserver`](crate::observe::WireObserver) for details.
+    pub fn wire_events<O>(self, observer: O) -> ClientBuilder
+    where
+        O: crate::observe::WireObserver,
+    {
+        self.with_inner(move |inner| inner.wire_events(observer))
+    }
+
+    /// Set an observer to be shown each completed request.
+    ///
+    /// See [`AuditObserver`](crate::observe::AuditObserver) for details.
+    pub fn audit<O>(self, observer: O) -> ClientBuilder
+    where
+        O: crate::observe::AuditObserver,
+    {
+        self.with_inner(move |inner| inner.audit(observer))
+    }
+
+    /// Set a callback to be shown each request which fails.
+    ///
+    /// See [`FailedRequest`](crate::observe::FailedRequest) for details.
+    pub fn on_error<F>(self, callback: F) -> ClientBuilder
+    where
+        F: Fn(&crate::observe::FailedRequest<'_>) + Send + Sync + 'static,
+    {
+        self.with_inner(move |inner| inner.on_error(callback))
+    }
+
     // HTTP options
 
     /// Set an optional timeout for idle sockets being kept-alive.
@@ -437,6 +722,37 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.pool_max_idle_per_host(max))
     }
 
+    /// Limit the number of requests in flight in total.
+    ///
+    /// See [`ClientBuilder::max_in_flight`](crate::ClientBuilder::max_in_flight).
+    pub fn max_in_flight(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.max_in_flight(max))
+    }
+
+    /// Limit the number of requests in flight to each host.
+    ///
+    /// See [`ClientBuilder::max_in_flight_per_host`](crate::ClientBuilder::max_in_flight_per_host).
+    pub fn max_in_flight_per_host(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.max_in_flight_per_host(max))
+    }
+
+    /// Set how long a request waits for the number of requests in flight
+    /// to drop under the limits.
+    ///
+    /// Default is no timeout.
+    pub fn in_flight_queue_timeout(self, timeout: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.in_flight_queue_timeout(timeout))
+    }
+
+    /// Stop sending requests to the hosts which keep failing.
+    ///
+    /// See [`circuit`](crate::circuit) for how failures are counted.
+    ///
+    /// By default, there is no circuit breaker.
+    pub fn circuit_breaker(self, breaker: crate::circuit::Breaker) -> ClientBuilder {
+        self.with_inner(move |inner| inner.circuit_breaker(breaker))
+    }
+
     /// Send headers as title case instead of lowercase.
     pub fn http1_title_case_headers(self) -> ClientBuilder {
         self.with_inner(|inner| inner.http1_title_case_headers())
@@ -465,6 +781,28 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.http1_allow_spaces_after_header_name_in_responses(value))
     }
 
+    /// Set the maximum number of headers of HTTP/1 responses.
+    ///
+    /// Default is 100.
+    pub fn http1_max_headers(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.http1_max_headers(max))
+    }
+
+    /// Set the maximum size of the read buffer of HTTP/1 connections, which
+    /// limits the size of the head of a response.
+    ///
+    /// The minimum is 8192 bytes. Default is about 400 KiB.
+    pub fn http1_max_buf_size(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.http1_max_buf_size(max))
+    }
+
+    /// Set whether HTTP/1 connections write requests with vectored writes.
+    ///
+    /// Default is to use vectored writes if the connection supports them.
+    pub fn http1_writev(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.http1_writev(enabled))
+    }
+
     /// Only use HTTP/1.
     pub fn http1_only(self) -> ClientBuilder {
         self.with_inner(|inner| inner.http1_only())
@@ -721,6 +1059,28 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.interface(interface))
     }
 
+    cfg_interface_index! {
+        /// Bind to the interface with the specified index.
+        ///
+        /// See [`reqwest::ClientBuilder::interface_index`](crate::ClientBuilder::interface_index).
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// let client = reqwest::blocking::Client::builder()
+        ///     .interface_index(1)
+        ///     .build().unwrap();
+        /// ```
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index(self, index: u32) -> ClientBuilder {
+            self.with_inner(move |inner| inner.interface_index(index))
+        }
+    }
+
     /// Set that all sockets have `SO_KEEPALIVE` set with the supplied duration.
     ///
     /// If `None`, the option will not be set.
@@ -765,6 +1125,18 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.tcp_user_timeout(val))
     }
 
+    /// Send a PROXY protocol header at the start of each connection.
+    ///
+    /// See the [`proxy_protocol`](crate::proxy_protocol) module for details.
+    ///
+    /// Default is `None`.
+    pub fn proxy_protocol<P>(self, header: P) -> ClientBuilder
+    where
+        P: Into<Option<ProxyProtocol>>,
+    {
+        self.with_inner(move |inner| inner.proxy_protocol(header))
+    }
+
     // Alt Transports
 
     /// Set that all connections will use this Unix socket.
@@ -1171,6 +1543,52 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.https_only(enabled))
     }
 
+    /// Upgrade the `http://` URLs of the domains in an HSTS preload list to
+    /// `https://`, before sending anything.
+    ///
+    /// See [`reqwest::ClientBuilder::hsts_preload`](crate::ClientBuilder::hsts_preload).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hsts-preload` feature to be enabled.
+    #[cfg(feature = "hsts-preload")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hsts-preload")))]
+    pub fn hsts_preload(self, preload: crate::hsts::Preload) -> ClientBuilder {
+        self.with_inner(move |inner| inner.hsts_preload(preload))
+    }
+
+    /// Check the `Content-Type` of responses before decoding their bodies.
+    ///
+    /// With this enabled, [`Response::json`](crate::blocking::Response::json)
+    /// refuses a response that isn't `application/json` or a `+json` type,
+    /// such as an HTML error page, and the other typed decoders refuse
+    /// responses without a `Content-Type`.
+    ///
+    /// Defaults to false.
+    pub fn strict_content_type(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.strict_content_type(enabled))
+    }
+
+    /// Convert international domain names of URLs to their ASCII form.
+    ///
+    /// See [`reqwest::ClientBuilder::idna`](crate::ClientBuilder::idna).
+    pub fn idna(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.idna(enabled))
+    }
+
+    /// Answer the requests for `file://` URLs with local files.
+    ///
+    /// See [`reqwest::ClientBuilder::file_urls`](crate::ClientBuilder::file_urls).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `file` feature to be enabled.
+    #[cfg(feature = "file")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file")))]
+    pub fn file_urls(self, enabled: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.file_urls(enabled))
+    }
+
     /// Override DNS resolution for specific domains to a particular IP address.
     ///
     /// Set the port to `0` to use the conventional port for the given scheme (e.g. 80 for http).
@@ -1196,6 +1614,19 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.dns_resolver(resolver))
     }
 
+    /// Set the order in which to try the addresses a name resolves to.
+    ///
+    /// See [`dns::Balance`](crate::dns::Balance) for the strategies.
+    pub fn dns_balance(self, balance: crate::dns::Balance) -> ClientBuilder {
+        self.with_inner(move |inner| inner.dns_balance(balance))
+    }
+
+    /// Try the addresses which recently failed to accept a connection after
+    /// the other addresses of their name, for `duration`.
+    pub fn avoid_failed_addresses(self, duration: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.avoid_failed_addresses(duration))
+    }
+
     /// Adds a new Tower [`Layer`](https://docs.rs/tower/latest/tower/trait.Layer.html) to the
     /// base connector [`Service`](https://docs.rs/tower/latest/tower/trait.Service.html) which
     /// is responsible for connection establishment.
@@ -1225,6 +1656,97 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.connector_layer(layer))
     }
 
+    /// Replaces the internal HTTP client with a custom Tower
+    /// [`Service`](https://docs.rs/tower/latest/tower/trait.Service.html).
+    ///
+    /// The service runs on the blocking client's internal runtime. See
+    /// [`crate::ClientBuilder::http_service`] for more details.
+    pub fn http_service<S>(self, service: S) -> ClientBuilder
+    where
+        S: Service<http::Request<async_impl::Body>, Response = http::Response<async_impl::Body>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.with_inner(|inner| inner.http_service(service))
+    }
+
+    /// Send the requests for URLs of a custom scheme to a service.
+    ///
+    /// The service runs on the blocking client's internal runtime. See
+    /// [`crate::ClientBuilder::scheme_handler`] for more details.
+    pub fn scheme_handler<S>(self, scheme: &str, service: S) -> ClientBuilder
+    where
+        S: Service<http::Request<async_impl::Body>, Response = http::Response<async_impl::Body>>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.with_inner(|inner| inner.scheme_handler(scheme, service))
+    }
+
+    /// Use an existing Tokio runtime, instead of starting a new one.
+    ///
+    /// By default, each blocking `Client` starts a thread running its own
+    /// runtime. With this option, the client's requests run on the runtime
+    /// of the given handle instead, and no thread is started.
+    ///
+    /// The blocking methods of the client must still be called from outside
+    /// of an async context, such as from a plain thread, inside
+    /// [`tokio::task::spawn_blocking`], or inside
+    /// [`tokio::task::block_in_place`][block_in_place] on a multi-threaded
+    /// runtime.
+    ///
+    /// Requests sent once the runtime has been shut down fail with an error,
+    /// as do the requests it was running.
+    ///
+    /// [block_in_place]: https://docs.rs/tokio/1/tokio/task/fn.block_in_place.html
+    pub fn runtime_handle(mut self, handle: tokio::runtime::Handle) -> ClientBuilder {
+        self.runtime = Runtime::Handle(handle);
+        self
+    }
+
+    /// Use a runtime shared with other blocking clients.
+    ///
+    /// By default, each blocking `Client` starts a thread running its own
+    /// runtime. With this option, the client instead uses a single
+    /// background thread that is started by the first client that needs it,
+    /// and kept for the rest of the process. This avoids the cost of a
+    /// thread and runtime when creating many short-lived clients.
+    ///
+    /// This replaces a runtime set with
+    /// [`runtime_handle()`](ClientBuilder::runtime_handle).
+    ///
+    /// Default is `false`.
+    pub fn shared_runtime(mut self, enable: bool) -> ClientBuilder {
+        self.runtime = if enable {
+            Runtime::Shared
+        } else {
+            Runtime::Dedicated
+        };
+        self
+    }
+
+    /// Start the client's runtime and build its inner client on the first
+    /// request, instead of in [`build()`](ClientBuilder::build).
+    ///
+    /// This makes building a `Client` cheap, and allows doing so from within
+    /// an async runtime, such as when it is stored in a struct. Any error
+    /// from starting the client is then returned by the first request, and
+    /// later requests fail with the same error.
+    ///
+    /// Default is `false`.
+    pub fn lazy_runtime(mut self, enable: bool) -> ClientBuilder {
+        self.lazy_runtime = enable;
+        self
+    }
+
     // private
 
     fn with_inner<F>(mut self, func: F) -> ClientBuilder
@@ -1234,6 +1756,15 @@ impl ClientBuilder {
         self.inner = func(self.inner);
         self
     }
+
+    fn duplicate(&self) -> ClientBuilder {
+        ClientBuilder {
+            inner: self.inner.duplicate(),
+            timeout: self.timeout,
+            runtime: self.runtime.clone(),
+            lazy_runtime: self.lazy_runtime,
+        }
+    }
 }
 
 impl From<async_impl::ClientBuilder> for ClientBuilder {
@@ -1241,6 +1772,8 @@ impl From<async_impl::ClientBuilder> for ClientBuilder {
         Self {
             inner: builder,
             timeout: Timeout::default(),
+            runtime: Runtime::Dedicated,
+            lazy_runtime: false,
         }
     }
 }
@@ -1275,6 +1808,30 @@ impl Client {
         ClientBuilder::new()
     }
 
+    /// Creates a `ClientBuilder` with the configuration of this `Client`.
+    ///
+    /// See [`Client::to_builder`](crate::Client::to_builder) for what the
+    /// clients built from it share with this one.
+    pub fn to_builder(&self) -> ClientBuilder {
+        let builder = &self.inner.builder;
+        #[cfg(feature = "__tls")]
+        {
+            let startup = self.inner.startup.lock().expect("startup lock poisoned");
+            if let Startup::Started(ref inner) = *startup {
+                // with the certificates of the last `reload_tls()`
+                if let Some(ref client) = inner.client {
+                    return ClientBuilder {
+                        inner: client.to_builder(),
+                        timeout: builder.timeout,
+                        runtime: builder.runtime.clone(),
+                        lazy_runtime: builder.lazy_runtime,
+                    };
+                }
+            }
+        }
+        builder.duplicate()
+    }
+
     /// Convenience method to make a `GET` request to a URL.
     ///
     /// # Errors
@@ -1329,6 +1886,15 @@ impl Client {
         self.request(Method::HEAD, url)
     }
 
+    /// Convenience method to make an `OPTIONS` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever supplied `Url` cannot be parsed.
+    pub fn options<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::OPTIONS, url)
+    }
+
     /// Start building a `Request` with the `Method` and `Url`.
     ///
     /// Returns a `RequestBuilder`, which will allow setting headers and
@@ -1338,7 +1904,8 @@ impl Client {
     ///
     /// This method fails whenever supplied `Url` cannot be parsed.
     pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
-        let req = url.into_url().map(move |url| Request::new(method, url));
+        let req = crate::into_url::request_url(url, self.inner.base_url.as_ref(), self.inner.idna)
+            .map(move |url| Request::new(method, url));
         RequestBuilder::new(self.clone(), req)
     }
 
@@ -1357,6 +1924,100 @@ impl Client {
     pub fn execute(&self, request: Request) -> crate::Result<Response> {
         self.inner.execute_request(request)
     }
+
+    /// Executes several `Request`s concurrently.
+    ///
+    /// The requests are sent on the client's runtime, with at most `limit`
+    /// of them in flight at once. A `limit` of `0` is treated as `1`.
+    ///
+    /// The results are returned in the same order as the requests. Each
+    /// request keeps its own timeout, falling back to the client's timeout.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::blocking::Client::new();
+    /// let requests = vec![
+    ///     client.get("http://httpbin.org/get").build()?,
+    ///     client.get("http://httpbin.org/ip").build()?,
+    /// ];
+    /// for result in client.send_all(requests, 2) {
+    ///     println!("{}", result?.status());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_all(&self, requests: Vec<Request>, limit: usize) -> Vec<crate::Result<Response>> {
+        self.inner.execute_all(requests, limit)
+    }
+
+    /// Creates a blocking `Client` that sends its requests through an
+    /// existing async [`Client`](crate::Client).
+    ///
+    /// The two clients share their configuration, connection pool and
+    /// cookie store. Timeouts are the ones configured on the async client.
+    ///
+    /// The requests run on the runtime of `handle`, which must be the one
+    /// the async client is used on: the connections in the shared pool
+    /// belong to the runtime that opened them, and stop working once it is
+    /// shut down. As with [`ClientBuilder::runtime_handle`], requests must
+    /// be sent from outside of that runtime, and fail with an error once it
+    /// has been shut down.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = reqwest::Client::new();
+    /// let handle = tokio::runtime::Handle::current();
+    /// let blocking = reqwest::blocking::Client::from_async(client.clone(), handle);
+    /// let res = tokio::task::spawn_blocking(move || {
+    ///     blocking.get("http://httpbin.org/get").send()
+    /// })
+    /// .await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_async(client: async_impl::Client, handle: tokio::runtime::Handle) -> Client {
+        Client {
+            inner: ClientHandle::from_async(client, handle),
+        }
+    }
+
+    /// Returns what this `Client` has transferred so far.
+    ///
+    /// See [`TransferStats`](crate::observe::TransferStats) for what is
+    /// counted.
+    pub fn transfer_stats(&self) -> crate::observe::TransferStats {
+        self.inner.counters.stats()
+    }
+
+    /// Replace the root certificates or the identity of this `Client`.
+    ///
+    /// With a lazy runtime, this starts it.
+    ///
+    /// See [`reqwest::Client::reload_tls`](crate::Client::reload_tls) for
+    /// more.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "default-tls", feature = "native-tls", feature = "rustls")))
+    )]
+    pub fn reload_tls(&self, reload: tls::TlsReload) -> crate::Result<()> {
+        let inner = self.inner.started()?;
+        let client = inner.client.as_ref().expect("client is running");
+        client.reload_tls(reload)
+    }
+
+    pub(crate) fn redaction(&self) -> Option<&Redaction> {
+        self.inner.redaction.as_ref()
+    }
 }
 
 impl fmt::Debug for Client {
@@ -1378,7 +2039,42 @@ impl fmt::Debug for ClientBuilder {
 #[derive(Clone)]
 struct ClientHandle {
     timeout: Timeout,
-    inner: Arc<InnerClientHandle>,
+    redaction: Option<Redaction>,
+    base_url: Option<Url>,
+    idna: bool,
+    counters: Arc<Counters>,
+    startup: Arc<Mutex<Startup>>,
+    // the configuration of the client, for `Client::to_builder()`
+    builder: Arc<ClientBuilder>,
+}
+
+/// The state of a client's runtime, which is started on the first request
+/// when using `lazy_runtime()`.
+enum Startup {
+    Pending(Box<Launch>),
+    Started(Arc<InnerClientHandle>),
+    Failed(String),
+}
+
+/// What is needed to start a client's runtime.
+struct Launch {
+    runtime: Runtime,
+    source: Source,
+}
+
+/// Where the async client that runs the requests comes from.
+enum Source {
+    Builder(Box<async_impl::ClientBuilder>),
+    Client(async_impl::Client),
+}
+
+impl Source {
+    fn build(self) -> crate::Result<async_impl::Client> {
+        match self {
+            Source::Builder(builder) => builder.build(),
+            Source::Client(client) => Ok(client),
+        }
+    }
 }
 
 type OneshotResponse = oneshot::Sender<crate::Result<async_impl::Response>>;
@@ -1387,15 +2083,26 @@ type ThreadSender = mpsc::UnboundedSender<(async_impl::Request, OneshotResponse)
 struct InnerClientHandle {
     tx: Option<ThreadSender>,
     thread: Option<thread::JoinHandle<()>>,
+    /// The client running on the runtime, to reload its TLS configuration.
+    #[cfg(feature = "__tls")]
+    client: Option<async_impl::Client>,
 }
 
 impl Drop for InnerClientHandle {
     fn drop(&mut self) {
-        let id = self
-            .thread
-            .as_ref()
-            .map(|h| h.thread().id())
-            .expect("thread not dropped yet");
+        // The connections of the client must be dropped while its runtime
+        // is still running.
+        #[cfg(feature = "__tls")]
+        self.client.take();
+
+        let id = match self.thread {
+            Some(ref h) => h.thread().id(),
+            None => {
+                // On a provided runtime, closing the channel ends the task.
+                self.tx.take();
+                return;
+            }
+        };
 
         trace!("closing runtime thread ({id:?})");
         self.tx.take();
@@ -1405,12 +2112,132 @@ impl Drop for InnerClientHandle {
     }
 }
 
+impl InnerClientHandle {
+    /// Hand a request to the runtime of the client.
+    fn send(&self, req: async_impl::Request, tx: OneshotResponse) -> crate::Result<()> {
+        match self.tx {
+            Some(ref sender) if sender.send((req, tx)).is_ok() => Ok(()),
+            _ => Err(self.stopped()),
+        }
+    }
+
+    /// The error of a request that the runtime of the client dropped.
+    fn stopped(&self) -> crate::Error {
+        if self.thread.is_some() {
+            // the thread of the client only stops once the client is dropped
+            event_loop_panicked();
+        }
+        // the runtime belongs to the caller, who may shut it down
+        crate::error::request(RuntimeShutDown)
+    }
+}
+
 impl ClientHandle {
     fn new(builder: ClientBuilder) -> crate::Result<ClientHandle> {
+        let copy = Arc::new(builder.duplicate());
         let timeout = builder.timeout;
-        let builder = builder.inner;
+        let redaction = builder.inner.configured_redaction().cloned();
+        let base_url = builder.inner.configured_base_url().cloned();
+        let idna = builder.inner.configured_idna();
+        let counters = builder.inner.configured_counters().clone();
+        let launch = Launch {
+            runtime: builder.runtime,
+            source: Source::Builder(Box::new(builder.inner)),
+        };
+        let startup = if builder.lazy_runtime {
+            Startup::Pending(Box::new(launch))
+        } else {
+            Startup::Started(ClientHandle::start(launch)?)
+        };
+        Ok(ClientHandle {
+            timeout,
+            redaction,
+            base_url,
+            idna,
+            counters,
+            startup: Arc::new(Mutex::new(startup)),
+            builder: copy,
+        })
+    }
+
+    fn from_async(client: async_impl::Client, handle: tokio::runtime::Handle) -> ClientHandle {
+        let redaction = client.redaction().cloned();
+        let base_url = client.base_url().cloned();
+        let idna = client.idna();
+        let counters = client.counters().clone();
+        let builder = Arc::new(ClientBuilder {
+            inner: client.to_builder(),
+            timeout: Timeout(None),
+            runtime: Runtime::Handle(handle.clone()),
+            lazy_runtime: true,
+        });
+        let launch = Launch {
+            runtime: Runtime::Handle(handle),
+            source: Source::Client(client),
+        };
+        ClientHandle {
+            timeout: Timeout(None),
+            redaction,
+            base_url,
+            idna,
+            counters,
+            startup: Arc::new(Mutex::new(Startup::Pending(Box::new(launch)))),
+            builder,
+        }
+    }
+
+    /// Get the handle of the client's runtime, starting it if needed.
+    fn started(&self) -> crate::Result<Arc<InnerClientHandle>> {
+        let mut startup = self.startup.lock().expect("startup lock poisoned");
+        match &*startup {
+            Startup::Started(inner) => return Ok(inner.clone()),
+            Startup::Failed(msg) => return Err(crate::error::builder(msg.clone())),
+            Startup::Pending(_) => (),
+        }
+
+        let launch = match mem::replace(&mut *startup, Startup::Failed(String::new())) {
+            Startup::Pending(launch) => *launch,
+            _ => unreachable!("startup was pending"),
+        };
+        match ClientHandle::start(launch) {
+            Ok(inner) => {
+                *startup = Startup::Started(inner.clone());
+                Ok(inner)
+            }
+            Err(err) => {
+                *startup = Startup::Failed(failure_message(&err));
+                Err(err)
+            }
+        }
+    }
+
+    fn start(launch: Launch) -> crate::Result<Arc<InnerClientHandle>> {
+        let runtime = match launch.runtime {
+            Runtime::Dedicated => None,
+            Runtime::Shared => Some(shared_runtime()?),
+            Runtime::Handle(handle) => Some(handle),
+        };
+        let source = launch.source;
         let (tx, rx) = mpsc::unbounded_channel::<(async_impl::Request, OneshotResponse)>();
-        let (spawn_tx, spawn_rx) = oneshot::channel::<crate::Result<()>>();
+
+        if let Some(runtime) = runtime {
+            let client = {
+                let _enter = runtime.enter();
+                source.build()?
+            };
+            #[cfg(feature = "__tls")]
+            let running = client.clone();
+            runtime.spawn(dispatch(client, rx));
+
+            return Ok(Arc::new(InnerClientHandle {
+                tx: Some(tx),
+                thread: None,
+                #[cfg(feature = "__tls")]
+                client: Some(running),
+            }));
+        }
+
+        let (spawn_tx, spawn_rx) = oneshot::channel::<crate::Result<async_impl::Client>>();
         let handle = thread::Builder::new()
             .name("reqwest-internal-sync-runtime".into())
             .spawn(move || {
@@ -1430,7 +2257,7 @@ impl ClientHandle {
                 };
 
                 let f = async move {
-                    let client = match builder.build() {
+                    let client = match source.build() {
                         Err(e) => {
                             if let Err(e) = spawn_tx.send(Err(e)) {
                                 error!("Failed to communicate client creation failure: {e:?}");
@@ -1439,19 +2266,12 @@ impl ClientHandle {
                         }
                         Ok(v) => v,
                     };
-                    if let Err(e) = spawn_tx.send(Ok(())) {
+                    if let Err(e) = spawn_tx.send(Ok(client.clone())) {
                         error!("Failed to communicate successful startup: {e:?}");
                         return;
                     }
 
-                    let mut rx = rx;
-
-                    while let Some((req, req_tx)) = rx.recv().await {
-                        let req_fut = client.execute(req);
-                        tokio::spawn(forward(req_fut, req_tx));
-                    }
-
-                    trace!("({:?}) Receiver is shutdown", thread::current().id());
+                    dispatch(client, rx).await;
                 };
 
                 trace!("({:?}) start runtime::block_on", thread::current().id());
@@ -1463,61 +2283,140 @@ impl ClientHandle {
             .map_err(crate::error::builder)?;
 
         // Wait for the runtime thread to start up...
-        match wait::timeout(spawn_rx, None) {
-            Ok(Ok(())) => (),
+        let running = match wait::timeout(spawn_rx, None) {
+            Ok(Ok(client)) => client,
             Ok(Err(err)) => return Err(err),
             Err(_canceled) => event_loop_panicked(),
-        }
+        };
+        #[cfg(not(feature = "__tls"))]
+        drop(running);
 
-        let inner_handle = Arc::new(InnerClientHandle {
+        Ok(Arc::new(InnerClientHandle {
             tx: Some(tx),
             thread: Some(handle),
-        });
-
-        Ok(ClientHandle {
-            timeout,
-            inner: inner_handle,
-        })
+            #[cfg(feature = "__tls")]
+            client: Some(running),
+        }))
     }
 
     fn execute_request(&self, req: Request) -> crate::Result<Response> {
+        let inner = self
+            .started()
+            .map_err(|err| self.redacted(err.with_url(req.url().clone())))?;
         let (tx, rx) = oneshot::channel();
         let (req, body) = req.into_async();
         let url = req.url().clone();
         let timeout = req.timeout().copied().or(self.timeout.0);
 
-        self.inner
-            .tx
-            .as_ref()
-            .expect("core thread exited early")
-            .send((req, tx))
-            .expect("core thread panicked");
+        if let Err(err) = inner.send(req, tx) {
+            return Err(self.redacted(err.with_url(url)));
+        }
 
+        let stopped = || inner.stopped();
         let result: Result<crate::Result<async_impl::Response>, wait::Waited<crate::Error>> =
             if let Some(body) = body {
                 let f = async move {
                     body.send().await?;
-                    rx.await.map_err(|_canceled| event_loop_panicked())
+                    rx.await.map_err(|_canceled| stopped())
                 };
                 wait::timeout(f, timeout)
             } else {
-                let f = async move { rx.await.map_err(|_canceled| event_loop_panicked()) };
+                let f = async move { rx.await.map_err(|_canceled| stopped()) };
                 wait::timeout(f, timeout)
             };
 
-        match result {
+        let result = match result {
             Ok(Err(err)) => Err(err.with_url(url)),
             Ok(Ok(res)) => Ok(Response::new(
                 res,
                 timeout,
-                KeepCoreThreadAlive(Some(self.inner.clone())),
+                KeepCoreThreadAlive(Some(inner)),
             )),
             Err(wait::Waited::TimedOut(e)) => Err(crate::error::request(e).with_url(url)),
             Err(wait::Waited::Inner(err)) => Err(err.with_url(url)),
+        };
+        result.map_err(|err| self.redacted(err))
+    }
+
+    /// Show the url of a blocking error with the policy of the client.
+    fn redacted(&self, err: crate::Error) -> crate::Error {
+        err.with_redaction(self.redaction.as_ref())
+    }
+
+    fn execute_all(&self, reqs: Vec<Request>, limit: usize) -> Vec<crate::Result<Response>> {
+        use futures_util::stream::{self, StreamExt};
+
+        let inner = match self.started() {
+            Ok(inner) => inner,
+            Err(err) => {
+                // each request fails with an error of its own
+                let msg = failure_message(&err);
+                let mut err = Some(err);
+                return reqs
+                    .into_iter()
+                    .map(|req| {
+                        let err = err
+                            .take()
+                            .unwrap_or_else(|| crate::error::builder(msg.clone()));
+                        Err(self.redacted(err.with_url(req.url().clone())))
+                    })
+                    .collect();
+            }
+        };
+
+        let all = stream::iter(reqs)
+            .map(|req| {
+                let (tx, rx) = oneshot::channel();
+                let (mut req, body) = req.into_async();
+                let url = req.url().clone();
+                let timeout = req.timeout().copied().or(self.timeout.0);
+                // Let the runtime enforce each deadline, since this thread
+                // waits on all of the requests at once.
+                *req.timeout_mut() = timeout;
+
+                let sent = inner.send(req, tx);
+
+                let handle = inner.clone();
+                let keep_alive = KeepCoreThreadAlive(Some(inner.clone()));
+                let redaction = self.redaction.clone();
+                async move {
+                    let res = async move {
+                        sent?;
+                        if let Some(body) = body {
+                            body.send().await?;
+                        }
+                        rx.await.map_err(|_canceled| handle.stopped())?
+                    }
+                    .await;
+
+                    match res {
+                        Ok(res) => Ok(Response::new(res, timeout, keep_alive)),
+                        Err(err) => Err(err.with_url(url).with_redaction(redaction.as_ref())),
+                    }
+                }
+            })
+            .buffered(limit.max(1))
+            .collect::<Vec<_>>();
+
+        match wait::timeout(async { Ok::<_, crate::Error>(all.await) }, None) {
+            Ok(results) => results,
+            Err(_) => unreachable!("waited without a timeout"),
         }
     }
 }
 
+async fn dispatch(
+    client: async_impl::Client,
+    mut rx: mpsc::UnboundedReceiver<(async_impl::Request, OneshotResponse)>,
+) {
+    while let Some((req, req_tx)) = rx.recv().await {
+        let req_fut = client.execute(req);
+        tokio::spawn(forward(req_fut, req_tx));
+    }
+
+    trace!("({:?}) Receiver is shutdown", thread::current().id());
+}
+
 async fn forward<F>(fut: F, mut tx: OneshotResponse)
 where
     F: Future<Output = crate::Result<async_impl::Response>>,
@@ -1543,6 +2442,49 @@ where
     // else request is canceled
 }
 
+/// Where a blocking client runs its requests.
+#[derive(Clone)]
+enum Runtime {
+    /// A runtime on a thread started for this client.
+    Dedicated,
+    /// The runtime from `shared_runtime()`.
+    Shared,
+    /// A runtime provided by the user.
+    Handle(tokio::runtime::Handle),
+}
+
+/// Get the runtime shared by all clients built with `shared_runtime(true)`,
+/// starting it on first use.
+fn shared_runtime() -> crate::Result<tokio::runtime::Handle> {
+    static SHARED: OnceLock<Result<tokio::runtime::Handle, String>> = OnceLock::new();
+
+    SHARED
+        .get_or_init(|| {
+            let (tx, rx) = std::sync::mpsc::channel();
+            thread::Builder::new()
+                .name("reqwest-shared-sync-runtime".into())
+                .spawn(move || {
+                    let rt = match tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                    {
+                        Ok(rt) => rt,
+                        Err(e) => {
+                            let _ = tx.send(Err(e.to_string()));
+                            return;
+                        }
+                    };
+                    let _ = tx.send(Ok(rt.handle().clone()));
+                    // Drive the clients' tasks for the rest of the process.
+                    rt.block_on(std::future::pending::<()>());
+                })
+                .map_err(|e| e.to_string())?;
+            rx.recv().map_err(|e| e.to_string())?
+        })
+        .clone()
+        .map_err(crate::error::builder)
+}
+
 #[derive(Clone, Copy)]
 struct Timeout(Option<Duration>);
 
@@ -1561,6 +2503,26 @@ impl KeepCoreThreadAlive {
     }
 }
 
+/// The message of a startup failure, kept to fail later requests with.
+fn failure_message(err: &crate::Error) -> String {
+    match std::error::Error::source(err) {
+        Some(source) => source.to_string(),
+        None => err.to_string(),
+    }
+}
+
+/// The runtime given with `runtime_handle()` was shut down.
+#[derive(Debug)]
+struct RuntimeShutDown;
+
+impl fmt::Display for RuntimeShutDown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("runtime shut down")
+    }
+}
+
+impl std::error::Error for RuntimeShutDown {}
+
 #[cold]
 #[inline(never)]
 fn event_loop_panicked() -> ! {
@@ -69,12 +69,15 @@ mod client;
 pub mod multipart;
 mod request;
 mod response;
+pub mod sse;
+mod upgrade;
 mod wait;
 
 pub use self::body::Body;
 pub use self::client::{Client, ClientBuilder};
 pub use self::request::{Request, RequestBuilder};
 pub use self::response::Response;
+pub use self::upgrade::Upgraded;
 
 /// Shortcut method to quickly make a *blocking* `GET` request.
 ///
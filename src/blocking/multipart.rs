@@ -42,6 +42,7 @@ use std::fs::File;
 use std::io::{self, Cursor, Read};
 use std::path::Path;
 
+use bytes::Bytes;
 use mime_guess::{self, Mime};
 
 use super::Body;
@@ -148,6 +149,58 @@ impl Form {
         Reader::new(self)
     }
 
+    /// The body of a request sending this form.
+    ///
+    /// If all its parts are in memory, the bytes of each part are sent as
+    /// they are, rather than copied by a reader into the buffers of the
+    /// request.
+    pub(crate) fn into_body(mut self) -> Body {
+        let in_memory = self
+            .inner
+            .fields
+            .iter()
+            .all(|(_, field)| field.value.as_bytes().is_some());
+        if in_memory {
+            return Body::chunks(self.chunks());
+        }
+        match self.compute_length() {
+            Some(length) => Body::sized(self.reader(), length),
+            None => Body::new(self.reader()),
+        }
+    }
+
+    // The boundary and headers of each part, in between the bytes of the
+    // parts, as `Reader` writes them.
+    fn chunks(self) -> Vec<Bytes> {
+        let FormParts {
+            boundary,
+            fields,
+            percent_encoding,
+            ..
+        } = self.inner;
+        let mut chunks = Vec::with_capacity(fields.len() * 2 + 1);
+        let mut head = Vec::new();
+        for (name, field) in fields {
+            head.extend_from_slice(b"--");
+            head.extend_from_slice(boundary.as_bytes());
+            head.extend_from_slice(b"\r\n");
+            head.extend_from_slice(&percent_encoding.encode_headers(&name, field.metadata()));
+            head.extend_from_slice(b"\r\n\r\n");
+            chunks.push(Bytes::from(std::mem::take(&mut head)));
+            chunks.push(field.value.into_bytes().expect("part is in memory"));
+            head.extend_from_slice(b"\r\n");
+        }
+        // According to https://tools.ietf.org/html/rfc2046#section-5.1.1
+        // the very last field has a special boundary
+        if !chunks.is_empty() {
+            head.extend_from_slice(b"--");
+            head.extend_from_slice(boundary.as_bytes());
+            head.extend_from_slice(b"--\r\n");
+            chunks.push(head.into());
+        }
+        chunks
+    }
+
     /// Produce a reader over the multipart form data.
     pub fn into_reader(self) -> impl Read {
         self.reader()
@@ -204,6 +257,9 @@ impl Part {
     /// Adds a generic reader.
     ///
     /// Does not set filename or mime.
+    ///
+    /// The reader is streamed to the connection as the request is sent,
+    /// rather than read into memory first.
     pub fn reader<T: Read + Send + 'static>(value: T) -> Part {
         Part::new(Body::new(value))
     }
@@ -211,6 +267,9 @@ impl Part {
     /// Adds a generic reader with known length.
     ///
     /// Does not set filename or mime.
+    ///
+    /// The reader is streamed to the connection as the request is sent,
+    /// rather than read into memory first.
     pub fn reader_with_length<T: Read + Send + 'static>(value: T, length: u64) -> Part {
         Part::new(Body::sized(value, length))
     }
@@ -466,6 +525,46 @@ mod tests {
         assert_eq!(length.unwrap(), expected.len() as u64);
     }
 
+    #[test]
+    fn into_body_in_memory() {
+        use http_body_util::BodyExt;
+
+        let bytes = vec![b'x'; 1024];
+        let ptr = bytes.as_ptr();
+        let mut form = Form::new()
+            .text("key1", "value1")
+            .part("key2", Part::bytes(bytes).file_name("filename"));
+        form.inner.boundary = "boundary".to_string();
+        let expected = format!(
+            "--boundary\r\n\
+             Content-Disposition: form-data; name=\"key1\"\r\n\r\n\
+             value1\r\n\
+             --boundary\r\n\
+             Content-Disposition: form-data; name=\"key2\"; filename=\"filename\"\r\n\r\n\
+             {}\r\n--boundary--\r\n",
+            "x".repeat(1024)
+        );
+
+        let (tx, body, length) = form.into_body().into_async();
+        assert!(tx.is_none());
+        assert_eq!(length, Some(expected.len() as u64));
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let chunks = rt.block_on(async {
+            let mut chunks = Vec::new();
+            let mut body = body;
+            while let Some(frame) = body.frame().await {
+                chunks.push(frame.unwrap().into_data().unwrap());
+            }
+            chunks
+        });
+        assert_eq!(chunks.concat(), expected.as_bytes());
+        // the bytes of the part are sent as they are, in a single chunk
+        assert!(chunks.iter().any(|chunk| chunk.as_ptr() == ptr));
+    }
+
     #[test]
     fn read_to_end_with_header() {
         let mut output = Vec::new();
@@ -1,9 +1,16 @@
 use std::convert::TryFrom;
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use http::{request::Parts, Request as HttpRequest, Version};
-#[cfg(any(feature = "query", feature = "form", feature = "json"))]
+#[cfg(any(
+    feature = "query",
+    feature = "form",
+    feature = "json",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "xml"
+))]
 use serde::Serialize;
 #[cfg(feature = "json")]
 use serde_json;
@@ -12,9 +19,9 @@ use super::body::{self, Body};
 #[cfg(feature = "multipart")]
 use super::multipart;
 use super::Client;
-#[cfg(any(feature = "multipart", feature = "form", feature = "json"))]
-use crate::header::CONTENT_TYPE;
-use crate::header::{HeaderMap, HeaderName, HeaderValue};
+use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use crate::redact::{self, Redaction};
+use crate::config::{EarlyHints, OnInformational, RequestConfig};
 use crate::{async_impl, Method, Url};
 
 /// A request which can be executed with `Client::execute()`.
@@ -115,6 +122,109 @@ impl Request {
         self.inner.timeout_mut()
     }
 
+    /// Get the deadline.
+    #[inline]
+    pub fn deadline(&self) -> Option<&Instant> {
+        self.inner.deadline()
+    }
+
+    /// Get a mutable reference to the deadline.
+    #[inline]
+    pub fn deadline_mut(&mut self) -> &mut Option<Instant> {
+        self.inner.deadline_mut()
+    }
+
+    /// Get the read timeout.
+    #[inline]
+    pub fn read_timeout(&self) -> Option<&Duration> {
+        self.inner.read_timeout()
+    }
+
+    /// Get a mutable reference to the read timeout.
+    #[inline]
+    pub fn read_timeout_mut(&mut self) -> &mut Option<Duration> {
+        self.inner.read_timeout_mut()
+    }
+
+    /// Get the header timeout.
+    #[inline]
+    pub fn header_timeout(&self) -> Option<&Duration> {
+        self.inner.header_timeout()
+    }
+
+    /// Get a mutable reference to the header timeout.
+    #[inline]
+    pub fn header_timeout_mut(&mut self) -> &mut Option<Duration> {
+        self.inner.header_timeout_mut()
+    }
+
+    /// Get the queue priority.
+    #[inline]
+    pub fn queue_priority(&self) -> Option<&crate::QueuePriority> {
+        self.inner.queue_priority()
+    }
+
+    /// Get a mutable reference to the queue priority.
+    #[inline]
+    pub fn queue_priority_mut(&mut self) -> &mut Option<crate::QueuePriority> {
+        self.inner.queue_priority_mut()
+    }
+
+    /// Get the raw request target.
+    #[inline]
+    pub fn raw_target(&self) -> Option<&http::uri::PathAndQuery> {
+        self.inner.raw_target()
+    }
+
+    /// Get a mutable reference to the raw request target.
+    #[inline]
+    pub fn raw_target_mut(&mut self) -> &mut Option<http::uri::PathAndQuery> {
+        self.inner.raw_target_mut()
+    }
+
+    cfg_interface_index! {
+        /// Get the index of the network interface the request is bound to.
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        #[inline]
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index(&self) -> Option<&u32> {
+            self.inner.interface_index()
+        }
+    }
+
+    cfg_interface_index! {
+        /// Get a mutable reference to the index of the network interface the
+        /// request is bound to.
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        #[inline]
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index_mut(&mut self) -> &mut Option<u32> {
+            self.inner.interface_index_mut()
+        }
+    }
+
+    /// Get the cache mode.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    #[inline]
+    pub fn cache_mode(&self) -> Option<&crate::cache::Mode> {
+        self.inner.cache_mode()
+    }
+
+    /// Get a mutable reference to the cache mode.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    #[inline]
+    pub fn cache_mode_mut(&mut self) -> &mut Option<crate::cache::Mode> {
+        self.inner.cache_mode_mut()
+    }
+
     /// Attempts to clone the `Request`.
     ///
     /// None is returned if a body is which can not be cloned. This can be because the body is a
@@ -149,12 +259,21 @@ impl Request {
             *req_async.body_mut() = Some(body);
             tx
         });
+        // The request is sent from the runtime thread, so remember which
+        // span it comes from.
+        #[cfg(feature = "tracing")]
+        req_async
+            .extensions_mut()
+            .insert(crate::trace::CallerSpan(tracing::Span::current()));
         (req_async, body)
     }
 }
 
 impl RequestBuilder {
-    pub(crate) fn new(client: Client, request: crate::Result<Request>) -> RequestBuilder {
+    pub(crate) fn new(client: Client, mut request: crate::Result<Request>) -> RequestBuilder {
+        if let (Ok(req), Some(redaction)) = (request.as_mut(), client.redaction()) {
+            req.inner.extensions_mut().insert(redaction.clone());
+        }
         let mut builder = RequestBuilder { client, request };
 
         let auth = builder
@@ -366,6 +485,155 @@ impl RequestBuilder {
         self
     }
 
+    /// Set a time by which this request must be done, whatever its
+    /// timeout.
+    ///
+    /// See [`reqwest::RequestBuilder::deadline`](crate::RequestBuilder::deadline).
+    pub fn deadline(mut self, deadline: Instant) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.deadline_mut() = Some(deadline);
+        }
+        self
+    }
+
+    /// Enables a read timeout.
+    ///
+    /// The timeout applies to each read operation, and resets after a
+    /// successful read. It affects only this request and overrides the read
+    /// timeout configured using `ClientBuilder::read_timeout()`.
+    ///
+    /// The connect timeout can only be set on the `Client`, since
+    /// connections are shared between requests.
+    pub fn read_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.read_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Enables a timeout for the response headers.
+    ///
+    /// The timeout applies from when the request is sent until the headers
+    /// of its response are received, including the time to connect and to
+    /// follow redirects, but not the time to read the response body. It is
+    /// bounded by the total timeout of the request.
+    pub fn header_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.header_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Set the priority of this request, when it waits for the number of
+    /// requests in flight to go under the limit of the client.
+    ///
+    /// See [`reqwest::RequestBuilder::queue_priority`](crate::RequestBuilder::queue_priority).
+    pub fn queue_priority(mut self, priority: crate::QueuePriority) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.queue_priority_mut() = Some(priority);
+        }
+        self
+    }
+
+    /// Send the path and query of this request as they are given, rather
+    /// than as they are in its URL.
+    ///
+    /// See [`reqwest::RequestBuilder::raw_target`](crate::RequestBuilder::raw_target).
+    pub fn raw_target(mut self, target: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            match async_impl::request::parse_raw_target(target) {
+                Ok(target) => *req.raw_target_mut() = Some(target),
+                Err(err) => self.request = Err(err),
+            }
+        }
+        self
+    }
+
+    cfg_interface_index! {
+        /// Bind the connection of this request only on the network interface
+        /// with the specified index, overriding the one of the client.
+        ///
+        /// See [`reqwest::RequestBuilder::interface_index`](crate::RequestBuilder::interface_index).
+        ///
+        /// # Optional
+        ///
+        /// This requires the optional `interface-index` feature to be enabled.
+        #[cfg_attr(docsrs, doc(cfg(feature = "interface-index")))]
+        pub fn interface_index(mut self, index: u32) -> RequestBuilder {
+            if let Ok(ref mut req) = self.request {
+                *req.interface_index_mut() = Some(index);
+            }
+            self
+        }
+    }
+
+    /// Set a function called with each interim `1xx` response, as it
+    /// arrives.
+    ///
+    /// See [`reqwest::RequestBuilder::on_informational`](crate::RequestBuilder::on_informational).
+    /// The function runs on the thread of the blocking client.
+    pub fn on_informational<F>(mut self, f: F) -> RequestBuilder
+    where
+        F: Fn(&crate::informational::Informational) + Send + Sync + 'static,
+    {
+        if let Ok(ref mut req) = self.request {
+            *RequestConfig::<OnInformational>::get_mut(req.inner.extensions_mut()) =
+                Some(crate::informational::Callback::new(f));
+        }
+        self
+    }
+
+    /// Set a function called with each `103 Early Hints` interim response.
+    ///
+    /// See [`reqwest::RequestBuilder::on_early_hints`](crate::RequestBuilder::on_early_hints).
+    /// The function runs on the thread of the blocking client.
+    pub fn on_early_hints<F>(mut self, f: F) -> RequestBuilder
+    where
+        F: Fn(&crate::early_hints::EarlyHints) + Send + Sync + 'static,
+    {
+        if let Ok(ref mut req) = self.request {
+            *RequestConfig::<EarlyHints>::get_mut(req.inner.extensions_mut()) =
+                Some(crate::early_hints::Callback::new(f));
+        }
+        self
+    }
+
+    /// Set the priority of this request, with the `Priority` header of
+    /// RFC 9218.
+    ///
+    /// See [`reqwest::RequestBuilder::priority`](crate::RequestBuilder::priority).
+    ///
+    /// # Errors
+    ///
+    /// Sending the request fails if `urgency` is greater than `7`.
+    pub fn priority(mut self, urgency: u8, incremental: bool) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            match crate::util::priority(urgency, incremental) {
+                Ok(value) => {
+                    req.headers_mut().insert(crate::util::PRIORITY, value);
+                }
+                Err(err) => self.request = Err(err),
+            }
+        }
+        self
+    }
+
+    /// Set how this request uses the cache of the client.
+    ///
+    /// See [`reqwest::RequestBuilder::cache_mode`](crate::RequestBuilder::cache_mode).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cache` feature to be enabled.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn cache_mode(mut self, mode: crate::cache::Mode) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.cache_mode_mut() = Some(mode);
+        }
+        self
+    }
+
     /// Modify the query string of the URL.
     ///
     /// Modifies the URL of this request, adding the parameters provided.
@@ -424,6 +692,71 @@ impl RequestBuilder {
         self
     }
 
+    /// Modify the query string of the URL, with a custom encoder.
+    ///
+    /// `encode` turns `query` into an `application/x-www-form-urlencoded`
+    /// string, which is appended to the query string of the URL like
+    /// [`query`](RequestBuilder::query) does. It can be any serializer, such
+    /// as one with other casing or formats for booleans and dates than
+    /// `serde_urlencoded`, or one supporting nested structures.
+    ///
+    /// ```rust
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// struct Search {
+    ///     term: String,
+    ///     exact_match: bool,
+    /// }
+    ///
+    /// // camelCase keys, and booleans as `1` or `0`
+    /// fn encode(search: &Search) -> Result<String, std::convert::Infallible> {
+    ///     Ok(url::form_urlencoded::Serializer::new(String::new())
+    ///         .append_pair("term", &search.term)
+    ///         .append_pair("exactMatch", if search.exact_match { "1" } else { "0" })
+    ///         .finish())
+    /// }
+    ///
+    /// let search = Search {
+    ///     term: "rust http".into(),
+    ///     exact_match: true,
+    /// };
+    /// let res = reqwest::blocking::Client::new()
+    ///     .get("http://httpbin.org/get")
+    ///     .query_with(&search, encode)
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `encode` fails.
+    pub fn query_with<T, F, E>(mut self, query: &T, encode: F) -> RequestBuilder
+    where
+        T: ?Sized,
+        F: FnOnce(&T) -> Result<String, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match encode(query) {
+                Ok(encoded) if encoded.is_empty() => {}
+                Ok(encoded) => {
+                    let url = req.url_mut();
+                    let query = match url.query() {
+                        Some(existing) if !existing.is_empty() => format!("{existing}&{encoded}"),
+                        _ => encoded,
+                    };
+                    url.set_query(Some(&query));
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
     /// Set HTTP version
     pub fn version(mut self, version: Version) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
@@ -485,6 +818,43 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a form body, with a custom encoder.
+    ///
+    /// `encode` turns `form` into an `application/x-www-form-urlencoded`
+    /// string, sent as the body like [`form`](RequestBuilder::form) does,
+    /// with the same `Content-Type`. It can be any serializer, such as one
+    /// with other casing or formats for booleans and dates than
+    /// `serde_urlencoded`, or one supporting nested structures.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `encode` fails.
+    pub fn form_with<T, F, E>(mut self, form: &T, encode: F) -> RequestBuilder
+    where
+        T: ?Sized,
+        F: FnOnce(&T) -> Result<String, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match encode(form) {
+                Ok(body) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert(HeaderValue::from_static(
+                            "application/x-www-form-urlencoded",
+                        ));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
     /// Send a JSON body.
     ///
     /// Sets the body to the JSON serialization of the passed value, and
@@ -537,6 +907,175 @@ impl RequestBuilder {
         self
     }
 
+    /// Send a GraphQL query, with its variables.
+    ///
+    /// The query and the variables are sent as a JSON body, without the
+    /// variables if they serialize to `null`, such as `()`. The
+    /// `Content-Type` is set to `application/json` and the `Accept` header
+    /// to the GraphQL response media type, unless the request has them.
+    /// The method of the request is set to `POST`, as a query sent with a
+    /// body must be.
+    ///
+    /// See [`reqwest::RequestBuilder::graphql`](crate::RequestBuilder::graphql).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql<T: Serialize + ?Sized>(mut self, query: &str, variables: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match crate::graphql::body(query, variables) {
+                Ok(body) => {
+                    *req.method_mut() = Method::POST;
+                    let headers = req.headers_mut();
+                    headers
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/json"));
+                    headers
+                        .entry(crate::header::ACCEPT)
+                        .or_insert_with(|| HeaderValue::from_static(crate::graphql::ACCEPT));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send a MessagePack body.
+    ///
+    /// Structs are serialized as maps, with the names of their fields, and
+    /// the `Content-Type` is set to `application/msgpack` if the request has
+    /// none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `msgpack` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn msgpack<T: Serialize + ?Sized>(mut self, msgpack: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match rmp_serde::to_vec_named(msgpack) {
+                Ok(body) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/msgpack"));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send a CBOR body.
+    ///
+    /// The `Content-Type` is set to `application/cbor` if the request has
+    /// none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cbor` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn cbor<T: Serialize + ?Sized>(mut self, cbor: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            let mut body = Vec::new();
+            match ciborium::into_writer(cbor, &mut body) {
+                Ok(()) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/cbor"));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send an XML body.
+    ///
+    /// The root element is named after the type of `xml`, and the
+    /// `Content-Type` is set to `application/xml` if the request has none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `xml` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` can't be represented as an XML document.
+    #[cfg(feature = "xml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xml")))]
+    pub fn xml<T: Serialize + ?Sized>(mut self, xml: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            match quick_xml::se::to_string(xml) {
+                Ok(body) => {
+                    req.headers_mut()
+                        .entry(CONTENT_TYPE)
+                        .or_insert_with(|| HeaderValue::from_static("application/xml"));
+                    *req.body_mut() = Some(body.into());
+                }
+                Err(err) => error = Some(crate::error::builder(err)),
+            }
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
+    /// Send a Protocol Buffers message.
+    ///
+    /// The `Content-Type` is set to `application/x-protobuf` if the request
+    /// has none.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `protobuf` feature enabled.
+    #[cfg(feature = "protobuf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+    pub fn protobuf<M: prost::Message>(mut self, message: &M) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.headers_mut()
+                .entry(CONTENT_TYPE)
+                .or_insert_with(|| HeaderValue::from_static("application/x-protobuf"));
+            *req.body_mut() = Some(message.encode_to_vec().into());
+        }
+        self
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -558,16 +1097,13 @@ impl RequestBuilder {
     /// See [`multipart`](multipart/) for more examples.
     #[cfg(feature = "multipart")]
     #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
-    pub fn multipart(self, mut multipart: multipart::Form) -> RequestBuilder {
+    pub fn multipart(self, multipart: multipart::Form) -> RequestBuilder {
         let mut builder = self.header(
             CONTENT_TYPE,
             format!("multipart/form-data; boundary={}", multipart.boundary()).as_str(),
         );
         if let Ok(ref mut req) = builder.request {
-            *req.body_mut() = Some(match multipart.compute_length() {
-                Some(length) => Body::sized(multipart.reader(), length),
-                None => Body::new(multipart.reader()),
-            })
+            *req.body_mut() = Some(multipart.into_body());
         }
         builder
     }
@@ -691,9 +1227,10 @@ fn fmt_request_fields<'a, 'b>(
     f: &'a mut fmt::DebugStruct<'a, 'b>,
     req: &Request,
 ) -> &'a mut fmt::DebugStruct<'a, 'b> {
+    let redaction = req.inner.extensions().get::<Redaction>();
     f.field("method", req.method())
-        .field("url", req.url())
-        .field("headers", req.headers())
+        .field("url", &redact::url(redaction, req.url()))
+        .field("headers", &redact::headers(redaction, req.headers()))
 }
 
 #[cfg(test)]
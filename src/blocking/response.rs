@@ -1,5 +1,5 @@
 use std::fmt;
-use std::io::{self, Read};
+use std::io::{self, BufRead, Read};
 use std::mem;
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -10,10 +10,17 @@ use futures_util::TryStreamExt;
 use http;
 use http_body_util::BodyExt;
 use hyper::header::HeaderMap;
-#[cfg(feature = "json")]
+#[cfg(any(
+    feature = "json",
+    feature = "msgpack",
+    feature = "cbor",
+    feature = "xml"
+))]
 use serde::de::DeserializeOwned;
 
 use super::client::KeepCoreThreadAlive;
+use super::sse::Events;
+use super::upgrade::Upgraded;
 use super::wait;
 #[cfg(feature = "cookies")]
 use crate::cookie;
@@ -23,10 +30,34 @@ use crate::{async_impl, StatusCode, Url, Version};
 pub struct Response {
     inner: async_impl::Response,
     body: Option<Pin<Box<dyn futures_util::io::AsyncRead + Send + Sync>>>,
+    buffer: ReadBuffer,
     timeout: Option<Duration>,
     _thread_handle: KeepCoreThreadAlive,
 }
 
+/// The buffer used by the `BufRead` implementation of `Response`.
+struct ReadBuffer {
+    data: Vec<u8>,
+    pos: usize,
+    filled: usize,
+    capacity: usize,
+}
+
+impl ReadBuffer {
+    fn new() -> ReadBuffer {
+        ReadBuffer {
+            data: Vec::new(),
+            pos: 0,
+            filled: 0,
+            capacity: 8 * 1024,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.filled
+    }
+}
+
 impl fmt::Debug for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Debug::fmt(&self.inner, f)
@@ -42,6 +73,7 @@ impl Response {
         Response {
             inner: res,
             body: None,
+            buffer: ReadBuffer::new(),
             timeout,
             _thread_handle: thread,
         }
@@ -180,6 +212,13 @@ impl Response {
         self.inner.remote_addr()
     }
 
+    /// Get the interim `1xx` responses received before this `Response`.
+    ///
+    /// See the [`informational`](crate::informational) module for details.
+    pub fn informational(&self) -> &[crate::informational::Informational] {
+        self.inner.informational()
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &http::Extensions {
         self.inner.extensions()
@@ -207,6 +246,32 @@ impl Response {
         self.inner.content_length()
     }
 
+    /// Set the size of the buffer used when reading the body through
+    /// [`BufRead`].
+    ///
+    /// The default is 8 KiB. A capacity of `0` is treated as `1`. Data that
+    /// is already buffered is kept, and the new size applies from the next
+    /// read of the body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::BufRead;
+    ///
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let resp = reqwest::blocking::get("http://httpbin.org/stream/20")?
+    ///     .with_buffer_capacity(64 * 1024);
+    /// for line in resp.lines() {
+    ///     println!("{}", line?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Response {
+        self.buffer.capacity = capacity.max(1);
+        self
+    }
+
     /// Try and deserialize the response body as JSON using `serde`.
     ///
     /// # Optional
@@ -252,6 +317,111 @@ impl Response {
         })
     }
 
+    /// Try to deserialize the `data` of a GraphQL response.
+    ///
+    /// See [`reqwest::Response::graphql_data`](crate::Response::graphql_data).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `graphql` feature enabled.
+    #[cfg(feature = "graphql")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql_data<T: DeserializeOwned>(self) -> crate::Result<T> {
+        wait::timeout(self.inner.graphql_data(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Try to deserialize the response body as MessagePack.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `msgpack` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/msgpack`, `application/x-msgpack` or
+    /// `application/vnd.msgpack`, or if the body cannot be deserialized to
+    /// the target type `T`. A response without a `Content-Type` is decoded.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    pub fn msgpack<T: DeserializeOwned>(self) -> crate::Result<T> {
+        wait::timeout(self.inner.msgpack(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Try to deserialize the response body as CBOR.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cbor` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/cbor` or one with the `+cbor` suffix, or if the body
+    /// cannot be deserialized to the target type `T`. A response without a
+    /// `Content-Type` is decoded.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    pub fn cbor<T: DeserializeOwned>(self) -> crate::Result<T> {
+        wait::timeout(self.inner.cbor(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Try to deserialize the response body as XML.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `xml` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/xml`, `text/xml` or one with the `+xml` suffix, or if the
+    /// body cannot be deserialized to the target type `T`. A response without
+    /// a `Content-Type` is decoded.
+    #[cfg(feature = "xml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xml")))]
+    pub fn xml<T: DeserializeOwned>(self) -> crate::Result<T> {
+        wait::timeout(self.inner.xml(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Try to decode the response body as a Protocol Buffers message.
+    ///
+    /// If the `Content-Type` of the response has a `delimited=true` or
+    /// `encoding=delimited` parameter, the body starts with the length of the
+    /// message as a varint, and only this first message is decoded.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `protobuf` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has a `Content-Type` other than
+    /// `application/x-protobuf`, `application/protobuf`,
+    /// `application/vnd.google.protobuf` or `application/x-google-protobuf`,
+    /// or if the body isn't a valid `M` message. A response without a
+    /// `Content-Type` is decoded as a single message.
+    #[cfg(feature = "protobuf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+    pub fn protobuf<M: prost::Message + Default>(self) -> crate::Result<M> {
+        wait::timeout(self.inner.protobuf(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
     /// Get the full response body as `Bytes`.
     ///
     /// # Example
@@ -334,6 +504,27 @@ impl Response {
         })
     }
 
+    /// Consumes the response, returning an iterator of its Server-Sent Events.
+    ///
+    /// The body is parsed as a `text/event-stream` while it is read, so each
+    /// event is returned as soon as it has been received.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = reqwest::blocking::get("http://example.com/updates")?;
+    /// for event in res.events() {
+    ///     let event = event?;
+    ///     println!("{}: {}", event.event(), event.data());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn events(self) -> Events {
+        Events::new(self)
+    }
+
     /// Copy the response body into a writer.
     ///
     /// This function internally uses [`std::io::copy`] and hence will continuously read data from
@@ -380,6 +571,7 @@ impl Response {
     pub fn error_for_status(self) -> crate::Result<Self> {
         let Response {
             body,
+            buffer,
             inner,
             timeout,
             _thread_handle,
@@ -387,11 +579,45 @@ impl Response {
         inner.error_for_status().map(move |inner| Response {
             inner,
             body,
+            buffer,
             timeout,
             _thread_handle,
         })
     }
 
+    /// Consumes the response and waits for a possible HTTP upgrade.
+    ///
+    /// The returned [`Upgraded`] connection can be read from and written to
+    /// directly, for example after a `CONNECT` request or a
+    /// `101 Switching Protocols` response.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::io::{Read, Write};
+    ///
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = reqwest::blocking::Client::new()
+    ///     .get("http://example.com/chat")
+    ///     .header("connection", "upgrade")
+    ///     .header("upgrade", "foobar")
+    ///     .send()?;
+    /// let mut upgraded = res.upgrade()?;
+    /// upgraded.write_all(b"hello")?;
+    /// let mut buf = [0; 5];
+    /// upgraded.read_exact(&mut buf)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn upgrade(self) -> crate::Result<Upgraded> {
+        let timeout = self.timeout;
+        match wait::timeout(self.inner.upgrade(), timeout) {
+            Ok(upgraded) => Ok(Upgraded::new(upgraded, self._thread_handle)),
+            Err(wait::Waited::TimedOut(e)) => Err(crate::error::upgrade(e)),
+            Err(wait::Waited::Inner(e)) => Err(e),
+        }
+    }
+
     /// Turn a reference to a response into an error if the server returned an error.
     ///
     /// # Example
@@ -430,10 +656,8 @@ impl Response {
         }
         self.body.as_mut().expect("body was init").as_mut()
     }
-}
 
-impl Read for Response {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    fn read_body(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         use futures_util::io::AsyncReadExt;
 
         let timeout = self.timeout;
@@ -444,6 +668,43 @@ impl Read for Response {
     }
 }
 
+impl Read for Response {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.is_empty() {
+            return self.read_body(buf);
+        }
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+/// The body is read into a buffer of
+/// [`with_buffer_capacity`](Response::with_buffer_capacity) bytes.
+///
+/// The bytes which are buffered but not consumed yet are dropped if the
+/// rest of the body is then read with `text()`, `json()` or `bytes()`,
+/// which only return what wasn't buffered.
+impl BufRead for Response {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.buffer.is_empty() {
+            // left empty if the read fails
+            self.buffer.pos = 0;
+            self.buffer.filled = 0;
+            let mut data = mem::take(&mut self.buffer.data);
+            data.resize(self.buffer.capacity, 0);
+            let res = self.read_body(&mut data);
+            self.buffer.data = data;
+            self.buffer.filled = res?;
+        }
+        Ok(&self.buffer.data[self.buffer.pos..self.buffer.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.buffer.pos = std::cmp::min(self.buffer.pos + amt, self.buffer.filled);
+    }
+}
+
 impl<T: Into<async_impl::body::Body>> From<http::Response<T>> for Response {
     fn from(r: http::Response<T>) -> Response {
         let response = async_impl::Response::from(r);
@@ -0,0 +1,287 @@
+//! Server-Sent Events.
+//!
+//! See [`Response::events`](super::Response::events).
+
+use std::fmt;
+use std::io::BufRead;
+use std::time::Duration;
+
+use super::response::Response;
+
+/// A single Server-Sent Event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    event: String,
+    data: String,
+    id: String,
+}
+
+impl Event {
+    /// The event type, `"message"` if the server didn't set one.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// The event data, with multiple `data` lines joined by `\n`.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// The last event ID set by the server, if any.
+    pub fn id(&self) -> Option<&str> {
+        if self.id.is_empty() {
+            None
+        } else {
+            Some(&self.id)
+        }
+    }
+}
+
+/// An iterator over the Server-Sent Events of a `Response`.
+///
+/// Created by [`Response::events`](super::Response::events).
+pub struct Events {
+    res: Response,
+    line: Vec<u8>,
+    // Whether a `\r` ended the last line, so a following `\n` is skipped.
+    after_cr: bool,
+    // Whether no byte of the stream has been read yet, to strip a BOM.
+    start: bool,
+    event: String,
+    data: String,
+    id: String,
+    retry: Option<Duration>,
+}
+
+impl Events {
+    pub(super) fn new(res: Response) -> Events {
+        Events {
+            res,
+            line: Vec::new(),
+            after_cr: false,
+            start: true,
+            event: String::new(),
+            data: String::new(),
+            id: String::new(),
+            retry: None,
+        }
+    }
+
+    /// The reconnection time most recently sent by the server, if any.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Read the next line into `self.line`, returning `false` at the end of
+    /// the stream.
+    ///
+    /// Lines may end with `\r\n`, `\n` or `\r`. An unterminated last line is
+    /// dropped, as an event is only complete once followed by a blank line.
+    fn read_line(&mut self) -> crate::Result<bool> {
+        self.line.clear();
+        loop {
+            let buf = self.res.fill_buf().map_err(crate::error::decode_io)?;
+            if buf.is_empty() {
+                return Ok(false);
+            }
+
+            let mut buf = buf;
+            let mut skipped = 0;
+            if self.start {
+                self.start = false;
+                if buf.starts_with(b"\xEF\xBB\xBF") {
+                    buf = &buf[3..];
+                    skipped = 3;
+                }
+            }
+            if self.after_cr {
+                self.after_cr = false;
+                if buf.first() == Some(&b'\n') {
+                    buf = &buf[1..];
+                    skipped += 1;
+                }
+            }
+
+            match buf.iter().position(|&b| b == b'\r' || b == b'\n') {
+                Some(i) => {
+                    self.after_cr = buf[i] == b'\r';
+                    self.line.extend_from_slice(&buf[..i]);
+                    self.res.consume(skipped + i + 1);
+                    return Ok(true);
+                }
+                None => {
+                    let len = buf.len();
+                    self.line.extend_from_slice(buf);
+                    self.res.consume(skipped + len);
+                }
+            }
+        }
+    }
+
+    /// Apply one line, returning an event if the line completed one.
+    fn process_line(&mut self) -> Option<Event> {
+        if self.line.is_empty() {
+            return self.dispatch();
+        }
+
+        let line = String::from_utf8_lossy(&self.line);
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (&*line, ""),
+        };
+
+        match field {
+            "event" => self.event = value.to_owned(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.id = value.to_owned(),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(ms) = value.parse() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = std::mem::take(&mut self.event);
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let mut data = std::mem::take(&mut self.data);
+        data.pop();
+        Some(Event {
+            event: if event.is_empty() {
+                "message".to_owned()
+            } else {
+                event
+            },
+            data,
+            id: self.id.clone(),
+        })
+    }
+}
+
+impl Iterator for Events {
+    type Item = crate::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.read_line() {
+                Ok(true) => {
+                    if let Some(event) = self.process_line() {
+                        return Some(Ok(event));
+                    }
+                }
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Events")
+            .field("url", self.res.url())
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(body: &'static str) -> Vec<Event> {
+        Response::from(http::Response::new(body))
+            .events()
+            .collect::<crate::Result<_>>()
+            .unwrap()
+    }
+
+    fn event(event: &str, data: &str, id: &str) -> Event {
+        Event {
+            event: event.to_owned(),
+            data: data.to_owned(),
+            id: id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn parses_fields() {
+        let evs = events(
+            ": comment\n\
+             data: first\n\
+             \n\
+             event: update\n\
+             id: 7\n\
+             data:second\n\
+             data:  third\n\
+             \n",
+        );
+        assert_eq!(
+            evs,
+            vec![
+                event("message", "first", ""),
+                event("update", "second\n third", "7"),
+            ]
+        );
+        assert_eq!(evs[0].id(), None);
+        assert_eq!(evs[1].id(), Some("7"));
+    }
+
+    #[test]
+    fn id_persists_across_events() {
+        let evs = events("id: a\ndata: 1\n\ndata: 2\n\nid\ndata: 3\n\n");
+        assert_eq!(
+            evs,
+            vec![
+                event("message", "1", "a"),
+                event("message", "2", "a"),
+                event("message", "3", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_endings() {
+        let evs = events("\u{FEFF}data: a\r\n\r\ndata: b\r\rdata\n\n");
+        assert_eq!(
+            evs,
+            vec![
+                event("message", "a", ""),
+                event("message", "b", ""),
+                event("message", "", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_empty_and_incomplete_events() {
+        let evs = events("event: nothing\n\ndata: done\n\ndata: partial");
+        assert_eq!(evs, vec![event("message", "done", "")]);
+    }
+
+    #[test]
+    fn retry() {
+        let mut evs = Response::from(http::Response::new(
+            "retry: 1500\ndata: a\n\nretry: soon\ndata: b\n\n",
+        ))
+        .events();
+        assert_eq!(evs.retry(), None);
+        assert_eq!(evs.next().unwrap().unwrap().data(), "a");
+        assert_eq!(evs.retry(), Some(Duration::from_millis(1500)));
+        assert_eq!(evs.next().unwrap().unwrap().data(), "b");
+        assert_eq!(evs.retry(), Some(Duration::from_millis(1500)));
+        assert!(evs.next().is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::client::KeepCoreThreadAlive;
+use super::wait;
+use crate::async_impl;
+
+/// An upgraded HTTP connection.
+///
+/// This is a blocking wrapper around [`crate::Upgraded`], for use after a
+/// `101 Switching Protocols` response or a successful `CONNECT` request.
+/// Reads and writes wait for the client's runtime to make progress on the
+/// underlying connection.
+pub struct Upgraded {
+    inner: async_impl::Upgraded,
+    _thread_handle: KeepCoreThreadAlive,
+}
+
+impl Upgraded {
+    pub(crate) fn new(inner: async_impl::Upgraded, thread: KeepCoreThreadAlive) -> Upgraded {
+        Upgraded {
+            inner,
+            _thread_handle: thread,
+        }
+    }
+
+    /// Shuts down the write side of the connection.
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        block_on(self.inner.shutdown())
+    }
+}
+
+impl Read for Upgraded {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        block_on(self.inner.read(buf))
+    }
+}
+
+impl Write for Upgraded {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        block_on(self.inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        block_on(self.inner.flush())
+    }
+}
+
+impl fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgraded").finish()
+    }
+}
+
+fn block_on<T>(fut: impl std::future::Future<Output = io::Result<T>>) -> io::Result<T> {
+    match wait::timeout(fut, None) {
+        Ok(val) => Ok(val),
+        Err(wait::Waited::Inner(err)) => Err(err),
+        Err(wait::Waited::TimedOut(_)) => unreachable!("waited without a timeout"),
+    }
+}
@@ -44,7 +44,9 @@ where
             let now = Instant::now();
             if now >= deadline {
                 log::trace!("wait timeout exceeded");
-                return Err(Waited::TimedOut(crate::error::TimedOut));
+                return Err(Waited::TimedOut(crate::error::TimedOut(
+                    crate::error::TimeoutPhase::Total,
+                )));
             }
 
             log::trace!(
@@ -0,0 +1,148 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use http::{Method, Request, Response, StatusCode};
+use http_body::Body as HttpBody;
+use tower::Service;
+
+use super::service::{
+    bypasses_cache, get, is_safe, varied_headers, vary_names, CacheBody, Directives, Writer,
+};
+use super::{Entry, NotModified, Store};
+use crate::async_impl::body::Body;
+
+/// How a client makes conditional requests.
+#[derive(Clone, Default)]
+pub(crate) struct Settings {
+    pub(crate) store: Option<Arc<dyn Store>>,
+    pub(crate) not_modified: NotModified,
+}
+
+/// A [`Service`] that remembers the validators of responses, and sends them
+/// with the next requests for the same URLs.
+#[derive(Clone)]
+pub(crate) struct ConditionalService<S> {
+    inner: S,
+    settings: Settings,
+}
+
+impl<S> ConditionalService<S> {
+    pub(crate) fn new(inner: S, settings: Settings) -> Self {
+        ConditionalService { inner, settings }
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for ConditionalService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+{
+    type Response = Response<CacheBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + Sync>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let Some(store) = self.settings.store.clone() else {
+            let fut = inner.call(req);
+            return Box::pin(sync_wrapper::SyncFuture::new(async move {
+                Ok(fut.await?.map(CacheBody::network))
+            }));
+        };
+        let not_modified = self.settings.not_modified;
+        Box::pin(sync_wrapper::SyncFuture::new(conditional(
+            inner,
+            store,
+            not_modified,
+            req,
+        )))
+    }
+}
+
+async fn conditional<S, ResBody>(
+    mut inner: S,
+    store: Arc<dyn Store>,
+    not_modified: NotModified,
+    mut req: Request<Body>,
+) -> Result<Response<CacheBody<ResBody>>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+{
+    let key = req.uri().to_string();
+
+    if !is_safe(req.method()) {
+        let res = inner.call(req).await?;
+        if res.status().is_success() || res.status().is_redirection() {
+            store.remove(&key);
+        }
+        return Ok(res.map(CacheBody::network));
+    }
+    if req.method() != Method::GET || bypasses_cache(req.headers()) {
+        return Ok(inner.call(req).await?.map(CacheBody::network));
+    }
+
+    let stored = get(&store, &key)
+        .await
+        .filter(|entry| entry.matches(req.headers()));
+    if let Some(ref entry) = stored {
+        entry.add_validators(req.headers_mut());
+    }
+    let directives = Directives::parse(req.headers());
+    let req_headers = req.headers().clone();
+    let res = inner.call(req).await?;
+    let now = SystemTime::now();
+
+    if res.status() == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = stored {
+            let entry = entry.refresh(res.headers(), now);
+            let fulfilled = match not_modified {
+                NotModified::Return => None,
+                NotModified::Fulfill => Some(entry.to_response(now)),
+            };
+            store.put(&key, entry);
+            return Ok(fulfilled.unwrap_or_else(|| res.map(CacheBody::network)));
+        }
+    }
+    if !has_validators(&directives, res.status(), res.headers()) {
+        return Ok(res.map(CacheBody::network));
+    }
+
+    let entry = Entry::new(res.status(), res.headers().clone(), Bytes::new())
+        .with_varied_headers(varied_headers(res.headers(), &req_headers))
+        .with_stored_at(now);
+    match not_modified {
+        NotModified::Return => {
+            store.put(&key, entry);
+            Ok(res.map(CacheBody::network))
+        }
+        NotModified::Fulfill => {
+            let writer = Writer::new(store, key, entry);
+            Ok(res.map(|body| CacheBody::store(body, writer)))
+        }
+    }
+}
+
+/// Whether the validators of a response should be remembered.
+fn has_validators(request: &Directives, status: StatusCode, headers: &HeaderMap) -> bool {
+    if status != StatusCode::OK || request.no_store || Directives::parse(headers).no_store {
+        return false;
+    }
+    if vary_names(headers).any(|name| name == "*") {
+        return false;
+    }
+    headers.contains_key(ETAG) || headers.contains_key(LAST_MODIFIED)
+}
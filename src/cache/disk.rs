@@ -0,0 +1,491 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::{Entry, Store};
+
+const INDEX: &str = "index.json";
+const BODIES: &str = "bodies";
+const DEFAULT_MAX_SIZE: u64 = 100 * 1024 * 1024;
+// How long changes to the index are batched before it is saved.
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// A [`Store`] keeping responses in a directory.
+///
+/// Bodies are kept in files named after the SHA-256 digest of their content,
+/// so identical bodies are only stored once, and everything else in an index
+/// file. When the bodies take more than the maximum size, the least recently
+/// used entries are removed.
+///
+/// Files are written by a background thread, so storing a response doesn't
+/// wait for the disk. The index is saved at most once a second, and when
+/// the store is dropped.
+///
+/// A directory should only be used by one `DiskStore` at a time.
+pub struct DiskStore {
+    inner: Arc<Mutex<Inner>>,
+    writer: Option<(mpsc::Sender<Job>, thread::JoinHandle<()>)>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    max_size: u64,
+    index: Index,
+    // Bodies not written to their file yet, by digest.
+    pending: HashMap<String, Bytes>,
+    // Whether the index changed since it was last saved.
+    dirty: bool,
+}
+
+/// The work of the writer thread.
+enum Job {
+    /// Write a pending body to its file.
+    Write(String),
+    /// Delete the file of a body, unless it is used again.
+    Delete(String),
+    /// Save the index, soon.
+    Save,
+}
+
+impl DiskStore {
+    /// Open the store in `dir`, creating the directory if needed.
+    ///
+    /// The maximum size is 100 MiB.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the directory cannot be created or read.
+    pub fn open<P: AsRef<Path>>(dir: P) -> crate::Result<DiskStore> {
+        let dir = dir.as_ref().to_owned();
+        fs::create_dir_all(dir.join(BODIES)).map_err(crate::error::builder)?;
+        let index = match fs::read(dir.join(INDEX)) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+                log::debug!("ignoring invalid cache index: {err}");
+                Index::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Index::default(),
+            Err(err) => return Err(crate::error::builder(err)),
+        };
+
+        let inner = Inner {
+            dir,
+            max_size: DEFAULT_MAX_SIZE,
+            index,
+            pending: HashMap::new(),
+            dirty: false,
+        };
+        inner
+            .remove_unused_bodies()
+            .map_err(crate::error::builder)?;
+        let inner = Arc::new(Mutex::new(inner));
+
+        let (tx, rx) = mpsc::channel();
+        let writer = thread::Builder::new()
+            .name("reqwest-disk-cache".into())
+            .spawn({
+                let inner = inner.clone();
+                move || write_files(&inner, rx)
+            })
+            .map_err(crate::error::builder)?;
+        Ok(DiskStore {
+            inner,
+            writer: Some((tx, writer)),
+        })
+    }
+
+    /// Set the maximum size of the stored bodies, in bytes.
+    ///
+    /// Bodies larger than this are not stored.
+    pub fn max_size(self, max_size: u64) -> DiskStore {
+        let jobs = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.max_size = max_size;
+            let mut jobs = Vec::new();
+            inner.evict(&mut jobs);
+            jobs
+        };
+        self.send(jobs);
+        self
+    }
+
+    /// The size of the stored bodies, in bytes.
+    pub fn size(&self) -> u64 {
+        self.inner.lock().unwrap().size()
+    }
+
+    fn send(&self, jobs: Vec<Job>) {
+        if let Some((ref tx, _)) = self.writer {
+            for job in jobs {
+                // the writer only stops once the store is dropped
+                let _ = tx.send(job);
+            }
+        }
+    }
+}
+
+impl Store for DiskStore {
+    fn get(&self, key: &str) -> Option<Entry> {
+        let (hash, path, body) = {
+            let inner = self.inner.lock().unwrap();
+            let hash = inner.index.entries.get(key)?.body.clone();
+            let path = body_path(&inner.dir, &hash);
+            let body = inner.pending.get(&hash).cloned();
+            (hash, path, body)
+        };
+        // read the file without holding the lock
+        let body = match body {
+            Some(body) => body,
+            None => match fs::read(path) {
+                Ok(body) => Bytes::from(body),
+                Err(err) => {
+                    log::debug!("dropping cache entry without a body: {err}");
+                    let mut jobs = Vec::new();
+                    let mut inner = self.inner.lock().unwrap();
+                    if inner
+                        .index
+                        .entries
+                        .get(key)
+                        .is_some_and(|meta| meta.body == hash)
+                    {
+                        inner.remove(key, &mut jobs);
+                    }
+                    drop(inner);
+                    self.send(jobs);
+                    return None;
+                }
+            },
+        };
+        self.inner.lock().unwrap().touch(key, &hash, body)
+    }
+
+    fn put(&self, key: &str, entry: Entry) {
+        let mut jobs = Vec::new();
+        self.inner.lock().unwrap().put(key, entry, &mut jobs);
+        self.send(jobs);
+    }
+
+    fn remove(&self, key: &str) {
+        let mut jobs = Vec::new();
+        self.inner.lock().unwrap().remove(key, &mut jobs);
+        self.send(jobs);
+    }
+}
+
+impl fmt::Debug for DiskStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("DiskStore")
+            .field("dir", &inner.dir)
+            .field("max_size", &inner.max_size)
+            .field("entries", &inner.index.entries.len())
+            .finish()
+    }
+}
+
+impl Drop for DiskStore {
+    fn drop(&mut self) {
+        // The writer writes what is left, and saves the index, once it
+        // has no more jobs.
+        if let Some((tx, writer)) = self.writer.take() {
+            drop(tx);
+            let _ = writer.join();
+        }
+    }
+}
+
+impl Inner {
+    /// Mark an entry as used, if it still has the body that was read.
+    fn touch(&mut self, key: &str, hash: &str, body: Bytes) -> Option<Entry> {
+        self.index.clock += 1;
+        let meta = self
+            .index
+            .entries
+            .get_mut(key)
+            .filter(|meta| meta.body == hash)?;
+        meta.used = self.index.clock;
+        self.dirty = true;
+        meta.to_entry(body)
+    }
+
+    fn put(&mut self, key: &str, entry: Entry, jobs: &mut Vec<Job>) {
+        // Don't write request credentials to disk. Dropping them instead
+        // would let the entry match requests without them.
+        let sensitive = entry.varied.values().any(HeaderValue::is_sensitive);
+        if sensitive || entry.body.len() as u64 > self.max_size {
+            return self.remove(key, jobs);
+        }
+
+        let digest = ring::digest::digest(&ring::digest::SHA256, &entry.body);
+        let hash = hex(digest.as_ref());
+        // a body used by an entry is in its file, or about to be
+        if !self.is_used(&hash) {
+            self.pending.insert(hash.clone(), entry.body.clone());
+            jobs.push(Job::Write(hash.clone()));
+        }
+
+        self.index.clock += 1;
+        let meta = Meta::new(&entry, hash, self.index.clock);
+        if let Some(old) = self.index.entries.insert(key.to_owned(), meta) {
+            self.release(old.body, jobs);
+        }
+        self.evict(jobs);
+        self.changed(jobs);
+    }
+
+    fn remove(&mut self, key: &str, jobs: &mut Vec<Job>) {
+        let Some(old) = self.index.entries.remove(key) else {
+            return;
+        };
+        self.release(old.body, jobs);
+        self.changed(jobs);
+    }
+
+    /// Remove the least recently used entries until the bodies fit.
+    fn evict(&mut self, jobs: &mut Vec<Job>) {
+        let mut evicted = false;
+        while self.size() > self.max_size {
+            let oldest = self
+                .index
+                .entries
+                .iter()
+                .min_by_key(|(_, meta)| meta.used)
+                .map(|(key, _)| key.clone());
+            let Some(old) = oldest.and_then(|key| self.index.entries.remove(&key)) else {
+                break;
+            };
+            self.release(old.body, jobs);
+            evicted = true;
+        }
+        if evicted {
+            self.changed(jobs);
+        }
+    }
+
+    /// Delete a body file once no entry uses it.
+    fn release(&mut self, hash: String, jobs: &mut Vec<Job>) {
+        if !self.is_used(&hash) {
+            self.pending.remove(&hash);
+            jobs.push(Job::Delete(hash));
+        }
+    }
+
+    fn is_used(&self, hash: &str) -> bool {
+        self.index.entries.values().any(|meta| meta.body == hash)
+    }
+
+    fn changed(&mut self, jobs: &mut Vec<Job>) {
+        self.dirty = true;
+        jobs.push(Job::Save);
+    }
+
+    /// Delete body files left behind by a run that stopped before saving
+    /// the index.
+    fn remove_unused_bodies(&self) -> io::Result<()> {
+        let used = self
+            .index
+            .entries
+            .values()
+            .map(|meta| meta.body.as_str())
+            .collect::<HashSet<_>>();
+        for file in fs::read_dir(self.dir.join(BODIES))? {
+            let file = file?;
+            let name = file.file_name();
+            if !name.to_str().is_some_and(|name| used.contains(name)) {
+                fs::remove_file(file.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> u64 {
+        let mut seen = HashSet::new();
+        self.index
+            .entries
+            .values()
+            .filter(|meta| seen.insert(&meta.body))
+            .map(|meta| meta.size)
+            .sum()
+    }
+}
+
+/// Run the jobs of a store until it is dropped, saving its index once no
+/// change came for `SAVE_DELAY`, and a last time when it is dropped.
+fn write_files(inner: &Mutex<Inner>, jobs: mpsc::Receiver<Job>) {
+    let mut save = false;
+    loop {
+        let job = match save {
+            true => jobs.recv_timeout(SAVE_DELAY),
+            false => jobs
+                .recv()
+                .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+        };
+        let result = match job {
+            Ok(Job::Write(hash)) => write_body(inner, &hash),
+            Ok(Job::Delete(hash)) => delete_body(inner, &hash),
+            Ok(Job::Save) => {
+                save = true;
+                Ok(())
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                save = false;
+                save_index(inner)
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                if let Err(err) = save_index(inner) {
+                    log::debug!("cache index save failed: {err}");
+                }
+                return;
+            }
+        };
+        if let Err(err) = result {
+            log::debug!("cache write failed: {err}");
+        }
+    }
+}
+
+fn write_body(inner: &Mutex<Inner>, hash: &str) -> io::Result<()> {
+    let (path, body) = {
+        let inner = inner.lock().unwrap();
+        match inner.pending.get(hash) {
+            Some(body) => (body_path(&inner.dir, hash), body.clone()),
+            // released meanwhile
+            None => return Ok(()),
+        }
+    };
+    let result = write_atomic(&path, &body);
+    let mut inner = inner.lock().unwrap();
+    inner.pending.remove(hash);
+    if result.is_err() {
+        // the entries of a body which isn't stored are dropped
+        let keys = inner
+            .index
+            .entries
+            .iter()
+            .filter(|(_, meta)| meta.body == hash)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            inner.index.entries.remove(&key);
+            inner.dirty = true;
+        }
+    }
+    result
+}
+
+fn delete_body(inner: &Mutex<Inner>, hash: &str) -> io::Result<()> {
+    let path = {
+        let inner = inner.lock().unwrap();
+        // stored again meanwhile
+        if inner.is_used(hash) {
+            return Ok(());
+        }
+        body_path(&inner.dir, hash)
+    };
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn save_index(inner: &Mutex<Inner>) -> io::Result<()> {
+    let (path, json) = {
+        let mut inner = inner.lock().unwrap();
+        if !inner.dirty {
+            return Ok(());
+        }
+        inner.dirty = false;
+        (inner.dir.join(INDEX), serde_json::to_vec(&inner.index)?)
+    };
+    write_atomic(&path, &json)
+}
+
+fn body_path(dir: &Path, hash: &str) -> PathBuf {
+    dir.join(BODIES).join(hash)
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// ===== index format =====
+
+#[derive(Default, Deserialize, Serialize)]
+struct Index {
+    // Incremented on every use, to order entries by when they were used.
+    clock: u64,
+    entries: HashMap<String, Meta>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Meta {
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    varied: Vec<(String, String)>,
+    body: String,
+    size: u64,
+    // Milliseconds since the Unix epoch.
+    stored_at: u64,
+    used: u64,
+}
+
+impl Meta {
+    fn new(entry: &Entry, body: String, used: u64) -> Meta {
+        let stored_at = entry
+            .stored_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Meta {
+            status: entry.status.as_u16(),
+            headers: store_headers(&entry.headers),
+            varied: store_headers(&entry.varied),
+            body,
+            size: entry.body.len() as u64,
+            stored_at: stored_at.as_millis() as u64,
+            used,
+        }
+    }
+
+    fn to_entry(&self, body: Bytes) -> Option<Entry> {
+        let status = StatusCode::from_u16(self.status).ok()?;
+        let stored_at = UNIX_EPOCH + Duration::from_millis(self.stored_at);
+        Some(
+            Entry::new(status, load_headers(&self.headers)?, body)
+                .with_varied_headers(load_headers(&self.varied)?)
+                .with_stored_at(stored_at),
+        )
+    }
+}
+
+fn store_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
+
+fn load_headers(stored: &[(String, String)]) -> Option<HeaderMap> {
+    let mut headers = HeaderMap::with_capacity(stored.len());
+    for (name, value) in stored {
+        let name = HeaderName::from_bytes(name.as_bytes()).ok()?;
+        let value = HeaderValue::from_str(value).ok()?;
+        headers.append(name, value);
+    }
+    Some(headers)
+}
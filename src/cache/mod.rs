@@ -0,0 +1,244 @@
+//! HTTP caching.
+//!
+//! A `Client` built with [`ClientBuilder::cache`](crate::ClientBuilder::cache)
+//! keeps the responses it may reuse in a [`Store`], following the rules of a
+//! private cache from [RFC 9111]. Fresh responses are then answered from the
+//! store without contacting the server, and stale ones are revalidated with a
+//! conditional request when they have an `ETag` or a `Last-Modified` date.
+//!
+//! [`DiskStore`] keeps the responses in a directory, so they are reused
+//! across runs of a program:
+//!
+//! ```rust,no_run
+//! # fn run() -> Result<(), reqwest::Error> {
+//! use std::sync::Arc;
+//! use reqwest::cache::DiskStore;
+//!
+//! let store = DiskStore::open("/tmp/my-tool/cache")?.max_size(50 * 1024 * 1024);
+//! let client = reqwest::Client::builder()
+//!     .cache(Arc::new(store))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only responses to `GET` requests are stored. Requests with their own
+//! conditional or `Range` headers bypass the cache, and a successful unsafe
+//! request, such as a `POST` or a `DELETE`, removes the stored response for
+//! its URL.
+//!
+//! Responses are stored as received, before any automatic decompression.
+//!
+//! Each request can also choose how it uses the cache with
+//! [`RequestBuilder::cache_mode`](crate::RequestBuilder::cache_mode). For
+//! instance, an application working offline can only use stored responses:
+//!
+//! ```rust
+//! # async fn run(client: reqwest::Client) -> Result<(), reqwest::Error> {
+//! use reqwest::cache::Mode;
+//!
+//! match client
+//!     .get("https://example.com/feed")
+//!     .cache_mode(Mode::OnlyIfCached)
+//!     .send()
+//!     .await
+//! {
+//!     Ok(res) => println!("{}", res.text().await?),
+//!     Err(err) if err.is_cache_miss() => println!("not available offline"),
+//!     Err(err) => return Err(err),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A client can also use stale responses within the windows allowed by the
+//! `stale-while-revalidate` and `stale-if-error` directives of [RFC 5861],
+//! with [`ClientBuilder::cache_stale_while_revalidate`] and
+//! [`ClientBuilder::cache_stale_if_error`].
+//!
+//! A client that should always get responses from the server can instead
+//! only remember their validators, with
+//! [`ClientBuilder::conditional_requests`](crate::ClientBuilder::conditional_requests).
+//! Each `GET` request then carries the `If-None-Match` and
+//! `If-Modified-Since` headers matching the last response for its URL, and a
+//! `304 Not Modified` answer is either returned as is, or replaced by the
+//! stored response, depending on [`NotModified`].
+//!
+//! # Optional
+//!
+//! This requires the optional `cache` feature to be enabled.
+//!
+//! [RFC 9111]: https://www.rfc-editor.org/rfc/rfc9111.html
+//! [RFC 5861]: https://www.rfc-editor.org/rfc/rfc5861.html
+//! [`ClientBuilder::cache_stale_while_revalidate`]: crate::ClientBuilder::cache_stale_while_revalidate
+//! [`ClientBuilder::cache_stale_if_error`]: crate::ClientBuilder::cache_stale_if_error
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::{HeaderMap, StatusCode};
+
+mod conditional;
+mod disk;
+mod service;
+
+pub(crate) use self::conditional::{ConditionalService, Settings as ConditionalSettings};
+pub use self::disk::DiskStore;
+pub(crate) use self::service::{CacheService, Settings};
+
+/// Storage for cached responses.
+///
+/// Keys are the URLs of the requests, so there is one entry for each URL: a
+/// response for other values of the headers named by its `Vary` header
+/// replaces the stored one. A store may drop entries at any time, for
+/// instance to stay within a size limit.
+///
+/// `get` is called on a blocking thread, with
+/// [`spawn_blocking`](tokio::task::spawn_blocking), so it may read from
+/// disk. `put` and `remove` are called from the tasks sending requests, so
+/// they should not block for long.
+pub trait Store: Send + Sync {
+    /// Get the entry stored for a key.
+    fn get(&self, key: &str) -> Option<Entry>;
+
+    /// Store an entry, replacing any previous entry for the key.
+    fn put(&self, key: &str, entry: Entry);
+
+    /// Remove the entry stored for a key.
+    fn remove(&self, key: &str);
+}
+
+/// How a request uses the cache.
+///
+/// These are the modes of the `cache` option of the [Fetch Standard].
+///
+/// [Fetch Standard]: https://fetch.spec.whatwg.org/#concept-request-cache-mode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Use fresh stored responses, revalidate stale ones, and store new
+    /// responses.
+    #[default]
+    Default,
+    /// Neither use nor update the cache.
+    NoStore,
+    /// Send the request without using stored responses, and store the new
+    /// response.
+    Reload,
+    /// Revalidate stored responses, even fresh ones, and store new
+    /// responses.
+    NoCache,
+    /// Use a stored response, even a stale one, and only send the request
+    /// if there is none.
+    ForceCache,
+    /// Only use a stored response, even a stale one.
+    ///
+    /// When there is none, the request fails with an error for which
+    /// [`Error::is_cache_miss`](crate::Error::is_cache_miss) returns `true`,
+    /// without being sent.
+    OnlyIfCached,
+}
+
+/// What a client making conditional requests returns for a
+/// `304 Not Modified` response.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotModified {
+    /// Return the `304 Not Modified` response, leaving the caller to use
+    /// the body it already has.
+    ///
+    /// Only the headers of the responses are stored.
+    #[default]
+    Return,
+    /// Return the stored response instead, with the headers of the
+    /// `304 Not Modified` response.
+    ///
+    /// The bodies of the responses are stored too.
+    Fulfill,
+}
+
+/// A stored response.
+#[derive(Clone)]
+pub struct Entry {
+    status: StatusCode,
+    headers: HeaderMap,
+    varied: HeaderMap,
+    body: Bytes,
+    stored_at: SystemTime,
+}
+
+impl Entry {
+    /// An entry for a response, stored now.
+    pub fn new(status: StatusCode, headers: HeaderMap, body: Bytes) -> Entry {
+        Entry {
+            status,
+            headers,
+            varied: HeaderMap::new(),
+            body,
+            stored_at: SystemTime::now(),
+        }
+    }
+
+    /// Set when the response was stored.
+    pub fn with_stored_at(mut self, stored_at: SystemTime) -> Entry {
+        self.stored_at = stored_at;
+        self
+    }
+
+    /// Set the request headers named by the `Vary` header of the response.
+    pub fn with_varied_headers(mut self, varied: HeaderMap) -> Entry {
+        self.varied = varied;
+        self
+    }
+
+    /// The status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The request headers named by the `Vary` header of the response.
+    ///
+    /// A later request only gets this response if it has the same values for
+    /// these headers.
+    pub fn varied_headers(&self) -> &HeaderMap {
+        &self.varied
+    }
+
+    /// The body of the response, as received.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// When the response was stored.
+    pub fn stored_at(&self) -> SystemTime {
+        self.stored_at
+    }
+}
+
+/// A request could only be answered from the cache, which had no response.
+#[derive(Debug)]
+pub(crate) struct NotCached;
+
+impl fmt::Display for NotCached {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("no cached response for an only-if-cached request")
+    }
+}
+
+impl StdError for NotCached {}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("body", &self.body.len())
+            .field("stored_at", &self.stored_at)
+            .finish()
+    }
+}
@@ -0,0 +1,697 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use bytes::{Bytes, BytesMut};
+use http::header::{
+    HeaderMap, HeaderName, HeaderValue, AGE, CACHE_CONTROL, CONTENT_LENGTH, DATE, ETAG, EXPIRES,
+    IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE, LAST_MODIFIED,
+    PRAGMA, RANGE, VARY,
+};
+use http::{Method, Request, Response, StatusCode};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::BodyExt;
+use pin_project_lite::pin_project;
+use tower::Service;
+
+use super::{Entry, Mode, NotCached, Store};
+use crate::async_impl::body::Body;
+
+/// Responses with a larger body are not stored.
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// How a client uses its cache.
+#[derive(Clone, Default)]
+pub(crate) struct Settings {
+    pub(crate) store: Option<Arc<dyn Store>>,
+    pub(crate) stale_while_revalidate: bool,
+    pub(crate) stale_if_error: bool,
+}
+
+/// A [`Service`] that answers requests from a cache when it can, and stores
+/// the responses it may reuse.
+#[derive(Clone)]
+pub(crate) struct CacheService<S> {
+    inner: S,
+    settings: Settings,
+}
+
+impl<S> CacheService<S> {
+    pub(crate) fn new(inner: S, settings: Settings) -> Self {
+        CacheService { inner, settings }
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for CacheService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: From<crate::Error> + Send,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+{
+    type Response = Response<CacheBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + Sync>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let Some(store) = self.settings.store.clone() else {
+            if mode(&req) == Mode::OnlyIfCached {
+                return Box::pin(async { Err(not_cached()) });
+            }
+            let fut = inner.call(req);
+            return Box::pin(sync_wrapper::SyncFuture::new(async move {
+                Ok(fut.await?.map(CacheBody::network))
+            }));
+        };
+        let settings = self.settings.clone();
+        Box::pin(sync_wrapper::SyncFuture::new(cached(
+            inner, store, settings, req,
+        )))
+    }
+}
+
+async fn cached<S, ResBody>(
+    mut inner: S,
+    store: Arc<dyn Store>,
+    settings: Settings,
+    mut req: Request<Body>,
+) -> Result<Response<CacheBody<ResBody>>, S::Error>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: From<crate::Error>,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+{
+    let key = req.uri().to_string();
+    let mode = mode(&req);
+
+    if !is_safe(req.method()) {
+        if mode == Mode::OnlyIfCached {
+            return Err(not_cached());
+        }
+        let res = inner.call(req).await?;
+        if res.status().is_success() || res.status().is_redirection() {
+            store.remove(&key);
+        }
+        return Ok(res.map(CacheBody::network));
+    }
+    if req.method() != Method::GET || bypasses_cache(req.headers()) || mode == Mode::NoStore {
+        if mode == Mode::OnlyIfCached {
+            return Err(not_cached());
+        }
+        return Ok(inner.call(req).await?.map(CacheBody::network));
+    }
+
+    let directives = Directives::parse(req.headers());
+    let now = SystemTime::now();
+    let stored = match directives.no_store || mode == Mode::Reload {
+        true => None,
+        false => get(&store, &key)
+            .await
+            .filter(|entry| entry.matches(req.headers())),
+    };
+    match stored {
+        Some(ref entry) => {
+            match mode {
+                Mode::ForceCache | Mode::OnlyIfCached => return Ok(entry.to_response(now)),
+                Mode::Default if entry.is_fresh(&directives, now) => {
+                    return Ok(entry.to_response(now))
+                }
+                _ => {}
+            }
+            entry.add_validators(req.headers_mut());
+            if mode == Mode::Default
+                && settings.stale_while_revalidate
+                && entry.may_revalidate_later(&directives, now)
+            {
+                revalidate_later(inner, store, key, entry.clone(), req, now);
+                return Ok(entry.to_response(now));
+            }
+        }
+        None if mode == Mode::OnlyIfCached => return Err(not_cached()),
+        None => {}
+    }
+
+    let serve_stale = |entry: &Option<Entry>| {
+        entry
+            .as_ref()
+            .filter(|entry| settings.stale_if_error && entry.may_serve_on_error(&directives, now))
+            .map(|entry| entry.to_response(now))
+    };
+
+    let req_headers = req.headers().clone();
+    let res = match inner.call(req).await {
+        Ok(res) => res,
+        Err(err) => return serve_stale(&stored).ok_or(err),
+    };
+
+    if is_error_status(res.status()) {
+        if let Some(stale) = serve_stale(&stored) {
+            return Ok(stale);
+        }
+    }
+    if res.status() == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = stored {
+            let entry = entry.refresh(res.headers(), now);
+            let res = entry.to_response(now);
+            store.put(&key, entry);
+            return Ok(res);
+        }
+    }
+    if !is_storable(&directives, res.status(), res.headers()) {
+        return Ok(res.map(CacheBody::network));
+    }
+
+    let entry = Entry::new(res.status(), res.headers().clone(), Bytes::new())
+        .with_varied_headers(varied_headers(res.headers(), &req_headers))
+        .with_stored_at(now);
+    let writer = Writer::new(store, key, entry);
+    Ok(res.map(|body| CacheBody::store(body, writer)))
+}
+
+/// Get the entry stored for a key on a blocking thread, as the store may
+/// read it from disk.
+pub(super) async fn get(store: &Arc<dyn Store>, key: &str) -> Option<Entry> {
+    let store = store.clone();
+    let key = key.to_owned();
+    match tokio::task::spawn_blocking(move || store.get(&key)).await {
+        Ok(entry) => entry,
+        Err(err) => {
+            log::debug!("cache lookup failed: {err}");
+            None
+        }
+    }
+}
+
+fn mode(req: &Request<Body>) -> Mode {
+    req.extensions().get::<Mode>().copied().unwrap_or_default()
+}
+
+fn not_cached<E: From<crate::Error>>() -> E {
+    crate::error::request(NotCached).into()
+}
+
+/// Revalidate a stale entry in a background task, for the next requests.
+fn revalidate_later<S, ResBody>(
+    mut inner: S,
+    store: Arc<dyn Store>,
+    key: String,
+    entry: Entry,
+    req: Request<Body>,
+    now: SystemTime,
+) where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Send + 'static,
+    S::Future: Send,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let req_headers = req.headers().clone();
+        let Ok(res) = inner.call(req).await else {
+            log::debug!("background revalidation of {key} failed");
+            return;
+        };
+        if res.status() == StatusCode::NOT_MODIFIED {
+            store.put(&key, entry.refresh(res.headers(), now));
+            return;
+        }
+        if !is_storable(&Directives::default(), res.status(), res.headers()) {
+            return;
+        }
+        let stored = Entry::new(res.status(), res.headers().clone(), Bytes::new())
+            .with_varied_headers(varied_headers(res.headers(), &req_headers))
+            .with_stored_at(now);
+        let writer = Writer::new(store, key, stored);
+        // The writer stores the response once the body is read.
+        let _ = CacheBody::store(res.into_body(), writer).collect().await;
+    });
+}
+
+/// Statuses for which a stale response may be used instead, as defined by
+/// RFC 5861, section 4.
+fn is_error_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 500 | 502 | 503 | 504)
+}
+
+pub(super) fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Whether the caller handles validation or partial content itself.
+pub(super) fn bypasses_cache(headers: &HeaderMap) -> bool {
+    [
+        IF_NONE_MATCH,
+        IF_MODIFIED_SINCE,
+        IF_MATCH,
+        IF_UNMODIFIED_SINCE,
+        IF_RANGE,
+        RANGE,
+    ]
+    .iter()
+    .any(|name| headers.contains_key(name))
+}
+
+fn is_storable(request: &Directives, status: StatusCode, headers: &HeaderMap) -> bool {
+    // Statuses that may be reused without explicit freshness information.
+    const STATUSES: &[u16] = &[200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+    let response = Directives::parse(headers);
+    if request.no_store || response.no_store || !STATUSES.contains(&status.as_u16()) {
+        return false;
+    }
+    if vary_names(headers).any(|name| name == "*") {
+        return false;
+    }
+    // Without freshness or a validator, the response could never be reused.
+    response.max_age.is_some()
+        || headers.contains_key(EXPIRES)
+        || headers.contains_key(ETAG)
+        || headers.contains_key(LAST_MODIFIED)
+}
+
+pub(super) fn vary_names(headers: &HeaderMap) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+pub(super) fn varied_headers(res_headers: &HeaderMap, req_headers: &HeaderMap) -> HeaderMap {
+    let mut varied = HeaderMap::new();
+    for name in vary_names(res_headers) {
+        let Ok(name) = HeaderName::from_bytes(name.as_bytes()) else {
+            continue;
+        };
+        for value in req_headers.get_all(&name) {
+            varied.append(name.clone(), value.clone());
+        }
+    }
+    varied
+}
+
+fn http_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    let value = headers.get(name)?.to_str().ok()?;
+    httpdate::parse_http_date(value).ok()
+}
+
+impl Entry {
+    /// Whether a request may get this response, given the `Vary` header.
+    pub(super) fn matches(&self, req_headers: &HeaderMap) -> bool {
+        vary_names(&self.headers).all(|name| {
+            let stored = self.varied.get_all(name).iter();
+            let sent = req_headers.get_all(name).iter();
+            stored.eq(sent)
+        })
+    }
+
+    fn is_fresh(&self, request: &Directives, now: SystemTime) -> bool {
+        let response = Directives::parse(&self.headers);
+        if request.no_cache || response.no_cache {
+            return false;
+        }
+        let lifetime = match request.max_age {
+            Some(max_age) => self.freshness_lifetime().min(max_age),
+            None => self.freshness_lifetime(),
+        };
+        self.age(now) < lifetime
+    }
+
+    /// Whether the entry may be returned while it is revalidated.
+    fn may_revalidate_later(&self, request: &Directives, now: SystemTime) -> bool {
+        let response = Directives::parse(&self.headers);
+        self.is_within_stale_window(request, &response, response.stale_while_revalidate, now)
+    }
+
+    /// Whether the entry may be returned when the server fails.
+    fn may_serve_on_error(&self, request: &Directives, now: SystemTime) -> bool {
+        let response = Directives::parse(&self.headers);
+        let window = request.stale_if_error.or(response.stale_if_error);
+        self.is_within_stale_window(request, &response, window, now)
+    }
+
+    fn is_within_stale_window(
+        &self,
+        request: &Directives,
+        response: &Directives,
+        window: Option<Duration>,
+        now: SystemTime,
+    ) -> bool {
+        if request.no_cache || response.no_cache || response.must_revalidate {
+            return false;
+        }
+        let Some(window) = window else {
+            return false;
+        };
+        self.age(now).saturating_sub(self.freshness_lifetime()) <= window
+    }
+
+    fn freshness_lifetime(&self) -> Duration {
+        if let Some(max_age) = Directives::parse(&self.headers).max_age {
+            return max_age;
+        }
+        let date = http_date(&self.headers, DATE).unwrap_or(self.stored_at);
+        if self.headers.contains_key(EXPIRES) {
+            // An invalid date means the response has already expired.
+            return http_date(&self.headers, EXPIRES)
+                .and_then(|expires| expires.duration_since(date).ok())
+                .unwrap_or_default();
+        }
+        // A tenth of the time since the last modification, as suggested by
+        // RFC 9111, section 4.2.2.
+        http_date(&self.headers, LAST_MODIFIED)
+            .and_then(|modified| date.duration_since(modified).ok())
+            .map(|since| since / 10)
+            .unwrap_or_default()
+    }
+
+    fn age(&self, now: SystemTime) -> Duration {
+        let received = self
+            .headers
+            .get(AGE)
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+        received + now.duration_since(self.stored_at).unwrap_or_default()
+    }
+
+    pub(super) fn add_validators(&self, headers: &mut HeaderMap) {
+        if let Some(etag) = self.headers.get(ETAG) {
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(modified) = self.headers.get(LAST_MODIFIED) {
+            headers.insert(IF_MODIFIED_SINCE, modified.clone());
+        }
+    }
+
+    /// Update the entry with the headers of a `304 Not Modified` response.
+    pub(super) fn refresh(mut self, headers: &HeaderMap, now: SystemTime) -> Entry {
+        self.headers.remove(AGE);
+        for name in headers.keys() {
+            if name == CONTENT_LENGTH {
+                continue;
+            }
+            self.headers.remove(name);
+            for value in headers.get_all(name) {
+                self.headers.append(name.clone(), value.clone());
+            }
+        }
+        self.stored_at = now;
+        self
+    }
+
+    pub(super) fn to_response<B>(&self, now: SystemTime) -> Response<CacheBody<B>> {
+        let mut res = Response::new(CacheBody::Stored {
+            body: Some(self.body.clone()),
+        });
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut()
+            .insert(AGE, HeaderValue::from(self.age(now).as_secs()));
+        res
+    }
+}
+
+/// The directives of `Cache-Control` that this cache follows.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Directives {
+    pub(super) no_store: bool,
+    no_cache: bool,
+    must_revalidate: bool,
+    max_age: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    stale_if_error: Option<Duration>,
+}
+
+impl Directives {
+    pub(super) fn parse(headers: &HeaderMap) -> Directives {
+        let mut directives = Directives::default();
+        let values = headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for directive in values {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            let seconds = || {
+                value
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs)
+            };
+            if name.eq_ignore_ascii_case("no-store") {
+                directives.no_store = true;
+            } else if name.eq_ignore_ascii_case("no-cache") {
+                directives.no_cache = true;
+            } else if name.eq_ignore_ascii_case("must-revalidate")
+                || name.eq_ignore_ascii_case("proxy-revalidate")
+            {
+                directives.must_revalidate = true;
+            } else if name.eq_ignore_ascii_case("max-age") {
+                directives.max_age = seconds();
+            } else if name.eq_ignore_ascii_case("stale-while-revalidate") {
+                directives.stale_while_revalidate = seconds();
+            } else if name.eq_ignore_ascii_case("stale-if-error") {
+                directives.stale_if_error = seconds();
+            }
+        }
+        if !headers.contains_key(CACHE_CONTROL) {
+            directives.no_cache = headers
+                .get_all(PRAGMA)
+                .iter()
+                .any(|value| value.as_bytes().eq_ignore_ascii_case(b"no-cache"));
+        }
+        directives
+    }
+}
+
+/// Collects a response body, to store it once it is complete.
+pub(crate) struct Writer {
+    store: Arc<dyn Store>,
+    key: String,
+    entry: Entry,
+    buf: BytesMut,
+}
+
+impl Writer {
+    pub(super) fn new(store: Arc<dyn Store>, key: String, entry: Entry) -> Writer {
+        Writer {
+            store,
+            key,
+            entry,
+            buf: BytesMut::new(),
+        }
+    }
+
+    /// Add some data, returning `false` if the body is too large to store.
+    fn push(&mut self, data: &[u8]) -> bool {
+        if self.buf.len() + data.len() > MAX_BODY_SIZE {
+            return false;
+        }
+        self.buf.extend_from_slice(data);
+        true
+    }
+
+    fn finish(mut self) {
+        self.entry.body = self.buf.freeze();
+        self.store.put(&self.key, self.entry);
+    }
+}
+
+pin_project! {
+    /// A response body, either from the cache or being stored in it.
+    #[project = CacheBodyProj]
+    pub(crate) enum CacheBody<B> {
+        Stored {
+            body: Option<Bytes>,
+        },
+        Network {
+            #[pin]
+            inner: B,
+            writer: Option<Writer>,
+        },
+    }
+}
+
+impl<B: HttpBody> CacheBody<B> {
+    pub(super) fn network(inner: B) -> Self {
+        CacheBody::Network {
+            inner,
+            writer: None,
+        }
+    }
+
+    pub(super) fn store(inner: B, writer: Writer) -> Self {
+        let writer = if inner.is_end_stream() {
+            writer.finish();
+            None
+        } else {
+            Some(writer)
+        };
+        CacheBody::Network { inner, writer }
+    }
+}
+
+impl<B> HttpBody for CacheBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.project() {
+            CacheBodyProj::Stored { body } => {
+                let body = body.take().filter(|body| !body.is_empty());
+                Poll::Ready(body.map(|body| Ok(Frame::data(body))))
+            }
+            CacheBodyProj::Network { mut inner, writer } => {
+                match ready!(inner.as_mut().poll_frame(cx)) {
+                    Some(Ok(frame)) => {
+                        if let (Some(data), Some(w)) = (frame.data_ref(), writer.as_mut()) {
+                            if !w.push(data) {
+                                *writer = None;
+                            }
+                        }
+                        if inner.is_end_stream() {
+                            if let Some(writer) = writer.take() {
+                                writer.finish();
+                            }
+                        }
+                        Poll::Ready(Some(Ok(frame)))
+                    }
+                    Some(Err(err)) => {
+                        *writer = None;
+                        Poll::Ready(Some(Err(err)))
+                    }
+                    None => {
+                        if let Some(writer) = writer.take() {
+                            writer.finish();
+                        }
+                        Poll::Ready(None)
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            CacheBody::Stored { body } => {
+                SizeHint::with_exact(body.as_ref().map_or(0, |body| body.len() as u64))
+            }
+            CacheBody::Network { inner, .. } => inner.size_hint(),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            CacheBody::Stored { body } => body.as_ref().is_none_or(|body| body.is_empty()),
+            CacheBody::Network { inner, .. } => inner.is_end_stream(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|&(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_directives() {
+        let parsed = Directives::parse(&headers(&[
+            ("cache-control", "public, Max-Age=\"60\""),
+            ("cache-control", "no-cache"),
+        ]));
+        assert_eq!(
+            parsed,
+            Directives {
+                no_cache: true,
+                max_age: Some(Duration::from_secs(60)),
+                ..Directives::default()
+            }
+        );
+
+        let parsed = Directives::parse(&headers(&[(
+            "cache-control",
+            "max-age=0, stale-while-revalidate=30, stale-if-error=600",
+        )]));
+        assert_eq!(parsed.stale_while_revalidate, Some(Duration::from_secs(30)));
+        assert_eq!(parsed.stale_if_error, Some(Duration::from_secs(600)));
+
+        let parsed = Directives::parse(&headers(&[("pragma", "no-cache")]));
+        assert!(parsed.no_cache);
+    }
+
+    #[test]
+    fn freshness_lifetime() {
+        let entry = |pairs| Entry::new(StatusCode::OK, headers(pairs), Bytes::new());
+
+        let max_age = entry(&[
+            ("cache-control", "max-age=30"),
+            ("expires", "Thu, 01 Jan 2026 00:00:00 GMT"),
+        ]);
+        assert_eq!(max_age.freshness_lifetime(), Duration::from_secs(30));
+
+        let expires = entry(&[
+            ("date", "Thu, 01 Jan 2026 00:00:00 GMT"),
+            ("expires", "Thu, 01 Jan 2026 00:10:00 GMT"),
+        ]);
+        assert_eq!(expires.freshness_lifetime(), Duration::from_secs(600));
+
+        let invalid = entry(&[("expires", "0")]);
+        assert_eq!(invalid.freshness_lifetime(), Duration::ZERO);
+
+        let heuristic = entry(&[
+            ("date", "Thu, 01 Jan 2026 01:40:00 GMT"),
+            ("last-modified", "Thu, 01 Jan 2026 00:00:00 GMT"),
+        ]);
+        assert_eq!(heuristic.freshness_lifetime(), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn vary_matching() {
+        let entry = Entry::new(
+            StatusCode::OK,
+            headers(&[("vary", "accept, accept-language")]),
+            Bytes::new(),
+        )
+        .with_varied_headers(headers(&[("accept", "text/html")]));
+
+        assert!(entry.matches(&headers(&[("accept", "text/html")])));
+        assert!(!entry.matches(&headers(&[("accept", "application/json")])));
+        assert!(!entry.matches(&headers(&[
+            ("accept", "text/html"),
+            ("accept-language", "fr")
+        ])));
+    }
+}
@@ -0,0 +1,395 @@
+//! Circuit breakers
+//!
+//! When a host is down or overloaded, sending it more requests only adds to
+//! its load, and makes each caller wait for a failure. A circuit breaker,
+//! set with [`ClientBuilder::circuit_breaker`], watches the outcome of the
+//! requests to each host, and stops sending requests to a host which keeps
+//! failing:
+//!
+//! - while the circuit of a host is **closed**, requests are sent as usual,
+//!   and their failures are counted;
+//! - once too many of them fail, the circuit **opens**: for a cool-down
+//!   period, requests to the host fail at once, with an error for which
+//!   [`Error::is_circuit_open`](crate::Error::is_circuit_open) is `true`;
+//! - after the cool-down, the circuit is **half-open**: a few probe requests
+//!   are let through. If they all succeed, the circuit closes again, and if
+//!   one of them fails, it opens for another cool-down.
+//!
+//! A request fails if it ends with an error, such as a connect error or a
+//! timeout, or with a `5xx` status. Hosts are told apart by scheme, host and
+//! port, and each hop of a redirect counts for its own host. Retries of a
+//! request count as a single outcome, and a request dropped by its caller
+//! before it times out counts as none.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! # fn run() -> Result<(), reqwest::Error> {
+//! let client = reqwest::Client::builder()
+//!     .circuit_breaker(
+//!         reqwest::circuit::Breaker::new()
+//!             .failure_threshold(5)
+//!             .failure_rate(0.5, 20)
+//!             .cool_down(Duration::from_secs(10)),
+//!     )
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ClientBuilder::circuit_breaker`]: crate::ClientBuilder::circuit_breaker
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use http::{Request, Response, Uri};
+use tower::Service;
+
+use crate::async_impl::body::Body;
+
+/// Builder to configure a circuit breaker.
+///
+/// By default, the circuit of a host opens after 5 failures in a row, stays
+/// open for 30 seconds, and closes again after 1 successful probe.
+#[derive(Clone, Debug)]
+pub struct Breaker {
+    failure_threshold: u32,
+    failure_rate: Option<(f32, u32)>,
+    cool_down: Duration,
+    probes: u32,
+}
+
+impl Breaker {
+    /// Create a circuit breaker with the default settings.
+    pub fn new() -> Breaker {
+        Breaker {
+            failure_threshold: 5,
+            failure_rate: None,
+            cool_down: Duration::from_secs(30),
+            probes: 1,
+        }
+    }
+
+    /// Open the circuit of a host after `failures` requests to it failed in
+    /// a row.
+    ///
+    /// Default is `5`. A `failures` of `0` disables this check, leaving only
+    /// the [`failure_rate`](Breaker::failure_rate).
+    pub fn failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures;
+        self
+    }
+
+    /// Also open the circuit of a host once at least `rate` of its last
+    /// `window` requests failed.
+    ///
+    /// The rate is a fraction between `0.0` and `1.0`, and it is only
+    /// checked once `window` requests are known, so that a host isn't cut
+    /// off by the first failures.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not between `0.0` and `1.0`, or if `window` is
+    /// `0`.
+    pub fn failure_rate(mut self, rate: f32, window: u32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "failure rate must be between 0.0 and 1.0"
+        );
+        assert!(window > 0, "failure rate window must not be empty");
+        self.failure_rate = Some((rate, window));
+        self
+    }
+
+    /// Set how long the circuit of a host stays open, before probe
+    /// requests are sent to it.
+    ///
+    /// Default is 30 seconds.
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+
+    /// Set how many probe requests must succeed for a half-open circuit to
+    /// close again.
+    ///
+    /// Up to `probes` requests are sent at once while the circuit is
+    /// half-open, and the other requests fail as if it was open.
+    ///
+    /// Default is `1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probes` is `0`.
+    pub fn half_open_probes(mut self, probes: u32) -> Self {
+        assert!(probes > 0, "a half-open circuit needs at least one probe");
+        self.probes = probes;
+        self
+    }
+}
+
+impl Default for Breaker {
+    fn default() -> Breaker {
+        Breaker::new()
+    }
+}
+
+/// The circuits of the hosts of a client, shared by all its services.
+pub(crate) struct Circuits {
+    breaker: Breaker,
+    hosts: Mutex<HashMap<String, Circuit>>,
+}
+
+enum Circuit {
+    Closed {
+        failures_in_a_row: u32,
+        // the outcomes of the last requests, `true` for the failures
+        window: VecDeque<bool>,
+    },
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        probing: u32,
+        succeeded: u32,
+    },
+}
+
+impl Circuit {
+    fn closed() -> Circuit {
+        Circuit::Closed {
+            failures_in_a_row: 0,
+            window: VecDeque::new(),
+        }
+    }
+
+    fn is_healthy(&self) -> bool {
+        match self {
+            Circuit::Closed {
+                failures_in_a_row,
+                window,
+            } => *failures_in_a_row == 0 && !window.contains(&true),
+            _ => false,
+        }
+    }
+}
+
+impl Circuits {
+    pub(crate) fn new(breaker: Breaker) -> Circuits {
+        Circuits {
+            breaker,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Let a request to `uri` through, unless the circuit of its host is
+    /// open.
+    fn admit(
+        self: &Arc<Self>,
+        uri: &Uri,
+        timed_out: Option<TimedOutSlot>,
+    ) -> Result<Admission, CircuitOpen> {
+        let key = format!(
+            "{}://{}",
+            uri.scheme_str().unwrap_or(""),
+            uri.authority().map_or("", |a| a.as_str())
+        );
+        let mut hosts = self.hosts.lock().expect("lock poisoned");
+        if !hosts.contains_key(&key) {
+            // forget the hosts without recent failures
+            hosts.retain(|_, circuit| !circuit.is_healthy());
+        }
+        let circuit = hosts.entry(key.clone()).or_insert_with(Circuit::closed);
+        let probe = match circuit {
+            Circuit::Closed { .. } => false,
+            Circuit::Open { until } if Instant::now() < *until => return Err(CircuitOpen),
+            Circuit::Open { .. } => {
+                *circuit = Circuit::HalfOpen {
+                    probing: 1,
+                    succeeded: 0,
+                };
+                true
+            }
+            Circuit::HalfOpen { probing, succeeded } => {
+                if *probing + *succeeded >= self.breaker.probes {
+                    return Err(CircuitOpen);
+                }
+                *probing += 1;
+                true
+            }
+        };
+        Ok(Admission {
+            circuits: self.clone(),
+            key,
+            probe,
+            outcome: None,
+            timed_out,
+        })
+    }
+
+    fn record(&self, key: &str, probe: bool, failed: Option<bool>) {
+        let mut hosts = self.hosts.lock().expect("lock poisoned");
+        let Some(circuit) = hosts.get_mut(key) else {
+            return;
+        };
+        let open = Circuit::Open {
+            until: Instant::now() + self.breaker.cool_down,
+        };
+        match circuit {
+            Circuit::Closed {
+                failures_in_a_row,
+                window,
+            } if !probe => {
+                let Some(failed) = failed else {
+                    return;
+                };
+                if failed {
+                    *failures_in_a_row += 1;
+                } else {
+                    *failures_in_a_row = 0;
+                }
+                if let Some((_, size)) = self.breaker.failure_rate {
+                    window.push_back(failed);
+                    if window.len() > size as usize {
+                        window.pop_front();
+                    }
+                }
+
+                let threshold = self.breaker.failure_threshold;
+                let over_threshold = threshold > 0 && *failures_in_a_row >= threshold;
+                let over_rate = self.breaker.failure_rate.is_some_and(|(rate, size)| {
+                    let failures = window.iter().filter(|&&failed| failed).count();
+                    window.len() == size as usize && failures as f32 >= rate * size as f32
+                });
+                if failed && (over_threshold || over_rate) {
+                    *circuit = open;
+                }
+            }
+            Circuit::HalfOpen { probing, succeeded } if probe => match failed {
+                Some(true) => *circuit = open,
+                Some(false) => {
+                    *probing -= 1;
+                    *succeeded += 1;
+                    if *succeeded >= self.breaker.probes {
+                        *circuit = Circuit::closed();
+                    }
+                }
+                // the probe was dropped, let another one through
+                None => *probing -= 1,
+            },
+            // outcomes of requests sent before the circuit last changed
+            _ => (),
+        }
+    }
+}
+
+/// A request let through a circuit, which records its outcome once known,
+/// or when dropped.
+struct Admission {
+    circuits: Arc<Circuits>,
+    key: String,
+    probe: bool,
+    outcome: Option<bool>,
+    timed_out: Option<TimedOutSlot>,
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        // the timeouts of a request fire above this layer, which then drops
+        // the request without an outcome
+        let outcome = self.outcome.or_else(|| {
+            self.timed_out
+                .as_ref()
+                .filter(|slot| slot.is_set())
+                .map(|_| true)
+        });
+        self.circuits.record(&self.key, self.probe, outcome);
+    }
+}
+
+/// Set by a request when one of its timeouts fires, to tell its circuit
+/// that it failed, rather than being dropped by its caller.
+#[derive(Clone, Default)]
+pub(crate) struct TimedOutSlot(Arc<AtomicBool>);
+
+impl TimedOutSlot {
+    pub(crate) fn set(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+// ===== impl CircuitService =====
+
+/// A [`Service`] that fails the requests to the hosts whose circuit is
+/// open, and records the outcome of the others.
+#[derive(Clone)]
+pub(crate) struct CircuitService<S> {
+    inner: S,
+    circuits: Option<Arc<Circuits>>,
+}
+
+impl<S> CircuitService<S> {
+    pub(crate) fn new(inner: S, circuits: Option<Arc<Circuits>>) -> Self {
+        CircuitService { inner, circuits }
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for CircuitService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: From<crate::Error>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + Sync>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = mem::replace(&mut self.inner, clone);
+        let Some(circuits) = self.circuits.clone() else {
+            return Box::pin(sync_wrapper::SyncFuture::new(inner.call(req)));
+        };
+        Box::pin(sync_wrapper::SyncFuture::new(async move {
+            let timed_out = req.extensions().get::<TimedOutSlot>().cloned();
+            let mut admission = circuits
+                .admit(req.uri(), timed_out)
+                .map_err(crate::error::request)?;
+            let res = inner.call(req).await;
+            admission.outcome = Some(match res {
+                Ok(ref res) => res.status().is_server_error(),
+                Err(_) => true,
+            });
+            res
+        }))
+    }
+}
+
+/// A request was not sent, because the circuit of its host is open.
+#[derive(Debug)]
+pub(crate) struct CircuitOpen;
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("circuit open, the host failed too many requests")
+    }
+}
+
+impl StdError for CircuitOpen {}
@@ -0,0 +1,255 @@
+//! Client configuration from files.
+//!
+//! A [`ClientConfig`] holds the settings of a client that applications
+//! usually let their users tune, and can be deserialized with `serde` from
+//! any format, such as the TOML configuration file of an application:
+//!
+//! ```toml
+//! user_agent = "my-app/1.0"
+//! timeout = "30s"
+//! connect_timeout = "5s"
+//! max_redirects = 5
+//!
+//! [headers]
+//! x-tenant = "acme"
+//!
+//! [proxy]
+//! all = "http://proxy.internal:3128"
+//! no_proxy = "localhost, .internal"
+//!
+//! [pool]
+//! idle_timeout = "90s"
+//! max_idle_per_host = 8
+//!
+//! [tls]
+//! min_version = "1.2"
+//! root_certificates = ["/etc/my-app/ca.pem"]
+//! ```
+//!
+//! [`ClientBuilder::from_config`](crate::ClientBuilder::from_config) then
+//! starts a builder with these settings, which can still be changed before
+//! building the client:
+//!
+//! ```rust
+//! # fn run(config: reqwest::client_config::ClientConfig) -> Result<(), reqwest::Error> {
+//! let client = reqwest::ClientBuilder::from_config(&config)
+//!     .referer(false)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every setting is optional, and the ones missing keep the default of the
+//! builder. Durations are either a number of seconds, or a string with a
+//! unit, among `ms`, `s`, `m` and `h`, such as `"500ms"` or `"1.5s"`.
+//! Unknown settings are refused, so that typos don't go unnoticed.
+//!
+//! # Optional
+//!
+//! This requires the optional `client-config` feature to be enabled.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+
+/// The settings of a client, loaded from a configuration file.
+///
+/// See the [module documentation](self) for the format.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ClientConfig {
+    /// The `User-Agent` header of every request.
+    pub user_agent: Option<String>,
+    /// Headers added to every request.
+    pub headers: BTreeMap<String, String>,
+    /// The total timeout of a request.
+    #[serde(deserialize_with = "duration")]
+    pub timeout: Option<Duration>,
+    /// The timeout of the connect phase.
+    #[serde(deserialize_with = "duration")]
+    pub connect_timeout: Option<Duration>,
+    /// The timeout of each read.
+    #[serde(deserialize_with = "duration")]
+    pub read_timeout: Option<Duration>,
+    /// The maximum number of redirects followed, `0` to follow none.
+    pub max_redirects: Option<usize>,
+    /// Whether to refuse URLs which aren't `https`.
+    pub https_only: Option<bool>,
+    /// The proxy settings.
+    pub proxy: ProxyConfig,
+    /// The connection pool settings.
+    pub pool: PoolConfig,
+    /// The TCP settings.
+    pub tcp: TcpConfig,
+    /// The TLS settings.
+    pub tls: TlsConfig,
+}
+
+/// The proxy settings of a [`ClientConfig`].
+///
+/// Without any proxy, the client uses the proxies of the system, unless
+/// `system` is `false`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ProxyConfig {
+    /// The proxy of all requests.
+    pub all: Option<String>,
+    /// The proxy of `http` requests.
+    pub http: Option<String>,
+    /// The proxy of `https` requests.
+    pub https: Option<String>,
+    /// The hosts reached without the proxies, in the format of
+    /// [`NoProxy::from_string`](crate::NoProxy::from_string).
+    pub no_proxy: Option<String>,
+    /// Whether to use the proxies of the system.
+    pub system: Option<bool>,
+}
+
+/// The connection pool settings of a [`ClientConfig`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct PoolConfig {
+    /// How long an idle connection is kept open.
+    #[serde(deserialize_with = "duration")]
+    pub idle_timeout: Option<Duration>,
+    /// The maximum number of idle connections kept for each host.
+    pub max_idle_per_host: Option<usize>,
+}
+
+/// The TCP settings of a [`ClientConfig`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct TcpConfig {
+    /// Whether to set `TCP_NODELAY` on the sockets.
+    pub nodelay: Option<bool>,
+    /// The interval of the TCP keepalive probes.
+    #[serde(deserialize_with = "duration")]
+    pub keepalive: Option<Duration>,
+}
+
+/// The TLS settings of a [`ClientConfig`].
+///
+/// Applying them requires a TLS backend: without one, building the client
+/// fails if any of them is set.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct TlsConfig {
+    /// The minimum TLS version, among `"1.0"`, `"1.1"`, `"1.2"` and `"1.3"`.
+    pub min_version: Option<String>,
+    /// The maximum TLS version, in the same format as `min_version`.
+    pub max_version: Option<String>,
+    /// PEM files of root certificates trusted in addition to the built-in
+    /// ones.
+    pub root_certificates: Vec<PathBuf>,
+    /// Whether to trust only the `root_certificates`.
+    pub certs_only: bool,
+    /// Whether to use TLS server name indication.
+    pub sni: Option<bool>,
+    /// Whether to accept invalid certificates. This is dangerous, see
+    /// [`ClientBuilder::tls_danger_accept_invalid_certs`](crate::ClientBuilder::tls_danger_accept_invalid_certs).
+    pub danger_accept_invalid_certs: Option<bool>,
+}
+
+#[cfg(not(feature = "__tls"))]
+impl TlsConfig {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.min_version.is_none()
+            && self.max_version.is_none()
+            && self.root_certificates.is_empty()
+            && !self.certs_only
+            && self.sni.is_none()
+            && self.danger_accept_invalid_certs.is_none()
+    }
+}
+
+#[cfg(feature = "__tls")]
+pub(crate) fn tls_version(version: &str) -> crate::Result<crate::tls::Version> {
+    match version.trim().trim_start_matches("TLS").trim() {
+        "1.0" => Ok(crate::tls::Version::TLS_1_0),
+        "1.1" => Ok(crate::tls::Version::TLS_1_1),
+        "1.2" => Ok(crate::tls::Version::TLS_1_2),
+        "1.3" => Ok(crate::tls::Version::TLS_1_3),
+        _ => Err(crate::error::builder(format!(
+            "unknown TLS version: {version:?}"
+        ))),
+    }
+}
+
+fn duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DurationVisitor).map(Some)
+}
+
+struct DurationVisitor;
+
+impl<'de> Visitor<'de> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number of seconds, or a duration such as \"500ms\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Duration, E> {
+        Ok(Duration::from_secs(secs))
+    }
+
+    fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Duration, E> {
+        u64::try_from(secs)
+            .map(Duration::from_secs)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(secs), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, secs: f64) -> Result<Duration, E> {
+        Duration::try_from_secs_f64(secs)
+            .map_err(|_| E::invalid_value(de::Unexpected::Float(secs), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Duration, E> {
+        parse_duration(s).ok_or_else(|| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+}
+
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value.parse().ok()?;
+    let secs = match unit.trim() {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2 m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1d"), None);
+        assert_eq!(parse_duration("-1s"), None);
+        assert_eq!(parse_duration("s"), None);
+    }
+}
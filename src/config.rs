@@ -108,3 +108,75 @@ pub(crate) struct TotalTimeout;
 impl RequestConfigValue for TotalTimeout {
     type Value = Duration;
 }
+
+#[derive(Clone, Copy)]
+pub(crate) struct Deadline;
+
+impl RequestConfigValue for Deadline {
+    type Value = std::time::Instant;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct ReadTimeout;
+
+impl RequestConfigValue for ReadTimeout {
+    type Value = Duration;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct HeaderTimeout;
+
+impl RequestConfigValue for HeaderTimeout {
+    type Value = Duration;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct OnInformational;
+
+impl RequestConfigValue for OnInformational {
+    type Value = crate::informational::Callback;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct EarlyHints;
+
+impl RequestConfigValue for EarlyHints {
+    type Value = crate::early_hints::Callback;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestPriority;
+
+impl RequestConfigValue for RequestPriority {
+    type Value = crate::in_flight::QueuePriority;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RawTarget;
+
+impl RequestConfigValue for RawTarget {
+    type Value = http::uri::PathAndQuery;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct InterfaceIndex;
+
+impl RequestConfigValue for InterfaceIndex {
+    type Value = u32;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct InFlightReserved;
+
+impl RequestConfigValue for InFlightReserved {
+    type Value = crate::in_flight::Reserved;
+}
+
+#[cfg(feature = "cache")]
+#[derive(Clone, Copy)]
+pub(crate) struct CacheMode;
+
+#[cfg(feature = "cache")]
+impl RequestConfigValue for CacheMode {
+    type Value = crate::cache::Mode;
+}
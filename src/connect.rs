@@ -14,6 +14,8 @@ use tower::util::{BoxCloneSyncServiceLayer, MapRequestLayer};
 use tower::{timeout::TimeoutLayer, util::BoxCloneSyncService, ServiceBuilder};
 use tower_service::Service;
 
+#[cfg(feature = "__tls")]
+use std::collections::HashMap;
 use std::future::Future;
 use std::io::{self, IoSlice};
 use std::net::IpAddr;
@@ -26,17 +28,21 @@ use std::time::Duration;
 use self::native_tls_conn::NativeTlsConn;
 #[cfg(feature = "__rustls")]
 use self::rustls_tls_conn::RustlsTlsConn;
+#[cfg(feature = "socks")]
 use crate::dns::DynResolver;
+use crate::dns::{BalancedTcp, Balancer};
 use crate::error::{cast_to_internal_error, BoxError};
+use crate::observe::{self, Counters, Observer, ObservedTcp};
 use crate::proxy::{Intercepted, Matcher as ProxyMatcher};
+use crate::proxy_protocol::{ProxyProtocol, ProxyProtocolTcp};
+use crate::redact::Redaction;
+use crate::tcp::TcpConnector;
 use sealed::{Conn, Unnameable};
 
-pub(crate) type HttpConnector = hyper_util::client::legacy::connect::HttpConnector<DynResolver>;
-
 #[derive(Clone)]
 pub(crate) enum Connector {
     // base service, with or without an embedded timeout
-    Simple(ConnectorService),
+    Simple(Box<ConnectorService>),
     // at least one custom layer along with maybe an outer timeout layer
     // from `builder.connect_timeout()`
     WithLayers(BoxCloneSyncService<Unnameable, Conn, BoxError>),
@@ -67,17 +73,27 @@ pub(crate) type BoxedConnectorService = BoxCloneSyncService<Unnameable, Conn, Bo
 pub(crate) type BoxedConnectorLayer =
     BoxCloneSyncServiceLayer<BoxedConnectorService, Unnameable, Conn, BoxError>;
 
+#[derive(Clone)]
 pub(crate) struct ConnectorBuilder {
     inner: Inner,
     proxies: Arc<Vec<ProxyMatcher>>,
     verbose: verbose::Wrapper,
+    observer: Option<Observer>,
+    counters: Option<Arc<Counters>>,
+    balancer: Option<Arc<Balancer>>,
+    proxy_protocol: Option<ProxyProtocol>,
     timeout: Option<Duration>,
+    retry: Option<crate::retry::Connect>,
     #[cfg(feature = "__tls")]
     nodelay: bool,
     #[cfg(feature = "__tls")]
     tls_info: bool,
     #[cfg(feature = "__tls")]
     user_agent: Option<HeaderValue>,
+    #[cfg(feature = "__tls")]
+    tls_reloader: TlsReloader,
+    #[cfg(feature = "__tls")]
+    host_tls: Arc<HostTls>,
     #[cfg(feature = "socks")]
     resolver: Option<DynResolver>,
     #[cfg(unix)]
@@ -94,12 +110,21 @@ where {
             inner: self.inner,
             proxies: self.proxies,
             verbose: self.verbose,
+            observer: self.observer,
+            counters: self.counters,
+            balancer: self.balancer,
+            proxy_protocol: self.proxy_protocol,
+            retry: self.retry,
             #[cfg(feature = "__tls")]
             nodelay: self.nodelay,
             #[cfg(feature = "__tls")]
             tls_info: self.tls_info,
             #[cfg(feature = "__tls")]
             user_agent: self.user_agent,
+            #[cfg(feature = "__tls")]
+            tls_reloader: self.tls_reloader,
+            #[cfg(feature = "__tls")]
+            host_tls: self.host_tls,
             simple_timeout: None,
             #[cfg(feature = "socks")]
             resolver: self.resolver.unwrap_or_else(DynResolver::gai),
@@ -123,7 +148,7 @@ where {
         if layers.is_empty() {
             // we have no user-provided layers, only use concrete types
             base_service.simple_timeout = self.timeout;
-            return Connector::Simple(base_service);
+            return Connector::Simple(Box::new(base_service));
         }
 
         // otherwise we have user provided layers
@@ -169,7 +194,7 @@ where {
 
     #[cfg(not(feature = "__tls"))]
     pub(crate) fn new<T>(
-        mut http: HttpConnector,
+        mut http: TcpConnector,
         proxies: Arc<Vec<ProxyMatcher>>,
         local_addr: T,
         #[cfg(any(
@@ -212,7 +237,12 @@ where {
             inner: Inner::Http(http),
             proxies,
             verbose: verbose::OFF,
+            observer: None,
+            counters: None,
+            balancer: None,
+            proxy_protocol: None,
             timeout: None,
+            retry: None,
             #[cfg(feature = "socks")]
             resolver: None,
             #[cfg(unix)]
@@ -224,7 +254,7 @@ where {
 
     #[cfg(feature = "__native-tls")]
     pub(crate) fn new_native_tls<T>(
-        http: HttpConnector,
+        http: TcpConnector,
         tls: TlsConnectorBuilder,
         proxies: Arc<Vec<ProxyMatcher>>,
         user_agent: Option<HeaderValue>,
@@ -275,7 +305,7 @@ where {
 
     #[cfg(feature = "__native-tls")]
     pub(crate) fn from_built_native_tls<T>(
-        mut http: HttpConnector,
+        mut http: TcpConnector,
         tls: TlsConnector,
         proxies: Arc<Vec<ProxyMatcher>>,
         user_agent: Option<HeaderValue>,
@@ -313,19 +343,27 @@ where {
             target_os = "watchos",
         ))]
         if let Some(interface) = interface {
-            http.set_interface(interface);
+            http.set_interface(interface.to_owned());
         }
         http.set_nodelay(nodelay);
         http.enforce_http(false);
 
+        let tls_reloader = TlsReloader::new(&proxies);
         ConnectorBuilder {
             inner: Inner::NativeTls(http, tls),
             proxies,
             verbose: verbose::OFF,
+            observer: None,
+            counters: None,
+            balancer: None,
+            proxy_protocol: None,
             nodelay,
             tls_info,
             user_agent,
+            tls_reloader,
+            host_tls: Default::default(),
             timeout: None,
+            retry: None,
             #[cfg(feature = "socks")]
             resolver: None,
             #[cfg(unix)]
@@ -337,7 +375,7 @@ where {
 
     #[cfg(feature = "__rustls")]
     pub(crate) fn new_rustls_tls<T>(
-        mut http: HttpConnector,
+        mut http: TcpConnector,
         tls: rustls::ClientConfig,
         proxies: Arc<Vec<ProxyMatcher>>,
         user_agent: Option<HeaderValue>,
@@ -380,14 +418,8 @@ where {
         http.set_nodelay(nodelay);
         http.enforce_http(false);
 
-        let (tls, tls_proxy) = if proxies.is_empty() {
-            let tls = Arc::new(tls);
-            (tls.clone(), tls)
-        } else {
-            let mut tls_proxy = tls.clone();
-            tls_proxy.alpn_protocols.clear();
-            (Arc::new(tls), Arc::new(tls_proxy))
-        };
+        let (tls, tls_proxy) = rustls_configs(tls, !proxies.is_empty());
+        let tls_reloader = TlsReloader::new(&proxies);
 
         ConnectorBuilder {
             inner: Inner::RustlsTls {
@@ -397,10 +429,17 @@ where {
             },
             proxies,
             verbose: verbose::OFF,
+            observer: None,
+            counters: None,
+            balancer: None,
+            proxy_protocol: None,
             nodelay,
             tls_info,
             user_agent,
+            tls_reloader,
+            host_tls: Default::default(),
             timeout: None,
+            retry: None,
             #[cfg(feature = "socks")]
             resolver: None,
             #[cfg(unix)]
@@ -410,12 +449,48 @@ where {
         }
     }
 
+    /// Get the handle replacing the TLS configuration of the connections
+    /// made by the connector once it is built.
+    #[cfg(feature = "__tls")]
+    pub(crate) fn tls_reloader(&self) -> TlsReloader {
+        self.tls_reloader.clone()
+    }
+
+    #[cfg(feature = "__tls")]
+    pub(crate) fn set_host_tls(&mut self, host_tls: HostTls) {
+        self.host_tls = Arc::new(host_tls);
+    }
+
     pub(crate) fn set_timeout(&mut self, timeout: Option<Duration>) {
         self.timeout = timeout;
     }
 
+    pub(crate) fn set_retry(&mut self, retry: Option<crate::retry::Connect>) {
+        self.retry = retry;
+    }
+
     pub(crate) fn set_verbose(&mut self, enabled: bool) {
-        self.verbose.0 = enabled;
+        self.verbose.enabled = enabled;
+    }
+
+    pub(crate) fn set_redaction(&mut self, redaction: Option<Redaction>) {
+        self.verbose.redaction = redaction;
+    }
+
+    pub(crate) fn set_observer(&mut self, observer: Option<Observer>) {
+        self.observer = observer;
+    }
+
+    pub(crate) fn set_counters(&mut self, counters: Arc<Counters>) {
+        self.counters = Some(counters);
+    }
+
+    pub(crate) fn set_balancer(&mut self, balancer: Option<Arc<Balancer>>) {
+        self.balancer = balancer;
+    }
+
+    pub(crate) fn set_proxy_protocol(&mut self, header: Option<ProxyProtocol>) {
+        self.proxy_protocol = header;
     }
 
     pub(crate) fn set_keepalive(&mut self, dur: Option<Duration>) {
@@ -468,6 +543,17 @@ where {
         }
     }
 
+    pub(crate) fn set_interface_index(&mut self, index: Option<u32>) {
+        match &mut self.inner {
+            #[cfg(feature = "__native-tls")]
+            Inner::NativeTls(http, _tls) => http.set_interface_index(index),
+            #[cfg(feature = "__rustls")]
+            Inner::RustlsTls { http, .. } => http.set_interface_index(index),
+            #[cfg(not(feature = "__tls"))]
+            Inner::Http(http) => http.set_interface_index(index),
+        }
+    }
+
     #[cfg(unix)]
     pub(crate) fn set_unix_socket(&mut self, path: Option<Arc<std::path::Path>>) {
         self.unix_socket = path;
@@ -485,6 +571,13 @@ pub(crate) struct ConnectorService {
     inner: Inner,
     proxies: Arc<Vec<ProxyMatcher>>,
     verbose: verbose::Wrapper,
+    observer: Option<Observer>,
+    counters: Option<Arc<Counters>>,
+    balancer: Option<Arc<Balancer>>,
+    /// Sent on direct TCP connections, but not on connections to a proxy.
+    proxy_protocol: Option<ProxyProtocol>,
+    /// Attempts a connection again when it is refused or times out.
+    retry: Option<crate::retry::Connect>,
     /// When there is a single timeout layer and no other layers,
     /// we embed it directly inside our base Service::call().
     /// This lets us avoid an extra `Box::pin` indirection layer
@@ -496,6 +589,11 @@ pub(crate) struct ConnectorService {
     tls_info: bool,
     #[cfg(feature = "__tls")]
     user_agent: Option<HeaderValue>,
+    #[cfg(feature = "__tls")]
+    tls_reloader: TlsReloader,
+    /// Replaces the TLS configuration for the hosts which have their own.
+    #[cfg(feature = "__tls")]
+    host_tls: Arc<HostTls>,
     #[cfg(feature = "socks")]
     resolver: DynResolver,
     /// If set, this always takes priority over TCP.
@@ -508,12 +606,12 @@ pub(crate) struct ConnectorService {
 #[derive(Clone)]
 enum Inner {
     #[cfg(not(feature = "__tls"))]
-    Http(HttpConnector),
+    Http(TcpConnector),
     #[cfg(feature = "__native-tls")]
-    NativeTls(HttpConnector, TlsConnector),
+    NativeTls(TcpConnector, TlsConnector),
     #[cfg(any(feature = "__rustls"))]
     RustlsTls {
-        http: HttpConnector,
+        http: TcpConnector,
         tls: Arc<rustls::ClientConfig>,
         tls_proxy: Arc<rustls::ClientConfig>,
     },
@@ -521,7 +619,7 @@ enum Inner {
 
 impl Inner {
     #[cfg(feature = "socks")]
-    fn get_http_connector(&mut self) -> &mut crate::connect::HttpConnector {
+    fn get_http_connector(&mut self) -> &mut TcpConnector {
         match self {
             #[cfg(feature = "__native-tls")]
             Inner::NativeTls(http, _) => http,
@@ -549,11 +647,17 @@ impl ConnectorService {
             Inner::NativeTls(http, tls) => {
                 if dst.scheme() == Some(&Scheme::HTTPS) {
                     let host = dst.host().ok_or("no host in url")?.to_string();
-                    let conn = socks::connect(proxy, dst, dns, &self.resolver, http).await?;
+                    let tls = self.host_tls.native_tls(&dst).unwrap_or(tls).clone();
+                    let conn = socks::connect(proxy, dst, dns, &self.resolver, http)
+                        .await
+                        .map_err(crate::error::proxy)?;
                     let conn = TokioIo::new(conn);
                     let conn = TokioIo::new(conn);
-                    let tls_connector = tokio_native_tls::TlsConnector::from(tls.clone());
-                    let io = tls_connector.connect(&host, conn).await?;
+                    let tls_connector = tokio_native_tls::TlsConnector::from(tls);
+                    let io = tls_connector
+                        .connect(&host, conn)
+                        .await
+                        .map_err(crate::error::tls)?;
                     let io = TokioIo::new(io);
                     return Ok(Conn {
                         inner: self.verbose.wrap(NativeTlsConn { inner: io }),
@@ -568,9 +672,11 @@ impl ConnectorService {
                     use std::convert::TryFrom;
                     use tokio_rustls::TlsConnector as RustlsConnector;
 
-                    let tls = tls.clone();
+                    let tls = self.host_tls.rustls(&dst).unwrap_or(tls).clone();
                     let host = dst.host().ok_or("no host in url")?.to_string();
-                    let conn = socks::connect(proxy, dst, dns, &self.resolver, http).await?;
+                    let conn = socks::connect(proxy, dst, dns, &self.resolver, http)
+                        .await
+                        .map_err(crate::error::proxy)?;
                     let conn = TokioIo::new(conn);
                     let conn = TokioIo::new(conn);
                     let server_name =
@@ -578,7 +684,8 @@ impl ConnectorService {
                             .map_err(|_| "Invalid Server Name")?;
                     let io = RustlsConnector::from(tls)
                         .connect(server_name, conn)
-                        .await?;
+                        .await
+                        .map_err(crate::error::tls)?;
                     let io = TokioIo::new(io);
                     return Ok(Conn {
                         inner: self.verbose.wrap(RustlsTlsConn { inner: io }),
@@ -589,7 +696,9 @@ impl ConnectorService {
             }
             #[cfg(not(feature = "__tls"))]
             Inner::Http(http) => {
-                let conn = socks::connect(proxy, dst, dns, &self.resolver, http).await?;
+                let conn = socks::connect(proxy, dst, dns, &self.resolver, http)
+                    .await
+                    .map_err(crate::error::proxy)?;
                 return Ok(Conn {
                     inner: self.verbose.wrap(TokioIo::new(conn)),
                     is_proxy: false,
@@ -607,13 +716,21 @@ impl ConnectorService {
                 is_proxy: false,
                 tls_info: false,
             })
-            .map_err(Into::into)
+            .map_err(crate::error::proxy)
     }
 
     async fn connect_with_maybe_proxy(self, dst: Uri, is_proxy: bool) -> Result<Conn, BoxError> {
+        let proxy_protocol = self.proxy_protocol.clone().filter(|_| !is_proxy);
         match self.inner {
             #[cfg(not(feature = "__tls"))]
-            Inner::Http(mut http) => {
+            Inner::Http(http) => {
+                let mut http = ObservedTcp::new(
+                    BalancedTcp::new(
+                        ProxyProtocolTcp::new(http, proxy_protocol),
+                        self.balancer.clone(),
+                    ),
+                    self.observer.clone(),
+                );
                 let io = http.call(dst).await?;
                 Ok(Conn {
                     inner: self.verbose.wrap(io),
@@ -632,11 +749,28 @@ impl ConnectorService {
                     http.set_nodelay(true);
                 }
 
+                let tls = match self.host_tls.native_tls(&dst) {
+                    Some(host_tls) if !is_proxy => host_tls.clone(),
+                    _ => tls,
+                };
                 let tls_connector = tokio_native_tls::TlsConnector::from(tls.clone());
+                let host = dst.host().unwrap_or_default().to_owned();
+                let http = ObservedTcp::new(
+                    BalancedTcp::new(
+                        ProxyProtocolTcp::new(http, proxy_protocol),
+                        self.balancer.clone(),
+                    ),
+                    self.observer.clone(),
+                );
                 let mut http = hyper_tls::HttpsConnector::from((http, tls_connector));
                 let io = http.call(dst).await?;
 
                 if let hyper_tls::MaybeHttpsStream::Https(stream) = io {
+                    #[cfg(feature = "__native-tls-alpn")]
+                    let alpn = stream.inner().get_ref().negotiated_alpn().ok().flatten();
+                    #[cfg(not(feature = "__native-tls-alpn"))]
+                    let alpn: Option<Vec<u8>> = None;
+                    crate::observe::tls_handshake(self.observer.as_ref(), &host, alpn.as_deref());
                     if !self.nodelay {
                         stream
                             .inner()
@@ -671,10 +805,24 @@ impl ConnectorService {
                     http.set_nodelay(true);
                 }
 
+                let host = dst.host().unwrap_or_default().to_owned();
+                let http = ObservedTcp::new(
+                    BalancedTcp::new(
+                        ProxyProtocolTcp::new(http, proxy_protocol),
+                        self.balancer.clone(),
+                    ),
+                    self.observer.clone(),
+                );
+                let tls = match self.host_tls.rustls(&dst) {
+                    Some(host_tls) if !is_proxy => host_tls.clone(),
+                    _ => tls,
+                };
                 let mut http = hyper_rustls::HttpsConnector::from((http, tls));
                 let io = http.call(dst).await?;
 
                 if let hyper_rustls::MaybeHttpsStream::Https(stream) = io {
+                    let alpn = stream.inner().get_ref().1.alpn_protocol();
+                    crate::observe::tls_handshake(self.observer.as_ref(), &host, alpn);
                     if !self.nodelay {
                         let (io, _) = stream.inner().get_ref();
                         io.inner().inner().set_nodelay(false)?;
@@ -823,11 +971,16 @@ impl ConnectorService {
                     }
                     // We don't wrap this again in an HttpsConnector since that uses Maybe,
                     // and we know this is definitely HTTPS.
-                    let tunneled = tunnel.call(dst.clone()).await?;
+                    let tunneled = tunnel
+                        .call(dst.clone())
+                        .await
+                        .map_err(crate::error::proxy)?;
+                    let tls = self.host_tls.native_tls(&dst).unwrap_or(tls);
                     let tls_connector = tokio_native_tls::TlsConnector::from(tls.clone());
                     let io = tls_connector
                         .connect(dst.host().ok_or("no host in url")?, TokioIo::new(tunneled))
-                        .await?;
+                        .await
+                        .map_err(crate::error::tls)?;
                     return Ok(Conn {
                         inner: self.verbose.wrap(NativeTlsConn {
                             inner: TokioIo::new(io),
@@ -867,13 +1020,18 @@ impl ConnectorService {
                     }
                     // We don't wrap this again in an HttpsConnector since that uses Maybe,
                     // and we know this is definitely HTTPS.
-                    let tunneled = tunnel.call(dst.clone()).await?;
+                    let tunneled = tunnel
+                        .call(dst.clone())
+                        .await
+                        .map_err(crate::error::proxy)?;
                     let host = dst.host().ok_or("no host in url")?.to_string();
                     let server_name = ServerName::try_from(host.as_str().to_owned())
                         .map_err(|_| "Invalid Server Name")?;
+                    let tls = self.host_tls.rustls(&dst).unwrap_or(tls);
                     let io = RustlsConnector::from(tls.clone())
                         .connect(server_name, TokioIo::new(tunneled))
-                        .await?;
+                        .await
+                        .map_err(crate::error::tls)?;
 
                     return Ok(Conn {
                         inner: self.verbose.wrap(RustlsTlsConn {
@@ -888,7 +1046,9 @@ impl ConnectorService {
             Inner::Http(_) => (),
         }
 
-        self.connect_with_maybe_proxy(proxy_dst, true).await
+        self.connect_with_maybe_proxy(proxy_dst, true)
+            .await
+            .map_err(crate::error::proxy)
     }
 
     #[cfg(any(unix, target_os = "windows"))]
@@ -901,13 +1061,16 @@ impl ConnectorService {
     }
 }
 
-async fn with_timeout<T, F>(f: F, timeout: Option<Duration>) -> Result<T, BoxError>
+async fn with_timeout<T, F>(f: F, timeout: Option<Duration>, tls: bool) -> Result<T, BoxError>
 where
     F: Future<Output = Result<T, BoxError>>,
 {
     if let Some(to) = timeout {
         match tokio::time::timeout(to, f).await {
-            Err(_elapsed) => Err(Box::new(crate::error::TimedOut) as BoxError),
+            Err(_elapsed) => {
+                let phase = observe::connect_phase(tls);
+                Err(Box::new(crate::error::TimedOut(phase)) as BoxError)
+            }
             Ok(Ok(try_res)) => Ok(try_res),
             Ok(Err(e)) => Err(e),
         }
@@ -916,6 +1079,139 @@ where
     }
 }
 
+/// Replaces the TLS configuration of the new connections of a connector,
+/// when a client reloads its certificates.
+#[cfg(feature = "__tls")]
+#[derive(Clone)]
+pub(crate) struct TlsReloader(Arc<ReloadState>);
+
+#[cfg(feature = "__tls")]
+struct ReloadState {
+    current: std::sync::RwLock<Option<ReloadedTls>>,
+    #[cfg(feature = "__rustls")]
+    proxied: bool,
+}
+
+#[cfg(feature = "__tls")]
+#[derive(Clone)]
+enum ReloadedTls {
+    #[cfg(feature = "__native-tls")]
+    NativeTls(TlsConnector),
+    #[cfg(feature = "__rustls")]
+    Rustls {
+        tls: Arc<rustls::ClientConfig>,
+        tls_proxy: Arc<rustls::ClientConfig>,
+    },
+}
+
+#[cfg(feature = "__tls")]
+impl TlsReloader {
+    fn new(_proxies: &[ProxyMatcher]) -> TlsReloader {
+        TlsReloader(Arc::new(ReloadState {
+            current: Default::default(),
+            #[cfg(feature = "__rustls")]
+            proxied: !_proxies.is_empty(),
+        }))
+    }
+
+    #[cfg(feature = "__native-tls")]
+    pub(crate) fn set_native_tls(&self, tls: TlsConnectorBuilder) -> crate::Result<()> {
+        let tls = tls.build().map_err(crate::error::builder)?;
+        self.set(ReloadedTls::NativeTls(tls));
+        Ok(())
+    }
+
+    #[cfg(feature = "__rustls")]
+    pub(crate) fn set_rustls(&self, tls: rustls::ClientConfig) {
+        let (tls, tls_proxy) = rustls_configs(tls, self.0.proxied);
+        self.set(ReloadedTls::Rustls { tls, tls_proxy });
+    }
+
+    fn set(&self, tls: ReloadedTls) {
+        *self.0.current.write().expect("lock poisoned") = Some(tls);
+    }
+
+    fn apply(&self, inner: &mut Inner) {
+        let current = self.0.current.read().expect("lock poisoned");
+        match (current.as_ref(), inner) {
+            #[cfg(feature = "__native-tls")]
+            (Some(ReloadedTls::NativeTls(reloaded)), Inner::NativeTls(_, tls)) => {
+                *tls = reloaded.clone();
+            }
+            #[cfg(feature = "__rustls")]
+            (
+                Some(ReloadedTls::Rustls {
+                    tls: reloaded,
+                    tls_proxy: reloaded_proxy,
+                }),
+                Inner::RustlsTls { tls, tls_proxy, .. },
+            ) => {
+                *tls = reloaded.clone();
+                *tls_proxy = reloaded_proxy.clone();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The TLS configurations of the hosts which have TLS settings of their
+/// own, used in place of the one of the client for the connections to them,
+/// but not for the connections to a proxy.
+#[cfg(feature = "__tls")]
+#[derive(Default)]
+pub(crate) struct HostTls {
+    #[cfg(feature = "__native-tls")]
+    native_tls: HashMap<String, TlsConnector>,
+    #[cfg(feature = "__rustls")]
+    rustls: HashMap<String, Arc<rustls::ClientConfig>>,
+}
+
+#[cfg(feature = "__tls")]
+impl HostTls {
+    #[cfg(feature = "__native-tls")]
+    pub(crate) fn insert_native_tls(
+        &mut self,
+        host: &str,
+        tls: TlsConnectorBuilder,
+    ) -> crate::Result<()> {
+        let tls = tls.build().map_err(crate::error::builder)?;
+        self.native_tls.insert(host.to_owned(), tls);
+        Ok(())
+    }
+
+    #[cfg(feature = "__rustls")]
+    pub(crate) fn insert_rustls(&mut self, host: &str, tls: rustls::ClientConfig) {
+        self.rustls.insert(host.to_owned(), Arc::new(tls));
+    }
+
+    #[cfg(feature = "__native-tls")]
+    fn native_tls(&self, dst: &Uri) -> Option<&TlsConnector> {
+        self.native_tls.get(dst.host()?)
+    }
+
+    #[cfg(feature = "__rustls")]
+    fn rustls(&self, dst: &Uri) -> Option<&Arc<rustls::ClientConfig>> {
+        self.rustls.get(dst.host()?)
+    }
+}
+
+/// Split a rustls configuration into the one of direct connections, and the
+/// one of connections through a proxy, which don't negotiate ALPN.
+#[cfg(feature = "__rustls")]
+fn rustls_configs(
+    tls: rustls::ClientConfig,
+    proxied: bool,
+) -> (Arc<rustls::ClientConfig>, Arc<rustls::ClientConfig>) {
+    if proxied {
+        let mut tls_proxy = tls.clone();
+        tls_proxy.alpn_protocols.clear();
+        (Arc::new(tls), Arc::new(tls_proxy))
+    } else {
+        let tls = Arc::new(tls);
+        (tls.clone(), tls)
+    }
+}
+
 impl Service<Uri> for ConnectorService {
     type Response = Conn;
     type Error = BoxError;
@@ -927,7 +1223,44 @@ impl Service<Uri> for ConnectorService {
 
     fn call(&mut self, dst: Uri) -> Self::Future {
         log::debug!("starting new connection '{:?}'", dst.host());
+        #[cfg(feature = "__tls")]
+        self.tls_reloader.apply(&mut self.inner);
+        let connecting = match self.retry {
+            Some(ref retry) => Box::pin(self.clone().retrying(dst.clone(), retry.clone())),
+            None => self.connecting(dst.clone()),
+        };
+        Box::pin(observed::connect(
+            connecting,
+            dst,
+            self.observer.clone(),
+            self.counters.clone(),
+        ))
+    }
+}
+
+impl ConnectorService {
+    async fn retrying(self, dst: Uri, retry: crate::retry::Connect) -> Result<Conn, BoxError> {
+        let mut attempt = 1;
+        loop {
+            let err = match self.connecting(dst.clone()).await {
+                Ok(conn) => return Ok(conn),
+                Err(err) => err,
+            };
+            let Some(backoff) = retry.backoff_after(attempt, &*err) else {
+                return Err(err);
+            };
+            log::debug!(
+                "connect attempt {attempt} to {:?} failed, retrying in {backoff:?}: {err}",
+                dst.host()
+            );
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    fn connecting(&self, dst: Uri) -> Connecting {
         let timeout = self.simple_timeout;
+        let tls = dst.scheme_str() == Some("https");
 
         // Local transports (UDS, Windows Named Pipes) skip proxies
         #[cfg(any(unix, target_os = "windows"))]
@@ -935,6 +1268,7 @@ impl Service<Uri> for ConnectorService {
             return Box::pin(with_timeout(
                 self.clone().connect_local_transport(dst),
                 timeout,
+                false,
             ));
         }
 
@@ -943,6 +1277,7 @@ impl Service<Uri> for ConnectorService {
                 return Box::pin(with_timeout(
                     self.clone().connect_via_proxy(dst, intercepted),
                     timeout,
+                    tls,
                 ));
             }
         }
@@ -950,6 +1285,7 @@ impl Service<Uri> for ConnectorService {
         Box::pin(with_timeout(
             self.clone().connect_with_maybe_proxy(dst, false),
             timeout,
+            tls,
         ))
     }
 }
@@ -1855,7 +2191,7 @@ mod socks {
         dst: Uri,
         dns_mode: DnsResolve,
         resolver: &crate::dns::DynResolver,
-        http_connector: &mut crate::connect::HttpConnector,
+        http_connector: &mut crate::tcp::TcpConnector,
     ) -> Result<TcpStream, SocksProxyError> {
         let https = dst.scheme() == Some(&Scheme::HTTPS);
         let original_host = dst.host().ok_or(SocksProxyError::SocksNoHostInUrl)?;
@@ -1941,7 +2277,157 @@ mod socks {
     }
 }
 
+mod observed {
+    use super::{BoxConn, Conn, Connecting};
+    use crate::error::BoxError;
+    use crate::observe::{self, ConnectTimings, ConnectionInfo, Counters, Observer};
+    use http::Uri;
+    use hyper::rt::{Read, ReadBufCursor, Write};
+    use hyper_util::client::legacy::connect::{Connected, Connection};
+    use std::io::{self, IoSlice};
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::time::Instant;
+
+    pub(super) async fn connect(
+        connecting: Connecting,
+        dst: Uri,
+        observer: Option<Observer>,
+        counters: Option<Arc<Counters>>,
+    ) -> Result<Conn, BoxError> {
+        let (res, timings) = observe::track(connecting).await;
+        let mut conn = res?;
+        let observer = observer.map(|observer| {
+            let info = observe::connection_info(&dst, &conn.connected(), &timings);
+            observer.connection_established(&info);
+            (observer, info, Instant::now())
+        });
+        conn.inner = Box::new(Observed {
+            inner: conn.inner,
+            timings,
+            writing: false,
+            observer,
+            counters,
+        });
+        Ok(conn)
+    }
+
+    /// Records when requests are written, counts the bytes transferred, and
+    /// reports the connection as closed once it is dropped.
+    struct Observed {
+        inner: BoxConn,
+        timings: ConnectTimings,
+        writing: bool,
+        observer: Option<(Observer, ConnectionInfo, Instant)>,
+        counters: Option<Arc<Counters>>,
+    }
+
+    impl Drop for Observed {
+        fn drop(&mut self) {
+            if let Some((ref observer, ref info, opened)) = self.observer {
+                observer.connection_closed(info, opened.elapsed());
+            }
+        }
+    }
+
+    impl Observed {
+        fn wrote(&mut self, res: &Poll<io::Result<usize>>) {
+            if let Poll::Ready(Ok(n)) = *res {
+                if !self.writing {
+                    self.writing = true;
+                    self.timings.write_started();
+                }
+                if let Some(ref counters) = self.counters {
+                    counters.sent(n);
+                }
+            }
+        }
+    }
+
+    impl Connection for Observed {
+        fn connected(&self) -> Connected {
+            self.inner.connected().extra(self.timings.clone())
+        }
+    }
+
+    impl Read for Observed {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            mut buf: ReadBufCursor<'_>,
+        ) -> Poll<io::Result<()>> {
+            let Some(counters) = self.counters.clone() else {
+                return Pin::new(&mut self.inner).poll_read(cx, buf);
+            };
+            // SAFETY: Passing to a ReadBuf will never de-initialize any bytes.
+            let mut cbuf = hyper::rt::ReadBuf::uninit(unsafe { buf.as_mut() });
+            let res = Pin::new(&mut self.inner).poll_read(cx, cbuf.unfilled());
+            if let Poll::Ready(Ok(())) = res {
+                let len = cbuf.filled().len();
+                counters.received(len);
+                // SAFETY: The two cursors were for the same buffer. What was
+                // filled in one is safe in the other.
+                unsafe {
+                    buf.advance(len);
+                }
+            }
+            res
+        }
+    }
+
+    impl Write for Observed {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<Result<usize, io::Error>> {
+            let res = Pin::new(&mut self.inner).poll_write(cx, buf);
+            self.wrote(&res);
+            res
+        }
+
+        fn poll_write_vectored(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<Result<usize, io::Error>> {
+            let res = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+            self.wrote(&res);
+            res
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            self.inner.is_write_vectored()
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), io::Error>> {
+            let res = Pin::new(&mut self.inner).poll_flush(cx);
+            if self.writing && matches!(res, Poll::Ready(Ok(()))) {
+                self.writing = false;
+                self.timings.flushed();
+            }
+            res
+        }
+
+        fn poll_shutdown(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+        ) -> Poll<Result<(), io::Error>> {
+            Pin::new(&mut self.inner).poll_shutdown(cx)
+        }
+    }
+
+    #[cfg(feature = "__tls")]
+    impl super::TlsInfoFactory for Observed {
+        fn tls_info(&self) -> Option<crate::tls::TlsInfo> {
+            self.inner.tls_info()
+        }
+    }
+}
+
 mod verbose {
+    use crate::redact::{self, Redaction};
     use crate::util::Escape;
     use hyper::rt::{Read, ReadBufCursor, Write};
     use hyper_util::client::legacy::connect::{Connected, Connection};
@@ -1951,17 +2437,24 @@ mod verbose {
     use std::pin::Pin;
     use std::task::{Context, Poll};
 
-    pub(super) const OFF: Wrapper = Wrapper(false);
+    pub(super) const OFF: Wrapper = Wrapper {
+        enabled: false,
+        redaction: None,
+    };
 
-    #[derive(Clone, Copy)]
-    pub(super) struct Wrapper(pub(super) bool);
+    #[derive(Clone)]
+    pub(super) struct Wrapper {
+        pub(super) enabled: bool,
+        pub(super) redaction: Option<Redaction>,
+    }
 
     impl Wrapper {
         pub(super) fn wrap<T: super::AsyncConnWithInfo>(&self, conn: T) -> super::BoxConn {
-            if self.0 && log::log_enabled!(log::Level::Trace) {
+            if self.enabled && log::log_enabled!(log::Level::Trace) {
                 Box::new(Verbose {
                     // truncate is fine
                     id: crate::util::fast_random() as u32,
+                    redaction: self.redaction.clone(),
                     inner: conn,
                 })
             } else {
@@ -1972,6 +2465,7 @@ mod verbose {
 
     struct Verbose<T> {
         id: u32,
+        redaction: Option<Redaction>,
         inner: T,
     }
 
@@ -1993,7 +2487,11 @@ mod verbose {
             let mut vbuf = hyper::rt::ReadBuf::uninit(unsafe { buf.as_mut() });
             match Pin::new(&mut self.inner).poll_read(cx, vbuf.unfilled()) {
                 Poll::Ready(Ok(())) => {
-                    log::trace!("{:08x} read: {:?}", self.id, Escape::new(vbuf.filled()));
+                    log::trace!(
+                        "{:08x} read: {:?}",
+                        self.id,
+                        Escape::new(&redact::wire(self.redaction.as_ref(), vbuf.filled()))
+                    );
                     let len = vbuf.filled().len();
                     // SAFETY: The two cursors were for the same buffer. What was
                     // filled in one is safe in the other.
@@ -2016,7 +2514,11 @@ mod verbose {
         ) -> Poll<Result<usize, std::io::Error>> {
             match Pin::new(&mut self.inner).poll_write(cx, buf) {
                 Poll::Ready(Ok(n)) => {
-                    log::trace!("{:08x} write: {:?}", self.id, Escape::new(&buf[..n]));
+                    log::trace!(
+                        "{:08x} write: {:?}",
+                        self.id,
+                        Escape::new(&redact::wire(self.redaction.as_ref(), &buf[..n]))
+                    );
                     Poll::Ready(Ok(n))
                 }
                 Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
@@ -2034,7 +2536,11 @@ mod verbose {
                     log::trace!(
                         "{:08x} write (vectored): {:?}",
                         self.id,
-                        Vectored { bufs, nwritten }
+                        Vectored {
+                            bufs,
+                            nwritten,
+                            redaction: self.redaction.as_ref(),
+                        }
                     );
                     Poll::Ready(Ok(nwritten))
                 }
@@ -2072,6 +2578,7 @@ mod verbose {
     struct Vectored<'a, 'b> {
         bufs: &'a [IoSlice<'b>],
         nwritten: usize,
+        redaction: Option<&'a Redaction>,
     }
 
     impl fmt::Debug for Vectored<'_, '_> {
@@ -2082,7 +2589,7 @@ mod verbose {
                     break;
                 }
                 let n = min(left, buf.len());
-                Escape::new(&buf[..n]).fmt(f)?;
+                Escape::new(&redact::wire(self.redaction, &buf[..n])).fmt(f)?;
                 left -= n;
             }
             Ok(())
@@ -0,0 +1,442 @@
+//! Content-Digest support
+//!
+//! When enabled on a `Client`, outgoing request bodies get an [RFC 9530]
+//! `Content-Digest` field, and responses that carry one are verified as the
+//! body is read.
+//!
+//! [RFC 9530]: https://www.rfc-editor.org/rfc/rfc9530.html
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Buf;
+use http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, TRAILER};
+use http::{Method, Request, Response, StatusCode};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use tower::Service;
+
+use crate::async_impl::body::Body;
+use crate::error::BoxError;
+
+pub(crate) const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+
+/// The digest algorithms that can be computed and verified.
+///
+/// Ordered from weakest to strongest, so that when a field lists several
+/// digests, the strongest one is checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Algorithm {
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    const ALL: [Algorithm; 2] = [Algorithm::Sha256, Algorithm::Sha512];
+
+    fn key(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha-256",
+            Algorithm::Sha512 => "sha-512",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Algorithm> {
+        Algorithm::ALL
+            .into_iter()
+            .find(|alg| key.eq_ignore_ascii_case(alg.key()))
+    }
+
+    fn context(self) -> ring::digest::Context {
+        match self {
+            Algorithm::Sha256 => ring::digest::Context::new(&ring::digest::SHA256),
+            Algorithm::Sha512 => ring::digest::Context::new(&ring::digest::SHA512),
+        }
+    }
+}
+
+/// Format a `Content-Digest` field value for the given digest.
+fn field_value(alg: Algorithm, digest: &[u8]) -> HeaderValue {
+    let value = format!("{}=:{}:", alg.key(), STANDARD.encode(digest));
+    HeaderValue::try_from(value).expect("base64 is a valid header value")
+}
+
+/// Parse every `Content-Digest` value in the map, returning the strongest
+/// digest of an algorithm we understand.
+///
+/// The field is a structured Dictionary of byte sequences. Members that are
+/// malformed, or use an algorithm we don't know, are ignored.
+fn parse(headers: &HeaderMap) -> Option<(Algorithm, Vec<u8>)> {
+    headers
+        .get_all(CONTENT_DIGEST)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|member| {
+            let (key, value) = member.trim().split_once('=')?;
+            let alg = Algorithm::from_key(key.trim())?;
+            // drop any parameters
+            let value = value.split(';').next()?.trim();
+            let value = value.strip_prefix(':')?.strip_suffix(':')?;
+            let digest = STANDARD.decode(value).ok()?;
+            Some((alg, digest))
+        })
+        .max_by_key(|(alg, _)| *alg)
+}
+
+fn declares_trailer(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(TRAILER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|name| name.trim().eq_ignore_ascii_case(CONTENT_DIGEST.as_str()))
+}
+
+/// Add a `Content-Digest` to the request, unless one was already provided.
+///
+/// Buffered bodies are hashed immediately. Streaming bodies are hashed as
+/// they are sent, and the digest is sent as a trailer field.
+fn add_to_request(req: &mut Request<Body>) {
+    if req.headers().contains_key(CONTENT_DIGEST) || req.body().is_end_stream() {
+        return;
+    }
+
+    if let Some(bytes) = req.body().as_bytes() {
+        let mut ctx = Algorithm::Sha256.context();
+        ctx.update(bytes);
+        let value = field_value(Algorithm::Sha256, ctx.finish().as_ref());
+        req.headers_mut().insert(CONTENT_DIGEST, value);
+        return;
+    }
+
+    // A trailer can't be sent with a fixed length HTTP/1 body, and the
+    // length is already promised to the peer.
+    if req.headers().contains_key(CONTENT_LENGTH) {
+        log::debug!("skipping Content-Digest for a streaming body with a Content-Length");
+        return;
+    }
+
+    req.headers_mut()
+        .append(TRAILER, HeaderValue::from_static("content-digest"));
+    let body = std::mem::take(req.body_mut());
+    *req.body_mut() = Body::wrap(DigestTrailerBody {
+        inner: body,
+        alg: Algorithm::Sha256,
+        ctx: Some(Algorithm::Sha256.context()),
+    });
+}
+
+// ===== impl ContentDigestService =====
+
+/// A [`Service`] that computes request digests, and verifies response digests.
+#[derive(Clone)]
+pub(crate) struct ContentDigestService<S> {
+    inner: S,
+    enabled: bool,
+}
+
+impl<S> ContentDigestService<S> {
+    pub(crate) fn new(inner: S, enabled: bool) -> Self {
+        ContentDigestService { inner, enabled }
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for ContentDigestService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+{
+    type Response = Response<VerifyBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let verify = self.enabled && req.method() != Method::HEAD;
+        if self.enabled {
+            add_to_request(&mut req);
+        }
+        ResponseFuture {
+            future: self.inner.call(req),
+            verify,
+        }
+    }
+}
+
+pin_project! {
+    pub(crate) struct ResponseFuture<F> {
+        #[pin]
+        future: F,
+        verify: bool,
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<VerifyBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.future.poll(cx))?;
+        let verifier = if *this.verify {
+            Verifier::for_response(res.status(), res.headers())
+        } else {
+            None
+        };
+        Poll::Ready(Ok(res.map(|inner| VerifyBody { inner, verifier })))
+    }
+}
+
+// ===== impl DigestTrailerBody =====
+
+pin_project! {
+    /// A request body that sends its digest in the trailers.
+    struct DigestTrailerBody {
+        #[pin]
+        inner: Body,
+        alg: Algorithm,
+        ctx: Option<ring::digest::Context>,
+    }
+}
+
+impl HttpBody for DigestTrailerBody {
+    type Data = bytes::Bytes;
+    type Error = crate::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let ctx = match this.ctx {
+            Some(ctx) => ctx,
+            None => return Poll::Ready(None),
+        };
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    ctx.update(data);
+                    return Poll::Ready(Some(Ok(frame)));
+                }
+                match frame.into_trailers() {
+                    Ok(mut trailers) => {
+                        let digest = this.ctx.take().expect("checked above").finish();
+                        trailers
+                            .entry(CONTENT_DIGEST)
+                            .or_insert_with(|| field_value(*this.alg, digest.as_ref()));
+                        Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+                    }
+                    Err(frame) => Poll::Ready(Some(Ok(frame))),
+                }
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => {
+                let digest = this.ctx.take().expect("checked above").finish();
+                let mut trailers = HeaderMap::with_capacity(1);
+                trailers.insert(CONTENT_DIGEST, field_value(*this.alg, digest.as_ref()));
+                Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        // The trailers can only be sent over HTTP/1 if the body is chunked,
+        // so don't let the length be known ahead of time.
+        SizeHint::default()
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.ctx.is_none()
+    }
+}
+
+// ===== impl VerifyBody =====
+
+/// Checks the content against an expected digest.
+struct Verifier {
+    expected: Option<(Algorithm, Vec<u8>)>,
+    // One context for each algorithm that might be needed.
+    ctxs: Vec<(Algorithm, ring::digest::Context)>,
+}
+
+impl Verifier {
+    fn for_response(status: StatusCode, headers: &HeaderMap) -> Option<Verifier> {
+        if status == StatusCode::NO_CONTENT || status == StatusCode::NOT_MODIFIED {
+            return None;
+        }
+
+        if let Some((alg, digest)) = parse(headers) {
+            Some(Verifier {
+                expected: Some((alg, digest)),
+                ctxs: vec![(alg, alg.context())],
+            })
+        } else if declares_trailer(headers) {
+            // We don't know which algorithm will be used yet.
+            Some(Verifier {
+                expected: None,
+                ctxs: Algorithm::ALL
+                    .into_iter()
+                    .map(|alg| (alg, alg.context()))
+                    .collect(),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for (_, ctx) in &mut self.ctxs {
+            ctx.update(data);
+        }
+    }
+
+    fn trailers(&mut self, trailers: &HeaderMap) {
+        if self.expected.is_none() {
+            self.expected = parse(trailers);
+        }
+    }
+
+    fn finish(self) -> Result<(), BoxError> {
+        let (alg, expected) = match self.expected {
+            Some(expected) => expected,
+            // A trailer was promised, but never arrived. Nothing to check.
+            None => return Ok(()),
+        };
+        let (_, ctx) = self
+            .ctxs
+            .into_iter()
+            .find(|(a, _)| *a == alg)
+            .expect("context for every expected algorithm");
+        if ctx.finish().as_ref() == expected {
+            Ok(())
+        } else {
+            Err(crate::error::body(Mismatch { alg }).into())
+        }
+    }
+}
+
+pin_project! {
+    /// A response body that verifies the received content digest.
+    pub(crate) struct VerifyBody<B> {
+        #[pin]
+        inner: B,
+        verifier: Option<Verifier>,
+    }
+}
+
+impl<B> HttpBody for VerifyBody<B>
+where
+    B: HttpBody,
+    B::Error: Into<BoxError>,
+{
+    type Data = B::Data;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(verifier) = this.verifier {
+                    if let Some(data) = frame.data_ref() {
+                        verifier.update(data.chunk());
+                    } else if let Some(trailers) = frame.trailers_ref() {
+                        verifier.trailers(trailers);
+                    }
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
+            None => match this.verifier.take() {
+                Some(verifier) => Poll::Ready(verifier.finish().err().map(Err)),
+                None => Poll::Ready(None),
+            },
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        // The verifier still needs to be polled to finish.
+        self.verifier.is_none() && self.inner.is_end_stream()
+    }
+}
+
+// ===== impl Mismatch =====
+
+#[derive(Debug)]
+struct Mismatch {
+    alg: Algorithm,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "content digest mismatch ({})", self.alg.key())
+    }
+}
+
+impl StdError for Mismatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_picks_strongest_known() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            CONTENT_DIGEST,
+            HeaderValue::from_static("md5=:AAAA:, sha-256=:YQ==:"),
+        );
+        headers.append(CONTENT_DIGEST, HeaderValue::from_static("sha-512=:Yg==:;p=1"));
+
+        assert_eq!(parse(&headers), Some((Algorithm::Sha512, b"b".to_vec())));
+    }
+
+    #[test]
+    fn parse_ignores_malformed() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_DIGEST,
+            HeaderValue::from_static("sha-256=YQ==, sha-512=:not base64:"),
+        );
+
+        assert_eq!(parse(&headers), None);
+    }
+
+    #[test]
+    fn field_value_roundtrip() {
+        let digest = ring::digest::digest(&ring::digest::SHA256, b"hello");
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_DIGEST,
+            field_value(Algorithm::Sha256, digest.as_ref()),
+        );
+
+        assert_eq!(
+            headers[CONTENT_DIGEST],
+            "sha-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:"
+        );
+        assert_eq!(
+            parse(&headers),
+            Some((Algorithm::Sha256, digest.as_ref().to_vec()))
+        );
+    }
+}
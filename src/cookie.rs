@@ -2,9 +2,11 @@
 
 use crate::header::{HeaderValue, SET_COOKIE};
 use bytes::Bytes;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fmt;
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::SystemTime;
 
 /// Actions for a persistent cookie store providing session support.
@@ -27,8 +29,48 @@ pub struct Cookie<'a>(cookie_crate::Cookie<'a>);
 /// For more advanced scenarios, such as needing to serialize the store or
 /// manipulate it between requests, you may refer to the
 /// [reqwest_cookie_store crate](https://crates.io/crates/reqwest_cookie_store).
+///
+/// # Limits
+///
+/// A jar keeps every cookie it is given by default. With
+/// [`max_cookies`](Jar::max_cookies) or
+/// [`max_cookies_per_domain`](Jar::max_cookies_per_domain), storing a cookie
+/// past the limit first drops the expired cookies, then evicts the cookies
+/// least recently stored or sent. Cookies larger than
+/// [`max_cookie_size`](Jar::max_cookie_size) aren't stored at all.
+#[derive(Default)]
+pub struct Jar {
+    store: RwLock<cookie_store::CookieStore>,
+    limits: Limits,
+    // when each cookie was last stored or sent, only kept with a limit on
+    // the number of cookies
+    used: Mutex<HashMap<Key, u64>>,
+    clock: AtomicU64,
+    on_reject: Option<OnReject>,
+}
+
+/// Why a [`Jar`] dropped a cookie, as passed to [`Jar::on_reject`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Rejection {
+    /// The name and value of the cookie are longer than the
+    /// [`max_cookie_size`](Jar::max_cookie_size), and it wasn't stored.
+    TooLarge,
+    /// The cookie was stored, and evicted to make room for newer cookies.
+    Evicted,
+}
+
 #[derive(Debug, Default)]
-pub struct Jar(RwLock<cookie_store::CookieStore>);
+struct Limits {
+    per_domain: Option<usize>,
+    total: Option<usize>,
+    size: Option<usize>,
+}
+
+type OnReject = Arc<dyn Fn(&Cookie<'_>, Rejection) + Send + Sync>;
+
+/// The domain, path and name of a cookie in the store.
+type Key = (String, String, String);
 
 // ===== impl Cookie =====
 
@@ -159,7 +201,174 @@ impl Jar {
             .ok()
             .map(|c| c.into_owned())
             .into_iter();
-        self.0.write().unwrap().store_response_cookies(cookies, url);
+        self.store(cookies, url);
+    }
+
+    /// Set the maximum number of cookies stored for a single domain.
+    ///
+    /// Past it, the cookies of that domain are evicted, the expired ones
+    /// first, then the least recently used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use reqwest::cookie::Jar;
+    ///
+    /// let jar = Jar::default()
+    ///     .max_cookies_per_domain(50)
+    ///     .max_cookies(3000)
+    ///     .max_cookie_size(4096);
+    /// ```
+    pub fn max_cookies_per_domain(mut self, max: usize) -> Jar {
+        self.limits.per_domain = Some(max);
+        self
+    }
+
+    /// Set the maximum number of cookies stored in total.
+    ///
+    /// Past it, the cookies of any domain are evicted, the expired ones
+    /// first, then the least recently used.
+    pub fn max_cookies(mut self, max: usize) -> Jar {
+        self.limits.total = Some(max);
+        self
+    }
+
+    /// Set the maximum size of a cookie, as the length of its name and
+    /// value together.
+    ///
+    /// Larger cookies aren't stored.
+    pub fn max_cookie_size(mut self, max: usize) -> Jar {
+        self.limits.size = Some(max);
+        self
+    }
+
+    /// Set a function called with each cookie the jar drops, either because
+    /// it is too large to store or evicted by a limit.
+    ///
+    /// It is called once the jar is unlocked, and may use the jar.
+    pub fn on_reject<F>(mut self, f: F) -> Jar
+    where
+        F: Fn(&Cookie<'_>, Rejection) + Send + Sync + 'static,
+    {
+        self.on_reject = Some(Arc::new(f));
+        self
+    }
+
+    fn tracks_use(&self) -> bool {
+        self.limits.per_domain.is_some() || self.limits.total.is_some()
+    }
+
+    fn store<I>(&self, cookies: I, url: &url::Url)
+    where
+        I: Iterator<Item = cookie_crate::Cookie<'static>>,
+    {
+        let mut rejected = Vec::new();
+        {
+            let mut store = self.store.write().unwrap();
+            for cookie in cookies {
+                if self
+                    .limits
+                    .size
+                    .is_some_and(|max| cookie.name().len() + cookie.value().len() > max)
+                {
+                    rejected.push((Cookie(cookie), Rejection::TooLarge));
+                    continue;
+                }
+                if !self.tracks_use() {
+                    store.store_response_cookies(std::iter::once(cookie), url);
+                    continue;
+                }
+                let Ok(cookie) = cookie_store::Cookie::try_from_raw_cookie(&cookie, url) else {
+                    continue;
+                };
+                let key = key(&cookie);
+                match store.insert(cookie, url) {
+                    Ok(cookie_store::StoreAction::ExpiredExisting) => {
+                        self.used.lock().unwrap().remove(&key);
+                        continue;
+                    }
+                    Ok(_) => (),
+                    Err(_) => {
+                        let (domain, path, name) = &key;
+                        if !store.contains_any(domain, path, name) {
+                            self.used.lock().unwrap().remove(&key);
+                        }
+                        continue;
+                    }
+                }
+                self.touch([key.clone()]);
+                if let Some(max) = self.limits.per_domain {
+                    self.evict(&mut store, max, Some(&key.0), &mut rejected);
+                }
+                if let Some(max) = self.limits.total {
+                    self.evict(&mut store, max, None, &mut rejected);
+                }
+            }
+        }
+        if let Some(ref on_reject) = self.on_reject {
+            for (cookie, rejection) in &rejected {
+                on_reject(cookie, *rejection);
+            }
+        }
+    }
+
+    /// Mark the cookies of `keys` as just used.
+    fn touch(&self, keys: impl IntoIterator<Item = Key>) {
+        let mut used = self.used.lock().unwrap();
+        for key in keys {
+            used.insert(key, self.clock.fetch_add(1, Ordering::Relaxed));
+        }
+    }
+
+    /// Evict cookies of `domain`, or of any domain, until `max` are left.
+    fn evict(
+        &self,
+        store: &mut cookie_store::CookieStore,
+        max: usize,
+        domain: Option<&str>,
+        rejected: &mut Vec<(Cookie<'static>, Rejection)>,
+    ) {
+        let mut used = self.used.lock().unwrap();
+        let mut cookies = store
+            .iter_any()
+            .map(|cookie| (key(cookie), cookie.is_expired()))
+            .collect::<Vec<_>>();
+        // forget the cookies the store dropped by itself
+        let stored = cookies.iter().map(|(key, _)| key).collect::<HashSet<_>>();
+        used.retain(|key, _| stored.contains(key));
+        cookies.retain(|((d, _, _), _)| domain.is_none_or(|domain| d == domain));
+        if cookies.len() <= max {
+            return;
+        }
+        // the expired cookies first, then from the least recently used
+        cookies.sort_by_key(|(key, expired)| (!expired, used.get(key).copied()));
+        let excess = cookies.len() - max;
+        for (key, expired) in cookies.into_iter().take(excess) {
+            used.remove(&key);
+            let (domain, path, name) = &key;
+            if let Some(cookie) = store.remove(domain, path, name) {
+                if !expired {
+                    rejected.push((Cookie((*cookie).clone()), Rejection::Evicted));
+                }
+            }
+        }
+    }
+}
+
+fn key(cookie: &cookie_store::Cookie<'_>) -> Key {
+    (
+        String::from(&cookie.domain),
+        String::from(&cookie.path),
+        cookie.name().to_owned(),
+    )
+}
+
+impl fmt::Debug for Jar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jar")
+            .field("store", &self.store)
+            .field("limits", &self.limits)
+            .finish()
     }
 }
 
@@ -168,16 +377,21 @@ impl CookieStore for Jar {
         let iter =
             cookie_headers.filter_map(|val| Cookie::parse(val).map(|c| c.0.into_owned()).ok());
 
-        self.0.write().unwrap().store_response_cookies(iter, url);
+        self.store(iter, url);
     }
 
     fn cookies(&self, url: &url::Url) -> Option<HeaderValue> {
-        let s = self
-            .0
-            .read()
-            .unwrap()
-            .get_request_values(url)
-            .map(|(name, value)| format!("{name}={value}"))
+        let store = self.store.read().unwrap();
+        let cookies = store.matches(url);
+        if self.tracks_use() {
+            self.touch(cookies.iter().map(|cookie| key(cookie)));
+        }
+        let s = cookies
+            .iter()
+            .map(|cookie| {
+                let (name, value) = cookie.name_value();
+                format!("{name}={value}")
+            })
             .collect::<Vec<_>>()
             .join("; ");
 
@@ -291,3 +505,32 @@ pub(crate) mod service {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Jar;
+
+    #[test]
+    fn used_forgets_dropped_cookies() {
+        let jar = Jar::default().max_cookies(10);
+        let url = "http://a.local/".parse().unwrap();
+        let used = || jar.used.lock().unwrap().len();
+
+        jar.add_cookie_str("one=1", &url);
+        jar.add_cookie_str("two=2", &url);
+        assert_eq!(used(), 2);
+
+        // deleted by the server
+        jar.add_cookie_str("one=; Max-Age=0", &url);
+        assert_eq!(used(), 1);
+
+        // refused by the store
+        jar.add_cookie_str("three=3; Domain=b.local", &url);
+        assert_eq!(used(), 1);
+
+        // dropped by the store without the jar knowing
+        jar.store.write().unwrap().clear();
+        jar.add_cookie_str("four=4", &url);
+        assert_eq!(used(), 1);
+    }
+}
@@ -0,0 +1,326 @@
+//! CORS preflight requests.
+//!
+//! Before a browser sends a cross-origin request that isn't a simple one, it
+//! asks the server whether the request is allowed with a preflight `OPTIONS`
+//! request. [`Client::preflight`](crate::Client::preflight) sends the same
+//! request, to check the CORS configuration of a server without a browser:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::header::CONTENT_TYPE;
+//! use reqwest::Method;
+//!
+//! let client = reqwest::Client::new();
+//! let preflight = client
+//!     .preflight("https://api.example.com/items", "https://app.example.com", Method::PUT)
+//!     .request_header(CONTENT_TYPE)
+//!     .send()
+//!     .await?;
+//! if !preflight.is_allowed() {
+//!     println!("PUT is refused, only {:?} are allowed", preflight.allow_methods());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The `Access-Control-Allow-*` headers of the response are parsed into a
+//! [`Preflight`], and [`Preflight::is_allowed`] runs the same checks as a
+//! browser, as described by the [Fetch standard].
+//!
+//! [Fetch standard]: https://fetch.spec.whatwg.org/#cors-preflight-fetch
+
+use std::time::Duration;
+
+use http::header::{
+    HeaderMap, HeaderName, ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+    ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_HEADERS, AUTHORIZATION,
+};
+use http::{Method, StatusCode};
+
+use crate::RequestBuilder;
+
+/// A builder for a CORS preflight request.
+///
+/// Created with [`Client::preflight`](crate::Client::preflight).
+#[must_use = "PreflightBuilder does nothing until you 'send' it"]
+#[derive(Debug)]
+pub struct PreflightBuilder {
+    builder: RequestBuilder,
+    origin: String,
+    method: Method,
+    headers: Vec<HeaderName>,
+    credentials: bool,
+}
+
+/// The answer of a server to a CORS preflight request.
+#[derive(Clone, Debug)]
+pub struct Preflight {
+    status: StatusCode,
+    origin: String,
+    method: Method,
+    headers: Vec<HeaderName>,
+    credentials: bool,
+    allow_origin: Option<String>,
+    allow_methods: Vec<Method>,
+    allow_headers: Vec<HeaderName>,
+    allow_credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl PreflightBuilder {
+    pub(crate) fn new(builder: RequestBuilder, origin: &str, method: Method) -> PreflightBuilder {
+        PreflightBuilder {
+            builder,
+            origin: origin.to_owned(),
+            method,
+            headers: Vec::new(),
+            credentials: false,
+        }
+    }
+
+    /// Add a header that the request would have, to the
+    /// `Access-Control-Request-Headers` of the preflight request.
+    pub fn request_header(mut self, name: HeaderName) -> PreflightBuilder {
+        if !self.headers.contains(&name) {
+            self.headers.push(name);
+        }
+        self
+    }
+
+    /// Set whether the request would be sent with credentials, such as
+    /// cookies.
+    ///
+    /// The preflight request itself never has credentials, but a response
+    /// allowing any origin, method or header with `*` doesn't allow requests
+    /// with credentials.
+    ///
+    /// Default is `false`.
+    pub fn credentials(mut self, credentials: bool) -> PreflightBuilder {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Send the preflight request.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the request can't be sent, or if the origin isn't a
+    /// valid header value. A response refusing the request isn't an error.
+    pub async fn send(mut self) -> crate::Result<Preflight> {
+        self.headers.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let mut builder = self
+            .builder
+            .header(http::header::ORIGIN, self.origin.as_str())
+            .header(
+                http::header::ACCESS_CONTROL_REQUEST_METHOD,
+                self.method.as_str(),
+            );
+        if !self.headers.is_empty() {
+            let names = self
+                .headers
+                .iter()
+                .map(HeaderName::as_str)
+                .collect::<Vec<_>>();
+            builder = builder.header(ACCESS_CONTROL_REQUEST_HEADERS, names.join(","));
+        }
+        let res = builder.send().await?;
+        Ok(Preflight::new(
+            res.status(),
+            res.headers(),
+            self.origin,
+            self.method,
+            self.headers,
+            self.credentials,
+        ))
+    }
+}
+
+impl Preflight {
+    fn new(
+        status: StatusCode,
+        headers: &HeaderMap,
+        origin: String,
+        method: Method,
+        requested: Vec<HeaderName>,
+        credentials: bool,
+    ) -> Preflight {
+        let allow_origin = headers
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_owned());
+        let allow_credentials = headers
+            .get(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .is_some_and(|value| value == "true");
+        let max_age = headers
+            .get(ACCESS_CONTROL_MAX_AGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        Preflight {
+            status,
+            origin,
+            method,
+            headers: requested,
+            credentials,
+            allow_origin,
+            allow_methods: list(headers, &ACCESS_CONTROL_ALLOW_METHODS),
+            allow_headers: list(headers, &ACCESS_CONTROL_ALLOW_HEADERS),
+            allow_credentials,
+            max_age,
+        }
+    }
+
+    /// Get the status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Get the `Access-Control-Allow-Origin` of the response.
+    pub fn allow_origin(&self) -> Option<&str> {
+        self.allow_origin.as_deref()
+    }
+
+    /// Get the methods of the `Access-Control-Allow-Methods` of the
+    /// response, including `*`.
+    pub fn allow_methods(&self) -> &[Method] {
+        &self.allow_methods
+    }
+
+    /// Get the headers of the `Access-Control-Allow-Headers` of the
+    /// response, including `*`.
+    pub fn allow_headers(&self) -> &[HeaderName] {
+        &self.allow_headers
+    }
+
+    /// Get whether the response has `Access-Control-Allow-Credentials: true`.
+    pub fn allow_credentials(&self) -> bool {
+        self.allow_credentials
+    }
+
+    /// Get the `Access-Control-Max-Age` of the response, how long the
+    /// answer may be cached.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Check whether a browser would send the request.
+    ///
+    /// The status must be successful, the origin allowed, and so must be the
+    /// method, unless it is `GET`, `HEAD` or `POST`, and each requested
+    /// header. With credentials, `*` doesn't allow anything, and
+    /// `Access-Control-Allow-Credentials` must be `true`.
+    pub fn is_allowed(&self) -> bool {
+        self.status.is_success()
+            && self.origin_allowed()
+            && self.method_allowed()
+            && self.headers.iter().all(|name| self.header_allowed(name))
+    }
+
+    fn origin_allowed(&self) -> bool {
+        match self.allow_origin.as_deref() {
+            Some("*") => !self.credentials,
+            Some(origin) => origin == self.origin && (!self.credentials || self.allow_credentials),
+            None => false,
+        }
+    }
+
+    fn method_allowed(&self) -> bool {
+        matches!(self.method, Method::GET | Method::HEAD | Method::POST)
+            || self.allow_methods.contains(&self.method)
+            || (!self.credentials && self.allow_methods.iter().any(|m| m.as_str() == "*"))
+    }
+
+    fn header_allowed(&self, name: &HeaderName) -> bool {
+        self.allow_headers.contains(name)
+            || (!self.credentials
+                && *name != AUTHORIZATION
+                && self.allow_headers.iter().any(|h| h.as_str() == "*"))
+    }
+}
+
+/// Parse the comma separated values of a header, skipping invalid ones.
+fn list<T: std::str::FromStr>(headers: &HeaderMap, name: &HeaderName) -> Vec<T> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| item.trim().parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preflight(headers: &[(&'static str, &'static str)], credentials: bool) -> Preflight {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, value.parse().unwrap());
+        }
+        Preflight::new(
+            StatusCode::NO_CONTENT,
+            &map,
+            "https://app.example.com".to_owned(),
+            Method::PUT,
+            vec![AUTHORIZATION, HeaderName::from_static("x-token")],
+            credentials,
+        )
+    }
+
+    #[test]
+    fn explicit_lists() {
+        let headers = [
+            ("access-control-allow-origin", "https://app.example.com"),
+            ("access-control-allow-methods", "GET, PUT"),
+            ("access-control-allow-headers", "Authorization, X-Token"),
+            ("access-control-allow-credentials", "true"),
+            ("access-control-max-age", "600"),
+        ];
+        let allowed = preflight(&headers, true);
+
+        assert_eq!(allowed.allow_methods(), [Method::GET, Method::PUT]);
+        assert_eq!(
+            allowed.allow_headers(),
+            [AUTHORIZATION, HeaderName::from_static("x-token")]
+        );
+        assert_eq!(allowed.max_age(), Some(Duration::from_secs(600)));
+        assert!(allowed.is_allowed());
+    }
+
+    #[test]
+    fn wildcards_without_credentials() {
+        let headers = [
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-methods", "*"),
+            ("access-control-allow-headers", "*, authorization"),
+        ];
+
+        assert!(preflight(&headers, false).is_allowed());
+        // `*` doesn't allow anything for requests with credentials
+        assert!(!preflight(&headers, true).is_allowed());
+    }
+
+    #[test]
+    fn wildcard_does_not_cover_authorization() {
+        let headers = [
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-methods", "PUT"),
+            ("access-control-allow-headers", "*"),
+        ];
+
+        assert!(!preflight(&headers, false).is_allowed());
+    }
+
+    #[test]
+    fn other_origin() {
+        let headers = [
+            ("access-control-allow-origin", "https://other.example.com"),
+            ("access-control-allow-methods", "PUT"),
+            ("access-control-allow-headers", "authorization, x-token"),
+        ];
+
+        assert!(!preflight(&headers, false).is_allowed());
+    }
+}
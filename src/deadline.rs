@@ -0,0 +1,55 @@
+//! Deadlines shared by nested requests
+//!
+//! A service answering a request within a budget has to make sure the
+//! requests it sends meanwhile end in time too. Rather than passing the time
+//! left to every [`RequestBuilder::timeout`](crate::RequestBuilder::timeout),
+//! the work can be run in a [`scope`] with a deadline: the requests sent
+//! within it time out at the deadline at the latest, on top of their own
+//! timeout.
+//!
+//! ```rust
+//! use std::time::{Duration, Instant};
+//!
+//! # async fn run(client: reqwest::Client) -> Result<(), reqwest::Error> {
+//! let deadline = Instant::now() + Duration::from_secs(2);
+//! let (user, orders) = reqwest::deadline::scope(deadline, async {
+//!     let user = client.get("https://example.com/user").send().await?;
+//!     let orders = client.get("https://example.com/orders").send().await?;
+//!     Ok::<_, reqwest::Error>((user.text().await?, orders.text().await?))
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Scopes nest: a scope can shorten the deadline of the scope it runs in,
+//! but not extend it. A middleware can also set the deadline of a single
+//! request, with [`Request::deadline_mut`](crate::Request::deadline_mut).
+//!
+//! The deadline of a scope applies to the requests sent from the task
+//! running it, which doesn't include the tasks it spawns, nor the requests
+//! of a blocking client.
+
+use std::future::Future;
+use std::time::Instant;
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Run `future` with a deadline for the requests it sends.
+///
+/// If `future` already runs within a scope with an earlier deadline, that
+/// one is kept.
+pub async fn scope<F: Future>(deadline: Instant, future: F) -> F::Output {
+    let deadline = match current() {
+        Some(outer) => outer.min(deadline),
+        None => deadline,
+    };
+    DEADLINE.scope(deadline, future).await
+}
+
+/// The deadline of the scope this task is running in, if any.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
@@ -0,0 +1,420 @@
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use http::Uri;
+
+use super::{Addrs, Name, Resolve, Resolving};
+use crate::error::BoxError;
+
+/// How to order the addresses a name resolves to, before connecting.
+///
+/// The addresses are tried one after the other until a connection is
+/// established, so the first ones get the most connections. Which address
+/// served a response is given by
+/// [`Response::remote_addr`](crate::Response::remote_addr).
+///
+/// Set with [`ClientBuilder::dns_balance`](crate::ClientBuilder::dns_balance).
+#[derive(Clone, Debug)]
+pub struct Balance {
+    strategy: Strategy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Strategy {
+    ResolverOrder,
+    RoundRobin,
+    Random,
+    Sticky,
+    LeastRecentlyFailed,
+}
+
+impl Balance {
+    /// Try the addresses in the order the resolver returned them.
+    ///
+    /// This is the default.
+    pub fn resolver_order() -> Balance {
+        Balance {
+            strategy: Strategy::ResolverOrder,
+        }
+    }
+
+    /// Start with the next address of a name on each new connection.
+    pub fn round_robin() -> Balance {
+        Balance {
+            strategy: Strategy::RoundRobin,
+        }
+    }
+
+    /// Try the addresses in a random order.
+    pub fn random() -> Balance {
+        Balance {
+            strategy: Strategy::Random,
+        }
+    }
+
+    /// Keep connecting to the last address of a name that accepted a
+    /// connection, until it fails.
+    pub fn sticky() -> Balance {
+        Balance {
+            strategy: Strategy::Sticky,
+        }
+    }
+
+    /// Try the addresses which never failed first, then the ones which
+    /// failed the longest time ago.
+    pub fn least_recently_failed() -> Balance {
+        Balance {
+            strategy: Strategy::LeastRecentlyFailed,
+        }
+    }
+
+    pub(crate) fn is_resolver_order(&self) -> bool {
+        self.strategy == Strategy::ResolverOrder
+    }
+}
+
+impl Default for Balance {
+    fn default() -> Balance {
+        Balance::resolver_order()
+    }
+}
+
+/// The addresses of the names resolved by a client, and how connecting to
+/// them went.
+pub(crate) struct Balancer {
+    strategy: Strategy,
+    avoid_failed: Option<Duration>,
+    names: Mutex<HashMap<String, Addresses>>,
+}
+
+struct Addresses {
+    rotation: usize,
+    connected: Option<SocketAddr>,
+    failed: HashMap<SocketAddr, Instant>,
+    resolved: Instant,
+}
+
+// How long a name is remembered after it was last resolved.
+const IDLE: Duration = Duration::from_secs(10 * 60);
+
+tokio::task_local! {
+    // The addresses offered to the connection being made on this task, in
+    // the order they are tried.
+    static OFFERED: Arc<Mutex<Vec<SocketAddr>>>;
+}
+
+impl Balancer {
+    pub(crate) fn new(balance: Balance, avoid_failed: Option<Duration>) -> Balancer {
+        Balancer {
+            strategy: balance.strategy,
+            avoid_failed,
+            names: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn order(&self, name: &str, mut addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        let now = Instant::now();
+        let mut names = self.names.lock().expect("lock poisoned");
+        if !names.contains_key(name) {
+            // forget the names not resolved for a while
+            names.retain(|_, state| now.duration_since(state.resolved) < IDLE);
+        }
+        let state = names.entry(name.to_owned()).or_insert_with(|| Addresses {
+            rotation: 0,
+            connected: None,
+            failed: HashMap::new(),
+            resolved: now,
+        });
+        state.resolved = now;
+        state.failed.retain(|addr, _| addrs.contains(addr));
+
+        match self.strategy {
+            Strategy::ResolverOrder => (),
+            Strategy::RoundRobin => {
+                if !addrs.is_empty() {
+                    let n = state.rotation % addrs.len();
+                    addrs.rotate_left(n);
+                }
+                state.rotation = state.rotation.wrapping_add(1);
+            }
+            Strategy::Random => {
+                for i in (1..addrs.len()).rev() {
+                    let j = (crate::util::fast_random() % (i as u64 + 1)) as usize;
+                    addrs.swap(i, j);
+                }
+            }
+            Strategy::Sticky => {
+                let connected = state.connected;
+                if let Some(i) = addrs.iter().position(|addr| Some(*addr) == connected) {
+                    addrs[..=i].rotate_right(1);
+                }
+            }
+            Strategy::LeastRecentlyFailed => {
+                addrs.sort_by_key(|addr| state.failed.get(addr).copied());
+            }
+        }
+
+        if let Some(avoid_failed) = self.avoid_failed {
+            addrs.sort_by_key(|addr| {
+                state
+                    .failed
+                    .get(addr)
+                    .is_some_and(|&at| now.duration_since(at) < avoid_failed)
+            });
+        }
+
+        addrs
+    }
+
+    /// Record which address of `name` accepted a connection, if any, out of
+    /// the `offered` ones.
+    fn connected(&self, name: &str, offered: &[SocketAddr], addr: Option<SocketAddr>) {
+        let mut names = self.names.lock().expect("lock poisoned");
+        let Some(state) = names.get_mut(name) else {
+            return;
+        };
+        let now = Instant::now();
+        match addr {
+            Some(addr) => {
+                // the addresses of the same family tried before it failed
+                let tried = offered.iter().take_while(|&&offered| offered != addr);
+                for offered in tried.filter(|offered| offered.is_ipv4() == addr.is_ipv4()) {
+                    state.failed.insert(*offered, now);
+                }
+                state.failed.remove(&addr);
+                state.connected = Some(addr);
+            }
+            None => {
+                for offered in offered {
+                    state.failed.insert(*offered, now);
+                }
+                state.connected = None;
+            }
+        }
+    }
+}
+
+/// A resolver that orders the addresses it resolves with a [`Balancer`].
+pub(crate) struct BalancedResolve {
+    inner: Arc<dyn Resolve>,
+    balancer: Arc<Balancer>,
+}
+
+impl BalancedResolve {
+    pub(crate) fn new(inner: Arc<dyn Resolve>, balancer: Arc<Balancer>) -> Self {
+        BalancedResolve { inner, balancer }
+    }
+}
+
+impl Resolve for BalancedResolve {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_owned();
+        let balancer = self.balancer.clone();
+        let resolving = self.inner.resolve(name);
+        Box::pin(async move {
+            let addrs = resolving.await?.collect();
+            let addrs = balancer.order(&host, addrs);
+            let _ = OFFERED.try_with(|offered| offered.lock().unwrap().clone_from(&addrs));
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// A TCP connector that tells a [`Balancer`] which address accepted each
+/// connection.
+pub(crate) struct BalancedTcp<S> {
+    inner: S,
+    balancer: Option<Arc<Balancer>>,
+}
+
+impl<S> BalancedTcp<S> {
+    pub(crate) fn new(inner: S, balancer: Option<Arc<Balancer>>) -> Self {
+        BalancedTcp { inner, balancer }
+    }
+}
+
+impl<S> Service<Uri> for BalancedTcp<S>
+where
+    S: Service<Uri, Response = TokioIo<TcpStream>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let host = dst.host().unwrap_or_default().to_owned();
+        let connecting = self.inner.call(dst);
+        let balancer = self.balancer.clone();
+        Box::pin(async move {
+            let Some(balancer) = balancer else {
+                return connecting.await.map_err(Into::into);
+            };
+            // the order this connection was given, whatever the order of
+            // the connections made meanwhile
+            let offered = Arc::new(Mutex::new(Vec::new()));
+            let res = OFFERED
+                .scope(offered.clone(), connecting)
+                .await
+                .map_err(Into::into);
+            let addr = match res {
+                Ok(ref io) => io.inner().peer_addr().ok(),
+                Err(_) => None,
+            };
+            let offered = offered.lock().unwrap();
+            balancer.connected(&host, &offered, addr);
+            res
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs() -> Vec<SocketAddr> {
+        vec![
+            "10.0.0.1:80".parse().unwrap(),
+            "10.0.0.2:80".parse().unwrap(),
+            "10.0.0.3:80".parse().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn round_robin() {
+        let balancer = Balancer::new(Balance::round_robin(), None);
+        let a = addrs();
+        assert_eq!(balancer.order("host", a.clone()), [a[0], a[1], a[2]]);
+        assert_eq!(balancer.order("host", a.clone()), [a[1], a[2], a[0]]);
+        assert_eq!(balancer.order("host", a.clone()), [a[2], a[0], a[1]]);
+        assert_eq!(balancer.order("host", a.clone()), [a[0], a[1], a[2]]);
+        // each name has its own rotation
+        assert_eq!(balancer.order("other", a.clone()), [a[0], a[1], a[2]]);
+    }
+
+    #[test]
+    fn random() {
+        let balancer = Balancer::new(Balance::random(), None);
+        let mut ordered = balancer.order("host", addrs());
+        ordered.sort();
+        assert_eq!(ordered, addrs());
+    }
+
+    #[test]
+    fn sticky() {
+        let balancer = Balancer::new(Balance::sticky(), None);
+        let a = addrs();
+        let offered = balancer.order("host", a.clone());
+        assert_eq!(offered, a);
+        balancer.connected("host", &offered, Some(a[1]));
+        assert_eq!(balancer.order("host", a.clone()), [a[1], a[0], a[2]]);
+        let offered = balancer.order("host", a.clone());
+        assert_eq!(offered, [a[1], a[0], a[2]]);
+
+        // until it fails
+        balancer.connected("host", &offered, None);
+        assert_eq!(balancer.order("host", a.clone()), a);
+    }
+
+    #[test]
+    fn least_recently_failed() {
+        let balancer = Balancer::new(Balance::least_recently_failed(), None);
+        let a = addrs();
+        let offered = balancer.order("host", a.clone());
+        assert_eq!(offered, a);
+        // the first two were tried before the third connected
+        balancer.connected("host", &offered, Some(a[2]));
+        let offered = balancer.order("host", a.clone());
+        assert_eq!(offered, [a[2], a[0], a[1]]);
+
+        // all failed, the third most recently
+        balancer.connected("host", &offered, None);
+        assert_eq!(balancer.order("host", a.clone()), [a[0], a[1], a[2]]);
+    }
+
+    #[test]
+    fn resolver_order() {
+        let balancer = Balancer::new(Balance::resolver_order(), None);
+        let offered = balancer.order("host", addrs());
+        balancer.connected("host", &offered, Some(addrs()[1]));
+        assert_eq!(balancer.order("host", addrs()), addrs());
+    }
+
+    #[test]
+    fn avoid_failed() {
+        let balancer = Balancer::new(Balance::round_robin(), Some(Duration::from_millis(50)));
+        let a = addrs();
+        let offered = balancer.order("host", a.clone());
+        assert_eq!(offered, [a[0], a[1], a[2]]);
+        balancer.connected("host", &offered, Some(a[1]));
+        // the first is tried last, wherever the rotation puts it
+        assert_eq!(balancer.order("host", a.clone()), [a[1], a[2], a[0]]);
+        assert_eq!(balancer.order("host", a.clone()), [a[2], a[1], a[0]]);
+
+        // only for a while
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(balancer.order("host", a.clone()), [a[0], a[1], a[2]]);
+    }
+
+    #[tokio::test]
+    async fn overlapping_connects() {
+        struct Fixed(Vec<SocketAddr>);
+
+        impl Resolve for Fixed {
+            fn resolve(&self, _: Name) -> Resolving {
+                let addrs = self.0.clone();
+                Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) })
+            }
+        }
+
+        let listeners = [(); 3].map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap());
+        let a = listeners
+            .iter()
+            .map(|listener| listener.local_addr().unwrap())
+            .collect::<Vec<_>>();
+        let balancer = Arc::new(Balancer::new(
+            Balance::round_robin(),
+            Some(Duration::from_secs(60)),
+        ));
+        let resolve = Arc::new(BalancedResolve::new(
+            Arc::new(Fixed(a.clone())),
+            balancer.clone(),
+        ));
+        // both connections resolve before either connects
+        let barrier = Arc::new(tokio::sync::Barrier::new(2));
+        let connect = tower::service_fn(move |_: Uri| {
+            let resolve = resolve.clone();
+            let barrier = barrier.clone();
+            async move {
+                let mut addrs = resolve.resolve("host".parse().unwrap()).await?;
+                barrier.wait().await;
+                let tcp = TcpStream::connect(addrs.next().unwrap()).await?;
+                Ok::<_, BoxError>(TokioIo::new(tcp))
+            }
+        });
+
+        let uri = Uri::from_static("http://host/");
+        let mut first = BalancedTcp::new(connect.clone(), Some(balancer.clone()));
+        let mut second = BalancedTcp::new(connect, Some(balancer.clone()));
+        let (first, second) = tokio::join!(first.call(uri.clone()), second.call(uri));
+        assert_eq!(first.unwrap().inner().peer_addr().unwrap(), a[0]);
+        assert_eq!(second.unwrap().inner().peer_addr().unwrap(), a[1]);
+
+        // neither connection failed any address
+        assert_eq!(balancer.order("host", a.clone()), [a[2], a[0], a[1]]);
+    }
+}
@@ -1,11 +1,14 @@
 //! DNS resolution
 
+pub use balance::Balance;
+pub(crate) use balance::{BalancedResolve, BalancedTcp, Balancer};
 pub use resolve::{Addrs, Name, Resolve, Resolving};
 pub(crate) use resolve::{DnsResolverWithOverrides, DynResolver};
 
 #[cfg(docsrs)]
 pub use resolve::IntoResolve;
 
+mod balance;
 pub(crate) mod gai;
 #[cfg(feature = "hickory-dns")]
 pub(crate) mod hickory;
@@ -81,7 +81,10 @@ impl DynResolver {
     ///
     /// This does the same thing that hyper-util's HttpConnector does, before
     /// calling out to its underlying DNS resolver.
-    #[cfg(feature = "socks")]
+    #[cfg(any(
+        feature = "socks",
+        all(feature = "interface-index", target_os = "windows")
+    ))]
     pub(crate) async fn http_resolve(
         &self,
         target: &http::Uri,
@@ -0,0 +1,678 @@
+//! Downloading responses to files.
+//!
+//! [`Client::download`] saves the body of a `GET` response to a file:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! let client = reqwest::Client::new();
+//! let download = client
+//!     .download("https://example.com/image.iso")
+//!     .to_file("image.iso")
+//!     .resume(true)
+//!     .send()
+//!     .await?;
+//! println!(
+//!     "{} bytes, {} of them from an earlier attempt",
+//!     download.len(),
+//!     download.resumed_from()
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With [`resume`](DownloadBuilder::resume), a download that was interrupted
+//! continues where it stopped. The request asks for the rest of the resource
+//! with a `Range` header, and an `If-Range` header so that the server sends
+//! the whole resource instead if it changed since. The `Content-Range` of
+//! the answer must start where the file ends. A server that ignores ranges
+//! sends the whole resource, and the download starts over.
+//!
+//! To know whether a file is complete, and which version of the resource it
+//! holds, the validator of the response (its `ETag`, or its `Last-Modified`
+//! date) is kept next to it, in a file with a `.resume` extension appended,
+//! until the download completes. A file without one is downloaded again.
+//!
+//! Downloads ask for the resource without any content coding, so that
+//! ranges count the same bytes as the file.
+//!
+//! # Segmented downloads
+//!
+//! With [`segments`](DownloadBuilder::segments), a large resource is split
+//! into ranges that are fetched at the same time, which is often faster from
+//! servers that limit the throughput of each connection:
+//!
+//! ```rust,no_run
+//! # async fn run(client: reqwest::Client) -> Result<(), reqwest::Error> {
+//! client
+//!     .download("https://example.com/image.iso")
+//!     .to_file("image.iso")
+//!     .segments(4)
+//!     .send()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A `HEAD` request first finds the length of the resource, and whether the
+//! server accepts ranges. If it doesn't, the resource is downloaded with a
+//! single request. Each range is requested with `If-Range`, so that they all
+//! come from the same version of the resource, and a range that fails is
+//! requested again from where it stopped, up to
+//! [`segment_retries`](DownloadBuilder::segment_retries) times.
+//!
+//! The ranges are requests of the client like any other, so they share its
+//! connection pool: each one in flight uses its own connection with
+//! HTTP/1.1, and they share one with HTTP/2. Segmented downloads start over
+//! instead of resuming.
+//!
+//! # Optional
+//!
+//! This requires the optional `download` feature to be enabled.
+
+use std::error::Error as StdError;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use futures_util::stream::{self, StreamExt};
+use http::header::{
+    HeaderMap, ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, ETAG, IF_RANGE,
+    LAST_MODIFIED, RANGE,
+};
+use http::StatusCode;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use crate::util::is_transient;
+use crate::{Client, Response, Url};
+
+const DEFAULT_SEGMENT_RETRIES: u32 = 3;
+
+/// A builder to download a resource to a file.
+///
+/// Created with [`Client::download`].
+#[must_use = "DownloadBuilder does nothing until you 'send' it"]
+pub struct DownloadBuilder {
+    client: Client,
+    url: crate::Result<Url>,
+    destination: Option<Destination>,
+    resume: bool,
+    segments: usize,
+    segment_retries: u32,
+}
+
+enum Destination {
+    File(PathBuf),
+    Writer(Box<dyn AsyncWrite + Send + Unpin>),
+}
+
+/// A completed download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Download {
+    len: u64,
+    resumed_from: u64,
+    segments: usize,
+}
+
+impl DownloadBuilder {
+    pub(crate) fn new(client: Client, url: crate::Result<Url>) -> DownloadBuilder {
+        DownloadBuilder {
+            client,
+            url,
+            destination: None,
+            resume: false,
+            segments: 1,
+            segment_retries: DEFAULT_SEGMENT_RETRIES,
+        }
+    }
+
+    /// Set the file to write the resource to.
+    ///
+    /// The file is created if needed, and replaced unless the download is
+    /// resumed.
+    pub fn to_file<P: AsRef<Path>>(mut self, path: P) -> DownloadBuilder {
+        self.destination = Some(Destination::File(path.as_ref().to_owned()));
+        self
+    }
+
+    /// Write the resource to a writer instead of a file.
+    ///
+    /// Such downloads cannot be resumed. In a segmented download, the ranges
+    /// after the one being written are kept in memory until their turn.
+    pub fn to_writer<W>(mut self, writer: W) -> DownloadBuilder
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        self.destination = Some(Destination::Writer(Box::new(writer)));
+        self
+    }
+
+    /// Continue an interrupted download of the same file.
+    ///
+    /// See the [module documentation](self) for how this works.
+    ///
+    /// Default is `false`.
+    pub fn resume(mut self, enable: bool) -> DownloadBuilder {
+        self.resume = enable;
+        self
+    }
+
+    /// Fetch the resource in this many ranges at the same time.
+    ///
+    /// See the [module documentation](self#segmented-downloads) for how
+    /// this works.
+    ///
+    /// Default is `1`, a single request.
+    pub fn segments(mut self, segments: usize) -> DownloadBuilder {
+        self.segments = segments.max(1);
+        self
+    }
+
+    /// Set how many times each range of a segmented download is requested
+    /// again after failing.
+    ///
+    /// Ranges are retried after errors sending the request or reading the
+    /// body, and after `5xx` statuses.
+    ///
+    /// Default is `3`.
+    pub fn segment_retries(mut self, retries: u32) -> DownloadBuilder {
+        self.segment_retries = retries;
+        self
+    }
+
+    /// Download the resource.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the request fails, if the response has an error status
+    /// or a `Content-Range` that doesn't match the file, if the body ends
+    /// early, or if the file cannot be written. The part of the body already
+    /// written stays in the file, so that the download can be resumed.
+    pub async fn send(self) -> crate::Result<Download> {
+        let url = self.url?;
+        let destination = self
+            .destination
+            .ok_or_else(|| crate::error::builder("no file or writer to download to"))?;
+
+        if self.segments > 1 {
+            if let Some((len, validator)) = probe(&self.client, &url).await {
+                let segmented = Segmented {
+                    client: &self.client,
+                    url: &url,
+                    len,
+                    validator,
+                    retries: self.segment_retries,
+                };
+                let segments = ranges(len, self.segments);
+                match destination {
+                    Destination::File(path) => segmented.to_file(&path, &segments).await?,
+                    Destination::Writer(writer) => segmented.to_writer(writer, &segments).await?,
+                }
+                return Ok(Download {
+                    len,
+                    resumed_from: 0,
+                    segments: segments.len(),
+                });
+            }
+            log::debug!("{url} doesn't support ranges, downloading in one request");
+        }
+
+        match destination {
+            Destination::File(path) => download_file(&self.client, url, &path, self.resume).await,
+            Destination::Writer(mut writer) => {
+                let res = get(&self.client, &url).send().await?.error_for_status()?;
+                let total = res.content_length();
+                let len = copy(res, &mut writer).await?;
+                check_complete(&url, total, len)?;
+                Ok(Download {
+                    len,
+                    resumed_from: 0,
+                    segments: 1,
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DownloadBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut builder = f.debug_struct("DownloadBuilder");
+        builder.field("url", &self.url);
+        match self.destination {
+            Some(Destination::File(ref path)) => builder.field("file", path),
+            Some(Destination::Writer(_)) => builder.field("writer", &true),
+            None => &mut builder,
+        };
+        builder
+            .field("resume", &self.resume)
+            .field("segments", &self.segments)
+            .field("segment_retries", &self.segment_retries)
+            .finish()
+    }
+}
+
+impl Download {
+    /// The length of the file.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// How many bytes were already in the file from an earlier attempt.
+    ///
+    /// This is `0` if the download started over.
+    pub fn resumed_from(&self) -> u64 {
+        self.resumed_from
+    }
+
+    /// How many ranges the resource was fetched in.
+    ///
+    /// This is `1` unless the download was segmented.
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+}
+
+fn get(client: &Client, url: &Url) -> crate::RequestBuilder {
+    client.get(url.clone()).header(ACCEPT_ENCODING, "identity")
+}
+
+async fn download_file(
+    client: &Client,
+    url: Url,
+    path: &Path,
+    resume: bool,
+) -> crate::Result<Download> {
+    let marker = marker_path(path);
+    let mut resume_from = match resume {
+        true => resume_offset(path, &marker).await,
+        false => None,
+    };
+    let (res, start, total) = loop {
+        let mut req = get(client, &url);
+        if let Some((offset, ref validator)) = resume_from {
+            req = req
+                .header(RANGE, format!("bytes={offset}-"))
+                .header(IF_RANGE, validator.as_str());
+        }
+        let res = req.send().await?;
+
+        let Some((offset, _)) = resume_from else {
+            let res = res.error_for_status()?;
+            let total = res.content_length();
+            break (res, 0, total);
+        };
+        match res.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let (start, _, total) = content_range(res.headers())
+                    .filter(|&(start, _, _)| start == offset)
+                    .ok_or_else(|| crate::error::decode(BadContentRange).with_url(url.clone()))?;
+                break (res, start, total);
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                if unsatisfied_range(res.headers()) == Some(offset) {
+                    // The file was complete already.
+                    remove_marker(&marker).await?;
+                    return Ok(Download {
+                        len: offset,
+                        resumed_from: offset,
+                        segments: 1,
+                    });
+                }
+                log::debug!("cannot resume download of {url}, starting over");
+                resume_from = None;
+            }
+            _ => {
+                let res = res.error_for_status()?;
+                let total = res.content_length();
+                break (res, 0, total);
+            }
+        }
+    };
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .await
+        .map_err(crate::error::body)?;
+    file.set_len(start).await.map_err(crate::error::body)?;
+    file.seek(SeekFrom::Start(start))
+        .await
+        .map_err(crate::error::body)?;
+    match validator(res.headers()).filter(|_| resume) {
+        Some(validator) => fs::write(&marker, validator)
+            .await
+            .map_err(crate::error::body)?,
+        None => remove_marker(&marker).await?,
+    }
+
+    let len = start + copy(res, &mut file).await?;
+    check_complete(&url, total, len)?;
+
+    remove_marker(&marker).await?;
+    Ok(Download {
+        len,
+        resumed_from: start,
+        segments: 1,
+    })
+}
+
+/// Write a response body, returning its length.
+async fn copy<W>(mut res: Response, writer: &mut W) -> crate::Result<u64>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut len = 0;
+    while let Some(chunk) = res.chunk().await? {
+        writer.write_all(&chunk).await.map_err(crate::error::body)?;
+        len += chunk.len() as u64;
+    }
+    writer.flush().await.map_err(crate::error::body)?;
+    Ok(len)
+}
+
+fn check_complete(url: &Url, total: Option<u64>, len: u64) -> crate::Result<()> {
+    match total {
+        Some(total) if total != len => Err(crate::error::body(Incomplete).with_url(url.clone())),
+        _ => Ok(()),
+    }
+}
+
+fn marker_path(path: &Path) -> PathBuf {
+    let mut marker = OsString::from(path.as_os_str());
+    marker.push(".resume");
+    PathBuf::from(marker)
+}
+
+/// Where an interrupted download stopped, and the validator of the response
+/// it was reading.
+async fn resume_offset(path: &Path, marker: &Path) -> Option<(u64, String)> {
+    let validator = fs::read_to_string(marker).await.ok()?;
+    let len = fs::metadata(path).await.ok()?.len();
+    Some((len, validator))
+}
+
+async fn remove_marker(marker: &Path) -> crate::Result<()> {
+    match fs::remove_file(marker).await {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(crate::error::body(err)),
+        _ => Ok(()),
+    }
+}
+
+// ===== segmented downloads =====
+
+/// Find the length and validator of a resource, if it can be fetched in
+/// ranges.
+async fn probe(client: &Client, url: &Url) -> Option<(u64, Option<String>)> {
+    let res = client
+        .head(url.clone())
+        .header(ACCEPT_ENCODING, "identity")
+        .send()
+        .await
+        .ok()?;
+    let accepts_ranges = res
+        .headers()
+        .get(ACCEPT_RANGES)
+        .is_some_and(|value| value == "bytes");
+    if !res.status().is_success() || !accepts_ranges {
+        return None;
+    }
+    let len = res
+        .headers()
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()
+        .filter(|&len| len > 0)?;
+    Some((len, validator(res.headers())))
+}
+
+/// Split `len` bytes into at most `count` inclusive ranges of about the
+/// same size.
+fn ranges(len: u64, count: usize) -> Vec<(u64, u64)> {
+    let count = (count as u64).min(len);
+    (0..count)
+        .map(|i| (i * len / count, (i + 1) * len / count - 1))
+        .collect()
+}
+
+struct Segmented<'a> {
+    client: &'a Client,
+    url: &'a Url,
+    len: u64,
+    validator: Option<String>,
+    retries: u32,
+}
+
+impl Segmented<'_> {
+    async fn to_file(&self, path: &Path, segments: &[(u64, u64)]) -> crate::Result<()> {
+        let file = File::create(path).await.map_err(crate::error::body)?;
+        file.set_len(self.len).await.map_err(crate::error::body)?;
+        drop(file);
+
+        stream::iter(segments.iter().copied())
+            .map(|(start, end)| async move {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .await
+                    .map_err(crate::error::body)?;
+                let mut sink = Sink::File { file, start };
+                self.fetch(start, end, &mut sink).await?;
+                sink.flush().await.map_err(crate::error::body)
+            })
+            .buffer_unordered(segments.len())
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    async fn to_writer(
+        &self,
+        mut writer: Box<dyn AsyncWrite + Send + Unpin>,
+        segments: &[(u64, u64)],
+    ) -> crate::Result<()> {
+        let mut fetched = stream::iter(segments.iter().copied())
+            .map(|(start, end)| async move {
+                let mut sink = Sink::Buffer(Vec::new());
+                self.fetch(start, end, &mut sink).await?;
+                Ok::<_, crate::Error>(sink)
+            })
+            .buffered(segments.len());
+        while let Some(sink) = fetched.next().await {
+            if let Sink::Buffer(data) = sink? {
+                writer.write_all(&data).await.map_err(crate::error::body)?;
+            }
+        }
+        writer.flush().await.map_err(crate::error::body)
+    }
+
+    /// Fetch a range, retrying from where it stopped after transient errors.
+    async fn fetch(&self, start: u64, end: u64, sink: &mut Sink) -> crate::Result<()> {
+        let mut received = 0;
+        let mut attempts = 0;
+        loop {
+            let result = async {
+                sink.rewind(received).await.map_err(crate::error::body)?;
+                self.fetch_range(start + received, end, sink, &mut received)
+                    .await
+            };
+            match result.await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempts < self.retries && is_transient(&err) => {
+                    attempts += 1;
+                    log::debug!(
+                        "range {start}-{end} of {} failed, retrying: {err}",
+                        self.url
+                    );
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn fetch_range(
+        &self,
+        start: u64,
+        end: u64,
+        sink: &mut Sink,
+        received: &mut u64,
+    ) -> crate::Result<()> {
+        let mut req = get(self.client, self.url).header(RANGE, format!("bytes={start}-{end}"));
+        if let Some(ref validator) = self.validator {
+            req = req.header(IF_RANGE, validator.as_str());
+        }
+        let mut res = req.send().await?.error_for_status()?;
+        let expected = (start, end, Some(self.len));
+        if res.status() != StatusCode::PARTIAL_CONTENT
+            || content_range(res.headers()) != Some(expected)
+        {
+            return Err(crate::error::decode(BadContentRange).with_url(self.url.clone()));
+        }
+
+        let mut remaining = end - start + 1;
+        while let Some(chunk) = res.chunk().await? {
+            let Some(left) = remaining.checked_sub(chunk.len() as u64) else {
+                return Err(crate::error::decode(BadContentRange).with_url(self.url.clone()));
+            };
+            sink.write(&chunk).await.map_err(crate::error::body)?;
+            remaining = left;
+            *received += chunk.len() as u64;
+        }
+        if remaining > 0 {
+            return Err(crate::error::body(Incomplete).with_url(self.url.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Where the data of a range goes.
+enum Sink {
+    File { file: File, start: u64 },
+    Buffer(Vec<u8>),
+}
+
+impl Sink {
+    /// Continue after the first `received` bytes of the range.
+    async fn rewind(&mut self, received: u64) -> io::Result<()> {
+        match self {
+            Sink::File { file, start } => {
+                file.seek(SeekFrom::Start(*start + received)).await?;
+            }
+            Sink::Buffer(data) => data.truncate(received as usize),
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Sink::File { file, .. } => file.write_all(data).await,
+            Sink::Buffer(buf) => {
+                buf.extend_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::File { file, .. } => file.flush().await,
+            Sink::Buffer(_) => Ok(()),
+        }
+    }
+}
+
+// ===== headers =====
+
+/// A validator that may be used with `If-Range`, which needs a strong one.
+fn validator(headers: &HeaderMap) -> Option<String> {
+    let etag = headers
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"));
+    etag.or_else(|| headers.get(LAST_MODIFIED)?.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Parse `Content-Range: bytes <start>-<end>/<total>` into the first and
+/// last positions, and the total length if known.
+fn content_range(headers: &HeaderMap) -> Option<(u64, u64, Option<u64>)> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.parse::<u64>().ok()?, end.parse::<u64>().ok()?);
+    if end < start {
+        return None;
+    }
+    match total {
+        "*" => Some((start, end, None)),
+        total => {
+            let total = total.parse::<u64>().ok().filter(|&total| end < total)?;
+            Some((start, end, Some(total)))
+        }
+    }
+}
+
+/// Parse `Content-Range: bytes */<total>`, sent with a `416` status.
+fn unsatisfied_range(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    value.strip_prefix("bytes */")?.parse().ok()
+}
+
+#[derive(Debug)]
+struct BadContentRange;
+
+impl fmt::Display for BadContentRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Content-Range doesn't match the requested range")
+    }
+}
+
+impl StdError for BadContentRange {}
+
+#[derive(Debug)]
+struct Incomplete;
+
+impl fmt::Display for Incomplete {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("download ended before the whole resource was received")
+    }
+}
+
+impl StdError for Incomplete {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_RANGE, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn parse_content_range() {
+        assert_eq!(
+            content_range(&headers("bytes 6-10/11")),
+            Some((6, 10, Some(11)))
+        );
+        assert_eq!(content_range(&headers("bytes 6-10/*")), Some((6, 10, None)));
+        assert_eq!(content_range(&headers("bytes 6-10/10")), None);
+        assert_eq!(content_range(&headers("bytes 10-6/11")), None);
+        assert_eq!(content_range(&headers("items 6-10/11")), None);
+        assert_eq!(unsatisfied_range(&headers("bytes */11")), Some(11));
+    }
+
+    #[test]
+    fn split_ranges() {
+        assert_eq!(ranges(10, 3), [(0, 2), (3, 5), (6, 9)]);
+        assert_eq!(ranges(2, 4), [(0, 0), (1, 1)]);
+        assert_eq!(ranges(5, 1), [(0, 4)]);
+    }
+}
@@ -0,0 +1,152 @@
+//! `103 Early Hints` responses.
+//!
+//! A server can send `103 Early Hints` interim responses ([RFC 8297]) before
+//! the final response of a request, with `Link` headers naming resources
+//! the final response is likely to use. With
+//! [`RequestBuilder::on_early_hints`](crate::RequestBuilder::on_early_hints),
+//! an application can connect to their origins or fetch them while the
+//! server is still preparing the response:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! let client = reqwest::Client::new();
+//! let res = client
+//!     .get("https://example.com/")
+//!     .on_early_hints(|hints| {
+//!         for link in hints.links() {
+//!             if link.rel().any(|rel| rel == "preload") {
+//!                 println!("preload {}", link.target());
+//!             }
+//!         }
+//!     })
+//!     .send()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The callback runs once for each `103` response, while the request is in
+//! flight, so it shouldn't block. Other informational responses, such as
+//! `100 Continue`, aren't passed to it. Interim responses are only read on
+//! HTTP/1 connections; with HTTP/2 and HTTP/3, the callback never runs.
+//!
+//! [RFC 8297]: https://www.rfc-editor.org/rfc/rfc8297
+
+use std::fmt;
+use std::sync::Arc;
+
+use http::header::{HeaderMap, LINK};
+
+use crate::util::{link_param, links};
+
+/// A `103 Early Hints` interim response.
+#[derive(Debug)]
+pub struct EarlyHints {
+    headers: HeaderMap,
+}
+
+impl EarlyHints {
+    /// Get the headers of the interim response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Get the links of the `Link` headers, in order.
+    pub fn links(&self) -> impl Iterator<Item = Link<'_>> {
+        self.headers
+            .get_all(LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(links)
+            .map(|(target, params)| Link { target, params })
+    }
+}
+
+/// A link of a `Link` header.
+#[derive(Clone, Copy, Debug)]
+pub struct Link<'a> {
+    target: &'a str,
+    params: &'a str,
+}
+
+impl<'a> Link<'a> {
+    /// Get the target of the link, as written.
+    ///
+    /// A relative target is relative to the URL of the request.
+    pub fn target(&self) -> &'a str {
+        self.target
+    }
+
+    /// Get the relation types of the link, such as `preload` or
+    /// `preconnect`.
+    pub fn rel(&self) -> impl Iterator<Item = &'a str> {
+        self.param("rel")
+            .into_iter()
+            .flat_map(str::split_ascii_whitespace)
+    }
+
+    /// Get the value of a parameter of the link, such as `as`, without
+    /// quotes.
+    pub fn param(&self, name: &str) -> Option<&'a str> {
+        link_param(self.params, name)
+    }
+}
+
+/// The early hints callback of a request.
+#[derive(Clone)]
+pub(crate) struct Callback(Arc<dyn Fn(&EarlyHints) + Send + Sync>);
+
+impl Callback {
+    pub(crate) fn new<F>(f: F) -> Callback
+    where
+        F: Fn(&EarlyHints) + Send + Sync + 'static,
+    {
+        Callback(Arc::new(f))
+    }
+
+    pub(crate) fn call(&self, headers: &HeaderMap) {
+        (self.0)(&EarlyHints {
+            headers: headers.clone(),
+        });
+    }
+}
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_links() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            LINK,
+            "</style.css>; rel=preload; as=style, <https://cdn.example.com>; rel=\"preconnect dns-prefetch\""
+                .parse()
+                .unwrap(),
+        );
+        headers.append(
+            LINK,
+            "</app.js>; rel=\"preload\"; as=\"script\"".parse().unwrap(),
+        );
+        let hints = EarlyHints { headers };
+
+        let links = hints.links().collect::<Vec<_>>();
+        assert_eq!(
+            links.iter().map(Link::target).collect::<Vec<_>>(),
+            ["/style.css", "https://cdn.example.com", "/app.js"]
+        );
+        assert_eq!(links[0].param("as"), Some("style"));
+        assert_eq!(
+            links[1].rel().collect::<Vec<_>>(),
+            ["preconnect", "dns-prefetch"]
+        );
+        assert_eq!(links[2].param("as"), Some("script"));
+        assert_eq!(links[2].param("crossorigin"), None);
+    }
+}
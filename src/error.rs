@@ -6,6 +6,7 @@ use std::error::Error as StdError;
 use std::fmt;
 use std::io;
 
+use crate::redact::{self, Redaction};
 use crate::util::Escape;
 use crate::{StatusCode, Url};
 
@@ -16,7 +17,8 @@ pub type Result<T> = std::result::Result<T, Error>;
 ///
 /// Note: Errors may include the full URL used to make the `Request`. If the URL
 /// contains sensitive information (e.g. an API key as a query parameter), be
-/// sure to remove it ([`without_url`](Error::without_url))
+/// sure to remove it ([`without_url`](Error::without_url)), or configure the
+/// client to hide it with a [redaction policy](crate::redact).
 pub struct Error {
     inner: Box<Inner>,
 }
@@ -27,6 +29,9 @@ struct Inner {
     kind: Kind,
     source: Option<BoxError>,
     url: Option<Url>,
+    redaction: Option<Redaction>,
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    connection: Option<crate::observe::RequestConnection>,
 }
 
 impl Error {
@@ -39,6 +44,12 @@ impl Error {
                 kind,
                 source: source.map(Into::into),
                 url: None,
+                redaction: None,
+                #[cfg(not(all(
+                    target_arch = "wasm32",
+                    any(target_os = "unknown", target_os = "none")
+                )))]
+                connection: None,
             }),
         }
     }
@@ -93,6 +104,53 @@ impl Error {
         self
     }
 
+    /// Returns the connection the request was sent on, if it failed after
+    /// getting one.
+    ///
+    /// This tells the server behind a load balancer which failed the
+    /// request, the HTTP version of the connection, and whether it was
+    /// reused from an earlier request. When following redirects, it is the
+    /// connection of the last request. Errors from reading a response body
+    /// don't have it, since the response has been returned by then.
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    pub fn connection(&self) -> Option<&crate::observe::RequestConnection> {
+        self.inner.connection.as_ref()
+    }
+
+    /// Add the connection a request failed on, taken from the error of the
+    /// connection pool, or else from `slot`.
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    pub(crate) fn with_connection(
+        mut self,
+        slot: &crate::observe::ConnectionSlot,
+        start: std::time::Instant,
+    ) -> Self {
+        if self.inner.connection.is_some() {
+            return self;
+        }
+        let mut source = self.source();
+        let mut connection = None;
+        while let Some(err) = source {
+            if let Some(pool_err) = err.downcast_ref::<hyper_util::client::legacy::Error>() {
+                connection = pool_err
+                    .connect_info()
+                    .map(|connected| crate::observe::RequestConnection::new(connected, start));
+                break;
+            }
+            source = err.source();
+        }
+        self.inner.connection = connection.or_else(|| slot.connection(start));
+        self
+    }
+
+    /// Show the url of this error with the policy of the client.
+    pub(crate) fn with_redaction(mut self, redaction: Option<&Redaction>) -> Self {
+        if let Some(redaction) = redaction {
+            self.inner.redaction = Some(redaction.clone());
+        }
+        self
+    }
+
     /// Returns true if the error is from a type Builder.
     pub fn is_builder(&self) -> bool {
         matches!(self.inner.kind, Kind::Builder)
@@ -116,6 +174,8 @@ impl Error {
     }
 
     /// Returns true if the error is related to a timeout.
+    ///
+    /// See [`Error::timeout_phase`] for which one.
     pub fn is_timeout(&self) -> bool {
         let mut source = self.source();
 
@@ -143,6 +203,35 @@ impl Error {
         false
     }
 
+    /// Returns which phase of the request timed out, if the error is a
+    /// timeout.
+    ///
+    /// This is `None` for the errors which aren't timeouts, and for the
+    /// timeouts which can't be tied to a phase, such as those reported by
+    /// the operating system or by HTTP/2 keep-alive pings. Those are still
+    /// [`is_timeout`](Error::is_timeout).
+    pub fn timeout_phase(&self) -> Option<TimeoutPhase> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(timed_out) = err.downcast_ref::<TimedOut>() {
+                return Some(timed_out.0);
+            }
+            #[cfg(not(all(
+                target_arch = "wasm32",
+                any(target_os = "unknown", target_os = "none")
+            )))]
+            if let Some(io) = err.downcast_ref::<io::Error>() {
+                if io.kind() == io::ErrorKind::TimedOut && self.is_connect() {
+                    return Some(TimeoutPhase::Connect);
+                }
+            }
+            source = err.source();
+        }
+
+        None
+    }
+
     /// Returns true if the error is related to the request
     pub fn is_request(&self) -> bool {
         matches!(self.inner.kind, Kind::Request)
@@ -182,6 +271,122 @@ impl Error {
         false
     }
 
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    /// Returns true if the error is from the TLS handshake, such as an
+    /// invalid or untrusted certificate.
+    pub fn is_tls(&self) -> bool {
+        self.has_source(|err| {
+            #[cfg(feature = "__native-tls")]
+            if err.is::<native_tls_crate::Error>() {
+                return true;
+            }
+            #[cfg(feature = "__rustls")]
+            if err.is::<rustls::Error>() {
+                return true;
+            }
+            err.is::<TlsError>()
+        })
+    }
+
+    /// Returns why the TLS handshake failed, if the error is from one.
+    ///
+    /// The details only a backend gives are in `Error::rustls_error` and
+    /// `Error::native_tls_error`.
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "native-tls", feature = "rustls"))))]
+    pub fn tls_failure(&self) -> Option<crate::tls::TlsFailure> {
+        #[cfg(feature = "__rustls")]
+        if let Some(err) = self.rustls_error() {
+            return Some(crate::tls::TlsFailure::from_rustls(err));
+        }
+        #[cfg(feature = "__native-tls")]
+        if self.native_tls_error().is_some() {
+            return Some(crate::tls::TlsFailure::Other);
+        }
+        None
+    }
+
+    /// Returns the error of the `rustls` backend, if the error is from a
+    /// TLS handshake using it.
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    #[cfg(feature = "__rustls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+    pub fn rustls_error(&self) -> Option<&rustls::Error> {
+        self.find_source()
+    }
+
+    /// Returns the error of the `native-tls` backend, if the error is from
+    /// a TLS handshake using it.
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    #[cfg(feature = "__native-tls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "native-tls")))]
+    pub fn native_tls_error(&self) -> Option<&native_tls_crate::Error> {
+        self.find_source()
+    }
+
+    /// Returns true if the error is from connecting to a proxy, or from
+    /// asking it for a tunnel to the host of the request.
+    pub fn is_proxy(&self) -> bool {
+        self.has_source(|err| err.is::<ProxyError>())
+    }
+
+    /// Returns true if the connection was reset by the peer.
+    pub fn is_connection_reset(&self) -> bool {
+        self.has_source(|err| {
+            err.downcast_ref::<io::Error>()
+                .is_some_and(|io| io.kind() == io::ErrorKind::ConnectionReset)
+        })
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    /// Returns true if the error is from a redirect that would have gone over
+    /// the limit of the redirect policy.
+    ///
+    /// See [`redirect::Policy::limited`](crate::redirect::Policy::limited).
+    pub fn is_too_many_redirects(&self) -> bool {
+        self.has_source(|err| err.is::<crate::redirect::TooManyRedirects>())
+    }
+
+    /// Returns true if the error is from a request that could only be
+    /// answered from the cache, which had no response for it.
+    ///
+    /// See [`cache::Mode::OnlyIfCached`](crate::cache::Mode::OnlyIfCached).
+    #[cfg(feature = "cache")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+    pub fn is_cache_miss(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<crate::cache::NotCached>() {
+                return true;
+            }
+
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns true if the error is from a request which was not sent,
+    /// because the circuit breaker of its host is open.
+    ///
+    /// See [`ClientBuilder::circuit_breaker`](crate::ClientBuilder::circuit_breaker).
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    pub fn is_circuit_open(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<crate::circuit::CircuitOpen>() {
+                return true;
+            }
+
+            source = err.source();
+        }
+
+        false
+    }
+
     /// Returns true if the error is related to the request or response body
     pub fn is_body(&self) -> bool {
         matches!(self.inner.kind, Kind::Body)
@@ -192,6 +397,37 @@ impl Error {
         matches!(self.inner.kind, Kind::Decode)
     }
 
+    /// Returns true if the error is from decoding a response body with an
+    /// unexpected `Content-Type`.
+    ///
+    /// See [`ClientBuilder::strict_content_type`](crate::ClientBuilder::strict_content_type).
+    pub fn is_content_type(&self) -> bool {
+        self.content_type_mismatch().is_some()
+    }
+
+    /// Returns the `Content-Type` of the response, if the error is from
+    /// decoding a body with an unexpected one.
+    pub fn content_type(&self) -> Option<&http::HeaderValue> {
+        self.content_type_mismatch()?.actual.as_ref()
+    }
+
+    /// Returns the errors of a GraphQL response, if the error is from
+    /// decoding one which has errors.
+    ///
+    /// See [`Response::graphql_data`](crate::Response::graphql_data).
+    #[cfg(all(
+        feature = "graphql",
+        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none")))
+    ))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphql")))]
+    pub fn graphql_errors(&self) -> Option<&crate::graphql::Errors> {
+        self.inner.source.as_ref()?.downcast_ref()
+    }
+
+    fn content_type_mismatch(&self) -> Option<&ContentTypeMismatch> {
+        self.inner.source.as_ref()?.downcast_ref()
+    }
+
     /// Returns the status code, if the error was generated from a response.
     pub fn status(&self) -> Option<StatusCode> {
         match self.inner.kind {
@@ -211,8 +447,89 @@ impl Error {
         matches!(self.inner.kind, Kind::Upgrade)
     }
 
+    /// Returns the code of the error, the most specific one which applies.
+    ///
+    /// Unlike the message of the error, the codes are stable: see
+    /// [`ErrorCode`].
+    pub fn code(&self) -> ErrorCode {
+        match self.inner.kind {
+            Kind::Builder => return ErrorCode::Builder,
+            Kind::Status(..) => return ErrorCode::Status,
+            Kind::Upgrade => return ErrorCode::Upgrade,
+            Kind::Redirect => {
+                #[cfg(not(all(
+                    target_arch = "wasm32",
+                    any(target_os = "unknown", target_os = "none")
+                )))]
+                if self.is_too_many_redirects() {
+                    return ErrorCode::TooManyRedirects;
+                }
+                return ErrorCode::Redirect;
+            }
+            Kind::Decode if self.is_content_type() => return ErrorCode::ContentType,
+            Kind::Decode => return ErrorCode::Decode,
+            Kind::Request | Kind::Body => (),
+        }
+
+        if self.is_timeout() {
+            return ErrorCode::Timeout;
+        }
+        #[cfg(feature = "cache")]
+        if self.is_cache_miss() {
+            return ErrorCode::CacheMiss;
+        }
+        #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+        {
+            if self.is_circuit_open() {
+                return ErrorCode::CircuitOpen;
+            }
+            if self.is_dns() {
+                return ErrorCode::Dns;
+            }
+            if self.is_tls() {
+                return ErrorCode::Tls;
+            }
+        }
+        if self.is_proxy() {
+            return ErrorCode::Proxy;
+        }
+        if self.is_connection_reset() {
+            return ErrorCode::ConnectionReset;
+        }
+        #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+        if self.is_connect() {
+            return ErrorCode::Connect;
+        }
+
+        match self.inner.kind {
+            Kind::Body => ErrorCode::Body,
+            _ => ErrorCode::Request,
+        }
+    }
+
     // private
 
+    // Whether `check` is true for a source of this error, including the
+    // errors that an `io::Error` wraps.
+    fn has_source(&self, check: impl Fn(&(dyn StdError + 'static)) -> bool) -> bool {
+        self.sources().any(check)
+    }
+
+    #[cfg(feature = "__tls")]
+    fn find_source<T: StdError + 'static>(&self) -> Option<&T> {
+        self.sources().find_map(|err| err.downcast_ref::<T>())
+    }
+
+    // the sources of this error, including those wrapped in an `io::Error`
+    fn sources(&self) -> impl Iterator<Item = &(dyn StdError + 'static)> {
+        std::iter::successors(self.source(), |&err| {
+            match err.downcast_ref::<io::Error>().and_then(io::Error::get_ref) {
+                Some(inner) => Some(inner as _),
+                None => err.source(),
+            }
+        })
+    }
+
     #[allow(unused)]
     pub(crate) fn into_io(self) -> io::Error {
         io::Error::new(io::ErrorKind::Other, self)
@@ -226,7 +543,7 @@ impl Error {
 #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
 pub(crate) fn cast_to_internal_error(error: BoxError) -> BoxError {
     if error.is::<tower::timeout::error::Elapsed>() {
-        Box::new(crate::error::TimedOut) as BoxError
+        Box::new(TimedOut(TimeoutPhase::Connect)) as BoxError
     } else {
         error
     }
@@ -239,11 +556,18 @@ impl fmt::Debug for Error {
         builder.field("kind", &self.inner.kind);
 
         if let Some(ref url) = self.inner.url {
-            builder.field("url", &url.as_str());
+            builder.field(
+                "url",
+                &redact::url(self.inner.redaction.as_ref(), url).as_str(),
+            );
         }
         if let Some(ref source) = self.inner.source {
             builder.field("source", source);
         }
+        #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+        if let Some(ref connection) = self.inner.connection {
+            builder.field("connection", connection);
+        }
 
         builder.finish()
     }
@@ -293,6 +617,7 @@ impl fmt::Display for Error {
         };
 
         if let Some(url) = &self.inner.url {
+            let url = redact::url(self.inner.redaction.as_ref(), url);
             write!(f, " for url ({url})")?;
         }
 
@@ -348,6 +673,11 @@ pub(crate) fn decode<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Decode, Some(e))
 }
 
+#[allow(unused)]
+pub(crate) fn content_type(expected: &'static str, actual: Option<http::HeaderValue>) -> Error {
+    Error::new(Kind::Decode, Some(ContentTypeMismatch { expected, actual }))
+}
+
 pub(crate) fn request<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Request, Some(e))
 }
@@ -356,6 +686,15 @@ pub(crate) fn dns<E: Into<BoxError>>(e: E) -> BoxError {
     Box::new(DnsError { inner: e.into() })
 }
 
+pub(crate) fn proxy<E: Into<BoxError>>(e: E) -> BoxError {
+    Box::new(ProxyError { inner: e.into() })
+}
+
+#[allow(unused)]
+pub(crate) fn tls<E: Into<BoxError>>(e: E) -> BoxError {
+    Box::new(TlsError { inner: e.into() })
+}
+
 pub(crate) fn redirect<E: Into<BoxError>>(e: E, url: Url) -> Error {
     Error::new(Kind::Redirect, Some(e)).with_url(url)
 }
@@ -411,10 +750,115 @@ pub(crate) fn decode_io(e: io::Error) -> Error {
     }
 }
 
+/// A stable code for the kind of an [`Error`].
+///
+/// Returned by [`Error::code`], for the applications which can't match on
+/// the predicates of `Error`, such as bindings to other languages. The
+/// number and the string identifier of a code never change, and a code is
+/// never reused for another kind of error. New codes may be added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u16)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The client or the request couldn't be built.
+    Builder = 1,
+    /// The request failed, for another reason than those below.
+    Request = 100,
+    /// The request timed out.
+    Timeout = 101,
+    /// Connecting to the host failed.
+    Connect = 102,
+    /// Resolving the name of the host failed.
+    Dns = 103,
+    /// The TLS handshake failed.
+    Tls = 104,
+    /// Connecting through a proxy failed.
+    Proxy = 105,
+    /// The connection was reset by the peer.
+    ConnectionReset = 106,
+    /// The circuit breaker of the host is open.
+    CircuitOpen = 107,
+    /// The request could only be answered from the cache, which had no
+    /// response for it.
+    CacheMiss = 108,
+    /// Following a redirect failed.
+    Redirect = 200,
+    /// A redirect went over the limit of the redirect policy.
+    TooManyRedirects = 201,
+    /// The response has an error status.
+    Status = 300,
+    /// Reading or writing a body failed.
+    Body = 400,
+    /// Decoding the body of the response failed.
+    Decode = 401,
+    /// The body of the response has an unexpected `Content-Type`.
+    ContentType = 402,
+    /// Upgrading the connection failed.
+    Upgrade = 500,
+}
+
+impl ErrorCode {
+    /// The number of this code.
+    pub fn as_u16(self) -> u16 {
+        self as u16
+    }
+
+    /// The string identifier of this code, such as `"too_many_redirects"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Builder => "builder",
+            ErrorCode::Request => "request",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::Connect => "connect",
+            ErrorCode::Dns => "dns",
+            ErrorCode::Tls => "tls",
+            ErrorCode::Proxy => "proxy",
+            ErrorCode::ConnectionReset => "connection_reset",
+            ErrorCode::CircuitOpen => "circuit_open",
+            ErrorCode::CacheMiss => "cache_miss",
+            ErrorCode::Redirect => "redirect",
+            ErrorCode::TooManyRedirects => "too_many_redirects",
+            ErrorCode::Status => "status",
+            ErrorCode::Body => "body",
+            ErrorCode::Decode => "decode",
+            ErrorCode::ContentType => "content_type",
+            ErrorCode::Upgrade => "upgrade",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The phase of a request which timed out.
+///
+/// See [`Error::timeout_phase`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TimeoutPhase {
+    /// Resolving the host name, under the connect timeout.
+    Dns,
+    /// Opening the connection, under the connect timeout.
+    Connect,
+    /// The TLS handshake, under the connect timeout.
+    Tls,
+    /// Waiting for an in-flight slot, under the queue timeout.
+    Queue,
+    /// Waiting for the response headers, under the read timeout.
+    ResponseHeaders,
+    /// Reading the response body, under the read timeout.
+    BodyRead,
+    /// Anywhere in the request, under its total timeout.
+    Total,
+}
+
 // internal Error "sources"
 
 #[derive(Debug)]
-pub(crate) struct TimedOut;
+pub(crate) struct TimedOut(pub(crate) TimeoutPhase);
 
 impl fmt::Display for TimedOut {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -435,6 +879,32 @@ impl fmt::Display for BadScheme {
 
 impl StdError for BadScheme {}
 
+#[derive(Debug)]
+pub(crate) struct ContentTypeMismatch {
+    expected: &'static str,
+    actual: Option<http::HeaderValue>,
+}
+
+impl fmt::Display for ContentTypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.actual {
+            Some(ref actual) => write!(
+                f,
+                "expected a {} response, got {}",
+                self.expected,
+                String::from_utf8_lossy(actual.as_bytes())
+            ),
+            None => write!(
+                f,
+                "expected a {} response, got no content type",
+                self.expected
+            ),
+        }
+    }
+}
+
+impl StdError for ContentTypeMismatch {}
+
 #[derive(Debug)]
 pub(crate) struct DnsError {
     pub(crate) inner: BoxError,
@@ -452,6 +922,40 @@ impl StdError for DnsError {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct ProxyError {
+    inner: BoxError,
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("error connecting through proxy")
+    }
+}
+
+impl StdError for ProxyError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.inner as _)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct TlsError {
+    inner: BoxError,
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("error in TLS handshake")
+    }
+}
+
+impl StdError for TlsError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.inner as _)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -502,14 +1006,43 @@ mod tests {
 
     #[test]
     fn is_timeout() {
-        let err = super::request(super::TimedOut);
+        let err = super::request(super::TimedOut(TimeoutPhase::Dns));
         assert!(err.is_timeout());
+        assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Dns));
 
         // todo: test `hyper::Error::is_timeout` when we can easily construct one
 
         let io = io::Error::from(io::ErrorKind::TimedOut);
         let nested = super::request(io);
         assert!(nested.is_timeout());
+        assert_eq!(nested.timeout_phase(), None);
+    }
+
+    #[test]
+    fn code() {
+        let err = super::builder("bad");
+        assert_eq!(err.code(), ErrorCode::Builder);
+
+        let err = super::request(super::TimedOut(TimeoutPhase::Total));
+        assert_eq!(err.code(), ErrorCode::Timeout);
+
+        let err = super::body(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert_eq!(err.code(), ErrorCode::ConnectionReset);
+
+        let err = super::body("bad");
+        assert_eq!(err.code(), ErrorCode::Body);
+
+        let err = super::request(super::proxy("refused"));
+        assert_eq!(err.code(), ErrorCode::Proxy);
+    }
+
+    #[test]
+    fn code_is_stable() {
+        assert_eq!(ErrorCode::Builder.as_u16(), 1);
+        assert_eq!(ErrorCode::Timeout.as_u16(), 101);
+        assert_eq!(ErrorCode::TooManyRedirects.as_u16(), 201);
+        assert_eq!(ErrorCode::TooManyRedirects.as_str(), "too_many_redirects");
+        assert_eq!(ErrorCode::ConnectionReset.to_string(), "connection_reset");
     }
 
     #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
@@ -518,4 +1051,36 @@ mod tests {
         let err = super::request(DnsError { inner: "".into() });
         assert!(err.is_dns());
     }
+
+    #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+    #[test]
+    fn is_tls() {
+        let err = super::request(super::tls("bad certificate"));
+        assert!(err.is_tls());
+        assert!(!super::request("other").is_tls());
+    }
+
+    #[test]
+    fn is_proxy() {
+        let dns = super::dns("no such host");
+        let err = super::request(super::proxy(dns));
+        assert!(err.is_proxy());
+        // the cause stays visible
+        #[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+        assert!(err.is_dns());
+    }
+
+    #[test]
+    fn is_connection_reset() {
+        let io = io::Error::from(io::ErrorKind::ConnectionReset);
+        assert!(super::request(io).is_connection_reset());
+
+        // within an io::Error wrapping another error
+        let inner = super::body(io::Error::from(io::ErrorKind::ConnectionReset));
+        let io = io::Error::other(inner);
+        assert!(super::decode(io).is_connection_reset());
+
+        let io = io::Error::from(io::ErrorKind::ConnectionRefused);
+        assert!(!super::request(io).is_connection_reset());
+    }
 }
@@ -0,0 +1,215 @@
+//! GraphQL requests.
+//!
+//! [`RequestBuilder::graphql`] sends a query and its variables as the JSON
+//! body of a request, as described by the [GraphQL over HTTP] specification,
+//! and [`Response::graphql_data`] separates the `data` of the response from
+//! its `errors`:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! #[derive(serde::Deserialize)]
+//! struct Data {
+//!     user: User,
+//! }
+//!
+//! #[derive(serde::Deserialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! let data: Data = reqwest::Client::new()
+//!     .post("https://api.example.com/graphql")
+//!     .graphql(
+//!         "query($id: ID!) { user(id: $id) { name } }",
+//!         &serde_json::json!({ "id": "1" }),
+//!     )
+//!     .send()
+//!     .await?
+//!     .graphql_data()
+//!     .await?;
+//! println!("{}", data.user.name);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A response with errors fails to decode, even if it has some data. The
+//! errors, and the data, are then returned by
+//! [`Error::graphql_errors`](crate::Error::graphql_errors).
+//!
+//! [`RequestBuilder::graphql`]: crate::RequestBuilder::graphql
+//! [`Response::graphql_data`]: crate::Response::graphql_data
+//! [GraphQL over HTTP]: https://graphql.github.io/graphql-over-http/
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::BoxError;
+
+/// The `Accept` header of GraphQL requests, preferring the media type of
+/// GraphQL responses.
+pub(crate) const ACCEPT: &str = "application/graphql-response+json, application/json";
+
+/// The errors of a GraphQL response, with the data it has despite them.
+#[derive(Clone, Debug)]
+pub struct Errors {
+    errors: Vec<Error>,
+    data: Option<Value>,
+}
+
+/// An error of a GraphQL response.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Error {
+    message: String,
+    #[serde(default, deserialize_with = "nullable")]
+    locations: Vec<Location>,
+    #[serde(default)]
+    path: Option<Vec<PathSegment>>,
+    #[serde(default)]
+    extensions: Option<Map<String, Value>>,
+}
+
+/// A location in a GraphQL query.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub struct Location {
+    line: u32,
+    column: u32,
+}
+
+/// A segment of the path to the field of a GraphQL error.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PathSegment {
+    /// The name, or alias, of a field.
+    Field(String),
+    /// The index of an item in a list.
+    Index(usize),
+}
+
+// ===== impl Errors =====
+
+impl Errors {
+    /// The errors of the response, of which there is at least one.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// The data of the response, if it has some despite its errors.
+    pub fn data(&self) -> Option<&Value> {
+        self.data.as_ref()
+    }
+}
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("GraphQL response has errors: ")?;
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            fmt::Display::fmt(error, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Errors {}
+
+// ===== impl Error =====
+
+impl Error {
+    /// The description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The locations in the query the error relates to.
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    /// The path to the field of the response the error relates to, if any.
+    pub fn path(&self) -> Option<&[PathSegment]> {
+        self.path.as_deref()
+    }
+
+    /// The additional information of the error, such as a code.
+    pub fn extensions(&self) -> Option<&Map<String, Value>> {
+        self.extensions.as_ref()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// ===== impl Location =====
+
+impl Location {
+    /// The line, from 1.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// The column, from 1.
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+// ===== encoding =====
+
+#[derive(Serialize)]
+struct Request<'a> {
+    query: &'a str,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    variables: Value,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<Error>,
+}
+
+// A list which may be `null` rather than left out.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Encode the body of a request for `query`, with `variables` unless they
+/// serialize to `null`.
+pub(crate) fn body<T: Serialize + ?Sized>(
+    query: &str,
+    variables: &T,
+) -> serde_json::Result<Vec<u8>> {
+    serde_json::to_vec(&Request {
+        query,
+        variables: serde_json::to_value(variables)?,
+    })
+}
+
+/// Decode the data of a response, failing with its errors if it has any.
+pub(crate) fn data<T: DeserializeOwned>(body: &[u8]) -> Result<T, BoxError> {
+    let res: Response = serde_json::from_slice(body)?;
+    if !res.errors.is_empty() {
+        return Err(Box::new(Errors {
+            errors: res.errors,
+            data: res.data,
+        }));
+    }
+    match res.data {
+        Some(data) => Ok(serde_json::from_value(data)?),
+        None => Err("GraphQL response has neither data nor errors".into()),
+    }
+}
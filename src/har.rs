@@ -0,0 +1,369 @@
+//! HAR import and replay.
+//!
+//! A [`Har`] is loaded from an [HTTP Archive][har] file, such as one saved
+//! from the network panel of a browser. Its entries can then be sent again
+//! with a `Client`, either to the server they were captured from, or to
+//! another origin, such as a staging backend.
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use reqwest::har::Har;
+//!
+//! let har = Har::from_slice(&std::fs::read("capture.har")?)?;
+//! let client = reqwest::Client::new();
+//! let staging = "https://staging.example.com".parse()?;
+//!
+//! for (entry, res) in har.entries().iter().zip(har.replay_to(&client, &staging).await) {
+//!     let status = res?.status();
+//!     if Some(status) != entry.status() {
+//!         println!("{} {}: {} (was {:?})", entry.method(), entry.url(), status, entry.status());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Headers that describe the connection rather than the request, such as
+//! `host`, `content-length` and HTTP/2 pseudo-headers, are not replayed.
+//! The `Client` sets them itself.
+//!
+//! # Optional
+//!
+//! This requires the optional `har` feature to be enabled.
+//!
+//! [har]: https://w3c.github.io/web-performance/specs/HAR/Overview.html
+
+use std::fmt;
+use std::io::Read;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+
+use crate::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+
+/// The entries of an HTTP Archive.
+#[derive(Clone, Debug)]
+pub struct Har {
+    entries: Vec<Entry>,
+}
+
+/// A request captured in an HTTP Archive.
+#[derive(Clone)]
+pub struct Entry {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+    status: Option<StatusCode>,
+}
+
+impl Har {
+    /// Load an archive from the bytes of a HAR file.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the bytes are not a HAR file, or if a request in it has
+    /// an invalid method, URL or header.
+    pub fn from_slice(bytes: &[u8]) -> crate::Result<Har> {
+        let file: File = serde_json::from_slice(bytes).map_err(crate::error::decode)?;
+        Har::from_file(file)
+    }
+
+    /// Load an archive from a reader of a HAR file.
+    ///
+    /// # Errors
+    ///
+    /// This fails if reading fails, or for the same reasons as
+    /// [`Har::from_slice`].
+    pub fn from_reader<R: Read>(reader: R) -> crate::Result<Har> {
+        let file: File = serde_json::from_reader(reader).map_err(crate::error::decode)?;
+        Har::from_file(file)
+    }
+
+    /// The entries of the archive, in the order they were captured.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Send every entry again, one after the other.
+    ///
+    /// The results are in the order of the entries. A failed request does
+    /// not stop the replay.
+    pub async fn replay(&self, client: &Client) -> Vec<crate::Result<Response>> {
+        let mut results = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            results.push(entry.request(client).send().await);
+        }
+        results
+    }
+
+    /// Send every entry again to another origin, one after the other.
+    ///
+    /// See [`Entry::request_to`] for how the URLs are changed.
+    pub async fn replay_to(&self, client: &Client, origin: &Url) -> Vec<crate::Result<Response>> {
+        let mut results = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            results.push(entry.request_to(client, origin).send().await);
+        }
+        results
+    }
+
+    fn from_file(file: File) -> crate::Result<Har> {
+        let entries = file
+            .log
+            .entries
+            .into_iter()
+            .map(Entry::from_raw)
+            .collect::<crate::Result<_>>()?;
+        Ok(Har { entries })
+    }
+}
+
+impl Entry {
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The URL of the request.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The headers of the request that are replayed.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body of the request, if it had one.
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    /// The status of the response that was captured, if there was one.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// Build the request again with a `Client`.
+    pub fn request(&self, client: &Client) -> RequestBuilder {
+        self.build(client, self.url.clone())
+    }
+
+    /// Build the request again, to be sent to another origin.
+    ///
+    /// The scheme, host and port of the URL are replaced with those of
+    /// `origin`, while the path and query are kept. Headers that name the
+    /// original origin, such as `origin` and `referer`, are sent as
+    /// captured.
+    pub fn request_to(&self, client: &Client, origin: &Url) -> RequestBuilder {
+        let mut url = origin.clone();
+        url.set_path(self.url.path());
+        url.set_query(self.url.query());
+        self.build(client, url)
+    }
+
+    fn build(&self, client: &Client, url: Url) -> RequestBuilder {
+        let req = client
+            .request(self.method.clone(), url)
+            .headers(self.headers.clone());
+        match self.body {
+            Some(ref body) => req.body(body.clone()),
+            None => req,
+        }
+    }
+
+    fn from_raw(entry: RawEntry) -> crate::Result<Entry> {
+        let req = entry.request;
+        let method = Method::from_bytes(req.method.as_bytes()).map_err(crate::error::decode)?;
+        let url = Url::parse(&req.url).map_err(crate::error::decode)?;
+
+        let mut headers = HeaderMap::new();
+        for header in req.headers {
+            if is_connection_header(&header.name) {
+                continue;
+            }
+            let name =
+                HeaderName::from_bytes(header.name.as_bytes()).map_err(crate::error::decode)?;
+            let value = HeaderValue::from_str(&header.value).map_err(crate::error::decode)?;
+            headers.append(name, value);
+        }
+
+        let body = match req.post_data {
+            Some(PostData {
+                text: Some(text),
+                encoding: Some(encoding),
+            }) if encoding == "base64" => Some(Bytes::from(
+                BASE64_STANDARD
+                    .decode(text)
+                    .map_err(crate::error::decode)?,
+            )),
+            Some(PostData {
+                text: Some(text), ..
+            }) => Some(Bytes::from(text)),
+            _ => None,
+        };
+
+        // Browsers record requests that got no response with a status of 0.
+        let status = entry
+            .response
+            .and_then(|res| StatusCode::from_u16(res.status).ok());
+
+        Ok(Entry {
+            method,
+            url,
+            headers,
+            body,
+            status,
+        })
+    }
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("method", &self.method)
+            .field("url", &self.url.as_str())
+            .field("headers", &self.headers)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+fn is_connection_header(name: &str) -> bool {
+    const SKIPPED: &[&str] = &[
+        "host",
+        "content-length",
+        "connection",
+        "keep-alive",
+        "proxy-connection",
+        "transfer-encoding",
+        "te",
+        "upgrade",
+    ];
+    name.starts_with(':') || SKIPPED.iter().any(|s| s.eq_ignore_ascii_case(name))
+}
+
+// ===== HAR format =====
+
+#[derive(Deserialize)]
+struct File {
+    log: Log,
+}
+
+#[derive(Deserialize)]
+struct Log {
+    entries: Vec<RawEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    request: RawRequest,
+    response: Option<RawResponse>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<RawHeader>,
+    post_data: Option<PostData>,
+}
+
+#[derive(Deserialize)]
+struct RawHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct PostData {
+    text: Option<String>,
+    encoding: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawResponse {
+    status: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAR: &str = r#"{
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "test", "version": "1" },
+            "entries": [
+                {
+                    "request": {
+                        "method": "POST",
+                        "url": "https://example.com/api?id=1",
+                        "httpVersion": "HTTP/2",
+                        "headers": [
+                            { "name": ":authority", "value": "example.com" },
+                            { "name": "content-type", "value": "application/json" },
+                            { "name": "content-length", "value": "11" },
+                            { "name": "accept", "value": "a" },
+                            { "name": "accept", "value": "b" }
+                        ],
+                        "postData": { "mimeType": "application/json", "text": "{\"a\": true}" }
+                    },
+                    "response": { "status": 201 }
+                },
+                {
+                    "request": {
+                        "method": "PUT",
+                        "url": "https://example.com/upload",
+                        "headers": [],
+                        "postData": { "text": "aGVsbG8=", "encoding": "base64" }
+                    },
+                    "response": { "status": 0 }
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn parse_entries() {
+        let har = Har::from_slice(HAR.as_bytes()).unwrap();
+        let entries = har.entries();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].method(), Method::POST);
+        assert_eq!(entries[0].url().as_str(), "https://example.com/api?id=1");
+        assert_eq!(entries[0].headers().len(), 3);
+        assert!(!entries[0].headers().contains_key("content-length"));
+        assert_eq!(entries[0].headers().get_all("accept").iter().count(), 2);
+        assert_eq!(entries[0].body(), Some(&b"{\"a\": true}"[..]));
+        assert_eq!(entries[0].status(), Some(StatusCode::CREATED));
+
+        assert_eq!(entries[1].body(), Some(&b"hello"[..]));
+        assert_eq!(entries[1].status(), None);
+    }
+
+    #[test]
+    fn request_to_other_origin() {
+        let har = Har::from_slice(HAR.as_bytes()).unwrap();
+        let client = Client::new();
+        let origin = Url::parse("http://localhost:8080").unwrap();
+        let req = har.entries()[0]
+            .request_to(&client, &origin)
+            .build()
+            .unwrap();
+        assert_eq!(req.url().as_str(), "http://localhost:8080/api?id=1");
+        assert_eq!(req.headers()["content-type"], "application/json");
+    }
+
+    #[test]
+    fn invalid_har() {
+        let err = Har::from_slice(b"{\"log\": {}}").unwrap_err();
+        assert!(err.is_decode());
+    }
+}
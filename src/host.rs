@@ -0,0 +1,257 @@
+//! Per-host configuration.
+//!
+//! A single `Client` often talks to several backends, which don't all want
+//! the same headers or timeouts. Instead of building a client for each of
+//! them, the settings of a host can be overridden with
+//! [`ClientBuilder::for_host`](crate::ClientBuilder::for_host):
+//!
+//! ```rust
+//! # fn run() -> Result<(), reqwest::Error> {
+//! use std::time::Duration;
+//!
+//! use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer api-token"));
+//!
+//! let client = reqwest::Client::builder()
+//!     .timeout(Duration::from_secs(10))
+//!     .for_host("api.example.com", |host| host.headers(headers))
+//!     .for_host("reports.example.com", |host| {
+//!         host.timeout(Duration::from_secs(120))
+//!     })
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The settings of a request come first, then those of its host, then those
+//! of the client. Connections to all hosts share the pool of the client.
+//!
+//! A host can also be given TLS settings of its own, such as a root
+//! certificate of a private CA, or a client identity. The connections to it
+//! then use the TLS configuration of the client with these changes. They
+//! need the client to configure its TLS backend itself, so they can't be
+//! used with [`ClientBuilder::use_preconfigured_tls`], and they don't apply
+//! to HTTP/3 connections. [`Client::reload_tls`] only replaces the TLS
+//! configuration of the client, not those of the hosts.
+//!
+//! [`ClientBuilder::use_preconfigured_tls`]: crate::ClientBuilder::use_preconfigured_tls
+//! [`Client::reload_tls`]: crate::Client::reload_tls
+
+use std::fmt;
+use std::time::Duration;
+
+use http::{HeaderMap, Version};
+
+#[cfg(feature = "__tls")]
+use crate::tls;
+#[cfg(feature = "__tls")]
+use crate::Certificate;
+#[cfg(feature = "__tls")]
+use crate::Identity;
+
+/// The settings overridden for the requests to one host.
+///
+/// Created with [`ClientBuilder::for_host`](crate::ClientBuilder::for_host).
+#[derive(Clone, Default)]
+pub struct HostConfig {
+    pub(crate) headers: HeaderMap,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) version: Option<Version>,
+    #[cfg(feature = "__tls")]
+    pub(crate) root_certs: Vec<Certificate>,
+    #[cfg(feature = "__tls")]
+    pub(crate) identity: Option<Identity>,
+    #[cfg(feature = "__tls")]
+    pub(crate) accept_invalid_certs: Option<bool>,
+    #[cfg(feature = "__tls")]
+    pub(crate) min_tls_version: Option<tls::Version>,
+    #[cfg(feature = "__tls")]
+    pub(crate) max_tls_version: Option<tls::Version>,
+}
+
+impl HostConfig {
+    /// Add headers to the requests to this host.
+    ///
+    /// They replace the default headers of the client with the same name,
+    /// but not the headers set on a request.
+    pub fn headers(mut self, headers: HeaderMap) -> HostConfig {
+        for (key, value) in headers.iter() {
+            self.headers.insert(key, value.clone());
+        }
+        self
+    }
+
+    /// Set the total timeout of the requests to this host.
+    ///
+    /// See [`ClientBuilder::timeout`](crate::ClientBuilder::timeout).
+    pub fn timeout(mut self, timeout: Duration) -> HostConfig {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the read timeout of the requests to this host.
+    ///
+    /// See [`ClientBuilder::read_timeout`](crate::ClientBuilder::read_timeout).
+    pub fn read_timeout(mut self, timeout: Duration) -> HostConfig {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the HTTP version of the requests to this host which have the
+    /// default one, HTTP/1.1, rather than one set with
+    /// [`RequestBuilder::version`](crate::RequestBuilder::version).
+    ///
+    /// As with `RequestBuilder::version`, the client must be able to use the
+    /// version, such as `Version::HTTP_10`, or `Version::HTTP_3` with a
+    /// client configured for HTTP/3.
+    pub fn version(mut self, version: Version) -> HostConfig {
+        self.version = Some(version);
+        self
+    }
+
+    /// Add a root certificate trusted by the connections to this host, on
+    /// top of those of the client.
+    ///
+    /// See [`ClientBuilder::add_root_certificate`](crate::ClientBuilder::add_root_certificate).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "default-tls", feature = "native-tls", feature = "rustls")))
+    )]
+    pub fn add_root_certificate(mut self, cert: Certificate) -> HostConfig {
+        self.root_certs.push(cert);
+        self
+    }
+
+    /// Set the client identity of the connections to this host, in place of
+    /// the one of the client.
+    ///
+    /// See [`ClientBuilder::identity`](crate::ClientBuilder::identity).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `native-tls` or `rustls(-...)` feature to be
+    /// enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "native-tls", feature = "rustls"))))]
+    pub fn identity(mut self, identity: Identity) -> HostConfig {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Controls the use of certificate validation for the connections to
+    /// this host.
+    ///
+    /// See [`ClientBuilder::tls_danger_accept_invalid_certs`](crate::ClientBuilder::tls_danger_accept_invalid_certs).
+    ///
+    /// # Warning
+    ///
+    /// You should think very carefully before using this method. If
+    /// invalid certificates are trusted, *any* certificate for *any* site
+    /// will be trusted for use. This includes expired certificates. This
+    /// introduces significant vulnerabilities, and should only be used
+    /// as a last resort.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "default-tls", feature = "native-tls", feature = "rustls")))
+    )]
+    pub fn tls_danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> HostConfig {
+        self.accept_invalid_certs = Some(accept_invalid_certs);
+        self
+    }
+
+    /// Set the minimum required TLS version of the connections to this
+    /// host.
+    ///
+    /// See [`ClientBuilder::tls_version_min`](crate::ClientBuilder::tls_version_min).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "default-tls", feature = "native-tls", feature = "rustls")))
+    )]
+    pub fn tls_version_min(mut self, version: tls::Version) -> HostConfig {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    /// Set the maximum allowed TLS version of the connections to this host.
+    ///
+    /// See [`ClientBuilder::tls_version_max`](crate::ClientBuilder::tls_version_max).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `default-tls`, `native-tls`, or `rustls(-...)`
+    /// feature to be enabled.
+    #[cfg(feature = "__tls")]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "default-tls", feature = "native-tls", feature = "rustls")))
+    )]
+    pub fn tls_version_max(mut self, version: tls::Version) -> HostConfig {
+        self.max_tls_version = Some(version);
+        self
+    }
+
+    /// Whether the connections to this host need a TLS configuration of
+    /// their own.
+    #[cfg(feature = "__tls")]
+    pub(crate) fn has_tls(&self) -> bool {
+        !self.root_certs.is_empty()
+            || self.identity.is_some()
+            || self.accept_invalid_certs.is_some()
+            || self.min_tls_version.is_some()
+            || self.max_tls_version.is_some()
+    }
+}
+
+impl fmt::Debug for HostConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut d = f.debug_struct("HostConfig");
+        // only the names, the values are often credentials
+        d.field("headers", &self.headers.keys().collect::<Vec<_>>());
+        if let Some(ref timeout) = self.timeout {
+            d.field("timeout", timeout);
+        }
+        if let Some(ref timeout) = self.read_timeout {
+            d.field("read_timeout", timeout);
+        }
+        if let Some(ref version) = self.version {
+            d.field("version", version);
+        }
+        #[cfg(feature = "__tls")]
+        if self.has_tls() {
+            // only whether there is an identity, it holds a private key
+            d.field("root_certs", &self.root_certs.len());
+            d.field("identity", &self.identity.is_some());
+            if let Some(accept) = self.accept_invalid_certs {
+                d.field("accept_invalid_certs", &accept);
+            }
+            if let Some(ref version) = self.min_tls_version {
+                d.field("min_tls_version", version);
+            }
+            if let Some(ref version) = self.max_tls_version {
+                d.field("max_tls_version", version);
+            }
+        }
+        d.finish()
+    }
+}
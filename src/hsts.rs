@@ -0,0 +1,228 @@
+//! HSTS preloading
+//!
+//! Browsers ship a list of domains which are only ever reached over HTTPS,
+//! the [HSTS preload list][preload]: an `http://` URL of one of them is
+//! upgraded to `https://` before anything is sent, so that not even the
+//! first request to it can be read or tampered with on the network.
+//!
+//! A [`Preload`] list set with
+//! [`ClientBuilder::hsts_preload`](crate::ClientBuilder::hsts_preload) does
+//! the same for a `Client`, for the URLs of its requests and of the
+//! redirects it follows. The list of the browsers is loaded from the
+//! `transport_security_state_static.json` file of Chromium, which is
+//! updated with each of its releases:
+//!
+//! ```rust,no_run
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use reqwest::hsts::Preload;
+//!
+//! let file = std::fs::File::open("transport_security_state_static.json")?;
+//! let client = reqwest::Client::builder()
+//!     .hsts_preload(Preload::from_reader(file)?)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The list isn't embedded in this crate, since it keeps growing and a
+//! stale copy would give a false sense of safety: the application decides
+//! which release it trusts, and when to update it. Domains can also be
+//! added one by one, such as internal hosts which must never be reached
+//! over plain HTTP.
+//!
+//! Upgraded URLs use the default port of HTTPS, unless they had an explicit
+//! port other than `80`.
+//!
+//! # Optional
+//!
+//! This requires the optional `hsts-preload` feature to be enabled.
+//!
+//! [preload]: https://hstspreload.org/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+use serde::Deserialize;
+
+use crate::Url;
+
+/// A list of domains which are only reached over HTTPS.
+#[derive(Clone, Default)]
+pub struct Preload {
+    // whether the subdomains are included, by domain
+    domains: HashMap<String, bool>,
+}
+
+impl Preload {
+    /// Create an empty list.
+    pub fn new() -> Preload {
+        Preload::default()
+    }
+
+    /// Load the list from the bytes of Chromium's
+    /// `transport_security_state_static.json` file.
+    ///
+    /// Only the entries in `force-https` mode are kept, the others being
+    /// about certificate pinning.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the bytes are not a preload list.
+    pub fn from_slice(bytes: &[u8]) -> crate::Result<Preload> {
+        // the file has comment lines, which JSON doesn't allow
+        let json = bytes
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.trim_ascii_start().starts_with(b"//"))
+            .collect::<Vec<_>>()
+            .join(&b'\n');
+        let file: File = serde_json::from_slice(&json).map_err(crate::error::decode)?;
+        let mut preload = Preload::new();
+        for entry in file.entries {
+            if entry.mode.as_deref() == Some("force-https") {
+                preload.insert(&entry.name, entry.include_subdomains);
+            }
+        }
+        Ok(preload)
+    }
+
+    /// Load the list from a reader of Chromium's
+    /// `transport_security_state_static.json` file.
+    ///
+    /// # Errors
+    ///
+    /// This fails if reading fails, or for the same reasons as
+    /// [`Preload::from_slice`].
+    pub fn from_reader<R: Read>(mut reader: R) -> crate::Result<Preload> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(crate::error::decode_io)?;
+        Preload::from_slice(&bytes)
+    }
+
+    /// Add a domain to the list, with its subdomains if `include_subdomains`
+    /// is `true`.
+    pub fn domain(mut self, domain: &str, include_subdomains: bool) -> Preload {
+        self.insert(domain, include_subdomains);
+        self
+    }
+
+    fn insert(&mut self, domain: &str, include_subdomains: bool) {
+        let domain = normalize(domain);
+        let included = self.domains.entry(domain).or_insert(false);
+        *included |= include_subdomains;
+    }
+
+    /// Returns whether `host` is only reached over HTTPS.
+    pub fn contains(&self, host: &str) -> bool {
+        let host = normalize(host);
+        if self.domains.contains_key(&host) {
+            return true;
+        }
+        host.match_indices('.')
+            .any(|(i, _)| self.domains.get(&host[i + 1..]) == Some(&true))
+    }
+
+    /// The number of domains in the list.
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Returns whether the list has no domains.
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Switch `url` to HTTPS if it is a plain HTTP URL of a domain in the
+    /// list, returning whether it was.
+    pub(crate) fn upgrade(&self, url: &mut Url) -> bool {
+        if url.scheme() != "http" {
+            return false;
+        }
+        match url.host() {
+            Some(url::Host::Domain(domain)) if self.contains(domain) => (),
+            _ => return false,
+        }
+        let port = url.port();
+        if url.set_scheme("https").is_err() {
+            return false;
+        }
+        if port == Some(80) {
+            let _ = url.set_port(None);
+        }
+        true
+    }
+}
+
+fn normalize(domain: &str) -> String {
+    domain.trim_end_matches('.').to_ascii_lowercase()
+}
+
+impl fmt::Debug for Preload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Preload")
+            .field("domains", &self.domains.len())
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct File {
+    entries: Vec<Entry>,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    name: String,
+    #[serde(default)]
+    include_subdomains: bool,
+    mode: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_chromium_file() {
+        let json = br#"{
+  // comments are allowed
+  "entries": [
+    // with a policy
+    { "name": "example.com", "policy": "custom", "mode": "force-https", "include_subdomains": true },
+    { "name": "example.org", "policy": "custom", "mode": "force-https" },
+    { "name": "pinned.example", "policy": "custom", "pins": "example" }
+  ]
+}"#;
+        let preload = Preload::from_slice(json).unwrap();
+        assert_eq!(preload.len(), 2);
+        assert!(preload.contains("example.com"));
+        assert!(preload.contains("www.Example.COM."));
+        assert!(preload.contains("example.org"));
+        assert!(!preload.contains("www.example.org"));
+        assert!(!preload.contains("pinned.example"));
+        assert!(!preload.contains("notexample.com"));
+    }
+
+    #[test]
+    fn upgrade() {
+        let preload = Preload::new().domain("example.com", true);
+        let upgrade = |url: &str| {
+            let mut url = Url::parse(url).unwrap();
+            preload.upgrade(&mut url);
+            url.to_string()
+        };
+        assert_eq!(upgrade("http://example.com/a?b"), "https://example.com/a?b");
+        assert_eq!(
+            upgrade("http://a.example.com:80/"),
+            "https://a.example.com/"
+        );
+        assert_eq!(
+            upgrade("http://example.com:8080/"),
+            "https://example.com:8080/"
+        );
+        assert_eq!(upgrade("http://example.net/"), "http://example.net/");
+        assert_eq!(upgrade("http://127.0.0.1/"), "http://127.0.0.1/");
+    }
+}
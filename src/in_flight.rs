@@ -0,0 +1,490 @@
+//! Limits on the number of requests in flight, to each host and in total.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use http::{Request, Response, Uri};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Sleep;
+use tower::Service;
+
+use crate::async_impl::body::Body;
+use crate::error::{TimedOut, TimeoutPhase};
+
+/// The priority of a request, when waiting for the number of requests in
+/// flight to go under the limit of a client.
+///
+/// Set with
+/// [`RequestBuilder::queue_priority`](crate::RequestBuilder::queue_priority),
+/// and only used with a
+/// [`ClientBuilder::max_in_flight`](crate::ClientBuilder::max_in_flight)
+/// limit: the waiting requests are sent from the highest priority to the
+/// lowest, in the order they were sent for each priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QueuePriority {
+    /// Requests someone is waiting for.
+    High,
+    /// The priority of requests by default.
+    #[default]
+    Normal,
+    /// Background requests, sent once no other request waits.
+    Low,
+}
+
+/// A slot among the requests in flight of a client, reserved before building
+/// a request.
+///
+/// Returned by [`Client::ready`](crate::Client::ready) and
+/// [`Client::try_acquire`](crate::Client::try_acquire). Passed to
+/// [`RequestBuilder::in_flight_permit`](crate::RequestBuilder::in_flight_permit),
+/// the request takes the slot rather than waiting for one, and holds it until
+/// its response ends. Dropping the permit unused frees the slot.
+///
+/// With a [limit per host](crate::ClientBuilder::max_in_flight_per_host), a
+/// request whose host is at its limit gives the slot back and waits in line,
+/// rather than holding it while other requests wait.
+pub struct InFlightPermit {
+    total: Option<QueuePermit>,
+}
+
+impl InFlightPermit {
+    /// A permit of a client without a limit on its requests in flight.
+    pub(crate) fn unlimited() -> InFlightPermit {
+        InFlightPermit { total: None }
+    }
+}
+
+impl fmt::Debug for InFlightPermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InFlightPermit")
+            .field("reserved", &self.total.is_some())
+            .finish()
+    }
+}
+
+/// An [`InFlightPermit`] in the extensions of a request, taken by the first
+/// attempt to send it.
+#[derive(Clone)]
+pub(crate) struct Reserved(Arc<Mutex<Option<QueuePermit>>>);
+
+impl Reserved {
+    pub(crate) fn new(permit: InFlightPermit) -> Reserved {
+        Reserved(Arc::new(Mutex::new(permit.total)))
+    }
+
+    fn take(&self) -> Option<QueuePermit> {
+        self.0.lock().expect("lock poisoned").take()
+    }
+}
+
+impl fmt::Debug for Reserved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Reserved").finish()
+    }
+}
+
+/// The requests in flight to each host and in total, shared by all the
+/// services of a client.
+pub(crate) struct Limits {
+    per_host: Option<usize>,
+    queue_timeout: Option<Duration>,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+    total: Option<Arc<Queue>>,
+}
+
+/// What a request in flight holds until it ends.
+pub(crate) struct Permit {
+    _host: Option<OwnedSemaphorePermit>,
+    _total: Option<QueuePermit>,
+}
+
+impl Limits {
+    pub(crate) fn new(
+        per_host: Option<usize>,
+        total: Option<usize>,
+        queue_timeout: Option<Duration>,
+    ) -> Limits {
+        Limits {
+            per_host,
+            queue_timeout,
+            hosts: Mutex::new(HashMap::new()),
+            total: total.map(Queue::new),
+        }
+    }
+
+    fn semaphore(&self, max: usize, uri: &Uri) -> Arc<Semaphore> {
+        let key = format!(
+            "{}://{}",
+            uri.scheme_str().unwrap_or(""),
+            uri.authority().map_or("", |a| a.as_str())
+        );
+        let mut hosts = self.hosts.lock().expect("lock poisoned");
+        if let Some(semaphore) = hosts.get(&key) {
+            return semaphore.clone();
+        }
+        // forget the hosts without requests in flight, which only the map
+        // refers to
+        hosts.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        let semaphore = Arc::new(Semaphore::new(max));
+        hosts.insert(key, semaphore.clone());
+        semaphore
+    }
+
+    /// Wait for a slot among the requests in flight in total.
+    pub(crate) async fn ready(&self) -> crate::Result<InFlightPermit> {
+        let total = match self.total {
+            Some(ref queue) => queue.acquire(QueuePriority::Normal),
+            None => return Ok(InFlightPermit::unlimited()),
+        };
+        let total = match self.queue_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, total)
+                .await
+                .map_err(|_| crate::error::request(TimedOut(TimeoutPhase::Queue)))?,
+            None => total.await,
+        };
+        Ok(InFlightPermit { total: Some(total) })
+    }
+
+    /// Take a slot among the requests in flight in total, if one is free.
+    pub(crate) fn try_acquire(&self) -> Option<InFlightPermit> {
+        let total = match self.total {
+            Some(ref queue) => Some(queue.try_acquire()?),
+            None => None,
+        };
+        Some(InFlightPermit { total })
+    }
+
+    async fn acquire(&self, req: &Request<Body>) -> crate::Result<Permit> {
+        let priority = req
+            .extensions()
+            .get::<QueuePriority>()
+            .copied()
+            .unwrap_or_default();
+        // a permit reserved with this client, not another one
+        let reserved = req
+            .extensions()
+            .get::<Reserved>()
+            .and_then(Reserved::take)
+            .filter(|permit| match (&permit.queue, &self.total) {
+                (Some(queue), Some(total)) => Arc::ptr_eq(queue, total),
+                _ => false,
+            });
+        let acquire = async {
+            let mut reserved = reserved;
+            // always wait for the host first, so that no two requests wait
+            // for each other: a slot reserved in total is only kept if the
+            // host is free, and given back to wait in line otherwise
+            let host = match self.per_host {
+                Some(max) => {
+                    let semaphore = self.semaphore(max, req.uri());
+                    let permit = match semaphore.clone().try_acquire_owned() {
+                        Ok(permit) => Ok(permit),
+                        Err(_) => {
+                            drop(reserved.take());
+                            semaphore.acquire_owned().await
+                        }
+                    };
+                    Some(permit.expect("semaphore is never closed"))
+                }
+                None => None,
+            };
+            let total = match (reserved, &self.total) {
+                (Some(permit), _) => Some(permit),
+                (None, Some(queue)) => Some(queue.acquire(priority).await),
+                (None, None) => None,
+            };
+            Permit {
+                _host: host,
+                _total: total,
+            }
+        };
+        match self.queue_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, acquire)
+                .await
+                .map_err(|_| crate::error::request(TimedOut(TimeoutPhase::Queue))),
+            None => Ok(acquire.await),
+        }
+    }
+}
+
+// ===== impl Readiness =====
+
+/// The readiness of a client used as a [`Service`]: the slot among the
+/// requests in flight that `poll_ready` reserved for the next `call`.
+///
+/// A clone of a client is a service of its own, so it starts without a
+/// slot.
+#[derive(Default)]
+pub(crate) struct Readiness {
+    waiting: Option<oneshot::Receiver<QueuePermit>>,
+    timeout: Option<Pin<Box<Sleep>>>,
+    permit: Option<InFlightPermit>,
+}
+
+impl Readiness {
+    /// Wait for a slot among the requests in flight in total, as
+    /// [`Limits::ready`] does.
+    pub(crate) fn poll_ready(
+        &mut self,
+        limits: Option<&Limits>,
+        cx: &mut Context<'_>,
+    ) -> Poll<crate::Result<()>> {
+        if self.permit.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        let (queue, queue_timeout) = match limits {
+            Some(Limits {
+                total: Some(ref queue),
+                queue_timeout,
+                ..
+            }) => (queue, *queue_timeout),
+            _ => return Poll::Ready(Ok(())),
+        };
+        let waiting = match self.waiting {
+            Some(ref mut waiting) => waiting,
+            None => match queue.enqueue(QueuePriority::Normal) {
+                Ok(permit) => {
+                    self.permit = Some(InFlightPermit {
+                        total: Some(permit),
+                    });
+                    return Poll::Ready(Ok(()));
+                }
+                Err(waiting) => {
+                    self.timeout = queue_timeout.map(tokio::time::sleep).map(Box::pin);
+                    self.waiting.insert(waiting)
+                }
+            },
+        };
+        if let Poll::Ready(permit) = Pin::new(waiting).poll(cx) {
+            // the queue outlives its waiting requests
+            let permit = permit.expect("queue is never dropped with waiters");
+            self.waiting = None;
+            self.timeout = None;
+            self.permit = Some(InFlightPermit {
+                total: Some(permit),
+            });
+            return Poll::Ready(Ok(()));
+        }
+        if let Some(ref mut timeout) = self.timeout {
+            if timeout.as_mut().poll(cx).is_ready() {
+                // leaving the line, a slot sent meanwhile goes to the next
+                self.waiting = None;
+                self.timeout = None;
+                return Poll::Ready(Err(crate::error::request(TimedOut(TimeoutPhase::Queue))));
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Take the slot reserved by `poll_ready`, if any.
+    pub(crate) fn take(&mut self) -> Option<InFlightPermit> {
+        self.permit.take()
+    }
+}
+
+impl Clone for Readiness {
+    fn clone(&self) -> Readiness {
+        Readiness::default()
+    }
+}
+
+// ===== impl Queue =====
+
+/// A limit on the number of requests in flight, which lets the waiting
+/// requests through by priority.
+struct Queue {
+    state: Mutex<QueueState>,
+}
+
+struct QueueState {
+    available: usize,
+    // by `QueuePriority`, from the highest
+    waiting: [VecDeque<oneshot::Sender<QueuePermit>>; 3],
+}
+
+struct QueuePermit {
+    queue: Option<Arc<Queue>>,
+}
+
+impl Queue {
+    fn new(max: usize) -> Arc<Queue> {
+        Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                available: max,
+                waiting: Default::default(),
+            }),
+        })
+    }
+
+    fn try_acquire(self: &Arc<Self>) -> Option<QueuePermit> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        // free slots are handed to the waiting requests first, so there are
+        // none while requests wait
+        if state.available == 0 {
+            return None;
+        }
+        state.available -= 1;
+        Some(QueuePermit {
+            queue: Some(self.clone()),
+        })
+    }
+
+    async fn acquire(self: &Arc<Self>, priority: QueuePriority) -> QueuePermit {
+        match self.enqueue(priority) {
+            Ok(permit) => permit,
+            // the queue outlives its waiting requests
+            Err(waiting) => waiting.await.expect("queue is never dropped with waiters"),
+        }
+    }
+
+    /// Take a free slot, or wait in line for one.
+    fn enqueue(
+        self: &Arc<Self>,
+        priority: QueuePriority,
+    ) -> Result<QueuePermit, oneshot::Receiver<QueuePermit>> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        if state.available > 0 {
+            state.available -= 1;
+            return Ok(QueuePermit {
+                queue: Some(self.clone()),
+            });
+        }
+        let (tx, rx) = oneshot::channel();
+        let waiting = &mut state.waiting[priority as usize];
+        waiting.retain(|tx| !tx.is_closed());
+        waiting.push_back(tx);
+        Err(rx)
+    }
+
+    fn release(self: Arc<Self>) {
+        loop {
+            let next = {
+                let mut state = self.state.lock().expect("lock poisoned");
+                match state.waiting.iter_mut().find_map(VecDeque::pop_front) {
+                    Some(next) => next,
+                    None => {
+                        state.available += 1;
+                        return;
+                    }
+                }
+            };
+            let permit = QueuePermit {
+                queue: Some(self.clone()),
+            };
+            match next.send(permit) {
+                Ok(()) => return,
+                // the request stopped waiting, try the next one
+                Err(mut permit) => {
+                    permit.queue = None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.release();
+        }
+    }
+}
+
+// ===== impl InFlightService =====
+
+/// A [`Service`] that waits for the number of requests in flight to the
+/// host of each request to be under the limit, before passing it on.
+#[derive(Clone)]
+pub(crate) struct InFlightService<S> {
+    inner: S,
+    limits: Option<Arc<Limits>>,
+}
+
+impl<S> InFlightService<S> {
+    pub(crate) fn new(inner: S, limits: Option<Arc<Limits>>) -> Self {
+        InFlightService { inner, limits }
+    }
+}
+
+impl<S, ResBody> Service<Request<Body>> for InFlightService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: From<crate::Error>,
+    ResBody: HttpBody + Send + 'static,
+{
+    type Response = Response<InFlightBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + Sync>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let Some(limits) = self.limits.clone() else {
+            let fut = inner.call(req);
+            return Box::pin(sync_wrapper::SyncFuture::new(async move {
+                Ok(fut.await?.map(|body| InFlightBody::new(body, None)))
+            }));
+        };
+        Box::pin(sync_wrapper::SyncFuture::new(async move {
+            let permit = limits.acquire(&req).await?;
+            let res = inner.call(req).await?;
+            Ok(res.map(|body| InFlightBody::new(body, Some(permit))))
+        }))
+    }
+}
+
+// ===== impl InFlightBody =====
+
+pin_project! {
+    /// A response body keeping its request in flight until it ends.
+    pub(crate) struct InFlightBody<B> {
+        #[pin]
+        inner: B,
+        permit: Option<Permit>,
+    }
+}
+
+impl<B: HttpBody> InFlightBody<B> {
+    fn new(inner: B, permit: Option<Permit>) -> Self {
+        let permit = permit.filter(|_| !inner.is_end_stream());
+        InFlightBody { inner, permit }
+    }
+}
+
+impl<B: HttpBody> HttpBody for InFlightBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let frame = ready!(this.inner.as_mut().poll_frame(cx));
+        if !matches!(frame, Some(Ok(_))) || this.inner.is_end_stream() {
+            this.permit.take();
+        }
+        Poll::Ready(frame)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
@@ -0,0 +1,119 @@
+//! Interim `1xx` responses.
+//!
+//! Before the final response of a request, a server can send any number of
+//! informational responses, such as `100 Continue` or `103 Early Hints`.
+//! They are kept with the final response, and
+//! [`Response::informational`](crate::Response::informational) returns them
+//! in the order they were received:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! let res = reqwest::get("https://example.com/").await?;
+//! for interim in res.informational() {
+//!     println!("{} before {}", interim.status(), res.status());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! To see them as they arrive, while the request is still in flight, set a
+//! function with
+//! [`RequestBuilder::on_informational`](crate::RequestBuilder::on_informational).
+//! When a request is redirected, the interim responses of each hop are kept,
+//! and passed to the function.
+//!
+//! Interim responses are only read on HTTP/1 connections; the responses of
+//! requests sent with HTTP/2 or HTTP/3 have none.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use http::{HeaderMap, StatusCode, Version};
+
+/// An interim `1xx` response.
+#[derive(Clone, Debug)]
+pub struct Informational {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+}
+
+impl Informational {
+    /// Get the status of the interim response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Get the HTTP version of the interim response.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Get the headers of the interim response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+/// The informational callback of a request.
+#[derive(Clone)]
+pub(crate) struct Callback(Arc<dyn Fn(&Informational) + Send + Sync>);
+
+impl Callback {
+    pub(crate) fn new<F>(f: F) -> Callback
+    where
+        F: Fn(&Informational) + Send + Sync + 'static,
+    {
+        Callback(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// The interim responses of a request, kept in the extensions of its final
+/// response.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Interim(pub(crate) Vec<Informational>);
+
+/// Where the connection puts the interim responses of a request.
+#[derive(Clone, Default)]
+pub(crate) struct Received(Arc<Mutex<Vec<Informational>>>);
+
+impl Received {
+    /// Have the connection keep the interim responses of a request, and pass
+    /// them to the callbacks.
+    pub(crate) fn register<B>(
+        req: &mut http::Request<B>,
+        callback: Option<Callback>,
+        early_hints: Option<crate::early_hints::Callback>,
+    ) -> Received {
+        let received = Received::default();
+        let kept = received.0.clone();
+        hyper::ext::on_informational(req, move |res| {
+            let interim = Informational {
+                status: res.status(),
+                version: res.version(),
+                headers: res.headers().clone(),
+            };
+            if let Some(ref callback) = callback {
+                (callback.0)(&interim);
+            }
+            if let Some(ref early_hints) = early_hints {
+                if interim.status == StatusCode::EARLY_HINTS {
+                    early_hints.call(&interim.headers);
+                }
+            }
+            kept.lock().unwrap().push(interim);
+        });
+        received
+    }
+
+    /// Take the interim responses received so far.
+    pub(crate) fn take(&self) -> Interim {
+        Interim(std::mem::take(&mut *self.0.lock().unwrap()))
+    }
+}
@@ -208,11 +208,32 @@
 //! - **query**: Provides query parameter serialization.
 //! - **form**: Provides form data serialization.
 //! - **json**: Provides serialization and deserialization for JSON bodies.
+//! - **simd-json**: Deserializes JSON response bodies with SIMD instructions.
+//! - **msgpack**: Provides serialization and deserialization for MessagePack bodies.
+//! - **cbor**: Provides serialization and deserialization for CBOR bodies.
+//! - **xml**: Provides serialization and deserialization for XML bodies.
+//! - **protobuf**: Provides encoding and decoding of Protocol Buffers bodies.
+//! - **graphql**: Sends GraphQL queries and decodes their responses.
 //! - **multipart**: Provides functionality for multipart forms.
-//! - **stream**: Adds support for `futures::Stream`.
+//! - **content-digest**: Computes and verifies `Content-Digest` fields.
+//! - **message-signatures**: Signs requests with HTTP Message Signatures.
+//! - **stream**: Adds support for `futures::Stream`, and following paginated
+//!   responses.
 //! - **socks**: Provides SOCKS5 proxy support.
+//! - **tracing**: Propagates W3C trace context and records responses on
+//!   `tracing` spans.
+//! - **har**: Loads HTTP Archive files and replays their requests.
+//! - **vcr**: Records responses to files and replays them in tests.
+//! - **test-util**: Provides a mock transport for unit tests.
+//! - **cache**: Provides an HTTP cache, with a store that keeps responses on disk.
+//! - **download**: Downloads resources to files, resuming interrupted downloads.
+//! - **upload**: Uploads large bodies in chunks, resuming interrupted uploads.
+//! - **file**: Answers requests for `file://` URLs with local files.
+//! - **client-config**: Deserializes client settings from configuration files.
+//! - **hsts-preload**: Upgrades requests to domains on an HSTS preload list to HTTPS.
 //! - **hickory-dns**: Enables a hickory-dns async resolver instead of default
 //!   threadpool using `getaddrinfo`.
+//! - **interface-index**: Binds connections to a network interface by its index.
 //! - **system-proxy** *(enabled by default)*: Use Windows and macOS system
 //!   proxy settings automatically.
 //!
@@ -276,6 +297,47 @@ macro_rules! if_hyper {
     )*}
 }
 
+// The targets which can bind a socket to a network interface by its index,
+// with the `interface-index` feature. Takes items, or a block of statements.
+macro_rules! cfg_interface_index {
+    ($body:block) => {
+        #[cfg(all(
+            feature = "interface-index",
+            any(
+                target_os = "android",
+                target_os = "illumos",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "solaris",
+                target_os = "tvos",
+                target_os = "visionos",
+                target_os = "watchos",
+                target_os = "windows",
+            )
+        ))]
+        $body
+    };
+    ($($item:item)*) => {$(
+        #[cfg(all(
+            feature = "interface-index",
+            any(
+                target_os = "android",
+                target_os = "illumos",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "solaris",
+                target_os = "tvos",
+                target_os = "visionos",
+                target_os = "watchos",
+                target_os = "windows",
+            )
+        ))]
+        $item
+    )*};
+}
+
 pub use http::header;
 pub use http::Method;
 pub use http::{StatusCode, Version};
@@ -289,9 +351,11 @@ if_hyper! {
     mod config;
 }
 mod into_url;
+pub mod redact;
 mod response;
+pub mod uri_template;
 
-pub use self::error::{Error, Result};
+pub use self::error::{Error, ErrorCode, Result, TimeoutPhase};
 pub use self::into_url::IntoUrl;
 pub use self::response::ResponseBuilderExt;
 
@@ -361,6 +425,7 @@ if_hyper! {
     pub use self::async_impl::{
         Body, Client, ClientBuilder, Request, RequestBuilder, Response, Upgraded,
     };
+    pub use self::in_flight::{InFlightPermit, QueuePriority};
     pub use self::proxy::{Proxy,NoProxy};
     #[cfg(feature = "__tls")]
     // Re-exports, to be removed in a future release
@@ -370,18 +435,55 @@ if_hyper! {
 
 
     mod async_impl;
+    pub mod auth;
     #[cfg(feature = "blocking")]
     pub mod blocking;
+    #[cfg(feature = "cache")]
+    pub mod cache;
+    pub mod circuit;
+    #[cfg(feature = "client-config")]
+    pub mod client_config;
     mod connect;
+    pub mod deadline;
+    #[cfg(feature = "content-digest")]
+    mod content_digest;
     #[cfg(feature = "cookies")]
     pub mod cookie;
+    pub mod cors;
     pub mod dns;
+    #[cfg(feature = "download")]
+    pub mod download;
+    pub mod early_hints;
+    #[cfg(feature = "graphql")]
+    pub mod graphql;
+    #[cfg(feature = "har")]
+    pub mod har;
+    pub mod host;
+    #[cfg(feature = "hsts-preload")]
+    pub mod hsts;
+    mod in_flight;
+    pub mod informational;
+    pub mod observe;
+    #[cfg(feature = "stream")]
+    pub mod paginate;
     mod proxy;
+    pub mod proxy_protocol;
     pub mod redirect;
     pub mod retry;
+    #[cfg(feature = "message-signatures")]
+    pub mod signature;
+    mod tcp;
+    #[cfg(feature = "test-util")]
+    pub mod test;
     #[cfg(feature = "__tls")]
     pub mod tls;
+    #[cfg(feature = "tracing")]
+    pub mod trace;
+    #[cfg(feature = "upload")]
+    pub mod upload;
     mod util;
+    #[cfg(feature = "vcr")]
+    pub mod vcr;
 
     #[cfg(docsrs)]
     pub use connect::uds::UnixSocketProvider;
@@ -0,0 +1,1422 @@
+//! Observing the connections and requests of a `Client`.
+//!
+//! Implement [`ConnectionObserver`] and pass it to
+//! [`ClientBuilder::connection_events`](crate::ClientBuilder::connection_events)
+//! to receive events as connections are opened and closed.
+//!
+//! Every `Response` also carries the [`RequestTimings`] of its request in
+//! its extensions.
+//!
+//! For protocol debugging, a [`WireObserver`] passed to
+//! [`ClientBuilder::wire_events`](crate::ClientBuilder::wire_events)
+//! receives the head of every request and response, and the size of the
+//! body data sent and received, much like `curl --verbose`.
+//!
+//! For audit logs, an [`AuditObserver`] passed to
+//! [`ClientBuilder::audit`](crate::ClientBuilder::audit) is shown each
+//! completed [`Exchange`], once its response has been read or dropped, or
+//! its request has failed.
+//!
+//! For failure telemetry, a callback passed to
+//! [`ClientBuilder::on_error`](crate::ClientBuilder::on_error) is shown
+//! each [`FailedRequest`].
+//!
+//! A `Client` also counts the requests it sends and the bytes it transfers,
+//! in [`TransferStats`] returned by
+//! [`Client::transfer_stats`](crate::Client::transfer_stats).
+//!
+//! ```rust
+//! use reqwest::observe::{ConnectionInfo, ConnectionObserver};
+//!
+//! struct Log;
+//!
+//! impl ConnectionObserver for Log {
+//!     fn connection_established(&self, conn: &ConnectionInfo) {
+//!         println!("connected to {:?} in {:?}", conn.remote_addr(), conn.connect_duration());
+//!     }
+//! }
+//!
+//! let client = reqwest::Client::builder()
+//!     .connection_events(Log)
+//!     .build();
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+
+use http::{HeaderMap, Method, StatusCode, Uri, Version};
+use hyper_util::client::legacy::connect::CaptureConnection;
+use hyper_util::rt::TokioIo;
+use pin_project_lite::pin_project;
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+use crate::async_impl::body::{boxed, ResponseBody};
+use crate::dns::{Addrs, Name, Resolve, Resolving};
+use crate::error::{BoxError, TimeoutPhase};
+use crate::redact::{self, Redaction};
+use crate::{Body, Url};
+
+/// Receives events about the connections opened by a `Client`.
+///
+/// All methods do nothing by default, so an observer only implements the
+/// events it needs. They are called while connecting, so they should return
+/// quickly.
+///
+/// DNS, TCP and TLS events are emitted for connections made directly to a
+/// server. Connections through a proxy, or over a Unix socket, only emit the
+/// established and closed events.
+pub trait ConnectionObserver: Send + Sync + 'static {
+    /// Called when a DNS lookup starts.
+    fn dns_start(&self, _host: &str) {}
+
+    /// Called when a DNS lookup finishes, successfully or not.
+    fn dns_end(&self, _event: &DnsEnd<'_>) {}
+
+    /// Called when a TCP connection has been established.
+    fn tcp_connected(&self, _event: &TcpConnected) {}
+
+    /// Called when a TLS handshake has completed.
+    fn tls_handshake(&self, _event: &TlsHandshake<'_>) {}
+
+    /// Called when a connection is ready to send requests.
+    fn connection_established(&self, _conn: &ConnectionInfo) {}
+
+    /// Called when a connection has been closed, with how long it was open.
+    fn connection_closed(&self, _conn: &ConnectionInfo, _lifetime: Duration) {}
+}
+
+/// Receives the messages exchanged by a `Client`, as they are sent.
+///
+/// Each message sent to the connection, including each redirect and retry,
+/// gets its own `id`, unique within the process.
+///
+/// Heads are shown in HTTP/1 form, whatever the version of the connection.
+/// The `host` header is included, but other headers added by the
+/// connection itself, such as `content-length` and `transfer-encoding`, are
+/// not. Redacted headers and query values, as set with
+/// [`ClientBuilder::redaction`](crate::ClientBuilder::redaction), are
+/// replaced with `REDACTED`.
+///
+/// All methods do nothing by default.
+pub trait WireObserver: Send + Sync + 'static {
+    /// Called with the head of a request, before it is sent.
+    fn request_head(&self, _id: u64, _head: &[u8]) {}
+
+    /// Called with the size of each chunk of a request body, as it is sent.
+    fn request_body(&self, _id: u64, _len: usize) {}
+
+    /// Called with the head of the response to a request.
+    fn response_head(&self, _id: u64, _head: &[u8]) {}
+
+    /// Called with the size of each chunk of a response body, as it is read.
+    fn response_body(&self, _id: u64, _len: usize) {}
+}
+
+/// Is shown each request made by a `Client`, once it has completed.
+///
+/// The observer cannot change the request or its response. It is called on
+/// the task reading the response, so it should return quickly.
+pub trait AuditObserver: Send + Sync + 'static {
+    /// Called once for each request, when its response has been read to the
+    /// end, has failed, or was dropped, or when the request has failed.
+    fn exchange(&self, exchange: &Exchange<'_>);
+}
+
+/// A completed request, and its response if there was one.
+///
+/// When following redirects, this is the last request.
+pub struct Exchange<'a> {
+    method: &'a Method,
+    url: &'a Url,
+    request_headers: &'a HeaderMap,
+    request_body_size: Option<u64>,
+    response: Option<(StatusCode, &'a HeaderMap)>,
+    response_body_size: u64,
+    complete: bool,
+    duration: Duration,
+    error: Option<&'a (dyn StdError + 'static)>,
+    redaction: Option<&'a Redaction>,
+}
+
+impl Exchange<'_> {
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// The URL of the request.
+    pub fn url(&self) -> &Url {
+        self.url
+    }
+
+    /// The headers of the request, including the default headers of the
+    /// `Client`.
+    pub fn request_headers(&self) -> &HeaderMap {
+        self.request_headers
+    }
+
+    /// The size of the request body, if it was known before sending it.
+    pub fn request_body_size(&self) -> Option<u64> {
+        self.request_body_size
+    }
+
+    /// The status of the response, or `None` if the request failed.
+    pub fn status(&self) -> Option<StatusCode> {
+        self.response.map(|(status, _)| status)
+    }
+
+    /// The headers of the response, or `None` if the request failed.
+    pub fn response_headers(&self) -> Option<&HeaderMap> {
+        self.response.map(|(_, headers)| headers)
+    }
+
+    /// How many bytes of the response body were read, after any
+    /// decompression.
+    pub fn response_body_size(&self) -> u64 {
+        self.response_body_size
+    }
+
+    /// Whether the response body was read to the end.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// How long the exchange took, from sending the request to completing
+    /// it.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The error that ended the exchange, if any.
+    ///
+    /// This is usually a [`reqwest::Error`](crate::Error), which it can be
+    /// downcast to.
+    pub fn error(&self) -> Option<&(dyn StdError + 'static)> {
+        self.error
+    }
+}
+
+impl fmt::Debug for Exchange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut f = f.debug_struct("Exchange");
+        f.field("method", self.method)
+            .field("url", &redact::url(self.redaction, self.url).as_str())
+            .field(
+                "request_headers",
+                &redact::headers(self.redaction, self.request_headers),
+            )
+            .field("request_body_size", &self.request_body_size);
+        if let Some((status, headers)) = self.response {
+            f.field("status", &status)
+                .field("response_headers", &redact::headers(self.redaction, headers))
+                .field("response_body_size", &self.response_body_size)
+                .field("complete", &self.complete);
+        }
+        if let Some(error) = self.error {
+            f.field("error", &error);
+        }
+        f.field("duration", &self.duration).finish()
+    }
+}
+
+/// A request which failed, without a response.
+///
+/// Shown to the callback of
+/// [`ClientBuilder::on_error`](crate::ClientBuilder::on_error). When
+/// following redirects, this is the last request.
+pub struct FailedRequest<'a> {
+    method: &'a Method,
+    url: &'a Url,
+    error: &'a crate::Error,
+    attempts: u32,
+    duration: Duration,
+}
+
+impl FailedRequest<'_> {
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// The URL of the request, redacted as set with
+    /// [`ClientBuilder::redaction`](crate::ClientBuilder::redaction).
+    pub fn url(&self) -> &Url {
+        self.url
+    }
+
+    /// The error of the request.
+    pub fn error(&self) -> &crate::Error {
+        self.error
+    }
+
+    /// The code of the error.
+    pub fn code(&self) -> crate::ErrorCode {
+        self.error.code()
+    }
+
+    /// How many times the request was sent, counting its retries.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// How long the request took, from sending it to failing.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl fmt::Debug for FailedRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FailedRequest")
+            .field("method", self.method)
+            .field("url", &self.url.as_str())
+            .field("code", &self.code())
+            .field("attempts", &self.attempts)
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+/// Counts of what a `Client` has transferred since it was built.
+///
+/// Requests are counted each time they are sent, so a redirect or a retry
+/// counts as another request, and its response is counted too.
+///
+/// Bytes are counted as they are written to and read from connections, so
+/// they include the heads of messages, and compressed bodies before they
+/// are decompressed. They do not include the bytes of TLS records, or of
+/// HTTP/3 connections, nor anything sent with a custom
+/// [`http_service`](crate::ClientBuilder::http_service).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    requests: u64,
+    responses: [u64; 5],
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl TransferStats {
+    /// How many requests were sent.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// How many responses were received.
+    pub fn responses(&self) -> u64 {
+        self.responses.iter().sum()
+    }
+
+    /// How many responses had a 1xx status.
+    pub fn informational_responses(&self) -> u64 {
+        self.responses[0]
+    }
+
+    /// How many responses had a 2xx status.
+    pub fn success_responses(&self) -> u64 {
+        self.responses[1]
+    }
+
+    /// How many responses had a 3xx status.
+    pub fn redirection_responses(&self) -> u64 {
+        self.responses[2]
+    }
+
+    /// How many responses had a 4xx status.
+    pub fn client_error_responses(&self) -> u64 {
+        self.responses[3]
+    }
+
+    /// How many responses had a 5xx status.
+    pub fn server_error_responses(&self) -> u64 {
+        self.responses[4]
+    }
+
+    /// How many bytes were written to connections.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// How many bytes were read from connections.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+}
+
+/// The result of a DNS lookup.
+#[derive(Debug)]
+pub struct DnsEnd<'a> {
+    host: &'a str,
+    addrs: &'a [SocketAddr],
+    duration: Duration,
+    failed: bool,
+}
+
+impl DnsEnd<'_> {
+    /// The host that was looked up.
+    pub fn host(&self) -> &str {
+        self.host
+    }
+
+    /// The addresses found, empty if the lookup failed.
+    pub fn addrs(&self) -> &[SocketAddr] {
+        self.addrs
+    }
+
+    /// How long the lookup took.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Whether the lookup failed.
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+}
+
+/// An established TCP connection.
+#[derive(Debug)]
+pub struct TcpConnected {
+    remote_addr: Option<SocketAddr>,
+    local_addr: Option<SocketAddr>,
+    duration: Duration,
+}
+
+impl TcpConnected {
+    /// The address of the server.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// The local address of the connection.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// How long connecting took, not including any DNS lookup.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// A completed TLS handshake.
+#[derive(Debug)]
+pub struct TlsHandshake<'a> {
+    host: &'a str,
+    alpn: Option<&'a [u8]>,
+    duration: Duration,
+}
+
+impl TlsHandshake<'_> {
+    /// The server name the handshake was made with.
+    pub fn host(&self) -> &str {
+        self.host
+    }
+
+    /// The protocol negotiated with ALPN, if any.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn
+    }
+
+    /// How long the handshake took.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// Information about a connection.
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo {
+    id: u64,
+    host: String,
+    remote_addr: Option<SocketAddr>,
+    local_addr: Option<SocketAddr>,
+    tls: bool,
+    h2: bool,
+    proxied: bool,
+    connect_duration: Duration,
+}
+
+impl ConnectionInfo {
+    /// An identifier for the connection, unique within the process.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The host the connection was made for.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The address of the server, or of the proxy.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// The local address of the connection.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Whether the connection to the host uses TLS.
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+
+    /// Whether HTTP/2 was negotiated with ALPN.
+    pub fn is_negotiated_h2(&self) -> bool {
+        self.h2
+    }
+
+    /// Whether the connection goes through an HTTP proxy.
+    pub fn is_proxied(&self) -> bool {
+        self.proxied
+    }
+
+    /// How long it took to open the connection, including any DNS lookup
+    /// and TLS handshake.
+    pub fn connect_duration(&self) -> Duration {
+        self.connect_duration
+    }
+}
+
+/// The connection a request was sent on, given by
+/// [`Error::connection`](crate::Error::connection) when the request failed
+/// after it got a connection.
+///
+/// ```rust
+/// # async fn run(client: reqwest::Client) {
+/// if let Err(err) = client.get("https://example.com/").send().await {
+///     if let Some(conn) = err.connection() {
+///         eprintln!(
+///             "failed on {:?} (reused: {}): {err}",
+///             conn.remote_addr(),
+///             conn.is_reused(),
+///         );
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RequestConnection {
+    remote_addr: Option<SocketAddr>,
+    local_addr: Option<SocketAddr>,
+    h2: bool,
+    proxied: bool,
+    reused: bool,
+}
+
+impl RequestConnection {
+    /// The address of the server, or of the proxy.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// The local address of the connection.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// The HTTP version of the connection: HTTP/2 if it was negotiated with
+    /// ALPN, HTTP/1.1 otherwise.
+    pub fn version(&self) -> Version {
+        if self.h2 {
+            Version::HTTP_2
+        } else {
+            Version::HTTP_11
+        }
+    }
+
+    /// Whether HTTP/2 was negotiated with ALPN.
+    pub fn is_negotiated_h2(&self) -> bool {
+        self.h2
+    }
+
+    /// Whether the connection goes through an HTTP proxy.
+    pub fn is_proxied(&self) -> bool {
+        self.proxied
+    }
+
+    /// Whether the connection was opened before the request was sent, for
+    /// an earlier request, rather than for this one.
+    pub fn is_reused(&self) -> bool {
+        self.reused
+    }
+
+    pub(crate) fn new(
+        connected: &hyper_util::client::legacy::connect::Connected,
+        start: Instant,
+    ) -> RequestConnection {
+        use hyper_util::client::legacy::connect::HttpInfo;
+
+        let mut extras = http::Extensions::new();
+        connected.get_extras(&mut extras);
+        let http_info = extras.get::<HttpInfo>();
+        let opened = extras.get::<ConnectTimings>().map(|t| t.steps.start);
+        RequestConnection {
+            remote_addr: http_info.map(|info| info.remote_addr()),
+            local_addr: http_info.map(|info| info.local_addr()),
+            h2: connected.is_negotiated_h2(),
+            proxied: connected.is_proxied(),
+            reused: opened.is_some_and(|opened| opened < start),
+        }
+    }
+}
+
+/// When each step of a request happened.
+///
+/// Every `Response` carries a `RequestTimings` in its
+/// [extensions](crate::Response::extensions).
+///
+/// The connection steps are only known when a new connection was opened for
+/// the request, and writing the request is only tracked on HTTP/1
+/// connections. When following redirects, the steps are those of the last
+/// request.
+///
+/// ```rust
+/// # async fn run() -> Result<(), reqwest::Error> {
+/// use reqwest::observe::RequestTimings;
+///
+/// let res = reqwest::get("https://hyper.rs").await?;
+/// let timings = res.extensions().get::<RequestTimings>().cloned().unwrap();
+/// res.bytes().await?;
+///
+/// println!("headers after {:?}", timings.first_byte() - timings.start());
+/// if let Some(end) = timings.body_complete() {
+///     println!("body after {:?}", end - timings.start());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RequestTimings {
+    start: Instant,
+    queue_end: Option<Instant>,
+    dns: Option<(Instant, Instant)>,
+    connect: Option<(Instant, Instant)>,
+    tls: Option<(Instant, Instant)>,
+    request_written: Option<Instant>,
+    first_byte: Instant,
+    body_complete: Arc<OnceLock<Instant>>,
+}
+
+impl RequestTimings {
+    /// When the request was sent to the `Client`.
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// When the request stopped waiting for a connection, either because a
+    /// new connection started being opened for it, or because it started
+    /// being written to an idle HTTP/1 connection.
+    pub fn queue_end(&self) -> Option<Instant> {
+        self.queue_end
+    }
+
+    /// When the DNS lookup started.
+    pub fn dns_start(&self) -> Option<Instant> {
+        self.dns.map(|(start, _)| start)
+    }
+
+    /// When the DNS lookup finished.
+    pub fn dns_end(&self) -> Option<Instant> {
+        self.dns.map(|(_, end)| end)
+    }
+
+    /// When the TCP connection started being made.
+    pub fn connect_start(&self) -> Option<Instant> {
+        self.connect.map(|(start, _)| start)
+    }
+
+    /// When the TCP connection was established.
+    pub fn connect_end(&self) -> Option<Instant> {
+        self.connect.map(|(_, end)| end)
+    }
+
+    /// When the TLS handshake started.
+    pub fn tls_start(&self) -> Option<Instant> {
+        self.tls.map(|(start, _)| start)
+    }
+
+    /// When the TLS handshake completed.
+    pub fn tls_end(&self) -> Option<Instant> {
+        self.tls.map(|(_, end)| end)
+    }
+
+    /// When the request was written to the connection.
+    pub fn request_written(&self) -> Option<Instant> {
+        self.request_written
+    }
+
+    /// When the head of the response was received.
+    pub fn first_byte(&self) -> Instant {
+        self.first_byte
+    }
+
+    /// When the body of the response was read to the end, or `None` if it
+    /// has not been yet.
+    pub fn body_complete(&self) -> Option<Instant> {
+        self.body_complete.get().copied()
+    }
+
+    pub(crate) fn new(
+        start: Instant,
+        conn: Option<ConnectTimings>,
+        version: Version,
+        body_complete: Arc<OnceLock<Instant>>,
+    ) -> RequestTimings {
+        let mut timings = RequestTimings {
+            start,
+            queue_end: None,
+            dns: None,
+            connect: None,
+            tls: None,
+            request_written: None,
+            first_byte: Instant::now(),
+            body_complete,
+        };
+        let Some(conn) = conn else {
+            return timings;
+        };
+
+        let steps = conn.steps;
+        let io = *conn.io.lock().unwrap();
+        let http1 = version <= Version::HTTP_11;
+        if steps.start >= start {
+            // the connection was opened for this request
+            timings.queue_end = Some(steps.start);
+            timings.dns = steps.dns_start.zip(steps.dns_end);
+            let connect_start = steps.dns_end.unwrap_or(steps.start);
+            timings.connect = steps.tcp_end.map(|end| (connect_start, end));
+            timings.tls = steps.tcp_end.zip(steps.tls_end);
+        } else if http1 {
+            timings.queue_end = io.write_start.filter(|&at| at >= start);
+        }
+        if http1 {
+            timings.request_written = io.flushed.filter(|&at| at >= start);
+        }
+        timings
+    }
+}
+
+// ===== internal =====
+
+pub(crate) type Observer = Arc<dyn ConnectionObserver>;
+
+pub(crate) type Wire = Arc<dyn WireObserver>;
+
+pub(crate) type Audit = Arc<dyn AuditObserver>;
+
+pub(crate) type OnError = Arc<dyn Fn(&FailedRequest<'_>) + Send + Sync>;
+
+/// Show a failed request to the callback of a `Client`.
+pub(crate) fn failed(
+    on_error: &OnError,
+    method: &Method,
+    url: &Url,
+    error: &crate::Error,
+    redaction: Option<&Redaction>,
+    slot: &ConnectionSlot,
+    start: Instant,
+) {
+    let url = error.url().unwrap_or(url);
+    on_error(&FailedRequest {
+        method,
+        url: &redact::url(redaction, url),
+        error,
+        attempts: slot.attempts(),
+        duration: start.elapsed(),
+    });
+}
+
+/// The live counts behind [`TransferStats`].
+#[derive(Default)]
+pub(crate) struct Counters {
+    requests: AtomicU64,
+    responses: [AtomicU64; 5],
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Counters {
+    fn request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn response(&self, status: StatusCode) {
+        // `StatusCode` is always between 100 and 999; 6xx and up are not counted.
+        if let Some(count) = self.responses.get(usize::from(status.as_u16() / 100 - 1)) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn sent(&self, n: usize) {
+        self.bytes_sent.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn received(&self, n: usize) {
+        self.bytes_received.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> TransferStats {
+        TransferStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            responses: std::array::from_fn(|i| self.responses[i].load(Ordering::Relaxed)),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+tokio::task_local! {
+    // The steps of the connection being made on this task.
+    static STEPS: Arc<Mutex<Steps>>;
+}
+
+/// When each step of making a connection finished.
+#[derive(Clone, Copy)]
+struct Steps {
+    start: Instant,
+    dns_start: Option<Instant>,
+    dns_end: Option<Instant>,
+    tcp_end: Option<Instant>,
+    tls_end: Option<Instant>,
+}
+
+fn with_steps<R>(f: impl FnOnce(&mut Steps) -> R) -> Option<R> {
+    STEPS.try_with(|steps| f(&mut steps.lock().unwrap())).ok()
+}
+
+/// The timings of a connection, attached to the responses it receives.
+#[derive(Clone)]
+pub(crate) struct ConnectTimings {
+    steps: Steps,
+    io: Arc<Mutex<IoTimes>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct IoTimes {
+    write_start: Option<Instant>,
+    flushed: Option<Instant>,
+}
+
+impl ConnectTimings {
+    pub(crate) fn write_started(&self) {
+        self.io.lock().unwrap().write_start = Some(Instant::now());
+    }
+
+    pub(crate) fn flushed(&self) {
+        self.io.lock().unwrap().flushed = Some(Instant::now());
+    }
+}
+
+fn next_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The connection of the last request sent for a `Request`, and how many
+/// times it was retried, kept across its redirects and retries.
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionSlot {
+    capture: Arc<Mutex<Option<CaptureConnection>>>,
+    retries: Arc<AtomicU32>,
+}
+
+impl ConnectionSlot {
+    pub(crate) fn retried(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How many times the request was sent.
+    pub(crate) fn attempts(&self) -> u32 {
+        1 + self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Capture the connection `req` will be sent on.
+    pub(crate) fn capture<B>(&self, req: &mut http::Request<B>) {
+        let capture = hyper_util::client::legacy::connect::capture_connection(req);
+        *self.capture.lock().unwrap() = Some(capture);
+    }
+
+    pub(crate) fn connection(&self, start: Instant) -> Option<RequestConnection> {
+        let capture = self.capture.lock().unwrap();
+        let metadata = capture.as_ref()?.connection_metadata();
+        metadata
+            .as_ref()
+            .map(|connected| RequestConnection::new(connected, start))
+    }
+}
+
+/// Run a connect future, tracking when each of its steps finished.
+pub(crate) async fn track<F: Future>(fut: F) -> (F::Output, ConnectTimings) {
+    let steps = Arc::new(Mutex::new(Steps {
+        start: Instant::now(),
+        dns_start: None,
+        dns_end: None,
+        tcp_end: None,
+        tls_end: None,
+    }));
+    let out = STEPS.scope(steps.clone(), fut).await;
+    let steps = *steps.lock().unwrap();
+    let timings = ConnectTimings {
+        steps,
+        io: Default::default(),
+    };
+    (out, timings)
+}
+
+/// The step of the connection being made on this task which hasn't
+/// finished yet, when its connect timeout expires.
+pub(crate) fn connect_phase(tls: bool) -> TimeoutPhase {
+    with_steps(|steps| {
+        if steps.dns_start.is_some() && steps.dns_end.is_none() {
+            TimeoutPhase::Dns
+        } else if tls && steps.tcp_end.is_some() {
+            TimeoutPhase::Tls
+        } else {
+            TimeoutPhase::Connect
+        }
+    })
+    .unwrap_or(TimeoutPhase::Connect)
+}
+
+pub(crate) fn connection_info(
+    dst: &Uri,
+    connected: &hyper_util::client::legacy::connect::Connected,
+    timings: &ConnectTimings,
+) -> ConnectionInfo {
+    use hyper_util::client::legacy::connect::HttpInfo;
+
+    let mut extras = http::Extensions::new();
+    connected.get_extras(&mut extras);
+    let http_info = extras.get::<HttpInfo>();
+    ConnectionInfo {
+        id: next_id(),
+        host: dst.host().unwrap_or_default().to_owned(),
+        remote_addr: http_info.map(|info| info.remote_addr()),
+        local_addr: http_info.map(|info| info.local_addr()),
+        tls: dst.scheme() == Some(&http::uri::Scheme::HTTPS),
+        h2: connected.is_negotiated_h2(),
+        proxied: connected.is_proxied(),
+        connect_duration: timings.steps.start.elapsed(),
+    }
+}
+
+/// Record a TLS handshake that just completed on this task.
+#[cfg(feature = "__tls")]
+pub(crate) fn tls_handshake(observer: Option<&Observer>, host: &str, alpn: Option<&[u8]>) {
+    let now = Instant::now();
+    let start = with_steps(|steps| {
+        steps.tls_end = Some(now);
+        steps.tcp_end
+    })
+    .flatten();
+    if let (Some(observer), Some(start)) = (observer, start) {
+        observer.tls_handshake(&TlsHandshake {
+            host,
+            alpn,
+            duration: now - start,
+        });
+    }
+}
+
+/// A resolver that records lookups, and reports them to an observer.
+pub(crate) struct ObservedResolve {
+    inner: Arc<dyn Resolve>,
+    observer: Option<Observer>,
+}
+
+impl ObservedResolve {
+    pub(crate) fn new(inner: Arc<dyn Resolve>, observer: Option<Observer>) -> Self {
+        ObservedResolve { inner, observer }
+    }
+}
+
+impl Resolve for ObservedResolve {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_owned();
+        let observer = self.observer.clone();
+        if let Some(ref observer) = observer {
+            observer.dns_start(&host);
+        }
+        let resolving = self.inner.resolve(name);
+        Box::pin(async move {
+            let start = Instant::now();
+            with_steps(|steps| steps.dns_start = Some(start));
+            let res = resolving.await;
+            let now = Instant::now();
+            with_steps(|steps| steps.dns_end = Some(now));
+            let Some(observer) = observer else {
+                return res;
+            };
+            match res {
+                Ok(addrs) => {
+                    let addrs = addrs.collect::<Vec<_>>();
+                    observer.dns_end(&DnsEnd {
+                        host: &host,
+                        addrs: &addrs,
+                        duration: now - start,
+                        failed: false,
+                    });
+                    Ok(Box::new(addrs.into_iter()) as Addrs)
+                }
+                Err(err) => {
+                    observer.dns_end(&DnsEnd {
+                        host: &host,
+                        addrs: &[],
+                        duration: now - start,
+                        failed: true,
+                    });
+                    Err(err)
+                }
+            }
+        })
+    }
+}
+
+/// A TCP connector that records established connections, and reports them
+/// to an observer.
+#[derive(Clone)]
+pub(crate) struct ObservedTcp<S> {
+    inner: S,
+    observer: Option<Observer>,
+}
+
+impl<S> ObservedTcp<S> {
+    pub(crate) fn new(inner: S, observer: Option<Observer>) -> Self {
+        ObservedTcp { inner, observer }
+    }
+}
+
+impl<S> Service<Uri> for ObservedTcp<S>
+where
+    S: Service<Uri, Response = TokioIo<TcpStream>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let start = Instant::now();
+        let connecting = self.inner.call(dst);
+        let observer = self.observer.clone();
+        Box::pin(async move {
+            let io = connecting.await.map_err(Into::into)?;
+            let now = Instant::now();
+            let dns_end = with_steps(|steps| {
+                steps.tcp_end = Some(now);
+                steps.dns_end
+            })
+            .flatten();
+            if let Some(observer) = observer {
+                let tcp = io.inner();
+                observer.tcp_connected(&TcpConnected {
+                    remote_addr: tcp.peer_addr().ok(),
+                    local_addr: tcp.local_addr().ok(),
+                    duration: now - dns_end.unwrap_or(start).max(start),
+                });
+            }
+            Ok(io)
+        })
+    }
+}
+
+pin_project! {
+    /// A response body that records when it was read to the end.
+    pub(crate) struct CompleteBody<B> {
+        #[pin]
+        inner: B,
+        done: Arc<OnceLock<Instant>>,
+    }
+}
+
+impl<B> CompleteBody<B> {
+    pub(crate) fn new(inner: B, done: Arc<OnceLock<Instant>>) -> Self {
+        CompleteBody { inner, done }
+    }
+}
+
+impl<B: hyper::body::Body> hyper::body::Body for CompleteBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let item = ready!(this.inner.poll_frame(cx));
+        if item.is_none() {
+            this.done.get_or_init(Instant::now);
+        }
+        Poll::Ready(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+/// A [`Service`] that counts each request and response, and shows them to
+/// a wire observer.
+#[derive(Clone)]
+pub(crate) struct WireService<S> {
+    inner: S,
+    counters: Arc<Counters>,
+    observer: Option<Wire>,
+    redaction: Option<Redaction>,
+}
+
+impl<S> WireService<S> {
+    pub(crate) fn new(
+        inner: S,
+        counters: Arc<Counters>,
+        observer: Option<Wire>,
+        redaction: Option<Redaction>,
+    ) -> Self {
+        WireService {
+            inner,
+            counters,
+            observer,
+            redaction,
+        }
+    }
+}
+
+impl<S> Service<http::Request<Body>> for WireService<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<ResponseBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<ResponseBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, S::Error>> + Send>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        self.counters.request();
+        let counters = self.counters.clone();
+        let Some(observer) = self.observer.clone() else {
+            let fut = self.inner.call(req);
+            return Box::pin(async move {
+                let res = fut.await?;
+                counters.response(res.status());
+                Ok(res)
+            });
+        };
+        let id = next_id();
+        let head = request_head(&req);
+        observer.request_head(id, &redact::wire(self.redaction.as_ref(), &head));
+
+        let req = req.map(|body| {
+            if hyper::body::Body::is_end_stream(&body) {
+                return body;
+            }
+            Body::wrap(WireBody {
+                inner: body,
+                observer: observer.clone(),
+                id,
+                request: true,
+            })
+        });
+        let redaction = self.redaction.clone();
+        let fut = self.inner.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            counters.response(res.status());
+            let head = response_head(&res);
+            observer.response_head(id, &redact::wire(redaction.as_ref(), &head));
+            Ok(res.map(|body| {
+                boxed(WireBody {
+                    inner: body,
+                    observer,
+                    id,
+                    request: false,
+                })
+            }))
+        })
+    }
+}
+
+fn request_head(req: &http::Request<Body>) -> Vec<u8> {
+    let target = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    let mut head = format!("{} {} {:?}\r\n", req.method(), target, req.version()).into_bytes();
+    if !req.headers().contains_key(http::header::HOST) {
+        if let Some(authority) = req.uri().authority() {
+            head.extend_from_slice(b"host: ");
+            head.extend_from_slice(authority.as_str().as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+    }
+    write_headers(&mut head, req.headers());
+    head
+}
+
+fn response_head(res: &http::Response<ResponseBody>) -> Vec<u8> {
+    let status = res.status();
+    let reason = res
+        .extensions()
+        .get::<hyper::ext::ReasonPhrase>()
+        .map(|reason| reason.as_bytes())
+        .or_else(|| status.canonical_reason().map(str::as_bytes))
+        .unwrap_or_default();
+    let mut head = format!("{:?} {} ", res.version(), status.as_str()).into_bytes();
+    head.extend_from_slice(reason);
+    head.extend_from_slice(b"\r\n");
+    write_headers(&mut head, res.headers());
+    head
+}
+
+fn write_headers(head: &mut Vec<u8>, headers: &http::HeaderMap) {
+    for (name, value) in headers {
+        head.extend_from_slice(name.as_str().as_bytes());
+        head.extend_from_slice(b": ");
+        head.extend_from_slice(value.as_bytes());
+        head.extend_from_slice(b"\r\n");
+    }
+    head.extend_from_slice(b"\r\n");
+}
+
+pin_project! {
+    /// A body that reports the size of its chunks to a wire observer.
+    struct WireBody<B> {
+        #[pin]
+        inner: B,
+        observer: Wire,
+        id: u64,
+        request: bool,
+    }
+}
+
+impl<B> hyper::body::Body for WireBody<B>
+where
+    B: hyper::body::Body<Data = bytes::Bytes>,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let item = ready!(this.inner.poll_frame(cx));
+        if let Some(Ok(ref frame)) = item {
+            if let Some(data) = frame.data_ref() {
+                if *this.request {
+                    this.observer.request_body(*this.id, data.len());
+                } else {
+                    this.observer.response_body(*this.id, data.len());
+                }
+            }
+        }
+        Poll::Ready(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+/// A request being audited, until its exchange completes.
+pub(crate) struct Audited {
+    observer: Audit,
+    method: Method,
+    request_headers: HeaderMap,
+    request_body_size: Option<u64>,
+    start: Instant,
+    redaction: Option<Redaction>,
+}
+
+impl Audited {
+    pub(crate) fn new(
+        observer: Audit,
+        method: Method,
+        request_headers: HeaderMap,
+        request_body_size: Option<u64>,
+        start: Instant,
+        redaction: Option<Redaction>,
+    ) -> Self {
+        Audited {
+            observer,
+            method,
+            request_headers,
+            request_body_size,
+            start,
+            redaction,
+        }
+    }
+
+    /// Report a request that failed before its response.
+    pub(crate) fn failed(&self, url: &Url, error: &crate::Error) {
+        self.report(url, None, 0, false, Some(error));
+    }
+
+    /// Wrap the body of a response, to report the exchange once it ends.
+    pub(crate) fn response<B>(self, url: Url, res: http::Response<B>) -> http::Response<AuditBody<B>>
+    where
+        B: hyper::body::Body,
+    {
+        let (parts, body) = res.into_parts();
+        let report = Report {
+            audited: self,
+            url,
+            status: parts.status,
+            headers: parts.headers.clone(),
+            read: 0,
+        };
+        // An empty body may never be polled, so it completes right away.
+        let report = if body.is_end_stream() {
+            report.send(true, None);
+            None
+        } else {
+            Some(report)
+        };
+        let body = AuditBody {
+            inner: body,
+            report,
+        };
+        http::Response::from_parts(parts, body)
+    }
+
+    fn report(
+        &self,
+        url: &Url,
+        response: Option<(StatusCode, &HeaderMap)>,
+        response_body_size: u64,
+        complete: bool,
+        error: Option<&(dyn StdError + 'static)>,
+    ) {
+        self.observer.exchange(&Exchange {
+            method: &self.method,
+            url,
+            request_headers: &self.request_headers,
+            request_body_size: self.request_body_size,
+            response,
+            response_body_size,
+            complete,
+            duration: self.start.elapsed(),
+            error,
+            redaction: self.redaction.as_ref(),
+        });
+    }
+}
+
+struct Report {
+    audited: Audited,
+    url: Url,
+    status: StatusCode,
+    headers: HeaderMap,
+    read: u64,
+}
+
+impl Report {
+    fn send(self, complete: bool, error: Option<&(dyn StdError + 'static)>) {
+        self.audited.report(
+            &self.url,
+            Some((self.status, &self.headers)),
+            self.read,
+            complete,
+            error,
+        );
+    }
+}
+
+pin_project! {
+    /// A response body that reports its exchange once it ends, fails, or is
+    /// dropped.
+    pub(crate) struct AuditBody<B> {
+        #[pin]
+        inner: B,
+        report: Option<Report>,
+    }
+
+    impl<B> PinnedDrop for AuditBody<B> {
+        fn drop(this: Pin<&mut Self>) {
+            if let Some(report) = this.project().report.take() {
+                report.send(false, None);
+            }
+        }
+    }
+}
+
+impl<B> hyper::body::Body for AuditBody<B>
+where
+    B: hyper::body::Body<Data = bytes::Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = B::Data;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let (Some(data), Some(report)) = (frame.data_ref(), this.report.as_mut()) {
+                    report.read += data.len() as u64;
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(err)) => {
+                let err = err.into();
+                if let Some(report) = this.report.take() {
+                    report.send(false, Some(&*err));
+                }
+                Poll::Ready(Some(Err(err)))
+            }
+            None => {
+                if let Some(report) = this.report.take() {
+                    report.send(true, None);
+                }
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
@@ -0,0 +1,299 @@
+//! Following paginated responses.
+//!
+//! [`Client::paginate`] sends a request, and then a request for each next
+//! page, as a [`Stream`] of responses:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! use futures_util::StreamExt;
+//!
+//! let client = reqwest::Client::new();
+//! let request = client.get("https://api.example.com/items").build()?;
+//! let mut pages = client.paginate(request).limit(10);
+//! while let Some(page) = pages.next().await {
+//!     println!("{}", page?.text().await?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! By default, the next page is the target of the `Link` header of a
+//! response with the `next` relation ([RFC 8288]), resolved against the URL
+//! of the response. The pages stop at a response without one, or after the
+//! [`limit`](Pages::limit). A [`next_page`](Pages::next_page) function finds
+//! the next page some other way, such as from a cursor in the body of a JSON
+//! page:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "json")]
+//! # async fn run(client: reqwest::Client) -> Result<(), reqwest::Error> {
+//! use futures_util::TryStreamExt;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Page {
+//!     items: Vec<String>,
+//!     next: Option<String>,
+//! }
+//!
+//! let request = client.get("https://api.example.com/items").build()?;
+//! let pages: Vec<Page> = client
+//!     .paginate(request)
+//!     .json::<Page>()
+//!     .next_page(|page| page.next.as_deref()?.parse().ok())
+//!     .try_collect()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each next page is requested with a copy of the first request, with its
+//! URL replaced, so its method, headers and body are sent again. A request
+//! with a streaming body cannot be copied, and asking for its next page is an
+//! error.
+//!
+//! # Optional
+//!
+//! This requires the optional `stream` feature to be enabled.
+//!
+//! [RFC 8288]: https://www.rfc-editor.org/rfc/rfc8288
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use http::header::{HeaderMap, LINK};
+use url::Url;
+
+use crate::util::{link_param, links};
+use crate::{Client, Request, Response};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = crate::Result<T>> + Send>>;
+type Convert<T> = Arc<dyn Fn(Response) -> BoxFuture<T> + Send + Sync>;
+type NextPage<T> = Arc<dyn Fn(&T) -> Option<Url> + Send + Sync>;
+
+/// A [`Stream`] of the pages of a paginated resource.
+///
+/// Created with [`Client::paginate`]. The pages are responses, or values
+/// deserialized from them with [`json`](Pages::json).
+#[must_use = "streams do nothing unless polled"]
+pub struct Pages<T> {
+    client: Client,
+    template: Option<Request>,
+    convert: Convert<T>,
+    next_page: Option<NextPage<T>>,
+    limit: Option<usize>,
+    fetched: usize,
+    state: State<T>,
+}
+
+enum State<T> {
+    Next(Box<Request>),
+    Fetching(BoxFuture<(T, Option<Url>)>),
+    Failed(crate::Error),
+    Done,
+}
+
+impl Pages<Response> {
+    pub(crate) fn new(client: Client, request: Request) -> Pages<Response> {
+        Pages {
+            client,
+            template: request.try_clone(),
+            convert: Arc::new(|res| Box::pin(async move { Ok(res) })),
+            next_page: None,
+            limit: None,
+            fetched: 0,
+            state: State::Next(Box::new(request)),
+        }
+    }
+
+    /// Deserialize each page from a JSON body.
+    ///
+    /// A response with an error status ends the pages with an error, as with
+    /// [`Response::error_for_status`]. This drops a
+    /// [`next_page`](Pages::next_page) function set before, so it is set
+    /// after, on the deserialized pages.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature to be enabled.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn json<T>(self) -> Pages<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let state = match self.state {
+            State::Next(request) => State::Next(request),
+            State::Failed(err) => State::Failed(err),
+            State::Fetching(_) | State::Done => State::Done,
+        };
+        Pages {
+            client: self.client,
+            template: self.template,
+            convert: Arc::new(|res| {
+                Box::pin(async move { res.error_for_status()?.json::<T>().await })
+            }),
+            next_page: None,
+            limit: self.limit,
+            fetched: self.fetched,
+            state,
+        }
+    }
+}
+
+impl<T: Send + 'static> Pages<T> {
+    /// Set a function finding the URL of the page after a page.
+    ///
+    /// The function replaces the `Link` header. Returning `None` ends the
+    /// pages.
+    pub fn next_page<F>(mut self, f: F) -> Pages<T>
+    where
+        F: Fn(&T) -> Option<Url> + Send + Sync + 'static,
+    {
+        self.next_page = Some(Arc::new(f));
+        self
+    }
+
+    /// Set the most pages to request.
+    ///
+    /// Default is no limit.
+    pub fn limit(mut self, pages: usize) -> Pages<T> {
+        self.limit = Some(pages);
+        self
+    }
+
+    fn fetch(&self, request: Request) -> BoxFuture<(T, Option<Url>)> {
+        let client = self.client.clone();
+        let convert = self.convert.clone();
+        let next_page = self.next_page.clone();
+        Box::pin(async move {
+            let res = client.execute(request).await?;
+            let url = res.url().clone();
+            let linked = next_link(res.headers(), &url);
+            let page = convert(res).await?;
+            let next = match next_page {
+                Some(next_page) => next_page(&page),
+                None => linked,
+            };
+            // a page linking to itself would never end
+            Ok((page, next.filter(|next| *next != url)))
+        })
+    }
+
+    fn request(&self, url: Url) -> crate::Result<Request> {
+        let mut request = self
+            .template
+            .as_ref()
+            .and_then(Request::try_clone)
+            .ok_or_else(|| crate::error::builder(NotCloneable).with_url(url.clone()))?;
+        *request.url_mut() = url;
+        Ok(request)
+    }
+}
+
+impl<T: Send + 'static> Stream for Pages<T> {
+    type Item = crate::Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match std::mem::replace(&mut this.state, State::Done) {
+                State::Next(request) => {
+                    if this.limit.is_some_and(|limit| this.fetched >= limit) {
+                        return Poll::Ready(None);
+                    }
+                    this.fetched += 1;
+                    this.state = State::Fetching(this.fetch(*request));
+                }
+                State::Fetching(mut fut) => {
+                    let (page, next) = match fut.as_mut().poll(cx) {
+                        Poll::Pending => {
+                            this.state = State::Fetching(fut);
+                            return Poll::Pending;
+                        }
+                        Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err))),
+                        Poll::Ready(Ok(fetched)) => fetched,
+                    };
+                    let more = this.limit.is_none_or(|limit| this.fetched < limit);
+                    if let Some(url) = next.filter(|_| more) {
+                        match this.request(url) {
+                            Ok(request) => this.state = State::Next(Box::new(request)),
+                            // the page is still returned, and the error after it
+                            Err(err) => this.state = State::Failed(err),
+                        }
+                    }
+                    return Poll::Ready(Some(Ok(page)));
+                }
+                State::Failed(err) => return Poll::Ready(Some(Err(err))),
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pages<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pages")
+            .field("limit", &self.limit)
+            .field("fetched", &self.fetched)
+            .finish()
+    }
+}
+
+/// The target of the first `Link` with the `next` relation.
+fn next_link(headers: &HeaderMap, base: &Url) -> Option<Url> {
+    headers
+        .get_all(LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(links)
+        .find(|(_, params)| is_next(params))
+        .and_then(|(target, _)| base.join(target).ok())
+}
+
+fn is_next(params: &str) -> bool {
+    link_param(params, "rel").is_some_and(|rels| {
+        rels.split_ascii_whitespace()
+            .any(|rel| rel.eq_ignore_ascii_case("next"))
+    })
+}
+
+#[derive(Debug)]
+struct NotCloneable;
+
+impl fmt::Display for NotCloneable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("request body cannot be sent again for the next page")
+    }
+}
+
+impl std::error::Error for NotCloneable {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_next_link() {
+        let base = Url::parse("https://api.example.com/items?page=1").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            "<https://api.example.com/items?page=1>; rel=\"prev first\", \
+             </items?page=2&per_page=10,20>; rel=\"next\", \
+             <https://api.example.com/items?page=9>; rel=last"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            next_link(&headers, &base).unwrap().as_str(),
+            "https://api.example.com/items?page=2&per_page=10,20"
+        );
+
+        headers.insert(LINK, "<https://example.com/>; rel=last".parse().unwrap());
+        assert_eq!(next_link(&headers, &base), None);
+    }
+}
@@ -73,6 +73,7 @@ struct Extra {
 
 // ===== Internal =====
 
+#[derive(Clone)]
 pub(crate) struct Matcher {
     inner: Matcher_,
     extra: Extra,
@@ -80,8 +81,9 @@ pub(crate) struct Matcher {
     maybe_has_http_custom_headers: bool,
 }
 
+#[derive(Clone)]
 enum Matcher_ {
-    Util(matcher::Matcher),
+    Util(Arc<matcher::Matcher>),
     Custom(Custom),
 }
 
@@ -378,34 +380,34 @@ impl Proxy {
                 maybe_has_http_auth = cache_maybe_has_http_auth(&url, &extra.auth);
                 maybe_has_http_custom_headers =
                     cache_maybe_has_http_custom_headers(&url, &extra.misc);
-                Matcher_::Util(
+                Matcher_::Util(Arc::new(
                     matcher::Matcher::builder()
                         .all(String::from(url))
                         .no(no_proxy.as_ref().map(|n| n.inner.as_ref()).unwrap_or(""))
                         .build(),
-                )
+                ))
             }
             Intercept::Http(url) => {
                 maybe_has_http_auth = cache_maybe_has_http_auth(&url, &extra.auth);
                 maybe_has_http_custom_headers =
                     cache_maybe_has_http_custom_headers(&url, &extra.misc);
-                Matcher_::Util(
+                Matcher_::Util(Arc::new(
                     matcher::Matcher::builder()
                         .http(String::from(url))
                         .no(no_proxy.as_ref().map(|n| n.inner.as_ref()).unwrap_or(""))
                         .build(),
-                )
+                ))
             }
             Intercept::Https(url) => {
                 maybe_has_http_auth = cache_maybe_has_http_auth(&url, &extra.auth);
                 maybe_has_http_custom_headers =
                     cache_maybe_has_http_custom_headers(&url, &extra.misc);
-                Matcher_::Util(
+                Matcher_::Util(Arc::new(
                     matcher::Matcher::builder()
                         .https(String::from(url))
                         .no(no_proxy.as_ref().map(|n| n.inner.as_ref()).unwrap_or(""))
                         .build(),
-                )
+                ))
             }
             Intercept::Custom(mut custom) => {
                 maybe_has_http_auth = true; // never know
@@ -514,7 +516,7 @@ impl NoProxy {
 impl Matcher {
     pub(crate) fn system() -> Self {
         Self {
-            inner: Matcher_::Util(matcher::Matcher::from_system()),
+            inner: Matcher_::Util(Arc::new(matcher::Matcher::from_system())),
             extra: Extra {
                 auth: None,
                 misc: None,
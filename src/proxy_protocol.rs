@@ -0,0 +1,239 @@
+//! PROXY protocol headers.
+//!
+//! Servers behind a load balancer speaking the HAProxy [PROXY protocol]
+//! expect each connection to start with a header naming the client it was
+//! accepted from. To talk to such a server directly, a client can send that
+//! header itself with
+//! [`ClientBuilder::proxy_protocol`](crate::ClientBuilder::proxy_protocol):
+//!
+//! ```rust
+//! # fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::proxy_protocol::ProxyProtocol;
+//!
+//! let client = reqwest::Client::builder()
+//!     .proxy_protocol(ProxyProtocol::v2().source("203.0.113.7:50000".parse().unwrap()))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The header is written as soon as the TCP connection is established,
+//! before any TLS handshake. Its destination is the address connected to,
+//! and its source the local address of the connection, unless another one is
+//! set. It is only sent on direct connections: not to an HTTP or SOCKS
+//! proxy, nor over a Unix socket.
+//!
+//! [PROXY protocol]: https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use http::Uri;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+use crate::error::BoxError;
+
+/// The signature starting a version 2 header.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The PROXY protocol header sent on new connections.
+#[derive(Clone, Debug)]
+pub struct ProxyProtocol {
+    version: Version,
+    source: Option<SocketAddr>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Version {
+    V1,
+    V2,
+}
+
+impl ProxyProtocol {
+    /// Send the human-readable version 1 header.
+    pub fn v1() -> ProxyProtocol {
+        ProxyProtocol {
+            version: Version::V1,
+            source: None,
+        }
+    }
+
+    /// Send the binary version 2 header.
+    pub fn v2() -> ProxyProtocol {
+        ProxyProtocol {
+            version: Version::V2,
+            source: None,
+        }
+    }
+
+    /// Set the source address announced in the header.
+    ///
+    /// Defaults to the local address of each connection.
+    pub fn source(mut self, addr: SocketAddr) -> ProxyProtocol {
+        self.source = Some(addr);
+        self
+    }
+
+    /// Encode the header of a connection from `local` to `peer`.
+    pub(crate) fn encode(&self, local: SocketAddr, peer: SocketAddr) -> Vec<u8> {
+        let (source, destination) = same_family(self.source.unwrap_or(local), peer);
+        match self.version {
+            Version::V1 => {
+                let family = if source.is_ipv4() { "TCP4" } else { "TCP6" };
+                format!(
+                    "PROXY {family} {} {} {} {}\r\n",
+                    source.ip(),
+                    destination.ip(),
+                    source.port(),
+                    destination.port()
+                )
+                .into_bytes()
+            }
+            Version::V2 => {
+                let mut header = V2_SIGNATURE.to_vec();
+                // version 2, PROXY command
+                header.push(0x21);
+                match (source.ip(), destination.ip()) {
+                    (IpAddr::V4(src), IpAddr::V4(dst)) => {
+                        // TCP over IPv4
+                        header.push(0x11);
+                        header.extend_from_slice(&12u16.to_be_bytes());
+                        header.extend_from_slice(&src.octets());
+                        header.extend_from_slice(&dst.octets());
+                    }
+                    (src, dst) => {
+                        // TCP over IPv6
+                        header.push(0x21);
+                        header.extend_from_slice(&36u16.to_be_bytes());
+                        header.extend_from_slice(&ipv6(src).octets());
+                        header.extend_from_slice(&ipv6(dst).octets());
+                    }
+                }
+                header.extend_from_slice(&source.port().to_be_bytes());
+                header.extend_from_slice(&destination.port().to_be_bytes());
+                header
+            }
+        }
+    }
+}
+
+/// Both addresses of a header are of the same family: if one of them is an
+/// IPv6 address, the other is mapped to IPv6 too.
+fn same_family(source: SocketAddr, destination: SocketAddr) -> (SocketAddr, SocketAddr) {
+    if source.is_ipv4() == destination.is_ipv4() {
+        return (source, destination);
+    }
+    (
+        SocketAddr::new(ipv6(source.ip()).into(), source.port()),
+        SocketAddr::new(ipv6(destination.ip()).into(), destination.port()),
+    )
+}
+
+fn ipv6(ip: IpAddr) -> std::net::Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+/// A TCP connector that sends a PROXY protocol header on the connections it
+/// establishes.
+#[derive(Clone)]
+pub(crate) struct ProxyProtocolTcp<S> {
+    inner: S,
+    header: Option<ProxyProtocol>,
+}
+
+impl<S> ProxyProtocolTcp<S> {
+    pub(crate) fn new(inner: S, header: Option<ProxyProtocol>) -> Self {
+        ProxyProtocolTcp { inner, header }
+    }
+}
+
+impl<S> Service<Uri> for ProxyProtocolTcp<S>
+where
+    S: Service<Uri, Response = TokioIo<TcpStream>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let connecting = self.inner.call(dst);
+        let header = self.header.clone();
+        Box::pin(async move {
+            let io = connecting.await.map_err(Into::into)?;
+            if let Some(header) = header {
+                let tcp = io.inner();
+                let header = header.encode(tcp.local_addr()?, tcp.peer_addr()?);
+                write_all(tcp, &header).await?;
+            }
+            Ok(io)
+        })
+    }
+}
+
+async fn write_all(tcp: &TcpStream, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        tcp.writable().await?;
+        match tcp.try_write(buf) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => buf = &buf[written..],
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_header() {
+        let local = "192.168.0.1:56324".parse().unwrap();
+        let peer = "192.168.0.11:443".parse().unwrap();
+
+        assert_eq!(
+            ProxyProtocol::v1().encode(local, peer),
+            b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n"
+        );
+        assert_eq!(
+            ProxyProtocol::v1()
+                .source("[2001:db8::1]:4000".parse().unwrap())
+                .encode(local, peer),
+            b"PROXY TCP6 2001:db8::1 ::ffff:192.168.0.11 4000 443\r\n"
+        );
+    }
+
+    #[test]
+    fn v2_header() {
+        let local = "127.0.0.1:1234".parse().unwrap();
+        let peer = "10.0.0.2:80".parse().unwrap();
+
+        let header = ProxyProtocol::v2().encode(local, peer);
+        assert_eq!(header[..12], V2_SIGNATURE);
+        assert_eq!(
+            header[12..],
+            [0x21, 0x11, 0, 12, 127, 0, 0, 1, 10, 0, 0, 2, 0x04, 0xd2, 0, 80]
+        );
+
+        let header = ProxyProtocol::v2().encode(local, "[::1]:80".parse().unwrap());
+        assert_eq!(header[12..16], [0x21, 0x21, 0, 36]);
+        assert_eq!(header.len(), 16 + 36);
+        assert_eq!(header[16..32], ipv6("127.0.0.1".parse().unwrap()).octets());
+        assert_eq!(header[48..], [0x04, 0xd2, 0, 80]);
+    }
+}
@@ -11,6 +11,8 @@ use crate::header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, REFERER, WWW_AUT
 use http::{HeaderMap, HeaderValue};
 use hyper::StatusCode;
 
+use crate::observe::ConnectionSlot;
+use crate::tcp::InterfaceIndexExtension;
 use crate::{async_impl, Url};
 use tower_http::follow_redirect::policy::{
     Action as TowerAction, Attempt as TowerAttempt, Policy as TowerPolicy,
@@ -25,6 +27,7 @@ use tower_http::follow_redirect::policy::{
 ///   the allowed maximum redirect hops in a chain.
 /// - `none` can be used to disable all redirect behavior.
 /// - `custom` can be used to create a customized policy.
+#[derive(Clone)]
 pub struct Policy {
     inner: PolicyKind,
 }
@@ -104,7 +107,7 @@ impl Policy {
         T: Fn(Attempt) -> Action + Send + Sync + 'static,
     {
         Self {
-            inner: PolicyKind::Custom(Box::new(policy)),
+            inner: PolicyKind::Custom(Arc::new(policy)),
         }
     }
 
@@ -205,8 +208,9 @@ impl<'a> Attempt<'a> {
     }
 }
 
+#[derive(Clone)]
 enum PolicyKind {
-    Custom(Box<dyn Fn(Attempt) -> Action + Send + Sync + 'static>),
+    Custom(Arc<dyn Fn(Attempt) -> Action + Send + Sync + 'static>),
     Limit(usize),
     None,
 }
@@ -252,7 +256,7 @@ pub(crate) fn remove_sensitive_headers(headers: &mut HeaderMap, next: &Url, prev
 }
 
 #[derive(Debug)]
-struct TooManyRedirects;
+pub(crate) struct TooManyRedirects;
 
 impl fmt::Display for TooManyRedirects {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -268,6 +272,10 @@ pub(crate) struct TowerRedirectPolicy {
     referer: bool,
     urls: Vec<Url>,
     https_only: bool,
+    #[cfg(feature = "hsts-preload")]
+    hsts_preload: Option<Arc<crate::hsts::Preload>>,
+    connection: Option<ConnectionSlot>,
+    interface: Option<InterfaceIndexExtension>,
 }
 
 impl TowerRedirectPolicy {
@@ -277,6 +285,10 @@ impl TowerRedirectPolicy {
             referer: false,
             urls: Vec::new(),
             https_only: false,
+            #[cfg(feature = "hsts-preload")]
+            hsts_preload: None,
+            connection: None,
+            interface: None,
         }
     }
 
@@ -289,6 +301,24 @@ impl TowerRedirectPolicy {
         self.https_only = https_only;
         self
     }
+
+    #[cfg(feature = "hsts-preload")]
+    pub(crate) fn with_hsts_preload(
+        &mut self,
+        hsts_preload: Option<Arc<crate::hsts::Preload>>,
+    ) -> &mut Self {
+        self.hsts_preload = hsts_preload;
+        self
+    }
+
+    // switch a URL to HTTPS if its domain is preloaded
+    #[cfg(feature = "hsts-preload")]
+    fn upgrade(&self, mut url: Url) -> Url {
+        if let Some(ref preload) = self.hsts_preload {
+            preload.upgrade(&mut url);
+        }
+        url
+    }
 }
 
 fn make_referer(next: &Url, previous: &Url) -> Option<HeaderValue> {
@@ -312,6 +342,8 @@ impl TowerPolicy<async_impl::body::Body, crate::Error> for TowerRedirectPolicy {
             Ok(url) => url,
             Err(e) => return Err(crate::error::builder(e)),
         };
+        #[cfg(feature = "hsts-preload")]
+        let next_url = self.upgrade(next_url);
 
         self.urls.push(previous_url.clone());
 
@@ -335,7 +367,31 @@ impl TowerPolicy<async_impl::body::Body, crate::Error> for TowerRedirectPolicy {
     }
 
     fn on_request(&mut self, req: &mut http::Request<async_impl::body::Body>) {
+        // only the first request has the slot, redirects lose the extensions
+        if let Some(slot) = req.extensions().get::<ConnectionSlot>() {
+            self.connection = Some(slot.clone());
+        }
+        if let Some(ref slot) = self.connection {
+            slot.capture(req);
+            req.extensions_mut().insert(slot.clone());
+        }
+        if let Some(index) = req.extensions().get::<InterfaceIndexExtension>() {
+            self.interface = Some(*index);
+        }
+        if let Some(index) = self.interface {
+            req.extensions_mut().insert(index);
+        }
         if let Ok(next_url) = Url::parse(&req.uri().to_string()) {
+            #[cfg(feature = "hsts-preload")]
+            let next_url = {
+                let upgraded = self.upgrade(next_url.clone());
+                if upgraded != next_url {
+                    if let Ok(uri) = upgraded.as_str().parse() {
+                        *req.uri_mut() = uri;
+                    }
+                }
+                upgraded
+            };
             remove_sensitive_headers(req.headers_mut(), &next_url, &self.urls);
             if self.referer {
                 if let Some(previous_url) = self.urls.last() {
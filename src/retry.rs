@@ -35,19 +35,89 @@
 //!
 //! Some common properties to check include if the request method is
 //! idempotent, or if the response status code indicates a transient error.
-
+//!
+//! A classifier implements the [`Classify`] trait, or is a closure given to
+//! [`Builder::classify_fn()`]. It sorts each attempt into an [`Action`]:
+//!
+//! - a success, which isn't retried,
+//! - a retryable failure, which is retried right away,
+//! - a fatal failure, which isn't retried, and doesn't count as a success
+//!   for the budget,
+//! - or a throttled failure, which is retried after a delay, such as the one
+//!   the server asked for in a `Retry-After` header.
+//!
+//! This lets signals specific to a server drive retries, like a custom
+//! `4xx` status code, or a header describing the error:
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use reqwest::retry::{Action, Classify, ReqRep};
+//!
+//! struct Api;
+//!
+//! impl Classify for Api {
+//!     fn classify(&self, req_rep: ReqRep<'_>) -> Action {
+//!         match req_rep.status().map(|status| status.as_u16()) {
+//!             Some(429) => {
+//!                 let delay = req_rep.retry_after().unwrap_or(Duration::from_secs(1));
+//!                 req_rep.throttled(delay)
+//!             }
+//!             Some(409) if req_rep.headers().is_some_and(|h| h.contains_key("x-busy")) => {
+//!                 req_rep.retryable()
+//!             }
+//!             Some(400..=499) => req_rep.fatal(),
+//!             _ => req_rep.success(),
+//!         }
+//!     }
+//! }
+//!
+//! let retries = reqwest::retry::for_host("api.example.com").classify(Api);
+//! ```
+//!
+//! Classifiers run once the response headers are received, before its body
+//! is read, so they can't look into the body.
+//!
+//! # Connections
+//!
+//! Separately from requests, a [`Connect`] policy set with
+//! [`ClientBuilder::connect_retry`](crate::ClientBuilder::connect_retry)
+//! retries opening a connection, with a backoff between the attempts, when
+//! the host refused it or, optionally, when it timed out. Since no request
+//! was sent yet, this is safe for any request, and it doesn't count against
+//! a retry budget:
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! # fn run() -> Result<(), reqwest::Error> {
+//! let client = reqwest::Client::builder()
+//!     .connect_retry(
+//!         reqwest::retry::Connect::new()
+//!             .attempts(4)
+//!             .backoff(Duration::from_millis(100), Duration::from_secs(2))
+//!             .retry_timeouts(true),
+//!     )
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 use tower::retry::budget::{Budget as _, TpsBudget as Budget};
 
-#[cfg(docsrs)]
-pub use classify::ReqRep;
+use crate::observe::ConnectionSlot;
+
+pub use classify::{Action, Classify, ReqRep};
 
 /// Builder to configure retries
 ///
 /// Construct with [`for_host()`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Builder {
     //backoff: Backoff,
     budget: Option<f32>,
@@ -220,9 +290,39 @@ impl Builder {
 
 type Req = http::Request<crate::async_impl::body::Body>;
 
+impl Policy {
+    fn withdraw(&mut self, req: &Req, delay: Option<Duration>) -> Option<Delay> {
+        if self.budget.as_ref().map(|b| b.withdraw()).unwrap_or(true) {
+            self.retry_cnt += 1;
+            if let Some(slot) = req.extensions().get::<ConnectionSlot>() {
+                slot.retried();
+            }
+            let sleep = delay.map(|delay| Box::pin(tokio::time::sleep(delay)));
+            Some(Delay(sleep))
+        } else {
+            log::debug!("retryable but could not withdraw from budget");
+            None
+        }
+    }
+}
+
+/// Waits before sending a retry, if its attempt was throttled.
+pub(crate) struct Delay(Option<Pin<Box<tokio::time::Sleep>>>);
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        match self.0 {
+            Some(ref mut sleep) => sleep.as_mut().poll(cx),
+            None => Poll::Ready(()),
+        }
+    }
+}
+
 impl<B> tower::retry::Policy<Req, http::Response<B>, crate::Error> for Policy {
     // TODO? backoff futures...
-    type Future = std::future::Ready<()>;
+    type Future = Delay;
 
     fn retry(
         &mut self,
@@ -237,15 +337,17 @@ impl<B> tower::retry::Policy<Req, http::Response<B>, crate::Error> for Policy {
                 }
                 None
             }
+            classify::Action::Fatal => {
+                log::trace!("fatal, shouldn't retry!");
+                None
+            }
             classify::Action::Retryable => {
                 log::trace!("could retry!");
-                if self.budget.as_ref().map(|b| b.withdraw()).unwrap_or(true) {
-                    self.retry_cnt += 1;
-                    Some(std::future::ready(()))
-                } else {
-                    log::debug!("retryable but could not withdraw from budget");
-                    None
-                }
+                self.withdraw(req, None)
+            }
+            classify::Action::Throttled(delay) => {
+                log::trace!("throttled, could retry after {delay:?}");
+                self.withdraw(req, Some(delay))
             }
         }
     }
@@ -317,6 +419,120 @@ fn is_retryable_error(err: &crate::Error) -> bool {
     false
 }
 
+/// A policy to retry opening connections.
+///
+/// By default, a connection is attempted once. With more attempts, a
+/// connection which is refused is attempted again after a backoff, which
+/// doubles after each attempt.
+///
+/// Each attempt tries every address the host resolves to, and is bounded by
+/// the [`connect_timeout`](crate::ClientBuilder::connect_timeout). Other
+/// failures, such as a failed DNS resolution or TLS handshake, are not
+/// retried.
+#[derive(Clone, Debug)]
+pub struct Connect {
+    attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    refused: bool,
+    timeouts: bool,
+}
+
+impl Connect {
+    /// Create a policy which attempts each connection once.
+    pub fn new() -> Connect {
+        Connect {
+            attempts: 1,
+            backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+            refused: true,
+            timeouts: false,
+        }
+    }
+
+    /// Set how many times a connection is attempted, the first one included.
+    ///
+    /// Default is `1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `attempts` is `0`.
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        assert!(attempts > 0, "a connection needs at least one attempt");
+        self.attempts = attempts;
+        self
+    }
+
+    /// Set the backoff before the second attempt, which doubles for each
+    /// attempt after it, up to `max`.
+    ///
+    /// Default is 50 milliseconds, up to 1 second.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Set whether a connection refused by the host is attempted again.
+    ///
+    /// Default is `true`.
+    pub fn retry_refused(mut self, enabled: bool) -> Self {
+        self.refused = enabled;
+        self
+    }
+
+    /// Set whether a connection which timed out is attempted again.
+    ///
+    /// Default is `false`, since a host which doesn't answer in time
+    /// usually won't on the next attempt either.
+    pub fn retry_timeouts(mut self, enabled: bool) -> Self {
+        self.timeouts = enabled;
+        self
+    }
+
+    /// The backoff before attempting again, after the failure of the
+    /// `attempt`th attempt, or `None` if it isn't attempted again.
+    pub(crate) fn backoff_after(
+        &self,
+        attempt: u32,
+        err: &(dyn std::error::Error + 'static),
+    ) -> Option<Duration> {
+        if attempt >= self.attempts || !self.retries(err) {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempt - 1);
+        Some(
+            self.backoff
+                .checked_mul(factor)
+                .map_or(self.max_backoff, |delay| delay.min(self.max_backoff)),
+        )
+    }
+
+    fn retries(&self, err: &(dyn std::error::Error + 'static)) -> bool {
+        let mut source = Some(err);
+        while let Some(err) = source {
+            if err.is::<crate::error::TimedOut>() {
+                return self.timeouts;
+            }
+            if let Some(io) = err.downcast_ref::<std::io::Error>() {
+                match io.kind() {
+                    std::io::ErrorKind::ConnectionRefused => return self.refused,
+                    std::io::ErrorKind::TimedOut => return self.timeouts,
+                    _ => (),
+                }
+            }
+            source = err.source();
+        }
+        false
+    }
+}
+
+impl Default for Connect {
+    fn default() -> Connect {
+        Connect::new()
+    }
+}
+
 // sealed types and traits on purpose while exploring design space
 mod scope {
     pub trait Scope: Send + Sync + 'static {
@@ -373,9 +589,15 @@ mod scope {
     }
 }
 
-// sealed types and traits on purpose while exploring design space
 mod classify {
+    use std::time::Duration;
+
+    /// Classifies the attempts of a request, to determine if it should be
+    /// retried.
+    ///
+    /// Set with [`Builder::classify()`](super::Builder::classify).
     pub trait Classify: Send + Sync + 'static {
+        /// Classify an attempt, from its request and its response or error.
         fn classify(&self, req_rep: ReqRep<'_>) -> Action;
     }
 
@@ -400,7 +622,10 @@ mod classify {
     ///
     /// This is passed to a `classify` function.
     #[derive(Debug)]
-    pub struct ReqRep<'a>(&'a super::Req, Result<http::StatusCode, &'a crate::Error>);
+    pub struct ReqRep<'a>(
+        &'a super::Req,
+        Result<(http::StatusCode, &'a http::HeaderMap), &'a crate::Error>,
+    );
 
     impl ReqRep<'_> {
         /// Access the request method.
@@ -415,7 +640,20 @@ mod classify {
 
         /// Access the response status, if it did not error.
         pub fn status(&self) -> Option<http::StatusCode> {
-            self.1.ok()
+            self.1.as_ref().ok().map(|&(status, _)| status)
+        }
+
+        /// Access the response headers, if it did not error.
+        pub fn headers(&self) -> Option<&http::HeaderMap> {
+            self.1.as_ref().ok().map(|&(_, headers)| headers)
+        }
+
+        /// The delay the response asked for in its `Retry-After` header, if
+        /// it is a number of seconds.
+        pub fn retry_after(&self) -> Option<Duration> {
+            let value = self.headers()?.get(http::header::RETRY_AFTER)?;
+            let secs = value.to_str().ok()?.trim().parse().ok()?;
+            Some(Duration::from_secs(secs))
         }
 
         /// Access the error, if a response was not received.
@@ -435,6 +673,21 @@ mod classify {
             Action::Success
         }
 
+        /// Classify this attempt as a failure which shouldn't be retried.
+        ///
+        /// Unlike a "success", it doesn't count towards the retry budget.
+        pub fn fatal(self) -> Action {
+            Action::Fatal
+        }
+
+        /// Classify this attempt as throttled by the server, to retry after
+        /// `delay`.
+        ///
+        /// The delay counts towards the timeout of the request.
+        pub fn throttled(self, delay: Duration) -> Action {
+            Action::Throttled(delay)
+        }
+
         fn is_protocol_nack(&self) -> bool {
             self.1
                 .as_ref()
@@ -444,11 +697,18 @@ mod classify {
         }
     }
 
+    /// What to do after an attempt, as classified by a [`Classify`].
     #[must_use]
     #[derive(Debug)]
     pub enum Action {
+        /// Don't retry.
         Success,
+        /// Retry right away.
         Retryable,
+        /// Don't retry, without counting as a success for the budget.
+        Fatal,
+        /// Retry after a delay.
+        Throttled(Duration),
     }
 
     #[derive(Clone)]
@@ -464,7 +724,7 @@ mod classify {
             req: &super::Req,
             res: &Result<http::Response<B>, crate::Error>,
         ) -> Action {
-            let req_rep = ReqRep(req, res.as_ref().map(|r| r.status()));
+            let req_rep = ReqRep(req, res.as_ref().map(|r| (r.status(), r.headers())));
             match self {
                 Self::Never => Action::Success,
                 Self::ProtocolNacks => {
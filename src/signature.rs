@@ -0,0 +1,659 @@
+//! HTTP Message Signatures
+//!
+//! A `Client` can be configured with a [`Signer`] that adds [RFC 9421]
+//! `Signature-Input` and `Signature` fields to every request it sends.
+//!
+//! The [`Signer`] decides which parts of a request are covered by the
+//! signature, and which parameters are included. The key material is
+//! pluggable, by implementing [`SigningKey`]. Implementations for
+//! `hmac-sha256` and `ed25519` are provided.
+//!
+//! Requests are signed right before they are sent, after cookies and
+//! `Content-Digest` have been added. Retried and redirected requests are
+//! signed again, since their target and `created` time may differ.
+//!
+//! ```
+//! # fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::signature::{Component, HmacSha256, Signer};
+//!
+//! let signer = Signer::new(HmacSha256::new(b"shared secret"))
+//!     .key_id("my-key")
+//!     .components([
+//!         Component::method(),
+//!         Component::target_uri(),
+//!         Component::header(reqwest::header::CONTENT_TYPE),
+//!     ]);
+//!
+//! let client = reqwest::Client::builder()
+//!     .message_signer(signer)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [RFC 9421]: https://www.rfc-editor.org/rfc/rfc9421.html
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures_util::future::{self, Either, Ready};
+use http::header::{HeaderName, HeaderValue};
+use http::{Request, Uri};
+use tower::Service;
+
+use crate::async_impl::body::Body;
+
+const SIGNATURE_INPUT: HeaderName = HeaderName::from_static("signature-input");
+const SIGNATURE: HeaderName = HeaderName::from_static("signature");
+const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+
+/// Key material used to sign requests.
+///
+/// Implement this to plug in keys held elsewhere, such as in a hardware
+/// module or a remote signing service.
+pub trait SigningKey: Send + Sync + 'static {
+    /// Sign the signature base, returning the raw signature bytes.
+    fn sign(&self, base: &[u8]) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>>;
+
+    /// The `alg` parameter to include, if any.
+    ///
+    /// The default is to not include one, leaving the verifier to derive
+    /// the algorithm from the key.
+    fn algorithm(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Signs requests using a shared secret with `hmac-sha256`.
+pub struct HmacSha256 {
+    key: ring::hmac::Key,
+}
+
+impl HmacSha256 {
+    /// Create a key from the shared secret.
+    pub fn new(secret: &[u8]) -> HmacSha256 {
+        HmacSha256 {
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret),
+        }
+    }
+}
+
+impl SigningKey for HmacSha256 {
+    fn sign(&self, base: &[u8]) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>> {
+        Ok(ring::hmac::sign(&self.key, base).as_ref().to_vec())
+    }
+
+    fn algorithm(&self) -> Option<&str> {
+        Some("hmac-sha256")
+    }
+}
+
+impl fmt::Debug for HmacSha256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HmacSha256").finish_non_exhaustive()
+    }
+}
+
+/// Signs requests using an `ed25519` private key.
+pub struct Ed25519 {
+    pair: ring::signature::Ed25519KeyPair,
+}
+
+impl Ed25519 {
+    /// Parse a DER-encoded PKCS#8 private key.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the key is not a valid Ed25519 PKCS#8 document.
+    pub fn from_pkcs8(der: &[u8]) -> crate::Result<Ed25519> {
+        ring::signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(der)
+            .map(|pair| Ed25519 { pair })
+            .map_err(|e| crate::error::builder(e.to_string()))
+    }
+}
+
+impl SigningKey for Ed25519 {
+    fn sign(&self, base: &[u8]) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>> {
+        Ok(self.pair.sign(base).as_ref().to_vec())
+    }
+
+    fn algorithm(&self) -> Option<&str> {
+        Some("ed25519")
+    }
+}
+
+impl fmt::Debug for Ed25519 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ed25519").finish_non_exhaustive()
+    }
+}
+
+/// A part of the request that is covered by a signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Component {
+    inner: ComponentKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ComponentKind {
+    Method,
+    TargetUri,
+    Authority,
+    Scheme,
+    RequestTarget,
+    Path,
+    Query,
+    Header(HeaderName),
+}
+
+impl Component {
+    /// The `@method` of the request.
+    pub fn method() -> Component {
+        Component::from(ComponentKind::Method)
+    }
+
+    /// The full `@target-uri` of the request.
+    pub fn target_uri() -> Component {
+        Component::from(ComponentKind::TargetUri)
+    }
+
+    /// The `@authority` of the target URI, without the default port.
+    pub fn authority() -> Component {
+        Component::from(ComponentKind::Authority)
+    }
+
+    /// The `@scheme` of the target URI.
+    pub fn scheme() -> Component {
+        Component::from(ComponentKind::Scheme)
+    }
+
+    /// The `@request-target`, which is the path and query.
+    pub fn request_target() -> Component {
+        Component::from(ComponentKind::RequestTarget)
+    }
+
+    /// The `@path` of the target URI.
+    pub fn path() -> Component {
+        Component::from(ComponentKind::Path)
+    }
+
+    /// The `@query` of the target URI, including the leading `?`.
+    pub fn query() -> Component {
+        Component::from(ComponentKind::Query)
+    }
+
+    /// A header field of the request.
+    ///
+    /// Sending fails if a covered header is not present on the request.
+    pub fn header(name: HeaderName) -> Component {
+        Component::from(ComponentKind::Header(name))
+    }
+
+    fn name(&self) -> &str {
+        match self.inner {
+            ComponentKind::Method => "@method",
+            ComponentKind::TargetUri => "@target-uri",
+            ComponentKind::Authority => "@authority",
+            ComponentKind::Scheme => "@scheme",
+            ComponentKind::RequestTarget => "@request-target",
+            ComponentKind::Path => "@path",
+            ComponentKind::Query => "@query",
+            ComponentKind::Header(ref name) => name.as_str(),
+        }
+    }
+
+    fn value<B>(&self, req: &Request<B>) -> Result<Vec<u8>, Missing> {
+        let uri = req.uri();
+        let value = match self.inner {
+            ComponentKind::Method => req.method().as_str().to_owned(),
+            ComponentKind::TargetUri => uri.to_string(),
+            ComponentKind::Authority => authority(uri).ok_or_else(|| self.missing())?,
+            ComponentKind::Scheme => uri
+                .scheme_str()
+                .ok_or_else(|| self.missing())?
+                .to_ascii_lowercase(),
+            ComponentKind::RequestTarget => uri
+                .path_and_query()
+                .map_or("/", |pq| pq.as_str())
+                .to_owned(),
+            ComponentKind::Path => match uri.path() {
+                "" => "/".to_owned(),
+                path => path.to_owned(),
+            },
+            ComponentKind::Query => format!("?{}", uri.query().unwrap_or("")),
+            ComponentKind::Header(ref name) => {
+                let mut values = req.headers().get_all(name).iter().peekable();
+                if values.peek().is_none() {
+                    return Err(self.missing());
+                }
+                let mut joined = Vec::new();
+                for (i, value) in values.enumerate() {
+                    if i > 0 {
+                        joined.extend_from_slice(b", ");
+                    }
+                    joined.extend_from_slice(value.as_bytes().trim_ascii());
+                }
+                return Ok(joined);
+            }
+        };
+        Ok(value.into_bytes())
+    }
+
+    fn missing(&self) -> Missing {
+        Missing {
+            name: self.name().to_owned(),
+        }
+    }
+}
+
+impl From<ComponentKind> for Component {
+    fn from(inner: ComponentKind) -> Component {
+        Component { inner }
+    }
+}
+
+fn authority(uri: &Uri) -> Option<String> {
+    let host = uri.host()?.to_ascii_lowercase();
+    let default_port = match uri.scheme_str() {
+        Some("http") => Some(80),
+        Some("https") => Some(443),
+        _ => None,
+    };
+    match uri.port_u16() {
+        Some(port) if Some(port) != default_port => Some(format!("{host}:{port}")),
+        _ => Some(host),
+    }
+}
+
+/// Configuration for signing requests.
+///
+/// By default, a signature covers the `@method`, `@target-uri`, and the
+/// `content-type` and `content-digest` headers if they are present on a
+/// request. It includes the `created` parameter, and is labeled `sig1`.
+pub struct Signer {
+    key: Arc<dyn SigningKey>,
+    label: String,
+    key_id: Option<String>,
+    tag: Option<String>,
+    components: Option<Vec<Component>>,
+    created: bool,
+    expires_in: Option<Duration>,
+    nonce: bool,
+}
+
+impl Signer {
+    /// Create a `Signer` that uses the given key.
+    pub fn new<K: SigningKey>(key: K) -> Signer {
+        Signer {
+            key: Arc::new(key),
+            label: "sig1".to_owned(),
+            key_id: None,
+            tag: None,
+            components: None,
+            created: true,
+            expires_in: None,
+            nonce: false,
+        }
+    }
+
+    /// Set the label of the signature in the `Signature-Input` and
+    /// `Signature` dictionaries.
+    ///
+    /// Default is `sig1`.
+    pub fn label(mut self, label: impl Into<String>) -> Signer {
+        self.label = label.into();
+        self
+    }
+
+    /// Set the `keyid` parameter.
+    pub fn key_id(mut self, key_id: impl Into<String>) -> Signer {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Set the `tag` parameter, identifying the application profile.
+    pub fn tag(mut self, tag: impl Into<String>) -> Signer {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Set the components covered by the signature, in order.
+    ///
+    /// Unlike the default selection, every listed header must be present on
+    /// a request, otherwise sending it fails.
+    pub fn components<I>(mut self, components: I) -> Signer
+    where
+        I: IntoIterator<Item = Component>,
+    {
+        self.components = Some(components.into_iter().collect());
+        self
+    }
+
+    /// Set whether to include the `created` parameter.
+    ///
+    /// Default is `true`.
+    pub fn created(mut self, enable: bool) -> Signer {
+        self.created = enable;
+        self
+    }
+
+    /// Include an `expires` parameter, the given duration after signing.
+    pub fn expires_in(mut self, duration: Duration) -> Signer {
+        self.expires_in = Some(duration);
+        self
+    }
+
+    /// Set whether to include a random `nonce` parameter.
+    ///
+    /// Default is `false`.
+    pub fn nonce(mut self, enable: bool) -> Signer {
+        self.nonce = enable;
+        self
+    }
+
+    fn covered<B>(&self, req: &Request<B>) -> Vec<Component> {
+        if let Some(ref components) = self.components {
+            return components.clone();
+        }
+        let mut covered = vec![Component::method(), Component::target_uri()];
+        for name in [http::header::CONTENT_TYPE, CONTENT_DIGEST] {
+            if req.headers().contains_key(&name) {
+                covered.push(Component::header(name));
+            }
+        }
+        covered
+    }
+
+    fn params(&self, now: u64) -> Result<String, Box<dyn StdError + Send + Sync>> {
+        use std::fmt::Write;
+
+        let mut params = String::new();
+        if self.created {
+            write!(params, ";created={now}")?;
+        }
+        if let Some(expires_in) = self.expires_in {
+            write!(params, ";expires={}", now + expires_in.as_secs())?;
+        }
+        if self.nonce {
+            let mut nonce = [0u8; 16];
+            ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut nonce)
+                .map_err(|_| "failed to generate nonce")?;
+            write!(params, ";nonce=\"{}\"", STANDARD.encode(nonce))?;
+        }
+        if let Some(alg) = self.key.algorithm() {
+            write!(params, ";alg={}", sf_string(alg)?)?;
+        }
+        if let Some(ref key_id) = self.key_id {
+            write!(params, ";keyid={}", sf_string(key_id)?)?;
+        }
+        if let Some(ref tag) = self.tag {
+            write!(params, ";tag={}", sf_string(tag)?)?;
+        }
+        Ok(params)
+    }
+
+    /// Build the signature base, and the `@signature-params` value.
+    fn base<B>(
+        &self,
+        req: &Request<B>,
+        now: u64,
+    ) -> Result<(Vec<u8>, String), Box<dyn StdError + Send + Sync>> {
+        let covered = self.covered(req);
+        let mut base = Vec::new();
+        let mut list = String::from("(");
+        for (i, component) in covered.iter().enumerate() {
+            if i > 0 {
+                list.push(' ');
+            }
+            list.push('"');
+            list.push_str(component.name());
+            list.push('"');
+
+            base.push(b'"');
+            base.extend_from_slice(component.name().as_bytes());
+            base.extend_from_slice(b"\": ");
+            base.extend_from_slice(&component.value(req)?);
+            base.push(b'\n');
+        }
+        list.push(')');
+        list.push_str(&self.params(now)?);
+
+        base.extend_from_slice(b"\"@signature-params\": ");
+        base.extend_from_slice(list.as_bytes());
+        Ok((base, list))
+    }
+
+    fn sign_at<B>(
+        &self,
+        req: &mut Request<B>,
+        now: u64,
+    ) -> Result<(), Box<dyn StdError + Send + Sync>> {
+        let (base, params) = self.base(req, now)?;
+        let signature = self.key.sign(&base)?;
+
+        let input = HeaderValue::try_from(format!("{}={}", self.label, params))?;
+        let signature =
+            HeaderValue::try_from(format!("{}=:{}:", self.label, STANDARD.encode(signature)))?;
+        req.headers_mut().append(SIGNATURE_INPUT, input);
+        req.headers_mut().append(SIGNATURE, signature);
+        Ok(())
+    }
+
+    fn sign<B>(&self, req: &mut Request<B>) -> crate::Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.sign_at(req, now).map_err(crate::error::request)
+    }
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("label", &self.label)
+            .field("key_id", &self.key_id)
+            .field("components", &self.components)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Serialize a structured field String.
+fn sf_string(s: &str) -> Result<String, Box<dyn StdError + Send + Sync>> {
+    if !s.bytes().all(|b| (0x20..0x7f).contains(&b)) {
+        return Err(format!("invalid signature parameter: {s:?}").into());
+    }
+    Ok(format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+#[derive(Debug)]
+struct Missing {
+    name: String,
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "covered component {:?} is missing", self.name)
+    }
+}
+
+impl StdError for Missing {}
+
+// ===== impl SignatureService =====
+
+/// A [`Service`] that signs each request before passing it on.
+#[derive(Clone)]
+pub(crate) struct SignatureService<S> {
+    inner: S,
+    signer: Option<Arc<Signer>>,
+}
+
+impl<S> SignatureService<S> {
+    pub(crate) fn new(inner: S, signer: Option<Arc<Signer>>) -> Self {
+        SignatureService { inner, signer }
+    }
+}
+
+impl<S> Service<Request<Body>> for SignatureService<S>
+where
+    S: Service<Request<Body>, Error = crate::Error>,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = Either<Ready<Result<S::Response, crate::Error>>, S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if let Some(ref signer) = self.signer {
+            if let Err(err) = signer.sign(&mut req) {
+                return Either::Left(future::ready(Err(err)));
+            }
+        }
+        Either::Right(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREATED: u64 = 1618884473;
+
+    // From RFC 9421, Appendix B.2
+    fn test_request() -> Request<()> {
+        Request::post("https://example.com/foo?param=Value&Pet=dog")
+            .header("date", "Tue, 20 Apr 2021 02:07:55 GMT")
+            .header("content-type", "application/json")
+            .header("content-length", "18")
+            .body(())
+            .unwrap()
+    }
+
+    #[test]
+    fn hmac_sha256_rfc_example() {
+        let secret = STANDARD
+            .decode(
+                "uzvJfB4u3N0Jy4T7NZ75MDVcr8zSTInedJtkgcu46YW4XByzNJjxBdtjUkdJPBtbmHhIDi6pcl8jsasjlTMtDQ==",
+            )
+            .unwrap();
+        let mut req = test_request();
+        Signer::new(HmacSha256::new(&secret))
+            .label("sig-b25")
+            .key_id("test-shared-secret")
+            .components([
+                Component::header(http::header::DATE),
+                Component::authority(),
+                Component::header(http::header::CONTENT_TYPE),
+            ])
+            .sign_at(&mut req, CREATED)
+            .unwrap();
+
+        // The RFC example omits the `alg` parameter.
+        assert_eq!(
+            req.headers()["signature-input"],
+            "sig-b25=(\"date\" \"@authority\" \"content-type\");created=1618884473;alg=\"hmac-sha256\";keyid=\"test-shared-secret\""
+        );
+    }
+
+    #[test]
+    fn signature_base() {
+        let signer = Signer::new(HmacSha256::new(b"secret"))
+            .key_id("test-key-ed25519")
+            .components([
+                Component::header(http::header::DATE),
+                Component::method(),
+                Component::path(),
+                Component::authority(),
+                Component::header(http::header::CONTENT_TYPE),
+                Component::header(http::header::CONTENT_LENGTH),
+            ]);
+        let (base, _) = signer.base(&test_request(), CREATED).unwrap();
+        assert_eq!(
+            String::from_utf8(base).unwrap(),
+            "\"date\": Tue, 20 Apr 2021 02:07:55 GMT\n\
+             \"@method\": POST\n\
+             \"@path\": /foo\n\
+             \"@authority\": example.com\n\
+             \"content-type\": application/json\n\
+             \"content-length\": 18\n\
+             \"@signature-params\": (\"date\" \"@method\" \"@path\" \"@authority\" \"content-type\" \"content-length\");created=1618884473;alg=\"hmac-sha256\";keyid=\"test-key-ed25519\""
+        );
+    }
+
+    #[test]
+    fn ed25519_rfc_example() {
+        struct NoAlg(Ed25519);
+
+        impl SigningKey for NoAlg {
+            fn sign(&self, base: &[u8]) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>> {
+                self.0.sign(base)
+            }
+        }
+
+        let der = STANDARD
+            .decode("MC4CAQAwBQYDK2VwBCIEIJ+DYvh6SEqVTm50DFtMDoQikTmiCqirVv9mWG9qfSnF")
+            .unwrap();
+        let mut req = test_request();
+        Signer::new(NoAlg(Ed25519::from_pkcs8(&der).unwrap()))
+            .label("sig-b26")
+            .key_id("test-key-ed25519")
+            .components([
+                Component::header(http::header::DATE),
+                Component::method(),
+                Component::path(),
+                Component::authority(),
+                Component::header(http::header::CONTENT_TYPE),
+                Component::header(http::header::CONTENT_LENGTH),
+            ])
+            .sign_at(&mut req, CREATED)
+            .unwrap();
+
+        assert_eq!(
+            req.headers()["signature"],
+            "sig-b26=:wqcAqbmYJ2ji2glfAMaRy4gruYYnx2nEFN2HN6jrnDnQCK1u02Gb04v9EDgwUPiu4A0w6vuQv5lIp5WPpBKRCw==:"
+        );
+    }
+
+    #[test]
+    fn missing_header_fails() {
+        let mut req = test_request();
+        let err = Signer::new(HmacSha256::new(b"secret"))
+            .components([Component::header(http::header::AUTHORIZATION)])
+            .sign_at(&mut req, CREATED)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "covered component \"authorization\" is missing"
+        );
+    }
+
+    #[test]
+    fn default_components() {
+        let signer = Signer::new(HmacSha256::new(b"secret"));
+        let names = signer
+            .covered(&test_request())
+            .iter()
+            .map(|c| c.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["@method", "@target-uri", "content-type"]);
+    }
+
+    #[test]
+    fn authority_strips_default_port() {
+        let uri = "https://Example.com:443/".parse().unwrap();
+        assert_eq!(authority(&uri).unwrap(), "example.com");
+        let uri = "http://example.com:8080/".parse().unwrap();
+        assert_eq!(authority(&uri).unwrap(), "example.com:8080");
+    }
+}
@@ -0,0 +1,333 @@
+//! The TCP connector of a client.
+//!
+//! Connections are made by hyper-util's `HttpConnector`, which can only bind
+//! them to a network interface by its name. A connection bound by index
+//! looks the name of the interface up when it is made, so that it follows
+//! renames. On Windows, where the `HttpConnector` can't bind connections at
+//! all, the sockets bound by index are set up here, with the options the
+//! `HttpConnector` was given.
+
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::Uri;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+use crate::dns::DynResolver;
+use crate::error::BoxError;
+
+type HttpConnector = hyper_util::client::legacy::connect::HttpConnector<DynResolver>;
+
+/// The index of the network interface a request is bound to, overriding
+/// the one of the client.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct InterfaceIndexExtension(pub(crate) u32);
+
+#[derive(Clone)]
+pub(crate) struct TcpConnector {
+    http: HttpConnector,
+    options: Options,
+}
+
+/// What the `HttpConnector` was told, to set up bound sockets the same way,
+/// and the index of the interface they are bound to.
+#[derive(Clone)]
+#[cfg_attr(
+    not(all(feature = "interface-index", target_os = "windows")),
+    allow(dead_code)
+)]
+struct Options {
+    resolver: DynResolver,
+    enforce_http: bool,
+    local_address: Option<IpAddr>,
+    connect_timeout: Option<Duration>,
+    nodelay: bool,
+    keepalive: Option<Duration>,
+    keepalive_interval: Option<Duration>,
+    // which Windows sockets can't set
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    keepalive_retries: Option<u32>,
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    user_timeout: Option<Duration>,
+    interface_index: Option<u32>,
+}
+
+impl TcpConnector {
+    pub(crate) fn new(resolver: DynResolver) -> TcpConnector {
+        TcpConnector {
+            http: HttpConnector::new_with_resolver(resolver.clone()),
+            options: Options {
+                resolver,
+                enforce_http: true,
+                local_address: None,
+                connect_timeout: None,
+                nodelay: false,
+                keepalive: None,
+                keepalive_interval: None,
+                keepalive_retries: None,
+                #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+                user_timeout: None,
+                interface_index: None,
+            },
+        }
+    }
+
+    #[cfg(feature = "__tls")]
+    pub(crate) fn enforce_http(&mut self, enforce: bool) {
+        self.http.enforce_http(enforce);
+        self.options.enforce_http = enforce;
+    }
+
+    pub(crate) fn set_local_address(&mut self, addr: Option<IpAddr>) {
+        self.http.set_local_address(addr);
+        self.options.local_address = addr;
+    }
+
+    pub(crate) fn set_connect_timeout(&mut self, dur: Option<Duration>) {
+        self.http.set_connect_timeout(dur);
+        self.options.connect_timeout = dur;
+    }
+
+    pub(crate) fn set_nodelay(&mut self, nodelay: bool) {
+        self.http.set_nodelay(nodelay);
+        self.options.nodelay = nodelay;
+    }
+
+    pub(crate) fn set_keepalive(&mut self, dur: Option<Duration>) {
+        self.http.set_keepalive(dur);
+        self.options.keepalive = dur;
+    }
+
+    pub(crate) fn set_keepalive_interval(&mut self, dur: Option<Duration>) {
+        self.http.set_keepalive_interval(dur);
+        self.options.keepalive_interval = dur;
+    }
+
+    pub(crate) fn set_keepalive_retries(&mut self, retries: Option<u32>) {
+        self.http.set_keepalive_retries(retries);
+        self.options.keepalive_retries = retries;
+    }
+
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub(crate) fn set_tcp_user_timeout(&mut self, dur: Option<Duration>) {
+        self.http.set_tcp_user_timeout(dur);
+        self.options.user_timeout = dur;
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "illumos",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "solaris",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "watchos",
+    ))]
+    pub(crate) fn set_interface(&mut self, interface: String) {
+        self.http.set_interface(interface);
+    }
+
+    /// Bind the sockets to the network interface with this index, instead of
+    /// the one named with `set_interface`.
+    pub(crate) fn set_interface_index(&mut self, index: Option<u32>) {
+        self.options.interface_index = index;
+    }
+}
+
+impl Service<Uri> for TcpConnector {
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        cfg_interface_index! {{
+            #[cfg(not(target_os = "windows"))]
+            if let Some(index) = self.options.interface_index {
+                let Some(name) = interface_name(index) else {
+                    return Box::pin(async move {
+                        Err(format!("no network interface has the index {index}").into())
+                    });
+                };
+                let mut http = self.http.clone();
+                http.set_interface(name);
+                let connecting = http.call(dst);
+                return Box::pin(async move { connecting.await.map_err(Into::into) });
+            }
+            #[cfg(target_os = "windows")]
+            if let Some(index) = self.options.interface_index {
+                let options = self.options.clone();
+                return Box::pin(async move {
+                    let tcp = bound::connect(dst, index, &options).await?;
+                    Ok(TokioIo::new(tcp))
+                });
+            }
+        }}
+        let connecting = self.http.call(dst);
+        Box::pin(async move { connecting.await.map_err(Into::into) })
+    }
+}
+
+cfg_interface_index! {
+    /// The name of the network interface with the given index, if there is one.
+    #[cfg(not(target_os = "windows"))]
+    fn interface_name(index: u32) -> Option<String> {
+        let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+        // SAFETY: the buffer has the `IF_NAMESIZE` bytes the name can take,
+        // with its nul terminator.
+        let name = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+        if name.is_null() {
+            return None;
+        }
+        // SAFETY: on success, the buffer holds a nul-terminated name.
+        let name = unsafe { std::ffi::CStr::from_ptr(name) };
+        Some(name.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(all(feature = "interface-index", target_os = "windows"))]
+mod bound {
+    use std::io;
+    use std::net::{IpAddr, SocketAddr};
+
+    use http::uri::Scheme;
+    use http::Uri;
+    use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+    use tokio::net::{TcpSocket, TcpStream};
+
+    use super::Options;
+    use crate::error::BoxError;
+
+    /// Connect to the addresses of `dst` in turn, until one accepts, within
+    /// the connect timeout shared by all of them, as hyper-util does.
+    pub(super) async fn connect(
+        dst: Uri,
+        index: u32,
+        options: &Options,
+    ) -> Result<TcpStream, BoxError> {
+        if options.enforce_http && dst.scheme() != Some(&Scheme::HTTP) {
+            return Err("invalid URL, scheme is not http".into());
+        }
+        let host = dst.host().ok_or("invalid URL, host is missing")?;
+        let addrs = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            Ok(ip) => {
+                let port = dst.port_u16().unwrap_or(match dst.scheme() {
+                    Some(scheme) if *scheme == Scheme::HTTPS => 443,
+                    _ => 80,
+                });
+                vec![SocketAddr::new(ip, port)]
+            }
+            Err(_) => options
+                .resolver
+                .http_resolve(&dst)
+                .await?
+                .collect::<Vec<_>>(),
+        };
+
+        let timeout = options
+            .connect_timeout
+            .and_then(|timeout| timeout.checked_div(addrs.len() as u32));
+        let mut last_err = None;
+        for addr in addrs {
+            let connecting = connect_addr(addr, index, options);
+            let result = match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, connecting).await {
+                    Ok(result) => result,
+                    Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "connect timeout")),
+                },
+                None => connecting.await,
+            };
+            match result {
+                Ok(tcp) => return Ok(tcp),
+                Err(err) => {
+                    log::debug!("connect error for {addr}: {err}");
+                    last_err = Some(err);
+                }
+            }
+        }
+        match last_err {
+            Some(err) => Err(err.into()),
+            None => Err("dns resolved no addresses".into()),
+        }
+    }
+
+    async fn connect_addr(
+        addr: SocketAddr,
+        index: u32,
+        options: &Options,
+    ) -> io::Result<TcpStream> {
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+        bind_interface(&socket, addr, index)?;
+        if let Some(ip) = options
+            .local_address
+            .filter(|ip| ip.is_ipv4() == addr.is_ipv4())
+        {
+            socket.bind(&SocketAddr::new(ip, 0).into())?;
+        }
+        socket.set_nonblocking(true)?;
+
+        let tcp = TcpSocket::from_std_stream(socket.into())
+            .connect(addr)
+            .await?;
+        tcp.set_nodelay(options.nodelay)?;
+        if let Some(keepalive) = keepalive(options) {
+            SockRef::from(&tcp).set_tcp_keepalive(&keepalive)?;
+        }
+        Ok(tcp)
+    }
+
+    fn keepalive(options: &Options) -> Option<TcpKeepalive> {
+        let mut keepalive = TcpKeepalive::new();
+        let mut set = false;
+        if let Some(time) = options.keepalive {
+            keepalive = keepalive.with_time(time);
+            set = true;
+        }
+        if let Some(interval) = options.keepalive_interval {
+            keepalive = keepalive.with_interval(interval);
+            set = true;
+        }
+        set.then_some(keepalive)
+    }
+
+    /// Set `IP_UNICAST_IF` or `IPV6_UNICAST_IF`, which Windows uses to pick
+    /// the interface of outgoing packets.
+    fn bind_interface(socket: &Socket, addr: SocketAddr, index: u32) -> io::Result<()> {
+        use std::os::windows::io::AsRawSocket;
+        use windows_sys::Win32::Networking::WinSock::{
+            setsockopt, IPPROTO_IP, IPPROTO_IPV6, IPV6_UNICAST_IF, IP_UNICAST_IF, SOCKET_ERROR,
+        };
+
+        // The IPv4 option takes the index in network byte order.
+        let (level, name, index) = match addr {
+            SocketAddr::V4(_) => (IPPROTO_IP, IP_UNICAST_IF, index.to_be()),
+            SocketAddr::V6(_) => (IPPROTO_IPV6, IPV6_UNICAST_IF, index),
+        };
+        // SAFETY: the socket is open, and the value is the `u32` the options
+        // take.
+        let ret = unsafe {
+            setsockopt(
+                socket.as_raw_socket() as _,
+                level,
+                name,
+                (&index as *const u32).cast(),
+                std::mem::size_of::<u32>() as i32,
+            )
+        };
+        if ret == SOCKET_ERROR {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+//! A mock transport for unit tests.
+//!
+//! A [`Mock`] answers the requests of a `Client` with canned replies, without
+//! any network or local server. Each route pairs a [`Matcher`] with the
+//! [`Reply`] to send to the requests it matches.
+//!
+//! ```rust
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::test::{Matcher, Mock, Reply};
+//! use reqwest::Method;
+//!
+//! let mock = Mock::new();
+//! mock.route(
+//!     Matcher::new(Method::GET, "https://api.example.com/user").header("accept", "application/json"),
+//!     Reply::new(200).body(r#"{"name":"ferris"}"#),
+//! );
+//! mock.route(Matcher::any(), Reply::new(404));
+//!
+//! let client = mock.client();
+//! let res = client
+//!     .get("https://api.example.com/user")
+//!     .header("accept", "application/json")
+//!     .send()
+//!     .await?;
+//! assert_eq!(res.text().await?, r#"{"name":"ferris"}"#);
+//! assert_eq!(mock.received().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The `Client` still handles requests and responses as usual, so default
+//! headers, redirects, cookies and timeouts all apply to mocked requests.
+//!
+//! # Optional
+//!
+//! This requires the optional `test-util` feature to be enabled.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http_body_util::BodyExt;
+use tower_service::Service;
+
+use crate::{Body, Client, Method, StatusCode, Url};
+
+/// A transport answering requests with canned replies.
+///
+/// Clones share the same routes and received requests.
+#[derive(Clone, Default)]
+pub struct Mock {
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    routes: Vec<(Matcher, Reply)>,
+    received: Vec<http::Request<Bytes>>,
+}
+
+/// Which requests a route applies to.
+#[derive(Clone, Debug, Default)]
+pub struct Matcher {
+    method: Option<Method>,
+    url: Option<String>,
+    path: Option<String>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: Option<Bytes>,
+}
+
+/// The reply to the requests of a route.
+#[derive(Clone, Debug)]
+pub struct Reply {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    delay: Option<Duration>,
+    error: Option<String>,
+}
+
+impl Mock {
+    /// Create a mock without routes.
+    pub fn new() -> Mock {
+        Mock::default()
+    }
+
+    /// Add a route.
+    ///
+    /// Routes are tried in the order they were added, and the first one
+    /// matching a request replies to it. A request that matches no route
+    /// fails with a request error.
+    pub fn route(&self, matcher: Matcher, reply: Reply) -> &Mock {
+        self.shared.lock().unwrap().routes.push((matcher, reply));
+        self
+    }
+
+    /// The requests received so far, with their bodies.
+    pub fn received(&self) -> Vec<http::Request<Bytes>> {
+        self.shared.lock().unwrap().received.clone()
+    }
+
+    /// Build a `Client` using this mock.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the `Client` cannot be built. Use
+    /// [`ClientBuilder::http_service`](crate::ClientBuilder::http_service)
+    /// to configure the `Client` further.
+    pub fn client(&self) -> Client {
+        Client::builder()
+            .http_service(self.clone())
+            .build()
+            .expect("Client::builder() with a mock")
+    }
+
+    fn reply(&self, req: http::Request<Bytes>) -> Result<Reply, NoRoute> {
+        let mut shared = self.shared.lock().unwrap();
+        let reply = shared
+            .routes
+            .iter()
+            .find(|(matcher, _)| matcher.matches(&req))
+            .map(|(_, reply)| reply.clone());
+        let no_route = NoRoute {
+            method: req.method().clone(),
+            uri: req.uri().to_string(),
+        };
+        shared.received.push(req);
+        reply.ok_or(no_route)
+    }
+}
+
+impl fmt::Debug for Mock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shared = self.shared.lock().unwrap();
+        f.debug_struct("Mock")
+            .field("routes", &shared.routes.len())
+            .field("received", &shared.received.len())
+            .finish()
+    }
+}
+
+impl Service<http::Request<Body>> for Mock {
+    type Response = http::Response<Body>;
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = crate::Result<http::Response<Body>>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let mock = self.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = body.collect().await?.to_bytes();
+            let reply = mock
+                .reply(http::Request::from_parts(parts, body))
+                .map_err(crate::error::request)?;
+
+            if let Some(delay) = reply.delay {
+                tokio::time::sleep(delay).await;
+            }
+            if let Some(error) = reply.error {
+                return Err(crate::error::request(error));
+            }
+            let mut res = http::Response::new(Body::from(reply.body));
+            *res.status_mut() = reply.status;
+            *res.headers_mut() = reply.headers;
+            Ok(res)
+        })
+    }
+}
+
+impl Matcher {
+    /// Match every request.
+    pub fn any() -> Matcher {
+        Matcher::default()
+    }
+
+    /// Match requests with this method and URL.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `url` is not a valid URL.
+    pub fn new(method: Method, url: &str) -> Matcher {
+        Matcher::any().method(method).url(url)
+    }
+
+    /// Only match requests with this method.
+    pub fn method(mut self, method: Method) -> Matcher {
+        self.method = Some(method);
+        self
+    }
+
+    /// Only match requests to this URL, including its query.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `url` is not a valid URL.
+    pub fn url(mut self, url: &str) -> Matcher {
+        let url = Url::parse(url).expect("Matcher::url() with an invalid URL");
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Only match requests to this path, whatever their host and query.
+    pub fn path(mut self, path: &str) -> Matcher {
+        self.path = Some(path.to_owned());
+        self
+    }
+
+    /// Only match requests with this header value.
+    ///
+    /// A request may have other values for the same header.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `name` or `value` are not valid.
+    pub fn header(mut self, name: &str, value: &str) -> Matcher {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("Matcher::header() name");
+        let value = HeaderValue::from_str(value).expect("Matcher::header() value");
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Only match requests with this body.
+    pub fn body<B: Into<Bytes>>(mut self, body: B) -> Matcher {
+        self.body = Some(body.into());
+        self
+    }
+
+    fn matches(&self, req: &http::Request<Bytes>) -> bool {
+        if self.method.as_ref().is_some_and(|m| m != req.method()) {
+            return false;
+        }
+        if self.url.as_ref().is_some_and(|url| *url != req.uri().to_string()) {
+            return false;
+        }
+        if self.path.as_ref().is_some_and(|path| path != req.uri().path()) {
+            return false;
+        }
+        let has_headers = self.headers.iter().all(|(name, value)| {
+            req.headers()
+                .get_all(name)
+                .iter()
+                .any(|candidate| candidate == value)
+        });
+        has_headers && self.body.as_ref().is_none_or(|body| body == req.body())
+    }
+}
+
+impl Reply {
+    /// A reply with this status, and no headers or body.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `status` is not between 100 and 999.
+    pub fn new(status: u16) -> Reply {
+        Reply {
+            status: StatusCode::from_u16(status).expect("Reply::new() with an invalid status"),
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            delay: None,
+            error: None,
+        }
+    }
+
+    /// A reply that fails the request with a request error, as if the
+    /// connection failed.
+    pub fn error(message: &str) -> Reply {
+        Reply {
+            error: Some(message.to_owned()),
+            ..Reply::new(500)
+        }
+    }
+
+    /// Add a header to the reply.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `name` or `value` are not valid.
+    pub fn header(mut self, name: &str, value: &str) -> Reply {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("Reply::header() name");
+        let value = HeaderValue::from_str(value).expect("Reply::header() value");
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Set the body of the reply.
+    pub fn body<B: Into<Bytes>>(mut self, body: B) -> Reply {
+        self.body = body.into();
+        self
+    }
+
+    /// Wait before replying, or failing.
+    pub fn delay(mut self, delay: Duration) -> Reply {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// No route matched a request.
+#[derive(Debug)]
+struct NoRoute {
+    method: Method,
+    uri: String,
+}
+
+impl fmt::Display for NoRoute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no mock route for {} {}", self.method, self.uri)
+    }
+}
+
+impl StdError for NoRoute {}
@@ -82,6 +82,7 @@ use std::{
 
 /// Represents a X509 certificate revocation list.
 #[cfg(feature = "__rustls")]
+#[derive(Clone)]
 pub struct CertificateRevocationList {
     #[cfg(feature = "__rustls")]
     inner: rustls_pki_types::CertificateRevocationListDer<'static>,
@@ -585,6 +586,7 @@ impl Version {
     }
 }
 
+#[derive(Clone)]
 pub(crate) enum TlsBackend {
     // This is the default and HTTP/3 feature does not use it so suppress it.
     #[allow(dead_code)]
@@ -804,6 +806,130 @@ impl std::fmt::Debug for TlsInfo {
     }
 }
 
+/// Why a TLS handshake failed.
+///
+/// Returned by [`Error::tls_failure`](crate::Error::tls_failure), in the
+/// same terms for each TLS backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TlsFailure {
+    /// The peer sent a fatal alert, with its code from the TLS
+    /// specification, such as `40` for `handshake_failure`.
+    Alert(u8),
+    /// The certificate of the peer was refused.
+    Certificate(CertificateFailure),
+    /// The peer doesn't support any of the enabled TLS versions.
+    UnsupportedProtocol,
+    /// Another failure, or one the backend doesn't tell apart.
+    ///
+    /// The `native-tls` backend only describes its failures in their
+    /// message.
+    Other,
+}
+
+/// Why the certificate of a peer was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CertificateFailure {
+    /// The certificate has expired.
+    Expired,
+    /// The certificate isn't valid yet.
+    NotValidYet,
+    /// The certificate wasn't issued by a trusted authority.
+    UnknownIssuer,
+    /// The certificate was revoked.
+    Revoked,
+    /// The certificate isn't valid for the name of the host.
+    NotValidForName,
+    /// The signature of the certificate is invalid.
+    BadSignature,
+    /// Another reason.
+    Other,
+}
+
+impl TlsFailure {
+    #[cfg(feature = "__rustls")]
+    pub(crate) fn from_rustls(err: &rustls::Error) -> TlsFailure {
+        use rustls::{AlertDescription, CertificateError, PeerIncompatible};
+
+        match err {
+            rustls::Error::AlertReceived(AlertDescription::ProtocolVersion) => {
+                TlsFailure::UnsupportedProtocol
+            }
+            rustls::Error::AlertReceived(alert) => TlsFailure::Alert(u8::from(*alert)),
+            rustls::Error::PeerIncompatible(
+                PeerIncompatible::ServerDoesNotSupportTls12Or13
+                | PeerIncompatible::SupportedVersionsExtensionRequired
+                | PeerIncompatible::Tls12NotOffered
+                | PeerIncompatible::Tls12NotOfferedOrEnabled,
+            ) => TlsFailure::UnsupportedProtocol,
+            rustls::Error::InvalidCertificate(cert) => TlsFailure::Certificate(match cert {
+                CertificateError::Expired | CertificateError::ExpiredContext { .. } => {
+                    CertificateFailure::Expired
+                }
+                CertificateError::NotValidYet | CertificateError::NotValidYetContext { .. } => {
+                    CertificateFailure::NotValidYet
+                }
+                CertificateError::UnknownIssuer => CertificateFailure::UnknownIssuer,
+                CertificateError::Revoked => CertificateFailure::Revoked,
+                CertificateError::NotValidForName
+                | CertificateError::NotValidForNameContext { .. } => {
+                    CertificateFailure::NotValidForName
+                }
+                CertificateError::BadSignature => CertificateFailure::BadSignature,
+                _ => CertificateFailure::Other,
+            }),
+            _ => TlsFailure::Other,
+        }
+    }
+}
+
+/// New certificates for a running `Client`.
+///
+/// Passed to [`Client::reload_tls`](crate::Client::reload_tls). What isn't
+/// set keeps its current value.
+#[derive(Clone, Debug, Default)]
+pub struct TlsReload {
+    pub(crate) root_certs: Option<Vec<Certificate>>,
+    pub(crate) identity: Option<Identity>,
+    pub(crate) drain: bool,
+}
+
+impl TlsReload {
+    /// Create a reload which changes nothing yet.
+    pub fn new() -> TlsReload {
+        TlsReload::default()
+    }
+
+    /// Replace the root certificates added with
+    /// [`ClientBuilder::tls_certs_merge`](crate::ClientBuilder::tls_certs_merge)
+    /// or [`ClientBuilder::tls_certs_only`](crate::ClientBuilder::tls_certs_only).
+    ///
+    /// Whether the built-in roots are trusted as well doesn't change.
+    pub fn root_certificates(mut self, certs: impl IntoIterator<Item = Certificate>) -> TlsReload {
+        self.root_certs = Some(certs.into_iter().collect());
+        self
+    }
+
+    /// Replace the identity used for client certificate authentication.
+    pub fn identity(mut self, identity: Identity) -> TlsReload {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Set whether the connections already open are drained.
+    ///
+    /// Drained connections are no longer reused: they finish the requests
+    /// they are sending, and close. Otherwise, they are reused until they
+    /// close on their own, such as after the idle timeout of the pool.
+    ///
+    /// Default is `false`.
+    pub fn drain(mut self, drain: bool) -> TlsReload {
+        self.drain = drain;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -908,4 +1034,44 @@ mod tests {
         let result = result.unwrap();
         assert_eq!(result.len(), 1);
     }
+
+    #[cfg(feature = "__rustls")]
+    #[test]
+    fn tls_failure_from_rustls() {
+        use rustls::{AlertDescription, CertificateError, PeerIncompatible};
+
+        let failure = |err| TlsFailure::from_rustls(&err);
+        assert_eq!(
+            failure(rustls::Error::AlertReceived(
+                AlertDescription::HandshakeFailure
+            )),
+            TlsFailure::Alert(40)
+        );
+        assert_eq!(
+            failure(rustls::Error::AlertReceived(
+                AlertDescription::ProtocolVersion
+            )),
+            TlsFailure::UnsupportedProtocol
+        );
+        assert_eq!(
+            failure(rustls::Error::PeerIncompatible(
+                PeerIncompatible::ServerDoesNotSupportTls12Or13
+            )),
+            TlsFailure::UnsupportedProtocol
+        );
+        assert_eq!(
+            failure(rustls::Error::InvalidCertificate(CertificateError::Expired)),
+            TlsFailure::Certificate(CertificateFailure::Expired)
+        );
+        assert_eq!(
+            failure(rustls::Error::InvalidCertificate(
+                CertificateError::BadEncoding
+            )),
+            TlsFailure::Certificate(CertificateFailure::Other)
+        );
+        assert_eq!(
+            failure(rustls::Error::HandshakeNotComplete),
+            TlsFailure::Other
+        );
+    }
 }
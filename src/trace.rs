@@ -0,0 +1,216 @@
+//! W3C trace context propagation.
+//!
+//! A `Client` configured with
+//! [`ClientBuilder::propagate_trace_context`](crate::ClientBuilder::propagate_trace_context)
+//! sends the current trace context in the `traceparent` and `tracestate`
+//! headers of every request, following the [W3C Trace Context][spec]
+//! specification, and records the status of the response on the current
+//! [`tracing`] span.
+//!
+//! The trace context is provided by a function, so it can be taken from
+//! whichever tracing system is in use, such as OpenTelemetry.
+//!
+//! ```rust
+//! use reqwest::trace::TraceContext;
+//!
+//! let client = reqwest::Client::builder()
+//!     .propagate_trace_context(|| {
+//!         // look up the context of the current span
+//!         Some(TraceContext::new(0x4bf92f3577b34da6a3ce929d0e0e4736, 0x00f067aa0ba902b7, true))
+//!     })
+//!     .build();
+//! ```
+//!
+//! The current span is the one entered when the request is sent. The status
+//! is recorded in its `http.response.status_code` field, which the span must
+//! declare for it to be kept:
+//!
+//! ```rust
+//! # async fn run(client: reqwest::Client) -> Result<(), reqwest::Error> {
+//! use tracing::Instrument;
+//!
+//! let span = tracing::info_span!("fetch", http.response.status_code = tracing::field::Empty);
+//! let res = async { client.get("https://hyper.rs").send().await }
+//!     .instrument(span)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Optional
+//!
+//! This requires the optional `tracing` feature to be enabled.
+//!
+//! [spec]: https://www.w3.org/TR/trace-context/
+
+use std::fmt;
+use std::sync::Arc;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http::StatusCode;
+
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+
+/// The trace context of a request.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: u128,
+    span_id: u64,
+    sampled: bool,
+    state: Option<HeaderValue>,
+}
+
+impl TraceContext {
+    /// Create a trace context for a span of a trace.
+    ///
+    /// A context with a zero trace or span ID is invalid, and isn't sent.
+    pub fn new(trace_id: u128, span_id: u64, sampled: bool) -> TraceContext {
+        TraceContext {
+            trace_id,
+            span_id,
+            sampled,
+            state: None,
+        }
+    }
+
+    /// Set the vendor-specific `tracestate` to send with the context.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `state` isn't a valid header value.
+    pub fn with_state(mut self, state: &str) -> crate::Result<TraceContext> {
+        let state = HeaderValue::from_str(state).map_err(crate::error::builder)?;
+        self.state = Some(state);
+        Ok(self)
+    }
+
+    /// The ID of the trace.
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// The ID of the span making the request.
+    pub fn span_id(&self) -> u64 {
+        self.span_id
+    }
+
+    /// Whether the trace is sampled.
+    pub fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    /// The vendor-specific `tracestate`, if any.
+    pub fn state(&self) -> Option<&str> {
+        self.state.as_ref().and_then(|state| state.to_str().ok())
+    }
+
+    fn is_valid(&self) -> bool {
+        self.trace_id != 0 && self.span_id != 0
+    }
+
+    fn traceparent(&self) -> HeaderValue {
+        let value = format!(
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.span_id, self.sampled as u8
+        );
+        HeaderValue::from_str(&value).expect("traceparent is a valid header value")
+    }
+}
+
+impl fmt::Debug for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TraceContext")
+            .field("trace_id", &format_args!("{:032x}", self.trace_id))
+            .field("span_id", &format_args!("{:016x}", self.span_id))
+            .field("sampled", &self.sampled)
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+pub(crate) type CurrentContext = Arc<dyn Fn() -> Option<TraceContext> + Send + Sync>;
+
+/// Add the current trace context to the headers, unless the request already
+/// has one.
+pub(crate) fn inject(current: &CurrentContext, headers: &mut HeaderMap) {
+    if headers.contains_key(TRACEPARENT) {
+        return;
+    }
+    let Some(cx) = current() else {
+        return;
+    };
+    if !cx.is_valid() {
+        return;
+    }
+    headers.insert(TRACEPARENT, cx.traceparent());
+    if let Some(state) = cx.state {
+        headers.insert(TRACESTATE, state);
+    }
+}
+
+/// The span a request was sent from, when it is sent from another thread.
+#[derive(Clone)]
+pub(crate) struct CallerSpan(pub(crate) tracing::Span);
+
+/// The span the request with these extensions was sent from.
+pub(crate) fn caller_span(extensions: &http::Extensions) -> tracing::Span {
+    match extensions.get::<CallerSpan>() {
+        Some(caller) => caller.0.clone(),
+        None => tracing::Span::current(),
+    }
+}
+
+pub(crate) fn record_status(span: &tracing::Span, status: StatusCode) {
+    span.record("http.response.status_code", status.as_u16());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn injected(cx: Option<TraceContext>) -> HeaderMap {
+        let current: CurrentContext = Arc::new(move || cx.clone());
+        let mut headers = HeaderMap::new();
+        inject(&current, &mut headers);
+        headers
+    }
+
+    #[test]
+    fn traceparent_format() {
+        let cx = TraceContext::new(0x4bf92f3577b34da6a3ce929d0e0e4736, 0x00f067aa0ba902b7, true);
+        assert_eq!(
+            cx.traceparent(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+
+        let cx = TraceContext::new(1, 2, false);
+        assert_eq!(
+            cx.traceparent(),
+            "00-00000000000000000000000000000001-0000000000000002-00"
+        );
+    }
+
+    #[test]
+    fn inject_state() {
+        let cx = TraceContext::new(1, 2, true).with_state("a=1,b=2").unwrap();
+        let headers = injected(Some(cx));
+        assert_eq!(headers[TRACESTATE], "a=1,b=2");
+
+        let headers = injected(Some(TraceContext::new(1, 2, true)));
+        assert!(headers.contains_key(TRACEPARENT));
+        assert!(!headers.contains_key(TRACESTATE));
+    }
+
+    #[test]
+    fn invalid_context_is_not_sent() {
+        assert!(injected(Some(TraceContext::new(0, 2, true))).is_empty());
+        assert!(injected(Some(TraceContext::new(1, 0, true))).is_empty());
+        assert!(injected(None).is_empty());
+    }
+
+    #[test]
+    fn invalid_state() {
+        assert!(TraceContext::new(1, 2, true).with_state("a\n").is_err());
+    }
+}
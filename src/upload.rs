@@ -0,0 +1,478 @@
+//! Uploading large bodies in chunks.
+//!
+//! [`Client::upload`] sends a file, or bytes, in chunks of a fixed size, so
+//! that a chunk that fails is sent again instead of the whole body, and an
+//! upload that was interrupted can continue later. Two protocols are
+//! supported:
+//!
+//! - [`Protocol::ContentRange`] sends each chunk in a `PUT` request to the
+//!   upload URL, with a `Content-Range` header saying where it goes, like
+//!   the resumable uploads of many storage services. The server answers the
+//!   chunks before the last one with `308 Resume Incomplete`, or a `2xx`
+//!   status.
+//! - [`Protocol::Tus`] follows the [tus] protocol: the upload is created
+//!   with a `POST` request to the URL, and the chunks are sent in order with
+//!   `PATCH` requests to the `Location` of the upload.
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::upload::Protocol;
+//!
+//! let client = reqwest::Client::new();
+//! let upload = client
+//!     .upload("https://tus.example.com/files/")
+//!     .file("video.mp4")
+//!     .protocol(Protocol::Tus)
+//!     .send()
+//!     .await?;
+//! println!("uploaded to {}", upload.url());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With [`resume`](UploadBuilder::resume), the URL is the one of an upload
+//! started earlier. The server is first asked how much of the body it has,
+//! with a `HEAD` request for tus, or an empty `PUT` request with a
+//! `Content-Range: bytes */<length>` header, and only the rest is sent. When
+//! an upload fails after it was created, [`Error::url`](crate::Error::url)
+//! is the URL to resume.
+//!
+//! # Optional
+//!
+//! This requires the optional `upload` feature to be enabled.
+//!
+//! [tus]: https://tus.io/protocols/resumable-upload
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures_util::stream::{self, TryStreamExt};
+use http::header::{HeaderMap, HeaderName, CONTENT_RANGE, CONTENT_TYPE, LOCATION, RANGE};
+use http::StatusCode;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::util::is_transient;
+use crate::{Client, Response, Url};
+
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+const DEFAULT_RETRIES: u32 = 3;
+
+const TUS_VERSION: &str = "1.0.0";
+static TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+static UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+static UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+
+/// A builder to upload a body in chunks.
+///
+/// Created with [`Client::upload`].
+#[must_use = "UploadBuilder does nothing until you 'send' it"]
+#[derive(Debug)]
+pub struct UploadBuilder {
+    client: Client,
+    url: crate::Result<Url>,
+    source: Option<Source>,
+    protocol: Protocol,
+    chunk_size: usize,
+    concurrency: usize,
+    retries: u32,
+    resume: bool,
+}
+
+/// How the chunks of an upload are sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Protocol {
+    /// `PUT` each chunk to the URL, with a `Content-Range` header.
+    #[default]
+    ContentRange,
+    /// The [tus](https://tus.io/protocols/resumable-upload) protocol,
+    /// version 1.0.0.
+    Tus,
+}
+
+/// A completed upload.
+#[derive(Clone, Debug)]
+pub struct Upload {
+    url: Url,
+    len: u64,
+    resumed_from: u64,
+}
+
+#[derive(Debug)]
+enum Source {
+    File(PathBuf),
+    Bytes(Bytes),
+}
+
+impl UploadBuilder {
+    pub(crate) fn new(client: Client, url: crate::Result<Url>) -> UploadBuilder {
+        UploadBuilder {
+            client,
+            url,
+            source: None,
+            protocol: Protocol::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: 1,
+            retries: DEFAULT_RETRIES,
+            resume: false,
+        }
+    }
+
+    /// Upload the content of a file.
+    ///
+    /// Chunks are read from the file when they are sent, so it should not
+    /// change during the upload.
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> UploadBuilder {
+        self.source = Some(Source::File(path.as_ref().to_owned()));
+        self
+    }
+
+    /// Upload bytes.
+    pub fn bytes<B: Into<Bytes>>(mut self, bytes: B) -> UploadBuilder {
+        self.source = Some(Source::Bytes(bytes.into()));
+        self
+    }
+
+    /// Set the protocol of the upload.
+    ///
+    /// Default is [`Protocol::ContentRange`].
+    pub fn protocol(mut self, protocol: Protocol) -> UploadBuilder {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set the size of the chunks, in bytes.
+    ///
+    /// Default is 8 MiB.
+    pub fn chunk_size(mut self, size: usize) -> UploadBuilder {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Send up to this many chunks at the same time.
+    ///
+    /// This only applies to [`Protocol::ContentRange`], and to servers that
+    /// accept chunks in any order. Chunks of a tus upload are always sent
+    /// one after the other.
+    ///
+    /// Default is `1`.
+    pub fn concurrency(mut self, concurrency: usize) -> UploadBuilder {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how many times each chunk is sent again after failing.
+    ///
+    /// Chunks are retried after errors sending the request, and after `5xx`
+    /// statuses.
+    ///
+    /// Default is `3`.
+    pub fn retries(mut self, retries: u32) -> UploadBuilder {
+        self.retries = retries;
+        self
+    }
+
+    /// Continue an upload started earlier at the URL.
+    ///
+    /// See the [module documentation](self) for how this works.
+    ///
+    /// Default is `false`.
+    pub fn resume(mut self, enable: bool) -> UploadBuilder {
+        self.resume = enable;
+        self
+    }
+
+    /// Upload the body.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the file cannot be read, if a chunk still fails after
+    /// its retries, or if the server answers in a way the protocol doesn't
+    /// allow.
+    pub async fn send(self) -> crate::Result<Upload> {
+        let url = self.url?;
+        let source = self
+            .source
+            .ok_or_else(|| crate::error::builder("no file or bytes to upload"))?;
+        let uploader = Uploader {
+            client: &self.client,
+            len: source.len().await?,
+            source,
+            chunk_size: self.chunk_size as u64,
+            retries: self.retries,
+        };
+        match self.protocol {
+            Protocol::ContentRange => {
+                uploader
+                    .put_chunks(url, self.resume, self.concurrency)
+                    .await
+            }
+            Protocol::Tus => uploader.tus(url, self.resume).await,
+        }
+    }
+}
+
+impl Upload {
+    /// The URL the body was uploaded to.
+    ///
+    /// With tus, this is the `Location` of the upload.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The length of the body.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// How many bytes the server already had from an earlier attempt.
+    pub fn resumed_from(&self) -> u64 {
+        self.resumed_from
+    }
+}
+
+impl Source {
+    async fn len(&self) -> crate::Result<u64> {
+        match self {
+            Source::File(path) => {
+                let metadata = tokio::fs::metadata(path)
+                    .await
+                    .map_err(crate::error::body)?;
+                Ok(metadata.len())
+            }
+            Source::Bytes(bytes) => Ok(bytes.len() as u64),
+        }
+    }
+
+    async fn chunk(&self, start: u64, len: u64) -> crate::Result<Bytes> {
+        match self {
+            Source::File(path) => {
+                let mut file = File::open(path).await.map_err(crate::error::body)?;
+                file.seek(SeekFrom::Start(start))
+                    .await
+                    .map_err(crate::error::body)?;
+                let mut buf = vec![0; len as usize];
+                file.read_exact(&mut buf)
+                    .await
+                    .map_err(crate::error::body)?;
+                Ok(buf.into())
+            }
+            Source::Bytes(bytes) => Ok(bytes.slice(start as usize..(start + len) as usize)),
+        }
+    }
+}
+
+struct Uploader<'a> {
+    client: &'a Client,
+    source: Source,
+    len: u64,
+    chunk_size: u64,
+    retries: u32,
+}
+
+impl Uploader<'_> {
+    // ===== Content-Range =====
+
+    async fn put_chunks(
+        &self,
+        url: Url,
+        resume: bool,
+        concurrency: usize,
+    ) -> crate::Result<Upload> {
+        if self.len == 0 {
+            let res = self
+                .client
+                .put(url.clone())
+                .header(CONTENT_RANGE, "bytes */0")
+                .send()
+                .await?;
+            accepted(res)?;
+            return Ok(self.finished(url, 0));
+        }
+
+        let received = match resume {
+            true => self.received(&url).await?,
+            false => 0,
+        };
+        let chunks = (received..self.len)
+            .step_by(self.chunk_size as usize)
+            .map(|start| Ok((start, self.chunk_size.min(self.len - start))));
+        stream::iter(chunks)
+            .try_for_each_concurrent(concurrency, |(start, len)| self.put_chunk(&url, start, len))
+            .await?;
+        Ok(self.finished(url, received))
+    }
+
+    async fn put_chunk(&self, url: &Url, start: u64, len: u64) -> crate::Result<()> {
+        let range = format!("bytes {start}-{}/{}", start + len - 1, self.len);
+        let mut attempts = 0;
+        loop {
+            let result = async {
+                let chunk = self.source.chunk(start, len).await?;
+                let res = self
+                    .client
+                    .put(url.clone())
+                    .header(CONTENT_RANGE, range.as_str())
+                    .body(chunk)
+                    .send()
+                    .await?;
+                accepted(res)
+            };
+            match result.await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempts < self.retries && is_transient(&err) => {
+                    attempts += 1;
+                    log::debug!("chunk {range} to {url} failed, retrying: {err}");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Ask the server how many bytes of the body it has.
+    async fn received(&self, url: &Url) -> crate::Result<u64> {
+        let res = self
+            .client
+            .put(url.clone())
+            .header(CONTENT_RANGE, format!("bytes */{}", self.len))
+            .send()
+            .await?;
+        if res.status() != StatusCode::PERMANENT_REDIRECT {
+            // The upload is complete.
+            res.error_for_status()?;
+            return Ok(self.len);
+        }
+        let Some(range) = res.headers().get(RANGE) else {
+            return Ok(0);
+        };
+        range
+            .to_str()
+            .ok()
+            .and_then(|range| range.strip_prefix("bytes=0-")?.parse::<u64>().ok())
+            .map(|end| end + 1)
+            .filter(|&received| received <= self.len)
+            .ok_or_else(|| crate::error::decode(Unexpected("Range")).with_url(url.clone()))
+    }
+
+    // ===== tus =====
+
+    async fn tus(&self, url: Url, resume: bool) -> crate::Result<Upload> {
+        let (location, mut offset) = match resume {
+            true => {
+                let offset = self.tus_offset(&url).await?;
+                (url, offset)
+            }
+            false => (self.tus_create(&url).await?, 0),
+        };
+        let resumed_from = offset;
+
+        let mut attempts = 0;
+        while offset < self.len {
+            let len = self.chunk_size.min(self.len - offset);
+            match self.tus_patch(&location, offset, len).await {
+                Ok(next) => {
+                    offset = next;
+                    attempts = 0;
+                }
+                Err(err) if attempts < self.retries && is_transient(&err) => {
+                    attempts += 1;
+                    log::debug!("chunk at {offset} to {location} failed, retrying: {err}");
+                    // The server may have kept part of the chunk.
+                    offset = self.tus_offset(&location).await?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(self.finished(location, resumed_from))
+    }
+
+    async fn tus_create(&self, url: &Url) -> crate::Result<Url> {
+        let res = self
+            .client
+            .post(url.clone())
+            .header(&TUS_RESUMABLE, TUS_VERSION)
+            .header(&UPLOAD_LENGTH, self.len)
+            .send()
+            .await?
+            .error_for_status()?;
+        res.headers()
+            .get(LOCATION)
+            .and_then(|location| url.join(location.to_str().ok()?).ok())
+            .ok_or_else(|| crate::error::decode(Unexpected("Location")).with_url(url.clone()))
+    }
+
+    async fn tus_offset(&self, location: &Url) -> crate::Result<u64> {
+        let res = self
+            .client
+            .head(location.clone())
+            .header(&TUS_RESUMABLE, TUS_VERSION)
+            .send()
+            .await?
+            .error_for_status()?;
+        upload_offset(res.headers())
+            .filter(|&offset| offset <= self.len)
+            .ok_or_else(|| {
+                crate::error::decode(Unexpected("Upload-Offset")).with_url(location.clone())
+            })
+    }
+
+    /// Send a chunk, returning the new offset.
+    async fn tus_patch(&self, location: &Url, offset: u64, len: u64) -> crate::Result<u64> {
+        let chunk = self.source.chunk(offset, len).await?;
+        let res = self
+            .client
+            .patch(location.clone())
+            .header(&TUS_RESUMABLE, TUS_VERSION)
+            .header(&UPLOAD_OFFSET, offset)
+            .header(CONTENT_TYPE, "application/offset+octet-stream")
+            .body(chunk)
+            .send()
+            .await?
+            .error_for_status()?;
+        upload_offset(res.headers())
+            .filter(|&next| next > offset && next <= offset + len)
+            .ok_or_else(|| {
+                crate::error::decode(Unexpected("Upload-Offset")).with_url(location.clone())
+            })
+    }
+
+    fn finished(&self, url: Url, resumed_from: u64) -> Upload {
+        Upload {
+            url,
+            len: self.len,
+            resumed_from,
+        }
+    }
+}
+
+/// Check the answer to a chunk sent with `Content-Range`.
+fn accepted(res: Response) -> crate::Result<()> {
+    if res.status() == StatusCode::PERMANENT_REDIRECT {
+        return Ok(());
+    }
+    res.error_for_status().map(drop)
+}
+
+fn upload_offset(headers: &HeaderMap) -> Option<u64> {
+    headers.get(&UPLOAD_OFFSET)?.to_str().ok()?.parse().ok()
+}
+
+/// A response header was missing or had an unexpected value.
+#[derive(Debug)]
+struct Unexpected(&'static str);
+
+impl fmt::Display for Unexpected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "missing or unexpected {} header in upload response",
+            self.0
+        )
+    }
+}
+
+impl StdError for Unexpected {}
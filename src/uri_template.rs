@@ -0,0 +1,461 @@
+//! URI templates.
+//!
+//! A [`UriTemplate`] builds a URL from a template ([RFC 6570]) and the values
+//! of its variables, percent-encoding each of them as needed where it is
+//! expanded. It can be used wherever a URL is expected:
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::uri_template::UriTemplate;
+//!
+//! let url = UriTemplate::new("https://api.example.com/users/{id}/repos{?page,per_page}")
+//!     .var("id", "jane doe")
+//!     .var("page", 2);
+//! // GET https://api.example.com/users/jane%20doe/repos?page=2
+//! let res = reqwest::Client::new().get(url).send().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! All the expressions of the RFC are supported, up to level 4: the
+//! operators `+`, `#`, `.`, `/`, `;`, `?` and `&`, prefixes such as
+//! `{var:3}`, and lists and maps, exploded with `{var*}`. A variable without
+//! a value, like `per_page` above, is left out.
+//!
+//! [RFC 6570]: https://www.rfc-editor.org/rfc/rfc6570
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use url::Url;
+
+use crate::into_url::{IntoUrl, IntoUrlSealed};
+
+/// A URI template, and the values of its variables.
+#[derive(Clone, Debug)]
+pub struct UriTemplate {
+    template: String,
+    vars: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+    String(String),
+    List(Vec<String>),
+    Map(Vec<(String, String)>),
+}
+
+/// How the values of an expression are joined, after its operator.
+struct Operator {
+    first: &'static str,
+    sep: &'static str,
+    named: bool,
+    ifemp: &'static str,
+    reserved: bool,
+}
+
+const SIMPLE: Operator = Operator {
+    first: "",
+    sep: ",",
+    named: false,
+    ifemp: "",
+    reserved: false,
+};
+
+enum Modifier {
+    None,
+    Prefix(usize),
+    Explode,
+}
+
+impl UriTemplate {
+    /// Create a template, without values.
+    ///
+    /// The template is only parsed when it is expanded: an invalid one is
+    /// an error when the request is sent.
+    pub fn new<T: Into<String>>(template: T) -> UriTemplate {
+        UriTemplate {
+            template: template.into(),
+            vars: HashMap::new(),
+        }
+    }
+
+    /// Set the value of a variable.
+    pub fn var<V: fmt::Display>(mut self, name: &str, value: V) -> UriTemplate {
+        self.vars
+            .insert(name.to_owned(), Value::String(value.to_string()));
+        self
+    }
+
+    /// Set a list of values for a variable.
+    ///
+    /// An empty list leaves the variable without a value.
+    pub fn list<I>(mut self, name: &str, values: I) -> UriTemplate
+    where
+        I: IntoIterator,
+        I::Item: fmt::Display,
+    {
+        let values = values.into_iter().map(|v| v.to_string()).collect();
+        self.vars.insert(name.to_owned(), Value::List(values));
+        self
+    }
+
+    /// Set a map of keys and values for a variable, in order.
+    ///
+    /// An empty map leaves the variable without a value.
+    pub fn map<I, K, V>(mut self, name: &str, pairs: I) -> UriTemplate
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: fmt::Display,
+        V: fmt::Display,
+    {
+        let pairs = pairs
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        self.vars.insert(name.to_owned(), Value::Map(pairs));
+        self
+    }
+
+    /// Expand the template into a URL.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the template is invalid, or doesn't expand to a valid
+    /// URL.
+    pub fn expand(&self) -> crate::Result<Url> {
+        self.expand_with_base(None)
+    }
+
+    fn expand_with_base(&self, base: Option<&Url>) -> crate::Result<Url> {
+        let expanded = self.expand_str().map_err(|msg| {
+            crate::error::builder(format!("invalid URI template {:?}: {msg}", self.template))
+        })?;
+        expanded.into_url_with_base(base)
+    }
+
+    fn expand_str(&self) -> Result<String, &'static str> {
+        let mut out = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest.as_bytes()[start] == b'}' {
+                return Err("unmatched `}`");
+            }
+            encode(&mut out, &rest[..start], true);
+            let len = rest[start..].find('}').ok_or("unclosed expression")?;
+            self.expression(&mut out, &rest[start + 1..start + len])?;
+            rest = &rest[start + len + 1..];
+        }
+        encode(&mut out, rest, true);
+        Ok(out)
+    }
+
+    fn expression(&self, out: &mut String, expr: &str) -> Result<(), &'static str> {
+        let (op, list) = match expr.chars().next() {
+            Some(c @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => (operator(c), &expr[1..]),
+            Some('=' | ',' | '!' | '@' | '|') => return Err("reserved operator"),
+            _ => (SIMPLE, expr),
+        };
+
+        let mut first = true;
+        for spec in list.split(',') {
+            let (name, modifier) = varspec(spec)?;
+            let value = match self.vars.get(name) {
+                Some(Value::List(values)) if values.is_empty() => continue,
+                Some(Value::Map(pairs)) if pairs.is_empty() => continue,
+                Some(value) => value,
+                None => continue,
+            };
+            out.push_str(if first { op.first } else { op.sep });
+            first = false;
+
+            match (value, modifier) {
+                (Value::String(value), modifier) => {
+                    if op.named {
+                        out.push_str(name);
+                        if value.is_empty() {
+                            out.push_str(op.ifemp);
+                            continue;
+                        }
+                        out.push('=');
+                    }
+                    let value = match modifier {
+                        Modifier::Prefix(len) => prefix(value, len),
+                        _ => value,
+                    };
+                    encode(out, value, op.reserved);
+                }
+                (Value::List(values), Modifier::Explode) => {
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            out.push_str(op.sep);
+                        }
+                        if op.named {
+                            out.push_str(name);
+                            if value.is_empty() {
+                                out.push_str(op.ifemp);
+                                continue;
+                            }
+                            out.push('=');
+                        }
+                        encode(out, value, op.reserved);
+                    }
+                }
+                (Value::Map(pairs), Modifier::Explode) => {
+                    for (i, (key, value)) in pairs.iter().enumerate() {
+                        if i > 0 {
+                            out.push_str(op.sep);
+                        }
+                        encode(out, key, op.reserved);
+                        if op.named && value.is_empty() {
+                            out.push_str(op.ifemp);
+                            continue;
+                        }
+                        out.push('=');
+                        encode(out, value, op.reserved);
+                    }
+                }
+                // prefixes don't apply to lists and maps
+                (Value::List(values), _) => {
+                    if op.named {
+                        out.push_str(name);
+                        out.push('=');
+                    }
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        encode(out, value, op.reserved);
+                    }
+                }
+                (Value::Map(pairs), _) => {
+                    if op.named {
+                        out.push_str(name);
+                        out.push('=');
+                    }
+                    for (i, (key, value)) in pairs.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        encode(out, key, op.reserved);
+                        out.push(',');
+                        encode(out, value, op.reserved);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn operator(c: char) -> Operator {
+    let (first, sep, named, ifemp, reserved) = match c {
+        '+' => ("", ",", false, "", true),
+        '#' => ("#", ",", false, "", true),
+        '.' => (".", ".", false, "", false),
+        '/' => ("/", "/", false, "", false),
+        ';' => (";", ";", true, "", false),
+        '?' => ("?", "&", true, "=", false),
+        '&' => ("&", "&", true, "=", false),
+        _ => return SIMPLE,
+    };
+    Operator {
+        first,
+        sep,
+        named,
+        ifemp,
+        reserved,
+    }
+}
+
+fn varspec(spec: &str) -> Result<(&str, Modifier), &'static str> {
+    let (name, modifier) = if let Some(name) = spec.strip_suffix('*') {
+        (name, Modifier::Explode)
+    } else if let Some((name, len)) = spec.split_once(':') {
+        let len = Some(len)
+            .filter(|len| !len.starts_with('0') && len.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|len| len.parse::<usize>().ok())
+            .filter(|len| *len < 10_000)
+            .ok_or("invalid prefix length")?;
+        (name, Modifier::Prefix(len))
+    } else {
+        (spec, Modifier::None)
+    };
+    let valid = !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'.' || b == b'%');
+    if valid {
+        Ok((name, modifier))
+    } else {
+        Err("invalid variable name")
+    }
+}
+
+/// The first `len` characters of a value.
+fn prefix(value: &str, len: usize) -> &str {
+    match value.char_indices().nth(len) {
+        Some((end, _)) => &value[..end],
+        None => value,
+    }
+}
+
+/// Percent-encode a value, keeping the unreserved characters, and the
+/// reserved ones and percent-encoded triplets if `reserved` is set.
+fn encode(out: &mut String, value: &str, reserved: bool) {
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let unreserved = b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~');
+        if unreserved || (reserved && b"!#$&'()*+,/:;=?@[]".contains(&b)) {
+            out.push(b as char);
+        } else if reserved
+            && b == b'%'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit)
+        {
+            out.push_str(&value[i..i + 3]);
+            i += 3;
+            continue;
+        } else {
+            let _ = write!(out, "%{b:02X}");
+        }
+        i += 1;
+    }
+}
+
+impl IntoUrl for UriTemplate {}
+
+impl IntoUrlSealed for UriTemplate {
+    fn into_url(self) -> crate::Result<Url> {
+        self.expand()
+    }
+
+    fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    fn into_url_with_base(self, base: Option<&Url>) -> crate::Result<Url> {
+        self.expand_with_base(base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(template: &str) -> String {
+        UriTemplate::new(template)
+            .var("var", "value")
+            .var("hello", "Hello World!")
+            .var("path", "/foo/bar")
+            .var("empty", "")
+            .var("x", 1024)
+            .var("y", 768)
+            .list("list", ["red", "green", "blue"])
+            .map("keys", [("semi", ";"), ("dot", "."), ("comma", ",")])
+            .list("none", Vec::<String>::new())
+            .expand_str()
+            .unwrap()
+    }
+
+    #[test]
+    fn rfc_examples() {
+        let cases = [
+            // level 1
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            // level 2
+            ("{+var}", "value"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("here?ref={+path}", "here?ref=/foo/bar"),
+            ("X{#var}", "X#value"),
+            ("X{#hello}", "X#Hello%20World!"),
+            // level 3
+            ("map?{x,y}", "map?1024,768"),
+            ("{x,hello,y}", "1024,Hello%20World%21,768"),
+            ("{+x,hello,y}", "1024,Hello%20World!,768"),
+            ("{+path,x}/here", "/foo/bar,1024/here"),
+            ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+            ("X{.var}", "X.value"),
+            ("X{.x,y}", "X.1024.768"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{&x,y,empty}", "&x=1024&y=768&empty="),
+            // level 4
+            ("{var:3}", "val"),
+            ("{var:30}", "value"),
+            ("{list}", "red,green,blue"),
+            ("{list*}", "red,green,blue"),
+            ("{keys}", "semi,%3B,dot,.,comma,%2C"),
+            ("{keys*}", "semi=%3B,dot=.,comma=%2C"),
+            ("{+path:6}/here", "/foo/b/here"),
+            ("{+keys}", "semi,;,dot,.,comma,,"),
+            ("{+keys*}", "semi=;,dot=.,comma=,"),
+            ("{#keys*}", "#semi=;,dot=.,comma=,"),
+            ("X{.list*}", "X.red.green.blue"),
+            ("X{.keys*}", "X.semi=%3B.dot=..comma=%2C"),
+            ("{/var:1,var}", "/v/value"),
+            ("{/list*,path:4}", "/red/green/blue/%2Ffoo"),
+            ("{/keys*}", "/semi=%3B/dot=./comma=%2C"),
+            ("{;hello:5}", ";hello=Hello"),
+            ("{;list*}", ";list=red;list=green;list=blue"),
+            ("{;keys*}", ";semi=%3B;dot=.;comma=%2C"),
+            ("{?list}", "?list=red,green,blue"),
+            ("{?list*}", "?list=red&list=green&list=blue"),
+            ("{?keys}", "?keys=semi,%3B,dot,.,comma,%2C"),
+            ("{&keys*}", "&semi=%3B&dot=.&comma=%2C"),
+            // variables without values
+            ("{?x,undef,none}", "?x=1024"),
+            ("{/undef}", ""),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(expand(template), expected, "{template}");
+        }
+    }
+
+    #[test]
+    fn literals_are_encoded() {
+        assert_eq!(
+            expand("/caf\u{e9} {var}%2F%zz"),
+            "/caf%C3%A9%20value%2F%25zz"
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for template in [
+            "{var",
+            "var}",
+            "{=var}",
+            "{var:0}",
+            "{var:10000}",
+            "{}",
+            "{a b}",
+        ] {
+            assert!(
+                UriTemplate::new(template).expand_str().is_err(),
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn expand_url() {
+        let url = UriTemplate::new("https://api.example.com/users/{id}/repos{?page,per_page}")
+            .var("id", "jane doe")
+            .var("page", 2)
+            .expand()
+            .unwrap();
+
+        assert_eq!(
+            url.as_str(),
+            "https://api.example.com/users/jane%20doe/repos?page=2"
+        );
+        assert!(UriTemplate::new("/{id}").var("id", 1).expand().is_err());
+    }
+}
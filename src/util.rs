@@ -47,6 +47,68 @@ pub(crate) fn fast_random() -> u64 {
     })
 }
 
+/// Whether a failed step of a download or upload may succeed if tried again.
+#[cfg(any(feature = "download", feature = "upload"))]
+pub(crate) fn is_transient(err: &crate::Error) -> bool {
+    match err.status() {
+        Some(status) => status.is_server_error(),
+        None => !err.is_decode() && !err.is_builder(),
+    }
+}
+
+/// Split a `Link` field value into its targets and their parameters.
+#[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+pub(crate) fn links(value: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = value;
+    std::iter::from_fn(move || {
+        let start = rest.find('<')?;
+        let end = start + rest[start..].find('>')?;
+        let target = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+        let params_end = rest.find('<').unwrap_or(rest.len());
+        let params = &rest[..params_end];
+        rest = &rest[params_end..];
+        Some((target, params))
+    })
+}
+
+/// The value of a parameter of a link, without quotes.
+#[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+pub(crate) fn link_param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params
+        .split(';')
+        .filter_map(|param| param.split_once('='))
+        .find(|(param, _)| param.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| {
+            value
+                .trim()
+                .trim_end_matches(',')
+                .trim_end()
+                .trim_matches('"')
+        })
+}
+
+/// The `Priority` header of RFC 9218.
+#[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+pub(crate) const PRIORITY: crate::header::HeaderName =
+    crate::header::HeaderName::from_static("priority");
+
+/// The value of a `Priority` header.
+#[cfg(not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "none"))))]
+pub(crate) fn priority(urgency: u8, incremental: bool) -> crate::Result<HeaderValue> {
+    if urgency > 7 {
+        return Err(crate::error::builder(format!(
+            "priority urgency must be between 0 and 7, got {urgency}"
+        )));
+    }
+    let value = if incremental {
+        format!("u={urgency}, i")
+    } else {
+        format!("u={urgency}")
+    };
+    Ok(HeaderValue::try_from(value).expect("priority is a valid header value"))
+}
+
 pub(crate) fn replace_headers(dst: &mut HeaderMap, src: HeaderMap) {
     // IntoIter of HeaderMap yields (Option<HeaderName>, HeaderValue).
     // The first time a name is yielded, it will be Some(name), and if
@@ -0,0 +1,369 @@
+//! Record and replay responses for tests.
+//!
+//! A [`Cassette`] is a transport for
+//! [`ClientBuilder::http_service`](crate::ClientBuilder::http_service). In
+//! record mode, it sends requests over the network and stores each exchange
+//! in a file. In replay mode, it answers requests from that file, without
+//! any network, so tests that use it are deterministic and work offline.
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), reqwest::Error> {
+//! use reqwest::vcr::Cassette;
+//!
+//! // records on the first run, and replays on the next ones
+//! let cassette = Cassette::new("tests/cassettes/user.json")?;
+//! let client = reqwest::Client::builder()
+//!     .http_service(cassette)
+//!     .build()?;
+//!
+//! let user = client.get("https://api.example.com/user").send().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Requests are matched by method and URL, and optionally by body. Each
+//! recorded exchange is replayed once, in the order it was recorded, so a
+//! test that makes the same request twice gets both responses back.
+//!
+//! Request headers marked as [sensitive](http::HeaderValue::set_sensitive),
+//! such as those set by `basic_auth` and `bearer_auth`, are not stored.
+//!
+//! # Optional
+//!
+//! This requires the optional `vcr` feature to be enabled.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use tower_service::Service;
+
+use crate::{redirect, Body, Client};
+
+/// A transport that records and replays responses.
+///
+/// Clones share the same recording.
+#[derive(Clone)]
+pub struct Cassette {
+    mode: Mode,
+    shared: Arc<Shared>,
+    match_body: bool,
+}
+
+#[derive(Clone)]
+enum Mode {
+    Record(Client),
+    Replay,
+}
+
+struct Shared {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+struct State {
+    interactions: Vec<Interaction>,
+    played: Vec<bool>,
+}
+
+impl Cassette {
+    /// Replay the cassette at `path` if it exists, and record it otherwise.
+    ///
+    /// # Errors
+    ///
+    /// This fails for the same reasons as [`Cassette::record`] and
+    /// [`Cassette::replay`].
+    pub fn new<P: AsRef<Path>>(path: P) -> crate::Result<Cassette> {
+        let path = path.as_ref();
+        if path.exists() {
+            Cassette::replay(path)
+        } else {
+            Cassette::record(path)
+        }
+    }
+
+    /// Record a new cassette at `path`, replacing any existing one.
+    ///
+    /// The requests are sent with a `Client` that does not follow redirects,
+    /// as the `Client` using the cassette follows them itself. Use
+    /// [`Cassette::with_client`] to send them with another one.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the `Client` used for recording cannot be built.
+    pub fn record<P: AsRef<Path>>(path: P) -> crate::Result<Cassette> {
+        let client = Client::builder()
+            .redirect(redirect::Policy::none())
+            .build()?;
+        Ok(Cassette::with_mode(
+            path.as_ref(),
+            Mode::Record(client),
+            Vec::new(),
+        ))
+    }
+
+    /// Replay the cassette at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the cassette cannot be read.
+    pub fn replay<P: AsRef<Path>>(path: P) -> crate::Result<Cassette> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(crate::error::builder)?;
+        let file: File = serde_json::from_slice(&bytes).map_err(crate::error::builder)?;
+        Ok(Cassette::with_mode(path, Mode::Replay, file.interactions))
+    }
+
+    /// Send the requests being recorded with this `Client`.
+    ///
+    /// This does nothing when replaying.
+    pub fn with_client(mut self, client: Client) -> Cassette {
+        if let Mode::Record(ref mut recording) = self.mode {
+            *recording = client;
+        }
+        self
+    }
+
+    /// Set whether requests must also have the same body as the recorded
+    /// ones to match.
+    ///
+    /// Streaming bodies cannot be compared, and never match a recorded body.
+    ///
+    /// Default is `false`.
+    pub fn match_body(mut self, enabled: bool) -> Cassette {
+        self.match_body = enabled;
+        self
+    }
+
+    /// Whether this cassette is recording.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, Mode::Record(_))
+    }
+
+    fn with_mode(path: &Path, mode: Mode, interactions: Vec<Interaction>) -> Cassette {
+        let played = vec![false; interactions.len()];
+        Cassette {
+            mode,
+            shared: Arc::new(Shared {
+                path: path.to_owned(),
+                state: Mutex::new(State {
+                    interactions,
+                    played,
+                }),
+            }),
+            match_body: false,
+        }
+    }
+
+    fn play(&self, req: &http::Request<Body>) -> crate::Result<http::Response<Body>> {
+        let mut state = self.shared.state.lock().unwrap();
+        let State {
+            ref interactions,
+            ref mut played,
+        } = *state;
+        let found = (0..interactions.len())
+            .find(|&i| !played[i] && self.matches(&interactions[i].request, req));
+        let Some(i) = found else {
+            return Err(crate::error::request(NotRecorded {
+                method: req.method().to_string(),
+                url: req.uri().to_string(),
+            }));
+        };
+        played[i] = true;
+        interactions[i].response.to_response()
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, req: &http::Request<Body>) -> bool {
+        if recorded.method != req.method().as_str() || recorded.url != req.uri().to_string() {
+            return false;
+        }
+        if !self.match_body {
+            return true;
+        }
+        match (&recorded.body, req.body().as_bytes()) {
+            (None, None) => true,
+            (Some(recorded), Some(body)) => recorded.decode().is_ok_and(|b| b == body),
+            _ => false,
+        }
+    }
+
+    fn save(&self, interaction: Interaction) -> crate::Result<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.interactions.push(interaction);
+        let file = File {
+            interactions: state.interactions.clone(),
+        };
+        let json = serde_json::to_vec_pretty(&file).map_err(crate::error::request)?;
+        if let Some(dir) = self.shared.path.parent() {
+            fs::create_dir_all(dir).map_err(crate::error::request)?;
+        }
+        fs::write(&self.shared.path, json).map_err(crate::error::request)
+    }
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.shared.path)
+            .field("recording", &self.is_recording())
+            .field("match_body", &self.match_body)
+            .finish()
+    }
+}
+
+impl Service<http::Request<Body>> for Cassette {
+    type Response = http::Response<Body>;
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = crate::Result<http::Response<Body>>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let Mode::Record(ref client) = self.mode else {
+            let res = self.play(&req);
+            return Box::pin(async move { res });
+        };
+
+        let request = RecordedRequest::new(&req);
+        let mut client = client.clone();
+        let cassette = self.clone();
+        Box::pin(async move {
+            let res = client.call(req).await?;
+            let (parts, body) = res.into_parts();
+            let body = body.collect().await?.to_bytes();
+            let response = RecordedResponse::new(&parts.status, &parts.headers, &body);
+            cassette.save(Interaction { request, response })?;
+            Ok(http::Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+/// No recorded exchange matched a request.
+#[derive(Debug)]
+struct NotRecorded {
+    method: String,
+    url: String,
+}
+
+impl fmt::Display for NotRecorded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no recorded response for {} {}", self.method, self.url)
+    }
+}
+
+impl StdError for NotRecorded {}
+
+// ===== cassette format =====
+
+#[derive(Deserialize, Serialize)]
+struct File {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Stored>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default)]
+    body: Stored,
+}
+
+/// Bytes stored as text when they are UTF-8, and base64 otherwise.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Stored {
+    #[default]
+    Empty,
+    Text(String),
+    Base64(String),
+}
+
+impl RecordedRequest {
+    fn new(req: &http::Request<Body>) -> RecordedRequest {
+        RecordedRequest {
+            method: req.method().to_string(),
+            url: req.uri().to_string(),
+            headers: store_headers(req.headers()),
+            body: req.body().as_bytes().map(Stored::encode),
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn new(status: &http::StatusCode, headers: &HeaderMap, body: &[u8]) -> RecordedResponse {
+        RecordedResponse {
+            status: status.as_u16(),
+            headers: store_headers(headers),
+            body: Stored::encode(body),
+        }
+    }
+
+    fn to_response(&self) -> crate::Result<http::Response<Body>> {
+        let mut res = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(crate::error::decode)?;
+            let value = HeaderValue::from_str(value).map_err(crate::error::decode)?;
+            res = res.header(name, value);
+        }
+        let body = self.body.decode().map_err(crate::error::decode)?;
+        res.body(Body::from(body)).map_err(crate::error::decode)
+    }
+}
+
+impl Stored {
+    fn encode(bytes: &[u8]) -> Stored {
+        if bytes.is_empty() {
+            return Stored::Empty;
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Stored::Text(text.to_owned()),
+            Err(_) => Stored::Base64(BASE64_STANDARD.encode(bytes)),
+        }
+    }
+
+    fn decode(&self) -> Result<Bytes, base64::DecodeError> {
+        match self {
+            Stored::Empty => Ok(Bytes::new()),
+            Stored::Text(text) => Ok(Bytes::from(text.clone())),
+            Stored::Base64(b64) => BASE64_STANDARD.decode(b64).map(Bytes::from),
+        }
+    }
+}
+
+fn store_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(_, value)| !value.is_sensitive())
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
@@ -22,6 +22,9 @@ enum Inner {
     /// MultipartForm holds a multipart/form-data body.
     #[cfg(feature = "multipart")]
     MultipartForm(Form),
+    /// Stream holds a `ReadableStream` that is uploaded as it is read.
+    #[cfg(feature = "stream")]
+    Stream(web_sys::ReadableStream),
 }
 
 #[derive(Clone)]
@@ -61,25 +64,91 @@ impl Single {
 impl Body {
     /// Returns a reference to the internal data of the `Body`.
     ///
-    /// `None` is returned, if the underlying data is a multipart form.
+    /// `None` is returned, if the underlying data is a multipart form or a
+    /// stream.
     #[inline]
     pub fn as_bytes(&self) -> Option<&[u8]> {
         match &self.inner {
             Inner::Single(single) => Some(single.as_bytes()),
             #[cfg(feature = "multipart")]
             Inner::MultipartForm(_) => None,
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => None,
         }
     }
 
-    pub(crate) fn to_js_value(&self) -> crate::Result<JsValue> {
+    /// Wrap a futures `Stream` in a box inside `Body`.
+    ///
+    /// The stream is passed to `fetch` as a `ReadableStream`, so the body is
+    /// uploaded as it is produced, instead of being buffered first.
+    ///
+    /// # WASM
+    ///
+    /// Streaming request bodies require browser support, and are only sent
+    /// over HTTP/2 or newer. Otherwise, sending the request fails.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn wrap_stream<S>(stream: S) -> Body
+    where
+        S: futures_core::stream::TryStream + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        Bytes: From<S::Ok>,
+    {
+        use futures_util::TryStreamExt;
+
+        let stream = stream
+            .map_ok(|chunk| {
+                let chunk = Bytes::from(chunk);
+                JsValue::from(Uint8Array::from(&chunk[..]))
+            })
+            .map_err(|err| {
+                let err: Box<dyn std::error::Error + Send + Sync> = err.into();
+                JsValue::from_str(&err.to_string())
+            });
+        Body::from_readable_stream(wasm_streams::ReadableStream::from_stream(stream).into_raw())
+    }
+
+    /// Use a JavaScript `ReadableStream` as the body.
+    ///
+    /// See [`Body::wrap_stream`] for the requirements of streaming bodies.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn from_readable_stream(stream: web_sys::ReadableStream) -> Body {
+        Body {
+            inner: Inner::Stream(stream),
+        }
+    }
+
+    pub(crate) async fn to_js_value(&self) -> crate::Result<JsValue> {
         match &self.inner {
             Inner::Single(single) => Ok(single.to_js_value()),
             #[cfg(feature = "multipart")]
             Inner::MultipartForm(form) => {
-                let form_data = form.to_form_data()?;
+                let form_data = form.to_form_data().await?;
                 let js_value: &JsValue = form_data.as_ref();
                 Ok(js_value.to_owned())
             }
+            #[cfg(feature = "stream")]
+            Inner::Stream(stream) => {
+                let js_value: &JsValue = stream.as_ref();
+                Ok(js_value.to_owned())
+            }
+        }
+    }
+
+    pub(crate) fn is_stream(&self) -> bool {
+        match &self.inner {
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => true,
+            _ => false,
         }
     }
 
@@ -87,11 +156,19 @@ impl Body {
     pub(crate) fn as_single(&self) -> Option<&Single> {
         match &self.inner {
             Inner::Single(single) => Some(single),
-            Inner::MultipartForm(_) => None,
+            _ => None,
         }
     }
 
     #[inline]
+    #[cfg(all(feature = "multipart", feature = "stream"))]
+    pub(crate) fn as_readable_stream(&self) -> Option<&web_sys::ReadableStream> {
+        match &self.inner {
+            Inner::Stream(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
     #[cfg(feature = "multipart")]
     pub(crate) fn from_form(f: Form) -> Body {
         Self {
@@ -109,6 +186,10 @@ impl Body {
             Inner::MultipartForm(form) => Self {
                 inner: Inner::MultipartForm(form),
             },
+            #[cfg(feature = "stream")]
+            Inner::Stream(stream) => Self {
+                inner: Inner::Stream(stream),
+            },
         }
     }
 
@@ -117,6 +198,8 @@ impl Body {
             Inner::Single(single) => single.is_empty(),
             #[cfg(feature = "multipart")]
             Inner::MultipartForm(form) => form.is_empty(),
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => false,
         }
     }
 
@@ -127,6 +210,8 @@ impl Body {
             }),
             #[cfg(feature = "multipart")]
             Inner::MultipartForm(_) => None,
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => None,
         }
     }
 }
@@ -226,6 +311,7 @@ mod tests {
         init.method("POST");
         init.body(Some(
             body.to_js_value()
+                .await
                 .expect("could not convert body to JsValue")
                 .as_ref(),
         ));
@@ -248,6 +334,7 @@ mod tests {
         init.method("POST");
         init.body(Some(
             body.to_js_value()
+                .await
                 .expect("could not convert body to JsValue")
                 .as_ref(),
         ));
@@ -271,6 +358,7 @@ mod tests {
         init.method("POST");
         init.body(Some(
             body.to_js_value()
+                .await
                 .expect("could not convert body to JsValue")
                 .as_ref(),
         ));
@@ -299,6 +387,7 @@ mod tests {
         init.method("POST");
         init.body(Some(
             body.to_js_value()
+                .await
                 .expect("could not convert body to JsValue")
                 .as_ref(),
         ));
@@ -2,10 +2,12 @@ use http::header::USER_AGENT;
 use http::{HeaderMap, HeaderValue, Method};
 use js_sys::Promise;
 use std::convert::TryInto;
+use std::time::Duration;
 use std::{fmt, future::Future, sync::Arc};
 use url::Url;
 use wasm_bindgen::prelude::{wasm_bindgen, UnwrapThrowExt as _};
 use wasm_bindgen::JsCast;
+use web_sys::RequestCredentials;
 
 use super::{AbortGuard, Request, RequestBuilder, Response};
 use crate::IntoUrl;
@@ -113,6 +115,15 @@ impl Client {
         self.request(Method::HEAD, url)
     }
 
+    /// Convenience method to make an `OPTIONS` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever supplied `Url` cannot be parsed.
+    pub fn options<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::OPTIONS, url)
+    }
+
     /// Start building a `Request` with the `Method` and `Url`.
     ///
     /// Returns a `RequestBuilder`, which will allow setting headers and
@@ -163,6 +174,12 @@ impl Client {
         mut req: Request,
     ) -> impl Future<Output = crate::Result<Response>> {
         self.merge_headers(&mut req);
+        if req.credentials.is_none() {
+            req.credentials = self.config.credentials;
+        }
+        if req.timeout().is_none() {
+            *req.timeout_mut() = self.config.timeout;
+        }
         fetch(req)
     }
 }
@@ -235,9 +252,30 @@ async fn fetch(req: Request) -> crate::Result<Response> {
     }
     init.headers(&js_headers.into());
 
-    // When req.cors is true, do nothing because the default mode is 'cors'
-    if !req.cors {
-        init.mode(web_sys::RequestMode::NoCors);
+    if let Some(mode) = req.mode {
+        init.mode(mode);
+    }
+
+    if let Some(integrity) = &req.integrity {
+        init.set_integrity(integrity);
+    }
+
+    if let Some(redirect) = req.redirect {
+        init.set_redirect(redirect);
+    }
+
+    // `web-sys` doesn't expose `keepalive` and the priority hint, so set
+    // them by name.
+    if req.keepalive {
+        js_sys::Reflect::set(init.as_ref(), &"keepalive".into(), &true.into())
+            .map_err(crate::error::wasm)
+            .map_err(crate::error::builder)?;
+    }
+
+    if let Some(priority) = req.priority {
+        js_sys::Reflect::set(init.as_ref(), &"priority".into(), &priority.into())
+            .map_err(crate::error::wasm)
+            .map_err(crate::error::builder)?;
     }
 
     if let Some(creds) = req.credentials {
@@ -250,7 +288,13 @@ async fn fetch(req: Request) -> crate::Result<Response> {
 
     if let Some(body) = req.body() {
         if !body.is_empty() {
-            init.body(Some(body.to_js_value()?.as_ref()));
+            init.body(Some(body.to_js_value().await?.as_ref()));
+        }
+        if body.is_stream() {
+            // Streaming uploads have to opt in to half-duplex.
+            js_sys::Reflect::set(init.as_ref(), &"duplex".into(), &"half".into())
+                .map_err(crate::error::wasm)
+                .map_err(crate::error::builder)?;
         }
     }
 
@@ -258,6 +302,9 @@ async fn fetch(req: Request) -> crate::Result<Response> {
     if let Some(timeout) = req.timeout() {
         abort.timeout(*timeout);
     }
+    if let Some(signal) = &req.signal {
+        abort.follow(signal);
+    }
     init.signal(Some(&abort.signal()));
 
     let js_req = web_sys::Request::new_with_str_and_init(req.url().as_str(), &init)
@@ -270,13 +317,19 @@ async fn fetch(req: Request) -> crate::Result<Response> {
         .await
         .map_err(|error| {
             if error.to_string() == "JsValue(\"reqwest::errors::TimedOut\")" {
-                crate::error::TimedOut.into()
+                crate::error::TimedOut(crate::error::TimeoutPhase::Total).into()
             } else {
                 error
             }
         })
         .map_err(crate::error::request)?;
 
+    // An opaque redirect hides everything about the response, including
+    // its status and location.
+    if js_resp.type_() == web_sys::ResponseType::Opaqueredirect {
+        return Err(crate::error::redirect(OpaqueRedirect, req.url().clone()));
+    }
+
     // Convert from the js Response
     let mut resp = http::Response::builder().status(js_resp.status());
 
@@ -305,6 +358,17 @@ async fn fetch(req: Request) -> crate::Result<Response> {
         .map_err(crate::error::request)
 }
 
+#[derive(Debug)]
+struct OpaqueRedirect;
+
+impl fmt::Display for OpaqueRedirect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("redirect not followed due to manual redirect mode")
+    }
+}
+
+impl std::error::Error for OpaqueRedirect {}
+
 // ===== impl ClientBuilder =====
 
 impl ClientBuilder {
@@ -353,6 +417,68 @@ impl ClientBuilder {
         }
         self
     }
+
+    /// Enables a total request timeout.
+    ///
+    /// The timeout is applied from when the request starts connecting until
+    /// the response body has finished. Also considered a total deadline.
+    ///
+    /// A timeout set on a request overrides this one.
+    ///
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the default fetch credentials to 'same-origin'.
+    ///
+    /// A request can override this with its own credentials mode.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request credentials][mdn] will be set to 'same-origin'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/credentials
+    pub fn fetch_credentials_same_origin(mut self) -> ClientBuilder {
+        self.config.credentials = Some(RequestCredentials::SameOrigin);
+        self
+    }
+
+    /// Set the default fetch credentials to 'include'.
+    ///
+    /// This is needed to send cookies on cross-origin requests. A request
+    /// can override this with its own credentials mode.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request credentials][mdn] will be set to 'include'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/credentials
+    pub fn fetch_credentials_include(mut self) -> ClientBuilder {
+        self.config.credentials = Some(RequestCredentials::Include);
+        self
+    }
+
+    /// Set the default fetch credentials to 'omit'.
+    ///
+    /// A request can override this with its own credentials mode.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request credentials][mdn] will be set to 'omit'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/credentials
+    pub fn fetch_credentials_omit(mut self) -> ClientBuilder {
+        self.config.credentials = Some(RequestCredentials::Omit);
+        self
+    }
 }
 
 impl Default for ClientBuilder {
@@ -364,6 +490,8 @@ impl Default for ClientBuilder {
 #[derive(Debug)]
 struct Config {
     headers: HeaderMap,
+    credentials: Option<RequestCredentials>,
+    timeout: Option<Duration>,
     error: Option<crate::Error>,
 }
 
@@ -371,6 +499,8 @@ impl Default for Config {
     fn default() -> Config {
         Config {
             headers: HeaderMap::new(),
+            credentials: None,
+            timeout: None,
             error: None,
         }
     }
@@ -379,6 +509,14 @@ impl Default for Config {
 impl Config {
     fn fmt_fields(&self, f: &mut fmt::DebugStruct<'_, '_>) {
         f.field("default_headers", &self.headers);
+
+        if let Some(ref credentials) = self.credentials {
+            f.field("credentials", credentials);
+        }
+
+        if let Some(ref timeout) = self.timeout {
+            f.field("timeout", timeout);
+        }
     }
 }
 
@@ -453,6 +591,31 @@ mod tests {
         );
     }
 
+    #[wasm_bindgen_test]
+    fn default_credentials() {
+        use web_sys::RequestCredentials;
+
+        let client = crate::Client::builder()
+            .fetch_credentials_include()
+            .build()
+            .expect("client");
+
+        // Requests built from the client are untouched until sent.
+        let req = client
+            .get("https://www.example.com")
+            .build()
+            .expect("request");
+        assert_eq!(req.credentials, None);
+
+        let req = client
+            .get("https://www.example.com")
+            .fetch_credentials_omit()
+            .build()
+            .expect("request");
+        assert_eq!(req.credentials, Some(RequestCredentials::Omit));
+        assert_eq!(client.config.credentials, Some(RequestCredentials::Include));
+    }
+
     #[wasm_bindgen_test]
     fn user_agent_header() {
         use crate::header::USER_AGENT;
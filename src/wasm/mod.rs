@@ -45,6 +45,7 @@ where
 struct AbortGuard {
     ctrl: AbortController,
     timeout: Option<(JsValue, Closure<dyn FnMut()>)>,
+    external: Option<(AbortSignal, Closure<dyn FnMut()>)>,
 }
 
 impl AbortGuard {
@@ -54,6 +55,7 @@ impl AbortGuard {
                 .map_err(crate::error::wasm)
                 .map_err(crate::error::builder)?,
             timeout: None,
+            external: None,
         })
     }
 
@@ -73,6 +75,22 @@ impl AbortGuard {
             clear_timeout(id);
         }
     }
+
+    /// Abort the request when an external signal is aborted.
+    fn follow(&mut self, signal: &AbortSignal) {
+        if signal.aborted() {
+            self.ctrl.abort_with_reason(&signal.reason());
+            return;
+        }
+        let ctrl = self.ctrl.clone();
+        let source = signal.clone();
+        let abort = Closure::<dyn FnMut()>::new(move || ctrl.abort_with_reason(&source.reason()));
+        // Can only fail if the callback is not a function.
+        let _ = signal.add_event_listener_with_callback("abort", abort.as_ref().unchecked_ref());
+        if let Some((prev, cb)) = self.external.replace((signal.clone(), abort)) {
+            let _ = prev.remove_event_listener_with_callback("abort", cb.as_ref().unchecked_ref());
+        }
+    }
 }
 
 impl Drop for AbortGuard {
@@ -81,5 +99,9 @@ impl Drop for AbortGuard {
         if let Some((id, _)) = self.timeout.take() {
             clear_timeout(id);
         }
+        if let Some((signal, cb)) = self.external.take() {
+            let _ =
+                signal.remove_event_listener_with_callback("abort", cb.as_ref().unchecked_ref());
+        }
     }
 }
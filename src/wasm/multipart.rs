@@ -89,15 +89,13 @@ impl Form {
         }
     }
 
-    pub(crate) fn to_form_data(&self) -> crate::Result<FormData> {
+    pub(crate) async fn to_form_data(&self) -> crate::Result<FormData> {
         let form = FormData::new()
             .map_err(crate::error::wasm)
             .map_err(crate::error::builder)?;
 
         for (name, part) in self.inner.fields.iter() {
-            part.append_to_form(name, &form)
-                .map_err(crate::error::wasm)
-                .map_err(crate::error::builder)?;
+            part.append_to_form(name, &form).await?;
         }
         Ok(form)
     }
@@ -137,6 +135,9 @@ impl Part {
     }
 
     /// Makes a new parameter from an arbitrary stream.
+    ///
+    /// `FormData` can't hold a stream, so a stream body is read to the end
+    /// when the request is sent, and the part is uploaded from memory.
     pub fn stream<T: Into<Body>>(value: T) -> Part {
         Part::new(value.into())
     }
@@ -181,41 +182,41 @@ impl Part {
         }
     }
 
-    fn append_to_form(
-        &self,
-        name: &str,
-        form: &web_sys::FormData,
-    ) -> Result<(), wasm_bindgen::JsValue> {
-        let single = self
-            .value
-            .as_single()
-            .expect("A part's body can't be multipart itself");
-
+    async fn append_to_form(&self, name: &str, form: &web_sys::FormData) -> crate::Result<()> {
         let mut mime_type = self.metadata().mime.as_ref();
 
         // The JS fetch API doesn't support file names and mime types for strings. So we do our best
         // effort to use `append_with_str` and fallback to `append_with_blob_*` if that's not
         // possible.
-        if let super::body::Single::Text(text) = single {
+        if let Some(super::body::Single::Text(text)) = self.value.as_single() {
             if mime_type.is_none() || mime_type == Some(&mime_guess::mime::TEXT_PLAIN) {
                 if self.metadata().file_name.is_none() {
-                    return form.append_with_str(name, text);
+                    return form
+                        .append_with_str(name, text)
+                        .map_err(crate::error::wasm)
+                        .map_err(crate::error::builder);
                 }
             } else {
                 mime_type = Some(&mime_guess::mime::TEXT_PLAIN);
             }
         }
 
-        let blob = self.blob(mime_type)?;
+        let blob = self.blob(mime_type).await?;
 
         if let Some(file_name) = &self.metadata().file_name {
             form.append_with_blob_and_filename(name, &blob, file_name)
         } else {
             form.append_with_blob(name, &blob)
         }
+        .map_err(crate::error::wasm)
+        .map_err(crate::error::builder)
     }
 
-    fn blob(&self, mime_type: Option<&Mime>) -> crate::Result<web_sys::Blob> {
+    // Can use new methods in web-sys when requiring v0.2.93.
+    // > `properties.type_(t)` to `properties.set_type(t)`
+    // For now, ignore their deprecation.
+    #[allow(deprecated)]
+    async fn blob(&self, mime_type: Option<&Mime>) -> crate::Result<web_sys::Blob> {
         use web_sys::Blob;
         use web_sys::BlobPropertyBag;
         let mut properties = BlobPropertyBag::new();
@@ -223,18 +224,37 @@ impl Part {
             properties.type_(mime.as_ref());
         }
 
-        let js_value = self
-            .value
-            .as_single()
-            .expect("A part's body can't be set to a multipart body")
-            .to_js_value();
-
         let body_array = js_sys::Array::new();
-        body_array.push(&js_value);
+        if let Some(single) = self.value.as_single() {
+            body_array.push(&single.to_js_value());
+            return Blob::new_with_u8_array_sequence_and_options(body_array.as_ref(), &properties)
+                .map_err(crate::error::wasm)
+                .map_err(crate::error::builder);
+        }
 
-        Blob::new_with_u8_array_sequence_and_options(body_array.as_ref(), &properties)
-            .map_err(crate::error::wasm)
-            .map_err(crate::error::builder)
+        #[cfg(feature = "stream")]
+        if let Some(stream) = self.value.as_readable_stream() {
+            // Let a `Response` read the stream into a blob, then give that
+            // the mime type of the part.
+            let response = web_sys::Response::new_with_opt_readable_stream(Some(stream))
+                .map_err(crate::error::wasm)
+                .map_err(crate::error::builder)?;
+            let promise = response
+                .blob()
+                .map_err(crate::error::wasm)
+                .map_err(crate::error::builder)?;
+            let blob = super::promise::<Blob>(promise)
+                .await
+                .map_err(crate::error::builder)?;
+            body_array.push(&blob);
+            return Blob::new_with_blob_sequence_and_options(body_array.as_ref(), &properties)
+                .map_err(crate::error::wasm)
+                .map_err(crate::error::builder);
+        }
+
+        Err(crate::error::builder(
+            "a part's body can't be a multipart form",
+        ))
     }
 }
 
@@ -323,6 +343,7 @@ impl PartMetadata {
     }
 }
 
+#[allow(deprecated)]
 #[cfg(test)]
 mod tests {
 
@@ -368,6 +389,7 @@ mod tests {
         init.method("POST");
         init.body(Some(
             form.to_form_data()
+                .await
                 .expect("could not convert to FormData")
                 .as_ref(),
         ));
@@ -416,4 +438,47 @@ mod tests {
 
         assert_eq!(binary, binary_content);
     }
+
+    #[cfg(feature = "stream")]
+    #[wasm_bindgen_test]
+    async fn test_multipart_js_stream() {
+        use super::{Form, Part};
+        use crate::Body;
+        use wasm_bindgen::JsValue;
+        use web_sys::{File, FormData};
+
+        let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("ab"), Ok("cd")];
+        let stream_part = Part::stream(Body::wrap_stream(futures_util::stream::iter(chunks)))
+            .file_name("stream.txt")
+            .mime_str("text/csv")
+            .expect("invalid mime type");
+        let form = Form::new().part("stream part", stream_part);
+
+        let mut init = web_sys::RequestInit::new();
+        init.method("POST");
+        init.body(Some(
+            form.to_form_data()
+                .await
+                .expect("could not convert to FormData")
+                .as_ref(),
+        ));
+
+        let js_req = web_sys::Request::new_with_str_and_init("", &init)
+            .expect("could not create JS request");
+
+        let form_data_promise = js_req.form_data().expect("could not get form_data promise");
+
+        let form_data = crate::wasm::promise::<FormData>(form_data_promise)
+            .await
+            .expect("could not get body as form data");
+
+        let stream_file = File::from(form_data.get("stream part"));
+        assert_eq!(stream_file.name(), "stream.txt");
+        assert_eq!(stream_file.type_(), "text/csv");
+
+        let text = crate::wasm::promise::<JsValue>(stream_file.text())
+            .await
+            .expect("could not get stream body as text");
+        assert_eq!(text.as_string().expect("text is not a string"), "abcd");
+    }
 }
@@ -9,7 +9,7 @@ use serde::Serialize;
 #[cfg(feature = "json")]
 use serde_json;
 use url::Url;
-use web_sys::{RequestCache, RequestCredentials};
+use web_sys::{AbortSignal, RequestCache, RequestCredentials, RequestMode, RequestRedirect};
 
 use super::{Body, Client, Response};
 #[cfg(any(feature = "form", feature = "json"))]
@@ -23,11 +23,16 @@ pub struct Request {
     headers: HeaderMap,
     body: Option<Body>,
     timeout: Option<Duration>,
-    pub(super) cors: bool,
+    pub(super) mode: Option<RequestMode>,
+    pub(super) integrity: Option<String>,
+    pub(super) keepalive: bool,
+    pub(super) redirect: Option<RequestRedirect>,
+    pub(super) priority: Option<&'static str>,
     pub(super) credentials: Option<RequestCredentials>,
     pub(super) cache: Option<RequestCache>,
     pub(super) referrer: Option<String>,
     pub(super) referrer_policy: Option<String>,
+    pub(super) signal: Option<AbortSignal>,
 }
 
 /// A builder to construct the properties of a `Request`.
@@ -46,11 +51,16 @@ impl Request {
             headers: HeaderMap::new(),
             body: None,
             timeout: None,
-            cors: true,
+            mode: None,
+            integrity: None,
+            keepalive: false,
+            redirect: None,
+            priority: None,
             credentials: None,
             cache: None,
             referrer: None,
             referrer_policy: None,
+            signal: None,
         }
     }
 
@@ -129,11 +139,16 @@ impl Request {
             headers: self.headers.clone(),
             body,
             timeout: self.timeout,
-            cors: self.cors,
+            mode: self.mode,
+            integrity: self.integrity.clone(),
+            keepalive: self.keepalive,
+            redirect: self.redirect,
+            priority: self.priority,
             credentials: self.credentials,
             cache: self.cache,
             referrer: self.referrer.clone(),
             referrer_policy: self.referrer_policy.clone(),
+            signal: self.signal.clone(),
         })
     }
 }
@@ -347,7 +362,61 @@ impl RequestBuilder {
     /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/mode
     pub fn fetch_mode_no_cors(mut self) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
-            req.cors = false;
+            req.mode = Some(RequestMode::NoCors);
+        }
+        self
+    }
+
+    /// Set fetch mode to 'cors'.
+    ///
+    /// This is the default.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request mode][mdn] will be set to 'cors'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/mode
+    pub fn fetch_mode_cors(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.mode = Some(RequestMode::Cors);
+        }
+        self
+    }
+
+    /// Set fetch mode to 'same-origin'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request mode][mdn] will be set to 'same-origin', so requests to
+    /// other origins fail.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/mode
+    pub fn fetch_mode_same_origin(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.mode = Some(RequestMode::SameOrigin);
+        }
+        self
+    }
+
+    /// Set the subresource integrity metadata of the request.
+    ///
+    /// The value lists the expected digests, such as `sha384-...`. If the
+    /// response body doesn't match, fetching it fails.
+    ///
+    /// # WASM
+    ///
+    /// This maps to the browser `RequestInit.integrity` field.
+    ///
+    /// See [Subresource Integrity][mdn].
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
+    pub fn fetch_integrity(mut self, integrity: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.integrity = Some(integrity.to_string());
         }
         self
     }
@@ -520,6 +589,156 @@ impl RequestBuilder {
         self
     }
 
+    /// Set fetch redirect mode to 'follow'.
+    ///
+    /// This is the default.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request redirect][mdn] mode will be set to 'follow'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/redirect
+    pub fn fetch_redirect_follow(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.redirect = Some(RequestRedirect::Follow);
+        }
+        self
+    }
+
+    /// Set fetch redirect mode to 'error'.
+    ///
+    /// If the server responds with a redirect, sending the request fails.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request redirect][mdn] mode will be set to 'error'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/redirect
+    pub fn fetch_redirect_error(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.redirect = Some(RequestRedirect::Error);
+        }
+        self
+    }
+
+    /// Set fetch redirect mode to 'manual'.
+    ///
+    /// Browsers don't expose the redirect response itself, so if the server
+    /// responds with a redirect, sending the request fails with an error
+    /// for which [`Error::is_redirect`](crate::Error::is_redirect) returns
+    /// `true`. This is similar to `redirect::Policy::none()` on other
+    /// targets, where the redirect response is returned instead.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request redirect][mdn] mode will be set to 'manual'.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/redirect
+    pub fn fetch_redirect_manual(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.redirect = Some(RequestRedirect::Manual);
+        }
+        self
+    }
+
+    /// Set whether the request may outlive the page.
+    ///
+    /// This is useful for beacon-style requests sent while the page is
+    /// unloading. Browsers limit the total size of such request bodies.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request keepalive][mdn] flag will be set.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/keepalive
+    pub fn fetch_keepalive(mut self, keepalive: bool) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.keepalive = keepalive;
+        }
+        self
+    }
+
+    /// Set fetch priority to 'high'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request priority][mdn] hint will be set to 'high'. Browsers that
+    /// don't support priority hints ignore it.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/RequestInit#priority
+    pub fn fetch_priority_high(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.priority = Some("high");
+        }
+        self
+    }
+
+    /// Set fetch priority to 'low'.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request priority][mdn] hint will be set to 'low'. Browsers that
+    /// don't support priority hints ignore it.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/RequestInit#priority
+    pub fn fetch_priority_low(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.priority = Some("low");
+        }
+        self
+    }
+
+    /// Set fetch priority to 'auto'.
+    ///
+    /// This is the default.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request priority][mdn] hint will be set to 'auto'. Browsers that
+    /// don't support priority hints ignore it.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/RequestInit#priority
+    pub fn fetch_priority_auto(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.priority = Some("auto");
+        }
+        self
+    }
+
+    /// Abort the request when the given signal is aborted.
+    ///
+    /// The request, or reading its response body, fails with an error
+    /// once the signal is aborted. Dropping the response future also aborts
+    /// the underlying fetch.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// See [`AbortSignal`][mdn].
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/AbortSignal
+    pub fn fetch_signal(mut self, signal: &AbortSignal) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.signal = Some(signal.clone());
+        }
+        self
+    }
+
     /// Build a `Request`, which can be inspected, modified and executed with
     /// `Client::execute()`.
     pub fn build(self) -> crate::Result<Request> {
@@ -636,11 +855,16 @@ where
             headers,
             body: Some(body.into()),
             timeout: None,
-            cors: true,
+            mode: None,
+            integrity: None,
+            keepalive: false,
+            redirect: None,
+            priority: None,
             credentials: None,
             cache: None,
             referrer: None,
             referrer_policy: None,
+            signal: None,
         })
     }
 }
@@ -0,0 +1,247 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use http_body_util::BodyExt;
+use reqwest::auth::{TokenFuture, TokenProvider};
+use reqwest::StatusCode;
+
+struct Tokens {
+    current: Mutex<String>,
+    fetched: AtomicUsize,
+    refreshed: AtomicUsize,
+}
+
+impl Tokens {
+    fn new(token: &str) -> Arc<Tokens> {
+        Arc::new(Tokens {
+            current: Mutex::new(token.to_owned()),
+            fetched: AtomicUsize::new(0),
+            refreshed: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl TokenProvider for Tokens {
+    fn token(&self) -> TokenFuture {
+        self.fetched.fetch_add(1, Ordering::SeqCst);
+        let token = self.current.lock().unwrap().clone();
+        Box::pin(async move { Ok(token) })
+    }
+
+    fn refresh(&self, rejected: &str) -> TokenFuture {
+        let n = self.refreshed.fetch_add(1, Ordering::SeqCst) + 1;
+        assert_eq!(*self.current.lock().unwrap(), rejected);
+        let token = format!("fresh-{n}");
+        *self.current.lock().unwrap() = token.clone();
+        Box::pin(async move { Ok(token) })
+    }
+}
+
+/// A server accepting only `token`, echoing the request body.
+fn server(token: &'static str) -> server::Server {
+    server::http(move |req| async move {
+        let authorized = req.headers()["authorization"] == format!("Bearer {token}").as_str();
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        let status = if authorized { 200 } else { 401 };
+        http::Response::builder()
+            .status(status)
+            .body(body.into())
+            .unwrap()
+    })
+}
+
+#[tokio::test]
+async fn token_is_sent() {
+    let server = server("token");
+    let tokens = Tokens::new("token");
+    let client = reqwest::Client::builder()
+        .bearer_token_provider("127.0.0.1", tokens.clone())
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let res = client
+            .get(format!("http://{}/", server.addr()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    assert_eq!(tokens.fetched.load(Ordering::SeqCst), 2);
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn token_is_refreshed_on_unauthorized() {
+    let server = server("fresh-1");
+    let tokens = Tokens::new("expired");
+    let client = reqwest::Client::builder()
+        .bearer_token_provider("127.0.0.1", tokens.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .post(format!("http://{}/", server.addr()))
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 1);
+
+    // the new token is used from then on
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn token_is_refreshed_once() {
+    let server = server("never");
+    let tokens = Tokens::new("expired");
+    let client = reqwest::Client::builder()
+        .bearer_token_provider("127.0.0.1", tokens.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn request_authorization_is_kept() {
+    let server = server("own");
+    let tokens = Tokens::new("token");
+    let client = reqwest::Client::builder()
+        .bearer_token_provider("127.0.0.1", tokens.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .bearer_auth("own")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(tokens.fetched.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn provider_error() {
+    struct Failing;
+
+    impl TokenProvider for Failing {
+        fn token(&self) -> TokenFuture {
+            Box::pin(async { Err("authorization server unavailable".into()) })
+        }
+
+        fn refresh(&self, _rejected: &str) -> TokenFuture {
+            self.token()
+        }
+    }
+
+    let server = server("token");
+    let client = reqwest::Client::builder()
+        .bearer_token_provider("127.0.0.1", Failing)
+        .build()
+        .unwrap();
+
+    let err = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_request(), "{err:?}");
+    assert_eq!(err.url().unwrap().port(), Some(server.addr().port()));
+}
+
+#[tokio::test]
+async fn token_is_only_sent_to_its_host() {
+    let server = server::http(move |req| async move {
+        assert!(!req.headers().contains_key("authorization"));
+        http::Response::default()
+    });
+    let tokens = Tokens::new("token");
+    let client = reqwest::Client::builder()
+        .bearer_token_provider("api.example", tokens.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(tokens.fetched.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn unauthorized_from_another_host_is_not_refreshed() {
+    let other = server::http(move |req| async move {
+        assert!(!req.headers().contains_key("authorization"));
+        http::Response::builder()
+            .status(401)
+            .body(Default::default())
+            .unwrap()
+    });
+    let location = format!("http://{}/", other.addr());
+    let api = server::http(move |_req| {
+        let location = location.clone();
+        async move {
+            http::Response::builder()
+                .status(302)
+                .header("location", location)
+                .body(Default::default())
+                .unwrap()
+        }
+    });
+    let tokens = Tokens::new("token");
+    let client = reqwest::Client::builder()
+        .resolve("api.example", api.addr())
+        .bearer_token_provider("api.example", tokens.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://api.example:{}/", api.addr().port()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(tokens.fetched.load(Ordering::SeqCst), 1);
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 0);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_token_is_refreshed() {
+    let server = server("fresh-1");
+    let tokens = Tokens::new("expired");
+    let client = reqwest::blocking::Client::builder()
+        .bearer_token_provider("127.0.0.1", tokens.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 1);
+}
@@ -159,6 +159,40 @@ fn test_post_form() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[test]
+fn test_post_form_with() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.uri().query(), Some("page=2&PER-PAGE=10"));
+        assert_eq!(
+            req.headers()["content-type"],
+            "application/x-www-form-urlencoded"
+        );
+
+        let data = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&*data, b"FIRST-NAME=Sean");
+
+        http::Response::default()
+    });
+
+    fn kebab_upper<const N: usize>(pairs: &[(&str, &str); N]) -> Result<String, std::fmt::Error> {
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+        for (key, value) in pairs {
+            serializer.append_pair(&key.replace('_', "-").to_uppercase(), value);
+        }
+        Ok(serializer.finish())
+    }
+
+    let url = format!("http://{}/form?page=2", server.addr());
+    let res = reqwest::blocking::Client::new()
+        .post(&url)
+        .query_with(&[("per_page", "10")], kebab_upper)
+        .form_with(&[("first_name", "Sean")], kebab_upper)
+        .send()
+        .expect("request send");
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 /// Calling `Response::error_for_status`` on a response with status in 4xx
 /// returns an error.
 #[test]
@@ -390,6 +424,25 @@ fn blocking_update_json_content_type_if_set_manually() {
     assert_eq!("application/json", req.headers().get(CONTENT_TYPE).unwrap());
 }
 
+#[test]
+#[cfg(feature = "__tls")]
+fn test_reload_tls() {
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let url = format!("http://{}/", server.addr());
+
+    let client = reqwest::blocking::Client::builder()
+        .lazy_runtime(true)
+        .build()
+        .unwrap();
+
+    let reload = reqwest::tls::TlsReload::new().drain(true);
+    client.reload_tls(reload).unwrap();
+
+    let res = client.get(&url).send().unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[test]
 #[cfg(feature = "__tls")]
 fn test_response_no_tls_info_for_http() {
@@ -412,3 +465,385 @@ fn test_response_no_tls_info_for_http() {
     let body = res.text().unwrap();
     assert_eq!(b"Hello", body.as_bytes());
 }
+
+#[test]
+fn test_runtime_handle() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let client = reqwest::blocking::Client::builder()
+        .runtime_handle(rt.handle().clone())
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let url = format!("http://{}/text", server.addr());
+        let body = client.get(&url).send().unwrap().text().unwrap();
+        assert_eq!(body, "Hello");
+    }
+
+    drop(client);
+    rt.shutdown_background();
+}
+
+#[test]
+fn test_runtime_handle_shut_down() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let client = reqwest::blocking::Client::builder()
+        .runtime_handle(rt.handle().clone())
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/text", server.addr());
+    assert_eq!(client.get(&url).send().unwrap().text().unwrap(), "Hello");
+
+    rt.shutdown_background();
+
+    let err = client.get(&url).send().unwrap_err();
+    assert!(err.is_request());
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+
+    let requests = vec![client.get(&url).build().unwrap()];
+    let results = client.send_all(requests, 1);
+    assert!(results[0].as_ref().unwrap_err().is_request());
+}
+
+#[test]
+fn test_shared_runtime() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let url = format!("http://{}/text", server.addr());
+    for _ in 0..2 {
+        let a = reqwest::blocking::Client::builder()
+            .shared_runtime(true)
+            .build()
+            .unwrap();
+        let b = reqwest::blocking::Client::builder()
+            .shared_runtime(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(a.get(&url).send().unwrap().text().unwrap(), "Hello");
+        assert_eq!(b.get(&url).send().unwrap().text().unwrap(), "Hello");
+        drop(a);
+        assert_eq!(b.get(&url).send().unwrap().text().unwrap(), "Hello");
+    }
+}
+
+#[test]
+fn test_send_all() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let (in_flight2, max_in_flight2) = (in_flight.clone(), max_in_flight.clone());
+    let server = server::http(move |req| {
+        let in_flight = in_flight2.clone();
+        let max_in_flight = max_in_flight2.clone();
+        async move {
+            let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(n, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            http::Response::new(req.uri().path().to_owned().into())
+        }
+    });
+
+    let client = reqwest::blocking::Client::new();
+    let requests = (0..6)
+        .map(|i| {
+            client
+                .get(format!("http://{}/{i}", server.addr()))
+                .build()
+                .unwrap()
+        })
+        .collect();
+
+    let results = client.send_all(requests, 2);
+    assert_eq!(results.len(), 6);
+    for (i, res) in results.into_iter().enumerate() {
+        assert_eq!(res.unwrap().text().unwrap(), format!("/{i}"));
+    }
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_send_all_timeout() {
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/slow" {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        http::Response::default()
+    });
+
+    let client = reqwest::blocking::Client::new();
+    let slow = client
+        .get(format!("http://{}/slow", server.addr()))
+        .timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let fast = client
+        .get(format!("http://{}/fast", server.addr()))
+        .build()
+        .unwrap();
+
+    let mut results = client.send_all(vec![slow, fast], 2).into_iter();
+    let err = results.next().unwrap().unwrap_err();
+    assert!(err.is_timeout());
+    assert_eq!(
+        err.url().map(|u| u.path()),
+        Some("/slow"),
+        "error should carry the request url"
+    );
+    assert_eq!(
+        results.next().unwrap().unwrap().status(),
+        reqwest::StatusCode::OK
+    );
+}
+
+#[test]
+fn test_response_buf_read() {
+    use std::io::{BufRead, Read};
+
+    let server = server::http(move |_req| async {
+        http::Response::new("first\nsecond\r\nthird\nrest".into())
+    });
+
+    let url = format!("http://{}/lines", server.addr());
+    let mut res = reqwest::blocking::get(&url)
+        .unwrap()
+        .with_buffer_capacity(4);
+
+    let mut line = String::new();
+    res.read_line(&mut line).unwrap();
+    assert_eq!(line, "first\n");
+
+    let mut lines = (&mut res).lines();
+    assert_eq!(lines.next().unwrap().unwrap(), "second");
+    assert_eq!(lines.next().unwrap().unwrap(), "third");
+
+    // Plain reads continue after the buffered data.
+    let mut rest = String::new();
+    res.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "rest");
+}
+
+#[test]
+fn test_response_events() {
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .header("content-type", "text/event-stream")
+            .body("data: hello\n\nevent: update\ndata: 1\ndata: 2\n\n".into())
+            .unwrap()
+    });
+
+    let url = format!("http://{}/events", server.addr());
+    let events = reqwest::blocking::get(&url)
+        .unwrap()
+        .events()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event(), "message");
+    assert_eq!(events[0].data(), "hello");
+    assert_eq!(events[1].event(), "update");
+    assert_eq!(events[1].data(), "1\n2");
+}
+
+#[test]
+fn test_lazy_runtime_built_in_async_context() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let client = rt.block_on(async {
+        reqwest::blocking::Client::builder()
+            .lazy_runtime(true)
+            .build()
+            .unwrap()
+    });
+    drop(rt);
+
+    let url = format!("http://{}/text", server.addr());
+    assert_eq!(client.get(&url).send().unwrap().text().unwrap(), "Hello");
+}
+
+#[cfg(feature = "__rustls")]
+#[test]
+fn test_lazy_runtime_start_error() {
+    let client = reqwest::blocking::Client::builder()
+        .lazy_runtime(true)
+        .tls_version_max(reqwest::tls::Version::TLS_1_0)
+        .build()
+        .expect("lazy build does not fail");
+
+    let url = "http://127.0.0.1:1/";
+    let err = client.get(url).send().unwrap_err();
+    assert!(err.is_builder());
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url));
+
+    // Later requests fail too, without retrying the startup.
+    let err = client.get(url).send().unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[test]
+fn test_send_all_start_error() {
+    let client = reqwest::blocking::Client::builder()
+        .lazy_runtime(true)
+        .tls_version_max(reqwest::tls::Version::TLS_1_0)
+        .build()
+        .expect("lazy build does not fail");
+
+    let urls = ["http://127.0.0.1:1/a", "http://127.0.0.1:1/b"];
+    let requests = urls
+        .iter()
+        .map(|url| client.get(*url).build().unwrap())
+        .collect();
+
+    let results = client.send_all(requests, 2);
+    assert_eq!(results.len(), 2);
+    for (url, res) in urls.iter().zip(results) {
+        let err = res.unwrap_err();
+        assert!(err.is_builder());
+        assert_eq!(err.url().map(|u| u.as_str()), Some(*url));
+    }
+}
+
+#[test]
+fn test_from_async_client() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["reqwest-test"], "shared");
+        http::Response::new("Hello".into())
+    });
+
+    let mut headers = http::HeaderMap::new();
+    headers.insert("reqwest-test", "shared".parse().unwrap());
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+    let url = format!("http://{}/text", server.addr());
+
+    let (client, blocking) = rt.block_on(async {
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let blocking = reqwest::blocking::Client::from_async(client.clone(), rt.handle().clone());
+        (client, blocking)
+    });
+
+    assert_eq!(blocking.get(&url).send().unwrap().text().unwrap(), "Hello");
+
+    // The pooled connection outlives the blocking client.
+    drop(blocking);
+    let body = rt.block_on(async { client.get(&url).send().await?.text().await });
+    assert_eq!(body.unwrap(), "Hello");
+}
+
+#[test]
+fn test_transfer_stats() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let client = reqwest::blocking::Client::builder()
+        .no_proxy()
+        .lazy_runtime(true)
+        .build()
+        .unwrap();
+    assert_eq!(client.transfer_stats().requests(), 0);
+
+    let url = format!("http://{}/text", server.addr());
+    assert_eq!(client.get(&url).send().unwrap().text().unwrap(), "Hello");
+
+    let stats = client.transfer_stats();
+    assert_eq!(stats.requests(), 1);
+    assert_eq!(stats.success_responses(), 1);
+    assert!(stats.bytes_received() > 5);
+}
+
+#[test]
+fn test_to_builder() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["user-agent"], "my-app/1.0");
+        http::Response::default()
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let base = reqwest::blocking::Client::builder()
+        .user_agent("my-app/1.0")
+        .no_proxy()
+        .lazy_runtime(true)
+        .build()
+        .unwrap();
+    let derived = base
+        .to_builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    assert_eq!(derived.get(&url).send().unwrap().status(), 200);
+    assert_eq!(base.transfer_stats().requests(), 0);
+}
+
+#[test]
+fn test_priority() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["priority"], "u=6");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/", server.addr());
+    let res = reqwest::blocking::Client::new()
+        .get(&url)
+        .priority(6, false)
+        .send()
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[test]
+fn test_base_url() {
+    let server =
+        server::http(move |req| async move { http::Response::new(req.uri().to_string().into()) });
+
+    let client = reqwest::blocking::Client::builder()
+        .base_url(format!("http://{}/api/", server.addr()))
+        .build()
+        .unwrap();
+
+    let res = client.get("users").send().unwrap();
+    assert_eq!(res.text().unwrap(), "/api/users");
+}
+
+#[test]
+fn test_default_query() {
+    let server =
+        server::http(move |req| async move { http::Response::new(req.uri().to_string().into()) });
+
+    let client = reqwest::blocking::Client::builder()
+        .default_query(&[("api_key", "secret")])
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/items?page=2", server.addr());
+    let res = client.get(url).send().unwrap();
+    assert_eq!(res.text().unwrap(), "/items?page=2&api_key=secret");
+}
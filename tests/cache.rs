@@ -0,0 +1,490 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use reqwest::cache::{DiskStore, Entry, Store};
+use reqwest::{Client, StatusCode};
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("reqwest-cache-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn client(name: &str) -> Client {
+    let store = DiskStore::open(cache_dir(name)).unwrap();
+    Client::builder()
+        .no_proxy()
+        .cache(Arc::new(store))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn fresh_response_is_reused() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |_req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {
+            http::Response::builder()
+                .header("cache-control", "max-age=60")
+                .body("hello".into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/fresh", server.addr());
+    let client = client("fresh");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["age"], "0");
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // the request can ask for a fresher response
+    let res = client
+        .get(&url)
+        .header("cache-control", "max-age=0")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn stale_response_is_revalidated() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            let builder = http::Response::builder()
+                .header("cache-control", "no-cache")
+                .header("etag", "\"v1\"");
+            if req
+                .headers()
+                .get("if-none-match")
+                .is_some_and(|tag| tag == "\"v1\"")
+            {
+                builder
+                    .status(304)
+                    .header("x-revalidated", "yes")
+                    .body(Default::default())
+                    .unwrap()
+            } else {
+                builder.body("hello".into()).unwrap()
+            }
+        }
+    });
+    let url = format!("http://{}/stale", server.addr());
+    let client = client("stale");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["x-revalidated"], "yes");
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn no_store_is_not_cached() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |_req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {
+            http::Response::builder()
+                .header("cache-control", "max-age=60, no-store")
+                .body("hello".into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/no-store", server.addr());
+    let client = client("no_store");
+
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.text().await.unwrap(), "hello");
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn unsafe_request_invalidates() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |_req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {
+            http::Response::builder()
+                .header("cache-control", "max-age=60")
+                .body("hello".into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/invalidate", server.addr());
+    let client = client("invalidate");
+
+    client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    client.post(&url).send().await.unwrap();
+    client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn vary_selects_response() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let accept = req.headers()["accept"].to_str().unwrap().to_owned();
+        async move {
+            http::Response::builder()
+                .header("cache-control", "max-age=60")
+                .header("vary", "accept")
+                .body(accept.into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/vary", server.addr());
+    let client = client("vary");
+
+    for accept in ["text/html", "text/html", "application/json"] {
+        let res = client
+            .get(&url)
+            .header("accept", accept)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), accept);
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+fn entry(body: &'static str) -> Entry {
+    Entry::new(StatusCode::OK, Default::default(), Bytes::from(body))
+}
+
+#[test]
+fn disk_store_persists() {
+    let dir = cache_dir("persists");
+    let store = DiskStore::open(&dir).unwrap();
+    store.put("https://example.com/a", entry("same body"));
+    store.put("https://example.com/b", entry("same body"));
+    // identical bodies are stored once
+    assert_eq!(store.size(), 9);
+    drop(store);
+
+    let store = DiskStore::open(&dir).unwrap();
+    let found = store.get("https://example.com/a").unwrap();
+    assert_eq!(found.status(), StatusCode::OK);
+    assert_eq!(found.body(), "same body");
+
+    store.remove("https://example.com/a");
+    assert!(store.get("https://example.com/a").is_none());
+    assert!(store.get("https://example.com/b").is_some());
+}
+
+#[test]
+fn disk_store_evicts_least_recently_used() {
+    let store = DiskStore::open(cache_dir("evicts")).unwrap().max_size(25);
+    store.put("a", entry("aaaaaaaaaa"));
+    store.put("b", entry("bbbbbbbbbb"));
+    assert!(store.get("a").is_some());
+    store.put("c", entry("cccccccccc"));
+
+    assert!(store.get("a").is_some());
+    assert!(store.get("b").is_none());
+    assert!(store.get("c").is_some());
+    assert_eq!(store.size(), 20);
+
+    // too large to ever fit
+    store.put("d", entry("dddddddddddddddddddddddddddddd"));
+    assert!(store.get("d").is_none());
+}
+
+#[tokio::test]
+async fn stale_while_revalidate() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |_req| {
+        let version = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        async move {
+            http::Response::builder()
+                .header("cache-control", "max-age=0, stale-while-revalidate=60")
+                .body(format!("v{version}").into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/swr", server.addr());
+    let store = Arc::new(DiskStore::open(cache_dir("swr")).unwrap());
+    let client = Client::builder()
+        .no_proxy()
+        .cache(store.clone())
+        .cache_stale_while_revalidate(true)
+        .build()
+        .unwrap();
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "v1");
+
+    // the stale response is returned, and refreshed in the background
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "v1");
+    for _ in 0..100 {
+        if store.get(&url).is_some_and(|entry| entry.body() == "v2") {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "v2");
+}
+
+#[tokio::test]
+async fn stale_if_error() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |_req| {
+        let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+        async move {
+            if first {
+                http::Response::builder()
+                    .header("cache-control", "max-age=0, stale-if-error=60")
+                    .body("hello".into())
+                    .unwrap()
+            } else {
+                http::Response::builder()
+                    .status(503)
+                    .body(Default::default())
+                    .unwrap()
+            }
+        }
+    });
+    let url = format!("http://{}/sie", server.addr());
+    let store = Arc::new(DiskStore::open(cache_dir("sie")).unwrap());
+    let client = |stale_if_error| {
+        Client::builder()
+            .no_proxy()
+            .cache(store.clone())
+            .cache_stale_if_error(stale_if_error)
+            .build()
+            .unwrap()
+    };
+
+    let res = client(true).get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    let res = client(false).get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let res = client(true).get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    // also when the server cannot be reached
+    drop(server);
+    let res = client(true).get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert!(client(false).get(&url).send().await.is_err());
+}
+
+#[tokio::test]
+async fn only_if_cached() {
+    use reqwest::cache::Mode;
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |_req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {
+            http::Response::builder()
+                .header("cache-control", "no-cache")
+                .header("etag", "\"v1\"")
+                .body("hello".into())
+                .unwrap()
+        }
+    });
+    let url = format!("http://{}/offline", server.addr());
+    let client = client("offline");
+
+    let err = client
+        .get(&url)
+        .cache_mode(Mode::OnlyIfCached)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_cache_miss());
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // the stored response is stale, but used as is
+    for mode in [Mode::OnlyIfCached, Mode::ForceCache] {
+        let res = client.get(&url).cache_mode(mode).send().await.unwrap();
+        assert_eq!(res.text().await.unwrap(), "hello");
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // without a cache, there is never a stored response
+    let err = Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .cache_mode(Mode::OnlyIfCached)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_cache_miss());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn reload_and_no_cache() {
+    use reqwest::cache::Mode;
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |req| {
+        let version = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let conditional = req.headers().contains_key("if-none-match");
+        async move {
+            let builder = http::Response::builder()
+                .header("cache-control", "max-age=60")
+                .header("etag", "\"v1\"");
+            if conditional {
+                builder.status(304).body(Default::default()).unwrap()
+            } else {
+                builder.body(format!("v{version}").into()).unwrap()
+            }
+        }
+    });
+    let url = format!("http://{}/reload", server.addr());
+    let client = client("reload");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "v1");
+
+    // a fresh response is revalidated anyway
+    let res = client
+        .get(&url)
+        .cache_mode(Mode::NoCache)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "v1");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // a new response replaces the stored one
+    let res = client
+        .get(&url)
+        .cache_mode(Mode::Reload)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "v3");
+
+    // and isn't replaced without storing
+    let res = client
+        .get(&url)
+        .cache_mode(Mode::NoStore)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "v4");
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "v3");
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn conditional_requests() {
+    use reqwest::cache::NotModified;
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let server = server::http(move |req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let matches = req
+            .headers()
+            .get("if-none-match")
+            .is_some_and(|tag| tag == "\"v1\"");
+        async move {
+            let builder = http::Response::builder().header("etag", "\"v1\"");
+            if matches {
+                builder.status(304).body(Default::default()).unwrap()
+            } else {
+                builder.body("hello".into()).unwrap()
+            }
+        }
+    });
+    let url = format!("http://{}/conditional", server.addr());
+    let client = |name, not_modified| {
+        let store = DiskStore::open(cache_dir(name)).unwrap();
+        Client::builder()
+            .no_proxy()
+            .conditional_requests(Arc::new(store), not_modified)
+            .build()
+            .unwrap()
+    };
+
+    let raw = client("conditional_raw", NotModified::Return);
+    let res = raw.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    let res = raw.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.text().await.unwrap(), "");
+
+    let fulfilled = client("conditional_fulfilled", NotModified::Fulfill);
+    let res = fulfilled.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    let res = fulfilled.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    // every request reaches the server
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,112 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Reading {
+    sensor: String,
+    value: f64,
+}
+
+fn reading() -> Reading {
+    Reading {
+        sensor: "temp-1".to_owned(),
+        value: 21.5,
+    }
+}
+
+fn encode(reading: &Reading) -> Vec<u8> {
+    let mut body = Vec::new();
+    ciborium::into_writer(reading, &mut body).unwrap();
+    body
+}
+
+#[tokio::test]
+async fn cbor_roundtrip() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["content-type"], "application/cbor");
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let mut reading: Reading = ciborium::from_reader(&body[..]).unwrap();
+        reading.value += 1.0;
+        http::Response::builder()
+            .header("content-type", "application/cbor")
+            .body(encode(&reading).into())
+            .unwrap()
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!("http://{}/readings", server.addr()))
+        .cbor(&reading())
+        .send()
+        .await
+        .unwrap();
+
+    let reading: Reading = res.cbor().await.unwrap();
+    assert_eq!(reading.sensor, "temp-1");
+    assert_eq!(reading.value, 22.5);
+}
+
+#[tokio::test]
+async fn cbor_suffix_content_type() {
+    let server = server::http(|_req| async move {
+        http::Response::builder()
+            .header("content-type", "application/senml+cbor")
+            .body(encode(&reading()).into())
+            .unwrap()
+    });
+
+    let res = reqwest::get(format!("http://{}/reading", server.addr()))
+        .await
+        .unwrap();
+
+    assert_eq!(res.cbor::<Reading>().await.unwrap(), reading());
+}
+
+#[tokio::test]
+async fn cbor_wrong_content_type() {
+    let server = server::http(|_req| async move {
+        http::Response::builder()
+            .header("content-type", "application/json")
+            .body(r#"{"sensor":"temp-1","value":21.5}"#.into())
+            .unwrap()
+    });
+
+    let err = reqwest::get(format!("http://{}/reading", server.addr()))
+        .await
+        .unwrap()
+        .cbor::<Reading>()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_cbor_roundtrip() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.headers()["content-type"], "application/cbor");
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        http::Response::builder()
+            .header("content-type", "application/cbor")
+            .body(body.into())
+            .unwrap()
+    });
+
+    let res = reqwest::blocking::Client::new()
+        .post(format!("http://{}/readings", server.addr()))
+        .cbor(&reading())
+        .send()
+        .unwrap();
+
+    assert_eq!(res.cbor::<Reading>().unwrap(), reading());
+}
@@ -0,0 +1,232 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::circuit::Breaker;
+use reqwest::StatusCode;
+
+/// A server answering with the status in `status`, counting its requests.
+fn server(status: Arc<AtomicU16>, hits: Arc<AtomicUsize>) -> server::Server {
+    server::http(move |_req| {
+        let status = status.load(Ordering::SeqCst);
+        hits.fetch_add(1, Ordering::SeqCst);
+        async move {
+            http::Response::builder()
+                .status(status)
+                .body(Default::default())
+                .unwrap()
+        }
+    })
+}
+
+#[tokio::test]
+async fn circuit_opens_after_threshold() {
+    let status = Arc::new(AtomicU16::new(500));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server(status, hits.clone());
+    let client = reqwest::Client::builder()
+        .circuit_breaker(
+            Breaker::new()
+                .failure_threshold(2)
+                .cool_down(Duration::from_secs(60)),
+        )
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_circuit_open(), "{err:?}");
+    assert!(err.is_request());
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // other hosts are not affected
+    let other = server::http(move |_req| async { http::Response::default() });
+    let res = client
+        .get(format!("http://{}/", other.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn successes_reset_threshold() {
+    let status = Arc::new(AtomicU16::new(500));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server(status.clone(), hits.clone());
+    let client = reqwest::Client::builder()
+        .circuit_breaker(Breaker::new().failure_threshold(2))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    for code in [500, 200, 500, 200, 500] {
+        status.store(code, Ordering::SeqCst);
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status().as_u16(), code);
+    }
+}
+
+#[tokio::test]
+async fn circuit_opens_on_failure_rate() {
+    let status = Arc::new(AtomicU16::new(200));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server(status.clone(), hits.clone());
+    let client = reqwest::Client::builder()
+        .circuit_breaker(Breaker::new().failure_threshold(0).failure_rate(0.5, 4))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    for code in [200, 500, 200, 500] {
+        status.store(code, Ordering::SeqCst);
+        client.get(&url).send().await.unwrap();
+    }
+    // the window is full, with half of its requests failed
+    status.store(200, Ordering::SeqCst);
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_circuit_open(), "{err:?}");
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn circuit_closes_after_probe() {
+    let status = Arc::new(AtomicU16::new(500));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server(status.clone(), hits.clone());
+    let client = reqwest::Client::builder()
+        .circuit_breaker(
+            Breaker::new()
+                .failure_threshold(1)
+                .cool_down(Duration::from_millis(100)),
+        )
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    client.get(&url).send().await.unwrap();
+    assert!(client.get(&url).send().await.unwrap_err().is_circuit_open());
+
+    // a failed probe opens the circuit again
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(client.get(&url).send().await.unwrap_err().is_circuit_open());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // a successful one closes it
+    status.store(200, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn connect_errors_are_failures() {
+    let client = reqwest::Client::builder()
+        .circuit_breaker(Breaker::new().failure_threshold(1))
+        .build()
+        .unwrap();
+
+    // nothing listens on this port
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    drop(listener);
+
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_connect(), "{err:?}");
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_circuit_open(), "{err:?}");
+}
+
+#[tokio::test]
+async fn timeouts_are_failures() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    // a host that hangs, answering long after the timeout
+    let server = server::http(move |_req| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            http::Response::default()
+        }
+    });
+    let client = reqwest::Client::builder()
+        .circuit_breaker(
+            Breaker::new()
+                .failure_threshold(1)
+                .cool_down(Duration::from_millis(100)),
+        )
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_timeout(), "{err:?}");
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_circuit_open(), "{err:?}");
+
+    // a probe timing out opens the circuit again
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_timeout(), "{err:?}");
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_circuit_open(), "{err:?}");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn dropped_requests_are_not_failures() {
+    let server = server::http(move |_req| async {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        http::Response::default()
+    });
+    let client = reqwest::Client::builder()
+        .circuit_breaker(Breaker::new().failure_threshold(1))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    // the caller gives up on the request, which says nothing of the host
+    for _ in 0..2 {
+        let send = client.get(&url).send();
+        tokio::time::timeout(Duration::from_millis(50), send)
+            .await
+            .unwrap_err();
+    }
+    let send = client.get(&url).send();
+    tokio::time::timeout(Duration::from_millis(50), send)
+        .await
+        .unwrap_err();
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_circuit_opens() {
+    let status = Arc::new(AtomicU16::new(503));
+    let hits = Arc::new(AtomicUsize::new(0));
+    let server = server(status, hits.clone());
+    let client = reqwest::blocking::Client::builder()
+        .circuit_breaker(Breaker::new().failure_threshold(1))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    client.get(&url).send().unwrap();
+    let err = client.get(&url).send().unwrap_err();
+    assert!(err.is_circuit_open(), "{err:?}");
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
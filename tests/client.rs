@@ -159,6 +159,101 @@ async fn response_json() {
     assert_eq!("Hello", text);
 }
 
+#[tokio::test]
+#[cfg(feature = "json")]
+async fn response_json_large() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        let items = (0..10_000)
+            .map(|i| format!(r#"{{"id":{i},"name":"item \u00e9{i}","tags":["a","b"]}}"#))
+            .collect::<Vec<_>>();
+        http::Response::new(format!("[{}]", items.join(",")).into())
+    });
+
+    #[derive(serde::Deserialize)]
+    struct Item {
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    let res = reqwest::get(format!("http://{}/json", server.addr()))
+        .await
+        .expect("Failed to get");
+    let items = res.json::<Vec<Item>>().await.expect("Failed to get json");
+    assert_eq!(items.len(), 10_000);
+    assert_eq!(items[9_999].id, 9_999);
+    assert_eq!(items[9_999].name, "item \u{e9}9999");
+    assert_eq!(items[0].tags, ["a", "b"]);
+
+    let server = server::http(move |_req| async { http::Response::new("[1, 2".into()) });
+    let err = reqwest::get(format!("http://{}/json", server.addr()))
+        .await
+        .expect("Failed to get")
+        .json::<Vec<u32>>()
+        .await
+        .unwrap_err();
+    assert!(err.is_decode());
+}
+
+#[tokio::test]
+#[cfg(feature = "json")]
+async fn strict_content_type() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let content_type = match req.uri().path() {
+            "/json" => Some("application/problem+json"),
+            "/html" => Some("text/html; charset=utf-8"),
+            _ => None,
+        };
+        let mut res = http::Response::builder();
+        if let Some(content_type) = content_type {
+            res = res.header("content-type", content_type);
+        }
+        res.body("\"Hello\"".into()).unwrap()
+    });
+
+    let client = Client::builder().strict_content_type(true).build().unwrap();
+
+    let res = client
+        .get(format!("http://{}/json", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.json::<String>().await.unwrap(), "Hello");
+
+    let err = client
+        .get(format!("http://{}/html", server.addr()))
+        .send()
+        .await
+        .unwrap()
+        .json::<String>()
+        .await
+        .unwrap_err();
+    assert!(err.is_decode());
+    assert!(err.is_content_type());
+    assert_eq!(err.content_type().unwrap(), "text/html; charset=utf-8");
+
+    let err = client
+        .get(format!("http://{}/none", server.addr()))
+        .send()
+        .await
+        .unwrap()
+        .json::<String>()
+        .await
+        .unwrap_err();
+    assert!(err.is_content_type());
+    assert_eq!(err.content_type(), None);
+
+    // without strict mode, the body is parsed whatever its content type
+    let res = reqwest::get(format!("http://{}/html", server.addr()))
+        .await
+        .unwrap();
+    assert_eq!(res.json::<String>().await.unwrap(), "Hello");
+}
+
 #[tokio::test]
 async fn body_pipe_response() {
     use http_body_util::BodyExt;
@@ -234,6 +329,7 @@ async fn dns_resolution_failure_is_dns_error() {
     assert!(err.is_dns(), "expected a DNS error, got: {err:?}");
     // DNS errors are a refinement of connect errors.
     assert!(err.is_connect(), "expected is_connect() to also be true");
+    assert_eq!(err.code(), reqwest::ErrorCode::Dns);
 }
 
 #[tokio::test]
@@ -293,6 +389,266 @@ async fn overridden_dns_resolution_with_gai_multiple() {
     assert_eq!("Hello", text);
 }
 
+#[tokio::test]
+async fn dns_balance_round_robin() {
+    let a = server::http(move |_req| async { http::Response::new("a".into()) });
+    let b = server::http(move |_req| async { http::Response::new("b".into()) });
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .pool_max_idle_per_host(0)
+        .resolve_to_addrs("balanced.test", &[a.addr(), b.addr()])
+        .dns_balance(reqwest::dns::Balance::round_robin())
+        .build()
+        .expect("client builder");
+
+    let mut served = Vec::new();
+    for _ in 0..4 {
+        let res = client.get("http://balanced.test/").send().await.unwrap();
+        let addr = res.remote_addr().unwrap();
+        let text = res.text().await.unwrap();
+        assert_eq!(addr, if text == "a" { a.addr() } else { b.addr() });
+        served.push(text);
+    }
+    assert_eq!(served, ["a", "b", "a", "b"]);
+}
+
+#[tokio::test]
+async fn connect_falls_back_to_next_address() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    // nothing listens on this port
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let dead = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .pool_max_idle_per_host(0)
+        .resolve_to_addrs("fallback.test", &[dead, server.addr()])
+        .avoid_failed_addresses(std::time::Duration::from_secs(60))
+        .build()
+        .expect("client builder");
+
+    for _ in 0..2 {
+        let res = client.get("http://fallback.test/").send().await.unwrap();
+        assert_eq!(res.remote_addr(), Some(server.addr()));
+    }
+}
+
+#[tokio::test]
+async fn connection_reset_error() {
+    use tokio::io::AsyncReadExt;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1024];
+        let _ = socket.read(&mut buf).await;
+        // reset the connection instead of answering
+        socket.set_zero_linger().unwrap();
+    });
+
+    let err = reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(format!("http://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connection_reset(), "{err:?}");
+    assert!(!err.is_tls());
+    assert!(!err.is_proxy());
+}
+
+#[tokio::test]
+async fn error_has_connection() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1024];
+        let _ = socket.read(&mut buf).await;
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+            .await
+            .unwrap();
+        // reset the connection on the second request
+        let _ = socket.read(&mut buf).await;
+        socket.set_zero_linger().unwrap();
+    });
+
+    let client = reqwest::Client::builder().no_proxy().build().unwrap();
+    let url = format!("http://{addr}/");
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "ok");
+
+    let err = client.get(&url).send().await.unwrap_err();
+    let conn = err.connection().expect("connection");
+    assert_eq!(conn.remote_addr(), Some(addr));
+    assert_eq!(conn.version(), reqwest::Version::HTTP_11);
+    assert!(conn.is_reused());
+    assert!(!conn.is_proxied());
+}
+
+#[tokio::test]
+async fn timeout_error_has_connection() {
+    let server = server::http(move |_req| async {
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        http::Response::default()
+    });
+
+    let location = format!("http://{}/", server.addr());
+    let redirect = server::http(move |_req| {
+        let location = location.clone();
+        async move {
+            http::Response::builder()
+                .status(302)
+                .header("location", location)
+                .body(Default::default())
+                .unwrap()
+        }
+    });
+
+    // the connection of the request after the redirect
+    let err = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(std::time::Duration::from_millis(200))
+        .build()
+        .unwrap()
+        .get(format!("http://{}/", redirect.addr()))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+    let conn = err.connection().expect("connection");
+    assert_eq!(conn.remote_addr(), Some(server.addr()));
+    assert!(!conn.is_reused());
+}
+
+#[tokio::test]
+async fn connect_error_has_no_connection() {
+    // nothing listens on this port
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let err = reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(format!("http://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+    assert!(err.connection().is_none());
+}
+
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn tls_handshake_error() {
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    // the server doesn't speak TLS
+    let err = reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(format!("https://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_tls(), "{err:?}");
+    assert!(err.is_connect());
+    assert!(!err.is_dns());
+    assert_eq!(err.tls_failure(), Some(reqwest::tls::TlsFailure::Other));
+}
+
+// A TLS server with the test certificate, which isn't trusted.
+#[cfg(feature = "__rustls-aws-lc-rs")]
+async fn rustls_server(
+    versions: &[&'static rustls::SupportedProtocolVersion],
+) -> std::net::SocketAddr {
+    let cert = std::fs::read("tests/support/server.cert").unwrap().into();
+    let key = std::fs::read("tests/support/server.key")
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let provider = std::sync::Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(versions)
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((tcp, _)) = listener.accept().await {
+            let _ = acceptor.accept(tcp).await;
+        }
+    });
+    addr
+}
+
+#[cfg(feature = "__rustls-aws-lc-rs")]
+#[tokio::test]
+async fn tls_failure_unknown_issuer() {
+    use reqwest::tls::{CertificateFailure, TlsFailure};
+
+    let addr = rustls_server(rustls::DEFAULT_VERSIONS).await;
+    let err = reqwest::Client::builder()
+        .tls_backend_rustls()
+        .no_proxy()
+        .resolve("localhost", addr)
+        .build()
+        .unwrap()
+        .get(format!("https://localhost:{}/", addr.port()))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_tls(), "{err:?}");
+    assert_eq!(
+        err.tls_failure(),
+        Some(TlsFailure::Certificate(CertificateFailure::UnknownIssuer))
+    );
+    assert!(matches!(
+        err.rustls_error(),
+        Some(rustls::Error::InvalidCertificate(_))
+    ));
+}
+
+#[cfg(feature = "__rustls-aws-lc-rs")]
+#[tokio::test]
+async fn tls_failure_unsupported_protocol() {
+    let addr = rustls_server(&[&rustls::version::TLS12]).await;
+    let err = reqwest::Client::builder()
+        .tls_backend_rustls()
+        .tls_version_min(reqwest::tls::Version::TLS_1_3)
+        .no_proxy()
+        .resolve("localhost", addr)
+        .build()
+        .unwrap()
+        .get(format!("https://localhost:{}/", addr.port()))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err.tls_failure(),
+        Some(reqwest::tls::TlsFailure::UnsupportedProtocol),
+        "{err:?}"
+    );
+}
+
 #[cfg(feature = "hickory-dns")]
 #[tokio::test]
 async fn overridden_dns_resolution_with_hickory_dns() {
@@ -400,6 +756,72 @@ fn use_preconfigured_rustls_default() {
         .expect("preconfigured rustls tls");
 }
 
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn reload_tls_drain() {
+    use reqwest::observe::{ConnectionObserver, TcpConnected};
+    use reqwest::tls::TlsReload;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Connects(Arc<AtomicUsize>);
+
+    impl ConnectionObserver for Connects {
+        fn tcp_connected(&self, _event: &TcpConnected) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let connects = Connects::default();
+    let client = Client::builder()
+        .no_proxy()
+        .connection_events(connects.clone())
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    client.get(&url).send().await.unwrap();
+    client.reload_tls(TlsReload::new()).unwrap();
+    client.get(&url).send().await.unwrap();
+    assert_eq!(connects.0.load(Ordering::SeqCst), 1);
+
+    client.reload_tls(TlsReload::new().drain(true)).unwrap();
+    client.get(&url).send().await.unwrap();
+    assert_eq!(connects.0.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(all(feature = "rustls", not(feature = "__native-tls")))]
+#[test]
+fn reload_tls_errors() {
+    use reqwest::tls::TlsReload;
+
+    // rustls only parses the certificate when the configuration is built
+    let bogus = reqwest::Certificate::from_der(b"not der").unwrap();
+    let client = reqwest::Client::builder()
+        .tls_certs_only([])
+        .build()
+        .unwrap();
+    client
+        .reload_tls(TlsReload::new().root_certificates([bogus]))
+        .unwrap_err();
+    client.reload_tls(TlsReload::new()).unwrap();
+
+    let tls = rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(
+        rustls::crypto::aws_lc_rs::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_root_certificates(rustls::RootCertStore::empty())
+    .with_no_client_auth();
+    let client = reqwest::Client::builder()
+        .tls_backend_preconfigured(tls)
+        .build()
+        .unwrap();
+    client.reload_tls(TlsReload::new()).unwrap_err();
+}
+
 #[cfg(all(feature = "__tls", not(any(feature = "http2", feature = "http3")),))]
 #[tokio::test]
 async fn http1_only() {
@@ -491,6 +913,57 @@ fn update_json_content_type_if_set_manually() {
     assert_eq!("application/json", req.headers().get(CONTENT_TYPE).unwrap());
 }
 
+fn upper_case_pairs<const N: usize>(pairs: &[(&str, bool); N]) -> Result<String, std::fmt::Error> {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in pairs {
+        serializer.append_pair(&key.to_uppercase(), if *value { "yes" } else { "no" });
+    }
+    Ok(serializer.finish())
+}
+
+#[test]
+fn query_with_custom_encoder() {
+    let req = Client::new()
+        .get("https://google.com/?q=rust")
+        .query_with(&[("safe", true), ("images", false)], upper_case_pairs)
+        .query_with(&[], upper_case_pairs)
+        .build()
+        .expect("request is not valid");
+
+    assert_eq!(req.url().query(), Some("q=rust&SAFE=yes&IMAGES=no"));
+
+    let req = Client::new()
+        .get("https://google.com/")
+        .query_with(&[("safe", true)], upper_case_pairs)
+        .build()
+        .expect("request is not valid");
+
+    assert_eq!(req.url().query(), Some("SAFE=yes"));
+
+    let err = Client::new()
+        .get("https://google.com/")
+        .query_with(&(), |_| Err(std::fmt::Error))
+        .build()
+        .unwrap_err();
+
+    assert!(err.is_builder());
+}
+
+#[test]
+fn form_with_custom_encoder() {
+    let req = Client::new()
+        .post("https://google.com/")
+        .form_with(&[("subscribe", true)], upper_case_pairs)
+        .build()
+        .expect("request is not valid");
+
+    assert_eq!(
+        "application/x-www-form-urlencoded",
+        req.headers().get(CONTENT_TYPE).unwrap()
+    );
+    assert_eq!(req.body().unwrap().as_bytes(), Some(&b"SUBSCRIBE=yes"[..]));
+}
+
 #[cfg(all(feature = "__tls", not(feature = "rustls-no-provider")))]
 #[tokio::test]
 async fn test_tls_info() {
@@ -542,6 +1015,89 @@ async fn close_connection_after_idle_timeout() {
         .any(|e| matches!(e, server::Event::ConnectionClosed)));
 }
 
+#[tokio::test]
+async fn early_hints() {
+    let server = server::low_level_with_response(|_raw_request, client_socket| {
+        Box::new(async move {
+            client_socket
+                .write_all(
+                    b"HTTP/1.1 100 Continue\r\n\r\n\
+                      HTTP/1.1 103 Early Hints\r\n\
+                      Link: </style.css>; rel=preload; as=style\r\n\r\n\
+                      HTTP/1.1 103 Early Hints\r\n\
+                      Link: </app.js>; rel=preload; as=script\r\n\r\n\
+                      HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+                )
+                .await
+                .expect("response write_all failed");
+        })
+    });
+
+    let hinted = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = hinted.clone();
+    let res = Client::new()
+        .get(format!("http://{}", server.addr()))
+        .on_early_hints(move |hints| {
+            let mut seen = seen.lock().unwrap();
+            for link in hints.links() {
+                seen.push(format!("{} {}", link.param("as").unwrap(), link.target()));
+            }
+        })
+        .send()
+        .await
+        .expect("Failed to get");
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "ok");
+    assert_eq!(
+        *hinted.lock().unwrap(),
+        ["style /style.css", "script /app.js"]
+    );
+}
+
+#[tokio::test]
+async fn informational_responses() {
+    let server = server::low_level_with_response(|_raw_request, client_socket| {
+        Box::new(async move {
+            client_socket
+                .write_all(
+                    b"HTTP/1.1 102 Processing\r\n\r\n\
+                      HTTP/1.1 103 Early Hints\r\n\
+                      Link: </style.css>; rel=preload\r\n\r\n\
+                      HTTP/1.1 204 No Content\r\n\r\n",
+                )
+                .await
+                .expect("response write_all failed");
+        })
+    });
+
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let statuses = seen.clone();
+    let res = Client::new()
+        .get(format!("http://{}", server.addr()))
+        .on_informational(move |interim| statuses.lock().unwrap().push(interim.status()))
+        .send()
+        .await
+        .expect("Failed to get");
+
+    assert_eq!(res.status(), reqwest::StatusCode::NO_CONTENT);
+    let informational = res.informational();
+    assert_eq!(informational.len(), 2);
+    assert_eq!(informational[0].status(), reqwest::StatusCode::PROCESSING);
+    assert_eq!(informational[1].status(), reqwest::StatusCode::EARLY_HINTS);
+    assert_eq!(
+        informational[1].headers()["link"],
+        "</style.css>; rel=preload"
+    );
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            reqwest::StatusCode::PROCESSING,
+            reqwest::StatusCode::EARLY_HINTS
+        ]
+    );
+}
+
 #[tokio::test]
 async fn http1_reason_phrase() {
     let server = server::low_level_with_response(|_raw_request, client_socket| {
@@ -571,8 +1127,1308 @@ async fn http1_reason_phrase() {
 }
 
 #[tokio::test]
-async fn error_has_url() {
-    let u = "http://does.not.exist.local/ever";
-    let err = reqwest::get(u).await.unwrap_err();
-    assert_eq!(err.url().map(AsRef::as_ref), Some(u), "{err:?}");
+async fn http1_max_headers() {
+    let server = server::http(move |_req| async move {
+        let mut res = http::Response::builder();
+        for i in 0..150 {
+            res = res.header(format!("x-header-{i}"), "value");
+        }
+        res.body(Default::default()).unwrap()
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let err = Client::new().get(&url).send().await.unwrap_err();
+    assert!(err.is_request());
+
+    let res = Client::builder()
+        .http1_max_headers(200)
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["x-header-149"], "value");
+}
+
+#[tokio::test]
+async fn http1_max_buf_size() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-security-policy", "a".repeat(600 * 1024))
+            .body(Default::default())
+            .unwrap()
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let err = Client::new().get(&url).send().await.unwrap_err();
+    assert!(err.is_request());
+
+    let res = Client::builder()
+        .http1_max_buf_size(1024 * 1024)
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["content-security-policy"].len(), 600 * 1024);
+
+    let err = Client::builder()
+        .http1_max_buf_size(1024)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn http1_writev() {
+    use http_body_util::BodyExt;
+
+    let server = server::http(move |req| async move {
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello world");
+        http::Response::default()
+    });
+
+    for enabled in [true, false] {
+        let res = Client::builder()
+            .http1_writev(enabled)
+            .build()
+            .unwrap()
+            .post(format!("http://{}/", server.addr()))
+            .body(bytes::Bytes::from_static(b"hello world"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+    }
+}
+
+#[cfg(all(feature = "interface-index", target_os = "linux"))]
+#[tokio::test]
+async fn interface_index() {
+    let server = server::http(move |_req| async { http::Response::default() });
+    let url = format!("http://{}/", server.addr());
+    // SAFETY: the name is nul-terminated.
+    let lo = unsafe { libc::if_nametoindex(c"lo".as_ptr()) };
+    assert_ne!(lo, 0);
+
+    let client = Client::builder().interface_index(lo).build().unwrap();
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let err = Client::builder()
+        .interface_index(u32::MAX)
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+}
+
+#[cfg(all(feature = "interface-index", target_os = "linux"))]
+#[tokio::test]
+async fn request_interface_index() {
+    let server = server::http(move |req| async move {
+        if req.uri() == "/redirect" {
+            http::Response::builder()
+                .status(302)
+                .header("location", "/dst")
+                .body(Default::default())
+                .unwrap()
+        } else {
+            http::Response::default()
+        }
+    });
+    // SAFETY: the name is nul-terminated.
+    let lo = unsafe { libc::if_nametoindex(c"lo".as_ptr()) };
+    let client = Client::new();
+
+    let res = client
+        .get(format!("http://{}/redirect", server.addr()))
+        .interface_index(lo)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().path(), "/dst");
+
+    let err = client
+        .get(format!("http://{}/", server.addr()))
+        .interface_index(u32::MAX)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+
+    // requests which are not bound keep using the pool of the client
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn priority_header() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["priority"], "u=1, i");
+        http::Response::default()
+    });
+
+    let res = Client::new()
+        .get(format!("http://{}/", server.addr()))
+        .header("priority", "u=5")
+        .priority(1, true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[cfg(feature = "http2")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn priority_header_http2() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.version(), http::Version::HTTP_2);
+        assert_eq!(req.headers()["priority"], "u=0");
+        http::Response::default()
+    });
+
+    let res = reqwest::Client::builder()
+        .http2_prior_knowledge()
+        .build()
+        .unwrap()
+        .get(format!("http://{}/", server.addr()))
+        .priority(0, false)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn priority_urgency_out_of_range() {
+    let err = Client::new()
+        .get("http://localhost/")
+        .priority(8, false)
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn default_priority() {
+    let server = server::http(move |req| async move {
+        let expected = match req.uri().path() {
+            "/bulk" => "u=7, i",
+            _ => "u=0",
+        };
+        assert_eq!(req.headers()["priority"], expected);
+        http::Response::default()
+    });
+
+    let client = reqwest::Client::builder()
+        .priority(7, true)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/bulk", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let res = client
+        .get(format!("http://{}/api", server.addr()))
+        .priority(0, false)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let err = reqwest::Client::builder()
+        .priority(9, false)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn uri_template() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.uri(), "/users/jane%20doe/repos?page=2");
+        http::Response::default()
+    });
+
+    let template = "http://127.0.0.1:{port}/users/{id}/repos{?page,per_page}";
+    let url = reqwest::uri_template::UriTemplate::new(template)
+        .var("port", server.addr().port())
+        .var("id", "jane doe")
+        .var("page", 2);
+    let res = Client::new().get(url).send().await.unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn base_url() {
+    let server =
+        server::http(move |req| async move { http::Response::new(req.uri().to_string().into()) });
+
+    let client = reqwest::Client::builder()
+        .base_url(format!("http://{}/api", server.addr()))
+        .build()
+        .unwrap();
+
+    let paths = [
+        ("users?page=2", "/api/users?page=2"),
+        ("/health", "/health"),
+        ("../other", "/other"),
+    ];
+    for (url, path) in paths {
+        let res = client.get(url).send().await.unwrap();
+        assert_eq!(res.text().await.unwrap(), path);
+    }
+
+    let absolute = format!("http://{}/absolute", server.addr());
+    let res = client.get(&absolute).send().await.unwrap();
+    assert_eq!(res.url().as_str(), absolute);
+
+    let template = reqwest::uri_template::UriTemplate::new("users/{id}").var("id", "a b");
+    let res = client.get(template).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "/api/users/a%20b");
+}
+
+#[test]
+fn base_url_invalid() {
+    let err = reqwest::Client::builder()
+        .base_url("/relative")
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn default_query() {
+    let server =
+        server::http(move |req| async move { http::Response::new(req.uri().to_string().into()) });
+
+    let client = reqwest::Client::builder()
+        .default_query(&[("api_key", "old"), ("version", "2")])
+        .default_query(&[("api_key", "s3cr&t")])
+        .build()
+        .unwrap();
+
+    let paths = [
+        ("/items", "/items?version=2&api_key=s3cr%26t"),
+        ("/items?page=2", "/items?page=2&version=2&api_key=s3cr%26t"),
+        ("/items?version=3", "/items?version=3&api_key=s3cr%26t"),
+        (
+            "/items?api_key=mine&version=1",
+            "/items?api_key=mine&version=1",
+        ),
+    ];
+    for (path, expected) in paths {
+        let res = client
+            .get(format!("http://{}{path}", server.addr()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), expected);
+    }
+
+    assert!(format!("{client:?}").contains(r#"default_query: ["version", "api_key"]"#));
+    assert!(!format!("{client:?}").contains("s3cr"));
+}
+
+#[tokio::test]
+async fn error_has_url() {
+    let u = "http://does.not.exist.local/ever";
+    let err = reqwest::get(u).await.unwrap_err();
+    assert_eq!(err.url().map(AsRef::as_ref), Some(u), "{err:?}");
+}
+
+#[tokio::test]
+async fn client_as_http_service() {
+    use http_body_util::BodyExt;
+    use tower::{Service, ServiceExt};
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.uri(), "/service");
+        assert_eq!(req.headers()["x-custom"], "yes");
+        http::Response::new("pong".into())
+    });
+
+    let client = Client::new();
+    let mut svc = tower::limit::ConcurrencyLimit::new(client, 1);
+
+    let req = http::Request::builder()
+        .uri(format!("http://{}/service", server.addr()))
+        .header("x-custom", "yes")
+        .body(reqwest::Body::from("ping"))
+        .unwrap();
+
+    let res = ServiceExt::<http::Request<reqwest::Body>>::ready(&mut svc)
+        .await
+        .unwrap()
+        .call(req)
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "pong");
+}
+
+#[tokio::test]
+async fn client_as_http_service_invalid_uri() {
+    use tower::ServiceExt;
+
+    let req = http::Request::builder()
+        .uri("/relative")
+        .body(reqwest::Body::from(""))
+        .unwrap();
+
+    let err = Client::new().oneshot(req).await.unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn custom_http_service() {
+    let service = tower::service_fn(|req: http::Request<reqwest::Body>| async move {
+        let res = match req.uri().path() {
+            "/start" => http::Response::builder()
+                .status(302)
+                .header("location", "/end")
+                .body(reqwest::Body::from(""))
+                .unwrap(),
+            "/end" => {
+                assert_eq!(req.uri(), "http://in.process/end");
+                assert_eq!(req.headers()["x-default"], "yes");
+                http::Response::new(reqwest::Body::from("done"))
+            }
+            _ => unreachable!(),
+        };
+        Ok::<_, std::convert::Infallible>(res)
+    });
+
+    let mut headers = http::HeaderMap::new();
+    headers.insert("x-default", "yes".parse().unwrap());
+
+    let client = Client::builder()
+        .http_service(service)
+        .default_headers(headers)
+        .build()
+        .unwrap();
+
+    let res = client.get("http://in.process/start").send().await.unwrap();
+    assert_eq!(res.url().as_str(), "http://in.process/end");
+    assert_eq!(res.text().await.unwrap(), "done");
+}
+
+#[tokio::test]
+async fn custom_http_service_error() {
+    let service = tower::service_fn(|_req: http::Request<reqwest::Body>| async move {
+        Err::<http::Response<reqwest::Body>, _>(std::io::Error::other("unreachable"))
+    });
+
+    let client = Client::builder().http_service(service).build().unwrap();
+
+    let err = client.get("http://in.process/").send().await.unwrap_err();
+    assert!(err.is_request());
+    assert_eq!(err.url().map(AsRef::as_ref), Some("http://in.process/"));
+}
+
+#[tokio::test]
+async fn custom_scheme_handler() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["x-default"], "yes");
+        http::Response::new("remote".into())
+    });
+    let assets = tower::service_fn(|req: http::Request<reqwest::Body>| {
+        assert_eq!(req.uri(), "app://assets/index.html");
+        assert_eq!(req.headers()["x-default"], "yes");
+        let res = match req.uri().path() {
+            "/index.html" => http::Response::new(reqwest::Body::from("local")),
+            _ => http::Response::builder()
+                .status(404)
+                .body(reqwest::Body::from(""))
+                .unwrap(),
+        };
+        std::future::ready(Ok::<_, std::convert::Infallible>(res))
+    });
+
+    let mut headers = http::HeaderMap::new();
+    headers.insert("x-default", "yes".parse().unwrap());
+
+    let client = Client::builder()
+        .scheme_handler("APP", assets)
+        .default_headers(headers)
+        .build()
+        .unwrap();
+
+    let res = client.get("app://assets/index.html").send().await.unwrap();
+    assert_eq!(res.url().as_str(), "app://assets/index.html");
+    assert_eq!(res.text().await.unwrap(), "local");
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "remote");
+
+    let err = client.get("other://assets/").send().await.unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn raw_target() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.uri(), "/a/%2e%2e/b/../c?q=%zz");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/a/%2e%2e/b/../c?q=%zz", server.addr());
+    let client = Client::new();
+    let req = client.get(&url).build().unwrap();
+    assert_eq!(req.url().path(), "/c");
+
+    let res = client
+        .get(&url)
+        .raw_target("/a/%2e%2e/b/../c?q=%zz")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().path(), "/c");
+
+    for target in ["/a b", "/a#b"] {
+        let err = client.get(&url).raw_target(target).build().unwrap_err();
+        assert!(err.is_builder());
+    }
+}
+
+#[tokio::test]
+async fn raw_target_redirect() {
+    let server = server::http(move |req| async move {
+        match req.uri().path() {
+            "/raw/%2e%2e/page" => http::Response::builder()
+                .status(302)
+                .header("location", "next")
+                .body(Default::default())
+                .unwrap(),
+            "/dir/next" => http::Response::new("next".into()),
+            path => panic!("unexpected request for {path}"),
+        }
+    });
+
+    let res = Client::new()
+        .get(format!("http://{}/dir/page", server.addr()))
+        .raw_target("/raw/%2e%2e/page")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().path(), "/dir/next");
+    assert_eq!(res.text().await.unwrap(), "next");
+}
+
+#[tokio::test]
+async fn custom_scheme_handler_keeps_port() {
+    let assets = tower::service_fn(|req: http::Request<reqwest::Body>| {
+        assert_eq!(req.uri(), "app://assets:80/index.html");
+        std::future::ready(Ok::<_, std::convert::Infallible>(http::Response::new(
+            reqwest::Body::from("local"),
+        )))
+    });
+
+    let client = Client::builder()
+        .scheme_handler("app", assets)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get("app://assets:80/index.html")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.url().port(), Some(80));
+    assert_eq!(res.text().await.unwrap(), "local");
+}
+
+#[tokio::test]
+async fn idna_disabled() {
+    let client = Client::builder().idna(false).build().unwrap();
+
+    let err = client.get("http://bücher.example/").build().unwrap_err();
+    assert!(err.is_builder());
+    assert_eq!(
+        err.url().map(|u| u.as_str()),
+        Some("http://xn--bcher-kva.example/")
+    );
+
+    let req = client.get("http://xn--bcher-kva.example/").build().unwrap();
+    assert_eq!(req.url().host_str(), Some("xn--bcher-kva.example"));
+
+    let req = Client::new().get("http://bücher.example/").build().unwrap();
+    assert_eq!(req.url().host_str(), Some("xn--bcher-kva.example"));
+}
+
+#[tokio::test]
+async fn scheme_handler_for_http_fails() {
+    let service = tower::service_fn(|_req: http::Request<reqwest::Body>| async move {
+        Ok::<_, std::convert::Infallible>(http::Response::new(reqwest::Body::from("")))
+    });
+
+    let err = Client::builder()
+        .scheme_handler("https", service)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn redirect_to_custom_scheme_fails() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .status(302)
+            .header("location", "app://assets/secret")
+            .body(Default::default())
+            .unwrap()
+    });
+    let assets = tower::service_fn(|_req: http::Request<reqwest::Body>| async move {
+        panic!("redirected to a custom scheme");
+        #[allow(unreachable_code)]
+        Ok::<_, std::convert::Infallible>(http::Response::new(reqwest::Body::from("")))
+    });
+
+    let client = Client::builder()
+        .scheme_handler("app", assets)
+        .build()
+        .unwrap();
+
+    let err = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_builder());
+    assert_eq!(err.url().map(AsRef::as_ref), Some("app://assets/secret"));
+}
+
+#[tokio::test]
+async fn fetch_cache_mode_is_ignored() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers().get("cache-control"), None);
+        http::Response::default()
+    });
+
+    let res = Client::new()
+        .get(format!("http://{}/", server.addr()))
+        .fetch_cache_no_store()
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn connection_events() {
+    use reqwest::observe::{ConnectionInfo, ConnectionObserver, DnsEnd, TcpConnected};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Recorder {
+        fn push(&self, event: String) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl ConnectionObserver for Recorder {
+        fn dns_start(&self, host: &str) {
+            self.push(format!("dns_start {host}"));
+        }
+
+        fn dns_end(&self, event: &DnsEnd<'_>) {
+            self.push(format!("dns_end {} {:?}", event.host(), event.addrs()));
+        }
+
+        fn tcp_connected(&self, event: &TcpConnected) {
+            self.push(format!("tcp_connected {:?}", event.remote_addr()));
+        }
+
+        fn connection_established(&self, conn: &ConnectionInfo) {
+            assert!(!conn.is_tls());
+            assert!(!conn.is_proxied());
+            self.push(format!(
+                "established {} {:?}",
+                conn.host(),
+                conn.remote_addr()
+            ));
+        }
+
+        fn connection_closed(&self, conn: &ConnectionInfo, _lifetime: Duration) {
+            self.push(format!("closed {}", conn.host()));
+        }
+    }
+
+    let server = server::http(move |_req| async { http::Response::default() });
+    let addr = server.addr();
+
+    let recorder = Recorder::default();
+    let client = Client::builder()
+        .no_proxy()
+        .resolve("observed.test", addr)
+        .connection_events(recorder.clone())
+        .build()
+        .unwrap();
+
+    let url = format!("http://observed.test:{}/", addr.port());
+    client.get(&url).send().await.unwrap();
+    client.get(&url).send().await.unwrap();
+    drop(client);
+
+    for _ in 0..50 {
+        if recorder.0.lock().unwrap().len() == 5 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![
+            "dns_start observed.test".to_owned(),
+            format!("dns_end observed.test [{addr}]"),
+            format!("tcp_connected Some({addr})"),
+            format!("established observed.test Some({addr})"),
+            "closed observed.test".to_owned(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn request_timings() {
+    use reqwest::observe::RequestTimings;
+
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+    let addr = server.addr();
+
+    let client = Client::builder()
+        .no_proxy()
+        .resolve("timings.test", addr)
+        .build()
+        .unwrap();
+    let url = format!("http://timings.test:{}/", addr.port());
+
+    let res = client.get(&url).send().await.unwrap();
+    let timings = res.extensions().get::<RequestTimings>().unwrap().clone();
+    let queue_end = timings.queue_end().unwrap();
+    let dns_end = timings.dns_end().unwrap();
+    let connect_end = timings.connect_end().unwrap();
+    let written = timings.request_written().unwrap();
+    assert!(timings.start() <= queue_end);
+    assert!(queue_end <= timings.dns_start().unwrap());
+    assert!(timings.dns_start().unwrap() <= dns_end);
+    assert_eq!(timings.connect_start(), Some(dns_end));
+    assert!(dns_end <= connect_end);
+    assert!(connect_end <= written);
+    assert!(written <= timings.first_byte());
+    assert_eq!(timings.tls_start(), None);
+    assert_eq!(timings.body_complete(), None);
+
+    assert_eq!(res.text().await.unwrap(), "Hello");
+    assert!(timings.body_complete().unwrap() >= timings.first_byte());
+
+    // the connection is reused
+    let res = client.get(&url).send().await.unwrap();
+    let timings = res.extensions().get::<RequestTimings>().unwrap();
+    assert_eq!(timings.dns_start(), None);
+    assert_eq!(timings.connect_start(), None);
+    let queue_end = timings.queue_end().unwrap();
+    let written = timings.request_written().unwrap();
+    assert!(timings.start() <= queue_end);
+    assert!(queue_end <= written);
+    assert!(written <= timings.first_byte());
+}
+
+#[tokio::test]
+async fn redaction_policy() {
+    use reqwest::redact::Policy;
+
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .status(404)
+            .header("set-cookie", "session=secret")
+            .body("".into())
+            .unwrap()
+    });
+
+    let client = Client::builder()
+        .no_proxy()
+        .redaction(Policy::strict().redact_header("x-api-key".parse().unwrap()))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/path?token=secret", server.addr());
+
+    let req = client
+        .get(&url)
+        .header("x-api-key", "secret")
+        .build()
+        .unwrap();
+    let debug = format!("{req:?}");
+    assert!(!debug.contains("secret"), "{debug}");
+
+    let res = client.execute(req).await.unwrap();
+    let debug = format!("{res:?}");
+    assert!(!debug.contains("secret"), "{debug}");
+    assert!(debug.contains("token=REDACTED"), "{debug}");
+
+    let err = res.error_for_status().unwrap_err();
+    let display = err.to_string();
+    assert!(!display.contains("secret"), "{display}");
+    assert!(display.contains("/path?token=REDACTED"), "{display}");
+    assert!(!format!("{err:?}").contains("secret"));
+    // the url itself is untouched
+    assert_eq!(err.url().unwrap().query(), Some("token=secret"));
+
+    let err = client
+        .get("http://127.0.0.1:1/?token=secret")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(!err.to_string().contains("secret"), "{err}");
+}
+
+#[tokio::test]
+async fn wire_events() {
+    use reqwest::observe::WireObserver;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl WireObserver for Recorder {
+        fn request_head(&self, _id: u64, head: &[u8]) {
+            let head = String::from_utf8_lossy(head).into_owned();
+            self.0.lock().unwrap().push(head);
+        }
+
+        fn request_body(&self, _id: u64, len: usize) {
+            self.0.lock().unwrap().push(format!("> {len} bytes"));
+        }
+
+        fn response_head(&self, _id: u64, head: &[u8]) {
+            let head = String::from_utf8_lossy(head).into_owned();
+            self.0.lock().unwrap().push(head);
+        }
+
+        fn response_body(&self, _id: u64, len: usize) {
+            self.0.lock().unwrap().push(format!("< {len} bytes"));
+        }
+    }
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()[CONTENT_LENGTH], "5");
+        http::Response::builder()
+            .header("set-cookie", "session=secret")
+            .body("world".into())
+            .unwrap()
+    });
+
+    let recorder = Recorder::default();
+    let client = Client::builder()
+        .no_proxy()
+        .redaction(reqwest::redact::Policy::strict())
+        .wire_events(recorder.clone())
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/echo?token=secret", server.addr());
+    let res = client
+        .post(&url)
+        .header("x-custom", "yes")
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "world");
+
+    let events = recorder.0.lock().unwrap().clone();
+    assert_eq!(events.len(), 4, "{events:?}");
+    let head = format!(
+        "POST /echo?token=REDACTED HTTP/1.1\r\nhost: {}\r\nx-custom: yes\r\naccept: */*\r\n",
+        server.addr()
+    );
+    assert!(events[0].starts_with(&head), "{}", events[0]);
+    assert!(events[0].ends_with("\r\n\r\n"), "{}", events[0]);
+    assert_eq!(events[1], "> 5 bytes");
+    assert!(events[2].starts_with("HTTP/1.1 200 OK\r\n"), "{}", events[2]);
+    assert!(events[2].contains("set-cookie: REDACTED\r\n"), "{}", events[2]);
+    assert_eq!(events[3], "< 5 bytes");
+}
+
+#[tokio::test]
+async fn audit_exchanges() {
+    use reqwest::observe::{AuditObserver, Exchange};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl AuditObserver for Recorder {
+        fn exchange(&self, exchange: &Exchange<'_>) {
+            self.0.lock().unwrap().push(format!(
+                "{} {} {:?} {:?} {} {} {}",
+                exchange.method(),
+                exchange.url().path(),
+                exchange.request_body_size(),
+                exchange.status().map(|status| status.as_u16()),
+                exchange.response_body_size(),
+                exchange.is_complete(),
+                exchange.error().is_some(),
+            ));
+        }
+    }
+
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .header("x-audit", "yes")
+            .body("world".into())
+            .unwrap()
+    });
+
+    let recorder = Recorder::default();
+    let client = Client::builder()
+        .no_proxy()
+        .audit(recorder.clone())
+        .build()
+        .unwrap();
+
+    let res = client
+        .post(format!("http://{}/read", server.addr()))
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+    assert!(recorder.0.lock().unwrap().is_empty());
+    assert_eq!(res.text().await.unwrap(), "world");
+
+    let res = client
+        .get(format!("http://{}/dropped", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    drop(res);
+
+    client
+        .get("http://127.0.0.1:1/failed")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        vec![
+            "POST /read Some(5) Some(200) 5 true false",
+            "GET /dropped Some(0) Some(200) 0 false false",
+            "GET /failed Some(0) None 0 false true",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn on_error_telemetry() {
+    use std::sync::{Arc, Mutex};
+
+    // closes each connection without answering
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((conn, _)) = listener.accept().await {
+            drop(conn);
+        }
+    });
+
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let recorded = failures.clone();
+    let retries = reqwest::retry::for_host(addr.ip().to_string())
+        .no_budget()
+        .classify_fn(|req_rep| {
+            if req_rep.error().is_some() {
+                req_rep.retryable()
+            } else {
+                req_rep.success()
+            }
+        });
+    let client = Client::builder()
+        .no_proxy()
+        .redaction(reqwest::redact::Policy::strict())
+        .retry(retries)
+        .on_error(move |failed| {
+            recorded.lock().unwrap().push(format!(
+                "{} {} {} {}",
+                failed.method(),
+                failed.url(),
+                failed.code(),
+                failed.attempts(),
+            ));
+        })
+        .build()
+        .unwrap();
+
+    client
+        .get(format!("http://{addr}/closed?token=secret"))
+        .send()
+        .await
+        .unwrap_err();
+    client
+        .get("http://127.0.0.1:1/refused")
+        .send()
+        .await
+        .unwrap_err();
+
+    // a response with an error status isn't a failure
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .status(500)
+            .body("".into())
+            .unwrap()
+    });
+    client
+        .get(format!("http://{}/status", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *failures.lock().unwrap(),
+        vec![
+            format!("GET http://{addr}/closed?token=REDACTED request 3"),
+            "GET http://127.0.0.1:1/refused connect 3".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn transfer_stats() {
+    let server = server::http(move |req| async move {
+        let status = if req.uri().path() == "/missing" {
+            404
+        } else {
+            200
+        };
+        http::Response::builder()
+            .status(status)
+            .body("hello".into())
+            .unwrap()
+    });
+
+    let client = Client::builder().no_proxy().build().unwrap();
+    assert_eq!(client.transfer_stats(), Default::default());
+
+    let res = client
+        .post(format!("http://{}/found", server.addr()))
+        .body("0123456789")
+        .send()
+        .await
+        .unwrap();
+    res.bytes().await.unwrap();
+    client
+        .get(format!("http://{}/missing", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    let stats = client.transfer_stats();
+    assert_eq!(stats.requests(), 2);
+    assert_eq!(stats.responses(), 2);
+    assert_eq!(stats.success_responses(), 1);
+    assert_eq!(stats.client_error_responses(), 1);
+    assert_eq!(stats.server_error_responses(), 0);
+    // request heads and the body
+    assert!(stats.bytes_sent() > 10, "{stats:?}");
+    // response heads and both bodies
+    assert!(stats.bytes_received() > 10, "{stats:?}");
+}
+
+#[tokio::test]
+async fn to_builder() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["user-agent"], "my-app/1.0");
+        let tenant = req
+            .headers()
+            .get("x-tenant")
+            .map(|v| v.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        http::Response::new(tenant.into())
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let base = Client::builder()
+        .user_agent("my-app/1.0")
+        .no_proxy()
+        .build()
+        .unwrap();
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-tenant", "acme".parse().unwrap());
+    let tenant = base.to_builder().default_headers(headers).build().unwrap();
+
+    let res = tenant.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "acme");
+    let res = base.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "");
+
+    // each client counts its own transfers
+    assert_eq!(base.transfer_stats().requests(), 1);
+    assert_eq!(tenant.transfer_stats().requests(), 1);
+}
+
+#[tokio::test]
+async fn max_in_flight_per_host() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let most = Arc::new(AtomicUsize::new(0));
+    let server = server::http({
+        let in_flight = in_flight.clone();
+        let most = most.clone();
+        move |_req| {
+            let in_flight = in_flight.clone();
+            let most = most.clone();
+            async move {
+                let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(n, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                http::Response::new("done".into())
+            }
+        }
+    });
+
+    let client = Client::builder()
+        .no_proxy()
+        .max_in_flight_per_host(2)
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+    let requests = (0..5).map(|_| {
+        let client = client.clone();
+        let url = url.clone();
+        tokio::spawn(async move { client.get(url).send().await?.text().await })
+    });
+    for request in requests.collect::<Vec<_>>() {
+        assert_eq!(request.await.unwrap().unwrap(), "done");
+    }
+    assert_eq!(most.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn in_flight_queue_timeout() {
+    let server = server::http(move |_req| async {
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        http::Response::default()
+    });
+
+    let client = Client::builder()
+        .no_proxy()
+        .max_in_flight_per_host(1)
+        .in_flight_queue_timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    let first = tokio::spawn(client.get(&url).send());
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_timeout(), "{err:?}");
+    assert_eq!(err.timeout_phase(), Some(reqwest::TimeoutPhase::Queue));
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+
+    // the first response frees its place once read
+    first.await.unwrap().unwrap().bytes().await.unwrap();
+    client.get(&url).send().await.unwrap();
+
+    let err = Client::builder()
+        .max_in_flight_per_host(0)
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn max_in_flight_queue_priority() {
+    use reqwest::QueuePriority;
+    use std::sync::{Arc, Mutex};
+
+    let served = Arc::new(Mutex::new(Vec::new()));
+    let server = server::http({
+        let served = served.clone();
+        move |req| {
+            let served = served.clone();
+            async move {
+                if req.uri().path() == "/first" {
+                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                }
+                served.lock().unwrap().push(req.uri().path().to_owned());
+                http::Response::default()
+            }
+        }
+    });
+
+    let client = Client::builder()
+        .no_proxy()
+        .max_in_flight(1)
+        .build()
+        .unwrap();
+    let send = |path: &str, priority| {
+        let req = client
+            .get(format!("http://{}{path}", server.addr()))
+            .queue_priority(priority);
+        tokio::spawn(async move { req.send().await?.bytes().await })
+    };
+
+    let mut requests = vec![send("/first", QueuePriority::Normal)];
+    for (path, priority) in [
+        ("/low", QueuePriority::Low),
+        ("/normal", QueuePriority::Normal),
+        ("/high", QueuePriority::High),
+    ] {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        requests.push(send(path, priority));
+    }
+    for request in requests {
+        request.await.unwrap().unwrap();
+    }
+    assert_eq!(
+        *served.lock().unwrap(),
+        ["/first", "/high", "/normal", "/low"]
+    );
+
+    let err = Client::builder().max_in_flight(0).build().unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[tokio::test]
+async fn max_in_flight_ready() {
+    let server = server::http(move |_req| async { http::Response::new("hello".into()) });
+
+    let client = Client::builder()
+        .no_proxy()
+        .max_in_flight(1)
+        .in_flight_queue_timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    let permit = client.ready().await.unwrap();
+    assert!(client.try_acquire().is_none());
+    let err = client.ready().await.unwrap_err();
+    assert_eq!(err.timeout_phase(), Some(reqwest::TimeoutPhase::Queue));
+
+    // the request takes the reserved place rather than waiting for it
+    let res = client
+        .get(&url)
+        .in_flight_permit(permit)
+        .send()
+        .await
+        .unwrap();
+    assert!(client.try_acquire().is_none());
+    res.bytes().await.unwrap();
+
+    // an unused permit frees its place once dropped
+    drop(client.try_acquire().unwrap());
+    client.ready().await.unwrap();
+
+    let unlimited = Client::new();
+    unlimited.try_acquire().unwrap();
+    unlimited.ready().await.unwrap();
+}
+
+#[tokio::test]
+async fn max_in_flight_ready_per_host() {
+    let server = server::http(move |_req| async { http::Response::new("hello".into()) });
+
+    let client = Client::builder()
+        .no_proxy()
+        .max_in_flight(1)
+        .max_in_flight_per_host(1)
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    // one request holds the slot in total, the other the slot of the host
+    let permit = client.ready().await.unwrap();
+    let waiting = tokio::spawn({
+        let req = client.get(&url);
+        async move { req.send().await?.bytes().await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    // the reserved slot is given back rather than waiting for the host
+    let sent = async {
+        let res = client.get(&url).in_flight_permit(permit).send().await?;
+        res.bytes().await
+    };
+    let (sent, waiting) =
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            tokio::join!(sent, waiting)
+        })
+        .await
+        .expect("requests wait for each other");
+    sent.unwrap();
+    waiting.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn max_in_flight_service_ready() {
+    use tower::{Service, ServiceExt};
+
+    let server = server::http(move |_req| async { http::Response::new("hello".into()) });
+
+    let client = Client::builder()
+        .no_proxy()
+        .max_in_flight(1)
+        .in_flight_queue_timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", server.addr());
+
+    // `poll_ready` reserves the room of the request of the next `call`
+    let mut svc = client.clone();
+    ServiceExt::<reqwest::Request>::ready(&mut svc)
+        .await
+        .unwrap();
+    assert!(client.try_acquire().is_none());
+    let err = ServiceExt::<reqwest::Request>::ready(&mut client.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(err.timeout_phase(), Some(reqwest::TimeoutPhase::Queue));
+
+    let res = svc.call(client.get(&url).build().unwrap()).await.unwrap();
+    assert!(client.try_acquire().is_none());
+    res.bytes().await.unwrap();
+
+    // the room is free again once the response ends
+    let req = http::Request::get(&url)
+        .body(reqwest::Body::default())
+        .unwrap();
+    let res = ServiceExt::<http::Request<reqwest::Body>>::ready(&mut svc)
+        .await
+        .unwrap()
+        .call(req)
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
 }
@@ -0,0 +1,141 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::time::Duration;
+
+use reqwest::client_config::ClientConfig;
+
+fn parse(config: &str) -> ClientConfig {
+    toml::from_str(config).unwrap()
+}
+
+#[test]
+fn deserialize() {
+    let config = parse(
+        r#"
+        user_agent = "my-app/1.0"
+        timeout = 30
+        connect_timeout = "1.5s"
+        read_timeout = "500ms"
+        max_redirects = 5
+
+        [headers]
+        x-tenant = "acme"
+
+        [proxy]
+        all = "http://proxy.test:3128"
+        no_proxy = "localhost"
+
+        [pool]
+        idle_timeout = "2m"
+        max_idle_per_host = 8
+
+        [tcp]
+        nodelay = true
+
+        [tls]
+        min_version = "1.2"
+        "#,
+    );
+
+    assert_eq!(config.user_agent.as_deref(), Some("my-app/1.0"));
+    assert_eq!(config.headers["x-tenant"], "acme");
+    assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+    assert_eq!(config.connect_timeout, Some(Duration::from_millis(1500)));
+    assert_eq!(config.read_timeout, Some(Duration::from_millis(500)));
+    assert_eq!(config.max_redirects, Some(5));
+    assert_eq!(config.proxy.all.as_deref(), Some("http://proxy.test:3128"));
+    assert_eq!(config.pool.idle_timeout, Some(Duration::from_secs(120)));
+    assert_eq!(config.pool.max_idle_per_host, Some(8));
+    assert_eq!(config.tcp.nodelay, Some(true));
+    assert_eq!(config.tls.min_version.as_deref(), Some("1.2"));
+}
+
+#[test]
+fn unknown_settings_are_refused() {
+    assert!(toml::from_str::<ClientConfig>("timeuot = 30").is_err());
+    assert!(toml::from_str::<ClientConfig>("timeout = \"30 days\"").is_err());
+}
+
+#[tokio::test]
+async fn from_config() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.headers()["user-agent"], "my-app/1.0");
+        assert_eq!(req.headers()["x-tenant"], "acme");
+        http::Response::default()
+    });
+
+    let config = parse(
+        r#"
+        user_agent = "my-app/1.0"
+
+        [headers]
+        x-tenant = "acme"
+        "#,
+    );
+    let client = reqwest::ClientBuilder::from_config(&config)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn from_config_timeout() {
+    let server = server::http(|_req| async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        http::Response::default()
+    });
+
+    let client = reqwest::ClientBuilder::from_config(&parse("timeout = \"100ms\""))
+        .build()
+        .unwrap();
+
+    let err = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+}
+
+#[test]
+fn invalid_settings() {
+    for config in [
+        "[headers]\n\"bad header\" = \"value\"",
+        "[proxy]\nall = \"not a url\"",
+        "[tls]\nmin_version = \"1.4\"",
+        "[tls]\nroot_certificates = [\"does-not-exist.pem\"]",
+    ] {
+        let err = reqwest::ClientBuilder::from_config(&parse(config))
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder(), "{config}: {err:?}");
+    }
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_from_config() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.headers()["user-agent"], "my-app/1.0");
+        http::Response::default()
+    });
+
+    let config = parse("user_agent = \"my-app/1.0\"");
+    let client = reqwest::blocking::ClientBuilder::from_config(&config)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
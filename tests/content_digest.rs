@@ -0,0 +1,207 @@
+#![cfg(not(target_arch = "wasm32"))]
+#![cfg(not(feature = "rustls-no-provider"))]
+mod support;
+use support::server;
+
+use http_body_util::BodyExt;
+
+// sha-256 of "hello"
+const HELLO_DIGEST: &str = "sha-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:";
+
+#[tokio::test]
+async fn request_digest_header() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-digest"], HELLO_DIGEST);
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/digest", server.addr());
+    let res = reqwest::Client::builder()
+        .content_digest(true)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .post(&url)
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn request_digest_not_overwritten() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-digest"], "sha-512=:AAAA:");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/digest", server.addr());
+    let res = reqwest::Client::builder()
+        .content_digest(true)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .post(&url)
+        .header("content-digest", "sha-512=:AAAA:")
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn request_digest_trailer_when_streaming() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["trailer"], "content-digest");
+        assert!(req.headers().get("content-digest").is_none());
+
+        let collected = req.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().cloned().expect("trailers");
+        assert_eq!(&collected.to_bytes()[..], b"hello");
+        assert_eq!(trailers["content-digest"], HELLO_DIGEST);
+        http::Response::default()
+    });
+
+    let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("hel"), Ok("lo")];
+    let body = reqwest::Body::wrap_stream(futures_util::stream::iter(chunks));
+
+    let url = format!("http://{}/digest", server.addr());
+    let res = reqwest::Client::builder()
+        .content_digest(true)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .post(&url)
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn response_digest_verified() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-digest", HELLO_DIGEST)
+            .body("hello".into())
+            .unwrap()
+    });
+
+    let url = format!("http://{}/digest", server.addr());
+    let text = reqwest::Client::builder()
+        .content_digest(true)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert_eq!(text, "hello");
+}
+
+#[tokio::test]
+async fn response_digest_mismatch() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-digest", HELLO_DIGEST)
+            .body("goodbye".into())
+            .unwrap()
+    });
+
+    let url = format!("http://{}/digest", server.addr());
+    let err = reqwest::Client::builder()
+        .content_digest(true)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+
+    let mut source = std::error::Error::source(&err);
+    let mut found = false;
+    while let Some(err) = source {
+        found |= err.to_string().contains("content digest mismatch");
+        source = err.source();
+    }
+    assert!(found, "mismatch in source chain");
+}
+
+#[tokio::test]
+async fn response_digest_mismatch_ignored_when_disabled() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-digest", HELLO_DIGEST)
+            .body("goodbye".into())
+            .unwrap()
+    });
+
+    let url = format!("http://{}/digest", server.addr());
+    let text = reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert_eq!(text, "goodbye");
+}
+
+#[tokio::test]
+async fn response_digest_trailer_mismatch() {
+    let server = server::http(move |_req| async move {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("content-digest", HELLO_DIGEST.parse().unwrap());
+        let frames: Vec<Result<_, std::io::Error>> = vec![
+            Ok(http_body::Frame::data(bytes::Bytes::from_static(
+                b"goodbye",
+            ))),
+            Ok(http_body::Frame::trailers(trailers)),
+        ];
+        let body = http_body_util::StreamBody::new(futures_util::stream::iter(frames));
+
+        http::Response::builder()
+            .header("trailer", "content-digest")
+            .body(reqwest::Body::wrap(body))
+            .unwrap()
+    });
+
+    let url = format!("http://{}/digest", server.addr());
+    let err = reqwest::Client::builder()
+        .content_digest(true)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .header("te", "trailers")
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+}
@@ -201,3 +201,59 @@ async fn cookie_store_path() {
     let url = format!("http://{}/subpath", server.addr());
     client.get(&url).send().await.unwrap();
 }
+
+#[test]
+fn cookie_jar_limits() {
+    use reqwest::cookie::{CookieStore, Jar, Rejection};
+    use std::sync::{Arc, Mutex};
+
+    let rejected = Arc::new(Mutex::new(Vec::new()));
+    let jar = Jar::default()
+        .max_cookies_per_domain(2)
+        .max_cookies(3)
+        .max_cookie_size(16)
+        .on_reject({
+            let rejected = rejected.clone();
+            move |cookie, rejection| {
+                rejected
+                    .lock()
+                    .unwrap()
+                    .push((cookie.name().to_owned(), rejection));
+            }
+        });
+    let a = "http://a.local/".parse().unwrap();
+    let b = "http://b.local/".parse().unwrap();
+    let cookies = |url| {
+        let cookies = jar.cookies(url).unwrap();
+        let mut cookies = cookies
+            .to_str()
+            .unwrap()
+            .split("; ")
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        cookies.sort();
+        cookies
+    };
+
+    jar.add_cookie_str("one=1", &a);
+    jar.add_cookie_str("two=2", &a);
+    assert_eq!(cookies(&a), ["one=1", "two=2"]);
+    // storing `one` again makes `two` the least recently used
+    jar.add_cookie_str("one=1", &a);
+    jar.add_cookie_str("three=3", &a);
+
+    jar.add_cookie_str("large=0123456789abcdef", &b);
+    jar.add_cookie_str("four=4", &b);
+    jar.add_cookie_str("five=5", &b);
+    assert_eq!(cookies(&b), ["five=5", "four=4"]);
+    assert_eq!(cookies(&a), ["three=3"]);
+
+    assert_eq!(
+        *rejected.lock().unwrap(),
+        [
+            ("two".to_owned(), Rejection::Evicted),
+            ("large".to_owned(), Rejection::TooLarge),
+            ("one".to_owned(), Rejection::Evicted),
+        ]
+    );
+}
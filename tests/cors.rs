@@ -0,0 +1,102 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderName, CONTENT_TYPE};
+use reqwest::{Client, Method};
+
+fn cors_server() -> server::Server {
+    server::http(|req| async move {
+        assert_eq!(req.method(), "OPTIONS");
+        assert_eq!(req.headers()["origin"], "https://app.example.com");
+        if let Some(headers) = req.headers().get("access-control-request-headers") {
+            assert!(headers == "content-type" || headers == "x-token");
+        }
+        http::Response::builder()
+            .status(204)
+            .header("access-control-allow-origin", "https://app.example.com")
+            .header("access-control-allow-methods", "GET, PUT, DELETE")
+            .header("access-control-allow-headers", "content-type")
+            .header("access-control-max-age", "3600")
+            .header(
+                "x-requested-method",
+                req.headers()["access-control-request-method"].clone(),
+            )
+            .body(Default::default())
+            .unwrap()
+    })
+}
+
+#[tokio::test]
+async fn options_request() {
+    let server = cors_server();
+
+    let res = Client::new()
+        .options(format!("http://{}/items", server.addr()))
+        .header("origin", "https://app.example.com")
+        .header("access-control-request-method", "PUT")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::NO_CONTENT);
+    assert_eq!(res.headers()["x-requested-method"], "PUT");
+}
+
+#[tokio::test]
+async fn preflight_allowed() {
+    let server = cors_server();
+
+    let preflight = Client::new()
+        .preflight(
+            format!("http://{}/items", server.addr()),
+            "https://app.example.com",
+            Method::PUT,
+        )
+        .request_header(CONTENT_TYPE)
+        .send()
+        .await
+        .unwrap();
+
+    assert!(preflight.is_allowed());
+    assert_eq!(preflight.allow_origin(), Some("https://app.example.com"));
+    assert_eq!(
+        preflight.allow_methods(),
+        [Method::GET, Method::PUT, Method::DELETE]
+    );
+    assert_eq!(preflight.allow_headers(), [CONTENT_TYPE]);
+    assert_eq!(preflight.max_age(), Some(Duration::from_secs(3600)));
+    assert!(!preflight.allow_credentials());
+}
+
+#[tokio::test]
+async fn preflight_refused() {
+    let server = cors_server();
+    let client = Client::new();
+    let url = format!("http://{}/items", server.addr());
+
+    let patch = client
+        .preflight(&url, "https://app.example.com", Method::PATCH)
+        .send()
+        .await
+        .unwrap();
+    assert!(!patch.is_allowed());
+
+    let header = client
+        .preflight(&url, "https://app.example.com", Method::PUT)
+        .request_header(HeaderName::from_static("x-token"))
+        .send()
+        .await
+        .unwrap();
+    assert!(!header.is_allowed());
+
+    let credentials = client
+        .preflight(&url, "https://app.example.com", Method::PUT)
+        .credentials(true)
+        .send()
+        .await
+        .unwrap();
+    assert!(!credentials.is_allowed());
+}
@@ -0,0 +1,318 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use reqwest::Client;
+
+const CONTENT: &str = "hello world";
+
+fn download_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("reqwest-download-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(marker(&path));
+    path
+}
+
+fn marker(path: &Path) -> PathBuf {
+    let mut marker = path.as_os_str().to_owned();
+    marker.push(".resume");
+    marker.into()
+}
+
+/// A server supporting ranges, with `If-Range` validation.
+fn ranged_server() -> server::Server {
+    server::http(|req| async move {
+        assert_eq!(req.headers()["accept-encoding"], "identity");
+        let range = req
+            .headers()
+            .get("range")
+            .filter(|_| req.headers()["if-range"] == "\"v1\"")
+            .map(|range| {
+                let range = range.to_str().unwrap();
+                range["bytes=".len()..range.len() - 1]
+                    .parse::<usize>()
+                    .unwrap()
+            });
+        let builder = http::Response::builder().header("etag", "\"v1\"");
+        match range {
+            Some(start) if start >= CONTENT.len() => builder
+                .status(416)
+                .header("content-range", format!("bytes */{}", CONTENT.len()))
+                .body(Default::default())
+                .unwrap(),
+            Some(start) => builder
+                .status(206)
+                .header(
+                    "content-range",
+                    format!("bytes {start}-{}/{}", CONTENT.len() - 1, CONTENT.len()),
+                )
+                .body(CONTENT[start..].into())
+                .unwrap(),
+            None => builder.body(CONTENT.into()).unwrap(),
+        }
+    })
+}
+
+#[tokio::test]
+async fn download_to_file() {
+    let server = ranged_server();
+    let path = download_path("full.txt");
+
+    let download = Client::new()
+        .download(format!("http://{}/file", server.addr()))
+        .to_file(&path)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.len(), CONTENT.len() as u64);
+    assert_eq!(download.resumed_from(), 0);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CONTENT);
+    assert!(!marker(&path).exists());
+}
+
+#[tokio::test]
+async fn resume_partial_download() {
+    let server = ranged_server();
+    let path = download_path("partial.txt");
+    std::fs::write(&path, "hello ").unwrap();
+    std::fs::write(marker(&path), "\"v1\"").unwrap();
+
+    let download = Client::new()
+        .download(format!("http://{}/file", server.addr()))
+        .to_file(&path)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.resumed_from(), 6);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CONTENT);
+    assert!(!marker(&path).exists());
+}
+
+#[tokio::test]
+async fn resume_complete_download() {
+    let server = ranged_server();
+    let path = download_path("complete.txt");
+    std::fs::write(&path, CONTENT).unwrap();
+    std::fs::write(marker(&path), "\"v1\"").unwrap();
+
+    let download = Client::new()
+        .download(format!("http://{}/file", server.addr()))
+        .to_file(&path)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.len(), CONTENT.len() as u64);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CONTENT);
+    assert!(!marker(&path).exists());
+}
+
+#[tokio::test]
+async fn resource_changed_restarts() {
+    let server = ranged_server();
+    let path = download_path("changed.txt");
+    std::fs::write(&path, "HELLO ").unwrap();
+    std::fs::write(marker(&path), "\"v0\"").unwrap();
+
+    let download = Client::new()
+        .download(format!("http://{}/file", server.addr()))
+        .to_file(&path)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.resumed_from(), 0);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CONTENT);
+}
+
+#[tokio::test]
+async fn without_marker_restarts() {
+    let server = ranged_server();
+    let path = download_path("unknown.txt");
+    std::fs::write(&path, "something else entirely").unwrap();
+
+    let download = Client::new()
+        .download(format!("http://{}/file", server.addr()))
+        .to_file(&path)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.resumed_from(), 0);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CONTENT);
+}
+
+#[tokio::test]
+async fn mismatched_content_range_fails() {
+    let server = server::http(|_req| async {
+        http::Response::builder()
+            .status(206)
+            .header("etag", "\"v1\"")
+            .header("content-range", "bytes 0-10/11")
+            .body(CONTENT.into())
+            .unwrap()
+    });
+    let path = download_path("mismatched.txt");
+    std::fs::write(&path, "hello ").unwrap();
+    std::fs::write(marker(&path), "\"v1\"").unwrap();
+
+    let err = Client::new()
+        .download(format!("http://{}/file", server.addr()))
+        .to_file(&path)
+        .resume(true)
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello ");
+}
+
+const LARGE: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// A server supporting ranges, that fails the first request for each range
+/// after the first one.
+fn segment_server(accept_ranges: bool) -> (server::Server, Arc<Mutex<Vec<String>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    let server = server::http(move |req| {
+        let range = req
+            .headers()
+            .get("range")
+            .map(|range| range.to_str().unwrap().to_owned());
+        let retried = {
+            let mut seen = seen.lock().unwrap();
+            let key = format!("{} {}", req.method(), range.as_deref().unwrap_or("-"));
+            let retried = seen.contains(&key);
+            seen.push(key);
+            retried
+        };
+        let method = req.method().clone();
+        async move {
+            let mut builder = http::Response::builder().header("etag", "\"v1\"");
+            if accept_ranges {
+                builder = builder.header("accept-ranges", "bytes");
+            }
+            if method == http::Method::HEAD {
+                return builder
+                    .header("content-length", LARGE.len())
+                    .body(Default::default())
+                    .unwrap();
+            }
+            let Some(range) = range.filter(|_| accept_ranges) else {
+                return builder.body(LARGE.into()).unwrap();
+            };
+            let (start, end) = range["bytes=".len()..].split_once('-').unwrap();
+            let (start, end) = (
+                start.parse::<usize>().unwrap(),
+                end.parse::<usize>().unwrap(),
+            );
+            if start > 0 && !retried {
+                return builder.status(503).body(Default::default()).unwrap();
+            }
+            builder
+                .status(206)
+                .header(
+                    "content-range",
+                    format!("bytes {start}-{end}/{}", LARGE.len()),
+                )
+                .body(LARGE[start..=end].into())
+                .unwrap()
+        }
+    });
+    (server, requests)
+}
+
+#[tokio::test]
+async fn segmented_download_to_file() {
+    let (server, requests) = segment_server(true);
+    let path = download_path("segmented.txt");
+
+    let download = Client::new()
+        .download(format!("http://{}/large", server.addr()))
+        .to_file(&path)
+        .segments(3)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.segments(), 3);
+    assert_eq!(download.len(), LARGE.len() as u64);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), LARGE);
+
+    let requests = requests.lock().unwrap();
+    // a HEAD, and two failed ranges retried
+    assert_eq!(requests.len(), 6);
+    assert!(requests.contains(&"GET bytes=24-35".to_owned()));
+}
+
+#[tokio::test]
+async fn segmented_download_to_writer() {
+    let (server, _requests) = segment_server(true);
+    let (writer, mut reader) = tokio::io::duplex(LARGE.len());
+
+    let client = Client::new();
+    let url = format!("http://{}/large", server.addr());
+    let download = tokio::spawn(async move {
+        client
+            .download(url)
+            .to_writer(writer)
+            .segments(4)
+            .send()
+            .await
+    });
+    let mut received = String::new();
+    tokio::io::AsyncReadExt::read_to_string(&mut reader, &mut received)
+        .await
+        .unwrap();
+
+    assert_eq!(download.await.unwrap().unwrap().segments(), 4);
+    assert_eq!(received, LARGE);
+}
+
+#[tokio::test]
+async fn segmented_download_without_ranges() {
+    let (server, requests) = segment_server(false);
+    let path = download_path("unsegmented.txt");
+
+    let download = Client::new()
+        .download(format!("http://{}/large", server.addr()))
+        .to_file(&path)
+        .segments(3)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(download.segments(), 1);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), LARGE);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn segment_retries_exhausted() {
+    let (server, _requests) = segment_server(true);
+    let path = download_path("exhausted.txt");
+
+    let err = Client::new()
+        .download(format!("http://{}/large", server.addr()))
+        .to_file(&path)
+        .segments(2)
+        .segment_retries(0)
+        .send()
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+}
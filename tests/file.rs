@@ -0,0 +1,110 @@
+#![cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+use reqwest::{Client, StatusCode, Url};
+
+fn file_url(name: &str, content: Option<&str>) -> Url {
+    let dir = std::env::temp_dir().join(format!("reqwest-file-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path: PathBuf = dir.join(name);
+    if let Some(content) = content {
+        std::fs::write(&path, content).unwrap();
+    }
+    Url::from_file_path(path).unwrap()
+}
+
+fn client() -> Client {
+    Client::builder().file_urls(true).build().unwrap()
+}
+
+#[tokio::test]
+async fn get_file() {
+    let url = file_url("page.html", Some("<p>hello</p>"));
+
+    let res = client().get(url.clone()).send().await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.url(), &url);
+    assert_eq!(res.content_length(), Some(12));
+    assert_eq!(res.headers()["content-length"], "12");
+    assert_eq!(res.headers()["content-type"], "text/html");
+    assert_eq!(res.text().await.unwrap(), "<p>hello</p>");
+}
+
+#[tokio::test]
+async fn head_file() {
+    let url = file_url("data.bin", Some("0123456789"));
+
+    let res = client().head(url).send().await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-length"], "10");
+    assert_eq!(res.headers()["content-type"], "application/octet-stream");
+    assert_eq!(res.bytes().await.unwrap(), "");
+}
+
+#[tokio::test]
+async fn large_file() {
+    let content = "reqwest ".repeat(100_000);
+    let url = file_url("large.txt", Some(&content));
+
+    let res = client().get(url).send().await.unwrap();
+
+    assert_eq!(res.content_length(), Some(content.len() as u64));
+    assert_eq!(res.text().await.unwrap(), content);
+}
+
+#[tokio::test]
+async fn missing_file() {
+    let url = file_url("missing.txt", None);
+
+    let res = client().get(url).send().await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn directory() {
+    let url = file_url("dir", None);
+    std::fs::create_dir_all(url.to_file_path().unwrap()).unwrap();
+
+    let res = client().get(url).send().await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn other_methods() {
+    let url = file_url("post.txt", Some("content"));
+
+    let res = client().post(url).body("new").send().await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.headers()["allow"], "GET, HEAD");
+}
+
+#[tokio::test]
+async fn disabled_by_default() {
+    let url = file_url("disabled.txt", Some("content"));
+
+    let err = Client::new().get(url).send().await.unwrap_err();
+
+    assert!(err.is_builder());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_get_file() {
+    let url = file_url("blocking.json", Some("{}"));
+
+    let res = reqwest::blocking::Client::builder()
+        .file_urls(true)
+        .build()
+        .unwrap()
+        .get(url)
+        .send()
+        .unwrap();
+
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert_eq!(res.text().unwrap(), "{}");
+}
@@ -0,0 +1,189 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use reqwest::graphql::PathSegment;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Data {
+    user: User,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    name: String,
+}
+
+const QUERY: &str = "query($id: ID!) { user(id: $id) { name } }";
+
+#[tokio::test]
+async fn graphql_data() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["content-type"], "application/json");
+        assert_eq!(
+            req.headers()["accept"],
+            "application/graphql-response+json, application/json"
+        );
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "query": QUERY, "variables": { "id": "1" } })
+        );
+        http::Response::builder()
+            .header("content-type", "application/graphql-response+json")
+            .body(r#"{"data":{"user":{"name":"Ada"}}}"#.into())
+            .unwrap()
+    });
+
+    let data: Data = reqwest::Client::new()
+        .post(format!("http://{}/graphql", server.addr()))
+        .graphql(QUERY, &serde_json::json!({ "id": "1" }))
+        .send()
+        .await
+        .unwrap()
+        .graphql_data()
+        .await
+        .unwrap();
+    assert_eq!(
+        data,
+        Data {
+            user: User {
+                name: "Ada".to_owned()
+            }
+        }
+    );
+}
+
+#[tokio::test]
+async fn graphql_without_variables() {
+    let server = server::http(|req| async move {
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(body, r#"{"query":"{ version }"}"#);
+        http::Response::new(r#"{"data":{"version":"1.0"}}"#.into())
+    });
+
+    let data: serde_json::Value = reqwest::Client::new()
+        .post(format!("http://{}/graphql", server.addr()))
+        .graphql("{ version }", &())
+        .send()
+        .await
+        .unwrap()
+        .graphql_data()
+        .await
+        .unwrap();
+    assert_eq!(data, serde_json::json!({ "version": "1.0" }));
+}
+
+#[tokio::test]
+async fn graphql_errors() {
+    let server = server::http(|_req| async move {
+        http::Response::new(
+            r#"{
+                "data": { "user": null },
+                "errors": [{
+                    "message": "user not found",
+                    "locations": [{ "line": 1, "column": 19 }],
+                    "path": ["user", 0],
+                    "extensions": { "code": "NOT_FOUND" }
+                }]
+            }"#
+            .into(),
+        )
+    });
+
+    let err = reqwest::Client::new()
+        .post(format!("http://{}/graphql", server.addr()))
+        .graphql(QUERY, &serde_json::json!({ "id": "2" }))
+        .send()
+        .await
+        .unwrap()
+        .graphql_data::<Data>()
+        .await
+        .unwrap_err();
+    assert!(err.is_decode());
+
+    let errors = err.graphql_errors().unwrap();
+    assert_eq!(errors.data(), Some(&serde_json::json!({ "user": null })));
+    let [error] = errors.errors() else {
+        panic!("expected one error, got {:?}", errors.errors());
+    };
+    assert_eq!(error.message(), "user not found");
+    assert_eq!(error.locations()[0].line(), 1);
+    assert_eq!(error.locations()[0].column(), 19);
+    assert_eq!(
+        error.path(),
+        Some(&[PathSegment::Field("user".to_owned()), PathSegment::Index(0)][..])
+    );
+    assert_eq!(error.extensions().unwrap()["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn graphql_null_error_fields() {
+    let server = server::http(|_req| async move {
+        http::Response::new(
+            r#"{
+                "data": null,
+                "errors": [{ "message": "syntax error", "locations": null, "path": null }]
+            }"#
+            .into(),
+        )
+    });
+
+    let err = reqwest::Client::new()
+        .post(format!("http://{}/graphql", server.addr()))
+        .graphql("{ version", &())
+        .send()
+        .await
+        .unwrap()
+        .graphql_data::<serde_json::Value>()
+        .await
+        .unwrap_err();
+    let errors = err.graphql_errors().unwrap();
+    assert_eq!(errors.data(), None);
+    let [error] = errors.errors() else {
+        panic!("expected one error, got {:?}", errors.errors());
+    };
+    assert_eq!(error.message(), "syntax error");
+    assert!(error.locations().is_empty());
+    assert_eq!(error.path(), None);
+}
+
+#[tokio::test]
+async fn graphql_sets_post() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.method(), "POST");
+        http::Response::new(r#"{"data":{"version":"1.0"}}"#.into())
+    });
+
+    let req = reqwest::Client::new()
+        .get(format!("http://{}/graphql", server.addr()))
+        .graphql("{ version }", &());
+    let data: serde_json::Value = req.send().await.unwrap().graphql_data().await.unwrap();
+    assert_eq!(data, serde_json::json!({ "version": "1.0" }));
+}
+
+#[tokio::test]
+async fn graphql_not_a_response() {
+    let server = server::http(|_req| async move { http::Response::new("{}".into()) });
+
+    let err = reqwest::Client::new()
+        .post(format!("http://{}/graphql", server.addr()))
+        .graphql("{ version }", &())
+        .send()
+        .await
+        .unwrap()
+        .graphql_data::<serde_json::Value>()
+        .await
+        .unwrap_err();
+    assert!(err.is_decode());
+    assert!(err.graphql_errors().is_none());
+}
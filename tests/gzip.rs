@@ -9,12 +9,44 @@ use tokio::io::AsyncWriteExt;
 
 #[tokio::test]
 async fn gzip_response() {
-    gzip_case(10_000, 4096).await;
+    gzip_case(reqwest::Client::new(), 10_000, 4096).await;
 }
 
 #[tokio::test]
 async fn gzip_single_byte_chunks() {
-    gzip_case(10, 1).await;
+    gzip_case(reqwest::Client::new(), 10, 1).await;
+}
+
+#[tokio::test]
+async fn gzip_offloaded_response() {
+    let client = reqwest::Client::builder()
+        .offload_decompression(0)
+        .build()
+        .unwrap();
+    gzip_case(client, 10_000, 4096).await;
+}
+
+#[tokio::test]
+async fn gzip_offloaded_invalid_body() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body("not gzip".into())
+            .unwrap()
+    });
+
+    let client = reqwest::Client::builder()
+        .offload_decompression(0)
+        .build()
+        .unwrap();
+    let res = client
+        .get(&format!("http://{}/gzip", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    let err = res.text().await.unwrap_err();
+    assert!(err.is_decode());
 }
 
 #[tokio::test]
@@ -89,7 +121,7 @@ async fn test_accept_encoding_header_is_not_changed_if_set() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
-async fn gzip_case(response_size: usize, chunk_size: usize) {
+async fn gzip_case(client: reqwest::Client, response_size: usize, chunk_size: usize) {
     use futures_util::stream::StreamExt;
 
     let content: String = (0..response_size)
@@ -139,8 +171,6 @@ async fn gzip_case(response_size: usize, chunk_size: usize) {
         }
     });
 
-    let client = reqwest::Client::new();
-
     let res = client
         .get(&format!("http://{}/gzip", server.addr()))
         .send()
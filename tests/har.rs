@@ -0,0 +1,65 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use reqwest::har::Har;
+
+#[tokio::test]
+async fn replay_to_origin() {
+    let server = server::http(move |req| async move {
+        let status = match (req.method().as_str(), req.uri().path()) {
+            ("POST", "/items") => {
+                assert_eq!(req.uri().query(), Some("draft=1"));
+                assert_eq!(req.headers()["content-type"], "application/json");
+                assert_eq!(req.headers()["content-length"], "9");
+                201
+            }
+            ("GET", "/items/1") => 200,
+            _ => 500,
+        };
+        http::Response::builder()
+            .status(status)
+            .body(Default::default())
+            .unwrap()
+    });
+
+    let har = Har::from_slice(
+        br#"{
+            "log": {
+                "version": "1.2",
+                "entries": [
+                    {
+                        "request": {
+                            "method": "POST",
+                            "url": "https://example.com/items?draft=1",
+                            "headers": [
+                                { "name": "Host", "value": "example.com" },
+                                { "name": "Content-Type", "value": "application/json" }
+                            ],
+                            "postData": { "mimeType": "application/json", "text": "{\"id\": 1}" }
+                        },
+                        "response": { "status": 201 }
+                    },
+                    {
+                        "request": {
+                            "method": "GET",
+                            "url": "https://example.com/items/1",
+                            "headers": []
+                        },
+                        "response": { "status": 200 }
+                    }
+                ]
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let client = reqwest::Client::builder().no_proxy().build().unwrap();
+    let origin = format!("http://{}", server.addr()).parse().unwrap();
+    let results = har.replay_to(&client, &origin).await;
+
+    assert_eq!(results.len(), 2);
+    for (entry, res) in har.entries().iter().zip(results) {
+        assert_eq!(Some(res.unwrap().status()), entry.status());
+    }
+}
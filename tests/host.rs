@@ -0,0 +1,256 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderValue};
+
+fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(name, HeaderValue::from_static(value));
+    headers
+}
+
+fn echo_server() -> server::Server {
+    server::http(|req| async move {
+        let token = req
+            .headers()
+            .get("x-token")
+            .map(|value| value.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        let body = format!("{:?} {token}", req.version());
+        http::Response::new(body.into())
+    })
+}
+
+#[tokio::test]
+async fn host_headers() {
+    let server = echo_server();
+    let client = reqwest::Client::builder()
+        .resolve("api.test", server.addr())
+        .resolve("other.test", server.addr())
+        .default_headers(headers("x-token", "client"))
+        .for_host("API.test", |host| host.headers(headers("x-token", "host")))
+        .build()
+        .unwrap();
+
+    let get = |host: &str| client.get(format!("http://{host}:{}/", server.addr().port()));
+
+    let res = get("api.test").send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "HTTP/1.1 host");
+
+    let res = get("other.test").send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "HTTP/1.1 client");
+
+    let res = get("api.test")
+        .header("x-token", "request")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "HTTP/1.1 request");
+}
+
+#[tokio::test]
+async fn host_timeout() {
+    let server = server::http(|_req| async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        http::Response::default()
+    });
+    let client = reqwest::Client::builder()
+        .resolve("slow.test", server.addr())
+        .resolve("api.test", server.addr())
+        .for_host("api.test", |host| host.timeout(Duration::from_millis(50)))
+        .build()
+        .unwrap();
+
+    let port = server.addr().port();
+    let err = client
+        .get(format!("http://api.test:{port}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+
+    let res = client
+        .get(format!("http://slow.test:{port}/"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    // the timeout of a request comes first
+    let res = client
+        .get(format!("http://api.test:{port}/"))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn host_version() {
+    let server = echo_server();
+    let client = reqwest::Client::builder()
+        .resolve("legacy.test", server.addr())
+        .for_host("legacy.test", |host| {
+            host.version(reqwest::Version::HTTP_10)
+        })
+        .build()
+        .unwrap();
+
+    let url = format!("http://legacy.test:{}/", server.addr().port());
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "HTTP/1.0 ");
+
+    let url = format!("http://{}/", server.addr());
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "HTTP/1.1 ");
+}
+
+#[test]
+fn for_host_updates_existing() {
+    let client = reqwest::Client::builder()
+        .for_host("api.test", |host| host.timeout(Duration::from_secs(1)))
+        .for_host("api.test", |host| host.read_timeout(Duration::from_secs(2)))
+        .build()
+        .unwrap();
+
+    let debug = format!("{client:?}");
+    assert!(debug.contains("timeout: 1s"), "{debug}");
+    assert!(debug.contains("read_timeout: 2s"), "{debug}");
+}
+
+// A TLS 1.2 server with the test certificate, which isn't trusted, answering
+// each connection with an empty response.
+#[cfg(feature = "__rustls-aws-lc-rs")]
+async fn tls_server() -> std::net::SocketAddr {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let cert = std::fs::read("tests/support/server.cert").unwrap().into();
+    let key = std::fs::read("tests/support/server.key")
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let provider = std::sync::Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS12])
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((tcp, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut tls) = acceptor.accept(tcp).await else {
+                    return;
+                };
+                let mut buf = [0; 1024];
+                let _ = tls.read(&mut buf).await;
+                let res = b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                let _ = tls.write_all(res).await;
+                let _ = tls.shutdown().await;
+            });
+        }
+    });
+    addr
+}
+
+#[cfg(feature = "__rustls-aws-lc-rs")]
+#[tokio::test]
+async fn host_tls() {
+    let addr = tls_server().await;
+    let client = reqwest::Client::builder()
+        .tls_backend_rustls()
+        .no_proxy()
+        .resolve("localhost", addr)
+        .resolve("hyperium.tech", addr)
+        .for_host("localhost", |host| {
+            host.tls_danger_accept_invalid_certs(true)
+        })
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("https://localhost:{}/", addr.port()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    // the other hosts keep the TLS settings of the client
+    let err = client
+        .get(format!("https://hyperium.tech:{}/", addr.port()))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_tls(), "{err:?}");
+}
+
+#[cfg(feature = "__rustls-aws-lc-rs")]
+#[tokio::test]
+async fn host_tls_version() {
+    let addr = tls_server().await;
+    let client = reqwest::Client::builder()
+        .tls_backend_rustls()
+        .tls_danger_accept_invalid_certs(true)
+        .no_proxy()
+        .resolve("localhost", addr)
+        .for_host("localhost", |host| {
+            host.tls_version_min(reqwest::tls::Version::TLS_1_3)
+        })
+        .build()
+        .unwrap();
+
+    let err = client
+        .get(format!("https://localhost:{}/", addr.port()))
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.tls_failure(),
+        Some(reqwest::tls::TlsFailure::UnsupportedProtocol),
+        "{err:?}"
+    );
+}
+
+#[cfg(feature = "__rustls-aws-lc-rs")]
+#[test]
+fn host_tls_needs_a_configured_backend() {
+    let tls = rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(
+        rustls::crypto::aws_lc_rs::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_root_certificates(rustls::RootCertStore::empty())
+    .with_no_client_auth();
+    let err = reqwest::Client::builder()
+        .use_preconfigured_tls(tls)
+        .for_host("api.test", |host| {
+            host.tls_danger_accept_invalid_certs(true)
+        })
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_host_headers() {
+    let server = echo_server();
+    let client = reqwest::blocking::Client::builder()
+        .resolve("api.test", server.addr())
+        .for_host("api.test", |host| host.headers(headers("x-token", "host")))
+        .build()
+        .unwrap();
+
+    let url = format!("http://api.test:{}/", server.addr().port());
+    let res = client.get(url).send().unwrap();
+    assert_eq!(res.text().unwrap(), "HTTP/1.1 host");
+}
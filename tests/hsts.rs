@@ -0,0 +1,100 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reqwest::hsts::Preload;
+
+// The server only speaks plain HTTP, so an upgraded request never reaches it.
+fn counting_server(location: Option<String>) -> (server::Server, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counted = hits.clone();
+    let server = server::http(move |_req| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        let location = location.clone();
+        async move {
+            let mut res = http::Response::builder();
+            if let Some(location) = location {
+                res = res.status(302).header("location", location);
+            }
+            res.body(Default::default()).unwrap()
+        }
+    });
+    (server, hits)
+}
+
+#[tokio::test]
+async fn preloaded_domain_is_upgraded() {
+    let (server, hits) = counting_server(None);
+    let client = reqwest::Client::builder()
+        .hsts_preload(Preload::new().domain("preloaded.test", true))
+        .resolve("preloaded.test", server.addr())
+        .resolve("www.preloaded.test", server.addr())
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    for host in ["preloaded.test", "www.preloaded.test"] {
+        let url = format!("http://{host}:{}/", server.addr().port());
+        let err = client.get(&url).send().await.unwrap_err();
+        assert_eq!(err.url().unwrap().scheme(), "https");
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn other_domains_are_not_upgraded() {
+    let (server, hits) = counting_server(None);
+    let client = reqwest::Client::builder()
+        .hsts_preload(Preload::new().domain("preloaded.test", false))
+        .resolve("www.preloaded.test", server.addr())
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://www.preloaded.test:{}/", server.addr().port());
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.url().scheme(), "http");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn redirect_to_preloaded_domain_is_upgraded() {
+    let (target, target_hits) = counting_server(None);
+    let (server, _) = counting_server(Some(format!(
+        "http://preloaded.test:{}/next",
+        target.addr().port()
+    )));
+    let client = reqwest::Client::builder()
+        .hsts_preload(Preload::new().domain("preloaded.test", true))
+        .resolve("preloaded.test", target.addr())
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/", server.addr());
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(!err.is_redirect());
+    assert_eq!(target_hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn https_only_accepts_preloaded_domains() {
+    let (server, hits) = counting_server(None);
+    let client = reqwest::Client::builder()
+        .hsts_preload(Preload::new().domain("preloaded.test", false))
+        .resolve("preloaded.test", server.addr())
+        .https_only(true)
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://preloaded.test:{}/", server.addr().port());
+    let err = client.get(&url).send().await.unwrap_err();
+    // refused by the server, rather than for its scheme
+    assert!(!err.is_builder());
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+}
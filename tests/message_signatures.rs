@@ -0,0 +1,98 @@
+#![cfg(not(target_arch = "wasm32"))]
+#![cfg(not(feature = "rustls-no-provider"))]
+mod support;
+use support::server;
+
+use reqwest::signature::{Component, Signer, SigningKey};
+
+/// A key that "signs" with a fixed value, to check what is sent.
+struct FixedKey;
+
+impl SigningKey for FixedKey {
+    fn sign(&self, _base: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(vec![1, 2, 3])
+    }
+}
+
+#[tokio::test]
+async fn request_is_signed() {
+    let server = server::http(move |req| async move {
+        let input = req.headers()["signature-input"].to_str().unwrap();
+        assert!(
+            input.starts_with("sig1=(\"@method\" \"@authority\" \"@path\");created="),
+            "{input}"
+        );
+        assert!(input.ends_with(";keyid=\"test-key\""), "{input}");
+        assert_eq!(req.headers()["signature"], "sig1=:AQID:");
+        http::Response::default()
+    });
+
+    let signer = Signer::new(FixedKey).key_id("test-key").components([
+        Component::method(),
+        Component::authority(),
+        Component::path(),
+    ]);
+
+    let url = format!("http://{}/sign", server.addr());
+    let res = reqwest::Client::builder()
+        .message_signer(signer)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn request_signed_with_default_components() {
+    let server = server::http(move |req| async move {
+        let input = req.headers()["signature-input"].to_str().unwrap();
+        assert!(
+            input.starts_with("sig1=(\"@method\" \"@target-uri\" \"content-type\");created="),
+            "{input}"
+        );
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/sign", server.addr());
+    let res = reqwest::Client::builder()
+        .message_signer(Signer::new(FixedKey))
+        .no_proxy()
+        .build()
+        .unwrap()
+        .post(&url)
+        .header("content-type", "text/plain")
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn missing_covered_header_is_an_error() {
+    let server = server::http(move |_req| async move {
+        panic!("request should not be sent");
+    });
+
+    let signer =
+        Signer::new(FixedKey).components([Component::header(reqwest::header::AUTHORIZATION)]);
+
+    let url = format!("http://{}/sign", server.addr());
+    let err = reqwest::Client::builder()
+        .message_signer(signer)
+        .no_proxy()
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_request());
+}
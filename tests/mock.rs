@@ -0,0 +1,78 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::time::Duration;
+
+use reqwest::test::{Matcher, Mock, Reply};
+use reqwest::Method;
+
+#[tokio::test]
+async fn routes_in_order() {
+    let mock = Mock::new();
+    mock.route(
+        Matcher::new(Method::POST, "https://example.test/items").body("{}"),
+        Reply::new(201).header("location", "/items/1"),
+    )
+    .route(
+        Matcher::any().path("/items").header("x-page", "2"),
+        Reply::new(200).body("page 2"),
+    )
+    .route(Matcher::any(), Reply::new(404));
+
+    let client = mock.client();
+
+    let res = client
+        .post("https://example.test/items")
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.headers()["location"], "/items/1");
+
+    let res = client
+        .get("https://other.test/items?page=2")
+        .header("x-page", "2")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "page 2");
+
+    let res = client.get("https://example.test/nope").send().await.unwrap();
+    assert_eq!(res.status(), 404);
+
+    let received = mock.received();
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[0].method(), Method::POST);
+    assert_eq!(received[0].body(), "{}");
+    assert_eq!(received[2].uri(), "https://example.test/nope");
+}
+
+#[tokio::test]
+async fn no_route() {
+    let client = Mock::new().client();
+    let err = client.get("https://example.test/").send().await.unwrap_err();
+    assert!(err.is_request());
+}
+
+#[tokio::test]
+async fn errors_and_delays() {
+    let mock = Mock::new();
+    mock.route(Matcher::any().path("/fail"), Reply::error("connection reset"))
+        .route(
+            Matcher::any().path("/slow"),
+            Reply::new(200).delay(Duration::from_secs(5)),
+        );
+
+    let client = reqwest::Client::builder()
+        .http_service(mock)
+        .timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+
+    let err = client.get("https://example.test/fail").send().await.unwrap_err();
+    assert!(err.is_request());
+    assert!(format!("{err:?}").contains("connection reset"), "{err:?}");
+
+    let err = client.get("https://example.test/slow").send().await.unwrap_err();
+    assert!(err.is_timeout());
+}
@@ -0,0 +1,119 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Item {
+    name: String,
+    count: u32,
+}
+
+fn item() -> Item {
+    Item {
+        name: "apple".to_owned(),
+        count: 3,
+    }
+}
+
+#[tokio::test]
+async fn msgpack_roundtrip() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["content-type"], "application/msgpack");
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let mut item: Item = rmp_serde::from_slice(&body).unwrap();
+        item.count += 1;
+        http::Response::builder()
+            .header("content-type", "application/vnd.msgpack; charset=binary")
+            .body(rmp_serde::to_vec_named(&item).unwrap().into())
+            .unwrap()
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!("http://{}/items", server.addr()))
+        .msgpack(&item())
+        .send()
+        .await
+        .unwrap();
+
+    let item: Item = res.msgpack().await.unwrap();
+    assert_eq!(item.name, "apple");
+    assert_eq!(item.count, 4);
+}
+
+#[tokio::test]
+async fn msgpack_keeps_content_type() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.headers()["content-type"], "application/x-msgpack");
+        http::Response::default()
+    });
+
+    reqwest::Client::new()
+        .post(format!("http://{}/items", server.addr()))
+        .header("content-type", "application/x-msgpack")
+        .msgpack(&item())
+        .send()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn msgpack_without_content_type() {
+    let server = server::http(|_req| async move {
+        http::Response::new(rmp_serde::to_vec_named(&item()).unwrap().into())
+    });
+
+    let res = reqwest::get(format!("http://{}/item", server.addr()))
+        .await
+        .unwrap();
+
+    assert_eq!(res.msgpack::<Item>().await.unwrap(), item());
+}
+
+#[tokio::test]
+async fn msgpack_wrong_content_type() {
+    let server = server::http(|_req| async move {
+        http::Response::builder()
+            .header("content-type", "application/json")
+            .body(r#"{"name":"apple","count":3}"#.into())
+            .unwrap()
+    });
+
+    let err = reqwest::get(format!("http://{}/item", server.addr()))
+        .await
+        .unwrap()
+        .msgpack::<Item>()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_msgpack_roundtrip() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.headers()["content-type"], "application/msgpack");
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        http::Response::builder()
+            .header("content-type", "application/msgpack")
+            .body(body.into())
+            .unwrap()
+    });
+
+    let res = reqwest::blocking::Client::new()
+        .post(format!("http://{}/items", server.addr()))
+        .msgpack(&item())
+        .send()
+        .unwrap();
+
+    assert_eq!(res.msgpack::<Item>().unwrap(), item());
+}
@@ -177,6 +177,74 @@ fn blocking_file_part() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_reader_part_is_streamed() {
+    use std::io::Read;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    const TOTAL: u64 = 32 * 1024 * 1024;
+
+    // A large reader part that counts how much of it has been read.
+    struct Zeros {
+        remaining: u64,
+        read: Arc<AtomicU64>,
+    }
+
+    impl Read for Zeros {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = std::cmp::min(buf.len() as u64, self.remaining) as usize;
+            buf[..n].fill(0);
+            self.remaining -= n as u64;
+            self.read.fetch_add(n as u64, Ordering::SeqCst);
+            Ok(n)
+        }
+    }
+
+    let read = Arc::new(AtomicU64::new(0));
+    let form = reqwest::blocking::multipart::Form::new().part(
+        "zeros",
+        reqwest::blocking::multipart::Part::reader(Zeros {
+            remaining: TOTAL,
+            read: read.clone(),
+        }),
+    );
+
+    let server = server::http(move |req| {
+        let read = read.clone();
+        async move {
+            let mut body = req.into_body();
+            let mut received = 0;
+            let mut checked = false;
+            while let Some(frame) = body.frame().await {
+                if let Ok(data) = frame.unwrap().into_data() {
+                    received += data.len() as u64;
+                }
+                if !checked && received > 1024 * 1024 {
+                    checked = true;
+                    // Give the client time to read ahead, if it were buffering.
+                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                    assert!(read.load(Ordering::SeqCst) < TOTAL / 2);
+                }
+            }
+            assert!(checked);
+            assert!(received > TOTAL);
+            http::Response::default()
+        }
+    });
+
+    let url = format!("http://{}/multipart/reader", server.addr());
+
+    let res = reqwest::blocking::Client::new()
+        .post(&url)
+        .multipart(form)
+        .send()
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[cfg(feature = "stream")]
 #[tokio::test]
 async fn async_impl_file_part() {
@@ -0,0 +1,189 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest::Client;
+
+/// A server with three pages, linking each to the next one.
+fn linked_server() -> server::Server {
+    server::http(|req| async move {
+        let page = req
+            .uri()
+            .query()
+            .and_then(|query| query.strip_prefix("page="))
+            .unwrap_or("1")
+            .parse::<u32>()
+            .unwrap();
+        let mut builder = http::Response::builder();
+        if page < 3 {
+            builder = builder.header(
+                "link",
+                format!(
+                    "</items?page=1>; rel=\"first\", </items?page={}>; rel=\"next\"",
+                    page + 1
+                ),
+            );
+        }
+        builder
+            .body(format!("{{\"page\":{page},\"next\":null}}").into())
+            .unwrap()
+    })
+}
+
+async fn bodies(pages: reqwest::paginate::Pages<reqwest::Response>) -> Vec<String> {
+    let responses: Vec<reqwest::Response> = pages.try_collect().await.unwrap();
+    let mut bodies = Vec::new();
+    for res in responses {
+        bodies.push(res.text().await.unwrap());
+    }
+    bodies
+}
+
+#[tokio::test]
+async fn follows_link_headers() {
+    let server = linked_server();
+    let client = Client::new();
+    let request = client
+        .get(format!("http://{}/items", server.addr()))
+        .build()
+        .unwrap();
+
+    let bodies = bodies(client.paginate(request)).await;
+
+    assert_eq!(
+        bodies,
+        [
+            r#"{"page":1,"next":null}"#,
+            r#"{"page":2,"next":null}"#,
+            r#"{"page":3,"next":null}"#
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stops_at_limit() {
+    let server = linked_server();
+    let client = Client::new();
+    let request = client
+        .get(format!("http://{}/items", server.addr()))
+        .build()
+        .unwrap();
+
+    let bodies = bodies(client.paginate(request).limit(2)).await;
+
+    assert_eq!(bodies.len(), 2);
+}
+
+#[tokio::test]
+async fn next_page_function() {
+    let server = linked_server();
+    let client = Client::new();
+    let request = client
+        .get(format!("http://{}/items?page=3", server.addr()))
+        .build()
+        .unwrap();
+
+    // go backwards instead, ignoring the `Link` headers
+    let mut pages = client.paginate(request).next_page(|res| {
+        let page = res
+            .url()
+            .query()?
+            .strip_prefix("page=")?
+            .parse::<u32>()
+            .ok()?;
+        let mut url = res.url().clone();
+        url.set_query(Some(&format!(
+            "page={}",
+            page.checked_sub(1).filter(|page| *page > 0)?
+        )));
+        Some(url)
+    });
+
+    let mut urls = Vec::new();
+    while let Some(res) = pages.next().await {
+        urls.push(res.unwrap().url().query().unwrap().to_owned());
+    }
+    assert_eq!(urls, ["page=3", "page=2", "page=1"]);
+}
+
+#[tokio::test]
+async fn streaming_body_cannot_follow() {
+    let server = linked_server();
+    let client = Client::new();
+    let body =
+        reqwest::Body::wrap_stream(futures_util::stream::iter(vec![Ok::<_, std::io::Error>(
+            "query",
+        )]));
+    let request = client
+        .post(format!("http://{}/items", server.addr()))
+        .body(body)
+        .build()
+        .unwrap();
+
+    let mut pages = client.paginate(request);
+
+    assert!(pages.next().await.unwrap().is_ok());
+    assert!(pages.next().await.unwrap().unwrap_err().is_builder());
+    assert!(pages.next().await.is_none());
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn json_pages() {
+    #[derive(serde::Deserialize)]
+    struct Page {
+        page: u32,
+        next: Option<String>,
+    }
+
+    let server = server::http(|req| async move {
+        let body = match req.uri().path() {
+            "/first" => r#"{"page":1,"next":"/second"}"#,
+            "/second" => r#"{"page":2,"next":null}"#,
+            _ => unreachable!(),
+        };
+        http::Response::new(body.into())
+    });
+    let client = Client::new();
+    let base = reqwest::Url::parse(&format!("http://{}/first", server.addr())).unwrap();
+    let request = client.get(base.clone()).build().unwrap();
+
+    let pages: Vec<Page> = client
+        .paginate(request)
+        .json::<Page>()
+        .next_page(move |page| page.next.as_ref().and_then(|next| base.join(next).ok()))
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        pages.iter().map(|page| page.page).collect::<Vec<_>>(),
+        [1, 2]
+    );
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn json_pages_error_status() {
+    let server = server::http(|_req| async {
+        http::Response::builder()
+            .status(500)
+            .body(Default::default())
+            .unwrap()
+    });
+    let client = Client::new();
+    let request = client
+        .get(format!("http://{}/items", server.addr()))
+        .build()
+        .unwrap();
+
+    let mut pages = client.paginate(request).json::<serde_json::Value>();
+
+    let err = pages.next().await.unwrap().unwrap_err();
+    assert_eq!(
+        err.status(),
+        Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+    );
+    assert!(pages.next().await.is_none());
+}
@@ -0,0 +1,110 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+struct Metric {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(double, tag = "2")]
+    value: f64,
+}
+
+fn metric() -> Metric {
+    Metric {
+        name: "requests".to_owned(),
+        value: 42.0,
+    }
+}
+
+#[tokio::test]
+async fn protobuf_roundtrip() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["content-type"], "application/x-protobuf");
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        let mut metric = Metric::decode(body).unwrap();
+        metric.value += 1.0;
+        http::Response::builder()
+            .header("content-type", "application/protobuf")
+            .body(metric.encode_to_vec().into())
+            .unwrap()
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!("http://{}/metrics", server.addr()))
+        .protobuf(&metric())
+        .send()
+        .await
+        .unwrap();
+
+    let metric: Metric = res.protobuf().await.unwrap();
+    assert_eq!(metric.name, "requests");
+    assert_eq!(metric.value, 43.0);
+}
+
+#[tokio::test]
+async fn protobuf_length_delimited() {
+    let server = server::http(|_req| async move {
+        http::Response::builder()
+            .header(
+                "content-type",
+                "application/vnd.google.protobuf; proto=Metric; encoding=delimited",
+            )
+            .body(metric().encode_length_delimited_to_vec().into())
+            .unwrap()
+    });
+
+    let res = reqwest::get(format!("http://{}/metrics", server.addr()))
+        .await
+        .unwrap();
+
+    assert_eq!(res.protobuf::<Metric>().await.unwrap(), metric());
+}
+
+#[tokio::test]
+async fn protobuf_wrong_content_type() {
+    let server = server::http(|_req| async move {
+        http::Response::builder()
+            .header("content-type", "application/json")
+            .body(r#"{"name":"requests","value":42}"#.into())
+            .unwrap()
+    });
+
+    let err = reqwest::get(format!("http://{}/metrics", server.addr()))
+        .await
+        .unwrap()
+        .protobuf::<Metric>()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_protobuf_roundtrip() {
+    let server = server::http(|req| async move {
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        http::Response::builder()
+            .header("content-type", "application/x-protobuf")
+            .body(body.into())
+            .unwrap()
+    });
+
+    let res = reqwest::blocking::Client::new()
+        .post(format!("http://{}/metrics", server.addr()))
+        .protobuf(&metric())
+        .send()
+        .unwrap();
+
+    assert_eq!(res.protobuf::<Metric>().unwrap(), metric());
+}
@@ -304,6 +304,50 @@ async fn tunnel_detects_auth_required() {
     );
 }
 
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn tunnel_error_is_proxy() {
+    let server = server::http(move |_req| async {
+        let mut res = http::Response::default();
+        *res.status_mut() = http::StatusCode::FORBIDDEN;
+        res
+    });
+
+    let proxy = format!("http://{}", server.addr());
+
+    let err = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::https(&proxy).unwrap())
+        .build()
+        .unwrap()
+        .get("https://hyper.rs.local/prox")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_proxy(), "{err:?}");
+    assert!(!err.is_tls());
+}
+
+#[tokio::test]
+async fn unreachable_proxy_is_proxy() {
+    // nothing listens on this port
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let err = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::http(&proxy).unwrap())
+        .build()
+        .unwrap()
+        .get("http://hyper.rs.local/prox")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_proxy(), "{err:?}");
+    assert!(err.is_connect());
+}
+
 #[cfg(feature = "__tls")]
 #[tokio::test]
 async fn tunnel_includes_proxy_auth() {
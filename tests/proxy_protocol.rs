@@ -0,0 +1,101 @@
+#![cfg(not(target_arch = "wasm32"))]
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+
+use reqwest::proxy_protocol::ProxyProtocol;
+
+/// Accept a single connection, answer its first request, and return
+/// everything read from it.
+fn serve_once() -> (SocketAddr, thread::JoinHandle<(SocketAddr, Vec<u8>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        let (mut socket, peer) = listener.accept().unwrap();
+        let mut received = Vec::new();
+        let mut buf = [0; 1024];
+        while !(received.ends_with(b"\r\n\r\n") && contains(&received, b"HTTP/1.1")) {
+            let n = socket.read(&mut buf).unwrap();
+            assert_ne!(n, 0, "connection closed early");
+            received.extend_from_slice(&buf[..n]);
+        }
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .unwrap();
+        (peer, received)
+    });
+    (addr, handle)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[tokio::test]
+async fn sends_v1_header() {
+    let (addr, server) = serve_once();
+    let client = reqwest::Client::builder()
+        .proxy_protocol(ProxyProtocol::v1())
+        .build()
+        .unwrap();
+
+    let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let (peer, received) = server.join().unwrap();
+    let preamble = format!(
+        "PROXY TCP4 127.0.0.1 127.0.0.1 {} {}\r\nGET / HTTP/1.1\r\n",
+        peer.port(),
+        addr.port()
+    );
+    assert!(received.starts_with(preamble.as_bytes()));
+}
+
+#[tokio::test]
+async fn sends_v2_header_with_source() {
+    let (addr, server) = serve_once();
+    let client = reqwest::Client::builder()
+        .proxy_protocol(ProxyProtocol::v2().source("203.0.113.7:50000".parse().unwrap()))
+        .build()
+        .unwrap();
+
+    let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let (_, received) = server.join().unwrap();
+    let mut preamble = b"\r\n\r\n\0\r\nQUIT\n\x21\x11\0\x0c".to_vec();
+    preamble.extend_from_slice(&[203, 0, 113, 7, 127, 0, 0, 1]);
+    preamble.extend_from_slice(&50000u16.to_be_bytes());
+    preamble.extend_from_slice(&addr.port().to_be_bytes());
+    preamble.extend_from_slice(b"GET / HTTP/1.1\r\n");
+    assert!(received.starts_with(&preamble));
+}
+
+#[tokio::test]
+async fn no_header_by_default() {
+    let (addr, server) = serve_once();
+
+    let res = reqwest::get(format!("http://{addr}/")).await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let (_, received) = server.join().unwrap();
+    assert!(received.starts_with(b"GET / HTTP/1.1\r\n"));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_sends_header() {
+    let (addr, server) = serve_once();
+    let client = reqwest::blocking::Client::builder()
+        .proxy_protocol(ProxyProtocol::v1())
+        .build()
+        .unwrap();
+
+    let res = client.get(format!("http://{addr}/")).send().unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let (_, received) = server.join().unwrap();
+    assert!(received.starts_with(b"PROXY TCP4 127.0.0.1 127.0.0.1 "));
+}
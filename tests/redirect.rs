@@ -230,6 +230,45 @@ async fn test_redirect_policy_can_return_errors() {
     assert!(err.is_redirect());
 }
 
+#[tokio::test]
+async fn test_redirect_limit_error() {
+    let server = server::http(move |req| async move {
+        let i: u32 = req.uri().path()[1..].parse().unwrap();
+        http::Response::builder()
+            .status(302)
+            .header("location", format!("/{}", i + 1))
+            .body(Body::default())
+            .unwrap()
+    });
+
+    let url = format!("http://{}/0", server.addr());
+    let err = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(3))
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_redirect());
+    assert!(err.is_too_many_redirects());
+    assert_eq!(err.code(), reqwest::ErrorCode::TooManyRedirects);
+
+    // other errors of a policy are not about the limit
+    let err = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::custom(|attempt| {
+            attempt.error("not allowed")
+        }))
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_redirect());
+    assert!(!err.is_too_many_redirects());
+}
+
 #[tokio::test]
 async fn test_redirect_policy_can_stop_redirects_without_an_error() {
     let server = server::http(move |req| async move {
@@ -5,6 +5,7 @@ use support::server;
 
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[tokio::test]
 async fn retries_apply_in_scope() {
@@ -47,6 +48,110 @@ async fn retries_apply_in_scope() {
     assert_eq!(resp.status(), 200);
 }
 
+#[tokio::test]
+async fn throttled_retries_wait() {
+    let _ = env_logger::try_init();
+    let cnt = Arc::new(AtomicUsize::new(0));
+    let counted = cnt.clone();
+    let server = server::http(move |_req| {
+        let cnt = counted.clone();
+        async move {
+            if cnt.fetch_add(1, Ordering::Relaxed) == 0 {
+                http::Response::builder()
+                    .status(http::StatusCode::TOO_MANY_REQUESTS)
+                    .header("retry-after", "1")
+                    .body(Default::default())
+                    .unwrap()
+            } else {
+                http::Response::default()
+            }
+        }
+    });
+
+    let scope = server.addr().ip().to_string();
+    let retries = reqwest::retry::for_host(scope).classify_fn(|req_rep| {
+        if req_rep.status() == Some(http::StatusCode::TOO_MANY_REQUESTS) {
+            let delay = req_rep.retry_after().unwrap();
+            req_rep.throttled(delay)
+        } else {
+            req_rep.success()
+        }
+    });
+
+    let url = format!("http://{}", server.addr());
+    let start = Instant::now();
+    let resp = reqwest::Client::builder()
+        .retry(retries)
+        .build()
+        .unwrap()
+        .get(url)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(cnt.load(Ordering::Relaxed), 2);
+    assert!(start.elapsed() >= Duration::from_secs(1));
+}
+
+struct HeaderClassifier;
+
+impl reqwest::retry::Classify for HeaderClassifier {
+    fn classify(&self, req_rep: reqwest::retry::ReqRep<'_>) -> reqwest::retry::Action {
+        let retry = req_rep.headers().and_then(|h| h.get("x-retry"));
+        match retry.map(|v| v.as_bytes()) {
+            Some(b"yes") => req_rep.retryable(),
+            Some(_) => req_rep.fatal(),
+            None => req_rep.success(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn custom_classifier_reads_headers() {
+    let _ = env_logger::try_init();
+    let cnt = Arc::new(AtomicUsize::new(0));
+    let counted = cnt.clone();
+    let server = server::http(move |req| {
+        let cnt = counted.clone();
+        async move {
+            let n = cnt.fetch_add(1, Ordering::Relaxed);
+            let retry = match (req.uri().path(), n) {
+                ("/fatal", _) => "no",
+                (_, 0) => "yes",
+                _ => return http::Response::default(),
+            };
+            http::Response::builder()
+                .status(418)
+                .header("x-retry", retry)
+                .body(Default::default())
+                .unwrap()
+        }
+    });
+
+    let scope = server.addr().ip().to_string();
+    let client = reqwest::Client::builder()
+        .retry(reqwest::retry::for_host(scope).classify(HeaderClassifier))
+        .build()
+        .unwrap();
+
+    let resp = client
+        .get(format!("http://{}/retry", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(cnt.swap(0, Ordering::Relaxed), 2);
+
+    let resp = client
+        .get(format!("http://{}/fatal", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 418);
+    assert_eq!(cnt.load(Ordering::Relaxed), 1);
+}
+
 #[cfg(feature = "http2")]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn default_retries_have_a_limit() {
@@ -142,3 +247,86 @@ async fn highly_concurrent_requests_to_slow_http2_server_with_low_max_concurrent
 
     server.shutdown().await;
 }
+
+#[tokio::test]
+async fn connect_retries_refused_connections() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let _ = env_logger::try_init();
+    // a port on which nothing listens, until the server starts below
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let url = format!("http://{addr}/");
+
+    let err = reqwest::Client::new().get(&url).send().await.unwrap_err();
+    assert!(err.is_connect());
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1024];
+        let _ = socket.read(&mut buf).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .await
+            .unwrap();
+    });
+
+    let client = reqwest::Client::builder()
+        .connect_retry(
+            reqwest::retry::Connect::new()
+                .attempts(20)
+                .backoff(Duration::from_millis(50), Duration::from_millis(100)),
+        )
+        .build()
+        .unwrap();
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn connect_retries_are_limited() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let client = reqwest::Client::builder()
+        .connect_retry(
+            reqwest::retry::Connect::new()
+                .attempts(3)
+                .backoff(Duration::from_millis(100), Duration::from_secs(1)),
+        )
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    let err = client
+        .get(format!("http://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+    // backoffs of 100 then 200 milliseconds
+    assert!(start.elapsed() >= Duration::from_millis(300));
+
+    let client = reqwest::Client::builder()
+        .connect_retry(
+            reqwest::retry::Connect::new()
+                .attempts(3)
+                .backoff(Duration::from_secs(1), Duration::from_secs(1))
+                .retry_refused(false),
+        )
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    let err = client
+        .get(format!("http://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_connect());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
@@ -3,7 +3,9 @@
 mod support;
 use support::server;
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use reqwest::TimeoutPhase;
 
 #[tokio::test]
 async fn client_timeout() {
@@ -30,6 +32,7 @@ async fn client_timeout() {
     let err = res.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Total));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
@@ -88,6 +91,66 @@ async fn connect_timeout() {
     assert!(err.is_connect() && err.is_timeout());
 }
 
+struct PendingResolver;
+
+impl reqwest::dns::Resolve for PendingResolver {
+    fn resolve(&self, _name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(std::future::pending())
+    }
+}
+
+#[tokio::test]
+async fn connect_timeout_during_dns() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::Client::builder()
+        .dns_resolver(std::sync::Arc::new(PendingResolver))
+        .connect_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let err = client
+        .get("http://never.resolves.test/")
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_connect() && err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Dns));
+}
+
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn connect_timeout_during_tls() {
+    let _ = env_logger::try_init();
+
+    // accepts connections, but never answers the handshake
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut conns = Vec::new();
+        while let Ok((conn, _)) = listener.accept().await {
+            conns.push(conn);
+        }
+    });
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let err = client
+        .get(format!("https://{addr}/"))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_connect() && err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Tls));
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::test]
 async fn connect_many_timeout_succeeds() {
@@ -177,6 +240,7 @@ async fn response_timeout() {
     let err = body.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Total));
 }
 
 #[tokio::test]
@@ -204,6 +268,7 @@ async fn read_timeout_applies_to_headers() {
     let err = res.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::ResponseHeaders));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
@@ -237,6 +302,7 @@ async fn read_timeout_applies_to_body() {
     let err = body.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::BodyRead));
 }
 
 #[cfg(feature = "stream")]
@@ -279,6 +345,153 @@ async fn read_timeout_allows_slow_response_body() {
     assert_eq!(body, "012");
 }
 
+#[tokio::test]
+async fn request_read_timeout() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // delay returning the response
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            http::Response::default()
+        }
+    });
+
+    let client = reqwest::Client::builder()
+        // this should be overridden
+        .read_timeout(Duration::from_secs(5))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+
+    let err = client
+        .get(&url)
+        .read_timeout(Duration::from_millis(100))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+}
+
+#[tokio::test]
+async fn deadline_scope() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // delay returning the response
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            http::Response::default()
+        }
+    });
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+    let deadline = Instant::now() + Duration::from_millis(100);
+
+    // an inner scope doesn't extend the deadline
+    let res = reqwest::deadline::scope(deadline, async {
+        assert_eq!(reqwest::deadline::current(), Some(deadline));
+        let later = Instant::now() + Duration::from_secs(10);
+        reqwest::deadline::scope(later, client.get(&url).send()).await
+    })
+    .await;
+
+    let err = res.unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+    assert_eq!(reqwest::deadline::current(), None);
+}
+
+#[tokio::test]
+async fn request_deadline() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // delay returning the response
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            http::Response::default()
+        }
+    });
+
+    let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+
+    let res = client
+        .get(&url)
+        .timeout(Duration::from_secs(10))
+        .deadline(Instant::now() + Duration::from_millis(100))
+        .send()
+        .await;
+
+    let err = res.unwrap_err();
+
+    assert!(err.is_timeout());
+
+    // a later deadline leaves the timeout
+    let res = client
+        .get(&url)
+        .timeout(Duration::from_millis(100))
+        .deadline(Instant::now() + Duration::from_secs(10))
+        .send()
+        .await;
+
+    assert!(res.unwrap_err().is_timeout());
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn request_header_timeout() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/slow" {
+            // delay returning the response
+            tokio::time::sleep(Duration::from_millis(300)).await;
+        }
+        // a slow body is not limited by the header timeout
+        let body = reqwest::Body::wrap_stream(futures_util::stream::once(async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok::<_, std::convert::Infallible>("Hello")
+        }));
+        http::Response::new(body)
+    });
+
+    let client = reqwest::Client::new();
+
+    let url = format!("http://{}/slow", server.addr());
+    let err = client
+        .get(&url)
+        .header_timeout(Duration::from_millis(100))
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::ResponseHeaders));
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+
+    let res = client
+        .get(format!("http://{}/fast", server.addr()))
+        .header_timeout(Duration::from_millis(100))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "Hello");
+}
+
 /// Tests that a big [`Duration`] does not overflow the system clock
 /// and instead behaves as if no timeout was set (the request completes normally).
 #[cfg(feature = "blocking")]
@@ -353,6 +566,7 @@ fn timeout_blocking_request() {
         .unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Total));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
@@ -465,3 +679,60 @@ async fn response_body_timeout_forwards_size_hint() {
 
     assert_eq!(res.content_length(), Some(5));
 }
+
+#[cfg(feature = "blocking")]
+#[cfg(feature = "stream")]
+#[test]
+fn blocking_request_read_timeout_body() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::blocking::Client::new();
+
+    let server = server::http(move |_req| {
+        async {
+            // immediate response, but delayed body
+            let body = reqwest::Body::wrap_stream(futures_util::stream::once(async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                Ok::<_, std::convert::Infallible>("Hello")
+            }));
+
+            http::Response::new(body)
+        }
+    });
+
+    let url = format!("http://{}/slow", server.addr());
+    let res = client
+        .get(&url)
+        .read_timeout(Duration::from_millis(100))
+        .send()
+        .expect("get response");
+
+    let err = res.text().unwrap_err();
+    assert!(err.is_timeout());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_request_header_timeout() {
+    let _ = env_logger::try_init();
+
+    let client = reqwest::blocking::Client::new();
+
+    let server = server::http(move |_req| {
+        async {
+            // delay returning the response
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            http::Response::default()
+        }
+    });
+
+    let url = format!("http://{}/slow", server.addr());
+    let err = client
+        .get(&url)
+        .header_timeout(Duration::from_millis(100))
+        .send()
+        .unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::ResponseHeaders));
+}
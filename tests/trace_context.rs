@@ -0,0 +1,272 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::collections::HashMap;
+use std::sync::{Mutex, Once};
+
+use reqwest::trace::TraceContext;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+/// A subscriber that keeps the recorded status of every span.
+#[derive(Default)]
+struct Spans {
+    next_id: Mutex<u64>,
+    spans: Mutex<HashMap<u64, (&'static Metadata<'static>, Option<u64>)>>,
+}
+
+thread_local! {
+    static ENTERED: std::cell::RefCell<Vec<Id>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+struct Status(Option<u64>);
+
+impl Visit for Status {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "http.response.status_code" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl Subscriber for Spans {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut next_id = self.next_id.lock().unwrap();
+        *next_id += 1;
+        self.spans
+            .lock()
+            .unwrap()
+            .insert(*next_id, (span.metadata(), None));
+        Id::from_u64(*next_id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut status = Status(None);
+        values.record(&mut status);
+        if let Some(entry) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            entry.1 = entry.1.or(status.0);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, _span: &Id) {
+        ENTERED.with(|entered| entered.borrow_mut().pop());
+    }
+
+    fn current_span(&self) -> Current {
+        match ENTERED.with(|entered| entered.borrow().last().cloned()) {
+            Some(id) => {
+                let metadata = self.spans.lock().unwrap()[&id.into_u64()].0;
+                Current::new(id, metadata)
+            }
+            None => Current::none(),
+        }
+    }
+}
+
+fn spans() -> &'static Spans {
+    static SPANS: std::sync::OnceLock<&'static Spans> = std::sync::OnceLock::new();
+    static INIT: Once = Once::new();
+    let spans = *SPANS.get_or_init(|| Box::leak(Box::default()));
+    INIT.call_once(|| {
+        struct Shared(&'static Spans);
+        impl Subscriber for Shared {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                self.0.enabled(metadata)
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                self.0.new_span(span)
+            }
+            fn record(&self, span: &Id, values: &Record<'_>) {
+                self.0.record(span, values)
+            }
+            fn record_follows_from(&self, span: &Id, follows: &Id) {
+                self.0.record_follows_from(span, follows)
+            }
+            fn event(&self, event: &Event<'_>) {
+                self.0.event(event)
+            }
+            fn enter(&self, span: &Id) {
+                self.0.enter(span)
+            }
+            fn exit(&self, span: &Id) {
+                self.0.exit(span)
+            }
+            fn current_span(&self) -> Current {
+                self.0.current_span()
+            }
+        }
+        tracing::subscriber::set_global_default(Shared(spans)).unwrap();
+    });
+    spans
+}
+
+fn status_of(span: &tracing::Span) -> Option<u64> {
+    let id = span.id().unwrap().into_u64();
+    spans().spans.lock().unwrap()[&id].1
+}
+
+/// The context of the current span, using the span ID in the trace.
+fn current_context() -> Option<TraceContext> {
+    let id = tracing::Span::current().id()?;
+    Some(TraceContext::new(1, id.into_u64(), true))
+}
+
+fn echo_traceparent() -> server::Server {
+    server::http(move |req| async move {
+        let parent = req
+            .headers()
+            .get("traceparent")
+            .map(|value| value.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        let state = req
+            .headers()
+            .get("tracestate")
+            .map(|value| value.to_str().unwrap().to_owned())
+            .unwrap_or_default();
+        http::Response::builder()
+            .status(201)
+            .body(format!("{parent};{state}").into())
+            .unwrap()
+    })
+}
+
+#[tokio::test]
+async fn injects_trace_context_and_records_status() {
+    use tracing::Instrument;
+
+    spans();
+    let server = echo_traceparent();
+    let client = reqwest::Client::builder()
+        .propagate_trace_context(current_context)
+        .build()
+        .unwrap();
+
+    let span = tracing::info_span!("fetch", http.response.status_code = tracing::field::Empty);
+    let span_id = span.id().unwrap().into_u64();
+    let url = format!("http://{}/", server.addr());
+    let res = async { client.get(url).send().await }
+        .instrument(span.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), 201);
+    assert_eq!(
+        res.text().await.unwrap(),
+        format!("00-00000000000000000000000000000001-{span_id:016x}-01;")
+    );
+    assert_eq!(status_of(&span), Some(201));
+}
+
+#[tokio::test]
+async fn sends_trace_state() {
+    spans();
+    let server = echo_traceparent();
+    let client = reqwest::Client::builder()
+        .propagate_trace_context(|| {
+            Some(
+                TraceContext::new(3, 4, false)
+                    .with_state("vendor=value")
+                    .unwrap(),
+            )
+        })
+        .build()
+        .unwrap();
+
+    let body = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(
+        body,
+        "00-00000000000000000000000000000003-0000000000000004-00;vendor=value"
+    );
+}
+
+#[tokio::test]
+async fn keeps_existing_traceparent() {
+    spans();
+    let server = echo_traceparent();
+    let client = reqwest::Client::builder()
+        .propagate_trace_context(|| Some(TraceContext::new(3, 4, true)))
+        .build()
+        .unwrap();
+
+    let body = client
+        .get(format!("http://{}/", server.addr()))
+        .header("traceparent", "mine")
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "mine;");
+}
+
+#[tokio::test]
+async fn no_trace_context() {
+    spans();
+    let server = echo_traceparent();
+    let client = reqwest::Client::builder()
+        .propagate_trace_context(|| None)
+        .build()
+        .unwrap();
+
+    let body = client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, ";");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_uses_caller_span() {
+    spans();
+    let server = echo_traceparent();
+    let client = reqwest::blocking::Client::builder()
+        .propagate_trace_context(current_context)
+        .build()
+        .unwrap();
+
+    let span = tracing::info_span!("fetch", http.response.status_code = tracing::field::Empty);
+    let span_id = span.id().unwrap().into_u64();
+    let res = span.in_scope(|| {
+        client
+            .get(format!("http://{}/", server.addr()))
+            .send()
+            .unwrap()
+    });
+
+    assert_eq!(res.status(), 201);
+    assert_eq!(
+        res.text().unwrap(),
+        format!("00-00000000000000000000000000000001-{span_id:016x}-01;")
+    );
+    assert_eq!(status_of(&span), Some(201));
+}
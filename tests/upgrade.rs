@@ -50,3 +50,58 @@ async fn http_upgrade() {
     upgraded.read_to_end(&mut buf).await.unwrap();
     assert_eq!(buf, b"bar=foo");
 }
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_http_upgrade() {
+    use std::io::{Read, Write};
+
+    let server = server::http(move |req| {
+        assert_eq!(req.headers()["upgrade"], "foobar");
+
+        tokio::spawn(async move {
+            let mut upgraded = hyper_util::rt::TokioIo::new(hyper::upgrade::on(req).await.unwrap());
+
+            let mut buf = vec![0; 7];
+            upgraded.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, b"foo=bar");
+
+            upgraded.write_all(b"bar=foo").await.unwrap();
+        });
+
+        async {
+            http::Response::builder()
+                .status(http::StatusCode::SWITCHING_PROTOCOLS)
+                .header(http::header::CONNECTION, "upgrade")
+                .header(http::header::UPGRADE, "foobar")
+                .body(reqwest::Body::default())
+                .unwrap()
+        }
+    });
+
+    let res = reqwest::blocking::Client::new()
+        .get(format!("http://{}", server.addr()))
+        .header(http::header::CONNECTION, "upgrade")
+        .header(http::header::UPGRADE, "foobar")
+        .send()
+        .unwrap();
+
+    assert_eq!(res.status(), http::StatusCode::SWITCHING_PROTOCOLS);
+    let mut upgraded = res.upgrade().unwrap();
+
+    upgraded.write_all(b"foo=bar").unwrap();
+
+    let mut buf = vec![];
+    upgraded.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"bar=foo");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_upgrade_without_switching_protocols() {
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let res = reqwest::blocking::get(format!("http://{}", server.addr())).unwrap();
+    let err = res.upgrade().unwrap_err();
+    assert!(err.is_upgrade());
+}
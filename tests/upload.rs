@@ -0,0 +1,282 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use std::sync::{Arc, Mutex};
+
+use http_body_util::BodyExt;
+use reqwest::upload::Protocol;
+use reqwest::Client;
+
+const CONTENT: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// What a test server has received.
+#[derive(Default)]
+struct Received {
+    data: Vec<Option<u8>>,
+    requests: Vec<String>,
+    failed: bool,
+}
+
+impl Received {
+    fn with_prefix(len: usize) -> Received {
+        let mut data = vec![None; CONTENT.len()];
+        for (i, byte) in CONTENT[..len].iter().enumerate() {
+            data[i] = Some(*byte);
+        }
+        Received {
+            data,
+            ..Received::default()
+        }
+    }
+
+    /// The length of the data received from the start, without holes.
+    fn prefix(&self) -> usize {
+        self.data.iter().take_while(|byte| byte.is_some()).count()
+    }
+
+    fn complete(&self) -> Vec<u8> {
+        self.data.iter().map(|byte| byte.unwrap()).collect()
+    }
+}
+
+/// A server accepting chunks with `Content-Range`, that fails the first
+/// chunk starting at 10.
+fn content_range_server(received: Received) -> (server::Server, Arc<Mutex<Received>>) {
+    let shared = Arc::new(Mutex::new(received));
+    let state = shared.clone();
+    let server = server::http(move |req| {
+        let state = state.clone();
+        async move {
+            assert_eq!(req.method(), "PUT");
+            let range = req.headers()["content-range"].to_str().unwrap().to_owned();
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            let mut state = state.lock().unwrap();
+            state.requests.push(range.clone());
+            state.data.resize(CONTENT.len(), None);
+
+            let range = range.strip_prefix("bytes ").unwrap();
+            let (range, total) = range.split_once('/').unwrap();
+            assert_eq!(total, CONTENT.len().to_string());
+            if range != "*" {
+                let (start, end) = range.split_once('-').unwrap();
+                let (start, end) = (
+                    start.parse::<usize>().unwrap(),
+                    end.parse::<usize>().unwrap(),
+                );
+                assert_eq!(body.len(), end - start + 1);
+                if start == 10 && !state.failed {
+                    state.failed = true;
+                    return http::Response::builder()
+                        .status(503)
+                        .body(Default::default())
+                        .unwrap();
+                }
+                for (i, byte) in body.iter().enumerate() {
+                    state.data[start + i] = Some(*byte);
+                }
+            }
+
+            let prefix = state.prefix();
+            if prefix == CONTENT.len() {
+                return http::Response::builder()
+                    .status(201)
+                    .body(Default::default())
+                    .unwrap();
+            }
+            let mut builder = http::Response::builder().status(308);
+            if prefix > 0 {
+                builder = builder.header("range", format!("bytes=0-{}", prefix - 1));
+            }
+            builder.body(Default::default()).unwrap()
+        }
+    });
+    (server, shared)
+}
+
+#[tokio::test]
+async fn content_range_upload_from_file() {
+    let (server, received) = content_range_server(Received::default());
+    let path = std::env::temp_dir().join(format!("reqwest-upload-{}", std::process::id()));
+    std::fs::write(&path, CONTENT).unwrap();
+
+    let upload = Client::new()
+        .upload(format!("http://{}/upload", server.addr()))
+        .file(&path)
+        .chunk_size(10)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(upload.len(), CONTENT.len() as u64);
+    assert_eq!(upload.resumed_from(), 0);
+    let received = received.lock().unwrap();
+    assert_eq!(received.complete(), CONTENT);
+    assert_eq!(
+        received.requests,
+        [
+            "bytes 0-9/36",
+            "bytes 10-19/36",
+            "bytes 10-19/36",
+            "bytes 20-29/36",
+            "bytes 30-35/36"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn content_range_upload_concurrently() {
+    let (server, received) = content_range_server(Received::default());
+
+    Client::new()
+        .upload(format!("http://{}/upload", server.addr()))
+        .bytes(CONTENT)
+        .chunk_size(5)
+        .concurrency(3)
+        .send()
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.complete(), CONTENT);
+    assert_eq!(received.requests.len(), 9);
+}
+
+#[tokio::test]
+async fn content_range_resume() {
+    let (server, received) = content_range_server(Received::with_prefix(20));
+
+    let upload = Client::new()
+        .upload(format!("http://{}/upload", server.addr()))
+        .bytes(CONTENT)
+        .chunk_size(10)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(upload.resumed_from(), 20);
+    let received = received.lock().unwrap();
+    assert_eq!(received.complete(), CONTENT);
+    assert_eq!(
+        received.requests,
+        ["bytes */36", "bytes 20-29/36", "bytes 30-35/36"]
+    );
+}
+
+/// A tus server, that keeps only half of the first chunk at offset 10 and
+/// fails.
+fn tus_server(received: Received) -> (server::Server, Arc<Mutex<Received>>) {
+    let shared = Arc::new(Mutex::new(received));
+    let state = shared.clone();
+    let server = server::http(move |req| {
+        let state = state.clone();
+        async move {
+            assert_eq!(req.headers()["tus-resumable"], "1.0.0");
+            let method = req.method().clone();
+            let path = req.uri().path().to_owned();
+            let offset = req
+                .headers()
+                .get("upload-offset")
+                .map(|offset| offset.to_str().unwrap().parse::<usize>().unwrap());
+            let length = req.headers().get("upload-length").cloned();
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            let mut state = state.lock().unwrap();
+            state.requests.push(format!("{method} {path}"));
+
+            let builder = http::Response::builder().header("tus-resumable", "1.0.0");
+            match method.as_str() {
+                "POST" => {
+                    assert_eq!(path, "/files/");
+                    assert_eq!(length.unwrap(), CONTENT.len().to_string().as_str());
+                    state.data = vec![None; CONTENT.len()];
+                    builder
+                        .status(201)
+                        .header("location", "/files/1")
+                        .body(Default::default())
+                        .unwrap()
+                }
+                "HEAD" => builder
+                    .header("upload-offset", state.prefix())
+                    .body(Default::default())
+                    .unwrap(),
+                "PATCH" => {
+                    let offset = offset.unwrap();
+                    assert_eq!(offset, state.prefix());
+                    let keep = if offset == 10 && !state.failed {
+                        state.failed = true;
+                        body.len() / 2
+                    } else {
+                        body.len()
+                    };
+                    for (i, byte) in body[..keep].iter().enumerate() {
+                        state.data[offset + i] = Some(*byte);
+                    }
+                    if keep < body.len() {
+                        return builder.status(503).body(Default::default()).unwrap();
+                    }
+                    builder
+                        .status(204)
+                        .header("upload-offset", offset + keep)
+                        .body(Default::default())
+                        .unwrap()
+                }
+                _ => unreachable!("unexpected {method}"),
+            }
+        }
+    });
+    (server, shared)
+}
+
+#[tokio::test]
+async fn tus_upload() {
+    let (server, received) = tus_server(Received::default());
+
+    let upload = Client::new()
+        .upload(format!("http://{}/files/", server.addr()))
+        .bytes(CONTENT)
+        .protocol(Protocol::Tus)
+        .chunk_size(10)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(upload.url().path(), "/files/1");
+    let received = received.lock().unwrap();
+    assert_eq!(received.complete(), CONTENT);
+    assert_eq!(
+        received.requests,
+        [
+            "POST /files/",
+            "PATCH /files/1",
+            "PATCH /files/1",
+            "HEAD /files/1",
+            "PATCH /files/1",
+            "PATCH /files/1",
+            "PATCH /files/1",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn tus_resume() {
+    let (server, received) = tus_server(Received {
+        failed: true,
+        ..Received::with_prefix(12)
+    });
+
+    let upload = Client::new()
+        .upload(format!("http://{}/files/1", server.addr()))
+        .bytes(CONTENT)
+        .protocol(Protocol::Tus)
+        .chunk_size(100)
+        .resume(true)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(upload.resumed_from(), 12);
+    let received = received.lock().unwrap();
+    assert_eq!(received.complete(), CONTENT);
+    assert_eq!(received.requests, ["HEAD /files/1", "PATCH /files/1"]);
+}
@@ -0,0 +1,109 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use reqwest::vcr::Cassette;
+use reqwest::Client;
+
+fn cassette_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("reqwest-vcr-{}-{name}.json", std::process::id()))
+}
+
+#[tokio::test]
+async fn record_then_replay() {
+    let path = cassette_path("record_then_replay");
+    let _ = std::fs::remove_file(&path);
+
+    let server = server::http(move |req| async move {
+        http::Response::builder()
+            .header("x-served", "yes")
+            .body(format!("hello {}", req.uri().path()).into())
+            .unwrap()
+    });
+    let url = format!("http://{}/one", server.addr());
+
+    let recorder = Client::builder()
+        .no_proxy()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let cassette = Cassette::new(&path).unwrap().with_client(recorder);
+    assert!(cassette.is_recording());
+
+    let client = Client::builder().http_service(cassette).build().unwrap();
+    let res = client
+        .get(&url)
+        .bearer_auth("secret-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello /one");
+    drop(server);
+
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    assert!(!recorded.contains("secret-token"), "{recorded}");
+
+    let cassette = Cassette::new(&path).unwrap();
+    assert!(!cassette.is_recording());
+    let client = Client::builder().http_service(cassette).build().unwrap();
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.headers()["x-served"], "yes");
+    assert_eq!(res.text().await.unwrap(), "hello /one");
+
+    // each recorded response is only replayed once
+    let err = client.get(&url).send().await.unwrap_err();
+    assert!(err.is_request());
+    assert!(
+        std::error::Error::source(&err)
+            .unwrap()
+            .to_string()
+            .contains("no recorded response for GET"),
+        "{err:?}"
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn replay_matches_body() {
+    let path = cassette_path("replay_matches_body");
+    std::fs::write(
+        &path,
+        r#"{
+            "interactions": [
+                {
+                    "request": { "method": "POST", "url": "http://example.test/", "body": { "text": "a" } },
+                    "response": { "status": 200, "body": { "text": "first" } }
+                },
+                {
+                    "request": { "method": "POST", "url": "http://example.test/", "body": { "text": "b" } },
+                    "response": { "status": 201, "body": { "base64": "c2Vjb25k" } }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let cassette = Cassette::replay(&path).unwrap().match_body(true);
+    let client = Client::builder().http_service(cassette).build().unwrap();
+
+    let res = client
+        .post("http://example.test/")
+        .body("b")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.text().await.unwrap(), "second");
+
+    let res = client
+        .post("http://example.test/")
+        .body("a")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "first");
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -75,3 +75,74 @@ fn add_default_json_content_type_if_not_set_manually() {
 
     assert_eq!("application/json", req.headers().get(CONTENT_TYPE).unwrap());
 }
+
+#[wasm_bindgen_test]
+async fn request_with_aborted_signal() {
+    let ctrl = web_sys::AbortController::new().expect("abort controller");
+    ctrl.abort();
+
+    let err = reqwest::Client::new()
+        .get("https://hyper.rs")
+        .fetch_signal(&ctrl.signal())
+        .send()
+        .await
+        .expect_err("Expected error from aborted request");
+
+    assert!(err.is_request());
+    assert!(!err.is_timeout());
+}
+
+#[wasm_bindgen_test]
+#[cfg(feature = "stream")]
+fn streaming_body_is_not_buffered() {
+    let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("hello"), Ok(" "), Ok("world")];
+    let body = reqwest::Body::wrap_stream(futures_util::stream::iter(chunks));
+    assert!(body.as_bytes().is_none());
+
+    let req = reqwest::Client::new()
+        .post("https://hyper.rs")
+        .body(body)
+        .build()
+        .expect("request is valid");
+    assert!(req.try_clone().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn same_origin_mode_rejects_cross_origin() {
+    let err = reqwest::Client::new()
+        .get("https://hyper.rs")
+        .fetch_mode_same_origin()
+        .send()
+        .await
+        .expect_err("Expected error from cross-origin request");
+
+    assert!(err.is_request());
+}
+
+#[wasm_bindgen_test]
+async fn manual_redirect_mode() {
+    let err = reqwest::Client::new()
+        .get("https://httpbin.org/redirect-to?url=https%3A%2F%2Fhyper.rs")
+        .fetch_redirect_manual()
+        .send()
+        .await
+        .expect_err("Expected error from opaque redirect");
+
+    assert!(err.is_redirect());
+}
+
+#[wasm_bindgen_test]
+async fn client_with_timeout() {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(1))
+        .build()
+        .expect("client");
+    let err = client
+        .get("https://hyper.rs/not-cached")
+        .send()
+        .await
+        .expect_err("Expected error from aborted request");
+
+    assert!(err.is_request());
+    assert!(err.is_timeout());
+}
@@ -0,0 +1,114 @@
+#![cfg(not(target_arch = "wasm32"))]
+mod support;
+use support::server;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Order {
+    id: u32,
+    item: String,
+}
+
+fn order() -> Order {
+    Order {
+        id: 7,
+        item: "lamp".to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn xml_roundtrip() {
+    let server = server::http(|req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["content-type"], "application/xml");
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(body, "<Order><id>7</id><item>lamp</item></Order>");
+        http::Response::builder()
+            .header("content-type", "text/xml; charset=utf-8")
+            .body(r#"<?xml version="1.0"?><Order><id>8</id><item>desk</item></Order>"#.into())
+            .unwrap()
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!("http://{}/orders", server.addr()))
+        .xml(&order())
+        .send()
+        .await
+        .unwrap();
+
+    let order: Order = res.xml().await.unwrap();
+    assert_eq!(
+        order,
+        Order {
+            id: 8,
+            item: "desk".to_owned(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn xml_keeps_content_type() {
+    let server = server::http(|req| async move {
+        assert_eq!(
+            req.headers()["content-type"],
+            "application/soap+xml; charset=utf-8"
+        );
+        http::Response::default()
+    });
+
+    reqwest::Client::new()
+        .post(format!("http://{}/orders", server.addr()))
+        .header("content-type", "application/soap+xml; charset=utf-8")
+        .xml(&order())
+        .send()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn xml_wrong_content_type() {
+    let server = server::http(|_req| async move {
+        http::Response::builder()
+            .header("content-type", "application/json")
+            .body(r#"{"id":7,"item":"lamp"}"#.into())
+            .unwrap()
+    });
+
+    let err = reqwest::get(format!("http://{}/order", server.addr()))
+        .await
+        .unwrap()
+        .xml::<Order>()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+    assert!(err.is_content_type());
+    assert_eq!(err.content_type().unwrap(), "application/json");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_xml_roundtrip() {
+    let server = server::http(|req| async move {
+        let body = http_body_util::BodyExt::collect(req.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        http::Response::builder()
+            .header("content-type", "application/xml")
+            .body(body.into())
+            .unwrap()
+    });
+
+    let res = reqwest::blocking::Client::new()
+        .post(format!("http://{}/orders", server.addr()))
+        .xml(&order())
+        .send()
+        .unwrap();
+
+    assert_eq!(res.xml::<Order>().unwrap(), order());
+}